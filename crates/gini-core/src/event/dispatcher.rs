@@ -8,19 +8,39 @@ use std::fmt; // Import fmt
 
 use async_trait::async_trait;
 use crate::event::{Event, AsyncEventHandler, EventId, EventResult};
+use crate::plugin_system::traits::PluginPriority;
 // kernel::error::Result is no longer used here as methods are infallible or panic
 
 // This type represents an owned future that returns EventResult
 pub type BoxFuture<'a> = Pin<Box<dyn Future<Output = EventResult> + Send + 'a>>;
 
+/// A handler a plugin registers for events with a specific name
+pub type PluginHandlerFn = Box<dyn Fn(&dyn Event) -> BoxFuture<'_> + Send + Sync>;
+
 //--------------------------------------------------
 // EventDispatcher (Internal, wrapped by SharedEventDispatcher)
 //--------------------------------------------------
 
+/// A registered handler and the plugin that registered it, if any
+struct RegisteredHandler {
+    id: EventId,
+    /// Plugin that registered the handler; `None` for application handlers
+    owner: Option<String>,
+    /// Sort key: application handlers (`None`) run first, then plugin handlers by priority
+    priority: Option<PluginPriority>,
+    handler: Box<dyn AsyncEventHandler>,
+}
+
 /// Event dispatcher for managing and dispatching events (Internal Implementation)
+///
+/// Handlers for an event run in order of the priority of the plugin that registered
+/// them, Kernel first; application handlers run before all plugin handlers.
+/// Handlers with the same priority run in registration order.
 pub struct EventDispatcher {
-    handlers: HashMap<&'static str, Vec<(EventId, Box<dyn AsyncEventHandler>)>>,
-    type_handlers: HashMap<TypeId, Vec<(EventId, Box<dyn AsyncEventHandler>)>>,
+    handlers: HashMap<&'static str, Vec<RegisteredHandler>>,
+    type_handlers: HashMap<TypeId, Vec<RegisteredHandler>>,
+    /// Priorities of the plugins that register handlers
+    plugin_priorities: HashMap<String, PluginPriority>,
     next_handler_id: EventId,
     event_queue: VecDeque<Box<dyn Event>>,
 }
@@ -33,6 +53,7 @@ impl fmt::Debug for EventDispatcher {
         f.debug_struct("EventDispatcher")
          .field("name_handlers_count", &name_handler_count)
          .field("type_handlers_count", &type_handler_count)
+         .field("plugin_priorities", &self.plugin_priorities)
          .field("next_handler_id", &self.next_handler_id)
          .field("event_queue_size", &self.event_queue.len())
          .finish()
@@ -72,34 +93,75 @@ impl EventDispatcher {
         Self {
             handlers: HashMap::new(),
             type_handlers: HashMap::new(),
+            plugin_priorities: HashMap::new(),
             next_handler_id: 1,
             event_queue: VecDeque::new(),
         }
     }
 
     pub fn register_handler( &mut self, event_name: &'static str, handler: Box<dyn Fn(&dyn Event) -> BoxFuture<'_> + Send + Sync> ) -> EventId {
-        let id = self.next_handler_id; self.next_handler_id += 1;
-        let handler = SimpleHandler { handler };
-        self.handlers.entry(event_name).or_default().push((id, Box::new(handler)));
-        id
+        let handler = self.new_handler(None, Box::new(SimpleHandler { handler }));
+        Self::insert_handler(self.handlers.entry(event_name).or_default(), handler)
+    }
+
+    /// Register a handler on behalf of a plugin; it runs with the plugin's priority.
+    /// Until the priority is recorded with [`set_plugin_priority`](Self::set_plugin_priority),
+    /// the handler runs after those of every other plugin.
+    pub(crate) fn register_plugin_handler( &mut self, plugin_id: &str, event_name: &'static str, handler: PluginHandlerFn ) -> EventId {
+        let handler = self.new_handler(Some(plugin_id), Box::new(SimpleHandler { handler }));
+        Self::insert_handler(self.handlers.entry(event_name).or_default(), handler)
     }
 
     pub fn register_type_handler<E: Event + 'static>( &mut self, handler: Box<dyn Fn(&E) -> BoxFuture<'_> + Send + Sync> ) -> EventId {
+        let handler = self.new_handler(None, Box::new(TypedEventHandler { handler }));
+        Self::insert_handler(self.type_handlers.entry(TypeId::of::<E>()).or_default(), handler)
+    }
+
+    /// Record the priority of a plugin. Handlers it registered before are moved to their new place.
+    pub fn set_plugin_priority(&mut self, plugin_id: &str, priority: PluginPriority) {
+        self.plugin_priorities.insert(plugin_id.to_string(), priority.clone());
+        for handlers in self.handlers.values_mut().chain(self.type_handlers.values_mut()) {
+            let mut changed = false;
+            for entry in handlers.iter_mut().filter(|entry| entry.owner.as_deref() == Some(plugin_id)) {
+                entry.priority = Some(priority.clone());
+                changed = true;
+            }
+            if changed {
+                // Stable, so handlers with the same priority keep their registration order
+                handlers.sort_by(|a, b| a.priority.cmp(&b.priority));
+            }
+        }
+    }
+
+    /// The recorded priority of a plugin
+    pub fn plugin_priority(&self, plugin_id: &str) -> Option<PluginPriority> {
+        self.plugin_priorities.get(plugin_id).cloned()
+    }
+
+    fn new_handler(&mut self, owner: Option<&str>, handler: Box<dyn AsyncEventHandler>) -> RegisteredHandler {
         let id = self.next_handler_id; self.next_handler_id += 1;
-        let type_id = TypeId::of::<E>();
-        let handler = TypedEventHandler { handler };
-        self.type_handlers.entry(type_id).or_default().push((id, Box::new(handler)));
+        let priority = owner.map(|plugin_id| {
+            self.plugin_priorities.get(plugin_id).cloned().unwrap_or(PluginPriority::ThirdPartyLow(255))
+        });
+        RegisteredHandler { id, owner: owner.map(str::to_string), priority, handler }
+    }
+
+    /// Insert after every handler that runs before or with the same priority as `handler`
+    fn insert_handler(handlers: &mut Vec<RegisteredHandler>, handler: RegisteredHandler) -> EventId {
+        let id = handler.id;
+        let position = handlers.partition_point(|entry| entry.priority <= handler.priority);
+        handlers.insert(position, handler);
         id
     }
 
      pub fn unregister_handler(&mut self, id: EventId) -> bool {
         let mut found = false;
         self.handlers.values_mut().for_each(|handlers| {
-            let len_before = handlers.len(); handlers.retain(|(h_id, _)| *h_id != id);
+            let len_before = handlers.len(); handlers.retain(|entry| entry.id != id);
             if handlers.len() < len_before { found = true; }
         });
         self.type_handlers.values_mut().for_each(|handlers| {
-             let len_before = handlers.len(); handlers.retain(|(h_id, _)| *h_id != id);
+             let len_before = handlers.len(); handlers.retain(|entry| entry.id != id);
              if handlers.len() < len_before { found = true; }
         });
         found
@@ -108,8 +170,8 @@ impl EventDispatcher {
     pub async fn dispatch_internal(&self, event: &dyn Event) -> EventResult {
         let mut result = EventResult::Continue;
         if let Some(handlers) = self.handlers.get(event.name()) {
-            for (_, handler) in handlers {
-                match handler.handle(event).await {
+            for entry in handlers {
+                match entry.handler.handle(event).await {
                     EventResult::Continue => {},
                     EventResult::Stop => { result = EventResult::Stop; break; }
                 }
@@ -117,8 +179,8 @@ impl EventDispatcher {
         }
         if result == EventResult::Stop { return result; }
         if let Some(handlers) = self.type_handlers.get(&event.as_any().type_id()) {
-            for (_, handler) in handlers {
-                match handler.handle(event).await {
+            for entry in handlers {
+                match entry.handler.handle(event).await {
                     EventResult::Continue => {},
                     EventResult::Stop => { result = EventResult::Stop; break; }
                 }
@@ -190,11 +252,21 @@ impl SharedEventDispatcher {
         dispatcher.register_handler(event_name, handler)
     }
 
+    pub(crate) async fn register_plugin_handler( &self, plugin_id: &str, event_name: &'static str, handler: PluginHandlerFn ) -> EventId {
+        let mut dispatcher = self.dispatcher.lock().await;
+        dispatcher.register_plugin_handler(plugin_id, event_name, handler)
+    }
+
     pub async fn register_type_handler<E: Event + 'static>( &self, handler: Box<dyn Fn(&E) -> BoxFuture<'_> + Send + Sync> ) -> EventId {
         let mut dispatcher = self.dispatcher.lock().await;
         dispatcher.register_type_handler::<E>(handler)
     }
 
+    pub async fn set_plugin_priority(&self, plugin_id: &str, priority: PluginPriority) {
+        let mut dispatcher = self.dispatcher.lock().await;
+        dispatcher.set_plugin_priority(plugin_id, priority)
+    }
+
     pub async fn unregister_handler(&self, id: EventId) -> bool {
         let mut dispatcher = self.dispatcher.lock().await;
         dispatcher.unregister_handler(id)
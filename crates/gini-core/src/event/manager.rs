@@ -4,9 +4,10 @@ use async_trait::async_trait;
 
 use crate::event::{Event, EventId, EventResult};
 // Ensure BoxFuture is correctly imported or defined if it's local
-use crate::event::dispatcher::{self, BoxFuture};
+use crate::event::dispatcher::{self, BoxFuture, PluginHandlerFn};
 use crate::kernel::component::KernelComponent;
 use crate::kernel::error::Result; // Keep for KernelComponent trait methods
+use crate::plugin_system::traits::PluginPriority;
 // Specific event manager methods will no longer use kernel::error::Result
 
 /// Type alias for boxed event
@@ -25,6 +26,10 @@ pub trait EventManager: KernelComponent + Send + Sync { // Add Send + Sync bound
         handler: Box<dyn for<'a> Fn(&'a dyn Event) -> BoxFuture<'a> + Send + Sync>
     ) -> EventId;
 
+    /// Record the priority the handlers of a plugin run with.
+    /// The plugin registry records it before the plugin is initialized.
+    async fn set_plugin_priority(&self, plugin_id: &str, priority: PluginPriority);
+
     // Removed register_type_handler (generic)
     // Removed register_sync_handler (generic wrapper)
     // Removed register_sync_type_handler (generic wrapper)
@@ -63,6 +68,20 @@ impl DefaultEventManager {
         &self.dispatcher
     }
 
+    /// Register a handler on behalf of a plugin. Handlers run in order of the
+    /// priority of the plugin that registered them, after application handlers.
+    /// Plugins register through [`PluginAccess::register_event_handler`], which is bound to their ID.
+    ///
+    /// [`PluginAccess::register_event_handler`]: crate::plugin_system::permission::PluginAccess::register_event_handler
+    pub(crate) async fn register_plugin_handler(
+        &self,
+        plugin_id: &str,
+        event_name: &'static str,
+        handler: PluginHandlerFn
+    ) -> EventId {
+        self.dispatcher.register_plugin_handler(plugin_id, event_name, handler).await
+    }
+
     // Add back sync handler registration methods directly on the concrete type
     // if they are needed, as they can't be on the dyn trait.
 
@@ -113,6 +132,10 @@ impl EventManager for DefaultEventManager {
         self.dispatcher.register_handler(event_name, handler).await
     }
 
+    async fn set_plugin_priority(&self, plugin_id: &str, priority: PluginPriority) {
+        self.dispatcher.set_plugin_priority(plugin_id, priority).await
+    }
+
     // Removed register_type_handler impl
     // Removed register_sync_handler impl (moved to concrete struct)
     // Removed register_sync_type_handler impl (moved to concrete struct)
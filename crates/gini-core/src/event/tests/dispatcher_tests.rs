@@ -5,6 +5,7 @@ use tokio::sync::Mutex;
 
 use crate::event::{Event, EventPriority, EventResult};
 use crate::event::dispatcher::{EventDispatcher, create_dispatcher, sync_event_handler, sync_typed_handler};
+use crate::plugin_system::traits::PluginPriority;
 
 // Test event implementation
 #[derive(Debug, Clone)]
@@ -261,4 +262,31 @@ async fn test_shared_dispatcher_registration_and_dispatch() {
     shared_dispatcher.dispatch(&event3).await;
     assert_eq!(counter.load(Ordering::SeqCst), 2, "Handler should not run after unregistering via shared dispatcher");
 
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_handlers_run_in_plugin_priority_order() {
+    let mut dispatcher = EventDispatcher::new();
+    let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorder = |label: &'static str| {
+        let order = order.clone();
+        sync_event_handler(move |_| { order.lock().unwrap().push(label); EventResult::Continue })
+    };
+
+    dispatcher.set_plugin_priority("third-party", PluginPriority::ThirdParty(151));
+    dispatcher.register_plugin_handler("third-party", "test.event", recorder("third-party"));
+    dispatcher.register_plugin_handler("unrecorded", "test.event", recorder("unrecorded"));
+    dispatcher.register_plugin_handler("core", "test.event", recorder("core"));
+    dispatcher.register_handler("test.event", recorder("application"));
+    dispatcher.register_plugin_handler("third-party", "test.event", recorder("third-party 2"));
+    // Recording the priority later moves handlers already registered
+    dispatcher.set_plugin_priority("core", PluginPriority::Core(60));
+    dispatcher.set_plugin_priority("kernel", PluginPriority::Kernel(1));
+    dispatcher.register_plugin_handler("kernel", "test.event", recorder("kernel"));
+
+    dispatcher.dispatch_internal(&TestEvent::new("test.event", "ordered")).await;
+
+    assert_eq!(*order.lock().unwrap(), vec!["application", "kernel", "core", "third-party", "third-party 2", "unrecorded"]);
+    assert_eq!(dispatcher.plugin_priority("core"), Some(PluginPriority::Core(60)));
+    assert_eq!(dispatcher.plugin_priority("unrecorded"), None);
+}
@@ -24,6 +24,10 @@ pub enum SystemEvent {
     PipelineComplete { pipeline_id: String, success: bool },
     /// Configuration has changed
     ConfigChange { key: String, value: String },
+    /// A plugin accessed a resource it did not declare (audit event)
+    PermissionDenied { plugin_id: String, resource_type: String, identifier: String, access: String },
+    /// A plugin moved to another lifecycle state
+    PluginStateChanged { plugin_id: String, from: String, to: String, reason: String },
 }
 
 #[cfg(test)]
@@ -71,6 +75,8 @@ impl Event for SystemEvent {
             SystemEvent::PipelineBegin { .. } => "pipeline.begin",
             SystemEvent::PipelineComplete { .. } => "pipeline.complete",
             SystemEvent::ConfigChange { .. } => "config.change",
+            SystemEvent::PermissionDenied { .. } => "plugin.permission_denied",
+            SystemEvent::PluginStateChanged { .. } => "plugin.state_changed",
         }
     }
     
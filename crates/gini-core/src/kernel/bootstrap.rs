@@ -12,6 +12,7 @@ use crate::kernel::component::{KernelComponent, DependencyRegistry};
 use crate::event::{DefaultEventManager, EventManager}; // Remove braces
 use crate::stage_manager::manager::DefaultStageManager; // Remove braces
 use crate::plugin_system::DefaultPluginManager; // Remove braces
+use crate::plugin_system::permission::PluginAccess; // Handed to the plugin being initialized
 use crate::storage::DefaultStorageManager; // Remove braces
 use crate::ui_bridge::UnifiedUiManager; // Changed from UIManager
 
@@ -30,6 +31,8 @@ pub struct Application {
     // However, for direct ownership and mutable access via ui_manager_mut, this is fine.
     // It will be registered as Arc<UnifiedUiManager> in the dependency registry.
     ui_manager: UnifiedUiManager,
+    /// Access handle of the plugin whose `init` is running
+    plugin_access: Option<PluginAccess>,
 }
 
 // Updated impl Application block using simplified DependencyRegistry
@@ -67,13 +70,22 @@ impl Application {
 
         // Get the StageRegistry Arc from the StageManager to pass to PluginManager
         let stage_registry_arc_for_plugin = stage_manager.registry(); // Assuming DefaultStageManager has a .registry() method returning Arc<Mutex<StageRegistry>>
-        let plugin_manager = Arc::new(DefaultPluginManager::new(config_manager_for_plugin, stage_registry_arc_for_plugin)?);
-        registry.register_instance(plugin_manager.clone()); // Register Arc<DefaultPluginManager>, clone Arc
-        init_order.push(TypeId::of::<DefaultPluginManager>()); // Store concrete TypeId
- 
+
         // Instantiate UnifiedUiManager
         // Pass the EventManager to the UnifiedUiManager
+        // Created before the PluginManager, which asks the user about plugin conflicts through it
         let ui_manager_owned = UnifiedUiManager::new(event_manager.clone() as Arc<dyn EventManager>);
+
+        let plugin_manager = Arc::new(
+            DefaultPluginManager::new(config_manager_for_plugin, stage_registry_arc_for_plugin)?
+                .with_event_manager(event_manager.clone() as Arc<dyn EventManager>) // For plugin reload events
+                .with_data_dir(storage_manager.data_dir().to_path_buf()) // Search the XDG data dir for plugins
+                .with_config_dir(storage_manager.config_dir().to_path_buf()) // Trust store of plugin signing keys
+                .with_ui_manager(ui_manager_owned.clone()), // Shares interfaces with the Application's UI manager
+        );
+        registry.register_instance(plugin_manager.clone()); // Register Arc<DefaultPluginManager>, clone Arc
+        init_order.push(TypeId::of::<DefaultPluginManager>()); // Store concrete TypeId
+ 
         // Register an Arc of a clone of the owned instance.
         // The Application struct will hold the original owned instance.
         registry.register_instance(Arc::new(ui_manager_owned.clone()));
@@ -87,6 +99,7 @@ impl Application {
             dependencies: Arc::new(Mutex::new(registry)),
             component_init_order: init_order,
             ui_manager: ui_manager_owned, // Store the owned instance
+            plugin_access: None,
         })
     }
 
@@ -258,8 +271,28 @@ impl Application {
             .expect("Stage manager not found in registry")
     }
 
+    /// Get the event manager instance (synchronous convenience accessor)
+    /// Note: Uses try_lock for sync access.
+    pub fn event_manager(&self) -> Arc<DefaultEventManager> {
+        self.dependencies.try_lock()
+            .ok()
+            .and_then(|reg| reg.get_concrete::<DefaultEventManager>())
+            .expect("Event manager not found in registry")
+    }
+
     /// Returns a mutable reference to the UI manager.
     pub fn ui_manager_mut(&mut self) -> &mut UnifiedUiManager {
         &mut self.ui_manager
     }
+
+    /// The access handle of the plugin being initialized, while its `init` runs.
+    /// Plugins get it with [`PluginAccess::current`].
+    pub fn plugin_access(&self) -> Option<&PluginAccess> {
+        self.plugin_access.as_ref()
+    }
+
+    /// Set by the plugin registry around each plugin's `init`
+    pub(crate) fn set_plugin_access(&mut self, access: Option<PluginAccess>) {
+        self.plugin_access = access;
+    }
 }
\ No newline at end of file
@@ -22,6 +22,12 @@ pub const CORE_PLUGINS_DIR: &str = "plugins/core";
 /// Third-party plugins directory
 pub const THIRD_PARTY_PLUGINS_DIR: &str = "plugins/third_party";
 
+/// Trust store of plugin signing keys, relative to the config directory
+pub const PLUGIN_TRUST_STORE_FILE: &str = "trusted_plugin_keys.json";
+
+/// Environment variable listing additional plugin directories (`PATH`-style separators)
+pub const PLUGIN_PATH_ENV_VAR: &str = "GINI_PLUGIN_PATH";
+
 /// Default assets directory
 pub const ASSETS_DIR: &str = "assets";
 
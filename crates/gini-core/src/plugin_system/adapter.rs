@@ -1,3 +1,6 @@
+// The adapter items refer to each other
+#![allow(deprecated)]
+
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
 
@@ -5,6 +8,7 @@ use crate::kernel::error::{Result}; // Removed unused Error
 use crate::plugin_system::error::PluginSystemError; // Import PluginSystemError
 
 /// Adapter trait for providing type-safe interfaces between plugins
+#[deprecated(note = "publish typed services through the ServiceRegistry instead")]
 pub trait Adapter: Send + Sync {
     /// Get the adapter's type ID
     fn type_id(&self) -> TypeId;
@@ -20,6 +24,7 @@ pub trait Adapter: Send + Sync {
 }
 
 /// Registry for adapters
+#[deprecated(note = "use plugin_system::service::ServiceRegistry instead")]
 #[derive(Default)]
 pub struct AdapterRegistry {
     adapters: HashMap<TypeId, Box<dyn Adapter>>,
@@ -135,6 +140,7 @@ impl AdapterRegistry {
 }
 
 /// Macro to create an adapter implementation
+#[deprecated(note = "publish typed services through the ServiceRegistry instead")]
 #[macro_export]
 macro_rules! define_adapter {
     // Add $impl_type:ident as the second argument
@@ -1,39 +1,234 @@
 use crate::kernel::error::Result; // Removed unused Error
-use crate::plugin_system::manifest::{PluginManifest, ResourceAccessType as ManifestResourceAccessType};
+use crate::plugin_system::manifest::PluginManifest;
 use crate::plugin_system::error::PluginSystemError; // Import PluginSystemError
+use crate::plugin_system::traits::PluginPriority; // Compared by ConflictPolicy::PreferHigherPriority
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::hash::Hash;
+use std::path::{Component, Path};
+use std::str::FromStr;
+use serde::{Deserialize, Serialize}; // Claims are read from manifests
 
 /// Unique identifier for a resource.
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+///
+/// Identifiers are matched by kind (see [`overlaps`](Self::overlaps)) rather than compared for
+/// equality, so a claim on a directory also covers the files below it. Kinds are compared
+/// ignoring case and underscores, so `FilePath` and `file_path` are the same kind.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub struct ResourceIdentifier {
     /// The kind of resource (e.g., "FilePath", "NetworkPort", "NamedMutex", "Abstract").
+    #[serde(rename = "type")]
     pub kind: String,
     /// The unique ID of the resource within its kind (e.g., "/var/log/app.log", "tcp:8080", "my_app_global_lock").
+    #[serde(rename = "identifier")]
     pub id: String,
 }
 
+impl ResourceIdentifier {
+    /// Creates an identifier of the given kind.
+    pub fn new(kind: &str, id: &str) -> Self {
+        Self { kind: kind.to_string(), id: id.to_string() }
+    }
+
+    /// Check whether two identifiers can refer to the same resource.
+    ///
+    /// Identifiers of different kinds never overlap, and an empty (or blank) id overlaps nothing.
+    /// Within a kind:
+    /// - `file_path` and `storage_path`: one path contains the other (`/var/lib/gini/vms`
+    ///   overlaps `/var/lib/gini/vms/a.qcow2`, but not `/var/lib/gini/vms2`). Paths are
+    ///   normalized lexically first, so `/var/lib/gini/vms/../logs` is `/var/lib/gini/logs`.
+    /// - `network_port`: the port ranges intersect. Ports are written `8080`, `8000-8100`,
+    ///   optionally prefixed with a protocol (`tcp:8080`); different protocols never overlap.
+    /// - Anything else: the ids are glob patterns (`*` matches any run of characters, `?` a
+    ///   single one) and overlap if some id matches both.
+    pub fn overlaps(&self, other: &ResourceIdentifier) -> bool {
+        let kind = normalized_kind(&self.kind);
+        if kind != normalized_kind(&other.kind) || self.id.trim().is_empty() || other.id.trim().is_empty() {
+            return false;
+        }
+        match kind.as_str() {
+            "filepath" | "storagepath" => paths_overlap(&self.id, &other.id),
+            "networkport" => match (PortRange::parse(&self.id), PortRange::parse(&other.id)) {
+                (Some(first), Some(second)) => first.overlaps(&second),
+                _ => globs_overlap(&self.id, &other.id),
+            },
+            _ => globs_overlap(&self.id, &other.id),
+        }
+    }
+}
+
+impl fmt::Display for ResourceIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.kind, self.id)
+    }
+}
+
+/// Kind name with case and underscores ignored
+fn normalized_kind(kind: &str) -> String {
+    kind.chars().filter(|c| *c != '_').map(|c| c.to_ascii_lowercase()).collect()
+}
+
+/// Whether one path is the other or lies below it, compared component by component
+fn paths_overlap(first: &str, second: &str) -> bool {
+    let first = normalized_components(first);
+    let second = normalized_components(second);
+    first.iter().zip(&second).all(|(a, b)| a == b)
+}
+
+/// The components of `path` with `.` dropped and `..` applied, without touching the file system.
+/// `..` above the root stays at the root; leading `..` of a relative path is kept.
+fn normalized_components(path: &str) -> Vec<Component<'_>> {
+    let mut components: Vec<Component> = Vec::new();
+    for component in Path::new(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match components.last() {
+                Some(Component::Normal(_)) => {
+                    components.pop();
+                }
+                Some(Component::RootDir | Component::Prefix(_)) => {}
+                _ => components.push(component),
+            },
+            _ => components.push(component),
+        }
+    }
+    components
+}
+
+/// An inclusive range of ports, optionally for a single protocol
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PortRange {
+    protocol: Option<String>,
+    start: u16,
+    end: u16,
+}
+
+impl PortRange {
+    /// Parses `8080`, `8000-8100`, `tcp:8080` or `tcp:8000-8100`
+    fn parse(id: &str) -> Option<Self> {
+        let (protocol, ports) = match id.split_once(':') {
+            Some((protocol, ports)) => (Some(protocol.trim().to_ascii_lowercase()), ports),
+            None => (None, id),
+        };
+        let (start, end) = match ports.split_once('-') {
+            Some((start, end)) => (start.trim().parse().ok()?, end.trim().parse().ok()?),
+            None => {
+                let port = ports.trim().parse().ok()?;
+                (port, port)
+            }
+        };
+        (start <= end).then_some(PortRange { protocol, start, end })
+    }
+
+    /// Ranges without a protocol overlap ranges of any protocol
+    fn overlaps(&self, other: &PortRange) -> bool {
+        let same_protocol = match (&self.protocol, &other.protocol) {
+            (Some(first), Some(second)) => first == second,
+            _ => true,
+        };
+        same_protocol && self.start <= other.end && other.start <= self.end
+    }
+}
+
+/// Whether some string matches both glob patterns
+fn globs_overlap(first: &str, second: &str) -> bool {
+    let first: Vec<char> = first.chars().collect();
+    let second: Vec<char> = second.chars().collect();
+    let mut visited = HashSet::new();
+    globs_overlap_from(&first, &second, 0, 0, &mut visited)
+}
+
+/// Whether the pattern suffixes starting at `i` and `j` share a match.
+/// `visited` holds positions already known not to.
+fn globs_overlap_from(first: &[char], second: &[char], i: usize, j: usize, visited: &mut HashSet<(usize, usize)>) -> bool {
+    if !visited.insert((i, j)) {
+        return false;
+    }
+    match (first.get(i), second.get(j)) {
+        (None, None) => true,
+        // A star matches nothing, or absorbs one more character of the other pattern
+        (Some('*'), other) => {
+            globs_overlap_from(first, second, i + 1, j, visited)
+                || (other.is_some() && globs_overlap_from(first, second, i, j + 1, visited))
+        }
+        (other, Some('*')) => {
+            globs_overlap_from(first, second, i, j + 1, visited)
+                || (other.is_some() && globs_overlap_from(first, second, i + 1, j, visited))
+        }
+        (Some(a), Some(b)) => (a == b || *a == '?' || *b == '?') && globs_overlap_from(first, second, i + 1, j + 1, visited),
+        _ => false,
+    }
+}
+
 /// Defines how a plugin intends to use a resource.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)] // Added Copy
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy, Deserialize, Serialize)] // Added Copy
 pub enum ResourceAccessType {
     /// Exclusive read access. No other plugin can read or write this resource.
+    #[serde(rename = "exclusive_read")]
     ExclusiveRead,
     /// Exclusive write access. No other plugin can read or write this resource. Implies read capability.
+    #[serde(rename = "exclusive_write")]
     ExclusiveWrite,
     /// Shared read access. Multiple plugins can read this resource. No writes allowed by shared readers.
+    #[serde(rename = "shared_read")]
     SharedRead,
     /// Shared write access. Multiple plugins can write to this resource. Implies read capability.
+    #[serde(rename = "shared_write")]
     SharedWrite,
     /// Indicates the plugin defines or provides this resource uniquely.
+    /// e.g., A plugin `provides_unique_id` for a `stage_id`. Conflicts if another plugin also `provides_unique_id` for the same stage_id.
+    #[serde(rename = "provides_unique_id")]
     ProvidesUniqueId,
 }
 
-/// Represents a single resource claim made by a plugin.
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl ResourceAccessType {
+    /// Returns a string representation of the access type.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ResourceAccessType::ExclusiveRead => "ExclusiveRead",
+            ResourceAccessType::ExclusiveWrite => "ExclusiveWrite",
+            ResourceAccessType::SharedRead => "SharedRead",
+            ResourceAccessType::SharedWrite => "SharedWrite",
+            ResourceAccessType::ProvidesUniqueId => "ProvidesUniqueId",
+        }
+    }
+
+    /// Check whether two plugins claiming an overlapping resource with these access types conflict.
+    /// Only shared reads, and shared reads of a provided id, can coexist.
+    pub fn conflicts_with(&self, other: ResourceAccessType) -> bool {
+        !matches!(
+            (self, other),
+            (ResourceAccessType::SharedRead, ResourceAccessType::SharedRead)
+                | (ResourceAccessType::SharedRead, ResourceAccessType::ProvidesUniqueId)
+                | (ResourceAccessType::ProvidesUniqueId, ResourceAccessType::SharedRead)
+        )
+    }
+}
+
+/// Represents a single resource claim made by a plugin, either in its manifest's `resources`
+/// or through [`Plugin::declared_resources`](crate::plugin_system::traits::Plugin::declared_resources).
+///
+/// In a manifest a claim is written `{ "type": "file_path", "identifier": "/var/log/app.log", "access": "exclusive_write" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
 pub struct ResourceClaim {
+    #[serde(flatten)]
     pub resource: ResourceIdentifier,
+    #[serde(rename = "access")]
     pub access_type: ResourceAccessType,
 }
 
+impl ResourceClaim {
+    /// Creates a claim on the resource `id` of `kind`.
+    pub fn new(kind: &str, id: &str, access_type: ResourceAccessType) -> Self {
+        Self { resource: ResourceIdentifier::new(kind, id), access_type }
+    }
+
+    /// Check whether this claim and `other` cannot both be granted.
+    pub fn conflicts_with(&self, other: &ResourceClaim) -> bool {
+        self.resource.overlaps(&other.resource) && self.access_type.conflicts_with(other.access_type)
+    }
+}
+
 /// Types of plugin conflicts
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConflictType {
@@ -63,6 +258,11 @@ pub enum ConflictType {
 }
 
 impl ConflictType {
+    /// Whether the conflict comes from a plugin's own `conflicts_with` or `incompatible_with`
+    pub fn is_declared(&self) -> bool {
+        matches!(self, ConflictType::MutuallyExclusive | ConflictType::ExplicitlyIncompatible)
+    }
+
     /// Check if this conflict type is critical (must be resolved)
     pub fn is_critical(&self) -> bool {
         match self {
@@ -140,6 +340,136 @@ pub enum ResolutionStrategy {
     Custom(String),
 }
 
+/// A rule for choosing which plugin of a critical conflict stays enabled.
+///
+/// Rules are tried in order; the first one that tells the two plugins apart decides,
+/// and the other plugin is disabled. Core plugins are never disabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the plugin with the higher [`PluginPriority`] (the lower priority value)
+    PreferHigherPriority,
+    /// Keep core plugins over third-party plugins
+    PreferCore,
+    /// Keep the plugin the user explicitly enabled
+    PreferExplicitlyEnabled,
+    /// Keep the named plugin whenever it is involved
+    PreferPlugin(String),
+}
+
+impl ConflictPolicy {
+    /// The plugin this rule would disable, or `None` if it cannot tell `first` and `second` apart
+    fn loser<'a>(&self, first: &'a str, second: &'a str, parties: &HashMap<String, ConflictParty>) -> Option<&'a str> {
+        if let ConflictPolicy::PreferPlugin(preferred) = self {
+            return if preferred == first {
+                Some(second)
+            } else if preferred == second {
+                Some(first)
+            } else {
+                None
+            };
+        }
+
+        let (first_party, second_party) = (parties.get(first)?, parties.get(second)?);
+        let first_wins = match self {
+            ConflictPolicy::PreferHigherPriority => match first_party.priority.cmp(&second_party.priority) {
+                std::cmp::Ordering::Less => true,
+                std::cmp::Ordering::Greater => false,
+                std::cmp::Ordering::Equal => return None,
+            },
+            ConflictPolicy::PreferCore if first_party.is_core != second_party.is_core => first_party.is_core,
+            ConflictPolicy::PreferExplicitlyEnabled if first_party.explicitly_enabled != second_party.explicitly_enabled => {
+                first_party.explicitly_enabled
+            }
+            _ => return None,
+        };
+        Some(if first_wins { second } else { first })
+    }
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = String;
+
+    /// Parses `prefer-higher-priority`, `prefer-core`, `prefer-explicitly-enabled` or `prefer:<plugin id>`
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.trim() {
+            "prefer-higher-priority" => Ok(ConflictPolicy::PreferHigherPriority),
+            "prefer-core" => Ok(ConflictPolicy::PreferCore),
+            "prefer-explicitly-enabled" => Ok(ConflictPolicy::PreferExplicitlyEnabled),
+            other => match other.strip_prefix("prefer:") {
+                Some(plugin_id) if !plugin_id.is_empty() => Ok(ConflictPolicy::PreferPlugin(plugin_id.to_string())),
+                _ => Err(format!(
+                    "Unknown conflict policy '{}' (expected prefer-higher-priority, prefer-core, prefer-explicitly-enabled or prefer:<plugin id>)",
+                    other
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for ConflictPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConflictPolicy::PreferHigherPriority => write!(f, "prefer-higher-priority"),
+            ConflictPolicy::PreferCore => write!(f, "prefer-core"),
+            ConflictPolicy::PreferExplicitlyEnabled => write!(f, "prefer-explicitly-enabled"),
+            ConflictPolicy::PreferPlugin(plugin_id) => write!(f, "prefer:{}", plugin_id),
+        }
+    }
+}
+
+/// What [`ConflictManager::apply_policies`] needs to know about a plugin involved in a conflict.
+#[derive(Debug, Clone)]
+pub struct ConflictParty {
+    pub priority: PluginPriority,
+    pub is_core: bool,
+    /// Whether the user explicitly enabled the plugin (e.g. `gini plugin enable`)
+    pub explicitly_enabled: bool,
+}
+
+/// A conflict resolved by [`ConflictManager::apply_policies`].
+#[derive(Debug, Clone)]
+pub struct ConflictDecision {
+    pub first_plugin: String,
+    pub second_plugin: String,
+    /// Description of the conflict that was resolved
+    pub description: String,
+    pub strategy: ResolutionStrategy,
+    /// The policy that decided, for conflicts resolved by disabling a plugin
+    pub policy: Option<ConflictPolicy>,
+}
+
+impl ConflictDecision {
+    /// The plugin disabled by this decision, if any
+    pub fn disabled_plugin(&self) -> Option<&str> {
+        match self.strategy {
+            ResolutionStrategy::DisableFirst => Some(&self.first_plugin),
+            ResolutionStrategy::DisableSecond => Some(&self.second_plugin),
+            _ => None,
+        }
+    }
+
+    /// The plugin kept by this decision, if one was disabled
+    pub fn kept_plugin(&self) -> Option<&str> {
+        match self.strategy {
+            ResolutionStrategy::DisableFirst => Some(&self.second_plugin),
+            ResolutionStrategy::DisableSecond => Some(&self.first_plugin),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ConflictDecision {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.disabled_plugin(), self.kept_plugin(), &self.policy) {
+            (Some(disabled), Some(kept), Some(policy)) => {
+                write!(f, "Disabled '{}' in favour of '{}' ({}): {}", disabled, kept, policy, self.description)
+            }
+            (Some(disabled), Some(kept), None) => write!(f, "Disabled '{}' in favour of '{}': {}", disabled, kept, self.description),
+            _ => write!(f, "Allowed '{}' and '{}' to run together: {}", self.first_plugin, self.second_plugin, self.description),
+        }
+    }
+}
+
 impl PluginConflict {
     /// Create a new plugin conflict
     pub fn new(
@@ -257,6 +587,70 @@ impl ConflictManager {
         to_disable
     }
     
+    /// Resolve unresolved conflicts automatically.
+    ///
+    /// Non-critical conflicts are allowed with a warning. For a critical conflict, `policies` are
+    /// tried in order and the first one that prefers one of the plugins decides; the other plugin
+    /// is marked for disabling (see [`get_plugins_to_disable`](Self::get_plugins_to_disable)).
+    /// Conflicts involving a plugin already disabled by an earlier decision need no new decision.
+    /// Critical conflicts no policy can decide stay unresolved.
+    pub fn apply_policies(&mut self, policies: &[ConflictPolicy], parties: &HashMap<String, ConflictParty>) -> Vec<ConflictDecision> {
+        self.apply_policies_where(policies, parties, |_| true)
+    }
+
+    /// [`apply_policies`](Self::apply_policies), leaving out the conflicts `filter` rejects
+    pub fn apply_policies_where(
+        &mut self,
+        policies: &[ConflictPolicy],
+        parties: &HashMap<String, ConflictParty>,
+        filter: impl Fn(&PluginConflict) -> bool,
+    ) -> Vec<ConflictDecision> {
+        let mut decisions = Vec::new();
+        let mut disabled: HashSet<String> = self.get_plugins_to_disable().into_iter().collect();
+
+        for conflict in self.conflicts.iter_mut().filter(|conflict| !conflict.resolved && filter(conflict)) {
+            if disabled.contains(&conflict.first_plugin) {
+                conflict.resolve(ResolutionStrategy::DisableFirst);
+                continue;
+            }
+            if disabled.contains(&conflict.second_plugin) {
+                conflict.resolve(ResolutionStrategy::DisableSecond);
+                continue;
+            }
+
+            let (strategy, policy) = if !conflict.is_critical() {
+                (ResolutionStrategy::AllowWithWarning, None)
+            } else {
+                let is_core = |id: &str| parties.get(id).is_some_and(|party| party.is_core);
+                let decided = policies.iter().find_map(|policy| {
+                    policy
+                        .loser(&conflict.first_plugin, &conflict.second_plugin, parties)
+                        .filter(|loser| !is_core(loser))
+                        .map(|loser| (loser == conflict.first_plugin, policy))
+                });
+                match decided {
+                    Some((true, policy)) => (ResolutionStrategy::DisableFirst, Some(policy.clone())),
+                    Some((false, policy)) => (ResolutionStrategy::DisableSecond, Some(policy.clone())),
+                    None => continue,
+                }
+            };
+
+            conflict.resolve(strategy.clone());
+            let decision = ConflictDecision {
+                first_plugin: conflict.first_plugin.clone(),
+                second_plugin: conflict.second_plugin.clone(),
+                description: conflict.description.clone(),
+                strategy,
+                policy,
+            };
+            if let Some(loser) = decision.disabled_plugin() {
+                disabled.insert(loser.to_string());
+            }
+            decisions.push(decision);
+        }
+        decisions
+    }
+
     /// Detect conflicts between plugins based on their manifests.
     /// This method will populate the internal list of conflicts.
     pub fn detect_conflicts(&mut self, manifests: &[PluginManifest]) -> Result<()> {
@@ -269,80 +663,30 @@ impl ConflictManager {
                 let m2 = &manifests[j];
 
                 // Check for resource claim conflicts
-                for claim1_manifest in &m1.resources {
-                    for claim2_manifest in &m2.resources {
-                        if claim1_manifest.resource_type == claim2_manifest.resource_type &&
-                           claim1_manifest.identifier == claim2_manifest.identifier {
-                            
-                            // Map ManifestResourceAccessType to new ResourceAccessType
-                            let access1 = match claim1_manifest.access {
-                                ManifestResourceAccessType::ExclusiveWrite => ResourceAccessType::ExclusiveWrite,
-                                ManifestResourceAccessType::SharedRead => ResourceAccessType::SharedRead,
-                                ManifestResourceAccessType::ProvidesUniqueId => ResourceAccessType::ProvidesUniqueId,
-                            };
-                            let access2 = match claim2_manifest.access {
-                                ManifestResourceAccessType::ExclusiveWrite => ResourceAccessType::ExclusiveWrite,
-                                ManifestResourceAccessType::SharedRead => ResourceAccessType::SharedRead,
-                                ManifestResourceAccessType::ProvidesUniqueId => ResourceAccessType::ProvidesUniqueId,
-                            };
-
-                            // Potential conflict on the same resource instance
-                            // Use the conflict matrix logic from the design document (Section 4.1)
-                            let conflict_exists = match (access1, access2) {
-                                // ExclusiveWrite conflicts with anything
-                                (ResourceAccessType::ExclusiveWrite, _) | (_, ResourceAccessType::ExclusiveWrite) => true,
-
-                                // ExclusiveRead conflicts
-                                (ResourceAccessType::ExclusiveRead, ResourceAccessType::ExclusiveRead) => true,
-                                (ResourceAccessType::ExclusiveRead, ResourceAccessType::SharedWrite) => true,
-                                (ResourceAccessType::ExclusiveRead, ResourceAccessType::SharedRead) => true,
-                                (ResourceAccessType::ExclusiveRead, ResourceAccessType::ProvidesUniqueId) => true,
-                                (ResourceAccessType::SharedWrite, ResourceAccessType::ExclusiveRead) => true, // Symmetric
-                                (ResourceAccessType::SharedRead, ResourceAccessType::ExclusiveRead) => true,   // Symmetric
-                                (ResourceAccessType::ProvidesUniqueId, ResourceAccessType::ExclusiveRead) => true, // Symmetric
-                                
-                                // SharedWrite conflicts (includes Yes* from design)
-                                (ResourceAccessType::SharedWrite, ResourceAccessType::SharedWrite) => true,
-                                (ResourceAccessType::SharedWrite, ResourceAccessType::SharedRead) => true,
-                                (ResourceAccessType::SharedWrite, ResourceAccessType::ProvidesUniqueId) => true,
-                                (ResourceAccessType::SharedRead, ResourceAccessType::SharedWrite) => true,   // Symmetric
-                                (ResourceAccessType::ProvidesUniqueId, ResourceAccessType::SharedWrite) => true, // Symmetric
-
-                                // ProvidesUniqueId conflicts
-                                (ResourceAccessType::ProvidesUniqueId, ResourceAccessType::ProvidesUniqueId) => true,
-
-                                // Non-conflicting cases
-                                (ResourceAccessType::SharedRead, ResourceAccessType::SharedRead) => false,
-                                (ResourceAccessType::SharedRead, ResourceAccessType::ProvidesUniqueId) => false,
-                                (ResourceAccessType::ProvidesUniqueId, ResourceAccessType::SharedRead) => false,
-                                // This match is exhaustive for all 5x5 combinations of ResourceAccessType
-                            };
-
-                            if conflict_exists {
-                                let resource_id = ResourceIdentifier {
-                                    kind: claim1_manifest.resource_type.clone(),
-                                    id: claim1_manifest.identifier.clone(),
-                                };
-                                let description = format!(
+                for claim1 in &m1.resources {
+                    for claim2 in &m2.resources {
+                        if claim1.conflicts_with(claim2) {
+                            let description = if claim1.resource.id == claim2.resource.id {
+                                format!(
                                     "Resource conflict on type '{}', identifier '{}'. Plugin '{}' claims access {:?}, and Plugin '{}' claims access {:?}.",
-                                    resource_id.kind,
-                                    resource_id.id,
-                                    m1.id,
-                                    access1, // Use the new mapped access type
-                                    m2.id,
-                                    access2  // Use the new mapped access type
-                                );
-                                self.add_conflict(PluginConflict::new(
-                                    &m1.id,
-                                    &m2.id,
-                                    ConflictType::ResourceConflict {
-                                        resource: resource_id,
-                                        first_plugin_access: access1,
-                                        second_plugin_access: access2,
-                                    },
-                                    &description,
-                                ));
-                            }
+                                    claim1.resource.kind, claim1.resource.id, m1.id, claim1.access_type, m2.id, claim2.access_type
+                                )
+                            } else {
+                                format!(
+                                    "Resource conflict on type '{}', identifiers '{}' and '{}' overlap. Plugin '{}' claims access {:?}, and Plugin '{}' claims access {:?}.",
+                                    claim1.resource.kind, claim1.resource.id, claim2.resource.id, m1.id, claim1.access_type, m2.id, claim2.access_type
+                                )
+                            };
+                            self.add_conflict(PluginConflict::new(
+                                &m1.id,
+                                &m2.id,
+                                ConflictType::ResourceConflict {
+                                    resource: claim1.resource.clone(),
+                                    first_plugin_access: claim1.access_type,
+                                    second_plugin_access: claim2.access_type,
+                                },
+                                &description,
+                            ));
                         }
                     }
                 }
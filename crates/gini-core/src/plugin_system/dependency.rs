@@ -1,5 +1,6 @@
 use std::fmt;
 use crate::plugin_system::version::VersionRange;
+use crate::plugin_system::resolver::Incompatibility;
 use serde::Serialize; // Added Serialize
 use thiserror::Error; // Import thiserror
 
@@ -35,6 +36,17 @@ pub enum DependencyError {
     #[error("Circular dependency detected: {}", .0.join(" -> "))]
     CyclicDependency(Vec<String>),
     
+    /// No consistent set of plugins exists; lists the constraints that ruled out each candidate
+    #[error("No consistent set of plugins satisfies the constraints: {}", .0.iter().map(|reason| reason.to_string()).collect::<Vec<_>>().join("; "))]
+    Unsatisfiable(Vec<Incompatibility>),
+
+    /// A service the plugin requires is not published, or not in a matching version
+    #[error("Required service not available: '{service}' ({reason})")]
+    MissingService {
+        service: String,
+        reason: String,
+    },
+
     /// Other dependency resolution error
     #[error("Dependency error: {0}")]
     Other(String),
@@ -13,6 +13,7 @@ use std::path::PathBuf;
 use crate::plugin_system::version::VersionError;
 use crate::plugin_system::dependency::DependencyError;
 use crate::plugin_system::conflict::PluginConflict;
+use crate::storage::schema::ConfigViolation;
 
 #[derive(Debug, thiserror::Error)]
 pub enum PluginSystemError {
@@ -31,6 +32,41 @@ pub enum PluginSystemError {
         message: String,
     },
 
+    #[error("Plugin ABI mismatch for '{plugin_id}': {message}")]
+    AbiMismatch {
+        plugin_id: String,
+        path: Option<PathBuf>,
+        message: String,
+    },
+
+    #[error("Plugin host process error for '{plugin_id}' during '{operation}': {message}")]
+    HostProcessError {
+        plugin_id: String,
+        operation: String,
+        message: String,
+    },
+
+    #[error("WebAssembly error in plugin '{plugin_id}' during '{operation}': {message}")]
+    WasmError {
+        plugin_id: String,
+        operation: String,
+        message: String,
+    },
+
+    #[error("Plugin '{plugin_id}' was denied {access} access to undeclared {resource_type} '{identifier}'")]
+    PermissionDenied {
+        plugin_id: String,
+        resource_type: String,
+        identifier: String,
+        access: String,
+    },
+
+    #[error("Signature check failed for plugin '{plugin_id}': {message}")]
+    SignatureError {
+        plugin_id: String,
+        message: String,
+    },
+
     #[error("Plugin manifest error for '{path}': {message}")]
     ManifestError {
         path: PathBuf,
@@ -39,12 +75,31 @@ pub enum PluginSystemError {
         source: Option<Box<dyn std::error::Error + Send + Sync>>,
     },
 
+    #[error("Invalid plugin archive '{path}': {message}")]
+    ArchiveError {
+        path: PathBuf,
+        message: String,
+    },
+
+    #[error("Plugin installation failed for '{plugin_id}': {message}")]
+    InstallError {
+        plugin_id: String,
+        message: String,
+    },
+
     #[error("Plugin registration error for '{plugin_id}': {message}")]
     RegistrationError {
         plugin_id: String,
         message: String,
     },
 
+    #[error("Plugin '{plugin_id}' cannot move from state {from} to {to}")]
+    InvalidStateTransition {
+        plugin_id: String,
+        from: String,
+        to: String,
+    },
+
     #[error("Plugin initialization error for '{plugin_id}': {message}")]
     InitializationError {
         plugin_id: String,
@@ -53,6 +108,12 @@ pub enum PluginSystemError {
         source: Option<Box<PluginSystemErrorSource>>,
     },
 
+    #[error("Invalid configuration for plugin '{plugin_id}': {}", .violations.iter().map(|violation| violation.to_string()).collect::<Vec<_>>().join("; "))]
+    InvalidConfig {
+        plugin_id: String,
+        violations: Vec<ConfigViolation>,
+    },
+
     #[error("Plugin preflight check failed for '{plugin_id}': {message}")]
     PreflightCheckFailed {
         plugin_id: String,
@@ -81,6 +142,12 @@ pub enum PluginSystemError {
         message: String,
     },
 
+    #[error("Service '{service}': {reason}")]
+    ServiceError {
+        service: String,
+        reason: String,
+    },
+
     #[error("Operation error in plugin '{plugin_id}': {message}", plugin_id = .plugin_id.as_deref().unwrap_or("<unknown>"))]
     OperationError {
         plugin_id: Option<String>,
@@ -0,0 +1,341 @@
+//! Plugin side of the dynamic plugin FFI boundary.
+//!
+//! Generic `extern "C"` functions that expose any [`Plugin`] implementation through a
+//! [`PluginVTable`], following the ownership contract described in
+//! [`ffi_host`](crate::plugin_system::ffi_host). Strings and slices are handed out as
+//! owned allocations and released by the matching `free_*` entry, every entry is
+//! guarded against panics, and the async `preflight_check` is driven to completion on
+//! a dedicated thread.
+//!
+//! Plugins normally don't use this module directly: the `#[gini_plugin]` attribute from
+//! the `gini-plugin-macros` crate generates the `GINI_PLUGIN_ABI` and `_plugin_init`
+//! exports, which call [`plugin_init`].
+
+use std::ffi::{CString, c_void};
+use std::future::Future;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::dependency::PluginDependency;
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::traits::{
+    FfiPluginDependency, FfiPluginPriority, FfiResult, FfiSlice, FfiStageRequirement, FfiVersionRange, Plugin,
+    PluginAbiDescriptor, PluginPriority, PluginVTable, PLUGIN_CAP_PREFLIGHT_CHECK, PLUGIN_CAP_REGISTER_STAGES,
+    PLUGIN_CAP_SHUTDOWN,
+};
+use crate::stage_manager::context::StageContext;
+use crate::stage_manager::registry::StageRegistry;
+
+/// ABI descriptor for plugins exported through this module (all optional entries provided).
+pub const EXPORTED_ABI: PluginAbiDescriptor =
+    PluginAbiDescriptor::current(PLUGIN_CAP_PREFLIGHT_CHECK | PLUGIN_CAP_REGISTER_STAGES | PLUGIN_CAP_SHUTDOWN);
+
+/// Body of a generated `_plugin_init`: constructs the plugin and returns its VTable.
+/// Returns null if the constructor panics.
+pub fn plugin_init<P, F>(constructor: F) -> *mut PluginVTable
+where
+    P: Plugin + 'static,
+    F: FnOnce() -> P,
+{
+    match panic::catch_unwind(AssertUnwindSafe(|| Box::into_raw(Box::new(build_vtable(constructor()))))) {
+        Ok(vtable_ptr) => vtable_ptr,
+        Err(_) => {
+            eprintln!("Panic occurred while constructing plugin in _plugin_init");
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Builds a VTable owning `plugin`. The instance is freed by the VTable's `destroy` entry,
+/// and the boxed VTable returned by [`plugin_init`] by its `free_vtable` entry.
+pub fn build_vtable<P: Plugin + 'static>(plugin: P) -> PluginVTable {
+    PluginVTable {
+        instance: Box::into_raw(Box::new(plugin)) as *mut c_void,
+        destroy: ffi_destroy::<P>,
+        free_vtable: ffi_free_vtable,
+        name: ffi_name::<P>,
+        free_name: ffi_free_string,
+        version: ffi_version::<P>,
+        free_version: ffi_free_string,
+        is_core: ffi_is_core::<P>,
+        priority: ffi_priority::<P>,
+        compatible_api_versions: ffi_compatible_api_versions::<P>,
+        free_compatible_api_versions: ffi_free_version_ranges,
+        dependencies: ffi_dependencies::<P>,
+        free_dependencies: ffi_free_dependencies,
+        required_stages: ffi_required_stages::<P>,
+        free_required_stages: ffi_free_stage_requirements,
+        conflicts_with: ffi_conflicts_with::<P>,
+        free_conflicts_with: ffi_free_strings,
+        incompatible_with: ffi_incompatible_with::<P>,
+        free_incompatible_with: ffi_free_dependencies,
+        init: ffi_init::<P>,
+        preflight_check: Some(ffi_preflight_check::<P>),
+        register_stages: Some(ffi_register_stages::<P>),
+        shutdown: Some(ffi_shutdown::<P>),
+    }
+}
+
+// --- Helpers ---
+
+/// Runs `f`, returning `fallback` if it panics. Keeps panics from unwinding into the host.
+fn guard<R>(operation: &str, fallback: R, f: impl FnOnce() -> R) -> R {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| {
+        eprintln!("Panic in plugin FFI function '{}'", operation);
+        fallback
+    })
+}
+
+/// # Safety
+/// `instance` must be the instance pointer of a VTable built by [`build_vtable::<P>`].
+unsafe fn plugin_ref<'a, P>(instance: *const c_void) -> &'a P {
+    unsafe { &*(instance as *const P) }
+}
+
+/// Hands a string to the host. Returns null for strings with interior NUL bytes.
+fn into_c_string(value: &str) -> *const c_char {
+    CString::new(value).map(|c_str| c_str.into_raw() as *const c_char).unwrap_or(ptr::null())
+}
+
+/// # Safety
+/// `value` must be null or a pointer returned by [`into_c_string`] that was not freed yet.
+unsafe fn free_c_string(value: *const c_char) {
+    if !value.is_null() {
+        drop(unsafe { CString::from_raw(value as *mut c_char) });
+    }
+}
+
+/// Hands a vector to the host as a boxed slice (capacity == len). Empty vectors become a null slice.
+fn into_ffi_slice<T>(items: Vec<T>) -> FfiSlice<T> {
+    if items.is_empty() {
+        return FfiSlice { ptr: ptr::null(), len: 0 };
+    }
+    let boxed = items.into_boxed_slice();
+    let len = boxed.len();
+    FfiSlice { ptr: Box::into_raw(boxed) as *const T, len }
+}
+
+/// Takes back a slice produced by [`into_ffi_slice`].
+/// # Safety
+/// `slice` must be null/empty or come from [`into_ffi_slice`] and not have been freed yet.
+unsafe fn from_ffi_slice<T>(slice: FfiSlice<T>) -> Vec<T> {
+    if slice.ptr.is_null() || slice.len == 0 {
+        return Vec::new();
+    }
+    unsafe { Box::from_raw(ptr::slice_from_raw_parts_mut(slice.ptr as *mut T, slice.len)) }.into_vec()
+}
+
+fn to_ffi_dependency(dep: &PluginDependency) -> FfiPluginDependency {
+    FfiPluginDependency {
+        plugin_name: into_c_string(&dep.plugin_name),
+        version_constraint: dep.version_range.as_ref().map_or(ptr::null(), |range| into_c_string(range.constraint_string())),
+        required: dep.required,
+    }
+}
+
+fn to_ffi_priority(priority: PluginPriority) -> FfiPluginPriority {
+    let (category, value) = match priority {
+        PluginPriority::Kernel(v) => (0, v),
+        PluginPriority::CoreCritical(v) => (1, v),
+        PluginPriority::Core(v) => (2, v),
+        PluginPriority::ThirdPartyHigh(v) => (3, v),
+        PluginPriority::ThirdParty(v) => (4, v),
+        PluginPriority::ThirdPartyLow(v) => (5, v),
+    };
+    FfiPluginPriority { category, value }
+}
+
+/// Maps a lifecycle result to an `FfiResult`. The error message cannot cross the
+/// boundary, so it is printed here.
+fn lifecycle_result(plugin_name: &str, operation: &str, result: Result<(), PluginSystemError>) -> FfiResult {
+    match result {
+        Ok(()) => FfiResult::Ok,
+        Err(e) => {
+            eprintln!("Plugin '{}' {} failed: {}", plugin_name, operation, e);
+            FfiResult::Err
+        }
+    }
+}
+
+/// Drives a plugin future to completion on a dedicated thread with its own runtime,
+/// so it never blocks or nests inside the host's runtime.
+fn block_on_plugin_future<'a, T: Send>(future: impl Future<Output = T> + Send + 'a) -> Option<T> {
+    std::thread::scope(|scope| {
+        scope
+            .spawn(move || {
+                let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().ok()?;
+                Some(runtime.block_on(future))
+            })
+            .join()
+            .ok()
+            .flatten()
+    })
+}
+
+// --- VTable Entries ---
+
+extern "C" fn ffi_destroy<P: Plugin>(instance: *mut c_void) {
+    if !instance.is_null() {
+        guard("destroy", (), || drop(unsafe { Box::from_raw(instance as *mut P) }));
+    }
+}
+
+extern "C" fn ffi_free_vtable(vtable: *mut PluginVTable) {
+    if !vtable.is_null() {
+        guard("free_vtable", (), || drop(unsafe { Box::from_raw(vtable) }));
+    }
+}
+
+extern "C" fn ffi_name<P: Plugin>(instance: *const c_void) -> *const c_char {
+    guard("name", ptr::null(), || into_c_string(unsafe { plugin_ref::<P>(instance) }.name()))
+}
+
+extern "C" fn ffi_version<P: Plugin>(instance: *const c_void) -> *const c_char {
+    guard("version", ptr::null(), || into_c_string(unsafe { plugin_ref::<P>(instance) }.version()))
+}
+
+extern "C" fn ffi_free_string(value: *mut c_char) {
+    guard("free_string", (), || unsafe { free_c_string(value) });
+}
+
+extern "C" fn ffi_is_core<P: Plugin>(instance: *const c_void) -> bool {
+    guard("is_core", false, || unsafe { plugin_ref::<P>(instance) }.is_core())
+}
+
+extern "C" fn ffi_priority<P: Plugin>(instance: *const c_void) -> FfiPluginPriority {
+    // An invalid category makes the host reject the plugin
+    let invalid = FfiPluginPriority { category: u8::MAX, value: 0 };
+    guard("priority", invalid, || to_ffi_priority(unsafe { plugin_ref::<P>(instance) }.priority()))
+}
+
+extern "C" fn ffi_compatible_api_versions<P: Plugin>(instance: *const c_void) -> FfiSlice<FfiVersionRange> {
+    guard("compatible_api_versions", FfiSlice { ptr: ptr::null(), len: 0 }, || {
+        let ranges = unsafe { plugin_ref::<P>(instance) }.compatible_api_versions();
+        into_ffi_slice(
+            ranges
+                .iter()
+                .map(|range| FfiVersionRange { constraint: into_c_string(range.constraint_string()) })
+                .collect(),
+        )
+    })
+}
+
+extern "C" fn ffi_free_version_ranges(slice: FfiSlice<FfiVersionRange>) {
+    guard("free_compatible_api_versions", (), || {
+        for range in unsafe { from_ffi_slice(slice) } {
+            unsafe { free_c_string(range.constraint) };
+        }
+    });
+}
+
+extern "C" fn ffi_dependencies<P: Plugin>(instance: *const c_void) -> FfiSlice<FfiPluginDependency> {
+    guard("dependencies", FfiSlice { ptr: ptr::null(), len: 0 }, || {
+        let deps = unsafe { plugin_ref::<P>(instance) }.dependencies();
+        into_ffi_slice(deps.iter().map(to_ffi_dependency).collect())
+    })
+}
+
+extern "C" fn ffi_incompatible_with<P: Plugin>(instance: *const c_void) -> FfiSlice<FfiPluginDependency> {
+    guard("incompatible_with", FfiSlice { ptr: ptr::null(), len: 0 }, || {
+        let deps = unsafe { plugin_ref::<P>(instance) }.incompatible_with();
+        into_ffi_slice(deps.iter().map(to_ffi_dependency).collect())
+    })
+}
+
+extern "C" fn ffi_free_dependencies(slice: FfiSlice<FfiPluginDependency>) {
+    guard("free_dependencies", (), || {
+        for dep in unsafe { from_ffi_slice(slice) } {
+            unsafe {
+                free_c_string(dep.plugin_name);
+                free_c_string(dep.version_constraint);
+            }
+        }
+    });
+}
+
+extern "C" fn ffi_required_stages<P: Plugin>(instance: *const c_void) -> FfiSlice<FfiStageRequirement> {
+    guard("required_stages", FfiSlice { ptr: ptr::null(), len: 0 }, || {
+        let requirements = unsafe { plugin_ref::<P>(instance) }.required_stages();
+        into_ffi_slice(
+            requirements
+                .iter()
+                .map(|req| FfiStageRequirement {
+                    stage_id: into_c_string(&req.stage_id),
+                    required: req.required,
+                    provided: req.provided,
+                })
+                .collect(),
+        )
+    })
+}
+
+extern "C" fn ffi_free_stage_requirements(slice: FfiSlice<FfiStageRequirement>) {
+    guard("free_required_stages", (), || {
+        for req in unsafe { from_ffi_slice(slice) } {
+            unsafe { free_c_string(req.stage_id) };
+        }
+    });
+}
+
+extern "C" fn ffi_conflicts_with<P: Plugin>(instance: *const c_void) -> FfiSlice<*const c_char> {
+    guard("conflicts_with", FfiSlice { ptr: ptr::null(), len: 0 }, || {
+        let conflicts = unsafe { plugin_ref::<P>(instance) }.conflicts_with();
+        into_ffi_slice(conflicts.iter().map(|id| into_c_string(id)).collect())
+    })
+}
+
+extern "C" fn ffi_free_strings(slice: FfiSlice<*const c_char>) {
+    guard("free_conflicts_with", (), || {
+        for value in unsafe { from_ffi_slice(slice) } {
+            unsafe { free_c_string(value) };
+        }
+    });
+}
+
+extern "C" fn ffi_init<P: Plugin>(instance: *mut c_void, app_ptr: *mut c_void) -> FfiResult {
+    if app_ptr.is_null() {
+        return FfiResult::NullPointer;
+    }
+    guard("init", FfiResult::Err, || {
+        let plugin = unsafe { plugin_ref::<P>(instance) };
+        let app = unsafe { &mut *(app_ptr as *mut Application) };
+        lifecycle_result(plugin.name(), "init", plugin.init(app))
+    })
+}
+
+extern "C" fn ffi_preflight_check<P: Plugin>(instance: *const c_void, context_ptr: *const c_void) -> FfiResult {
+    if context_ptr.is_null() {
+        return FfiResult::NullPointer;
+    }
+    guard("preflight_check", FfiResult::Err, || {
+        let plugin = unsafe { plugin_ref::<P>(instance) };
+        let context = unsafe { &*(context_ptr as *const StageContext) };
+        match block_on_plugin_future(plugin.preflight_check(context)) {
+            Some(result) => lifecycle_result(plugin.name(), "preflight_check", result),
+            None => {
+                eprintln!("Plugin '{}' preflight_check panicked or could not start a runtime", plugin.name());
+                FfiResult::Err
+            }
+        }
+    })
+}
+
+extern "C" fn ffi_register_stages<P: Plugin>(instance: *const c_void, registry_ptr: *mut c_void) -> FfiResult {
+    if registry_ptr.is_null() {
+        return FfiResult::NullPointer;
+    }
+    guard("register_stages", FfiResult::Err, || {
+        let plugin = unsafe { plugin_ref::<P>(instance) };
+        let registry = unsafe { &mut *(registry_ptr as *mut StageRegistry) };
+        lifecycle_result(plugin.name(), "register_stages", plugin.register_stages(registry))
+    })
+}
+
+extern "C" fn ffi_shutdown<P: Plugin>(instance: *mut c_void) -> FfiResult {
+    guard("shutdown", FfiResult::Err, || {
+        let plugin = unsafe { plugin_ref::<P>(instance) };
+        lifecycle_result(plugin.name(), "shutdown", plugin.shutdown())
+    })
+}
@@ -0,0 +1,662 @@
+//! Host side of the dynamic plugin FFI boundary.
+//!
+//! Every dynamically loaded plugin goes through [`load_plugin_library`], which opens
+//! the shared library, calls its `_plugin_init` symbol and wraps the returned
+//! [`PluginVTable`] in a [`Plugin`] implementation.
+//!
+//! ## ABI handshake
+//!
+//! Before `_plugin_init` is called, the host reads the plugin's exported
+//! `GINI_PLUGIN_ABI` static ([`PluginAbiDescriptor`]). The plugin is rejected with
+//! [`PluginSystemError::AbiMismatch`] if the descriptor is missing, if its ABI version
+//! differs from [`PLUGIN_ABI_VERSION`], or if its VTable is smaller than the required
+//! entries. Optional VTable entries are only used when their capability bit is set and
+//! they lie within the reported VTable size, so plugins built before an optional entry
+//! was added keep working.
+//!
+//! ## Ownership and freeing contract
+//!
+//! - `_plugin_init` returns a `PluginVTable` allocated by the plugin. The host keeps it
+//!   until the plugin is dropped, then calls `destroy(instance)` and `free_vtable(vtable)`
+//!   exactly once each, in that order, and only then closes the library. No other VTable
+//!   function is called after `destroy`. The host never frees plugin memory itself.
+//! - Every string or slice returned by a getter (`name`, `version`,
+//!   `compatible_api_versions`, `dependencies`, `required_stages`, `conflicts_with`,
+//!   `incompatible_with`) stays owned by the plugin. The host copies the data and hands
+//!   the original back to the matching `free_*` function exactly once, including when
+//!   the value is null/empty or could not be converted. `free_*` functions must
+//!   therefore accept null pointers and empty slices.
+//! - Pointers passed into the plugin (`app_ptr`, `context_ptr`, `registry_ptr`) are
+//!   borrowed for the duration of that call only.
+//! - Lifecycle calls (`init`, `preflight_check`, `register_stages`, `shutdown`) are
+//!   guarded with `catch_unwind`, but plugins should not let panics unwind across the
+//!   boundary and should return an `FfiResult` error instead.
+
+use std::any::Any;
+use std::ffi::{CStr, c_void};
+use std::future::Future;
+use std::mem::{self, MaybeUninit};
+use std::os::raw::c_char;
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::ptr;
+
+use libloading::{Library, Symbol};
+
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::dependency::PluginDependency;
+use crate::plugin_system::error::{PluginSystemError, PluginSystemErrorSource};
+use crate::plugin_system::traits::{
+    FfiPluginDependency, FfiResult, FfiSlice, FfiStageRequirement, FfiVersionRange, Plugin, PluginAbiDescriptor,
+    PluginPriority, PluginVTable, PLUGIN_ABI_VERSION, PLUGIN_CAP_PREFLIGHT_CHECK, PLUGIN_CAP_REGISTER_STAGES,
+    PLUGIN_CAP_SHUTDOWN, PLUGIN_KNOWN_CAPABILITIES, PLUGIN_VTABLE_REQUIRED_SIZE,
+};
+use crate::plugin_system::version::VersionRange;
+use crate::stage_manager::context::StageContext;
+use crate::stage_manager::registry::StageRegistry;
+use crate::stage_manager::requirement::StageRequirement;
+
+/// Name of the symbol every dynamic plugin must export.
+pub const PLUGIN_INIT_SYMBOL: &[u8] = b"_plugin_init\0";
+
+/// Name of the ABI descriptor static every dynamic plugin must export.
+pub const PLUGIN_ABI_SYMBOL: &[u8] = b"GINI_PLUGIN_ABI\0";
+
+/// Signature of the `_plugin_init` symbol.
+/// `C-unwind` so a panicking init can be caught instead of aborting the host.
+pub type PluginInitFn = unsafe extern "C-unwind" fn() -> *mut PluginVTable;
+
+/// Placeholder used in error messages until the plugin name is known.
+const UNKNOWN_PLUGIN_NAME: &str = "<unknown>";
+
+// --- FFI Helper Functions ---
+
+/// Maps FfiResult to PluginSystemError
+pub(crate) fn map_ffi_error(ffi_err: FfiResult, plugin_id: &str, operation: &str) -> PluginSystemError {
+    PluginSystemError::FfiError {
+        plugin_id: plugin_id.to_string(),
+        operation: operation.to_string(),
+        message: format!("{:?}", ffi_err),
+    }
+}
+
+/// Safely converts an FFI C string pointer to a Rust String.
+/// # Safety
+/// The caller must ensure that `ptr` is a valid pointer to a null-terminated
+/// C string, and that it remains valid for the duration of this function call.
+/// The string data is expected to be UTF-8 encoded.
+pub(crate) unsafe fn ffi_string_from_ptr(ptr: *const c_char) -> std::result::Result<String, FfiResult> {
+    if ptr.is_null() {
+        return Err(FfiResult::NullPointer);
+    }
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .map(|s| s.to_owned())
+        .map_err(|_| FfiResult::Utf8Error)
+}
+
+/// Safely converts an FFI C string pointer (which can be null) to an Option<String>.
+/// # Safety
+/// The caller must ensure that if `ptr` is non-null, it is a valid pointer to a
+/// null-terminated C string, and that it remains valid for the duration of this
+/// function call. The string data is expected to be UTF-8 encoded.
+pub(crate) unsafe fn ffi_opt_string_from_ptr(ptr: *const c_char) -> std::result::Result<Option<String>, FfiResult> {
+    if ptr.is_null() {
+        Ok(None)
+    } else {
+        unsafe { ffi_string_from_ptr(ptr) }.map(Some)
+    }
+}
+
+/// Extracts a readable message from a caught panic payload.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s_ref) = payload.downcast_ref::<&'static str>() {
+        (*s_ref).to_string()
+    } else if let Some(s_obj) = payload.downcast_ref::<String>() {
+        s_obj.clone()
+    } else {
+        "Unknown panic reason".to_string()
+    }
+}
+
+/// Converts an FFI dependency entry (used by `dependencies` and `incompatible_with`).
+/// # Safety
+/// The string pointers in `ffi_dep` must follow the contract of [`ffi_string_from_ptr`].
+unsafe fn plugin_dependency_from_ffi(
+    ffi_dep: FfiPluginDependency,
+    plugin_name: &str,
+    operation: &str,
+) -> std::result::Result<PluginDependency, PluginSystemError> {
+    let name = unsafe { ffi_string_from_ptr(ffi_dep.plugin_name) }
+        .map_err(|e| map_ffi_error(e, plugin_name, &format!("getting {} name", operation)))?;
+    let version_constraint = unsafe { ffi_opt_string_from_ptr(ffi_dep.version_constraint) }
+        .map_err(|e| map_ffi_error(e, plugin_name, &format!("getting {} version constraint", operation)))?;
+    let version_range = version_constraint
+        .map(|s| VersionRange::from_constraint(s.as_str()))
+        .transpose()
+        .map_err(PluginSystemError::from)?;
+    Ok(PluginDependency {
+        plugin_name: name,
+        version_range,
+        required: ffi_dep.required,
+    })
+}
+
+// --- ABI Handshake ---
+
+/// Validates a plugin's ABI descriptor against this host.
+/// Unknown capability bits (from a newer plugin) are ignored.
+pub fn check_abi_descriptor(
+    descriptor: &PluginAbiDescriptor,
+    plugin_id: &str,
+    path: Option<&Path>,
+) -> std::result::Result<(), PluginSystemError> {
+    let mismatch = |message: String| PluginSystemError::AbiMismatch {
+        plugin_id: plugin_id.to_string(),
+        path: path.map(Path::to_path_buf),
+        message,
+    };
+
+    if descriptor.abi_version != PLUGIN_ABI_VERSION {
+        return Err(mismatch(format!(
+            "plugin was built for ABI version {}, but this host supports ABI version {}; rebuild the plugin against a matching gini-core",
+            descriptor.abi_version, PLUGIN_ABI_VERSION
+        )));
+    }
+    if descriptor.vtable_size < PLUGIN_VTABLE_REQUIRED_SIZE {
+        return Err(mismatch(format!(
+            "plugin VTable size {} is smaller than the {} bytes of required entries",
+            descriptor.vtable_size, PLUGIN_VTABLE_REQUIRED_SIZE
+        )));
+    }
+    if !descriptor.vtable_size.is_multiple_of(mem::align_of::<PluginVTable>()) {
+        return Err(mismatch(format!(
+            "plugin VTable size {} is not a multiple of the VTable alignment ({})",
+            descriptor.vtable_size,
+            mem::align_of::<PluginVTable>()
+        )));
+    }
+
+    let unknown_capabilities = descriptor.capabilities & !PLUGIN_KNOWN_CAPABILITIES;
+    if unknown_capabilities != 0 {
+        println!(
+            "[GINI_FFI_DEBUG] Plugin '{}' declares capabilities unknown to this host ({:#x}); ignoring them.",
+            plugin_id, unknown_capabilities
+        );
+    }
+    Ok(())
+}
+
+/// Whether an optional VTable entry can be used: its capability bit is set and the
+/// entry lies within the VTable size the plugin reported.
+fn optional_entry_available(descriptor: &PluginAbiDescriptor, capability: u64, entry_offset: usize) -> bool {
+    descriptor.has_capability(capability) && entry_offset + mem::size_of::<usize>() <= descriptor.vtable_size
+}
+
+/// Copies a plugin's VTable into a host-owned `PluginVTable`.
+/// Only the bytes the plugin reported are read; optional entries it does not provide
+/// (or whose capability bit is not set) are `None`.
+/// # Safety
+/// `vtable_ptr` must point to at least `descriptor.vtable_size` readable bytes, and the
+/// descriptor must have passed [`check_abi_descriptor`].
+unsafe fn copy_vtable(vtable_ptr: *const PluginVTable, descriptor: &PluginAbiDescriptor) -> PluginVTable {
+    let copy_len = descriptor.vtable_size.min(mem::size_of::<PluginVTable>());
+    let mut vtable = MaybeUninit::<PluginVTable>::zeroed();
+    // All entries past the required prefix are `Option`s, for which all-zero is `None`
+    let mut vtable = unsafe {
+        ptr::copy_nonoverlapping(vtable_ptr as *const u8, vtable.as_mut_ptr() as *mut u8, copy_len);
+        vtable.assume_init()
+    };
+
+    if !optional_entry_available(descriptor, PLUGIN_CAP_PREFLIGHT_CHECK, mem::offset_of!(PluginVTable, preflight_check)) {
+        vtable.preflight_check = None;
+    }
+    if !optional_entry_available(descriptor, PLUGIN_CAP_REGISTER_STAGES, mem::offset_of!(PluginVTable, register_stages)) {
+        vtable.register_stages = None;
+    }
+    if !optional_entry_available(descriptor, PLUGIN_CAP_SHUTDOWN, mem::offset_of!(PluginVTable, shutdown)) {
+        vtable.shutdown = None;
+    }
+    vtable
+}
+
+/// Checks that every optional entry the plugin declared a capability for is present.
+fn check_declared_entries(
+    vtable: &PluginVTable,
+    descriptor: &PluginAbiDescriptor,
+    plugin_id: &str,
+    path: Option<&Path>,
+) -> std::result::Result<(), PluginSystemError> {
+    let declared = [
+        (PLUGIN_CAP_PREFLIGHT_CHECK, "preflight_check", vtable.preflight_check.is_some()),
+        (PLUGIN_CAP_REGISTER_STAGES, "register_stages", vtable.register_stages.is_some()),
+        (PLUGIN_CAP_SHUTDOWN, "shutdown", vtable.shutdown.is_some()),
+    ];
+    for (capability, entry, present) in declared {
+        if descriptor.has_capability(capability) && !present {
+            return Err(PluginSystemError::AbiMismatch {
+                plugin_id: plugin_id.to_string(),
+                path: path.map(Path::to_path_buf),
+                message: format!("plugin declares the '{}' capability but its VTable entry is missing", entry),
+            });
+        }
+    }
+    Ok(())
+}
+
+// --- Library Loading ---
+
+/// Loads a plugin from a shared library.
+///
+/// Opens the library, calls its `_plugin_init` symbol and wraps the returned VTable.
+/// The library stays loaded for as long as the returned plugin is alive.
+pub fn load_plugin_library(path: &Path) -> std::result::Result<Box<dyn Plugin>, PluginSystemError> {
+    let path_id = path.to_string_lossy().into_owned();
+
+    let library = unsafe { Library::new(path) }.map_err(|e| PluginSystemError::LoadingError {
+        plugin_id: path_id.clone(),
+        path: Some(path.to_path_buf()),
+        source: Box::new(PluginSystemErrorSource::Other(format!("libloading error: {}", e))),
+    })?;
+
+    let init_fn: PluginInitFn = {
+        let init_symbol: Symbol<PluginInitFn> = unsafe { library.get(PLUGIN_INIT_SYMBOL) }.map_err(|e| {
+            PluginSystemError::LoadingError {
+                plugin_id: path_id.clone(),
+                path: Some(path.to_path_buf()),
+                source: Box::new(PluginSystemErrorSource::Other(format!("missing symbol _plugin_init: {}", e))),
+            }
+        })?;
+        *init_symbol
+    };
+
+    // Read and check the ABI descriptor before anything touches the VTable
+    let abi_descriptor: PluginAbiDescriptor = {
+        let abi_symbol: Symbol<*const PluginAbiDescriptor> = unsafe { library.get(PLUGIN_ABI_SYMBOL) }.map_err(|e| {
+            PluginSystemError::AbiMismatch {
+                plugin_id: path_id.clone(),
+                path: Some(path.to_path_buf()),
+                message: format!(
+                    "missing ABI descriptor symbol GINI_PLUGIN_ABI ({}); the plugin was built against an older gini-core",
+                    e
+                ),
+            }
+        })?;
+        unsafe { ptr::read_unaligned(*abi_symbol) }
+    };
+    check_abi_descriptor(&abi_descriptor, &path_id, Some(path))?;
+
+    println!("[GINI_FFI_DEBUG] Calling _plugin_init for {:?}", path);
+    let vtable_ptr = match panic::catch_unwind(|| unsafe { init_fn() }) {
+        Ok(ptr) if !ptr.is_null() => ptr,
+        Ok(_) => {
+            return Err(PluginSystemError::LoadingError {
+                plugin_id: path_id,
+                path: Some(path.to_path_buf()),
+                source: Box::new(PluginSystemErrorSource::Other(
+                    "Plugin init returned null VTable pointer.".to_string(),
+                )),
+            });
+        }
+        Err(panic_obj) => {
+            let panic_msg = panic_message(panic_obj.as_ref());
+            eprintln!("[GINI_FFI_DEBUG] _plugin_init for {:?} panicked: {}", path, panic_msg);
+            return Err(PluginSystemError::FfiError {
+                plugin_id: path_id,
+                operation: "_plugin_init".to_string(),
+                message: format!("panic: {}", panic_msg),
+            });
+        }
+    };
+
+    let plugin_wrapper = unsafe { VTablePluginWrapper::new(vtable_ptr, abi_descriptor, library, Some(path.to_path_buf()))? };
+    Ok(Box::new(plugin_wrapper))
+}
+
+// --- VTablePluginWrapper ---
+
+#[derive(Debug, Clone, Copy)]
+struct UnsafeVTablePtr(*mut PluginVTable);
+unsafe impl Send for UnsafeVTablePtr {}
+unsafe impl Sync for UnsafeVTablePtr {}
+
+/// Host-owned copy of a plugin's VTable (see [`copy_vtable`]).
+struct HostVTable(PluginVTable);
+unsafe impl Send for HostVTable {}
+unsafe impl Sync for HostVTable {}
+
+impl std::fmt::Debug for HostVTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HostVTable").field("instance", &self.0.instance).finish_non_exhaustive()
+    }
+}
+
+/// [`Plugin`] implementation backed by a plugin's VTable.
+/// Owns the VTable and the library it came from (see the module docs for the contract).
+#[derive(Debug)]
+pub(crate) struct VTablePluginWrapper {
+    vtable: HostVTable, // Entries the host calls
+    raw_vtable: UnsafeVTablePtr, // Plugin allocation, handed back to `free_vtable` on drop
+    abi: PluginAbiDescriptor,
+    library: Option<Library>, // Closed after the instance and VTable are destroyed
+    plugin_path: Option<PathBuf>, // Library path, for error context
+    name_cache: String,
+    version_cache: String,
+    is_core_cache: bool,
+    priority_cache: PluginPriority,
+}
+
+impl VTablePluginWrapper {
+    /// Creates a new wrapper from a VTable pointer and the loaded Library.
+    /// Takes ownership of both; if reading the plugin metadata fails, the instance
+    /// and VTable are destroyed and the library is closed before the error is returned.
+    /// # Safety
+    /// The `vtable_ptr` must be a non-dangling pointer to a `PluginVTable` allocated by the plugin
+    /// (via `Box::into_raw`) and returned by its `_plugin_init` function, `abi` must be the
+    /// plugin's descriptor (already checked with [`check_abi_descriptor`]), and `library`
+    /// must be the library they were obtained from.
+    pub(crate) unsafe fn new(
+        vtable_ptr: *mut PluginVTable,
+        abi: PluginAbiDescriptor,
+        library: Library,
+        plugin_path: Option<PathBuf>,
+    ) -> std::result::Result<Self, PluginSystemError> {
+        if vtable_ptr.is_null() {
+            drop(library);
+            return Err(PluginSystemError::LoadingError {
+                plugin_id: plugin_path.as_ref().map(|p| p.to_string_lossy().into_owned()).unwrap_or_else(|| UNKNOWN_PLUGIN_NAME.to_string()),
+                path: plugin_path,
+                source: Box::new(PluginSystemErrorSource::Other(
+                    "Received null VTable pointer from plugin init.".to_string(),
+                )),
+            });
+        }
+
+        // Build the wrapper first so Drop cleans up if any check or metadata call fails
+        let mut wrapper = Self {
+            vtable: HostVTable(unsafe { copy_vtable(vtable_ptr, &abi) }),
+            raw_vtable: UnsafeVTablePtr(vtable_ptr),
+            abi,
+            library: Some(library),
+            plugin_path,
+            name_cache: UNKNOWN_PLUGIN_NAME.to_string(),
+            version_cache: String::new(),
+            is_core_cache: false,
+            priority_cache: PluginPriority::ThirdPartyLow(u8::MAX),
+        };
+        check_declared_entries(&wrapper.vtable.0, &wrapper.abi, &wrapper.error_id(), wrapper.plugin_path.as_deref())?;
+
+        let (name_fn, free_name_fn) = (wrapper.vtable.0.name, wrapper.vtable.0.free_name);
+        let name = unsafe { wrapper.take_ffi_string(name_fn, free_name_fn, "getting plugin name")? };
+        wrapper.name_cache = name;
+
+        let (version_fn, free_version_fn) = (wrapper.vtable.0.version, wrapper.vtable.0.free_version);
+        wrapper.version_cache = unsafe { wrapper.take_ffi_string(version_fn, free_version_fn, "getting plugin version")? };
+        wrapper.is_core_cache = (wrapper.vtable.0.is_core)(wrapper.instance());
+
+        let ffi_priority = (wrapper.vtable.0.priority)(wrapper.instance());
+        wrapper.priority_cache = ffi_priority.to_plugin_priority().ok_or_else(|| PluginSystemError::LoadingError {
+            plugin_id: wrapper.name_cache.clone(),
+            path: wrapper.plugin_path.clone(),
+            source: Box::new(PluginSystemErrorSource::Other(format!(
+                "Invalid FFI priority value received: {:?}",
+                ffi_priority
+            ))),
+        })?;
+
+        println!(
+            "[GINI_FFI_DEBUG] Loaded plugin '{}' version {} (ABI v{}, capabilities {:#x})",
+            wrapper.name_cache, wrapper.version_cache, wrapper.abi.abi_version, wrapper.abi.capabilities
+        );
+        Ok(wrapper)
+    }
+
+    fn instance(&self) -> *const c_void {
+        self.vtable.0.instance as *const c_void
+    }
+
+    /// ID used in error messages: the plugin name once known, otherwise the library path.
+    fn error_id(&self) -> String {
+        if self.name_cache != UNKNOWN_PLUGIN_NAME {
+            return self.name_cache.clone();
+        }
+        self.plugin_path
+            .as_ref()
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|| UNKNOWN_PLUGIN_NAME.to_string())
+    }
+
+    /// Calls a string getter, copies the result and hands the pointer back to its free function.
+    unsafe fn take_ffi_string(
+        &self,
+        get_fn: extern "C" fn(instance: *const c_void) -> *const c_char,
+        free_fn: extern "C" fn(ptr: *mut c_char),
+        operation: &str,
+    ) -> std::result::Result<String, PluginSystemError> {
+        let ptr = (get_fn)(self.instance());
+        let result = unsafe { ffi_string_from_ptr(ptr) }.map_err(|e| map_ffi_error(e, &self.error_id(), operation));
+        (free_fn)(ptr as *mut c_char);
+        result
+    }
+
+    /// Calls a slice getter, converts every element and hands the slice back to its free function.
+    unsafe fn get_vector_from_ffi_slice<T, R, F>(
+        &self,
+        get_slice_fn: extern "C" fn(instance: *const c_void) -> FfiSlice<T>,
+        free_slice_fn: extern "C" fn(slice: FfiSlice<T>),
+        converter: F,
+    ) -> std::result::Result<Vec<R>, PluginSystemError>
+    where
+        T: Copy,
+        F: Fn(T) -> std::result::Result<R, PluginSystemError>,
+    {
+        let ffi_slice = (get_slice_fn)(self.instance());
+        let result = unsafe { ffi_slice.as_slice() }.map_or_else(
+            || Ok(Vec::new()),
+            |slice_data| {
+                slice_data.iter()
+                    .map(|&item| converter(item))
+                    .collect::<std::result::Result<Vec<R>, PluginSystemError>>()
+            },
+        );
+        (free_slice_fn)(ffi_slice);
+        result
+    }
+
+    /// Runs a lifecycle call behind `catch_unwind`, mapping errors and panics to `FfiError`.
+    fn call_lifecycle<F>(&self, operation: &str, call: F) -> std::result::Result<(), PluginSystemError>
+    where
+        F: FnOnce(&PluginVTable) -> FfiResult,
+    {
+        let vtable = &self.vtable.0;
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(move || call(vtable)));
+        match result {
+            Ok(FfiResult::Ok) => Ok(()),
+            Ok(ffi_res) => Err(map_ffi_error(ffi_res, &self.name_cache, operation)),
+            Err(panic_obj) => {
+                let panic_msg = panic_message(panic_obj.as_ref());
+                eprintln!("[GINI_FFI_DEBUG] Panic in FFI '{}' for plugin '{}': {}", operation, self.name_cache, panic_msg);
+                Err(PluginSystemError::FfiError {
+                    plugin_id: self.name_cache.clone(),
+                    operation: operation.to_string(),
+                    message: format!("panic: {}", panic_msg),
+                })
+            }
+        }
+    }
+}
+
+impl Drop for VTablePluginWrapper {
+    fn drop(&mut self) {
+        println!("[GINI_FFI_DEBUG] Dropping VTablePluginWrapper for '{}'", self.name_cache);
+        if !self.raw_vtable.0.is_null() {
+            (self.vtable.0.destroy)(self.vtable.0.instance);
+            // The plugin allocated the VTable, so it frees it too
+            (self.vtable.0.free_vtable)(self.raw_vtable.0);
+            self.raw_vtable.0 = ptr::null_mut();
+        }
+        // Close the library last; the functions above live in it
+        drop(self.library.take());
+    }
+}
+
+impl Plugin for VTablePluginWrapper {
+    fn name(&self) -> &str {
+        &self.name_cache
+    }
+
+    fn version(&self) -> &str {
+        &self.version_cache
+    }
+
+    fn is_core(&self) -> bool {
+        self.is_core_cache
+    }
+
+    fn priority(&self) -> PluginPriority {
+        self.priority_cache.clone()
+    }
+
+    fn compatible_api_versions(&self) -> Vec<VersionRange> {
+        unsafe {
+            self.get_vector_from_ffi_slice(
+                self.vtable.0.compatible_api_versions,
+                self.vtable.0.free_compatible_api_versions,
+                |ffi_range: FfiVersionRange| {
+                    let constraint = ffi_string_from_ptr(ffi_range.constraint)
+                        .map_err(|e| map_ffi_error(e, &self.name_cache, "getting compatible_api_versions constraint"))?;
+                    VersionRange::from_constraint(&constraint).map_err(PluginSystemError::from)
+                },
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("Error getting compatible API versions for plugin '{}': {}", self.name_cache, e);
+                Vec::new()
+            })
+        }
+    }
+
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        unsafe {
+            self.get_vector_from_ffi_slice(
+                self.vtable.0.dependencies,
+                self.vtable.0.free_dependencies,
+                |ffi_dep: FfiPluginDependency| plugin_dependency_from_ffi(ffi_dep, &self.name_cache, "dependency"),
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("Error getting dependencies for plugin '{}': {}", self.name_cache, e);
+                Vec::new()
+            })
+        }
+    }
+
+    fn required_stages(&self) -> Vec<StageRequirement> {
+        unsafe {
+            self.get_vector_from_ffi_slice(
+                self.vtable.0.required_stages,
+                self.vtable.0.free_required_stages,
+                |ffi_req: FfiStageRequirement| {
+                    let id = ffi_string_from_ptr(ffi_req.stage_id)
+                        .map_err(|e| map_ffi_error(e, &self.name_cache, "getting required_stages id"))?;
+                    Ok(StageRequirement {
+                        stage_id: id,
+                        required: ffi_req.required,
+                        provided: ffi_req.provided,
+                    })
+                },
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("Error getting required stages for plugin '{}': {}", self.name_cache, e);
+                Vec::new()
+            })
+        }
+    }
+
+    fn conflicts_with(&self) -> Vec<String> {
+        unsafe {
+            self.get_vector_from_ffi_slice(
+                self.vtable.0.conflicts_with,
+                self.vtable.0.free_conflicts_with,
+                |ffi_str_ptr: *const c_char| {
+                    ffi_string_from_ptr(ffi_str_ptr)
+                        .map_err(|e| map_ffi_error(e, &self.name_cache, "getting conflicts_with string"))
+                },
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("Error getting conflicts_with for plugin '{}': {}", self.name_cache, e);
+                Vec::new()
+            })
+        }
+    }
+
+    fn incompatible_with(&self) -> Vec<PluginDependency> {
+        unsafe {
+            self.get_vector_from_ffi_slice(
+                self.vtable.0.incompatible_with,
+                self.vtable.0.free_incompatible_with,
+                |ffi_dep: FfiPluginDependency| plugin_dependency_from_ffi(ffi_dep, &self.name_cache, "incompatible_with"),
+            )
+            .unwrap_or_else(|e| {
+                eprintln!("Error getting incompatible_with for plugin '{}': {}", self.name_cache, e);
+                Vec::new()
+            })
+        }
+    }
+
+    fn init(&self, app: &mut Application) -> std::result::Result<(), PluginSystemError> {
+        let app_ptr = app as *mut _ as *mut c_void;
+        self.call_lifecycle("init", |vtable| (vtable.init)(vtable.instance, app_ptr))
+    }
+
+    fn preflight_check<'life0, 'life1, 'async_trait>(
+        &'life0 self,
+        context: &'life1 StageContext
+    ) -> Pin<Box<dyn Future<Output = std::result::Result<(), PluginSystemError>> + Send + 'async_trait>>
+    where
+        'life0: 'async_trait,
+        'life1: 'async_trait,
+        Self: 'async_trait,
+    {
+        // The FFI call is synchronous; run it now and hand back a ready future
+        let context_ptr = context as *const _ as *const c_void;
+        let result = match self.vtable.0.preflight_check {
+            Some(preflight_fn) => self.call_lifecycle("preflight_check", |vtable| preflight_fn(vtable.instance, context_ptr)),
+            None => Ok(()), // Optional entry not provided
+        };
+        let result = result
+            .map_err(|e| {
+                let message = match e {
+                    PluginSystemError::FfiError { message, .. } if message.starts_with("panic: ") => {
+                        format!("FFI panic: {}", message.trim_start_matches("panic: "))
+                    }
+                    PluginSystemError::FfiError { message, .. } => format!("FFI Error: {}", message),
+                    other => other.to_string(),
+                };
+                PluginSystemError::PreflightCheckFailed {
+                    plugin_id: self.name_cache.clone(),
+                    message,
+                }
+            });
+        Box::pin(async move { result })
+    }
+
+    fn register_stages(&self, registry: &mut StageRegistry) -> std::result::Result<(), PluginSystemError> {
+        let registry_ptr = registry as *mut _ as *mut c_void;
+        match self.vtable.0.register_stages {
+            Some(register_fn) => self.call_lifecycle("register_stages", |vtable| register_fn(vtable.instance, registry_ptr)),
+            None => Ok(()), // Plugin provides no stages
+        }
+    }
+
+    fn shutdown(&self) -> std::result::Result<(), PluginSystemError> {
+        let Some(shutdown_fn) = self.vtable.0.shutdown else {
+            return Ok(()); // Optional entry not provided
+        };
+        let result = self.call_lifecycle("shutdown", |vtable| shutdown_fn(vtable.instance));
+        match &result {
+            Ok(()) => println!("Plugin '{}' FFI shutdown method executed successfully.", self.name_cache),
+            Err(e) => eprintln!("Error during FFI shutdown for plugin '{}': {}", self.name_cache, e),
+        }
+        result
+    }
+}
@@ -0,0 +1,250 @@
+//! # Plugin Host IPC Protocol
+//!
+//! Wire protocol between the core and an out-of-process plugin host (see
+//! [`sandbox`](crate::plugin_system::sandbox)).
+//!
+//! Every message is a frame of a 4-byte little-endian opcode, a 4-byte little-endian
+//! payload length and a UTF-8 JSON payload, the same framing the Discord IPC client in
+//! `core-rpc` uses. The core sends [`HostRequest`]s with [`OP_REQUEST`] and the plugin
+//! host answers each one with exactly one [`HostResponse`] using [`OP_RESPONSE`].
+//! Plugin metadata crosses the boundary as plain strings, like the FFI types in
+//! [`traits`](crate::plugin_system::traits).
+
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::plugin_system::dependency::PluginDependency;
+use crate::plugin_system::version::VersionRange;
+use crate::stage_manager::context::{ExecutionMode, StageContext};
+use crate::stage_manager::requirement::StageRequirement;
+
+/// Version of the request/response protocol, checked when the host describes its plugin
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Opcode of frames sent by the core to the plugin host
+pub const OP_REQUEST: u32 = 1;
+
+/// Opcode of frames sent by the plugin host to the core
+pub const OP_RESPONSE: u32 = 2;
+
+/// Largest payload accepted by [`read_framed_message`], protects against corrupted length headers
+pub const MAX_FRAME_PAYLOAD: u32 = 16 * 1024 * 1024;
+
+/// Packs the opcode, payload length, and JSON payload into a byte vector.
+/// Opcode: 4 bytes, little-endian
+/// Length: 4 bytes, little-endian (length of the JSON string payload)
+/// Payload: JSON string (UTF-8 encoded)
+pub fn frame_message(opcode: u32, payload_json: &str) -> io::Result<Vec<u8>> {
+    let payload_bytes = payload_json.as_bytes();
+    let payload_len = u32::try_from(payload_bytes.len())
+        .ok()
+        .filter(|len| *len <= MAX_FRAME_PAYLOAD)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("Payload of {} bytes is too large to frame", payload_bytes.len())))?;
+
+    let mut frame = Vec::with_capacity(8 + payload_bytes.len());
+    frame.extend_from_slice(&opcode.to_le_bytes());
+    frame.extend_from_slice(&payload_len.to_le_bytes());
+    frame.extend_from_slice(payload_bytes);
+    Ok(frame)
+}
+
+/// Reads a framed message from a blocking reader.
+/// Returns the opcode and the JSON payload string.
+pub fn read_framed_message<R: Read>(reader: &mut R) -> io::Result<(u32, String)> {
+    let mut header_buf = [0u8; 8]; // 4 bytes for opcode, 4 bytes for length
+    reader.read_exact(&mut header_buf)?;
+
+    let opcode = u32::from_le_bytes([header_buf[0], header_buf[1], header_buf[2], header_buf[3]]);
+    let length = u32::from_le_bytes([header_buf[4], header_buf[5], header_buf[6], header_buf[7]]);
+    if length > MAX_FRAME_PAYLOAD {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Frame payload of {} bytes exceeds the limit of {} bytes", length, MAX_FRAME_PAYLOAD)));
+    }
+
+    let mut payload_buf = vec![0u8; length as usize];
+    reader.read_exact(&mut payload_buf)?;
+    let payload_str = String::from_utf8(payload_buf)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("Payload is not valid UTF-8: {}", e)))?;
+    Ok((opcode, payload_str))
+}
+
+/// Serializes `message` to JSON and writes it as a single frame.
+pub fn write_message<W: Write, T: Serialize>(writer: &mut W, opcode: u32, message: &T) -> io::Result<()> {
+    let payload = serde_json::to_string(message).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+    writer.write_all(&frame_message(opcode, &payload)?)?;
+    writer.flush()
+}
+
+/// Reads a single frame and deserializes its JSON payload.
+/// Fails with `InvalidData` if the frame does not carry `expected_opcode`.
+pub fn read_message<R: Read, T: DeserializeOwned>(reader: &mut R, expected_opcode: u32) -> io::Result<T> {
+    let (opcode, payload) = read_framed_message(reader)?;
+    if opcode != expected_opcode {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("Unexpected opcode {} (expected {})", opcode, expected_opcode)));
+    }
+    serde_json::from_str(&payload).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Request sent by the core to the plugin host
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HostRequest {
+    /// Report the plugin's metadata
+    Describe,
+    /// Call `Plugin::init` (against the host process's own `Application`)
+    Init,
+    /// Call `Plugin::preflight_check`
+    Preflight { context: RemoteStageContext },
+    /// Call `Plugin::register_stages` and report the stages that were registered
+    RegisterStages,
+    /// Execute a stage registered by the plugin
+    ExecuteStage { stage_id: String, context: RemoteStageContext },
+    /// Call `Plugin::shutdown`; the host exits after answering
+    Shutdown,
+}
+
+impl HostRequest {
+    /// Short name of the request, used in log and error messages
+    pub fn operation(&self) -> &'static str {
+        match self {
+            HostRequest::Describe => "describe",
+            HostRequest::Init => "init",
+            HostRequest::Preflight { .. } => "preflight_check",
+            HostRequest::RegisterStages => "register_stages",
+            HostRequest::ExecuteStage { .. } => "execute_stage",
+            HostRequest::Shutdown => "shutdown",
+        }
+    }
+}
+
+/// Response sent by the plugin host for each request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum HostResponse {
+    /// The request succeeded
+    Ok,
+    /// The plugin (or the host) reported an error
+    Error { message: String },
+    /// Answer to [`HostRequest::Describe`]
+    Metadata { metadata: RemotePluginMetadata },
+    /// Answer to [`HostRequest::RegisterStages`]
+    Stages { stages: Vec<RemoteStageInfo> },
+}
+
+/// Plugin metadata as reported by the plugin host
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemotePluginMetadata {
+    pub protocol_version: u32,
+    pub name: String,
+    pub version: String,
+    pub is_core: bool,
+    pub priority: String, // `PluginPriority` in its "core:80" form
+    pub compatible_api_versions: Vec<String>,
+    pub dependencies: Vec<RemoteDependency>,
+    pub required_stages: Vec<RemoteStageRequirement>,
+    pub conflicts_with: Vec<String>,
+    pub incompatible_with: Vec<RemoteDependency>,
+}
+
+/// A plugin dependency with its version range as a constraint string
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteDependency {
+    pub plugin_name: String,
+    pub version_constraint: Option<String>, // None means any version
+    pub required: bool,
+}
+
+impl RemoteDependency {
+    pub fn from_dependency(dependency: &PluginDependency) -> Self {
+        Self {
+            plugin_name: dependency.plugin_name.clone(),
+            version_constraint: dependency.version_range.as_ref().map(|range| range.constraint_string().to_string()),
+            required: dependency.required,
+        }
+    }
+
+    /// Converts back to a `PluginDependency`; an unparsable constraint yields `None`
+    pub fn to_dependency(&self) -> Option<PluginDependency> {
+        let version_range = match &self.version_constraint {
+            Some(constraint) => Some(VersionRange::from_constraint(constraint).ok()?),
+            None => None,
+        };
+        Some(PluginDependency {
+            plugin_name: self.plugin_name.clone(),
+            version_range,
+            required: self.required,
+        })
+    }
+}
+
+/// A stage requirement as reported by the plugin host
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteStageRequirement {
+    pub stage_id: String,
+    pub required: bool,
+    pub provided: bool,
+}
+
+impl From<&StageRequirement> for RemoteStageRequirement {
+    fn from(requirement: &StageRequirement) -> Self {
+        Self {
+            stage_id: requirement.stage_id.clone(),
+            required: requirement.required,
+            provided: requirement.provided,
+        }
+    }
+}
+
+impl From<&RemoteStageRequirement> for StageRequirement {
+    fn from(requirement: &RemoteStageRequirement) -> Self {
+        StageRequirement {
+            stage_id: requirement.stage_id.clone(),
+            required: requirement.required,
+            provided: requirement.provided,
+        }
+    }
+}
+
+/// A stage registered inside the plugin host
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteStageInfo {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub supports_dry_run: bool,
+}
+
+/// The parts of a `StageContext` that can cross the process boundary.
+/// Shared data set by other stages stays in the core process.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RemoteStageContext {
+    pub dry_run: bool,
+    pub config_dir: PathBuf,
+    #[serde(default)]
+    pub cli_args: HashMap<String, String>,
+}
+
+impl RemoteStageContext {
+    pub fn from_context(context: &StageContext) -> Self {
+        Self {
+            dry_run: context.is_dry_run(),
+            config_dir: context.config_dir().clone(),
+            cli_args: context.cli_args().clone(),
+        }
+    }
+
+    /// Rebuilds a `StageContext` on the plugin host side
+    pub fn to_context(&self) -> StageContext {
+        let mut context = StageContext::new_live(self.config_dir.clone());
+        if self.dry_run {
+            context.mode = ExecutionMode::DryRun;
+        }
+        for (key, value) in &self.cli_args {
+            context.set_cli_arg(key, value);
+        }
+        context
+    }
+}
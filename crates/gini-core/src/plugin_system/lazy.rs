@@ -0,0 +1,216 @@
+//! # Manifest-Only Plugins
+//!
+//! Plugins discovered on disk are registered from their manifest alone, as a [`LazyPlugin`].
+//! Everything the registry needs before running a plugin (ID, version, priority, API
+//! versions, dependencies, conflicts and resource claims) comes from the manifest, so
+//! listing, resolving, enabling and disabling plugins never opens their libraries.
+//!
+//! The entry point library is loaded the first time the plugin's code is needed: when it is
+//! pre-flight checked or initialized. Stage and service requirements, the config schema
+//! declared in code and the stages themselves are only available from then on. A plugin that
+//! is never used, or is disabled, never has its library loaded, and shutting it down is a no-op.
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::conflict::ResourceClaim;
+use crate::plugin_system::dependency::PluginDependency;
+use crate::plugin_system::error::{PluginSystemError, PluginSystemErrorSource};
+use crate::plugin_system::loader::PluginLoader;
+use crate::plugin_system::manifest::PluginManifest;
+use crate::plugin_system::service::ServiceRequirement;
+use crate::plugin_system::signature::SignatureVerifier;
+use crate::plugin_system::traits::{Plugin, PluginPriority};
+use crate::plugin_system::version::VersionRange;
+use crate::stage_manager::context::StageContext;
+use crate::stage_manager::registry::StageRegistry;
+use crate::stage_manager::requirement::StageRequirement;
+use crate::storage::schema::ConfigSchema;
+
+/// Priority used for manifests that do not declare one (same default as `PluginLoader`).
+pub(crate) const DEFAULT_MANIFEST_PRIORITY: PluginPriority = PluginPriority::ThirdPartyLow(u8::MAX);
+
+/// A plugin registered from its manifest alone.
+///
+/// Metadata, dependencies, conflicts and resource claims are answered from the
+/// [`PluginManifest`]; the entry point library is only opened when the plugin is
+/// actually used (its pre-flight check or `init`). Listing, enabling and disabling a
+/// manifest-only plugin never touches the library, so a broken `.so` does not get in
+/// the way of those operations.
+pub struct LazyPlugin {
+    manifest: PluginManifest,
+    priority: PluginPriority,
+    instance: Mutex<Option<Arc<dyn Plugin>>>, // Loaded library instance, if any
+    signature_verifier: Arc<SignatureVerifier>, // Checked before the library is loaded
+}
+
+impl LazyPlugin {
+    /// Creates a manifest-only plugin entry. Nothing is loaded yet.
+    pub fn new(manifest: PluginManifest) -> Self {
+        let priority = manifest.get_priority().unwrap_or(DEFAULT_MANIFEST_PRIORITY);
+        Self {
+            manifest,
+            priority,
+            instance: Mutex::new(None),
+            signature_verifier: Arc::new(SignatureVerifier::default()),
+        }
+    }
+
+    /// Check the plugin's signature with `verifier` when it is loaded (the default allows unsigned plugins)
+    pub fn with_signature_verifier(mut self, verifier: Arc<SignatureVerifier>) -> Self {
+        self.signature_verifier = verifier;
+        self
+    }
+
+    /// Use `instance` as the loaded library instead of loading the entry point on first use.
+    /// The caller checks that it reports the manifest's plugin ID.
+    pub(crate) fn with_instance(self, instance: Arc<dyn Plugin>) -> Self {
+        if let Ok(mut loaded) = self.instance.lock() {
+            *loaded = Some(instance);
+        }
+        self
+    }
+
+    /// The manifest this plugin was registered from
+    pub fn manifest(&self) -> &PluginManifest {
+        &self.manifest
+    }
+
+    /// Path of the entry point library, relative paths resolved against the plugin's base directory
+    pub fn entry_point_path(&self) -> std::result::Result<PathBuf, PluginSystemError> {
+        PluginLoader::entry_point_path(&self.manifest)
+    }
+
+    /// Whether the entry point library has been loaded
+    pub fn is_loaded(&self) -> bool {
+        self.instance.lock().map(|instance| instance.is_some()).unwrap_or(false)
+    }
+
+    /// Returns the loaded instance without loading it
+    fn loaded_instance(&self) -> Option<Arc<dyn Plugin>> {
+        self.instance.lock().ok().and_then(|instance| instance.clone())
+    }
+
+    /// Loads the entry point library if that has not happened yet and returns the instance.
+    /// The library must report the same plugin ID as the manifest.
+    pub fn load(&self) -> std::result::Result<Arc<dyn Plugin>, PluginSystemError> {
+        let mut instance = self.instance.lock().map_err(|_| PluginSystemError::OperationError {
+            plugin_id: Some(self.manifest.id.clone()),
+            message: "Lazy plugin instance lock poisoned".to_string(),
+        })?;
+        if let Some(plugin) = instance.as_ref() {
+            return Ok(plugin.clone());
+        }
+
+        let library_path = self.entry_point_path()?;
+        println!("[LazyPlugin] Loading library for plugin '{}' from {:?}", self.manifest.id, library_path);
+        let plugin: Arc<dyn Plugin> = Arc::from(PluginLoader::load_entry_point(&self.manifest, &self.signature_verifier)?);
+
+        if plugin.name() != self.manifest.id {
+            return Err(PluginSystemError::LoadingError {
+                plugin_id: self.manifest.id.clone(),
+                path: Some(library_path),
+                source: Box::new(PluginSystemErrorSource::Other(format!(
+                    "Library reports plugin ID '{}' but the manifest declares '{}'",
+                    plugin.name(),
+                    self.manifest.id
+                ))),
+            });
+        }
+
+        *instance = Some(plugin.clone());
+        Ok(plugin)
+    }
+}
+
+#[async_trait]
+impl Plugin for LazyPlugin {
+    fn name(&self) -> &str {
+        self.manifest.id.as_str() // The plugin ID, not the display name
+    }
+
+    fn version(&self) -> &str {
+        &self.manifest.version
+    }
+
+    fn is_core(&self) -> bool {
+        self.manifest.is_core
+    }
+
+    fn priority(&self) -> PluginPriority {
+        self.priority.clone()
+    }
+
+    fn compatible_api_versions(&self) -> Vec<VersionRange> {
+        self.manifest.api_versions.clone()
+    }
+
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        self.manifest.dependencies.clone()
+    }
+
+    fn required_stages(&self) -> Vec<StageRequirement> {
+        // Stage requirements are not part of the manifest; they are only known once loaded
+        self.loaded_instance().map(|plugin| plugin.required_stages()).unwrap_or_default()
+    }
+
+    fn conflicts_with(&self) -> Vec<String> {
+        self.manifest.conflicts_with.clone()
+    }
+
+    fn incompatible_with(&self) -> Vec<PluginDependency> {
+        self.manifest.incompatible_with.clone()
+    }
+
+    fn declared_resources(&self) -> Vec<ResourceClaim> {
+        // Once loaded, claims the plugin declares in code are checked along with the manifest's
+        let mut claims = self.manifest.resources.clone();
+        if let Some(plugin) = self.loaded_instance() {
+            for claim in plugin.declared_resources() {
+                if !claims.contains(&claim) {
+                    claims.push(claim);
+                }
+            }
+        }
+        claims
+    }
+
+    fn config_schema(&self) -> Option<ConfigSchema> {
+        // The manifest's schema file is registered when the manifest is discovered
+        self.loaded_instance().and_then(|plugin| plugin.config_schema())
+    }
+
+    fn required_services(&self) -> Vec<ServiceRequirement> {
+        // Like stage requirements, only known once loaded
+        self.loaded_instance().map(|plugin| plugin.required_services()).unwrap_or_default()
+    }
+
+    fn init(&self, app: &mut Application) -> std::result::Result<(), PluginSystemError> {
+        self.load()?.init(app)
+    }
+
+    async fn preflight_check(&self, context: &StageContext) -> std::result::Result<(), PluginSystemError> {
+        let plugin = self.load()?;
+        plugin.preflight_check(context).await
+    }
+
+    fn register_stages(&self, registry: &mut StageRegistry) -> std::result::Result<(), PluginSystemError> {
+        match self.loaded_instance() {
+            Some(plugin) => plugin.register_stages(registry),
+            None => Err(PluginSystemError::OperationError {
+                plugin_id: Some(self.manifest.id.clone()),
+                message: "Cannot register stages before the plugin library is loaded".to_string(),
+            }),
+        }
+    }
+
+    fn shutdown(&self) -> std::result::Result<(), PluginSystemError> {
+        // Nothing to shut down if the library was never loaded
+        match self.loaded_instance() {
+            Some(plugin) => plugin.shutdown(),
+            None => Ok(()),
+        }
+    }
+}
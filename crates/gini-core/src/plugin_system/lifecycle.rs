@@ -0,0 +1,294 @@
+//! # Plugin Lifecycle
+//!
+//! Tracks the state of every registered plugin as a [`PluginState`]:
+//!
+//! ```text
+//! Discovered -> Loaded -> Resolved -> PreflightPassed -> Initialized -> Running
+//! ```
+//!
+//! Manifest-only plugins start out `Discovered` and other plugins `Loaded`; their code is
+//! loaded when they are pre-flight checked or initialized, so they may skip `Loaded`. A plugin
+//! becomes `Resolved` once its dependencies are satisfied, may pass its pre-flight check, is
+//! `Initialized` after `Plugin::init` and `Running` once its stages are registered. Shutting
+//! a plugin down returns it to `Loaded`. Any active state can turn `Failed` (keeping the
+//! error) or `Disabled`, and unregistering a plugin leaves it `Unloaded`.
+//!
+//! [`PluginLifecycle`] rejects other transitions, keeps the reason of each change and the last
+//! error of each plugin, and emits [`SystemEvent::PluginStateChanged`] for every transition.
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use crate::event::{EventManager, SystemEvent};
+use crate::plugin_system::error::PluginSystemError;
+
+/// Number of transitions kept per plugin.
+const HISTORY_LIMIT: usize = 16;
+
+/// Lifecycle state of a registered plugin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PluginState {
+    /// Registered from its manifest; its code is not loaded
+    Discovered,
+    /// Its code is loaded
+    Loaded,
+    /// Its dependencies are registered, enabled and of compatible versions
+    Resolved,
+    /// Its pre-flight check passed
+    PreflightPassed,
+    /// `Plugin::init` succeeded
+    Initialized,
+    /// Initialized and its stages are registered
+    Running,
+    /// A pre-flight check, initialization or shutdown failed; see the last error
+    Failed,
+    /// Disabled; it is not initialized
+    Disabled,
+    /// Removed from the registry
+    Unloaded,
+}
+
+impl PluginState {
+    /// Returns a string representation of the state.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluginState::Discovered => "Discovered",
+            PluginState::Loaded => "Loaded",
+            PluginState::Resolved => "Resolved",
+            PluginState::PreflightPassed => "PreflightPassed",
+            PluginState::Initialized => "Initialized",
+            PluginState::Running => "Running",
+            PluginState::Failed => "Failed",
+            PluginState::Disabled => "Disabled",
+            PluginState::Unloaded => "Unloaded",
+        }
+    }
+
+    /// Whether a plugin in this state takes part in initialization.
+    pub fn is_enabled(&self) -> bool {
+        !matches!(self, PluginState::Disabled | PluginState::Unloaded)
+    }
+
+    /// Whether `Plugin::init` has run and the plugin has not been shut down since.
+    pub fn is_initialized(&self) -> bool {
+        matches!(self, PluginState::Initialized | PluginState::Running)
+    }
+
+    /// Check whether a plugin may move from this state to `next`.
+    pub fn can_transition_to(&self, next: PluginState) -> bool {
+        use PluginState::*;
+        match (self, next) {
+            // Failing again replaces the last error
+            (Failed, Failed) => true,
+            (Disabled | Unloaded, Failed) => false,
+            (_, Failed) => true,
+            (Unloaded, Disabled) => false,
+            (Disabled, Disabled) => false,
+            (_, Disabled) => true,
+            (_, Unloaded) => *self != Unloaded,
+
+            (Discovered, Loaded | Resolved | PreflightPassed) => true,
+            (Loaded, Resolved | PreflightPassed) => true,
+            (Resolved, PreflightPassed | Initialized) => true,
+            (PreflightPassed, Initialized) => true,
+            (Initialized, Running) => true,
+            // Shut down; the code stays loaded
+            (Initialized | Running, Loaded) => true,
+            // Retried after a failure
+            (Failed, Loaded | Resolved | PreflightPassed) => true,
+            // Enabled again, or registered again after being unloaded
+            (Disabled | Unloaded, Discovered | Loaded) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for PluginState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A recorded change of a plugin's state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateTransition {
+    /// `None` for the state a plugin was registered in
+    pub from: Option<PluginState>,
+    pub to: PluginState,
+    pub reason: String,
+    pub at: SystemTime,
+}
+
+/// Current state of a plugin and how it got there.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginStatus {
+    pub plugin_id: String,
+    pub state: PluginState,
+    /// Why the plugin entered its current state
+    pub reason: String,
+    /// The error of the last failure, kept until the plugin is unregistered
+    pub last_error: Option<String>,
+    /// The most recent transitions, oldest first
+    pub history: VecDeque<StateTransition>,
+}
+
+impl PluginStatus {
+    fn record(&mut self, from: Option<PluginState>, to: PluginState, reason: &str) {
+        self.state = to;
+        self.reason = reason.to_string();
+        if self.history.len() == HISTORY_LIMIT {
+            self.history.pop_front();
+        }
+        self.history.push_back(StateTransition { from, to, reason: reason.to_string(), at: SystemTime::now() });
+    }
+}
+
+impl fmt::Display for PluginStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Plugin '{}': {} ({})", self.plugin_id, self.state, self.reason)?;
+        if let Some(error) = &self.last_error {
+            writeln!(f, "  last error: {}", error)?;
+        }
+        for transition in &self.history {
+            match transition.from {
+                Some(from) => writeln!(f, "  {} -> {}: {}", from, transition.to, transition.reason)?,
+                None => writeln!(f, "  registered as {}: {}", transition.to, transition.reason)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Lifecycle states of the registered plugins.
+#[derive(Default)]
+pub struct PluginLifecycle {
+    statuses: RwLock<HashMap<String, PluginStatus>>,
+    event_manager: RwLock<Option<Arc<dyn EventManager>>>, // Receives state change events
+}
+
+impl fmt::Debug for PluginLifecycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginLifecycle")
+            .field("plugins", &self.statuses.read().map(|statuses| statuses.len()).unwrap_or(0))
+            .finish()
+    }
+}
+
+impl PluginLifecycle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the event manager that receives `PluginStateChanged` events.
+    pub fn set_event_manager(&self, event_manager: Arc<dyn EventManager>) {
+        if let Ok(mut slot) = self.event_manager.write() {
+            *slot = Some(event_manager);
+        }
+    }
+
+    /// Starts tracking a newly registered plugin in `state`.
+    /// A plugin registered again after being unloaded keeps its history.
+    pub fn register(&self, plugin_id: &str, state: PluginState, reason: &str) -> Result<(), PluginSystemError> {
+        let previous = self.state(plugin_id);
+        match previous {
+            None => {
+                let mut status = PluginStatus {
+                    plugin_id: plugin_id.to_string(),
+                    state,
+                    reason: String::new(),
+                    last_error: None,
+                    history: VecDeque::new(),
+                };
+                status.record(None, state, reason);
+                self.write_statuses()?.insert(plugin_id.to_string(), status);
+                Ok(())
+            }
+            Some(_) => self.transition(plugin_id, state, reason),
+        }
+    }
+
+    /// Moves a plugin to `state`, if its current state allows it.
+    pub fn transition(&self, plugin_id: &str, state: PluginState, reason: &str) -> Result<(), PluginSystemError> {
+        let from = {
+            let mut statuses = self.write_statuses()?;
+            let status = statuses.get_mut(plugin_id).ok_or_else(|| PluginSystemError::RegistrationError {
+                plugin_id: plugin_id.to_string(),
+                message: "Plugin has no lifecycle state".to_string(),
+            })?;
+            let from = status.state;
+            if !from.can_transition_to(state) {
+                return Err(PluginSystemError::InvalidStateTransition {
+                    plugin_id: plugin_id.to_string(),
+                    from: from.to_string(),
+                    to: state.to_string(),
+                });
+            }
+            status.record(Some(from), state, reason);
+            from
+        };
+        self.emit_state_change(plugin_id, from, state, reason);
+        Ok(())
+    }
+
+    /// Marks a plugin `Failed` and keeps `error` as its last error.
+    /// Disabled and unloaded plugins keep their state; only the error is recorded.
+    pub fn fail(&self, plugin_id: &str, error: &str) {
+        if let Ok(mut statuses) = self.statuses.write()
+            && let Some(status) = statuses.get_mut(plugin_id)
+        {
+            status.last_error = Some(error.to_string());
+        }
+        if self.state(plugin_id).is_some_and(|state| state.can_transition_to(PluginState::Failed)) {
+            let _ = self.transition(plugin_id, PluginState::Failed, error);
+        }
+    }
+
+    /// The current state of a plugin, if it was ever registered.
+    pub fn state(&self, plugin_id: &str) -> Option<PluginState> {
+        self.statuses.read().ok()?.get(plugin_id).map(|status| status.state)
+    }
+
+    /// The state of a plugin together with the reason, last error and recent transitions.
+    pub fn status(&self, plugin_id: &str) -> Option<PluginStatus> {
+        self.statuses.read().ok()?.get(plugin_id).cloned()
+    }
+
+    /// The status of every plugin that was ever registered, by plugin ID.
+    pub fn statuses(&self) -> BTreeMap<String, PluginStatus> {
+        match self.statuses.read() {
+            Ok(statuses) => statuses.iter().map(|(id, status)| (id.clone(), status.clone())).collect(),
+            Err(_) => BTreeMap::new(),
+        }
+    }
+
+    /// IDs of the plugins currently in a state matching `filter`.
+    pub fn plugins_where(&self, filter: impl Fn(PluginState) -> bool) -> Vec<String> {
+        match self.statuses.read() {
+            Ok(statuses) => statuses.values().filter(|status| filter(status.state)).map(|status| status.plugin_id.clone()).collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn write_statuses(&self) -> Result<std::sync::RwLockWriteGuard<'_, HashMap<String, PluginStatus>>, PluginSystemError> {
+        self.statuses.write().map_err(|e| PluginSystemError::InternalError(format!("Plugin lifecycle lock poisoned: {}", e)))
+    }
+
+    fn emit_state_change(&self, plugin_id: &str, from: PluginState, to: PluginState, reason: &str) {
+        let Some(event_manager) = self.event_manager.read().ok().and_then(|slot| slot.clone()) else {
+            return;
+        };
+        let event = SystemEvent::PluginStateChanged {
+            plugin_id: plugin_id.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            reason: reason.to_string(),
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move { event_manager.queue_event(Box::new(event)).await });
+            }
+            Err(_) => log::warn!("State change event dropped outside of a tokio runtime: {:?}", event),
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! # Plugin Manifest Linting
+//!
+//! [`ManifestLint`] checks a plugin manifest (`manifest.json`, `plugin.toml` or
+//! `plugin.yaml`) for mistakes the loader either ignores or only reports one at a time:
+//!
+//! - keys the loader does not know, at the top level and in dependency entries
+//! - missing required fields
+//! - `api_versions`, `dependencies` and `incompatible_with` constraints that are not valid
+//!   [`VersionRange`]s
+//! - `priority` strings that are not a valid [`PluginPriority`]
+//! - malformed `resources` claims
+//! - a missing `entry_point`, and an entry point, `files` or `config_schema` that do not exist
+//!   relative to the plugin's directory
+//!
+//! Issues that stop the plugin from loading are errors; the rest are warnings.
+//! `gini plugin lint <path>` prints the result.
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use serde_json::Value;
+
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::loader::{deserialize_manifest, PluginLoader, DEPENDENCY_KEYS, MANIFEST_KEYS};
+use crate::plugin_system::manifest::{PluginManifest, ResourceClaim};
+use crate::plugin_system::traits::PluginPriority;
+use crate::plugin_system::version::VersionRange;
+use crate::storage::config::ConfigFormat;
+use crate::storage::schema::ConfigSchema;
+
+/// Fields every manifest must set
+const REQUIRED_KEYS: &[&str] = &["id", "name", "version", "description", "author"];
+
+/// How serious a lint issue is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintSeverity {
+    /// The plugin cannot be loaded, or fails once it is used
+    Error,
+    /// The manifest loads, but probably does not say what its author meant
+    Warning,
+}
+
+impl fmt::Display for LintSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintSeverity::Error => write!(f, "error"),
+            LintSeverity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A problem found in a manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    pub severity: LintSeverity,
+    /// The manifest field concerned, e.g. `dependencies[1].version_range`
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.field, self.message)
+    }
+}
+
+/// The result of linting one manifest file
+#[derive(Debug, Clone)]
+pub struct ManifestLint {
+    /// The manifest file that was checked
+    pub path: PathBuf,
+    pub issues: Vec<LintIssue>,
+}
+
+impl ManifestLint {
+    /// Lint the manifest at `path`, which is either a manifest file or a plugin directory.
+    /// Fails only if no manifest can be read.
+    pub fn lint<P: AsRef<Path>>(path: P) -> Result<Self, PluginSystemError> {
+        let path = path.as_ref();
+        let manifest_path = if path.is_dir() {
+            PluginManifest::find_file(path).ok_or_else(|| PluginSystemError::ManifestError {
+                path: path.to_path_buf(),
+                message: "No manifest file found in the directory".to_string(),
+                source: None,
+            })?
+        } else {
+            path.to_path_buf()
+        };
+        let content = std::fs::read_to_string(&manifest_path).map_err(|e| PluginSystemError::ManifestError {
+            path: manifest_path.clone(),
+            message: "Failed to read manifest".to_string(),
+            source: Some(Box::new(e)),
+        })?;
+        Ok(Self::lint_str(&content, &manifest_path))
+    }
+
+    /// Lint manifest `content` as if it had been read from `path`.
+    /// The format follows the extension of `path`, and files are looked up relative to its directory.
+    pub fn lint_str(content: &str, path: &Path) -> Self {
+        let mut lint = Self { path: path.to_path_buf(), issues: Vec::new() };
+        let format = ConfigFormat::from_path(path).unwrap_or(ConfigFormat::Json);
+        let value: Value = match deserialize_manifest(content, format) {
+            Ok(value) => value,
+            Err(e) => {
+                lint.error("manifest", format!("not valid {}: {}", format.extension().to_uppercase(), e));
+                return lint;
+            }
+        };
+        let Some(fields) = value.as_object() else {
+            lint.error("manifest", "must be a table of fields".to_string());
+            return lint;
+        };
+
+        for key in fields.keys().filter(|key| !MANIFEST_KEYS.contains(&key.as_str())) {
+            lint.warning(key, "unknown key, ignored by the loader".to_string());
+        }
+        for key in REQUIRED_KEYS.iter().filter(|key| !fields.contains_key(**key)) {
+            lint.error(key, "required but not set".to_string());
+        }
+
+        for (index, range) in Self::items(fields, "api_versions") {
+            lint.check_version_range(&format!("api_versions[{}]", index), range);
+        }
+        for key in ["dependencies", "incompatible_with"] {
+            for (index, entry) in Self::items(fields, key) {
+                lint.check_dependency(&format!("{}[{}]", key, index), entry);
+            }
+        }
+        if let Some(priority) = fields.get("priority").and_then(Value::as_str)
+            && PluginPriority::from_str(priority).is_none()
+        {
+            lint.error("priority", format!("'{}' is not a valid priority; expected e.g. 'third_party:151'", priority));
+        }
+        for (index, claim) in Self::items(fields, "resources") {
+            if let Err(e) = serde_json::from_value::<ResourceClaim>(claim.clone()) {
+                lint.error(&format!("resources[{}]", index), format!("invalid resource claim: {}", e));
+            }
+        }
+        lint.check_files(fields);
+
+        // Anything the checks above do not cover, such as fields of the wrong type
+        if !lint.has_errors()
+            && let Err(e) = PluginLoader::parse_manifest_as(content, path, format)
+        {
+            lint.error("manifest", e.to_string());
+        }
+        lint
+    }
+
+    /// Whether any issue stops the plugin from loading
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|issue| issue.severity == LintSeverity::Error)
+    }
+
+    fn error(&mut self, field: &str, message: String) {
+        self.issues.push(LintIssue { severity: LintSeverity::Error, field: field.to_string(), message });
+    }
+
+    fn warning(&mut self, field: &str, message: String) {
+        self.issues.push(LintIssue { severity: LintSeverity::Warning, field: field.to_string(), message });
+    }
+
+    /// The entries of the array field `key`, with their indices
+    fn items<'a>(fields: &'a serde_json::Map<String, Value>, key: &str) -> impl Iterator<Item = (usize, &'a Value)> {
+        fields.get(key).and_then(Value::as_array).into_iter().flatten().enumerate()
+    }
+
+    fn check_version_range(&mut self, field: &str, range: &Value) {
+        match range.as_str() {
+            Some(constraint) => {
+                if let Err(e) = VersionRange::from_str(constraint) {
+                    self.error(field, e.to_string());
+                }
+            }
+            None => self.error(field, format!("expected a version constraint string, found {}", range)),
+        }
+    }
+
+    fn check_dependency(&mut self, field: &str, entry: &Value) {
+        let Some(dependency) = entry.as_object() else {
+            self.error(field, format!("expected a table with 'id', 'version_range' and 'required', found {}", entry));
+            return;
+        };
+        for key in dependency.keys().filter(|key| !DEPENDENCY_KEYS.contains(&key.as_str())) {
+            self.warning(&format!("{}.{}", field, key), "unknown key, ignored by the loader".to_string());
+        }
+        if !dependency.contains_key("id") {
+            self.error(&format!("{}.id", field), "required but not set".to_string());
+        }
+        if let Some(range) = dependency.get("version_range") {
+            self.check_version_range(&format!("{}.version_range", field), range);
+        }
+    }
+
+    fn check_files(&mut self, fields: &serde_json::Map<String, Value>) {
+        let base_dir = self.path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+        let entry_point = match fields.get("entry_point").and_then(Value::as_str) {
+            Some(entry_point) => entry_point.to_string(),
+            None => {
+                let id = fields.get("id").and_then(Value::as_str).unwrap_or_default();
+                self.warning("entry_point", format!("not set; the loader uses 'lib{}.so'", id));
+                format!("lib{}.so", id)
+            }
+        };
+        if entry_point.contains("..") || Path::new(&entry_point).is_absolute() {
+            self.error("entry_point", format!("'{}' must be relative and must not contain '..'", entry_point));
+        } else if !base_dir.join(&entry_point).is_file() {
+            self.error("entry_point", format!("'{}' does not exist", base_dir.join(&entry_point).display()));
+        }
+        for (index, file) in Self::items(fields, "files") {
+            if let Some(file) = file.as_str()
+                && !base_dir.join(file).exists()
+            {
+                self.error(&format!("files[{}]", index), format!("'{}' does not exist", base_dir.join(file).display()));
+            }
+        }
+        if let Some(schema_file) = fields.get("config_schema").and_then(Value::as_str) {
+            let schema_path = base_dir.join(schema_file);
+            match std::fs::read_to_string(&schema_path) {
+                Ok(schema) => if let Err(e) = ConfigSchema::from_json_str(&schema) {
+                    self.error("config_schema", format!("'{}' is not a valid schema: {}", schema_path.display(), e));
+                },
+                Err(_) => self.error("config_schema", format!("'{}' does not exist", schema_path.display())),
+            }
+        }
+    }
+}
+
+impl fmt::Display for ManifestLint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.issues.is_empty() {
+            return writeln!(f, "{}: no problems found", self.path.display());
+        }
+        writeln!(f, "{}:", self.path.display())?;
+        for issue in &self.issues {
+            writeln!(f, "  {}", issue)?;
+        }
+        let errors = self.issues.iter().filter(|issue| issue.severity == LintSeverity::Error).count();
+        writeln!(f, "{} error(s), {} warning(s)", errors, self.issues.len() - errors)
+    }
+}
@@ -7,446 +7,139 @@ use std::pin::Pin;
 use semver::{Version}; // Removed VersionReq as VersionRange handles it
 // use thiserror::Error; // Removed unused import, derive handles it
 use std::str::FromStr; // For VersionRange::from_str
-use serde::Deserialize; // Import Deserialize
-use serde_json; // Added serde_json import
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
 use std::sync::Arc;
-use libloading::{Library, Symbol};
-use std::ffi::{CStr, c_void};
-use std::os::raw::c_char;
-use std::panic;
 
 use crate::kernel::error::{Error as KernelError, Result as KernelResult}; // Renamed Error to KernelError, added KernelResult alias
 use crate::plugin_system::error::PluginSystemError; // Import new error type
-use crate::plugin_system::traits::{
-    Plugin, FfiResult, FfiSlice, FfiVersionRange, FfiPluginDependency,
-    FfiStageRequirement, PluginVTable, PluginPriority, // Removed PluginError
-};
-use crate::kernel::bootstrap::Application; // For Plugin::init signature
-use crate::stage_manager::context::StageContext; // For Plugin::preflight_check
-use crate::stage_manager::registry::StageRegistry; // For Plugin::register_stages
+use crate::plugin_system::traits::{Plugin, PluginPriority};
+use crate::plugin_system::ffi_host; // Shared FFI host for dynamic plugin libraries
+use crate::plugin_system::signature::SignatureVerifier; // Checked before a library is opened
 
 // Import the final manifest structs
-use crate::plugin_system::manifest::{PluginManifest, ResourceClaim};
+use crate::plugin_system::manifest::{PluginManifest, ResourceClaim, MANIFEST_FILE_NAMES};
 use crate::plugin_system::registry::PluginRegistry;
 use crate::plugin_system::version::{ApiVersion, VersionRange};
-use crate::plugin_system::dependency::{PluginDependency, DependencyError}; // Import DependencyError
+use crate::plugin_system::dependency::{DependencyError, PluginDependency};
+use crate::storage::config::ConfigFormat; // Manifests can be written in any config format
 
 
-// --- Intermediate structs for deserialization ---
+// --- Intermediate structs for (de)serialization ---
 
-#[derive(Deserialize, Debug)]
-struct RawDependencyInfo {
+#[derive(Deserialize, Serialize, Debug)]
+pub(crate) struct RawDependencyInfo {
     id: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     version_range: Option<String>,
     #[serde(default)]
     required: bool,
 }
 
-#[derive(Deserialize, Debug)]
-struct RawPluginManifest {
+impl From<&PluginDependency> for RawDependencyInfo {
+    fn from(dependency: &PluginDependency) -> Self {
+        Self {
+            id: dependency.plugin_name.clone(),
+            version_range: dependency.version_range.as_ref().map(|range| range.to_string()),
+            required: dependency.required,
+        }
+    }
+}
+
+/// A manifest as written in `manifest.json`, `plugin.toml` or `plugin.yaml`
+#[derive(Deserialize, Serialize, Debug)]
+pub(crate) struct RawPluginManifest {
     id: String,
     name: String,
     version: String,
     description: String,
     author: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     website: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     license: Option<String>,
     #[serde(default)]
     api_versions: Vec<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     dependencies: Vec<RawDependencyInfo>,
     #[serde(default)]
     is_core: bool,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     priority: Option<String>,
     #[serde(default)]
     entry_point: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     files: Vec<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     config_schema: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     tags: Vec<String>,
-    #[serde(default)]
-    resources: Option<Vec<ResourceClaim>>,
-}
-
-// --- End Intermediate structs ---
-
-
-// --- FFI Helper Functions (copied from manager.rs) ---
-
-/// Maps FfiResult to PluginSystemError
-fn map_ffi_error_loader(ffi_err: FfiResult, plugin_id: &str, operation: &str) -> PluginSystemError {
-    PluginSystemError::FfiError {
-        plugin_id: plugin_id.to_string(),
-        operation: operation.to_string(),
-        message: format!("{:?}", ffi_err),
-    }
-}
-
-/// Safely converts an FFI C string pointer to a Rust String.
-/// # Safety
-/// The caller must ensure that `ptr` is a valid pointer to a null-terminated
-/// C string, and that it remains valid for the duration of this function call.
-/// The string data is expected to be UTF-8 encoded.
-unsafe fn ffi_string_from_ptr(ptr: *const c_char) -> std::result::Result<String, FfiResult> { // Explicit std::result
-    if ptr.is_null() {
-        return Err(FfiResult::NullPointer);
-    }
-    unsafe { CStr::from_ptr(ptr) }
-        .to_str()
-        .map(|s| s.to_owned())
-        .map_err(|_| FfiResult::Utf8Error)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    conflicts_with: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    incompatible_with: Vec<RawDependencyInfo>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    resources: Vec<ResourceClaim>,
 }
 
-/// Safely converts an FFI C string pointer (which can be null) to an Option<String>.
-/// # Safety
-/// The caller must ensure that if `ptr` is non-null, it is a valid pointer to a
-/// null-terminated C string, and that it remains valid for the duration of this
-/// function call. The string data is expected to be UTF-8 encoded.
-unsafe fn ffi_opt_string_from_ptr(ptr: *const c_char) -> std::result::Result<Option<String>, FfiResult> { // Explicit std::result
-    if ptr.is_null() {
-        Ok(None)
-    } else {
-        unsafe { CStr::from_ptr(ptr) }
-            .to_str()
-            .map(|s| Some(s.to_owned()))
-            .map_err(|_| FfiResult::Utf8Error)
-    }
-}
+/// Keys a manifest may contain; anything else is ignored by the loader and reported by the linter
+pub(crate) const MANIFEST_KEYS: &[&str] = &[
+    "id", "name", "version", "description", "author", "website", "license", "api_versions",
+    "dependencies", "is_core", "priority", "entry_point", "files", "config_schema", "tags",
+    "conflicts_with", "incompatible_with", "resources",
+];
 
-// --- VTablePluginWrapper (copied and adapted from manager.rs) ---
-
-#[derive(Debug, Clone, Copy)]
-struct UnsafeVTablePtr(*const PluginVTable);
-unsafe impl Send for UnsafeVTablePtr {}
-unsafe impl Sync for UnsafeVTablePtr {}
-
-#[derive(Debug)]
-struct VTablePluginWrapper {
-    vtable: UnsafeVTablePtr,
-    library: Option<Library>, // Store the library itself
-    name_cache: &'static str, // Store as static str after leaking once
-    version_cache: String,
-    is_core_cache: bool,
-    priority_cache: PluginPriority,
-}
+/// Keys of an entry in a manifest's `dependencies` or `incompatible_with`
+pub(crate) const DEPENDENCY_KEYS: &[&str] = &["id", "version_range", "required"];
 
-impl VTablePluginWrapper {
-    unsafe fn new(vtable_ptr: *mut PluginVTable, library: Library) -> std::result::Result<Self, PluginSystemError> {
-        if vtable_ptr.is_null() {
-            drop(library);
-            return Err(PluginSystemError::LoadingError {
-                plugin_id: "<unknown_on_null_vtable_loader>".to_string(),
-                path: None,
-                source: Box::new(crate::plugin_system::error::PluginSystemErrorSource::Other(
-                    "Received null VTable pointer from plugin init (loader).".to_string(),
-                )),
-            });
+impl From<&PluginManifest> for RawPluginManifest {
+    fn from(manifest: &PluginManifest) -> Self {
+        Self {
+            id: manifest.id.clone(),
+            name: manifest.name.clone(),
+            version: manifest.version.clone(),
+            description: manifest.description.clone(),
+            author: manifest.author.clone(),
+            website: manifest.website.clone(),
+            license: manifest.license.clone(),
+            api_versions: manifest.api_versions.iter().map(|range| range.to_string()).collect(),
+            dependencies: manifest.dependencies.iter().map(RawDependencyInfo::from).collect(),
+            is_core: manifest.is_core,
+            priority: manifest.priority.clone(),
+            entry_point: Some(manifest.entry_point.clone()),
+            files: manifest.files.clone(),
+            config_schema: manifest.config_schema.clone(),
+            tags: manifest.tags.clone(),
+            conflicts_with: manifest.conflicts_with.clone(),
+            incompatible_with: manifest.incompatible_with.iter().map(RawDependencyInfo::from).collect(),
+            resources: manifest.resources.clone(),
         }
-
-        let vtable_ref = unsafe { &*vtable_ptr };
-        let instance_const = vtable_ref.instance as *const c_void;
-
-        let name_ptr = (vtable_ref.name)(instance_const);
-        let name_string = unsafe { ffi_string_from_ptr(name_ptr) }
-            .map_err(|e| map_ffi_error_loader(e, "<unknown_loader>", "getting plugin name"))?;
-        (vtable_ref.free_name)(name_ptr as *mut c_char);
-        let static_name: &'static str = Box::leak(name_string.into_boxed_str());
-
-        let version_ptr = (vtable_ref.version)(instance_const);
-        let version = unsafe { ffi_string_from_ptr(version_ptr) }
-            .map_err(|e| map_ffi_error_loader(e, static_name, "getting plugin version"))?;
-        (vtable_ref.free_version)(version_ptr as *mut c_char);
-
-        let is_core = (vtable_ref.is_core)(instance_const);
-        let ffi_priority = (vtable_ref.priority)(instance_const);
-        let priority = ffi_priority.to_plugin_priority()
-            .ok_or_else(|| PluginSystemError::LoadingError {
-                plugin_id: static_name.to_string(),
-                path: None,
-                source: Box::new(crate::plugin_system::error::PluginSystemErrorSource::Other(
-                    format!("Invalid FFI priority value received: {:?}", ffi_priority)
-                )),
-            })?;
-
-        Ok(Self {
-            vtable: UnsafeVTablePtr(vtable_ptr),
-            library: Some(library),
-            name_cache: static_name,
-            version_cache: version,
-            is_core_cache: is_core,
-            priority_cache: priority,
-        })
-    }
-
-    unsafe fn get_vector_from_ffi_slice<T, R, FVal>(
-        &self,
-        get_slice_fn: extern "C" fn(instance: *const c_void) -> FfiSlice<T>,
-        free_slice_fn: extern "C" fn(slice: FfiSlice<T>),
-        converter: FVal,
-    ) -> std::result::Result<Vec<R>, PluginSystemError>
-    where
-        T: Copy,
-        FVal: Fn(T) -> std::result::Result<R, PluginSystemError>, // Converter returns PluginSystemError
-    {
-        let vtable = unsafe { &*self.vtable.0 };
-        let instance_const = vtable.instance as *const c_void;
-        let ffi_slice = (get_slice_fn)(instance_const);
-        let result = unsafe { ffi_slice.as_slice() }
-            .map_or_else(
-                || Ok(Vec::new()),
-                |slice_data| {
-                    slice_data.iter()
-                        .map(|&item| converter(item)) // item is T, converter returns Result<R, PluginSystemError>
-                        .collect::<std::result::Result<Vec<R>, PluginSystemError>>() // Collect into Result<Vec<R>, PluginSystemError>
-                },
-            );
-        (free_slice_fn)(ffi_slice);
-        result
     }
 }
 
-impl Drop for VTablePluginWrapper {
-    fn drop(&mut self) {
-        unsafe {
-            if !self.vtable.0.is_null() {
-                let vtable_ref = &*self.vtable.0;
-                (vtable_ref.destroy)(vtable_ref.instance);
-                let _vtable_box = Box::from_raw(self.vtable.0 as *mut PluginVTable);
-                self.vtable.0 = std::ptr::null_mut();
-            }
-            drop(self.library.take());
-        }
+/// Deserialize manifest content written in `format`
+pub(crate) fn deserialize_manifest<T: DeserializeOwned>(content: &str, format: ConfigFormat) -> std::result::Result<T, Box<dyn std::error::Error + Send + Sync>> {
+    match format {
+        ConfigFormat::Json => Ok(serde_json::from_str(content)?),
+        #[cfg(feature = "yaml-config")]
+        ConfigFormat::Yaml => Ok(serde_yaml::from_str(content)?),
+        #[cfg(feature = "toml-config")]
+        ConfigFormat::Toml => Ok(toml::from_str(content)?),
     }
 }
 
-#[async_trait::async_trait]
-impl Plugin for VTablePluginWrapper {
-    fn name(&self) -> &'static str { self.name_cache }
-    fn version(&self) -> &str { &self.version_cache }
-    fn is_core(&self) -> bool { self.is_core_cache }
-    fn priority(&self) -> PluginPriority { self.priority_cache.clone() }
-
-    fn compatible_api_versions(&self) -> Vec<VersionRange> {
-        unsafe {
-            self.get_vector_from_ffi_slice(
-                (*self.vtable.0).compatible_api_versions,
-                (*self.vtable.0).free_compatible_api_versions,
-                |ffi_range: FfiVersionRange| {
-                    let constraint = ffi_string_from_ptr(ffi_range.constraint)
-                        .map_err(|e| map_ffi_error_loader(e, self.name_cache, "getting compatible_api_versions constraint"))?;
-                    VersionRange::from_constraint(&constraint).map_err(PluginSystemError::from)
-                },
-            )
-            .unwrap_or_else(|e| {
-                eprintln!("Error getting compatible API versions for plugin '{}': {}", self.name_cache, e);
-                Vec::new()
-            })
-        }
-    }
-
-    fn dependencies(&self) -> Vec<PluginDependency> {
-        unsafe {
-            self.get_vector_from_ffi_slice(
-                (*self.vtable.0).dependencies,
-                (*self.vtable.0).free_dependencies,
-                |ffi_dep: FfiPluginDependency| {
-                    let name = ffi_string_from_ptr(ffi_dep.plugin_name)
-                        .map_err(|e| map_ffi_error_loader(e, self.name_cache, "getting dependency name"))?;
-                    let version_constraint_str = ffi_opt_string_from_ptr(ffi_dep.version_constraint)
-                         .map_err(|e| map_ffi_error_loader(e, self.name_cache, "getting dependency version constraint"))?;
-                    let version_range = version_constraint_str
-                        .map(|s| VersionRange::from_constraint(s.as_str()))
-                        .transpose()
-                        .map_err(PluginSystemError::from)?;
-                    Ok(PluginDependency {
-                        plugin_name: name,
-                        version_range: version_range,
-                        required: ffi_dep.required,
-                    })
-                },
-            )
-            .unwrap_or_else(|e| {
-                eprintln!("Error getting dependencies for plugin '{}': {}", self.name_cache, e);
-                Vec::new()
-            })
-        }
-    }
-
-     fn required_stages(&self) -> Vec<crate::stage_manager::requirement::StageRequirement> {
-        unsafe {
-            self.get_vector_from_ffi_slice(
-                (*self.vtable.0).required_stages,
-                (*self.vtable.0).free_required_stages,
-                |ffi_req: FfiStageRequirement| {
-                    let id = ffi_string_from_ptr(ffi_req.stage_id)
-                        .map_err(|e| map_ffi_error_loader(e, self.name_cache, "getting required_stages id"))?;
-                    Ok(crate::stage_manager::requirement::StageRequirement {
-                        stage_id: id,
-                        required: ffi_req.required,
-                        provided: ffi_req.provided,
-                    })
-                },
-            )
-            .unwrap_or_else(|e| {
-                eprintln!("Error getting required stages for plugin '{}': {}", self.name_cache, e);
-                Vec::new()
-            })
-        }
-    }
-
-    fn conflicts_with(&self) -> Vec<String> {
-        unsafe {
-            self.get_vector_from_ffi_slice(
-                (*self.vtable.0).conflicts_with,
-                (*self.vtable.0).free_conflicts_with,
-                |ffi_str_ptr: *const c_char| {
-                    ffi_string_from_ptr(ffi_str_ptr)
-                        .map_err(|e| map_ffi_error_loader(e, self.name_cache, "getting conflicts_with string"))
-                },
-            )
-            .unwrap_or_else(|e| {
-                eprintln!("Error getting conflicts_with for plugin '{}': {}", self.name_cache, e);
-                Vec::new()
-            })
-        }
-    }
-
-    fn incompatible_with(&self) -> Vec<PluginDependency> {
-        unsafe {
-            self.get_vector_from_ffi_slice(
-                (*self.vtable.0).incompatible_with,
-                (*self.vtable.0).free_incompatible_with,
-                |ffi_dep: FfiPluginDependency| {
-                     let name = ffi_string_from_ptr(ffi_dep.plugin_name)
-                        .map_err(|e| map_ffi_error_loader(e, self.name_cache, "getting incompatible_with name"))?;
-                    let version_constraint_str = ffi_opt_string_from_ptr(ffi_dep.version_constraint)
-                         .map_err(|e| map_ffi_error_loader(e, self.name_cache, "getting incompatible_with version constraint"))?;
-                    let version_range = version_constraint_str
-                        .map(|s| VersionRange::from_constraint(s.as_str()))
-                        .transpose()
-                        .map_err(PluginSystemError::from)?;
-                    Ok(PluginDependency {
-                        plugin_name: name,
-                        version_range: version_range,
-                        required: ffi_dep.required,
-                    })
-                },
-            )
-            .unwrap_or_else(|e| {
-                eprintln!("Error getting incompatible_with for plugin '{}': {}", self.name_cache, e);
-                Vec::new()
-            })
-        }
-    }
-
-    fn init(&self, app: &mut Application) -> std::result::Result<(), PluginSystemError> {
-        let vtable_ptr = self.vtable.0;
-        let plugin_name_for_err = self.name_cache;
-        let result = panic::catch_unwind(std::panic::AssertUnwindSafe(move || unsafe {
-            let vtable = &*vtable_ptr;
-            let app_ptr = app as *mut _ as *mut c_void;
-            (vtable.init)(vtable.instance, app_ptr)
-        }));
-        match result {
-            Ok(ffi_res) => {
-                if ffi_res == FfiResult::Ok { Ok(()) }
-                else { Err(map_ffi_error_loader(ffi_res, plugin_name_for_err, "init")) }
-            }
-            Err(panic_obj) => {
-                let panic_msg = if let Some(s_ref) = panic_obj.downcast_ref::<&'static str>() { (*s_ref).to_string() }
-                                else if let Some(s_obj) = panic_obj.downcast_ref::<String>() { s_obj.clone() }
-                                else { "Unknown panic reason".to_string() };
-                Err(PluginSystemError::FfiError{ plugin_id: plugin_name_for_err.to_string(), operation: "init".to_string(), message: format!("panic: {}", panic_msg) })
-            }
-        }
-    }
-
-    fn preflight_check<'life0, 'life1, 'async_trait>(
-        &'life0 self,
-        context: &'life1 StageContext
-    ) -> Pin<Box<dyn Future<Output = std::result::Result<(), PluginSystemError>> + Send + 'async_trait>>
-    where
-        'life0: 'async_trait,
-        'life1: 'async_trait,
-        Self: 'async_trait,
-    {
-        let vtable_ptr = self.vtable.0;
-        let plugin_name_for_err = self.name_cache;
-        let ffi_call_result = panic::catch_unwind(std::panic::AssertUnwindSafe(move || unsafe {
-            let vtable = &*vtable_ptr;
-            let context_ptr = context as *const _ as *const c_void;
-            (vtable.preflight_check)(vtable.instance, context_ptr)
-        }));
-        
-        Box::pin(async move {
-            match ffi_call_result {
-                Ok(ffi_res) => {
-                    if ffi_res == FfiResult::Ok { Ok(()) }
-                    else { Err(PluginSystemError::PreflightCheckFailed{ plugin_id: plugin_name_for_err.to_string(), message: format!("FFI Error: {:?}", ffi_res) }) }
-                }
-                Err(panic_obj) => {
-                    let panic_msg = if let Some(s_ref) = panic_obj.downcast_ref::<&'static str>() { (*s_ref).to_string() }
-                                    else if let Some(s_obj) = panic_obj.downcast_ref::<String>() { s_obj.clone() }
-                                    else { "Unknown panic reason".to_string() };
-                    Err(PluginSystemError::PreflightCheckFailed{ plugin_id: plugin_name_for_err.to_string(), message: format!("FFI panic: {}", panic_msg) })
-                }
-            }
-        })
-    }
-
-    fn register_stages(&self, registry: &mut StageRegistry) -> std::result::Result<(), PluginSystemError> {
-        let vtable_ptr = self.vtable.0;
-        let plugin_name_for_err = self.name_cache;
-        let result = panic::catch_unwind(std::panic::AssertUnwindSafe(move || unsafe {
-            let vtable = &*vtable_ptr;
-            let registry_ptr = registry as *mut _ as *mut c_void;
-            (vtable.register_stages)(vtable.instance, registry_ptr)
-        }));
-        match result {
-            Ok(ffi_res) => {
-                if ffi_res == FfiResult::Ok { Ok(()) }
-                else { Err(map_ffi_error_loader(ffi_res, plugin_name_for_err, "register_stages")) }
-            }
-            Err(panic_obj) => {
-                let panic_msg = if let Some(s_ref) = panic_obj.downcast_ref::<&'static str>() { (*s_ref).to_string() }
-                                else if let Some(s_obj) = panic_obj.downcast_ref::<String>() { s_obj.clone() }
-                                else { "Unknown panic reason".to_string() };
-                Err(PluginSystemError::FfiError{ plugin_id: plugin_name_for_err.to_string(), operation: "register_stages".to_string(), message: format!("panic: {}", panic_msg) })
-            }
-        }
-    }
-
-    fn shutdown(&self) -> std::result::Result<(), PluginSystemError> {
-        let vtable_ptr = self.vtable.0;
-        let plugin_name_for_err = self.name_cache;
-        let result = panic::catch_unwind(std::panic::AssertUnwindSafe(move || unsafe {
-            let vtable = &*vtable_ptr;
-            (vtable.shutdown)(vtable.instance)
-        }));
-        match result {
-            Ok(ffi_res) => {
-                if ffi_res == FfiResult::Ok { Ok(()) }
-                else { Err(map_ffi_error_loader(ffi_res, plugin_name_for_err, "shutdown")) }
-            }
-            Err(panic_obj) => {
-                let panic_msg = if let Some(s_ref) = panic_obj.downcast_ref::<&'static str>() { (*s_ref).to_string() }
-                                else if let Some(s_obj) = panic_obj.downcast_ref::<String>() { s_obj.clone() }
-                                else { "Unknown panic reason".to_string() };
-                Err(PluginSystemError::FfiError{ plugin_id: plugin_name_for_err.to_string(), operation: "shutdown".to_string(), message: format!("panic: {}", panic_msg) })
-            }
-        }
+/// Serialize a manifest in `format`
+pub(crate) fn serialize_manifest(manifest: &PluginManifest, format: ConfigFormat) -> std::result::Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let raw = RawPluginManifest::from(manifest);
+    match format {
+        ConfigFormat::Json => Ok(serde_json::to_string_pretty(&raw)?),
+        #[cfg(feature = "yaml-config")]
+        ConfigFormat::Yaml => Ok(serde_yaml::to_string(&raw)?),
+        #[cfg(feature = "toml-config")]
+        ConfigFormat::Toml => Ok(toml::to_string_pretty(&raw)?),
     }
 }
 
-// --- End VTablePluginWrapper ---
-
-
 /// Loads plugins from the filesystem or other sources
 #[derive(Clone)]
 pub struct PluginLoader {
@@ -454,105 +147,59 @@ pub struct PluginLoader {
     plugin_dirs: Vec<PathBuf>,
     /// Cached plugin manifests (using the final struct)
     manifests: HashMap<String, PluginManifest>,
+    /// Signature policy and trust store applied before loading
+    signature_verifier: Arc<SignatureVerifier>,
 }
 
 impl PluginLoader {
-    // Synchronous helper function for loading .so plugin
-    fn load_so_plugin_sync_helper(lib_path: &Path) -> KernelResult<Box<dyn Plugin>> {
-        type PluginInitFn = unsafe extern "C-unwind" fn() -> *mut PluginVTable;
-        let library = unsafe { Library::new(lib_path) }
-            .map_err(|e| PluginSystemError::LoadingError {
-                plugin_id: lib_path.to_string_lossy().into_owned(),
-                path: Some(lib_path.to_path_buf()),
-                source: Box::new(crate::plugin_system::error::PluginSystemErrorSource::Other(format!("libloading error: {}", e))),
-            })?; // ? will convert PluginSystemError to KernelError
-        
-        let init_symbol: Symbol<PluginInitFn> = unsafe { library.get(b"_plugin_init\0") }
-            .map_err(|e| PluginSystemError::LoadingError {
-                plugin_id: lib_path.to_string_lossy().into_owned(),
-                path: Some(lib_path.to_path_buf()),
-                source: Box::new(crate::plugin_system::error::PluginSystemErrorSource::Other(format!("missing symbol _plugin_init: {}", e))),
-            })?; // ? will convert PluginSystemError to KernelError
-        
-        // Dereference the Symbol to get the raw function pointer.
-        // The function pointer type PluginInitFn (unsafe extern "C-unwind" fn()) is UnwindSafe.
-        let func_to_call: PluginInitFn = *init_symbol;
-        
-        // The closure now captures func_to_call, which is UnwindSafe.
-        // Thus, AssertUnwindSafe is not strictly needed here if the closure only captures UnwindSafe types.
-        // However, the operation `func_to_call()` is unsafe, so the closure is unsafe.
-        // `catch_unwind` itself can take an unsafe fn if the closure is unsafe.
-        // For maximum clarity and safety, let's keep AssertUnwindSafe if there's any doubt,
-        // or ensure the closure is structured such that it's clearly UnwindSafe.
-        // Since `func_to_call` is `UnwindSafe`, the closure `|| unsafe { func_to_call() }` is also `UnwindSafe`.
-        let vtable_ptr_result = panic::catch_unwind(|| unsafe {
-            func_to_call()
-        });
-        
-        let vtable_ptr = match vtable_ptr_result {
-            Ok(ptr) if !ptr.is_null() => ptr,
-            Err(e) => {
-                let panic_msg = if let Some(s_ref) = e.downcast_ref::<&'static str>() { (*s_ref).to_string() }
-                                else if let Some(s_obj) = e.downcast_ref::<String>() { s_obj.clone() }
-                                else { "Unknown panic reason".to_string() };
-                return Err(KernelError::from(PluginSystemError::FfiError{ plugin_id: lib_path.to_string_lossy().into_owned(), operation: "_plugin_init_loader".to_string(), message: format!("panic: {}", panic_msg) }));
-            }
-            Ok(ptr) if ptr.is_null() => {
-                 return Err(KernelError::from(PluginSystemError::LoadingError{ plugin_id: lib_path.to_string_lossy().into_owned(), path: Some(lib_path.to_path_buf()), source: Box::new(crate::plugin_system::error::PluginSystemErrorSource::Other("Plugin init returned null VTable (loader)".to_string())) }));
-            }
-             _ => unreachable!(),
-        };
-        let plugin_wrapper = unsafe { VTablePluginWrapper::new(vtable_ptr, library).map_err(KernelError::from)? };
-        Ok(Box::new(plugin_wrapper))
-    }
-
     /// Create a new plugin loader
     pub fn new() -> Self {
         Self {
             plugin_dirs: Vec::new(),
             manifests: HashMap::new(),
+            signature_verifier: Arc::new(SignatureVerifier::default()),
         }
     }
 
+    /// Use `verifier` to check plugin signatures before loading (the default allows unsigned plugins)
+    pub fn with_signature_verifier(mut self, verifier: Arc<SignatureVerifier>) -> Self {
+        self.signature_verifier = verifier;
+        self
+    }
+
     /// Add a plugin directory to search
     pub fn add_plugin_dir<P: AsRef<Path>>(&mut self, dir: P) {
         self.plugin_dirs.push(dir.as_ref().to_path_buf());
     }
 
     /// Scan for plugin manifests asynchronously
+    ///
+    /// Directories are scanned in the order they were added. If the same plugin ID is
+    /// found more than once, the first copy wins and later copies are skipped.
     pub async fn scan_for_manifests(&mut self) -> KernelResult<Vec<PluginManifest>> {
-        let mut manifests = Vec::new();
+        let mut manifests: Vec<PluginManifest> = Vec::new();
 
         // Search each plugin directory
-        for dir in &self.plugin_dirs {
-             // Check directory existence asynchronously
-             let dir_exists = match fs::try_exists(dir).await {
-                 Ok(exists) => exists,
-                 Err(e) => {
-                     eprintln!("Error checking existence of plugin directory {}: {}", dir.display(), e);
-                     false // Assume doesn't exist on error
-                 }
-             };
-
-             if !dir_exists {
-                 continue;
-             }
-
-             // Check if it's a directory asynchronously
-             let metadata = match fs::metadata(dir).await {
-                 Ok(meta) => meta,
-                 Err(e) => {
-                     eprintln!("Failed to get metadata for plugin directory {}: {}", dir.display(), e);
-                     continue; // Skip this directory
-                 }
-             };
-
-             if !metadata.is_dir() {
-                 continue;
-             }
-
-            // Scan the directory asynchronously - use the non-recursive function that returns a boxed future
-            self.scan_directory_boxed(dir.clone(), &mut manifests).await?;
+        for dir in self.plugin_dirs.clone() {
+            let found = self.scan_plugin_dir(&dir).await?;
+
+            // Apply precedence: earlier directories win for duplicate plugin IDs
+            for manifest in found {
+                match manifests.iter().find(|existing| existing.id == manifest.id) {
+                    Some(existing) if existing.plugin_base_dir == manifest.plugin_base_dir => {
+                        // Same manifest reached again through an overlapping search directory
+                    }
+                    Some(existing) => {
+                        log::warn!(
+                            "Plugin '{}' in {} is shadowed by the copy in {}; skipping it.",
+                            manifest.id,
+                            manifest.plugin_base_dir.display(),
+                            existing.plugin_base_dir.display()
+                        );
+                    }
+                    None => manifests.push(manifest),
+                }
+            }
         }
 
         // Update the cache
@@ -563,6 +210,73 @@ impl PluginLoader {
         Ok(manifests)
     }
 
+    /// Scan for every plugin manifest, keeping all copies of a plugin ID
+    ///
+    /// Unlike [`scan_for_manifests`](Self::scan_for_manifests), copies of the same plugin found in
+    /// several places are all returned, so a [`DependencyResolver`](crate::plugin_system::resolver::DependencyResolver)
+    /// can choose between versions. Manifests are ordered by directory (in the order they were added),
+    /// newest version first within a directory, which makes the first copy the preferred candidate.
+    pub async fn scan_for_manifest_candidates(&mut self) -> KernelResult<Vec<PluginManifest>> {
+        let mut candidates: Vec<PluginManifest> = Vec::new();
+
+        for dir in self.plugin_dirs.clone() {
+            let mut found = self.scan_plugin_dir(&dir).await?;
+            // Newest first; manifests with unparsable versions go last
+            found.sort_by(|a, b| {
+                let a_version = Version::parse(&a.version).ok();
+                let b_version = Version::parse(&b.version).ok();
+                b_version.cmp(&a_version).then_with(|| a.id.cmp(&b.id))
+            });
+            for manifest in found {
+                // Skip the same manifest reached again through an overlapping search directory
+                if !candidates.iter().any(|existing| existing.id == manifest.id && existing.plugin_base_dir == manifest.plugin_base_dir) {
+                    candidates.push(manifest);
+                }
+            }
+        }
+
+        // Cache the preferred copy of each plugin
+        for manifest in &candidates {
+            self.manifests.entry(manifest.id.clone()).or_insert_with(|| manifest.clone());
+        }
+
+        Ok(candidates)
+    }
+
+    /// Scan a single plugin directory, returning no manifests if it does not exist
+    async fn scan_plugin_dir(&self, dir: &Path) -> KernelResult<Vec<PluginManifest>> {
+        // Check directory existence asynchronously
+        let dir_exists = match fs::try_exists(dir).await {
+            Ok(exists) => exists,
+            Err(e) => {
+                eprintln!("Error checking existence of plugin directory {}: {}", dir.display(), e);
+                false // Assume doesn't exist on error
+            }
+        };
+
+        if !dir_exists {
+            return Ok(Vec::new());
+        }
+
+        // Check if it's a directory asynchronously
+        let metadata = match fs::metadata(dir).await {
+            Ok(meta) => meta,
+            Err(e) => {
+                eprintln!("Failed to get metadata for plugin directory {}: {}", dir.display(), e);
+                return Ok(Vec::new()); // Skip this directory
+            }
+        };
+
+        if !metadata.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        // Scan the directory asynchronously - use the non-recursive function that returns a boxed future
+        let mut found = Vec::new();
+        self.scan_directory_boxed(dir.to_path_buf(), &mut found).await?;
+        Ok(found)
+    }
+
     /// Helper function that returns a boxed future for recursive scanning
     fn scan_directory_boxed<'a>(
         &'a self,
@@ -593,36 +307,17 @@ impl PluginLoader {
             };
 
             if metadata.is_dir() {
-                // Look for manifest.json in this directory
-                let manifest_path = entry_path.join("manifest.json");
-
-                // Check existence and if it's a file asynchronously
-                let manifest_exists = match fs::try_exists(&manifest_path).await {
-                    Ok(exists) => exists,
-                    Err(e) => {
-                        eprintln!("Error checking existence of {}: {}", manifest_path.display(), e);
-                        false // Assume not found on error
-                    }
-                };
-
-                if manifest_exists {
-                     // Double check it's a file (try_exists doesn't guarantee)
-                     let manifest_meta = match fs::metadata(&manifest_path).await {
-                         Ok(meta) => Some(meta),
-                         Err(_) => None, // Ignore error if we can't get metadata
-                     };
-
-                     if manifest_meta.map_or(false, |m| m.is_file()) {
-                        match self.load_manifest(&manifest_path).await {
-                            Ok(manifest) => manifests.push(manifest),
-                            Err(e) => {
-                                eprintln!(
-                                    "Error loading manifest from {}: {}",
-                                    manifest_path.display(), e
-                                );
-                            }
+                // Look for a manifest file in this directory
+                if let Some(manifest_path) = Self::find_manifest_file(&entry_path).await {
+                    match self.load_manifest(&manifest_path).await {
+                        Ok(manifest) => manifests.push(manifest),
+                        Err(e) => {
+                            eprintln!(
+                                "Error loading manifest from {}: {}",
+                                manifest_path.display(), e
+                            );
                         }
-                     }
+                    }
                 }
 
                 // Recursively scan subdirectories asynchronously
@@ -640,6 +335,28 @@ impl PluginLoader {
         Ok(())
     }
 
+    /// The manifest file in `dir`, the first of [`MANIFEST_FILE_NAMES`] that exists.
+    /// Other manifest files in the same directory are ignored with a warning.
+    async fn find_manifest_file(dir: &Path) -> Option<PathBuf> {
+        let mut found: Option<PathBuf> = None;
+        for name in MANIFEST_FILE_NAMES {
+            let path = dir.join(name);
+            // A directory named like a manifest is not one
+            if !fs::metadata(&path).await.is_ok_and(|meta| meta.is_file()) {
+                continue;
+            }
+            match &found {
+                Some(manifest_path) => log::warn!(
+                    "Ignoring {}; the plugin's manifest is {}",
+                    path.display(),
+                    manifest_path.display()
+                ),
+                None => found = Some(path),
+            }
+        }
+        found
+    }
+
     /// Load a plugin manifest from a file asynchronously
     async fn load_manifest<P: AsRef<Path>>(&self, path: P) -> KernelResult<PluginManifest> { // Return KernelResult
         let path_ref = path.as_ref();
@@ -649,14 +366,27 @@ impl PluginLoader {
         let content = fs::read_to_string(path_ref).await
             .map_err(|e| KernelError::io(e, "read_manifest", path_ref.to_path_buf()))?; // Use Error::io
 
-        // Parse the JSON content into the intermediate raw struct
-        // Explicitly type the variable receiving the result of from_str
-        let raw_manifest: RawPluginManifest = serde_json::from_str(&content)
+        Ok(Self::parse_manifest(&content, path_ref)?)
+    }
+
+    /// Parse and validate a manifest as if it had been read from `path`.
+    /// The format follows the extension of `path` (`.toml`, `.yaml` or `.yml`), and is JSON otherwise.
+    /// The manifest's `plugin_base_dir` is set to the parent directory of `path`.
+    pub fn parse_manifest(content: &str, path: &Path) -> std::result::Result<PluginManifest, PluginSystemError> {
+        Self::parse_manifest_as(content, path, ConfigFormat::from_path(path).unwrap_or(ConfigFormat::Json))
+    }
+
+    /// Parse and validate a manifest written in `format` as if it had been read from `path`.
+    pub fn parse_manifest_as(content: &str, path: &Path, format: ConfigFormat) -> std::result::Result<PluginManifest, PluginSystemError> {
+        let path_ref = path;
+
+        // Parse the content into the intermediate raw struct
+        let raw_manifest: RawPluginManifest = deserialize_manifest(content, format)
             .map_err(|e| PluginSystemError::ManifestError {
                 path: path_ref.to_path_buf(),
-                message: format!("Failed to parse manifest JSON: {}", e),
-                source: Some(Box::new(e)),
-            })?; // ? will convert PluginSystemError to KernelError
+                message: format!("Failed to parse manifest {}: {}", format.extension().to_uppercase(), e),
+                source: Some(e),
+            })?;
 
         // Convert RawPluginManifest to PluginManifest, parsing versions
         let plugin_base_dir = path_ref.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
@@ -677,13 +407,20 @@ impl PluginLoader {
             files: raw_manifest.files,
             config_schema: raw_manifest.config_schema,
             tags: raw_manifest.tags,
-            // Add default empty values for new fields
-            conflicts_with: Vec::new(),
-            incompatible_with: Vec::new(),
-            resources: raw_manifest.resources.unwrap_or_default(),
+            conflicts_with: raw_manifest.conflicts_with,
+            incompatible_with: Vec::new(), // Initialize empty, fill below
+            resources: raw_manifest.resources,
             plugin_base_dir,
         };
 
+        if let Some(claim) = final_manifest.resources.iter().find(|claim| claim.resource.id.trim().is_empty()) {
+            return Err(PluginSystemError::ManifestError {
+                path: path_ref.to_path_buf(),
+                message: format!("Resource claim of type '{}' has an empty identifier", claim.resource.kind),
+                source: None,
+            });
+        }
+
         // Parse API version strings
         for api_ver_str in raw_manifest.api_versions {
             match VersionRange::from_str(api_ver_str.as_str()) { // Use .as_str()
@@ -692,64 +429,98 @@ impl PluginLoader {
                     path: path_ref.to_path_buf(),
                     message: format!("Failed to parse API version range '{}': {}", api_ver_str, e),
                     source: None, // VersionError is not Error, so can't box directly
-                }.into()), // Convert to KernelError
+                }),
             }
         }
 
         // Parse dependency version strings
         for raw_dep in raw_manifest.dependencies {
-            let version_range = match raw_dep.version_range {
-                Some(vr_str) => { // vr_str is String
-                    match VersionRange::from_str(vr_str.as_str()) { // Use .as_str()
-                        Ok(vr) => Some(vr),
-                        Err(e) => return Err(PluginSystemError::ManifestError{
-                            path: path_ref.to_path_buf(),
-                            message: format!("Failed to parse dependency version range '{}' for dep '{}': {}", vr_str, raw_dep.id, e),
-                            source: None, // VersionError is not Error
-                        }.into()), // Convert to KernelError
-                    }
-                }
-                None => None,
-            };
-            // Use PluginDependency struct literal directly
-            final_manifest.dependencies.push(crate::plugin_system::dependency::PluginDependency {
-                plugin_name: raw_dep.id, // Use plugin_name field
-                version_range,
-                required: raw_dep.required,
-            });
+            let dependency = Self::parse_dependency(raw_dep, "dependency", path_ref)?;
+            final_manifest.dependencies.push(dependency);
+        }
+        for raw_dep in raw_manifest.incompatible_with {
+            let incompatibility = Self::parse_dependency(raw_dep, "incompatibility", path_ref)?;
+            final_manifest.incompatible_with.push(incompatibility);
         }
 
         Ok(final_manifest)
     }
 
-    // Removed unused load_so_plugin_sync method (it's now load_so_plugin_sync_helper)
+    fn parse_dependency(raw_dep: RawDependencyInfo, kind: &str, path: &Path) -> std::result::Result<PluginDependency, PluginSystemError> {
+        let version_range = match raw_dep.version_range {
+            Some(vr_str) => match VersionRange::from_str(vr_str.as_str()) {
+                Ok(vr) => Some(vr),
+                Err(e) => return Err(PluginSystemError::ManifestError {
+                    path: path.to_path_buf(),
+                    message: format!("Failed to parse {} version range '{}' for dep '{}': {}", kind, vr_str, raw_dep.id, e),
+                    source: None, // VersionError is not Error
+                }),
+            },
+            None => None,
+        };
+        Ok(PluginDependency {
+            plugin_name: raw_dep.id,
+            version_range,
+            required: raw_dep.required,
+        })
+    }
 
-    /// Load a specific plugin asynchronously
-    pub async fn load_plugin(&self, manifest: &PluginManifest) -> KernelResult<Arc<dyn Plugin>> { // Return KernelResult
-        let entry_point_name = &manifest.entry_point;
-        let base_dir = manifest.plugin_base_dir.clone();
-        let owned_entry_point_name = entry_point_name.clone();
-        let plugin_id_clone = manifest.id.clone(); // Used for error reporting
-        
-        let library_path = base_dir.join(&owned_entry_point_name);
+    // Removed unused load_so_plugin_sync method; native and WASM loading go through load_entry_point
+
+    /// Resolves the path of a manifest's entry point library.
+    /// The entry point must be relative to the plugin's base directory and must not traverse upwards.
+    pub(crate) fn entry_point_path(manifest: &PluginManifest) -> std::result::Result<PathBuf, PluginSystemError> {
+        let library_path = manifest.plugin_base_dir.join(&manifest.entry_point);
 
-        if owned_entry_point_name.contains("..") || Path::new(&owned_entry_point_name).is_absolute() {
+        if manifest.entry_point.contains("..") || Path::new(&manifest.entry_point).is_absolute() {
             return Err(PluginSystemError::LoadingError {
-                plugin_id: plugin_id_clone,
+                plugin_id: manifest.id.clone(),
                 path: Some(library_path),
                 source: Box::new(crate::plugin_system::error::PluginSystemErrorSource::Other(
-                    format!("Invalid entry_point path '{}': must be relative and not traverse upwards.", owned_entry_point_name)
+                    format!("Invalid entry_point path '{}': must be relative and not traverse upwards.", manifest.entry_point)
                 )),
-            }.into()); // Convert to KernelError
+            });
         }
-        
-        // Call the synchronous helper directly.
-        // This is generally okay for quick operations like FFI loading that might fail fast.
-        // For potentially long-running synchronous CPU-bound work, spawn_blocking is preferred.
-        // The `AssertUnwindSafe` is inside `load_so_plugin_sync_helper` around the FFI call.
-        match Self::load_so_plugin_sync_helper(&library_path) {
+        Ok(library_path)
+    }
+
+    /// Whether a manifest's entry point is a WebAssembly module rather than a native library.
+    pub fn is_wasm_entry_point(manifest: &PluginManifest) -> bool {
+        Path::new(&manifest.entry_point)
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("wasm"))
+    }
+
+    /// Loads a manifest's entry point: `.wasm` modules go to the WebAssembly runtime,
+    /// anything else is opened as a native plugin library.
+    /// The plugin's signature is checked against `verifier` first, before any of its code is loaded.
+    pub(crate) fn load_entry_point(manifest: &PluginManifest, verifier: &SignatureVerifier) -> std::result::Result<Box<dyn Plugin>, PluginSystemError> {
+        let library_path = Self::entry_point_path(manifest)?;
+        verifier.check(manifest)?;
+        if Self::is_wasm_entry_point(manifest) {
+            #[cfg(feature = "wasm-plugins")]
+            return crate::plugin_system::wasm::WasmPlugin::from_manifest(manifest)
+                .map(|plugin| Box::new(plugin) as Box<dyn Plugin>);
+            #[cfg(not(feature = "wasm-plugins"))]
+            return Err(PluginSystemError::LoadingError {
+                plugin_id: manifest.id.clone(),
+                path: Some(library_path),
+                source: Box::new(crate::plugin_system::error::PluginSystemErrorSource::Other(
+                    "WebAssembly plugins require the 'wasm-plugins' feature".to_string()
+                )),
+            });
+        }
+        // Panics in `_plugin_init` are caught inside `ffi_host::load_plugin_library`.
+        ffi_host::load_plugin_library(&library_path)
+    }
+
+    /// Load a specific plugin asynchronously
+    pub async fn load_plugin(&self, manifest: &PluginManifest) -> KernelResult<Arc<dyn Plugin>> { // Return KernelResult
+        // Loading is synchronous; this is generally okay for quick operations like FFI loading
+        // that might fail fast. For long-running CPU-bound work, spawn_blocking is preferred.
+        match Self::load_entry_point(manifest, &self.signature_verifier) {
             Ok(boxed_plugin) => Ok(Arc::from(boxed_plugin)),
-            Err(e) => Err(e),
+            Err(e) => Err(KernelError::from(e)),
         }
     }
 
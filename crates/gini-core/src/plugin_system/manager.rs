@@ -1,550 +1,102 @@
 // crates/gini-core/src/plugin_system/manager.rs
 use std::fmt::Debug;
-use std::future::Future; // Import Future
-use std::pin::Pin; // Import Pin
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::{Duration, SystemTime}; // Added for plugin watch mode
 use async_trait::async_trait;
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex};
 use std::fs;
-use libloading::{Library, Symbol};
-use std::panic;
-use std::ffi::{CStr, c_void};
-use std::os::raw::c_char;
 use log; // Added for logging
 
 use crate::kernel::bootstrap::Application;
-use crate::stage_manager::context::StageContext;
 // Removed unused: use crate::stage_manager::Stage;
 use crate::stage_manager::registry::StageRegistry; // Added for register_stages
-use crate::stage_manager::requirement::StageRequirement;
 use crate::plugin_system::loader::PluginLoader; // Added for PluginLoader
-use crate::plugin_system::conflict::ConflictManager; // Added for ConflictManager
+use crate::plugin_system::conflict::{ConflictDecision, ConflictManager, ConflictPolicy, PluginConflict, ResolutionStrategy}; // Added for ConflictManager
+use crate::plugin_system::resolver::{DependencyResolver, Incompatibility}; // Chooses plugin versions at startup
+use crate::plugin_system::search_path::{PluginSearchPaths, PluginPathSource}; // Added for configurable plugin dirs
 
 use crate::kernel::component::KernelComponent;
+use crate::event::{EventManager, SystemEvent}; // Added for hot-reload events
 use crate::storage::config::{ConfigManager, ConfigScope};
+use crate::storage::schema::PluginConfigReport; // Shown by `gini plugin config`
 // Removed unused StorageProvider import
 use crate::kernel::error::{Error, Result as KernelResult, KernelLifecyclePhase}; // Crate's Result alias, renamed to avoid conflict
 use crate::plugin_system::error::{PluginSystemError, PluginSystemErrorSource}; // Import new error types
-use crate::plugin_system::traits::{
-    FfiResult, FfiSlice, FfiVersionRange, FfiPluginDependency,
-    FfiStageRequirement, PluginVTable,
-    // PluginError, // Removed old PluginError
-};
-use crate::plugin_system::{Plugin, PluginManifest, ApiVersion, PluginRegistry, PluginPriority, VersionRange, PluginDependency};
+use crate::plugin_system::ffi_host; // Shared FFI host for dynamic plugin libraries
+use crate::plugin_system::sandbox::{SandboxConfig, SandboxedPlugin}; // Out-of-process plugin hosts
+use crate::plugin_system::permission::{PermissionEnforcement, PermissionManager}; // Runtime capability checks
+use crate::plugin_system::lifecycle::PluginLifecycle; // Plugin states shown by the CLI
+use crate::plugin_system::service::ServiceRegistry; // Typed APIs plugins publish for each other
+use crate::plugin_system::startup::InitFailurePolicy; // Whether a failing plugin stops startup
+use crate::plugin_system::signature::{SignaturePolicy, SignatureVerifier, VerificationStatus}; // Checked before plugin code is loaded
+use crate::plugin_system::{Plugin, PluginManifest, ApiVersion, PluginRegistry};
+use crate::ui_bridge::UnifiedUiManager; // Asks the user about unresolved conflicts
 use crate::kernel::constants;
 
 
 const CORE_SETTINGS_CONFIG_NAME: &str = "core_settings"; // Config file name for core settings
 
 
-// --- Local FFI Helper Functions ---
 
-/// Maps FfiResult to PluginSystemError
-fn map_ffi_error(ffi_err: FfiResult, plugin_id: &str, operation: &str) -> PluginSystemError {
-    PluginSystemError::FfiError {
-        plugin_id: plugin_id.to_string(),
-        operation: operation.to_string(),
-        message: format!("{:?}", ffi_err),
-    }
-}
 
-/// Safely converts an FFI C string pointer to a Rust String.
-/// # Safety
-/// The caller must ensure that `ptr` is a valid pointer to a null-terminated
-/// C string, and that it remains valid for the duration of this function call.
-/// The string data is expected to be UTF-8 encoded.
-unsafe fn ffi_string_from_ptr(ptr: *const c_char) -> std::result::Result<String, FfiResult> { unsafe { // Explicit std::result
-    if ptr.is_null() {
-        return Err(FfiResult::NullPointer);
-    }
-    CStr::from_ptr(ptr)
-        .to_str()
-        .map(|s| s.to_owned())
-        .map_err(|_| FfiResult::Utf8Error)
-}}
-
-/// Safely converts an FFI C string pointer (which can be null) to an Option<String>.
-/// # Safety
-/// The caller must ensure that if `ptr` is non-null, it is a valid pointer to a
-/// null-terminated C string, and that it remains valid for the duration of this
-/// function call. The string data is expected to be UTF-8 encoded.
-unsafe fn ffi_opt_string_from_ptr(ptr: *const c_char) -> std::result::Result<Option<String>, FfiResult> { unsafe { // Explicit std::result
-    if ptr.is_null() {
-        Ok(None)
-    } else {
-        CStr::from_ptr(ptr)
-            .to_str()
-            .map(|s| Some(s.to_owned()))
-            .map_err(|_| FfiResult::Utf8Error)
-    }
-}}
-
-// --- VTablePluginWrapper ---
-
-#[derive(Debug, Clone, Copy)]
-struct UnsafeVTablePtr(*const PluginVTable);
-unsafe impl Send for UnsafeVTablePtr {}
-unsafe impl Sync for UnsafeVTablePtr {}
-
-// Wrapper needs to hold the Library to ensure it's dropped correctly
-#[derive(Debug)]
-struct VTablePluginWrapper {
-    vtable: UnsafeVTablePtr,
-    library: Option<Library>, // Store the library itself
-    name_cache: &'static str, // Store as static str after leaking once
-    version_cache: String,
-    is_core_cache: bool,
-    priority_cache: PluginPriority,
+const DISABLED_PLUGINS_KEY: &str = "core.plugins.disabled";
+const PLUGIN_SEARCH_PATHS_KEY: &str = "core.plugins.search_paths"; // List of plugin directories in core settings
+const LAZY_LOADING_KEY: &str = "core.plugins.lazy_loading"; // Register plugins from manifests, load libraries on first use
+const SANDBOXED_PLUGINS_KEY: &str = "core.plugins.sandboxed"; // Plugin IDs run in a separate plugin host process
+const SIGNATURE_POLICY_KEY: &str = "core.plugins.signature_policy"; // allow-unsigned, warn or enforce
+const ENABLED_PLUGINS_KEY: &str = "core.plugins.enabled"; // Plugins the user explicitly enabled
+const CONFLICT_POLICIES_KEY: &str = "core.plugins.conflict_policies"; // Ordered ConflictPolicy rules
+const CONFLICT_DECISIONS_KEY: &str = "core.plugins.conflict_decisions"; // Disabled plugin -> why it was disabled
+const ALLOWED_CONFLICTS_KEY: &str = "core.plugins.allowed_conflicts"; // Plugin pairs the user allowed to run together
+const INIT_FAILURE_POLICY_KEY: &str = "core.plugins.init_failure_policy"; // isolate or fail-fast
+const CONFLICT_PROMPT_SOURCE: &str = "PluginManager"; // Source of conflict questions sent to the UI
+
+/// On-disk state of a dynamically loaded plugin, tracked for watch mode.
+#[derive(Debug, Clone)]
+struct WatchedPlugin {
+    /// Path to the plugin's entry point library (.so).
+    entry_point: PathBuf,
+    /// Last observed modification time of the entry point.
+    last_modified: Option<SystemTime>,
+    /// How the entry point is loaded again on reload.
+    runtime: PluginRuntime,
+    /// Manifest the plugin was loaded from; `None` for libraries loaded by path.
+    manifest: Option<Box<PluginManifest>>,
 }
 
-impl VTablePluginWrapper {
-    /// Creates a new wrapper from a VTable pointer and the loaded Library.
-    /// Takes ownership of the Library.
-    /// # Safety
-    /// The `vtable_ptr` must be a valid pointer to a `PluginVTable` allocated by the plugin
-    /// (e.g., via `Box::into_raw`) and returned by its `_plugin_init` function.
-    /// The `PluginVTable` and its `instance` pointer must remain valid until `destroy` is called
-    /// on the VTable, which happens in `VTablePluginWrapper::drop`.
-    /// The `library` argument must be the `libloading::Library` from which `vtable_ptr` was obtained.
-    unsafe fn new(vtable_ptr: *mut PluginVTable, library: Library, plugin_path_context: Option<PathBuf>) -> KernelResult<Self> {
-        if vtable_ptr.is_null() {
-            drop(library);
-            return Err(Error::from(PluginSystemError::LoadingError {
-                plugin_id: plugin_path_context.as_ref().map(|p| p.to_string_lossy().into_owned()).unwrap_or_else(|| "<unknown_on_null_vtable>".to_string()),
-                path: plugin_path_context,
-                source: Box::new(PluginSystemErrorSource::Other(
-                    "Received null VTable pointer from plugin init.".to_string(),
-                )),
-            }));
-        }
-
-        let vtable_ref = unsafe { &*vtable_ptr };
-        let instance_const = vtable_ref.instance as *const c_void;
-        println!("[GINI_FFI_DEBUG] VTablePluginWrapper::new - instance_const: {:?}", instance_const);
-
-        let name_ptr = (vtable_ref.name)(instance_const);
-        let name_string = unsafe { ffi_string_from_ptr(name_ptr) }.map_err(|e| {
-            Error::from(map_ffi_error(
-                e,
-                plugin_path_context.as_ref().map(|p| p.to_string_lossy().into_owned()).unwrap_or_else(|| "<unknown_during_name_fetch>".to_string()).as_str(),
-                "getting plugin name",
-            ))
-        })?;
-        (vtable_ref.free_name)(name_ptr as *mut c_char);
-        let static_name: &'static str = Box::leak(name_string.into_boxed_str());
-        println!("[GINI_FFI_DEBUG] Plugin name (static_name): {}", static_name);
-
-        let version_ptr = (vtable_ref.version)(instance_const);
-        let version = unsafe { ffi_string_from_ptr(version_ptr) }
-            .map_err(|e| Error::from(map_ffi_error(e, static_name, "getting plugin version")))?;
-        (vtable_ref.free_version)(version_ptr as *mut c_char);
-        println!("[GINI_FFI_DEBUG] VTable 'free_version' called. Plugin version: {}", version);
-
-        let is_core = (vtable_ref.is_core)(instance_const);
-        println!("[GINI_FFI_DEBUG] VTable 'is_core' returned: {}", is_core);
-
-        let ffi_priority = (vtable_ref.priority)(instance_const);
-        let priority = ffi_priority.to_plugin_priority().ok_or_else(|| {
-            Error::from(PluginSystemError::LoadingError {
-                plugin_id: static_name.to_string(),
-                path: plugin_path_context.clone(),
-                source: Box::new(PluginSystemErrorSource::Other(format!(
-                    "Invalid FFI priority value received: {:?}",
-                    ffi_priority
-                ))),
-            })
-        })?;
-        println!("[GINI_FFI_DEBUG] Parsed priority: {:?}", priority);
-
-        Ok(Self {
-            vtable: UnsafeVTablePtr(vtable_ptr),
-            library: Some(library),
-            name_cache: static_name,
-            version_cache: version,
-            is_core_cache: is_core,
-            priority_cache: priority,
-        })
-    }
-
-    unsafe fn get_vector_from_ffi_slice<T, R, F>(
-        &self,
-        get_slice_fn: extern "C" fn(instance: *const c_void) -> FfiSlice<T>,
-        free_slice_fn: extern "C" fn(slice: FfiSlice<T>),
-        converter: F,
-    ) -> std::result::Result<Vec<R>, PluginSystemError>
-    where
-        T: Copy,
-        F: Fn(T) -> std::result::Result<R, PluginSystemError>,
-    {
-        let vtable = unsafe { &*self.vtable.0 };
-        let instance_const = vtable.instance as *const c_void;
-        let ffi_slice = (get_slice_fn)(instance_const);
-        
-        let result = unsafe { ffi_slice.as_slice() }.map_or_else(
-            || Ok(Vec::new()),
-            |slice_data| {
-                slice_data.iter()
-                    .map(|&item| converter(item))
-                    .collect::<std::result::Result<Vec<R>, PluginSystemError>>()
-            },
-        );
-        (free_slice_fn)(ffi_slice);
-        result
-    }
+/// How a dynamic plugin's entry point is run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PluginRuntime {
+    /// Native library loaded into this process.
+    Native,
+    /// Native library loaded into a separate plugin host process.
+    Sandboxed,
+    /// WebAssembly module; needs its manifest, which supplies the plugin's metadata.
+    Wasm,
 }
 
-impl Drop for VTablePluginWrapper {
-    fn drop(&mut self) {
-        println!("[GINI_FFI_DEBUG] Dropping VTablePluginWrapper for '{}'", self.name_cache);
-        unsafe {
-            if !self.vtable.0.is_null() {
-                let vtable_ref = &*self.vtable.0;
-                let instance_ptr = vtable_ref.instance;
-                println!("[GINI_FFI_DEBUG] Drop: Calling FFI destroy function for plugin instance ({:?}) via VTable ({:?})", instance_ptr, self.vtable.0);
-                (vtable_ref.destroy)(instance_ptr);
-                println!("[GINI_FFI_DEBUG] Drop: FFI destroy function called.");
-
-                println!("[GINI_FFI_DEBUG] Drop: Reconstructing VTable Box from raw ptr ({:?}) to free its memory...", self.vtable.0);
-                let _vtable_box = Box::from_raw(self.vtable.0 as *mut PluginVTable);
-                println!("[GINI_FFI_DEBUG] Drop: VTable Box reconstructed and will be dropped.");
-                self.vtable.0 = std::ptr::null_mut();
-            } else {
-                 println!("[GINI_FFI_DEBUG] Drop: VTable pointer was null, skipping instance/VTable destruction.");
-            }
-            println!("[GINI_FFI_DEBUG] Drop: Dropping Library Option (unloads .so file)...");
-            drop(self.library.take());
-            println!("[GINI_FFI_DEBUG] Drop: Library Option dropped.");
+impl PluginRuntime {
+    /// How far the permissions of a plugin run this way are enforced.
+    fn permission_enforcement(self) -> PermissionEnforcement {
+        match self {
+            PluginRuntime::Native => PermissionEnforcement::NotSandboxed,
+            PluginRuntime::Sandboxed => PermissionEnforcement::ProcessIsolated,
+            PluginRuntime::Wasm => PermissionEnforcement::Enforced,
         }
-        println!("[GINI_FFI_DEBUG] Finished dropping VTablePluginWrapper for '{}'", self.name_cache);
     }
 }
 
-impl Plugin for VTablePluginWrapper {
-    fn name(&self) -> &'static str {
-        self.name_cache
-    }
-
-    fn version(&self) -> &str {
-        &self.version_cache
-    }
-
-    fn is_core(&self) -> bool {
-        self.is_core_cache
-    }
-
-    fn priority(&self) -> PluginPriority {
-        self.priority_cache.clone()
-    }
-
-    fn compatible_api_versions(&self) -> Vec<VersionRange> {
-        unsafe {
-            self.get_vector_from_ffi_slice(
-                (*self.vtable.0).compatible_api_versions,
-                (*self.vtable.0).free_compatible_api_versions,
-                |ffi_range: FfiVersionRange| {
-                    let constraint_str_ptr = ffi_range.constraint;
-                    let constraint = ffi_string_from_ptr(constraint_str_ptr).map_err(|e| {
-                        map_ffi_error(
-                            e,
-                            self.name_cache,
-                            "getting compatible_api_versions constraint",
-                        )
-                    })?;
-                    VersionRange::from_constraint(&constraint).map_err(PluginSystemError::from)
-                },
-            )
-            .unwrap_or_else(|e| {
-                eprintln!(
-                    "Error getting compatible API versions for plugin '{}': {}",
-                    self.name_cache, e
-                );
-                Vec::new()
-            })
-        }
-    }
-
-    fn dependencies(&self) -> Vec<PluginDependency> {
-        unsafe {
-            self.get_vector_from_ffi_slice(
-                (*self.vtable.0).dependencies,
-                (*self.vtable.0).free_dependencies,
-                |ffi_dep: FfiPluginDependency| {
-                    let name = ffi_string_from_ptr(ffi_dep.plugin_name).map_err(|e| {
-                        map_ffi_error(e, self.name_cache, "getting dependency name")
-                    })?;
-                    let version_constraint_str = ffi_opt_string_from_ptr(ffi_dep.version_constraint)
-                        .map_err(|e| {
-                            map_ffi_error(
-                                e,
-                                self.name_cache,
-                                "getting dependency version constraint",
-                            )
-                        })?;
-                    let version_range = version_constraint_str
-                        .map(|s| VersionRange::from_constraint(s.as_str()))
-                        .transpose()
-                        .map_err(PluginSystemError::from)?;
-
-                    Ok(PluginDependency {
-                        plugin_name: name,
-                        version_range: version_range,
-                        required: ffi_dep.required,
-                    })
-                },
-            )
-            .unwrap_or_else(|e| {
-                eprintln!("Error getting dependencies for plugin '{}': {}", self.name_cache, e);
-                Vec::new()
-            })
-        }
-    }
-
-     fn required_stages(&self) -> Vec<StageRequirement> {
-        unsafe {
-            self.get_vector_from_ffi_slice(
-                (*self.vtable.0).required_stages,
-                (*self.vtable.0).free_required_stages,
-                |ffi_req: FfiStageRequirement| {
-                    let id = ffi_string_from_ptr(ffi_req.stage_id).map_err(|e| {
-                        map_ffi_error(e, self.name_cache, "getting required_stages id")
-                    })?;
-                    Ok(StageRequirement {
-                        stage_id: id,
-                        required: ffi_req.required,
-                        provided: ffi_req.provided,
-                    })
-                },
-            )
-            .unwrap_or_else(|e| {
-                eprintln!("Error getting required stages for plugin '{}': {}", self.name_cache, e);
-                Vec::new()
-            })
-        }
-    }
-
-    fn conflicts_with(&self) -> Vec<String> {
-        unsafe {
-            self.get_vector_from_ffi_slice(
-                (*self.vtable.0).conflicts_with,
-                (*self.vtable.0).free_conflicts_with,
-                |ffi_str_ptr: *const c_char| {
-                    ffi_string_from_ptr(ffi_str_ptr).map_err(|e| {
-                        map_ffi_error(e, self.name_cache, "getting conflicts_with string")
-                    })
-                },
-            )
-            .unwrap_or_else(|e| {
-                eprintln!("Error getting conflicts_with for plugin '{}': {}", self.name_cache, e);
-                Vec::new()
-            })
-        }
-    }
-
-    fn incompatible_with(&self) -> Vec<PluginDependency> {
-        unsafe {
-            self.get_vector_from_ffi_slice(
-                (*self.vtable.0).incompatible_with,
-                (*self.vtable.0).free_incompatible_with,
-                |ffi_dep: FfiPluginDependency| {
-                    let name = ffi_string_from_ptr(ffi_dep.plugin_name).map_err(|e| {
-                        map_ffi_error(e, self.name_cache, "getting incompatible_with name")
-                    })?;
-                    let version_constraint_str =
-                        ffi_opt_string_from_ptr(ffi_dep.version_constraint).map_err(|e| {
-                            map_ffi_error(
-                                e,
-                                self.name_cache,
-                                "getting incompatible_with version constraint",
-                            )
-                        })?;
-                    let version_range = version_constraint_str
-                        .map(|s| VersionRange::from_constraint(s.as_str()))
-                        .transpose()
-                        .map_err(PluginSystemError::from)?;
-
-                    Ok(PluginDependency {
-                        plugin_name: name,
-                        version_range: version_range,
-                        required: ffi_dep.required,
-                    })
-                },
-            )
-            .unwrap_or_else(|e| {
-                eprintln!(
-                    "Error getting incompatible_with for plugin '{}': {}",
-                    self.name_cache, e
-                );
-                Vec::new()
-            })
-        }
-    }
-
-    fn init(&self, app: &mut Application) -> std::result::Result<(), PluginSystemError> {
-        let vtable_ptr = self.vtable.0;
-        let plugin_name_for_err = self.name_cache;
-
-        let result = panic::catch_unwind(std::panic::AssertUnwindSafe(move || unsafe {
-            let vtable = &*vtable_ptr;
-            let app_ptr = app as *mut _ as *mut c_void;
-            (vtable.init)(vtable.instance, app_ptr)
-        }));
-
-        match result {
-            Ok(ffi_res) => {
-                if ffi_res == FfiResult::Ok {
-                    Ok(())
-                } else {
-                    Err(map_ffi_error(ffi_res, plugin_name_for_err, "init"))
-                }
-            }
-            Err(panic_obj) => {
-                let panic_msg = if let Some(s_ref) = panic_obj.downcast_ref::<&'static str>() {
-                    (*s_ref).to_string()
-                } else if let Some(s_obj) = panic_obj.downcast_ref::<String>() {
-                    s_obj.clone()
-                } else {
-                    "Unknown panic reason".to_string()
-                };
-                eprintln!("[GINI_FFI_DEBUG] Panic in FFI 'init' for plugin '{}': {}", plugin_name_for_err, panic_msg);
-                Err(PluginSystemError::FfiError {
-                    plugin_id: plugin_name_for_err.to_string(),
-                    operation: "init".to_string(),
-                    message: format!("panic: {}", panic_msg),
-                })
-            }
-        }
-    }
-
-    // #[must_use = "The result of preflight_check should be handled"] // Removed as per compiler warning
-    fn preflight_check<'life0, 'life1, 'async_trait>(
-        &'life0 self,
-        context: &'life1 StageContext
-    ) -> Pin<Box<dyn Future<Output = std::result::Result<(), PluginSystemError>> + Send + 'async_trait>>
-    where
-        'life0: 'async_trait,
-        'life1: 'async_trait,
-        Self: 'async_trait,
-    {
-        let vtable_ptr = self.vtable.0;
-        let _plugin_name_for_err = self.name_cache; // Prefixed as it's unused in this specific function after recent changes
-
-        let ffi_call_result = panic::catch_unwind(std::panic::AssertUnwindSafe(move || unsafe {
-            let vtable = &*vtable_ptr;
-            let context_ptr = context as *const _ as *const c_void;
-            (vtable.preflight_check)(vtable.instance, context_ptr)
-        }));
-
-        let plugin_name = self.name_cache;
-
-        Box::pin(async move {
-            match ffi_call_result {
-                Ok(ffi_res) => {
-                    if ffi_res == FfiResult::Ok {
-                        Ok(())
-                    } else {
-                        Err(PluginSystemError::PreflightCheckFailed {
-                            plugin_id: plugin_name.to_string(),
-                            message: format!("FFI Error: {:?}", ffi_res),
-                        })
-                    }
-                }
-                Err(panic_obj) => {
-                    let panic_msg = if let Some(s_ref) = panic_obj.downcast_ref::<&'static str>() {
-                        (*s_ref).to_string()
-                    } else if let Some(s_obj) = panic_obj.downcast_ref::<String>() {
-                        s_obj.clone()
-                    } else {
-                        "Unknown panic reason".to_string()
-                    };
-                    eprintln!("[GINI_FFI_DEBUG] Panic in FFI 'preflight_check' for plugin '{}': {}", plugin_name, panic_msg);
-                    Err(PluginSystemError::PreflightCheckFailed {
-                        plugin_id: plugin_name.to_string(),
-                        message: format!("FFI panic: {}", panic_msg),
-                    })
-                }
-            }
-        })
-    }
-
-    fn register_stages(&self, registry: &mut StageRegistry) -> std::result::Result<(), PluginSystemError> {
-        let vtable_ptr = self.vtable.0;
-        let plugin_name_for_err = self.name_cache;
-
-        let result = panic::catch_unwind(std::panic::AssertUnwindSafe(move || unsafe {
-            let vtable = &*vtable_ptr;
-            let registry_ptr = registry as *mut _ as *mut c_void;
-            (vtable.register_stages)(vtable.instance, registry_ptr)
-        }));
-
-        match result {
-            Ok(ffi_res) => {
-                if ffi_res == FfiResult::Ok {
-                    Ok(())
-                } else {
-                    Err(map_ffi_error(ffi_res, plugin_name_for_err, "register_stages"))
-                }
-            }
-            Err(panic_obj) => {
-                let panic_msg = if let Some(s_ref) = panic_obj.downcast_ref::<&'static str>() {
-                    (*s_ref).to_string()
-                } else if let Some(s_obj) = panic_obj.downcast_ref::<String>() {
-                    s_obj.clone()
-                } else {
-                    "Unknown panic reason".to_string()
-                };
-                eprintln!("[GINI_FFI_DEBUG] Panic in FFI 'register_stages' for plugin '{}': {}", plugin_name_for_err, panic_msg);
-                Err(PluginSystemError::FfiError {
-                    plugin_id: plugin_name_for_err.to_string(),
-                    operation: "register_stages".to_string(),
-                    message: format!("panic: {}", panic_msg),
-                })
-            }
-        }
-    }
-
-    fn shutdown(&self) -> std::result::Result<(), PluginSystemError> {
-        let vtable_ptr = self.vtable.0;
-        let plugin_name_for_err = self.name_cache;
-
-        let result = panic::catch_unwind(std::panic::AssertUnwindSafe(move || unsafe {
-            let vtable = &*vtable_ptr;
-            (vtable.shutdown)(vtable.instance)
-        }));
-
-        match result {
-            Ok(ffi_res) => {
-                if ffi_res == FfiResult::Ok {
-                    println!("Plugin '{}' FFI shutdown method executed successfully.", plugin_name_for_err);
-                    Ok(())
-                } else {
-                    eprintln!("Error during FFI shutdown for plugin '{}': {:?}", plugin_name_for_err, ffi_res);
-                    Err(map_ffi_error(ffi_res, plugin_name_for_err, "shutdown"))
-                }
-            }
-            Err(panic_obj) => {
-                let panic_msg = if let Some(s_ref) = panic_obj.downcast_ref::<&'static str>() {
-                    (*s_ref).to_string()
-                } else if let Some(s_obj) = panic_obj.downcast_ref::<String>() {
-                    s_obj.clone()
-                } else {
-                    "Unknown panic reason".to_string()
-                };
-                eprintln!("[GINI_FFI_DEBUG] Panic in FFI 'shutdown' for plugin '{}': {}", plugin_name_for_err, panic_msg);
-                Err(PluginSystemError::FfiError {
-                    plugin_id: plugin_name_for_err.to_string(),
-                    operation: "shutdown".to_string(),
-                    message: format!("panic: {}", panic_msg),
-                })
-            }
-        }
-    }
+/// Formats resolver explanations on one line.
+fn join_reasons(reasons: &[Incompatibility]) -> String {
+    reasons.iter().map(|reason| reason.to_string()).collect::<Vec<_>>().join("; ")
 }
 
-// --- End VTablePluginWrapper ---
-
-
-const DISABLED_PLUGINS_KEY: &str = "core.plugins.disabled";
+/// Reads the modification time of a file, returning None if unavailable.
+fn file_modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
 
 
 /// Plugin system component interface
@@ -569,6 +121,15 @@ pub struct DefaultPluginManager {
     config_manager: Arc<ConfigManager>,
     plugin_loader: PluginLoader, // Added PluginLoader
     stage_registry_arc: Arc<Mutex<StageRegistry>>, // Added StageRegistry Arc
+    event_manager: Option<Arc<dyn EventManager>>, // Used to emit plugin load/unload events
+    watched_plugins: Arc<Mutex<HashMap<String, WatchedPlugin>>>, // Dynamic plugins by ID, for hot-reload
+    data_dir: Option<PathBuf>, // XDG data dir, searched for plugins below the standard layout
+    cli_plugin_dirs: Arc<Mutex<Vec<PathBuf>>>, // Plugin dirs passed on the command line
+    permissions: Arc<PermissionManager>, // Shared with the registry; reachable without locking it
+    lifecycle: Arc<PluginLifecycle>, // Shared with the registry; reachable without locking it
+    services: Arc<ServiceRegistry>, // Shared with the registry; plugins publish into it during init
+    signature_verifier: Arc<SignatureVerifier>, // Shared with the registry and its manifest-only plugins
+    ui_manager: Option<UnifiedUiManager>, // Asked about conflicts no policy resolves
 }
 
 impl DefaultPluginManager {
@@ -584,127 +145,674 @@ impl DefaultPluginManager {
                 message: format!("Failed to parse API_VERSION constant: {}", e),
                 source: None,
             })?;
+        let registry = PluginRegistry::new(api_version);
+        let permissions = registry.permissions().clone();
+        let lifecycle = registry.lifecycle().clone();
+        let services = registry.services().clone();
+        let signature_verifier = registry.signature_verifier().clone();
         Ok(Self {
             name: "DefaultPluginManager",
-            registry: Arc::new(Mutex::new(PluginRegistry::new(api_version))),
+            registry: Arc::new(Mutex::new(registry)),
             config_manager,
-            plugin_loader: PluginLoader::new(), // Initialize PluginLoader
+            plugin_loader: PluginLoader::new().with_signature_verifier(signature_verifier.clone()), // Initialize PluginLoader
             stage_registry_arc, // Store StageRegistry Arc
+            event_manager: None,
+            watched_plugins: Arc::new(Mutex::new(HashMap::new())),
+            data_dir: None,
+            cli_plugin_dirs: Arc::new(Mutex::new(Vec::new())),
+            permissions,
+            lifecycle,
+            services,
+            signature_verifier,
+            ui_manager: None,
         })
     }
 
+    /// Sets the data directory whose standard plugin layout
+    /// (`plugins/core`, `plugins/third_party`, `plugins`) is searched for plugins.
+    /// Relative `storage_path` permissions are resolved against the same directory.
+    pub fn with_data_dir(mut self, data_dir: PathBuf) -> Self {
+        self.permissions.set_data_dir(data_dir.clone());
+        self.data_dir = Some(data_dir);
+        self
+    }
+
+    /// Adds plugin directories given on the command line.
+    /// These take precedence over every other plugin directory source.
+    pub async fn add_cli_plugin_dirs(&self, dirs: &[PathBuf]) {
+        let mut cli_dirs = self.cli_plugin_dirs.lock().await;
+        for dir in dirs {
+            if !cli_dirs.contains(dir) {
+                cli_dirs.push(dir.clone());
+            }
+        }
+    }
+
+    /// Builds the ordered list of directories scanned for plugins during `initialize`.
+    ///
+    /// Sources, from highest to lowest precedence:
+    /// 1. Directories passed on the command line
+    /// 2. The `GINI_PLUGIN_PATH` environment variable
+    /// 3. `core.plugins.search_paths` in the core settings config
+    /// 4. The standard plugin layout below the data directory
+    /// 5. The standard plugin layout below the working directory
+    ///
+    /// When the same plugin ID is found in more than one directory, the copy found first wins.
+    pub async fn plugin_search_paths(&self) -> PluginSearchPaths {
+        let mut search_paths = PluginSearchPaths::new();
+
+        for dir in self.cli_plugin_dirs.lock().await.iter() {
+            search_paths.add(dir.clone(), PluginPathSource::CommandLine);
+        }
+
+        if let Some(env_value) = std::env::var_os(constants::PLUGIN_PATH_ENV_VAR) {
+            search_paths.add_path_list(&env_value, PluginPathSource::Environment);
+        }
+
+        match self.config_manager.load_config(CORE_SETTINGS_CONFIG_NAME, ConfigScope::Application) {
+            Ok(config_data) => {
+                let configured: Vec<String> = config_data.get_or(PLUGIN_SEARCH_PATHS_KEY, Vec::new());
+                for dir in configured {
+                    search_paths.add(PathBuf::from(dir), PluginPathSource::Config);
+                }
+            }
+            Err(e) => {
+                eprintln!("Warning: Failed to load plugin search paths from config: {}. Ignoring configured paths.", e);
+            }
+        }
+
+        if let Some(data_dir) = &self.data_dir {
+            search_paths.add_standard_layout(data_dir, PluginPathSource::DataDir);
+        }
+        search_paths.add_standard_layout(Path::new("."), PluginPathSource::Default);
+
+        search_paths
+    }
+
+    /// Attaches an event manager used to emit `SystemEvent::PluginUnload` and
+    /// `SystemEvent::PluginLoaded` when dynamic plugins are reloaded.
+    pub fn with_event_manager(mut self, event_manager: Arc<dyn EventManager>) -> Self {
+        self.permissions.set_event_manager(event_manager.clone()); // Receives permission audit events
+        self.lifecycle.set_event_manager(event_manager.clone()); // Receives plugin state changes
+        self.event_manager = Some(event_manager);
+        self
+    }
+
+    /// Attaches the UI manager. During `initialize`, critical conflicts left unresolved by
+    /// `core.plugins.conflict_policies` are put to the user through it, if an interactive
+    /// interface is registered.
+    pub fn with_ui_manager(mut self, ui_manager: UnifiedUiManager) -> Self {
+        self.ui_manager = Some(ui_manager);
+        self
+    }
+
+    /// Runtime permissions of the registered plugins, derived from their declared resources.
+    pub fn permissions(&self) -> &Arc<PermissionManager> {
+        &self.permissions
+    }
+
+    /// Lifecycle states of the plugins, with the reason for each state and the last error.
+    pub fn lifecycle(&self) -> &Arc<PluginLifecycle> {
+        &self.lifecycle
+    }
+
+    /// Typed services published by the initialized plugins. Plugins publish into it and look
+    /// up each other's services during `init` through `app.plugin_manager().services()`.
+    pub fn services(&self) -> &Arc<ServiceRegistry> {
+        &self.services
+    }
+
+    /// Sets the signature policy for dynamic plugins.
+    /// `core.plugins.signature_policy` in the core settings config takes precedence when set.
+    pub fn with_signature_policy(self, policy: SignaturePolicy) -> Self {
+        self.signature_verifier.set_policy(policy);
+        self
+    }
+
+    /// Sets the config directory. The trust store of plugin signing keys is read from
+    /// its `trusted_plugin_keys.json` during `initialize`.
+    pub fn with_config_dir(self, config_dir: PathBuf) -> Self {
+        self.signature_verifier.set_trust_store_path(config_dir.join(constants::PLUGIN_TRUST_STORE_FILE));
+        self
+    }
+
+    /// Signature policy, trust store and the verification status of each loaded plugin.
+    pub fn signature_verifier(&self) -> &Arc<SignatureVerifier> {
+        &self.signature_verifier
+    }
+
     pub fn registry(&self) -> &Arc<Mutex<PluginRegistry>> {
         &self.registry
     }
 
-    fn load_so_plugin(&self, path: &Path) -> KernelResult<Box<dyn Plugin>> {
-        type PluginInitFn = unsafe extern "C" fn() -> *mut PluginVTable;
+    /// Records the entry point of a dynamically loaded plugin so watch mode can detect rebuilds,
+    /// and how far its permissions are enforced.
+    async fn track_dynamic_plugin(&self, plugin_id: &str, entry_point: &Path, runtime: PluginRuntime, manifest: Option<&PluginManifest>) {
+        self.permissions.set_enforcement(plugin_id, runtime.permission_enforcement());
+        let mut watched = self.watched_plugins.lock().await;
+        watched.insert(plugin_id.to_string(), WatchedPlugin {
+            entry_point: entry_point.to_path_buf(),
+            last_modified: file_modified_time(entry_point),
+            runtime,
+            manifest: manifest.map(|manifest| Box::new(manifest.clone())),
+        });
+    }
 
-        let library = unsafe { Library::new(path) }.map_err(|e| {
-            Error::from(PluginSystemError::LoadingError {
-                plugin_id: path.to_string_lossy().into_owned(),
-                path: Some(path.to_path_buf()),
-                source: Box::new(PluginSystemErrorSource::Other(format!("Failed to load library: {}", e))),
-            })
-        })?;
+    /// Queues a system event if an event manager is attached.
+    async fn emit_system_event(&self, event: SystemEvent) {
+        if let Some(event_manager) = &self.event_manager {
+            event_manager.queue_event(Box::new(event)).await;
+        }
+    }
 
-        let init_symbol: Symbol<PluginInitFn> = unsafe { library.get(b"_plugin_init\0") }.map_err(|e| {
-            Error::from(PluginSystemError::LoadingError {
-                plugin_id: path.to_string_lossy().into_owned(),
-                path: Some(path.to_path_buf()),
-                source: Box::new(PluginSystemErrorSource::Other(format!("Failed to find _plugin_init symbol: {}", e))),
-            })
-        })?;
-        
-        println!("[GINI_FFI_DEBUG] DefaultPluginManager::load_so_plugin: Calling _plugin_init symbol for {:?}", path);
-        let vtable_ptr_result = panic::catch_unwind(|| unsafe { init_symbol() });
-        
-        let vtable_ptr = match vtable_ptr_result {
-            Ok(ptr) if !ptr.is_null() => {
-                println!("[GINI_FFI_DEBUG] DefaultPluginManager::load_so_plugin: _plugin_init for {:?} returned valid ptr: {:?}", path, ptr);
-                ptr
+    /// Returns the IDs of dynamic plugins whose entry point has been modified
+    /// since it was last loaded or checked. Each change is reported only once.
+    pub async fn detect_changed_plugins(&self) -> Vec<String> {
+        let mut watched = self.watched_plugins.lock().await;
+        let mut changed = Vec::new();
+        for (plugin_id, state) in watched.iter_mut() {
+            let current = file_modified_time(&state.entry_point);
+            if current.is_some() && current != state.last_modified {
+                state.last_modified = current;
+                changed.push(plugin_id.clone());
             }
-            Err(e) => {
-                let panic_msg = if let Some(s_ref) = e.downcast_ref::<&'static str>() {
-                    (*s_ref).to_string()
-                } else if let Some(s_obj) = e.downcast_ref::<String>() {
-                    s_obj.clone()
-                } else {
-                    "Unknown panic reason".to_string()
-                };
-                eprintln!("[GINI_FFI_DEBUG] DefaultPluginManager::load_so_plugin: _plugin_init for {:?} panicked: {}", path, panic_msg);
-                return Err(Error::from(PluginSystemError::FfiError {
-                    plugin_id: path.to_string_lossy().into_owned(),
-                    operation: "_plugin_init".to_string(),
-                    message: format!("panic: {}", panic_msg),
-                }));
+        }
+        changed.sort();
+        changed
+    }
+
+    /// Starts watch mode: spawns a background task that polls the entry points of
+    /// dynamically loaded plugins every `poll_interval` and sends the ID of each
+    /// changed plugin on the returned channel. The task stops once the receiver is dropped.
+    ///
+    /// Reloading needs mutable access to the `Application`, so the receiver side is
+    /// expected to call [`reload_plugin`](Self::reload_plugin) for each ID it receives.
+    pub fn watch_plugins(&self, poll_interval: Duration) -> mpsc::UnboundedReceiver<String> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let manager = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(poll_interval);
+            loop {
+                ticker.tick().await;
+                if tx.is_closed() {
+                    break;
+                }
+                for plugin_id in manager.detect_changed_plugins().await {
+                    println!("[PluginWatch] Detected change in entry point of plugin '{}'.", plugin_id);
+                    if tx.send(plugin_id).is_err() {
+                        return;
+                    }
+                }
             }
-            Ok(ptr) if ptr.is_null() => {
-                 eprintln!("[GINI_FFI_DEBUG] DefaultPluginManager::load_so_plugin: _plugin_init for {:?} returned a null pointer.", path);
-                 return Err(Error::from(PluginSystemError::LoadingError {
-                    plugin_id: path.to_string_lossy().into_owned(),
-                    path: Some(path.to_path_buf()),
-                    source: Box::new(PluginSystemErrorSource::Other(
-                        "Plugin initialization function returned a null VTable pointer.".to_string()
-                    )),
-                }));
+        });
+        rx
+    }
+
+    /// Reloads every dynamic plugin whose entry point changed since the last check.
+    /// Returns the IDs of the plugins that were reloaded successfully.
+    pub async fn reload_changed_plugins(&self, app: &mut Application) -> KernelResult<Vec<String>> {
+        let mut reloaded = Vec::new();
+        for plugin_id in self.detect_changed_plugins().await {
+            match self.reload_plugin(&plugin_id, app).await {
+                Ok(()) => reloaded.push(plugin_id),
+                Err(e) => eprintln!("Failed to hot-reload plugin '{}': {}", plugin_id, e),
             }
-             _ => unreachable!(),
+        }
+        Ok(reloaded)
+    }
+
+    /// Hot-reloads a dynamically loaded plugin from its entry point.
+    ///
+    /// The new build is loaded and checked (plugin ID and API compatibility) first; if that
+    /// fails, the old instance stays registered and watched and the error is returned.
+    /// Otherwise the old instance is shut down and its stages are unregistered, the plugin is
+    /// removed from the registry and its library is dropped before the new build takes its place.
+    /// The new instance keeps the enabled state of the old one and, if the old one was
+    /// initialized, is initialized and has its stages registered again.
+    pub async fn reload_plugin(&self, plugin_id: &str, app: &mut Application) -> KernelResult<()> {
+        let (entry_point, runtime, manifest) = {
+            let watched = self.watched_plugins.lock().await;
+            watched.get(plugin_id).map(|state| (state.entry_point.clone(), state.runtime, state.manifest.clone()))
+        }.ok_or_else(|| Error::from(PluginSystemError::OperationError {
+            plugin_id: Some(plugin_id.to_string()),
+            message: "Plugin was not loaded from a dynamic library and cannot be reloaded".to_string(),
+        }))?;
+
+        println!("Reloading plugin '{}' from {:?}", plugin_id, entry_point);
+        let mut registry = self.registry.lock().await;
+        let was_enabled = registry.is_enabled(plugin_id);
+        let was_initialized = registry.is_initialized(plugin_id);
+        let lazy_manifest = registry.get_manifest(plugin_id).cloned(); // Manifest-only plugins stay lazy
+
+        // Manifest-only plugins whose library was never loaded have nothing to replace yet
+        let replacement = if lazy_manifest.is_none() || registry.is_plugin_code_loaded(plugin_id) {
+            let plugin = self.load_replacement(plugin_id, &entry_point, runtime, lazy_manifest.as_ref().or(manifest.as_deref()))?;
+            registry.check_api_compatibility(plugin.as_ref()).map_err(Error::from)?;
+            Some(Arc::<dyn Plugin>::from(plugin))
+        } else {
+            None
         };
-        println!("[GINI_FFI_DEBUG] DefaultPluginManager::load_so_plugin: _plugin_init symbol raw returned ptr: {:?}", vtable_ptr);
 
-        println!("[GINI_FFI_DEBUG] DefaultPluginManager::load_so_plugin: Calling VTablePluginWrapper::new with vtable_ptr: {:?}", vtable_ptr);
-        let plugin_wrapper = unsafe { VTablePluginWrapper::new(vtable_ptr, library, Some(path.to_path_buf()))? };
+        self.emit_system_event(SystemEvent::PluginUnload { plugin_id: plugin_id.to_string() }).await;
+        let old_plugin = registry.unload_plugin(plugin_id, &self.stage_registry_arc).await.map_err(Error::from)?;
+        if Arc::strong_count(&old_plugin) > 1 {
+            log::warn!(
+                "Plugin '{}' is still referenced elsewhere; its library stays mapped until those references are dropped.",
+                plugin_id
+            );
+        }
+        drop(old_plugin); // Drops the VTablePluginWrapper and its Library (or stops the plugin host process)
+
+        match (lazy_manifest, replacement) {
+            (Some(manifest), Some(instance)) => registry.register_loaded_manifest(manifest, instance),
+            // Re-register from the manifest; the new build is loaded when the plugin is next initialized
+            (Some(manifest), None) => registry.register_manifest(manifest),
+            (None, Some(instance)) => registry.register_plugin(instance),
+            (None, None) => unreachable!("plugins registered as instances always have a replacement"),
+        }.map_err(Error::from)?;
+        if !was_enabled {
+            registry.disable_plugin(plugin_id, &self.stage_registry_arc).await.map_err(Error::from)?;
+        } else if was_initialized {
+            registry.initialize_plugin(plugin_id, app, &self.stage_registry_arc).await?;
+        }
+        drop(registry);
+
+        self.track_dynamic_plugin(plugin_id, &entry_point, runtime, manifest.as_deref()).await;
+        self.emit_system_event(SystemEvent::PluginLoaded { plugin_id: plugin_id.to_string() }).await;
+        println!("Plugin '{}' reloaded successfully.", plugin_id);
+        Ok(())
+    }
 
-        Ok(Box::new(plugin_wrapper))
+    /// Loads the new build of a dynamic plugin for [`reload_plugin`](Self::reload_plugin) and
+    /// checks that it reports the same plugin ID. Native libraries are loaded from a temporary
+    /// copy: the old build is still mapped from the entry point's path, and opening that path
+    /// again would return the old build.
+    fn load_replacement(&self, plugin_id: &str, entry_point: &Path, runtime: PluginRuntime, manifest: Option<&PluginManifest>) -> KernelResult<Box<dyn Plugin>> {
+        let plugin = match runtime {
+            PluginRuntime::Native => {
+                match manifest {
+                    Some(manifest) => self.signature_verifier.check(manifest),
+                    None => self.signature_verifier.check_unsigned(&entry_point.display().to_string()),
+                }.map_err(Error::from)?;
+                let staging_error = |e: std::io::Error| Error::from(PluginSystemError::LoadingError {
+                    plugin_id: plugin_id.to_string(),
+                    path: Some(entry_point.to_path_buf()),
+                    source: Box::new(PluginSystemErrorSource::Io(e)),
+                });
+                let prefix = format!("{}-reload-", plugin_id);
+                let extension = entry_point.extension().map(|ext| format!(".{}", ext.to_string_lossy())).unwrap_or_default();
+                let staged = tempfile::Builder::new().prefix(&prefix).suffix(&extension).tempfile().map_err(staging_error)?;
+                fs::copy(entry_point, staged.path()).map_err(staging_error)?;
+                // The copy is deleted when `staged` is dropped; the loaded library stays mapped
+                ffi_host::load_plugin_library(staged.path()).map_err(Error::from)?
+            }
+            PluginRuntime::Sandboxed | PluginRuntime::Wasm => self.load_dynamic_plugin(entry_point, runtime, manifest)?,
+        };
+        if plugin.name() != plugin_id {
+            return Err(Error::from(PluginSystemError::LoadingError {
+                plugin_id: plugin_id.to_string(),
+                path: Some(entry_point.to_path_buf()),
+                source: Box::new(PluginSystemErrorSource::Other(format!(
+                    "The new build reports plugin ID '{}'; keeping the loaded build",
+                    plugin.name()
+                ))),
+            }));
+        }
+        Ok(plugin)
     }
 
-    pub async fn persist_enable_plugin(&self, name: &str) -> KernelResult<()> {
+    /// Loads a library by path. Without a manifest there is no signature, so it counts as unsigned.
+    fn load_so_plugin(&self, path: &Path) -> KernelResult<Box<dyn Plugin>> {
+        self.signature_verifier.check_unsigned(&path.display().to_string()).map_err(Error::from)?;
+        ffi_host::load_plugin_library(path).map_err(Error::from)
+    }
+
+    /// Loads a dynamic plugin in-process, in a plugin host process, or in the WebAssembly runtime.
+    /// The signature policy is applied before any of the plugin's code is loaded.
+    fn load_dynamic_plugin(&self, path: &Path, runtime: PluginRuntime, manifest: Option<&PluginManifest>) -> KernelResult<Box<dyn Plugin>> {
+        match (runtime, manifest) {
+            (PluginRuntime::Native | PluginRuntime::Wasm, Some(manifest)) => {
+                PluginLoader::load_entry_point(manifest, &self.signature_verifier).map_err(Error::from)
+            }
+            (PluginRuntime::Native, None) => self.load_so_plugin(path),
+            (PluginRuntime::Sandboxed, _) => {
+                match manifest {
+                    Some(manifest) => self.signature_verifier.check(manifest),
+                    None => self.signature_verifier.check_unsigned(&path.display().to_string()),
+                }.map_err(Error::from)?;
+                SandboxedPlugin::spawn(path, SandboxConfig::default())
+                    .map(|plugin| Box::new(plugin) as Box<dyn Plugin>)
+                    .map_err(Error::from)
+            }
+            (PluginRuntime::Wasm, None) => Err(Error::from(PluginSystemError::OperationError {
+                plugin_id: None,
+                message: format!("WebAssembly plugin at {:?} cannot be loaded without its manifest", path),
+            })),
+        }
+    }
+
+    /// Persist a plugin as enabled and enable it in the registry, together with the disabled
+    /// plugins it requires. The plugins are not initialized; see [`enable_plugin`](Self::enable_plugin).
+    /// Returns the required plugins that were enabled along with it.
+    pub async fn persist_enable_plugin(&self, name: &str) -> KernelResult<Vec<String>> {
+        let dependencies: Vec<String> = {
+            let registry = self.registry.lock().await;
+            registry.required_dependencies_of(name).into_iter().filter(|dep| !registry.is_enabled(dep)).collect()
+        };
+        let plugin_ids: Vec<&str> = dependencies.iter().map(String::as_str).chain(std::iter::once(name)).collect();
+
         let mut config_data = self.config_manager.load_config(CORE_SETTINGS_CONFIG_NAME, ConfigScope::Application)?;
         let mut disabled_list: Vec<String> = config_data.get_or(DISABLED_PLUGINS_KEY, Vec::new());
-        disabled_list.retain(|disabled_name| disabled_name != name);
+        disabled_list.retain(|disabled_name| !plugin_ids.contains(&disabled_name.as_str()));
         config_data.set(DISABLED_PLUGINS_KEY, &disabled_list)?;
+        let mut enabled_list: Vec<String> = config_data.get_or(ENABLED_PLUGINS_KEY, Vec::new());
+        for plugin_id in &plugin_ids {
+            if !enabled_list.iter().any(|enabled_name| enabled_name == plugin_id) {
+                enabled_list.push(plugin_id.to_string());
+            }
+        }
+        config_data.set(ENABLED_PLUGINS_KEY, &enabled_list)?;
+        let mut decisions: BTreeMap<String, String> = config_data.get_or(CONFLICT_DECISIONS_KEY, BTreeMap::new());
+        let decision_count = decisions.len();
+        decisions.retain(|plugin_id, _| !plugin_ids.contains(&plugin_id.as_str()));
+        if decisions.len() != decision_count {
+            config_data.set(CONFLICT_DECISIONS_KEY, &decisions)?;
+        }
         self.config_manager.save_config(CORE_SETTINGS_CONFIG_NAME, &config_data, ConfigScope::Application)?;
         println!("Persisted state: Plugin '{}' marked as enabled (removed from disabled list).", name);
 
         let mut registry = self.registry.lock().await;
         if registry.has_plugin(name) {
-            registry.enable_plugin(name).map_err(Error::from)?;
+            for plugin_id in &plugin_ids {
+                registry.enable_plugin(plugin_id).map_err(Error::from)?;
+            }
+            if !dependencies.is_empty() {
+                println!("Also enabled the plugins required by '{}': {}", name, dependencies.join(", "));
+            }
         } else {
             println!("Attempted to enable non-existent plugin '{}' in registry (no runtime state change).", name);
         }
-        Ok(())
+        Ok(dependencies)
     }
 
-    pub async fn persist_disable_plugin(&self, name: &str) -> KernelResult<()> {
-        let registry = self.registry.lock().await;
-        if !registry.has_plugin(name) {
+    /// Enable a plugin while the application is running.
+    ///
+    /// The plugin and the disabled plugins it requires are persisted as enabled, then
+    /// initialized in dependency order and have their stages registered. Returns the
+    /// required plugins that were enabled along with it.
+    ///
+    /// Conflicts with the enabled plugins are settled first, as at startup: by the conflict
+    /// policies, then through the UI. If the plugin loses, it stays disabled. If a critical
+    /// conflict is left open, the plugin is not enabled and its persisted state is restored.
+    pub async fn enable_plugin(&self, name: &str, app: &mut Application) -> KernelResult<Vec<String>> {
+        if !self.registry.lock().await.has_plugin(name) {
             return Err(Error::from(PluginSystemError::RegistrationError {
                 plugin_id: name.to_string(),
                 message: "Plugin not found".to_string(),
             }));
         }
-        if let Some(plugin) = registry.get_plugin(name) {
-            if plugin.is_core() {
+        let persisted = self.config_manager.load_config(CORE_SETTINGS_CONFIG_NAME, ConfigScope::Application)?;
+        let dependencies = self.persist_enable_plugin(name).await?;
+
+        let mut decisions = self.apply_conflict_policies().await?;
+        decisions.extend(self.resolve_conflicts_interactively().await?);
+        if let Some(decision) = decisions.iter().find(|decision| decision.disabled_plugin() == Some(name)) {
+            return Err(Error::from(PluginSystemError::OperationError {
+                plugin_id: Some(name.to_string()),
+                message: decision.to_string(),
+            }));
+        }
+
+        let mut registry = self.registry.lock().await;
+        registry.detect_all_conflicts().map_err(Error::from)?;
+        let enabled_now: Vec<&str> = dependencies.iter().map(String::as_str).chain(std::iter::once(name)).collect();
+        let conflicts: Vec<PluginConflict> = registry
+            .conflict_manager()
+            .get_critical_unresolved_conflicts()
+            .into_iter()
+            .filter(|conflict| enabled_now.contains(&conflict.first_plugin.as_str()) || enabled_now.contains(&conflict.second_plugin.as_str()))
+            .cloned()
+            .collect();
+        if !conflicts.is_empty() {
+            for plugin_id in enabled_now.iter().rev() {
+                registry
+                    .disable_plugin_with_reason(plugin_id, "Conflicts with enabled plugins", &self.stage_registry_arc)
+                    .await
+                    .map_err(Error::from)?;
+            }
+            self.config_manager.save_config(CORE_SETTINGS_CONFIG_NAME, &persisted, ConfigScope::Application)?;
+            return Err(Error::from(PluginSystemError::UnresolvedPluginConflicts { conflicts }));
+        }
+        registry.initialize_plugin(name, app, &self.stage_registry_arc).await?;
+        println!("Plugin '{}' initialized.", name);
+        Ok(dependencies)
+    }
+
+    /// Disable a plugin while the application is running and persist it as disabled.
+    /// Fails if enabled plugins require it; see [`disable_plugin`](Self::disable_plugin).
+    pub async fn persist_disable_plugin(&self, name: &str) -> KernelResult<()> {
+        self.disable_plugin(name, false).await.map(|_| ())
+    }
+
+    /// Disable a plugin while the application is running and persist it as disabled.
+    ///
+    /// Enabled plugins that require it would be left with a missing dependency. Without
+    /// `cascade` the plugin is therefore only disabled if there are none; with `cascade`
+    /// they are shut down and disabled first, dependents before their dependencies, and
+    /// persisted as disabled as well. Returns the dependents that were disabled.
+    pub async fn disable_plugin(&self, name: &str, cascade: bool) -> KernelResult<Vec<String>> {
+        let dependents = {
+            let registry = self.registry.lock().await;
+            if !registry.has_plugin(name) {
+                return Err(Error::from(PluginSystemError::RegistrationError {
+                    plugin_id: name.to_string(),
+                    message: "Plugin not found".to_string(),
+                }));
+            }
+            if registry.get_plugin(name).is_some_and(|plugin| plugin.is_core()) {
                 return Err(Error::from(PluginSystemError::OperationError {
                     plugin_id: Some(name.to_string()),
                     message: "Core plugin cannot be disabled".to_string(),
                 }));
             }
-        }
-        drop(registry);
+            let dependents = registry.dependents_of(name);
+            if !cascade && !dependents.is_empty() {
+                return Err(Error::from(PluginSystemError::OperationError {
+                    plugin_id: Some(name.to_string()),
+                    message: format!("Enabled plugins require it: {}. Cascade to disable them as well", dependents.join(", ")),
+                }));
+            }
+            dependents
+        };
+        let plugin_ids: Vec<&str> = dependents.iter().map(String::as_str).chain(std::iter::once(name)).collect();
 
         let mut config_data = self.config_manager.load_config(CORE_SETTINGS_CONFIG_NAME, ConfigScope::Application)?;
         let mut disabled_list: Vec<String> = config_data.get_or(DISABLED_PLUGINS_KEY, Vec::new());
-        if !disabled_list.contains(&name.to_string()) {
-            disabled_list.push(name.to_string());
+        for plugin_id in &plugin_ids {
+            if !disabled_list.iter().any(|disabled_name| disabled_name == plugin_id) {
+                disabled_list.push(plugin_id.to_string());
+            }
         }
         config_data.set(DISABLED_PLUGINS_KEY, &disabled_list)?;
+        let mut enabled_list: Vec<String> = config_data.get_or(ENABLED_PLUGINS_KEY, Vec::new());
+        enabled_list.retain(|enabled_name| !plugin_ids.contains(&enabled_name.as_str()));
+        config_data.set(ENABLED_PLUGINS_KEY, &enabled_list)?;
         self.config_manager.save_config(CORE_SETTINGS_CONFIG_NAME, &config_data, ConfigScope::Application)?;
         println!("Persisted state: Plugin '{}' marked as disabled.", name);
 
         let mut registry = self.registry.lock().await;
-        registry.disable_plugin(name, &self.stage_registry_arc).await.map_err(Error::from)?;
+        registry.disable_plugin_cascade(name, "Disabled by the user", &self.stage_registry_arc).await.map_err(Error::from)
+    }
+
+    /// Effective configuration of a registered plugin, with the default of each key and any
+    /// schema violations. The plugin's own schema is registered first if it declares one.
+    pub async fn plugin_config_report(&self, plugin_id: &str) -> KernelResult<PluginConfigReport> {
+        let plugin = self.registry.lock().await.get_plugin(plugin_id).ok_or_else(|| {
+            Error::from(PluginSystemError::RegistrationError {
+                plugin_id: plugin_id.to_string(),
+                message: "Plugin not found".to_string(),
+            })
+        })?;
+        if let Some(schema) = plugin.config_schema() {
+            self.config_manager.register_plugin_schema(plugin_id, schema);
+        }
+        self.config_manager.plugin_config_report(plugin_id)
+    }
+
+    /// Resolve conflicts between enabled plugins with the rules in `core.plugins.conflict_policies`.
+    ///
+    /// Losing plugins are disabled and added to the persisted disabled list, with the reason
+    /// stored under `core.plugins.conflict_decisions`, so the decision holds on the next start.
+    /// Does nothing if no policy is configured.
+    pub async fn apply_conflict_policies(&self) -> KernelResult<Vec<ConflictDecision>> {
+        let config_data = self.config_manager.load_config(CORE_SETTINGS_CONFIG_NAME, ConfigScope::Application)?;
+        let policies: Vec<ConflictPolicy> = config_data
+            .get_or::<Vec<String>>(CONFLICT_POLICIES_KEY, Vec::new())
+            .iter()
+            .filter_map(|rule| match rule.parse::<ConflictPolicy>() {
+                Ok(policy) => Some(policy),
+                Err(e) => {
+                    eprintln!("Warning: {}. Ignoring it.", e);
+                    None
+                }
+            })
+            .collect();
+        if policies.is_empty() {
+            return Ok(Vec::new());
+        }
+        let explicitly_enabled: HashSet<String> = config_data.get_or::<Vec<String>>(ENABLED_PLUGINS_KEY, Vec::new()).into_iter().collect();
+
+        let decisions = {
+            let mut registry = self.registry.lock().await;
+            registry
+                .apply_conflict_policies(&policies, &explicitly_enabled, &self.stage_registry_arc)
+                .await
+                .map_err(Error::from)?
+        };
+        self.persist_conflict_decisions(&decisions, false)?;
+        Ok(decisions)
+    }
+
+    /// Ask the user, through the attached UI manager, how to resolve each critical conflict
+    /// between enabled plugins that is still unresolved.
+    ///
+    /// Every conflict is shown as a choice between disabling either plugin (core plugins are
+    /// never offered) and allowing both with a warning. The answer is applied with
+    /// [`ConflictManager::resolve_conflict`] and persisted like a policy decision; allowed pairs
+    /// are stored under `core.plugins.allowed_conflicts`. Either way the user is not asked again.
+    /// Does nothing without a UI manager or an interactive interface.
+    pub async fn resolve_conflicts_interactively(&self) -> KernelResult<Vec<ConflictDecision>> {
+        let Some(ui_manager) = &self.ui_manager else {
+            return Ok(Vec::new());
+        };
+        if !ui_manager.has_interactive_interface() {
+            return Ok(Vec::new());
+        }
+
+        let (conflicts, core_plugins) = {
+            let mut registry = self.registry.lock().await;
+            registry.detect_all_conflicts().map_err(Error::from)?;
+            let conflicts: Vec<(usize, PluginConflict)> = registry
+                .conflict_manager()
+                .get_conflicts()
+                .iter()
+                .enumerate()
+                .filter(|(_, conflict)| !conflict.resolved && conflict.is_critical())
+                .map(|(index, conflict)| (index, conflict.clone()))
+                .collect();
+            let core_plugins: HashSet<String> = registry.plugins.values().filter(|plugin| plugin.is_core()).map(|plugin| plugin.name().to_string()).collect();
+            (conflicts, core_plugins)
+        };
+
+        let mut decisions = Vec::new();
+        let mut disabled: HashSet<String> = HashSet::new();
+        for (index, conflict) in conflicts {
+            // An earlier answer may already have settled this conflict
+            let strategy = if disabled.contains(&conflict.first_plugin) {
+                ResolutionStrategy::DisableFirst
+            } else if disabled.contains(&conflict.second_plugin) {
+                ResolutionStrategy::DisableSecond
+            } else {
+                let mut choices = Vec::new();
+                if !core_plugins.contains(&conflict.first_plugin) {
+                    choices.push((ResolutionStrategy::DisableFirst, format!("Disable '{}'", conflict.first_plugin)));
+                }
+                if !core_plugins.contains(&conflict.second_plugin) {
+                    choices.push((ResolutionStrategy::DisableSecond, format!("Disable '{}'", conflict.second_plugin)));
+                }
+                choices.push((ResolutionStrategy::AllowWithWarning, "Allow both to run (with a warning)".to_string()));
+
+                let prompt = format!(
+                    "Plugins '{}' and '{}' conflict ({}): {} How should this be resolved?",
+                    conflict.first_plugin, conflict.second_plugin, conflict.conflict_type.description(), conflict.description
+                );
+                let labels: Vec<String> = choices.iter().map(|(_, label)| label.clone()).collect();
+                let answer = ui_manager.request_choice(CONFLICT_PROMPT_SOURCE, &prompt, &labels).await.map_err(Error::from)?;
+                let strategy = choices.swap_remove(answer).0;
+                decisions.push(ConflictDecision {
+                    first_plugin: conflict.first_plugin.clone(),
+                    second_plugin: conflict.second_plugin.clone(),
+                    description: conflict.description.clone(),
+                    strategy: strategy.clone(),
+                    policy: None,
+                });
+                strategy
+            };
+
+            let mut registry = self.registry.lock().await;
+            registry.conflict_manager_mut().resolve_conflict(index, strategy.clone())?;
+            let loser = match strategy {
+                ResolutionStrategy::DisableFirst => Some((&conflict.first_plugin, &conflict.second_plugin)),
+                ResolutionStrategy::DisableSecond => Some((&conflict.second_plugin, &conflict.first_plugin)),
+                _ => {
+                    registry.allow_conflict(&conflict.first_plugin, &conflict.second_plugin);
+                    None
+                }
+            };
+            if let Some((loser, winner)) = loser
+                && disabled.insert(loser.clone())
+            {
+                let reason = format!("Disabled by the user in favour of '{}': {}", winner, conflict.description);
+                registry.disable_plugin_with_reason(loser, &reason, &self.stage_registry_arc).await.map_err(Error::from)?;
+            }
+        }
+
+        self.persist_conflict_decisions(&decisions, true)?;
+        Ok(decisions)
+    }
+
+    /// Record conflict decisions in the core settings so they hold on the next start: disabled
+    /// plugins join `core.plugins.disabled` with the reason under `core.plugins.conflict_decisions`,
+    /// and, if `remember_allowed` is set, allowed pairs join `core.plugins.allowed_conflicts`.
+    fn persist_conflict_decisions(&self, decisions: &[ConflictDecision], remember_allowed: bool) -> KernelResult<()> {
+        if decisions.is_empty() {
+            return Ok(());
+        }
+
+        let mut config_data = self.config_manager.load_config(CORE_SETTINGS_CONFIG_NAME, ConfigScope::Application)?;
+        let mut disabled_list: Vec<String> = config_data.get_or(DISABLED_PLUGINS_KEY, Vec::new());
+        let mut recorded: BTreeMap<String, String> = config_data.get_or(CONFLICT_DECISIONS_KEY, BTreeMap::new());
+        let mut allowed: Vec<(String, String)> = config_data.get_or(ALLOWED_CONFLICTS_KEY, Vec::new());
+        for decision in decisions {
+            println!("Conflict resolved: {}", decision);
+            if let Some(disabled) = decision.disabled_plugin() {
+                if !disabled_list.iter().any(|name| name == disabled) {
+                    disabled_list.push(disabled.to_string());
+                }
+                recorded.insert(disabled.to_string(), decision.to_string());
+            } else if remember_allowed {
+                let pair = (decision.first_plugin.clone(), decision.second_plugin.clone());
+                if !allowed.contains(&pair) {
+                    allowed.push(pair);
+                }
+            }
+        }
+        config_data.set(DISABLED_PLUGINS_KEY, &disabled_list)?;
+        config_data.set(CONFLICT_DECISIONS_KEY, &recorded)?;
+        if remember_allowed {
+            config_data.set(ALLOWED_CONFLICTS_KEY, &allowed)?;
+        }
+        self.config_manager.save_config(CORE_SETTINGS_CONFIG_NAME, &config_data, ConfigScope::Application)?;
         Ok(())
     }
 }
@@ -726,28 +834,20 @@ impl KernelComponent for DefaultPluginManager {
     async fn initialize(&self) -> KernelResult<()> {
         println!("Initializing Plugin Manager...");
 
-        // 1. Create a mutable PluginLoader instance (or make self mutable if appropriate)
-        // For now, let's assume we might need to add dirs to it, so make it mutable.
-        // However, DefaultPluginManager holds an immutable plugin_loader.
-        // This suggests plugin_dirs should be configured on the loader at construction or via a method.
-        // For this step, we'll assume the loader is pre-configured or we configure it here.
+        // 1. Configure the loader with the ordered plugin search path
         let mut loader = PluginLoader::new(); // Create a new loader instance for scanning
-        
-        // Configure plugin directories for the loader.
-        // This should ideally come from configuration or a more robust mechanism.
-        // For now, using the same hardcoded path as before.
-        let plugin_dir_to_scan = PathBuf::from("./target/debug");
-        if plugin_dir_to_scan.exists() && plugin_dir_to_scan.is_dir() {
-            loader.add_plugin_dir(&plugin_dir_to_scan);
-        } else {
-            println!("Plugin directory {:?} not found, no directories added to loader.", plugin_dir_to_scan);
+        let search_paths = self.plugin_search_paths().await;
+        for entry in search_paths.entries() {
+            if entry.path.is_dir() {
+                println!("Plugin search directory ({}): {:?}", entry.source, entry.path);
+                loader.add_plugin_dir(&entry.path);
+            } else {
+                log::debug!("Plugin search directory ({}) {:?} not found, skipping.", entry.source, entry.path);
+            }
         }
-        // Add other standard plugin directories if necessary
-        // loader.add_plugin_dir(PathBuf::from("/usr/lib/gini/plugins"));
-
 
-        // 2. Scan for all manifests
-        let all_manifests = match loader.scan_for_manifests().await {
+        // 2. Scan for all manifests, keeping every copy so the resolver can choose between versions
+        let candidates = match loader.scan_for_manifest_candidates().await {
             Ok(manifests) => {
                 println!("Found {} plugin manifests.", manifests.len());
                 manifests
@@ -758,7 +858,31 @@ impl KernelComponent for DefaultPluginManager {
             }
         };
 
-        // 3. Perform conflict detection
+        // 3. Resolve versions and dependencies. Plugins that are already registered (static
+        // plugins) are kept and constrain the choice. Declared conflicts and incompatibilities are
+        // left to the conflict policies and the user once the plugins are registered.
+        let resolution = {
+            let registry = self.registry.lock().await;
+            let mut resolver = DependencyResolver::new(candidates)
+                .with_api_version(registry.api_version().clone())
+                .defer_declared_conflicts();
+            for (_, plugin) in registry.iter_plugins() {
+                resolver = resolver.with_loaded_plugin(plugin.as_ref());
+            }
+            resolver.resolve_all()
+        };
+        for excluded in &resolution.excluded {
+            eprintln!("Skipping plugin '{}': {}", excluded.plugin_id, join_reasons(&excluded.reasons));
+        }
+        for skipped in &resolution.skipped_optional {
+            println!(
+                "Optional dependency '{}' of {} is not loaded: {}",
+                skipped.dependency.plugin_name, skipped.plugin_id, join_reasons(&skipped.reasons)
+            );
+        }
+        let all_manifests = resolution.selected;
+
+        // Perform conflict detection on the resolved set
         let mut conflict_manager = ConflictManager::new();
         if let Err(e) = conflict_manager.detect_conflicts(&all_manifests) {
             eprintln!("Error during conflict detection: {}", e);
@@ -791,48 +915,105 @@ impl KernelComponent for DefaultPluginManager {
         // The original `load_plugins_from_directory` directly loads .so files.
         // We now have manifests, so we should iterate these and load them.
 
-        let mut loaded_count = 0;
-        let mut registry_locked = self.registry.lock().await; // Lock registry once
+        let lazy_loading = match self.config_manager.load_config(CORE_SETTINGS_CONFIG_NAME, ConfigScope::Application) {
+            Ok(config_data) => config_data.get_or(LAZY_LOADING_KEY, false),
+            Err(_) => false,
+        };
+        if lazy_loading {
+            println!("Lazy plugin loading enabled: plugins are registered from their manifests and loaded on first use.");
+        }
+        let sandboxed_plugins: Vec<String> = match self.config_manager.load_config(CORE_SETTINGS_CONFIG_NAME, ConfigScope::Application) {
+            Ok(config_data) => config_data.get_or(SANDBOXED_PLUGINS_KEY, Vec::new()),
+            Err(_) => Vec::new(),
+        };
+        if !sandboxed_plugins.is_empty() {
+            println!("Plugins running in separate plugin host processes: {:?}", sandboxed_plugins);
+        }
 
-        for manifest in &all_manifests {
-            // Check for conflicts with already registered and enabled plugins
-            let mut conflict_found_and_enabled = false;
-            if !manifest.conflicts_with.is_empty() { // Optimization: only iterate if there are potential conflicts
-                for conflicting_plugin_name in &manifest.conflicts_with {
-                    // We have registry_locked: MutexGuard<'_, PluginRegistry>
-                    if registry_locked.is_registered(conflicting_plugin_name) && registry_locked.is_enabled(conflicting_plugin_name) {
-                        log::warn!(
-                            "Skipping manifest for plugin '{}' (from file: {:?}) due to declared conflict with already registered and enabled plugin '{}'",
-                            manifest.id,
-                            manifest.plugin_base_dir.join(&manifest.entry_point),
-                            conflicting_plugin_name
-                        );
-                        conflict_found_and_enabled = true;
-                        break; // Found a conflict, no need to check further for this manifest
-                    }
-                }
+        // Signature policy from config (overrides with_signature_policy) and the trust store
+        if let Ok(config_data) = self.config_manager.load_config(CORE_SETTINGS_CONFIG_NAME, ConfigScope::Application)
+            && let Some(policy_value) = config_data.get::<String>(SIGNATURE_POLICY_KEY)
+        {
+            match policy_value.parse::<SignaturePolicy>() {
+                Ok(policy) => self.signature_verifier.set_policy(policy),
+                Err(e) => eprintln!("Warning: {}. Keeping signature policy '{}'.", e, self.signature_verifier.policy()),
             }
+        }
+        if let Err(e) = self.signature_verifier.reload_trust_store() {
+            eprintln!("Warning: {}. Keeping the current plugin trust store.", e);
+        }
+        if self.signature_verifier.policy() != SignaturePolicy::AllowUnsigned {
+            println!("Plugin signature policy: {}", self.signature_verifier.policy());
+        }
 
-            if conflict_found_and_enabled {
-                continue; // Skip to the next manifest
+        let mut loaded_count = 0;
+        let mut registry_locked = self.registry.lock().await; // Lock registry once
+
+        if let Ok(config_data) = self.config_manager.load_config(CORE_SETTINGS_CONFIG_NAME, ConfigScope::Application)
+            && let Some(policy_value) = config_data.get::<String>(INIT_FAILURE_POLICY_KEY)
+        {
+            match policy_value.parse::<InitFailurePolicy>() {
+                Ok(policy) => registry_locked.set_init_failure_policy(policy),
+                Err(e) => eprintln!("Warning: {}. Keeping init failure policy '{}'.", e, registry_locked.init_failure_policy()),
             }
+        }
 
+        for manifest in &all_manifests {
             // Check if plugin is already registered (e.g. static plugins)
             if registry_locked.is_registered(&manifest.id) {
                  println!("Plugin '{}' is already registered (likely static), skipping dynamic load.", manifest.id);
                  continue;
             }
 
+            // The plugin's configuration is checked against the manifest's schema before its init
+            match manifest.load_config_schema() {
+                Ok(Some(schema)) => self.config_manager.register_plugin_schema(&manifest.id, schema),
+                Ok(None) => {}
+                Err(e) => log::warn!("Ignoring the configuration schema of plugin '{}': {}", manifest.id, e),
+            }
+
             // Determine the path to the .so file from the manifest's entry_point and plugin_base_dir
             let entry_point_path = manifest.plugin_base_dir.join(&manifest.entry_point);
+
+            // WASM modules are already isolated, so they run in-process even if listed as sandboxed.
+            // Sandboxed plugins are started right away, since their host process answers for them.
+            let runtime = if PluginLoader::is_wasm_entry_point(manifest) {
+                PluginRuntime::Wasm
+            } else if sandboxed_plugins.contains(&manifest.id) {
+                PluginRuntime::Sandboxed
+            } else {
+                PluginRuntime::Native
+            };
+
+            // In lazy mode, register the manifest only. API compatibility is checked against the
+            // manifest's api_versions, so manifests without them are still loaded eagerly.
+            if lazy_loading && !matches!(runtime, PluginRuntime::Sandboxed) {
+                if manifest.api_versions.is_empty() {
+                    println!("Manifest for plugin '{}' declares no api_versions; loading its library eagerly.", manifest.id);
+                } else {
+                    match registry_locked.register_manifest(manifest.clone()) {
+                        Ok(_) => {
+                            println!("Registered plugin '{}' from its manifest (library not loaded yet).", manifest.id);
+                            self.track_dynamic_plugin(&manifest.id, &entry_point_path, runtime, Some(manifest)).await;
+                            loaded_count += 1;
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to register plugin {} from its manifest: {}", manifest.id, e);
+                        }
+                    }
+                    continue;
+                }
+            }
+
             println!("Attempting to load plugin '{}' from {:?}", manifest.id, entry_point_path);
 
-            match self.load_so_plugin(&entry_point_path) {
+            match self.load_dynamic_plugin(&entry_point_path, runtime, Some(manifest)) {
                 Ok(plugin_instance) => {
                     let plugin_name = plugin_instance.name().to_string();
                     match registry_locked.register_plugin(Arc::from(plugin_instance)) {
                         Ok(_) => {
                             println!("Successfully loaded and registered plugin: {}", plugin_name);
+                            self.track_dynamic_plugin(&plugin_name, &entry_point_path, runtime, Some(manifest)).await;
                             loaded_count += 1;
                         }
                         Err(e) => {
@@ -853,12 +1034,22 @@ impl KernelComponent for DefaultPluginManager {
         // Apply persisted disabled states (moved after loading all potential plugins)
         match self.config_manager.load_config(CORE_SETTINGS_CONFIG_NAME, ConfigScope::Application) {
             Ok(config_data) => {
+                let allowed: Vec<(String, String)> = config_data.get_or(ALLOWED_CONFLICTS_KEY, Vec::new());
+                if !allowed.is_empty() {
+                    let mut registry = self.registry.lock().await;
+                    for (first, second) in &allowed {
+                        registry.allow_conflict(first, second);
+                    }
+                }
                 let disabled_list: Vec<String> = config_data.get_or(DISABLED_PLUGINS_KEY, Vec::new());
                 if !disabled_list.is_empty() {
                     println!("Applying persisted disabled state for plugins: {:?}", disabled_list);
+                    // Plugins disabled by a conflict decision keep the decision as their reason
+                    let recorded: BTreeMap<String, String> = config_data.get_or(CONFLICT_DECISIONS_KEY, BTreeMap::new());
                     let mut registry = self.registry.lock().await;
                     for plugin_name in disabled_list {
-                        if let Err(e) = registry.disable_plugin(&plugin_name, &self.stage_registry_arc).await {
+                        let reason = recorded.get(&plugin_name).cloned().unwrap_or_else(|| format!("Listed in {}", DISABLED_PLUGINS_KEY));
+                        if let Err(e) = registry.disable_plugin_with_reason(&plugin_name, &reason, &self.stage_registry_arc).await {
                             eprintln!("Failed to apply persisted disable for {}: {}", plugin_name, e);
                         }
                     }
@@ -868,6 +1059,28 @@ impl KernelComponent for DefaultPluginManager {
                 eprintln!("Warning: Failed to load plugin disabled states from config: {}. Proceeding with defaults.", e);
             }
         }
+
+        // Settle conflicts between the remaining plugins before they are initialized
+        if let Err(e) = self.apply_conflict_policies().await {
+            eprintln!("Warning: Failed to apply plugin conflict policies: {}", e);
+        }
+        // Ask the user about whatever the policies left open
+        if let Err(e) = self.resolve_conflicts_interactively().await {
+            eprintln!("Warning: Failed to resolve plugin conflicts interactively: {}", e);
+        }
+        // Declared conflicts nobody decided go to the plugin with the higher priority, for this run only
+        let defaults = {
+            let mut registry = self.registry.lock().await;
+            registry.prefer_higher_priority_in_declared_conflicts(&self.stage_registry_arc).await
+        };
+        match defaults {
+            Ok(decisions) => {
+                for decision in decisions {
+                    println!("{}", decision);
+                }
+            }
+            Err(e) => eprintln!("Warning: Failed to resolve declared plugin conflicts by priority: {}", e),
+        }
         Ok(())
     }
 
@@ -896,7 +1109,13 @@ impl PluginManager for DefaultPluginManager {
                 let name = plugin.name().to_string();
                 let mut registry = self.registry.lock().await;
                 match registry.register_plugin(Arc::from(plugin)) {
-                    Ok(_) => { println!("Successfully loaded and registered plugin: {}", name); Ok(()) }
+                    Ok(_) => {
+                        println!("Successfully loaded and registered plugin: {}", name);
+                        drop(registry);
+                        self.track_dynamic_plugin(&name, path, PluginRuntime::Native, None).await;
+                        self.signature_verifier.record(&name, VerificationStatus::Unsigned);
+                        Ok(())
+                    }
                     Err(e) => { eprintln!("Failed to register plugin from {:?}: {}", path, e); Err(Error::from(e)) }
                 }
             }
@@ -932,7 +1151,12 @@ impl PluginManager for DefaultPluginManager {
                                     Ok(plugin) => {
                                         let name = plugin.name().to_string();
                                         match registry.register_plugin(Arc::from(plugin)) {
-                                            Ok(_) => { println!("Successfully loaded and registered plugin: {}", name); loaded_count += 1; }
+                                            Ok(_) => {
+                                                println!("Successfully loaded and registered plugin: {}", name);
+                                                self.track_dynamic_plugin(&name, &path, PluginRuntime::Native, None).await;
+                                                self.signature_verifier.record(&name, VerificationStatus::Unsigned);
+                                                loaded_count += 1;
+                                            }
                                             Err(plugin_system_err) => { // This is PluginSystemError
                                                 let err_msg = format!("Failed to register plugin from {:?}: {}", path, plugin_system_err);
                                                 eprintln!("{}", err_msg);
@@ -1032,6 +1256,9 @@ impl PluginManager for DefaultPluginManager {
 
     async fn get_plugin_manifest(&self, id: &str) -> KernelResult<Option<PluginManifest>> {
         let registry = self.registry.lock().await;
+        if let Some(manifest) = registry.get_manifest(id) {
+            return Ok(Some(manifest.clone())); // Registered from its manifest, return it as-is
+        }
         match registry.get_plugin(id) {
             Some(plugin_arc) => {
                 let plugin_ref = &*plugin_arc;
@@ -1071,6 +1298,15 @@ impl Clone for DefaultPluginManager {
             config_manager: Arc::clone(&self.config_manager),
             plugin_loader: self.plugin_loader.clone(), // Clone PluginLoader
             stage_registry_arc: Arc::clone(&self.stage_registry_arc), // Clone StageRegistry Arc
+            event_manager: self.event_manager.clone(),
+            watched_plugins: Arc::clone(&self.watched_plugins),
+            data_dir: self.data_dir.clone(),
+            cli_plugin_dirs: Arc::clone(&self.cli_plugin_dirs),
+            permissions: Arc::clone(&self.permissions),
+            lifecycle: Arc::clone(&self.lifecycle),
+            services: Arc::clone(&self.services),
+            signature_verifier: Arc::clone(&self.signature_verifier),
+            ui_manager: self.ui_manager.clone(),
         }
     }
 }
@@ -1,55 +1,27 @@
 use crate::plugin_system::version::VersionRange;
 use crate::plugin_system::traits::PluginPriority;
-use serde::{Deserialize, Serialize}; // Added Serialize
+use serde::Serialize; // Added Serialize
 use crate::plugin_system::dependency::PluginDependency; // Import PluginDependency
-use std::path::PathBuf; // Added for plugin_base_dir
-
-/// Defines the type of access a plugin requires or provides for a resource.
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)] // Added Serialize
-pub enum ResourceAccessType {
-    /// Claims exclusive control/write access. No other plugin can claim Exclusive or SharedWrite.
-    #[serde(rename = "exclusive_write")]
-    ExclusiveWrite,
-
-    /// Claims shared read access. Compatible with other SharedRead claims.
-    #[serde(rename = "shared_read")]
-    SharedRead,
-
-    /// Indicates the plugin provides a unique entity identified by `identifier` of `resource_type`.
-    /// e.g., A plugin `provides_unique_id` for a `stage_id`. Conflicts if another plugin also `provides_unique_id` for the same stage_id.
-    #[serde(rename = "provides_unique_id")]
-    ProvidesUniqueId,
-}
-
-impl ResourceAccessType {
-    /// Returns a string representation of the access type.
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            ResourceAccessType::ExclusiveWrite => "ExclusiveWrite",
-            ResourceAccessType::SharedRead => "SharedRead",
-            ResourceAccessType::ProvidesUniqueId => "ProvidesUniqueId",
-        }
-    }
-}
-
-/// Describes a resource a plugin claims or provides.
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)] // Added Serialize
-pub struct ResourceClaim {
-    /// Type of the resource (e.g., "file_path", "network_port", "stage_id", "config_scope").
-    /// Standardized strings should be used.
-    #[serde(rename = "type")]
-    pub resource_type: String,
-
-    /// Unique identifier for the resource within its type.
-    /// For "file_path": "/var/log/app.log"
-    /// For "network_port": "8080"
-    /// For "stage_id": "data_processing_stage"
-    /// For "config_scope": "global.settings.network"
-    pub identifier: String,
-
-    /// Describes how the plugin interacts with the resource.
-    pub access: ResourceAccessType,
-}
+use std::path::{Path, PathBuf}; // Added for plugin_base_dir
+use crate::plugin_system::error::PluginSystemError;
+use crate::storage::config::ConfigFormat;
+use crate::storage::schema::ConfigSchema; // Schema named by `config_schema`
+
+// Manifest `resources` entries use the same claim type as `Plugin::declared_resources`,
+// so both are checked together for conflicts and permissions.
+pub use crate::plugin_system::conflict::{ResourceAccessType, ResourceClaim};
+
+/// File names a plugin directory's manifest is looked up under, in order of preference.
+/// The format follows the extension.
+pub const MANIFEST_FILE_NAMES: &[&str] = &[
+    "manifest.json",
+    #[cfg(feature = "toml-config")]
+    "plugin.toml",
+    #[cfg(feature = "yaml-config")]
+    "plugin.yaml",
+    #[cfg(feature = "yaml-config")]
+    "plugin.yml",
+];
 
 /// Represents a plugin manifest that describes a plugin
 #[derive(Debug, Clone, Serialize)] // Added Serialize, Removed Deserialize
@@ -108,7 +80,7 @@ pub struct PluginManifest {
     /// Resource claims made by the plugin.
     pub resources: Vec<ResourceClaim>,
 
-    /// The base directory of the plugin, where its manifest.json is located.
+    /// The base directory of the plugin, where its manifest file is located.
     /// This is set by the PluginLoader during manifest scanning.
     #[serde(with = "pathbuf_serde_helper")]
     pub plugin_base_dir: PathBuf,
@@ -214,6 +186,40 @@ impl PluginManifest {
         self
     }
 
+    /// The manifest file in `dir`: the first of [`MANIFEST_FILE_NAMES`] that exists
+    pub fn find_file(dir: &Path) -> Option<PathBuf> {
+        MANIFEST_FILE_NAMES.iter()
+            .map(|name| dir.join(name))
+            .find(|path| path.is_file())
+    }
+
+    /// Write the manifest in `format`, in the form the loader reads back
+    pub fn to_manifest_string(&self, format: ConfigFormat) -> Result<String, PluginSystemError> {
+        crate::plugin_system::loader::serialize_manifest(self, format).map_err(|e| PluginSystemError::ManifestError {
+            path: self.plugin_base_dir.clone(),
+            message: format!("Failed to serialize manifest as {}", format.extension().to_uppercase()),
+            source: Some(e),
+        })
+    }
+
+    /// Read the JSON Schema file named by `config_schema`, relative to the plugin's directory
+    pub fn load_config_schema(&self) -> Result<Option<ConfigSchema>, PluginSystemError> {
+        let Some(schema_file) = &self.config_schema else {
+            return Ok(None);
+        };
+        let path = self.plugin_base_dir.join(schema_file);
+        let invalid = |message: &str, source: Box<dyn std::error::Error + Send + Sync>| PluginSystemError::ManifestError {
+            path: path.clone(),
+            message: message.to_string(),
+            source: Some(source),
+        };
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| invalid("Failed to read config schema", Box::new(e)))?;
+        ConfigSchema::from_json_str(&content)
+            .map(Some)
+            .map_err(|e| invalid("Invalid config schema", Box::new(e)))
+    }
+
     /// Get the plugin priority
     pub fn get_priority(&self) -> Option<PluginPriority> {
         self.priority.as_ref().and_then(|p| PluginPriority::from_str(p)) // from_str already returns Option
@@ -345,11 +351,7 @@ impl ManifestBuilder {
     /// * `identifier`: Unique identifier for the resource (e.g., "/path/to/file", "8080").
     /// * `access`: The type of access required for the resource.
     pub fn resource(mut self, resource_type: &str, identifier: &str, access: ResourceAccessType) -> Self {
-        self.manifest.resources.push(ResourceClaim {
-            resource_type: resource_type.to_string(),
-            identifier: identifier.to_string(),
-            access,
-        });
+        self.manifest.resources.push(ResourceClaim::new(resource_type, identifier, access));
         self
     }
 
@@ -10,25 +10,57 @@
 //!
 //! - **[`adapter`]**: Facilitates the interaction between the core system and
 //!   plugin-defined components, often involving FFI (Foreign Function Interface)
-//!   abstractions.
+//!   abstractions. Deprecated in favour of [`service`].
 //! - **[`conflict`]**: Handles the detection and resolution of conflicts between
 //!   plugins, such as incompatible dependencies or duplicate provisions.
 //! - **[`dependency`]**: Manages plugin dependencies, ensuring that required
 //!   plugins and their versions are available.
 //! - **[`error`]**: Defines specific error types (e.g., [`PluginError`](error::PluginError))
 //!   related to plugin operations.
+//! - **[`ffi_export`]**: Plugin side of the FFI boundary: exposes a [`Plugin`]
+//!   implementation through a `PluginVTable` (used by `#[gini_plugin]`).
+//! - **[`ffi_host`]**: Host side of the dynamic plugin FFI boundary: loads plugin
+//!   libraries and wraps their `PluginVTable`, with the ownership and freeing contract.
+//! - **[`ipc`]**: Framed request/response protocol spoken with out-of-process plugin hosts.
+//! - **[`lazy`]**: Manifest-only plugin entries ([`LazyPlugin`](lazy::LazyPlugin)) whose
+//!   libraries are loaded only when the plugin is actually used.
+//! - **[`lifecycle`]**: Lifecycle state of each plugin ([`PluginState`](lifecycle::PluginState))
+//!   with checked transitions, the reason for the current state and the last error.
+//! - **[`lint`]**: Checks plugin manifests for unknown keys, invalid version ranges and
+//!   priorities, and files that do not exist ([`ManifestLint`](lint::ManifestLint)).
 //! - **[`loader`]**: Responsible for finding, parsing plugin manifests, and loading
 //!   plugin libraries into memory.
 //! - **[`manager`]**: The central orchestrator ([`PluginManager`]) for the plugin system,
 //!   coordinating all aspects of plugin lifecycle and interaction.
 //! - **[`manifest`]**: Defines the structure of plugin metadata ([`PluginManifest`]),
 //!   which includes information like plugin name, version, dependencies, and capabilities.
+//! - **[`package`]**: Plugin archive format ([`PluginArchive`](package::PluginArchive)) and
+//!   installation of archives into the third-party plugin directory
+//!   ([`PluginInstaller`](package::PluginInstaller)).
+//! - **[`permission`]**: Enforces declared resources as capability permissions at runtime
+//!   ([`PermissionManager`](permission::PermissionManager), [`PluginAccess`](permission::PluginAccess)).
+//! - **[`sandbox`]**: Runs a dynamic plugin in a child process behind a proxy
+//!   ([`SandboxedPlugin`](sandbox::SandboxedPlugin)) that restarts it after a crash.
+//! - **[`signature`]**: ed25519 plugin signatures, the trust store of signing keys and the
+//!   signature policy applied before a library is loaded ([`SignatureVerifier`](signature::SignatureVerifier)).
+//! - **[`resolver`]**: Chooses a consistent set of plugin versions from the discovered
+//!   manifests ([`DependencyResolver`](resolver::DependencyResolver)), honouring version
+//!   ranges, optional dependencies, conflicts and incompatibilities.
+//! - **[`service`]**: Typed APIs plugins publish for each other
+//!   ([`ServiceRegistry`](service::ServiceRegistry)), withdrawn when their provider shuts down.
+//! - **[`startup`]**: What happens when a plugin fails to initialize
+//!   ([`InitFailurePolicy`](startup::InitFailurePolicy)) and the resulting
+//!   [`StartupReport`](startup::StartupReport).
+//! - **[`search_path`]**: Builds the ordered list of plugin directories from the
+//!   command line, environment, configuration, data directory and defaults.
 //! - **[`registry`]**: Maintains a collection ([`PluginRegistry`]) of all known, loaded,
 //!   and active plugins.
 //! - **[`traits`]**: Contains essential traits that plugins must implement, most notably
 //!   the [`Plugin`] trait, which defines the core interface for all plugins.
 //! - **[`version`]**: Provides utilities for parsing, comparing, and managing
 //!   plugin versions and version requirements.
+//! - **`wasm`** (feature `wasm-plugins`): Runs plugins whose entry point is a `.wasm`
+//!   module in an embedded WebAssembly runtime (`WasmPlugin`).
 //!
 //! The plugin system is designed to be robust and flexible, allowing for a rich
 //! ecosystem of extensions that can enhance and customize the Gini application.
@@ -42,6 +74,22 @@ pub mod manifest;
 pub mod conflict;
 pub mod manager;
 pub mod error; // Add the new error module
+pub mod search_path;
+pub mod lazy;
+pub mod lifecycle;
+pub mod lint;
+pub mod ffi_host;
+pub mod ffi_export;
+pub mod ipc;
+pub mod package;
+pub mod permission;
+pub mod resolver;
+pub mod sandbox;
+pub mod service;
+pub mod signature;
+pub mod startup;
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm;
 
 pub use registry::PluginRegistry;
 pub use traits::{Plugin, PluginPriority};
@@ -49,6 +97,7 @@ pub use version::{ApiVersion, VersionRange};
 pub use dependency::PluginDependency;
 pub use manifest::PluginManifest;
 pub use manager::{PluginManager, DefaultPluginManager};
+pub use search_path::{PluginSearchPaths, PluginPathSource};
 // Test module declaration
 #[cfg(test)]
 mod tests;
\ No newline at end of file
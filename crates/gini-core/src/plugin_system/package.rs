@@ -0,0 +1,502 @@
+//! # Plugin Archives and Installation
+//!
+//! Defines the plugin archive format and installs, upgrades and uninstalls
+//! third-party plugins from such archives.
+//!
+//! A plugin archive is a gzip-compressed tar file (conventionally named
+//! `<id>-<version>.tar.gz`) with these entries at its root:
+//!
+//! - the plugin manifest, under one of the names in [`MANIFEST_FILE_NAMES`] (required)
+//! - the manifest's `entry_point` library or WebAssembly module (required)
+//! - every path listed in the manifest's `files`
+//! - the manifest's `config_schema` file, if it names one
+//!
+//! A `manifest.sig` signature (see [`signature`](crate::plugin_system::signature)) may be
+//! included as well and is installed with the plugin.
+//!
+//! Only regular files and directories are accepted, every path must be relative and
+//! must not contain `..`, and entries the manifest does not mention are rejected.
+//! [`PluginArchive::create`] packs a plugin directory into this format.
+//!
+//! [`PluginInstaller`] unpacks archives into `<data dir>/plugins/third_party/<id>`. Before
+//! anything is written, the manifest is validated through [`PluginLoader`], its
+//! `api_versions` are checked against [`constants::API_VERSION`], and
+//! [`ConflictManager::detect_conflicts`] is run against the installed plugins. Critical
+//! conflicts abort the installation; other conflicts are returned as warnings.
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use semver::Version;
+
+use crate::kernel::constants;
+use crate::plugin_system::conflict::{ConflictManager, PluginConflict};
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::loader::PluginLoader;
+use crate::plugin_system::manifest::{PluginManifest, MANIFEST_FILE_NAMES};
+use crate::plugin_system::signature::SIGNATURE_FILE_NAME;
+use crate::plugin_system::version::ApiVersion;
+
+/// Subdirectory of the staging directory an archive is unpacked into before it is moved into place.
+const STAGED_PLUGIN_DIR: &str = "plugin";
+/// Subdirectory of the staging directory the replaced version is moved to during an upgrade.
+const PREVIOUS_PLUGIN_DIR: &str = "previous";
+
+fn archive_error(path: &Path, message: String) -> PluginSystemError {
+    PluginSystemError::ArchiveError { path: path.to_path_buf(), message }
+}
+
+fn install_error(plugin_id: &str, message: String) -> PluginSystemError {
+    PluginSystemError::InstallError { plugin_id: plugin_id.to_string(), message }
+}
+
+/// Normalizes an archive path to `a/b/c` form.
+/// Returns `None` for absolute paths, paths containing `..` and empty paths.
+fn normalize_entry_path(path: &Path) -> Option<String> {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_str()?),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    if parts.is_empty() {
+        return None;
+    }
+    Some(parts.join("/"))
+}
+
+/// The archive paths a manifest accounts for: the manifest itself (stored as `manifest_name`),
+/// the entry point, the listed files and the config schema.
+fn expected_entries(manifest: &PluginManifest, manifest_name: &str, path: &Path) -> Result<Vec<String>, PluginSystemError> {
+    let mut entries = vec![manifest_name.to_string()];
+    let declared = std::iter::once(&manifest.entry_point)
+        .chain(manifest.files.iter())
+        .chain(manifest.config_schema.iter());
+    for declared_path in declared {
+        let name = normalize_entry_path(Path::new(declared_path)).ok_or_else(|| {
+            archive_error(path, format!("Manifest path '{}' must be relative and must not contain '..'", declared_path))
+        })?;
+        if !entries.contains(&name) {
+            entries.push(name);
+        }
+    }
+    Ok(entries)
+}
+
+/// Plugin IDs name their install directory, so they must be a single plain path component.
+fn validate_plugin_id(plugin_id: &str, path: &Path) -> Result<(), PluginSystemError> {
+    let valid = !plugin_id.is_empty()
+        && !plugin_id.starts_with('.')
+        && !plugin_id.contains(['/', '\\'])
+        && normalize_entry_path(Path::new(plugin_id)).as_deref() == Some(plugin_id);
+    if valid {
+        Ok(())
+    } else {
+        Err(archive_error(path, format!("Plugin ID '{}' cannot be used as a directory name", plugin_id)))
+    }
+}
+
+/// A validated plugin archive, read fully into memory.
+#[derive(Debug, Clone)]
+pub struct PluginArchive {
+    path: PathBuf,
+    manifest: PluginManifest,
+    /// File contents by normalized archive path, the manifest included
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl PluginArchive {
+    /// Read and validate the archive at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, PluginSystemError> {
+        let path = path.as_ref();
+        let file = File::open(path).map_err(|e| archive_error(path, format!("Failed to open archive: {}", e)))?;
+        Self::from_reader(file, path)
+    }
+
+    /// Read and validate an archive from `reader`. `path` is only used in error messages.
+    pub fn from_reader<R: Read>(reader: R, path: &Path) -> Result<Self, PluginSystemError> {
+        let mut archive = tar::Archive::new(GzDecoder::new(reader));
+        let entries = archive.entries()
+            .map_err(|e| archive_error(path, format!("Failed to read archive: {}", e)))?;
+
+        let mut files = BTreeMap::new();
+        for entry in entries {
+            let mut entry = entry.map_err(|e| archive_error(path, format!("Failed to read archive entry: {}", e)))?;
+            let entry_path = entry.path()
+                .map_err(|e| archive_error(path, format!("Invalid archive entry path: {}", e)))?
+                .into_owned();
+            let name = normalize_entry_path(&entry_path).ok_or_else(|| {
+                archive_error(path, format!("Entry '{}' must be a relative path without '..'", entry_path.display()))
+            })?;
+
+            match entry.header().entry_type() {
+                tar::EntryType::Directory => continue, // Directories are recreated from the file paths
+                tar::EntryType::Regular => {}
+                other => {
+                    return Err(archive_error(path, format!(
+                        "Entry '{}' has unsupported type {:?}; only regular files and directories are allowed",
+                        name, other
+                    )));
+                }
+            }
+
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content)
+                .map_err(|e| archive_error(path, format!("Failed to read entry '{}': {}", name, e)))?;
+            if files.insert(name.clone(), content).is_some() {
+                return Err(archive_error(path, format!("Entry '{}' appears more than once", name)));
+            }
+        }
+
+        // The same precedence as in a plugin directory; any other manifest is rejected as undeclared below
+        let manifest_name = MANIFEST_FILE_NAMES.iter()
+            .copied()
+            .find(|name| files.contains_key(*name))
+            .ok_or_else(|| archive_error(path, format!("Archive has no manifest; expected one of {}", MANIFEST_FILE_NAMES.join(", "))))?;
+        let manifest_content = std::str::from_utf8(&files[manifest_name])
+            .map_err(|e| archive_error(path, format!("{} is not valid UTF-8: {}", manifest_name, e)))?;
+        // Validated the same way as manifests found on disk
+        let manifest = PluginLoader::parse_manifest(manifest_content, &path.join(manifest_name))?;
+        validate_plugin_id(&manifest.id, path)?;
+
+        let expected = expected_entries(&manifest, manifest_name, path)?;
+        if let Some(missing) = expected.iter().find(|name| !files.contains_key(*name)) {
+            return Err(archive_error(path, format!("Archive is missing '{}', which the manifest refers to", missing)));
+        }
+        if let Some(extra) = files.keys().find(|name| !expected.contains(*name) && name.as_str() != SIGNATURE_FILE_NAME) {
+            return Err(archive_error(path, format!(
+                "Archive entry '{}' is not the entry point, config schema or one of the manifest's files",
+                extra
+            )));
+        }
+
+        Ok(Self { path: path.to_path_buf(), manifest, files })
+    }
+
+    /// Pack the plugin in `plugin_dir` (its manifest file, entry point, `files`, config
+    /// schema and signature) into an archive at `output`, and return the archive as read back.
+    pub fn create<P: AsRef<Path>, Q: AsRef<Path>>(plugin_dir: P, output: Q) -> Result<Self, PluginSystemError> {
+        let plugin_dir = plugin_dir.as_ref();
+        let output = output.as_ref();
+        let manifest_path = PluginManifest::find_file(plugin_dir).ok_or_else(|| archive_error(plugin_dir, format!(
+            "No manifest found; expected one of {}",
+            MANIFEST_FILE_NAMES.join(", ")
+        )))?;
+        let manifest_name = manifest_path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+        let content = fs::read_to_string(&manifest_path)
+            .map_err(|e| archive_error(&manifest_path, format!("Failed to read manifest: {}", e)))?;
+        let manifest = PluginLoader::parse_manifest(&content, &manifest_path)?;
+        validate_plugin_id(&manifest.id, &manifest_path)?;
+
+        let file = File::create(output)
+            .map_err(|e| archive_error(output, format!("Failed to create archive: {}", e)))?;
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        let mut names = expected_entries(&manifest, manifest_name, &manifest_path)?;
+        if plugin_dir.join(SIGNATURE_FILE_NAME).is_file() {
+            names.push(SIGNATURE_FILE_NAME.to_string());
+        }
+        for name in names {
+            builder.append_path_with_name(plugin_dir.join(&name), &name)
+                .map_err(|e| archive_error(output, format!("Failed to add '{}': {}", name, e)))?;
+        }
+        builder.into_inner()
+            .and_then(|encoder| encoder.finish())
+            .map_err(|e| archive_error(output, format!("Failed to write archive: {}", e)))?;
+
+        Self::open(output)
+    }
+
+    /// Path the archive was read from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The validated manifest. Its `plugin_base_dir` points at the archive, not an install directory.
+    pub fn manifest(&self) -> &PluginManifest {
+        &self.manifest
+    }
+
+    /// Paths of the files in the archive, in sorted order
+    pub fn file_names(&self) -> impl Iterator<Item = &str> {
+        self.files.keys().map(String::as_str)
+    }
+
+    /// Check that one of the manifest's `api_versions` includes `api_version`.
+    pub fn check_api_version(&self, api_version: &ApiVersion) -> Result<(), PluginSystemError> {
+        let api_semver = Version::new(api_version.major as u64, api_version.minor as u64, api_version.patch as u64);
+        if self.manifest.api_versions.iter().any(|range| range.includes(&api_semver)) {
+            return Ok(());
+        }
+        let supported: Vec<String> = self.manifest.api_versions.iter().map(|range| range.to_string()).collect();
+        Err(install_error(&self.manifest.id, format!(
+            "Plugin supports API versions [{}], but this build provides API version {}",
+            supported.join(", "),
+            api_version
+        )))
+    }
+
+    /// Write the archive's files below `dir`, creating it if needed.
+    fn unpack(&self, dir: &Path) -> std::io::Result<()> {
+        for (name, content) in &self.files {
+            let destination = dir.join(name);
+            if let Some(parent) = destination.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&destination, content)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of installing or upgrading a plugin.
+#[derive(Debug, Clone)]
+pub struct InstallOutcome {
+    /// Manifest of the plugin, read back from its install directory
+    pub manifest: PluginManifest,
+    /// Version that was replaced (upgrades only)
+    pub previous_version: Option<String>,
+    /// Non-critical conflicts with installed plugins
+    pub warnings: Vec<PluginConflict>,
+}
+
+/// Installs, upgrades and uninstalls plugin archives in a third-party plugin directory.
+#[derive(Debug, Clone)]
+pub struct PluginInstaller {
+    install_dir: PathBuf,
+    /// Other plugin directories whose plugins count as installed
+    search_dirs: Vec<PathBuf>,
+    api_version: ApiVersion,
+}
+
+impl PluginInstaller {
+    /// Create an installer for `install_dir` that accepts plugins compatible with [`constants::API_VERSION`].
+    pub fn new<P: Into<PathBuf>>(install_dir: P) -> Self {
+        Self {
+            install_dir: install_dir.into(),
+            search_dirs: Vec::new(),
+            api_version: ApiVersion::from_str(constants::API_VERSION)
+                .expect("constants::API_VERSION is a valid version"),
+        }
+    }
+
+    /// Create an installer for the third-party plugin directory below `data_dir`.
+    pub fn for_data_dir(data_dir: &Path) -> Self {
+        Self::new(data_dir.join(constants::THIRD_PARTY_PLUGINS_DIR))
+    }
+
+    /// Also consider the plugins in `dirs` (e.g. the plugin search path) when checking for
+    /// conflicts, duplicate IDs and dependents. Only plugins in the install directory can be
+    /// upgraded or uninstalled.
+    pub fn with_search_dirs<I, P>(mut self, dirs: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: Into<PathBuf>,
+    {
+        self.search_dirs.extend(dirs.into_iter().map(Into::into));
+        self
+    }
+
+    /// Check archives against `api_version` instead of [`constants::API_VERSION`].
+    pub fn with_api_version(mut self, api_version: ApiVersion) -> Self {
+        self.api_version = api_version;
+        self
+    }
+
+    /// Directory plugins are installed into
+    pub fn install_dir(&self) -> &Path {
+        &self.install_dir
+    }
+
+    /// Manifests of the plugins in the install directory.
+    pub async fn installed(&self) -> Result<Vec<PluginManifest>, PluginSystemError> {
+        Self::scan(std::slice::from_ref(&self.install_dir)).await
+    }
+
+    /// Manifests of the installed plugins and of the plugins in the search directories.
+    async fn known_manifests(&self) -> Result<Vec<PluginManifest>, PluginSystemError> {
+        let mut dirs = vec![self.install_dir.clone()];
+        dirs.extend(self.search_dirs.iter().cloned());
+        Self::scan(&dirs).await
+    }
+
+    async fn scan(dirs: &[PathBuf]) -> Result<Vec<PluginManifest>, PluginSystemError> {
+        let mut loader = PluginLoader::new();
+        for dir in dirs {
+            loader.add_plugin_dir(dir);
+        }
+        loader.scan_for_manifests().await
+            .map_err(|e| PluginSystemError::InternalError(format!("Failed to scan for installed plugins: {}", e)))
+    }
+
+    /// Install the plugin in the archive at `archive_path`.
+    /// Fails if a plugin with the same ID is already installed or found in the search directories.
+    pub async fn install<P: AsRef<Path>>(&self, archive_path: P) -> Result<InstallOutcome, PluginSystemError> {
+        let archive = PluginArchive::open(archive_path)?;
+        let plugin_id = archive.manifest().id.clone();
+        archive.check_api_version(&self.api_version)?;
+
+        let known = self.known_manifests().await?;
+        if let Some(existing) = known.iter().find(|manifest| manifest.id == plugin_id) {
+            let message = if existing.plugin_base_dir.starts_with(&self.install_dir) {
+                format!("Version {} is already installed; upgrade it instead", existing.version)
+            } else {
+                format!("A plugin with this ID is already provided by {}", existing.plugin_base_dir.display())
+            };
+            return Err(install_error(&plugin_id, message));
+        }
+        let target = self.install_dir.join(&plugin_id);
+        if target.exists() {
+            return Err(install_error(&plugin_id, format!("{} already exists", target.display())));
+        }
+        let warnings = Self::check_conflicts(&archive, &known)?;
+
+        let staging = self.stage(&archive)?;
+        fs::rename(staging.path().join(STAGED_PLUGIN_DIR), &target)
+            .map_err(|e| install_error(&plugin_id, format!("Failed to move plugin into {}: {}", target.display(), e)))?;
+
+        Ok(InstallOutcome {
+            manifest: Self::read_installed_manifest(&plugin_id, &target)?,
+            previous_version: None,
+            warnings,
+        })
+    }
+
+    /// Replace an installed plugin with the newer version in the archive at `archive_path`.
+    /// The installed version is restored if the new one cannot be moved into place.
+    pub async fn upgrade<P: AsRef<Path>>(&self, archive_path: P) -> Result<InstallOutcome, PluginSystemError> {
+        let archive = PluginArchive::open(archive_path)?;
+        let plugin_id = archive.manifest().id.clone();
+        archive.check_api_version(&self.api_version)?;
+
+        let current = self.installed().await?
+            .into_iter()
+            .find(|manifest| manifest.id == plugin_id)
+            .ok_or_else(|| install_error(&plugin_id, format!("Not installed in {}; install it instead", self.install_dir.display())))?;
+
+        let parse_version = |version: &str| {
+            Version::parse(version).map_err(|e| install_error(&plugin_id, format!("Invalid version '{}': {}", version, e)))
+        };
+        let new_version = parse_version(&archive.manifest().version)?;
+        if new_version <= parse_version(&current.version)? {
+            return Err(install_error(&plugin_id, format!(
+                "Version {} is not newer than the installed version {}",
+                new_version, current.version
+            )));
+        }
+
+        // Dependents must accept the new version
+        let known = self.known_manifests().await?;
+        for dependent in &known {
+            let requirement = dependent.dependencies.iter()
+                .filter(|dependency| dependency.required && dependency.plugin_name == plugin_id)
+                .find_map(|dependency| dependency.version_range.as_ref());
+            if let Some(range) = requirement
+                && !range.includes(&new_version)
+            {
+                return Err(install_error(&plugin_id, format!(
+                    "Plugin '{}' requires version {}, which {} does not satisfy",
+                    dependent.id, range, new_version
+                )));
+            }
+        }
+        let warnings = Self::check_conflicts(&archive, &known)?;
+
+        let staging = self.stage(&archive)?;
+        let target = current.plugin_base_dir.clone();
+        let previous = staging.path().join(PREVIOUS_PLUGIN_DIR);
+        fs::rename(&target, &previous)
+            .map_err(|e| install_error(&plugin_id, format!("Failed to move aside {}: {}", target.display(), e)))?;
+        if let Err(e) = fs::rename(staging.path().join(STAGED_PLUGIN_DIR), &target) {
+            let _ = fs::rename(&previous, &target); // Put the installed version back
+            return Err(install_error(&plugin_id, format!("Failed to move plugin into {}: {}", target.display(), e)));
+        }
+        // The previous version is removed together with the staging directory
+
+        Ok(InstallOutcome {
+            manifest: Self::read_installed_manifest(&plugin_id, &target)?,
+            previous_version: Some(current.version),
+            warnings,
+        })
+    }
+
+    /// Remove an installed plugin and return its manifest.
+    /// Fails if another known plugin requires it.
+    pub async fn uninstall(&self, plugin_id: &str) -> Result<PluginManifest, PluginSystemError> {
+        let manifest = self.installed().await?
+            .into_iter()
+            .find(|manifest| manifest.id == plugin_id)
+            .ok_or_else(|| install_error(plugin_id, format!("Not installed in {}", self.install_dir.display())))?;
+        if manifest.plugin_base_dir == self.install_dir || !manifest.plugin_base_dir.starts_with(&self.install_dir) {
+            return Err(install_error(plugin_id, format!(
+                "Refusing to remove {}, which is not a plugin directory inside {}",
+                manifest.plugin_base_dir.display(), self.install_dir.display()
+            )));
+        }
+
+        let dependents: Vec<String> = self.known_manifests().await?
+            .into_iter()
+            .filter(|other| other.id != plugin_id)
+            .filter(|other| other.dependencies.iter().any(|dependency| dependency.required && dependency.plugin_name == plugin_id))
+            .map(|other| other.id)
+            .collect();
+        if !dependents.is_empty() {
+            return Err(install_error(plugin_id, format!("Required by: {}", dependents.join(", "))));
+        }
+
+        fs::remove_dir_all(&manifest.plugin_base_dir)
+            .map_err(|e| install_error(plugin_id, format!("Failed to remove {}: {}", manifest.plugin_base_dir.display(), e)))?;
+        Ok(manifest)
+    }
+
+    /// Runs conflict detection for the archive's plugin against the known plugins.
+    /// Critical conflicts are returned as an error, the rest as warnings.
+    fn check_conflicts(archive: &PluginArchive, known: &[PluginManifest]) -> Result<Vec<PluginConflict>, PluginSystemError> {
+        let plugin_id = &archive.manifest().id;
+        let mut manifests: Vec<PluginManifest> = known.iter()
+            .filter(|manifest| &manifest.id != plugin_id) // The copy being upgraded
+            .cloned()
+            .collect();
+        manifests.push(archive.manifest().clone());
+
+        let mut conflict_manager = ConflictManager::new();
+        conflict_manager.detect_conflicts(&manifests)
+            .map_err(|e| install_error(plugin_id, format!("Conflict detection failed: {}", e)))?;
+        let (critical, warnings): (Vec<PluginConflict>, Vec<PluginConflict>) = conflict_manager.get_conflicts()
+            .iter()
+            .filter(|conflict| &conflict.first_plugin == plugin_id || &conflict.second_plugin == plugin_id)
+            .cloned()
+            .partition(PluginConflict::is_critical);
+        if !critical.is_empty() {
+            return Err(PluginSystemError::UnresolvedPluginConflicts { conflicts: critical });
+        }
+        Ok(warnings)
+    }
+
+    /// Unpacks the archive into a fresh staging directory inside the install directory,
+    /// so it can be moved into place with a rename. The staging directory is removed on drop.
+    fn stage(&self, archive: &PluginArchive) -> Result<tempfile::TempDir, PluginSystemError> {
+        let plugin_id = &archive.manifest().id;
+        let io_error = |e: std::io::Error| install_error(plugin_id, format!("Failed to unpack into {}: {}", self.install_dir.display(), e));
+        fs::create_dir_all(&self.install_dir).map_err(io_error)?;
+        let staging = tempfile::Builder::new()
+            .prefix(".staging-")
+            .tempdir_in(&self.install_dir)
+            .map_err(io_error)?;
+        archive.unpack(&staging.path().join(STAGED_PLUGIN_DIR)).map_err(io_error)?;
+        Ok(staging)
+    }
+
+    fn read_installed_manifest(plugin_id: &str, dir: &Path) -> Result<PluginManifest, PluginSystemError> {
+        let manifest_path = PluginManifest::find_file(dir)
+            .ok_or_else(|| install_error(plugin_id, format!("No manifest found in {}", dir.display())))?;
+        let content = fs::read_to_string(&manifest_path)
+            .map_err(|e| install_error(plugin_id, format!("Failed to read {}: {}", manifest_path.display(), e)))?;
+        PluginLoader::parse_manifest(&content, &manifest_path)
+    }
+}
@@ -0,0 +1,687 @@
+//! # Plugin Capability Permissions
+//!
+//! Turns the resources a plugin declares (see [`Plugin::declared_resources`] and the
+//! manifest's `resources`) into the permissions it runs with. Four resource types act as
+//! capabilities:
+//!
+//! - `storage_path`: a file or directory; relative paths are relative to the data directory.
+//!   A directory grants access to everything below it.
+//! - `config_scope`: a configuration, named `app:<name>` or `plugin:<name>`. A plugin can
+//!   always read and write its own `plugin:<id>` configuration.
+//! - `event`: an event name the plugin may emit.
+//! - `context_key`: a [`StageContext`] data key.
+//!
+//! Read claims (`SharedRead`, `ExclusiveRead`) grant read access; write claims
+//! (`SharedWrite`, `ExclusiveWrite`, `ProvidesUniqueId`) grant read and write access.
+//! Other resource types are only used for conflict detection.
+//!
+//! [`PermissionManager`] holds the permissions of every registered plugin, records each
+//! access and denial, emits [`SystemEvent::PermissionDenied`] as an audit event, and
+//! produces a [`PermissionReport`] per plugin. Plugins reach permission-checked storage,
+//! configuration, events and context data through [`PluginAccess`], which the registry
+//! hands to each plugin during its `init` ([`PluginAccess::current`]). Event handlers a
+//! plugin registers through it run under the plugin's ID and priority.
+//!
+//! How much the permissions hold depends on how the plugin runs ([`PermissionEnforcement`]).
+//! WebAssembly plugins only reach the host through imports that check every access.
+//! Native plugins are not sandboxed: they receive the full `&mut Application` in `init`, so
+//! their permissions are only checked for the accesses they make through [`PluginAccess`].
+//!
+//! [`Plugin::declared_resources`]: crate::plugin_system::traits::Plugin::declared_resources
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use crate::event::dispatcher::PluginHandlerFn;
+use crate::event::manager::DefaultEventManager;
+use crate::event::{EventId, EventManager, EventPriority, PluginEvent, SystemEvent};
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::conflict::{ResourceAccessType, ResourceClaim};
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::service::ServiceRegistry;
+use crate::stage_manager::context::StageContext;
+use crate::storage::config::{ConfigData, ConfigManager, ConfigScope, PluginConfigScope};
+use crate::storage::error::StorageSystemError;
+use crate::storage::provider::StorageProvider;
+
+/// A kind of resource whose access is checked at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Capability {
+    StoragePath,
+    ConfigScope,
+    Event,
+    ContextKey,
+}
+
+impl Capability {
+    /// The `resource_type` that declares this capability in a manifest.
+    pub fn resource_type(&self) -> &'static str {
+        match self {
+            Capability::StoragePath => "storage_path",
+            Capability::ConfigScope => "config_scope",
+            Capability::Event => "event",
+            Capability::ContextKey => "context_key",
+        }
+    }
+
+    /// Maps a manifest `resource_type` onto a capability, if it is one.
+    pub fn from_resource_type(resource_type: &str) -> Option<Self> {
+        match resource_type {
+            "storage_path" => Some(Capability::StoragePath),
+            "config_scope" => Some(Capability::ConfigScope),
+            "event" => Some(Capability::Event),
+            "context_key" => Some(Capability::ContextKey),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.resource_type())
+    }
+}
+
+/// The kind of access requested. Write access implies read access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+impl Access {
+    fn from_claim(access_type: ResourceAccessType) -> Self {
+        match access_type {
+            ResourceAccessType::SharedRead | ResourceAccessType::ExclusiveRead => Access::Read,
+            ResourceAccessType::ExclusiveWrite | ResourceAccessType::SharedWrite | ResourceAccessType::ProvidesUniqueId => Access::Write,
+        }
+    }
+}
+
+impl fmt::Display for Access {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Access::Read => f.write_str("read"),
+            Access::Write => f.write_str("write"),
+        }
+    }
+}
+
+/// How far a plugin's permissions are enforced, which depends on how it runs.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PermissionEnforcement {
+    /// Native plugin in the `gini` process. It has the full `Application`, so only the
+    /// accesses it makes through [`PluginAccess`] are checked.
+    #[default]
+    NotSandboxed,
+    /// Native plugin in a separate plugin host process. It cannot reach the core's state,
+    /// but its accesses inside the host are not checked.
+    ProcessIsolated,
+    /// WebAssembly plugin. Every access goes through a host import that checks it.
+    Enforced,
+}
+
+impl fmt::Display for PermissionEnforcement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PermissionEnforcement::NotSandboxed => {
+                f.write_str("not sandboxed (native plugin; only accesses through PluginAccess are checked)")
+            }
+            PermissionEnforcement::ProcessIsolated => {
+                f.write_str("process-isolated (runs in a plugin host; accesses there are not checked)")
+            }
+            PermissionEnforcement::Enforced => f.write_str("enforced (WebAssembly; every access is checked)"),
+        }
+    }
+}
+
+/// Identifier of a configuration for `config_scope` permissions.
+pub fn config_scope_id(name: &str, scope: ConfigScope) -> String {
+    match scope {
+        ConfigScope::Application => format!("app:{}", name),
+        ConfigScope::Plugin(_) => format!("plugin:{}", name),
+    }
+}
+
+/// A single permission granted to a plugin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionGrant {
+    pub capability: Capability,
+    pub identifier: String,
+    pub access: Access,
+    /// Granted by default rather than declared (the plugin's own configuration)
+    pub implicit: bool,
+}
+
+impl PermissionGrant {
+    fn covers(&self, capability: Capability, identifier: &str, access: Access, data_dir: Option<&Path>) -> bool {
+        if self.capability != capability || self.access < access {
+            return false;
+        }
+        match capability {
+            Capability::StoragePath => {
+                let granted = resolve_storage_path(data_dir, Path::new(&self.identifier));
+                let requested = resolve_storage_path(data_dir, Path::new(identifier));
+                requested.starts_with(granted)
+            }
+            _ => self.identifier == identifier,
+        }
+    }
+}
+
+/// Resolves a storage path against the data directory and drops `.` components.
+fn resolve_storage_path(data_dir: Option<&Path>, path: &Path) -> PathBuf {
+    let full = match data_dir {
+        Some(data_dir) if path.is_relative() => data_dir.join(path),
+        _ => path.to_path_buf(),
+    };
+    full.components().filter(|c| !matches!(c, Component::CurDir)).collect()
+}
+
+/// The permissions of one plugin, built from its declared resources.
+#[derive(Debug, Clone, Default)]
+pub struct PermissionSet {
+    grants: Vec<PermissionGrant>,
+}
+
+impl PermissionSet {
+    pub fn from_claims(plugin_id: &str, claims: &[ResourceClaim]) -> Self {
+        let mut grants = vec![PermissionGrant {
+            capability: Capability::ConfigScope,
+            identifier: format!("plugin:{}", plugin_id),
+            access: Access::Write,
+            implicit: true,
+        }];
+        for claim in claims {
+            if let Some(capability) = Capability::from_resource_type(&claim.resource.kind) {
+                grants.push(PermissionGrant {
+                    capability,
+                    identifier: claim.resource.id.clone(),
+                    access: Access::from_claim(claim.access_type),
+                    implicit: false,
+                });
+            }
+        }
+        Self { grants }
+    }
+
+    pub fn grants(&self) -> &[PermissionGrant] {
+        &self.grants
+    }
+
+    pub fn allows(&self, capability: Capability, identifier: &str, access: Access, data_dir: Option<&Path>) -> bool {
+        self.grants.iter().any(|grant| grant.covers(capability, identifier, access, data_dir))
+    }
+}
+
+/// Number of allowed and denied accesses to one resource.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccessCounts {
+    pub allowed: u64,
+    pub denied: u64,
+}
+
+/// Recorded accesses of one plugin, keyed by capability, identifier and access kind.
+pub type AccessLog = BTreeMap<(Capability, String, Access), AccessCounts>;
+
+/// What a plugin was granted and what it tried to access.
+#[derive(Debug, Clone)]
+pub struct PermissionReport {
+    pub plugin_id: String,
+    pub enforcement: PermissionEnforcement,
+    pub grants: Vec<PermissionGrant>,
+    /// Accesses by (capability, identifier, access), in a stable order
+    pub accesses: AccessLog,
+}
+
+impl PermissionReport {
+    /// Accesses that were denied at least once.
+    pub fn denied(&self) -> impl Iterator<Item = (&(Capability, String, Access), &AccessCounts)> {
+        self.accesses.iter().filter(|(_, counts)| counts.denied > 0)
+    }
+}
+
+impl fmt::Display for PermissionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Permissions for plugin '{}':", self.plugin_id)?;
+        writeln!(f, "  enforcement: {}", self.enforcement)?;
+        for grant in &self.grants {
+            let implicit = if grant.implicit { " (implicit)" } else { "" };
+            writeln!(f, "  granted {} {} '{}'{}", grant.access, grant.capability, grant.identifier, implicit)?;
+        }
+        if self.accesses.is_empty() {
+            writeln!(f, "  no accesses recorded")?;
+        }
+        for ((capability, identifier, access), counts) in &self.accesses {
+            writeln!(
+                f,
+                "  {} {} '{}': {} allowed, {} denied",
+                access, capability, identifier, counts.allowed, counts.denied
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks plugin accesses against their declared resources and keeps an audit trail.
+#[derive(Default)]
+pub struct PermissionManager {
+    sets: RwLock<HashMap<String, PermissionSet>>,
+    enforcement: RwLock<HashMap<String, PermissionEnforcement>>, // Plugins not listed are not sandboxed
+    accesses: RwLock<HashMap<String, AccessLog>>,
+    data_dir: RwLock<Option<PathBuf>>, // Base of relative storage paths
+    event_manager: RwLock<Option<Arc<dyn EventManager>>>, // Receives audit events
+}
+
+impl fmt::Debug for PermissionManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PermissionManager")
+            .field("plugins", &self.sets.read().map(|sets| sets.len()).unwrap_or(0))
+            .finish()
+    }
+}
+
+impl PermissionManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the event manager that receives `PermissionDenied` audit events.
+    pub fn set_event_manager(&self, event_manager: Arc<dyn EventManager>) {
+        if let Ok(mut slot) = self.event_manager.write() {
+            *slot = Some(event_manager);
+        }
+    }
+
+    /// Sets the directory relative `storage_path` resources are resolved against.
+    pub fn set_data_dir(&self, data_dir: PathBuf) {
+        if let Ok(mut slot) = self.data_dir.write() {
+            *slot = Some(data_dir);
+        }
+    }
+
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        self.data_dir.read().ok().and_then(|data_dir| data_dir.clone())
+    }
+
+    /// Grants a plugin the capabilities among its declared resources, replacing any previous set.
+    pub fn register(&self, plugin_id: &str, claims: &[ResourceClaim]) {
+        if let Ok(mut sets) = self.sets.write() {
+            sets.insert(plugin_id.to_string(), PermissionSet::from_claims(plugin_id, claims));
+        }
+    }
+
+    /// Drops a plugin's permissions. Its recorded accesses are kept for the report.
+    pub fn unregister(&self, plugin_id: &str) {
+        if let Ok(mut sets) = self.sets.write() {
+            sets.remove(plugin_id);
+        }
+        if let Ok(mut enforcement) = self.enforcement.write() {
+            enforcement.remove(plugin_id);
+        }
+    }
+
+    /// Records how a registered plugin runs. Plugins without a record are native plugins
+    /// in this process, which are not sandboxed.
+    pub fn set_enforcement(&self, plugin_id: &str, enforcement: PermissionEnforcement) {
+        if let Ok(mut levels) = self.enforcement.write() {
+            levels.insert(plugin_id.to_string(), enforcement);
+        }
+    }
+
+    /// How far a plugin's permissions are enforced
+    pub fn enforcement(&self, plugin_id: &str) -> PermissionEnforcement {
+        self.enforcement.read().ok().and_then(|levels| levels.get(plugin_id).copied()).unwrap_or_default()
+    }
+
+    pub fn permission_set(&self, plugin_id: &str) -> Option<PermissionSet> {
+        self.sets.read().ok().and_then(|sets| sets.get(plugin_id).cloned())
+    }
+
+    /// Checks an access without recording it.
+    pub fn is_allowed(&self, plugin_id: &str, capability: Capability, identifier: &str, access: Access) -> bool {
+        let data_dir = self.data_dir();
+        self.sets
+            .read()
+            .map(|sets| {
+                sets.get(plugin_id)
+                    .is_some_and(|set| set.allows(capability, identifier, access, data_dir.as_deref()))
+            })
+            .unwrap_or(false)
+    }
+
+    /// Checks an access and records it. A denied access returns `PermissionDenied`,
+    /// is logged and emits a `PermissionDenied` audit event.
+    pub fn check(&self, plugin_id: &str, capability: Capability, identifier: &str, access: Access) -> Result<(), PluginSystemError> {
+        let allowed = self.is_allowed(plugin_id, capability, identifier, access);
+        if let Ok(mut accesses) = self.accesses.write() {
+            let counts = accesses
+                .entry(plugin_id.to_string())
+                .or_default()
+                .entry((capability, identifier.to_string(), access))
+                .or_default();
+            if allowed {
+                counts.allowed += 1;
+            } else {
+                counts.denied += 1;
+            }
+        }
+        if allowed {
+            return Ok(());
+        }
+
+        log::warn!(
+            "Plugin '{}' was denied {} access to undeclared {} '{}'",
+            plugin_id, access, capability, identifier
+        );
+        self.emit_audit_event(SystemEvent::PermissionDenied {
+            plugin_id: plugin_id.to_string(),
+            resource_type: capability.resource_type().to_string(),
+            identifier: identifier.to_string(),
+            access: access.to_string(),
+        });
+        Err(PluginSystemError::PermissionDenied {
+            plugin_id: plugin_id.to_string(),
+            resource_type: capability.resource_type().to_string(),
+            identifier: identifier.to_string(),
+            access: access.to_string(),
+        })
+    }
+
+    fn emit_audit_event(&self, event: SystemEvent) {
+        let Some(event_manager) = self.event_manager.read().ok().and_then(|slot| slot.clone()) else {
+            return;
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move { event_manager.queue_event(Box::new(event)).await });
+            }
+            Err(_) => log::warn!("Audit event dropped outside of a tokio runtime: {:?}", event),
+        }
+    }
+
+    /// The permission report of a plugin that is registered or has recorded accesses.
+    pub fn report(&self, plugin_id: &str) -> Option<PermissionReport> {
+        let grants = self.permission_set(plugin_id).map(|set| set.grants().to_vec());
+        let accesses = self.accesses.read().ok().and_then(|accesses| accesses.get(plugin_id).cloned());
+        if grants.is_none() && accesses.is_none() {
+            return None;
+        }
+        Some(PermissionReport {
+            plugin_id: plugin_id.to_string(),
+            enforcement: self.enforcement(plugin_id),
+            grants: grants.unwrap_or_default(),
+            accesses: accesses.unwrap_or_default(),
+        })
+    }
+
+    /// Reports for every registered plugin, sorted by plugin ID.
+    pub fn reports(&self) -> Vec<PermissionReport> {
+        let mut ids: Vec<String> = self.sets.read().map(|sets| sets.keys().cloned().collect()).unwrap_or_default();
+        ids.sort();
+        ids.iter().filter_map(|id| self.report(id)).collect()
+    }
+}
+
+/// Permission-checked access to storage, configuration, events and stage context data
+/// on behalf of one plugin, and the services it publishes.
+///
+/// The handle is bound to the plugin's registry ID: a plugin cannot act as another plugin
+/// through it. The plugin registry builds it before the plugin's `init`; the plugin gets it
+/// with [`current`](Self::current) and may keep a clone for later.
+#[derive(Clone)]
+pub struct PluginAccess {
+    plugin_id: String,
+    permissions: Arc<PermissionManager>,
+    storage: Arc<dyn StorageProvider>,
+    config_manager: Arc<ConfigManager>,
+    event_manager: Option<Arc<DefaultEventManager>>,
+    services: Arc<ServiceRegistry>,
+}
+
+impl PluginAccess {
+    pub(crate) fn new(
+        plugin_id: &str,
+        permissions: Arc<PermissionManager>,
+        storage: Arc<dyn StorageProvider>,
+        config_manager: Arc<ConfigManager>,
+        event_manager: Option<Arc<DefaultEventManager>>,
+        services: Arc<ServiceRegistry>,
+    ) -> Self {
+        Self {
+            plugin_id: plugin_id.to_string(),
+            permissions,
+            storage,
+            config_manager,
+            event_manager,
+            services,
+        }
+    }
+
+    /// Builds the access handle for a plugin from the application's managers and the
+    /// permissions and services of the registry initializing it.
+    pub(crate) fn for_plugin(app: &Application, plugin_id: &str, permissions: Arc<PermissionManager>, services: Arc<ServiceRegistry>) -> Self {
+        let storage_manager = app.storage_manager();
+        Self::new(
+            plugin_id,
+            permissions,
+            storage_manager.provider().clone(),
+            storage_manager.get_config_manager().clone(),
+            Some(app.event_manager()),
+            services,
+        )
+    }
+
+    /// The access handle of the plugin whose `init` is running.
+    pub fn current(app: &Application) -> Result<Self, PluginSystemError> {
+        app.plugin_access().cloned().ok_or_else(|| PluginSystemError::OperationError {
+            plugin_id: None,
+            message: "Plugin access is only handed out while a plugin initializes".to_string(),
+        })
+    }
+
+    pub fn plugin_id(&self) -> &str {
+        &self.plugin_id
+    }
+
+    pub fn permissions(&self) -> &Arc<PermissionManager> {
+        &self.permissions
+    }
+
+    /// Checks an access for this plugin.
+    pub fn check(&self, capability: Capability, identifier: &str, access: Access) -> Result<(), PluginSystemError> {
+        self.permissions.check(&self.plugin_id, capability, identifier, access)
+    }
+
+    /// Storage restricted to the plugin's declared `storage_path` resources.
+    /// Relative paths are resolved against the data directory.
+    pub fn storage(&self) -> Arc<dyn StorageProvider> {
+        Arc::new(PermissionedStorage {
+            plugin_id: self.plugin_id.clone(),
+            permissions: self.permissions.clone(),
+            inner: self.storage.clone(),
+        })
+    }
+
+    pub fn load_config(&self, name: &str, scope: ConfigScope) -> Result<ConfigData, PluginSystemError> {
+        self.check(Capability::ConfigScope, &config_scope_id(name, scope), Access::Read)?;
+        self.config_manager.load_config(name, scope).map_err(|e| self.operation_error(e))
+    }
+
+    pub fn save_config(&self, name: &str, config: &ConfigData, scope: ConfigScope) -> Result<(), PluginSystemError> {
+        self.check(Capability::ConfigScope, &config_scope_id(name, scope), Access::Write)?;
+        self.config_manager.save_config(name, config, scope).map_err(|e| self.operation_error(e))
+    }
+
+    /// The plugin's own configuration, user values over defaults.
+    pub fn plugin_config(&self) -> Result<ConfigData, PluginSystemError> {
+        self.check(Capability::ConfigScope, &config_scope_id(&self.plugin_id, ConfigScope::Plugin(PluginConfigScope::User)), Access::Read)?;
+        self.config_manager.get_plugin_config(&self.plugin_id).map_err(|e| self.operation_error(e))
+    }
+
+    /// Checks that the plugin may emit `name` and builds the event.
+    pub fn plugin_event(&self, name: &str, data: &str) -> Result<PluginEvent, PluginSystemError> {
+        self.check(Capability::Event, name, Access::Write)?;
+        Ok(PluginEvent {
+            name: name.to_string(),
+            source: self.plugin_id.clone(),
+            data: data.to_string(),
+            priority: EventPriority::Normal,
+            cancelable: false,
+        })
+    }
+
+    /// Queues a plugin event if the plugin declared it.
+    pub async fn emit_event(&self, name: &str, data: &str) -> Result<(), PluginSystemError> {
+        let event = self.plugin_event(name, data)?;
+        if let Some(event_manager) = &self.event_manager {
+            event_manager.queue_event(Box::new(event)).await;
+        }
+        Ok(())
+    }
+
+    /// Registers `handler` for events named `event_name` as this plugin's handler,
+    /// so it runs with the plugin's priority.
+    pub async fn register_event_handler(&self, event_name: &'static str, handler: PluginHandlerFn) -> Result<EventId, PluginSystemError> {
+        let event_manager = self.event_manager.as_ref()
+            .ok_or_else(|| self.operation_error("No event manager is available"))?;
+        Ok(event_manager.register_plugin_handler(&self.plugin_id, event_name, handler).await)
+    }
+
+    /// Publishes `service` under `name`, provided by this plugin.
+    /// See [`ServiceRegistry`] for how services are looked up and withdrawn.
+    pub fn publish_service<T: ?Sized + Send + Sync + 'static>(&self, name: &str, version: &str, service: Arc<T>) -> Result<(), PluginSystemError> {
+        self.services.publish(&self.plugin_id, name, version, service)
+    }
+
+    /// The services published by initialized plugins, for lookups
+    pub fn services(&self) -> &Arc<ServiceRegistry> {
+        &self.services
+    }
+
+    pub fn context_data<'a, T: 'static + Send + Sync>(&self, context: &'a StageContext, key: &str) -> Result<Option<&'a T>, PluginSystemError> {
+        self.check(Capability::ContextKey, key, Access::Read)?;
+        Ok(context.get_data::<T>(key))
+    }
+
+    pub fn set_context_data<T: 'static + Send + Sync>(&self, context: &mut StageContext, key: &str, value: T) -> Result<(), PluginSystemError> {
+        self.check(Capability::ContextKey, key, Access::Write)?;
+        context.set_data(key, value);
+        Ok(())
+    }
+
+    fn operation_error(&self, error: impl fmt::Display) -> PluginSystemError {
+        PluginSystemError::OperationError {
+            plugin_id: Some(self.plugin_id.clone()),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// A storage provider that checks every path against a plugin's `storage_path` permissions.
+#[derive(Debug)]
+struct PermissionedStorage {
+    plugin_id: String,
+    permissions: Arc<PermissionManager>,
+    inner: Arc<dyn StorageProvider>,
+}
+
+impl PermissionedStorage {
+    /// Checks the access and returns the path resolved against the data directory.
+    fn check(&self, path: &Path, access: Access, operation: &str) -> Result<PathBuf, StorageSystemError> {
+        let resolved = resolve_storage_path(self.permissions.data_dir().as_deref(), path);
+        if resolved.components().any(|c| matches!(c, Component::ParentDir)) {
+            return Err(StorageSystemError::AccessDenied(path.to_path_buf(), operation.to_string()));
+        }
+        self.permissions
+            .check(&self.plugin_id, Capability::StoragePath, &resolved.to_string_lossy(), access)
+            .map_err(|_| StorageSystemError::AccessDenied(path.to_path_buf(), operation.to_string()))?;
+        Ok(resolved)
+    }
+}
+
+impl StorageProvider for PermissionedStorage {
+    fn name(&self) -> &str {
+        "PermissionedStorage"
+    }
+
+    fn exists(&self, path: &Path) -> bool {
+        self.check(path, Access::Read, "exists").is_ok_and(|path| self.inner.exists(&path))
+    }
+
+    fn is_file(&self, path: &Path) -> bool {
+        self.check(path, Access::Read, "is_file").is_ok_and(|path| self.inner.is_file(&path))
+    }
+
+    fn is_dir(&self, path: &Path) -> bool {
+        self.check(path, Access::Read, "is_dir").is_ok_and(|path| self.inner.is_dir(&path))
+    }
+
+    fn create_dir(&self, path: &Path) -> Result<(), StorageSystemError> {
+        self.inner.create_dir(&self.check(path, Access::Write, "create_dir")?)
+    }
+
+    fn create_dir_all(&self, path: &Path) -> Result<(), StorageSystemError> {
+        self.inner.create_dir_all(&self.check(path, Access::Write, "create_dir_all")?)
+    }
+
+    fn read_to_string(&self, path: &Path) -> Result<String, StorageSystemError> {
+        self.inner.read_to_string(&self.check(path, Access::Read, "read_to_string")?)
+    }
+
+    fn read_to_bytes(&self, path: &Path) -> Result<Vec<u8>, StorageSystemError> {
+        self.inner.read_to_bytes(&self.check(path, Access::Read, "read_to_bytes")?)
+    }
+
+    fn write_string(&self, path: &Path, contents: &str) -> Result<(), StorageSystemError> {
+        self.inner.write_string(&self.check(path, Access::Write, "write_string")?, contents)
+    }
+
+    fn write_bytes(&self, path: &Path, contents: &[u8]) -> Result<(), StorageSystemError> {
+        self.inner.write_bytes(&self.check(path, Access::Write, "write_bytes")?, contents)
+    }
+
+    fn copy(&self, from: &Path, to: &Path) -> Result<(), StorageSystemError> {
+        let from = self.check(from, Access::Read, "copy")?;
+        self.inner.copy(&from, &self.check(to, Access::Write, "copy")?)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> Result<(), StorageSystemError> {
+        let from = self.check(from, Access::Write, "rename")?;
+        self.inner.rename(&from, &self.check(to, Access::Write, "rename")?)
+    }
+
+    fn remove_file(&self, path: &Path) -> Result<(), StorageSystemError> {
+        self.inner.remove_file(&self.check(path, Access::Write, "remove_file")?)
+    }
+
+    fn remove_dir(&self, path: &Path) -> Result<(), StorageSystemError> {
+        self.inner.remove_dir(&self.check(path, Access::Write, "remove_dir")?)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> Result<(), StorageSystemError> {
+        self.inner.remove_dir_all(&self.check(path, Access::Write, "remove_dir_all")?)
+    }
+
+    fn read_dir(&self, path: &Path) -> Result<Vec<PathBuf>, StorageSystemError> {
+        self.inner.read_dir(&self.check(path, Access::Read, "read_dir")?)
+    }
+
+    fn metadata(&self, path: &Path) -> Result<std::fs::Metadata, StorageSystemError> {
+        self.inner.metadata(&self.check(path, Access::Read, "metadata")?)
+    }
+
+    fn open_read(&self, path: &Path) -> Result<Box<dyn Read>, StorageSystemError> {
+        self.inner.open_read(&self.check(path, Access::Read, "open_read")?)
+    }
+
+    fn open_write(&self, path: &Path) -> Result<Box<dyn Write>, StorageSystemError> {
+        self.inner.open_write(&self.check(path, Access::Write, "open_write")?)
+    }
+
+    fn open_append(&self, path: &Path) -> Result<Box<dyn Write>, StorageSystemError> {
+        self.inner.open_append(&self.check(path, Access::Write, "open_append")?)
+    }
+}
@@ -7,9 +7,17 @@ use std::future::Future;
 use crate::kernel::error::{Error, Result as KernelResult}; // Import KernelResult alias
 use crate::plugin_system::error::PluginSystemError;
 use crate::kernel::bootstrap::Application;
+use crate::event::EventManager; // Records plugin priorities for event handlers
 use crate::plugin_system::traits::{Plugin, PluginPriority}; // Added PluginPriority
 use crate::plugin_system::version::ApiVersion;
-use crate::plugin_system::conflict::{ConflictManager, ConflictType, PluginConflict, ResourceAccessType}; // Removed ResourceIdentifier
+use crate::plugin_system::lazy::LazyPlugin; // Manifest-only plugin entries
+use crate::plugin_system::manifest::PluginManifest;
+use crate::plugin_system::conflict::{ConflictDecision, ConflictManager, ConflictParty, ConflictPolicy, ConflictType, PluginConflict, ResolutionStrategy}; // Removed ResourceIdentifier
+use crate::plugin_system::permission::{PermissionManager, PluginAccess}; // Capability permissions from declared resources
+use crate::plugin_system::lifecycle::{PluginLifecycle, PluginState}; // Lifecycle state of each plugin
+use crate::plugin_system::service::ServiceRegistry; // Typed APIs plugins publish for each other
+use crate::plugin_system::signature::SignatureVerifier; // Signature checks before lazy loads
+use crate::plugin_system::startup::{InitFailurePolicy, PluginOutcome, StartupReport}; // Isolated init failures
 use crate::stage_manager::registry::StageRegistry; // Keep StageRegistry, SharedStageRegistry not directly used in this file's signatures now
 use semver::{Version, VersionReq, Op}; // Removed Comparator
 
@@ -157,14 +165,28 @@ fn are_bounds_disjoint(
 pub struct PluginRegistry {
     /// Registered plugins (using Arc for shared ownership)
     pub plugins: HashMap<String, Arc<dyn Plugin>>,
-    /// Plugin initialization status
-    pub initialized: HashSet<String>,
-    /// Enabled plugin IDs
-    pub enabled: HashSet<String>,
+    /// Lifecycle state of each plugin; decides which plugins are enabled and initialized
+    lifecycle: Arc<PluginLifecycle>,
+    /// Plugins registered from their manifest only; their libraries are loaded on first use
+    lazy_plugins: HashMap<String, Arc<LazyPlugin>>,
     /// Current API version
     api_version: ApiVersion,
     /// Conflict manager
     conflict_manager: ConflictManager, // Add ConflictManager field
+    /// Runtime permissions derived from each plugin's declared resources
+    permissions: Arc<PermissionManager>,
+    /// Services published by initialized plugins, withdrawn as their providers shut down
+    services: Arc<ServiceRegistry>,
+    /// Signature checks for manifest-only plugins, shared with the plugin manager
+    signature_verifier: Arc<SignatureVerifier>,
+    /// Pairs of plugins the user allowed to run together despite a conflict (IDs in sorted order)
+    allowed_conflicts: HashSet<(String, String)>,
+    /// What `initialize_all` does when a non-core plugin fails to initialize
+    init_failure_policy: InitFailurePolicy,
+    /// Outcome of the last `initialize_all`
+    last_startup_report: Option<StartupReport>,
+    /// IDs of the initialized plugins in the order they were initialized; `shutdown_all` reverses it
+    init_order: Vec<String>,
 }
 
 // Helper struct for priority queue in topological_sort, moved to module scope
@@ -196,15 +218,67 @@ impl PluginRegistry {
     pub fn new(api_version: ApiVersion) -> Self {
         Self {
             plugins: HashMap::new(),
-            initialized: HashSet::new(),
-            enabled: HashSet::new(), // Initialize enabled set
+            lifecycle: Arc::new(PluginLifecycle::new()),
+            lazy_plugins: HashMap::new(),
             api_version,
             conflict_manager: ConflictManager::new(), // Initialize ConflictManager
+            permissions: Arc::new(PermissionManager::new()),
+            services: Arc::new(ServiceRegistry::new()),
+            signature_verifier: Arc::new(SignatureVerifier::default()),
+            allowed_conflicts: HashSet::new(),
+            init_failure_policy: InitFailurePolicy::default(),
+            last_startup_report: None,
+            init_order: Vec::new(),
         }
     }
-    
+
+    /// What [`initialize_all`](Self::initialize_all) does when a non-core plugin fails to initialize
+    pub fn init_failure_policy(&self) -> InitFailurePolicy {
+        self.init_failure_policy
+    }
+
+    /// Sets the policy applied when a non-core plugin fails to initialize
+    pub fn set_init_failure_policy(&mut self, policy: InitFailurePolicy) {
+        self.init_failure_policy = policy;
+    }
+
+    /// Outcome of the last [`initialize_all`](Self::initialize_all) that got to initializing plugins
+    pub fn last_startup_report(&self) -> Option<&StartupReport> {
+        self.last_startup_report.as_ref()
+    }
+
+    /// IDs of the initialized plugins in the order they were initialized
+    pub fn initialization_order(&self) -> Vec<String> {
+        self.init_order.iter().filter(|id| self.is_initialized(id)).cloned().collect()
+    }
+
+    /// Permissions of the registered plugins, kept in sync as plugins are registered and unregistered
+    pub fn permissions(&self) -> &Arc<PermissionManager> {
+        &self.permissions
+    }
+
+    /// Services published by the initialized plugins
+    pub fn services(&self) -> &Arc<ServiceRegistry> {
+        &self.services
+    }
+
+    /// Signature policy and trust store applied when manifest-only plugins are loaded
+    pub fn signature_verifier(&self) -> &Arc<SignatureVerifier> {
+        &self.signature_verifier
+    }
+
+    /// Lifecycle states of the registered plugins, with the reason for each state and the last error
+    pub fn lifecycle(&self) -> &Arc<PluginLifecycle> {
+        &self.lifecycle
+    }
+
     /// Register a plugin
     pub fn register_plugin(&mut self, plugin_arc: Arc<dyn Plugin>) -> std::result::Result<(), PluginSystemError> {
+        self.register_plugin_as(plugin_arc, PluginState::Loaded, "Registered")
+    }
+
+    /// Register a plugin that starts out in `state`; newly registered plugins are enabled
+    fn register_plugin_as(&mut self, plugin_arc: Arc<dyn Plugin>, state: PluginState, reason: &str) -> std::result::Result<(), PluginSystemError> {
         let name = plugin_arc.name().to_string();
         let id = name.clone(); // Use name as ID for now
 
@@ -215,8 +289,17 @@ impl PluginRegistry {
             });
         }
         
-        // Check API compatibility
-        let mut compatible = false;
+        self.check_api_compatibility(plugin_arc.as_ref())?;
+
+        // All good, register the plugin Arc and grant the capabilities it declares
+        self.lifecycle.register(&id, state, reason)?;
+        self.permissions.register(&id, &plugin_arc.declared_resources());
+        self.plugins.insert(id, plugin_arc);
+        Ok(())
+    }
+    
+    /// Check that a plugin supports the registry's API version
+    pub fn check_api_compatibility(&self, plugin: &dyn Plugin) -> std::result::Result<(), PluginSystemError> {
         // Convert ApiVersion to semver::Version for comparison
         let api_semver = match semver::Version::parse(&self.api_version.to_string()) {
             Ok(v) => v,
@@ -230,35 +313,63 @@ impl PluginRegistry {
                 ));
             }
         };
-        for version_range in plugin_arc.compatible_api_versions() {
-            if version_range.includes(&api_semver) {
-                compatible = true;
-                break;
-            }
-        }
-        
-        if !compatible {
-            return Err(PluginSystemError::LoadingError {
-                plugin_id: name,
-                path: None, // Path not directly available here, could be added if passed
-                source: Box::new(crate::plugin_system::error::PluginSystemErrorSource::Other(
-                    format!("Plugin not compatible with API version {}", self.api_version)
-                )),
-            });
+        if plugin.compatible_api_versions().iter().any(|version_range| version_range.includes(&api_semver)) {
+            return Ok(());
         }
-        
-        // All good, register the plugin Arc
-        self.plugins.insert(id.clone(), plugin_arc);
-        // Newly registered plugins are enabled by default
-        self.enabled.insert(id);
+        Err(PluginSystemError::LoadingError {
+            plugin_id: plugin.name().to_string(),
+            path: None, // Path not directly available here, could be added if passed
+            source: Box::new(crate::plugin_system::error::PluginSystemErrorSource::Other(
+                format!("Plugin not compatible with API version {}", self.api_version)
+            )),
+        })
+    }
+
+    /// Register a plugin from its manifest without loading its library.
+    /// Metadata, dependencies, conflicts and resources are taken from the manifest;
+    /// the entry point is only loaded when the plugin is pre-flight checked or initialized.
+    pub fn register_manifest(&mut self, manifest: PluginManifest) -> std::result::Result<(), PluginSystemError> {
+        let id = manifest.id.clone();
+        let lazy_plugin = Arc::new(LazyPlugin::new(manifest).with_signature_verifier(self.signature_verifier.clone()));
+        self.register_plugin_as(lazy_plugin.clone(), PluginState::Discovered, "Registered from its manifest")?;
+        self.lazy_plugins.insert(id, lazy_plugin);
         Ok(())
     }
-    
+
+    /// Register a plugin from its manifest like [`register_manifest`](Self::register_manifest),
+    /// with its library already loaded as `instance`. Used when a plugin is reloaded.
+    pub(crate) fn register_loaded_manifest(&mut self, manifest: PluginManifest, instance: Arc<dyn Plugin>) -> std::result::Result<(), PluginSystemError> {
+        let id = manifest.id.clone();
+        let lazy_plugin = Arc::new(LazyPlugin::new(manifest).with_signature_verifier(self.signature_verifier.clone()).with_instance(instance));
+        self.register_plugin_as(lazy_plugin.clone(), PluginState::Loaded, "Registered from its manifest")?;
+        self.lazy_plugins.insert(id, lazy_plugin);
+        Ok(())
+    }
+
+    /// Get the manifest of a plugin that was registered with `register_manifest`
+    pub fn get_manifest(&self, id: &str) -> Option<&PluginManifest> {
+        self.lazy_plugins.get(id).map(|plugin| plugin.manifest())
+    }
+
+    /// Check whether a plugin's code is loaded.
+    /// Always true for plugins registered as instances; manifest-only plugins are
+    /// loaded once they are pre-flight checked or initialized.
+    pub fn is_plugin_code_loaded(&self, id: &str) -> bool {
+        match self.lazy_plugins.get(id) {
+            Some(lazy_plugin) => lazy_plugin.is_loaded(),
+            None => self.plugins.contains_key(id),
+        }
+    }
+
     /// Unregister a plugin by ID
     pub fn unregister_plugin(&mut self, id: &str) -> std::result::Result<Arc<dyn Plugin>, PluginSystemError> {
         if let Some(plugin) = self.plugins.remove(id) {
-            self.initialized.remove(id);
-            self.enabled.remove(id);
+            // The state stays queryable after the plugin is gone
+            self.lifecycle.transition(id, PluginState::Unloaded, "Unregistered")?;
+            self.lazy_plugins.remove(id);
+            self.init_order.retain(|initialized| initialized != id);
+            self.permissions.unregister(id);
+            self.services.withdraw(id);
             Ok(plugin)
         } else {
             Err(PluginSystemError::RegistrationError {
@@ -295,7 +406,7 @@ impl PluginRegistry {
     pub fn get_enabled_plugins_arc(&self) -> Vec<Arc<dyn Plugin>> {
         self.plugins
             .iter()
-            .filter(|(id, _)| self.enabled.contains(*id))
+            .filter(|(id, _)| self.is_enabled(id))
             .map(|(_, plugin)| plugin.clone())
             .collect()
     }
@@ -446,7 +557,7 @@ impl PluginRegistry {
              return Ok(());
         }
 
-        if self.initialized.contains(id) {
+        if self.is_initialized(id) {
             return Ok(());
         }
 
@@ -461,76 +572,128 @@ impl PluginRegistry {
         };
         let dependencies = plugin_arc.dependencies().clone();
 
-        for dep in dependencies {
-            let dep_exists = self.has_plugin(&dep.plugin_name);
-            let dep_enabled = self.is_enabled(&dep.plugin_name);
+        let result: std::result::Result<(), PluginSystemError> = async {
+            for dep in dependencies {
+                let dep_exists = self.has_plugin(&dep.plugin_name);
+                let dep_enabled = self.is_enabled(&dep.plugin_name);
+
+                if dep.required && (!dep_exists || !dep_enabled) {
+                    return Err(PluginSystemError::DependencyResolution(crate::plugin_system::dependency::DependencyError::MissingPlugin(dep.plugin_name.clone())));
+                }
+
+                if dep_exists
+                    && let Some(dep_plugin) = self.get_plugin(&dep.plugin_name)
+                    && let Some(ref required_range) = dep.version_range
+                {
+                    match semver::Version::parse(dep_plugin.version()) {
+                        Ok(dep_version) => {
+                            if !required_range.includes(&dep_version) {
+                                return Err(PluginSystemError::DependencyResolution(crate::plugin_system::dependency::DependencyError::IncompatibleVersion {
+                                    plugin_name: dep.plugin_name.clone(),
+                                    required_range: required_range.clone(),
+                                    actual_version: dep_plugin.version().to_string(),
+                                }));
+                            }
+                        },
+                        Err(e) => {
+                            return Err(PluginSystemError::VersionParsing(crate::plugin_system::version::VersionError::ParseError(format!(
+                                "Failed to parse version string '{}' for dependency plugin '{}': {}",
+                                dep_plugin.version(), dep.plugin_name, e
+                            ))));
+                        }
+                    }
+                }
+
+                if dep.required && dep_exists && dep_enabled && !self.is_initialized(&dep.plugin_name) {
+                    self.initialize_plugin_recursive(
+                        &dep.plugin_name,
+                        app,
+                        stage_registry_arc,
+                        currently_initializing
+                    ).await?;
+                }
+            }
 
-            if dep.required && (!dep_exists || !dep_enabled) {
-                return Err(PluginSystemError::DependencyResolution(crate::plugin_system::dependency::DependencyError::MissingPlugin(dep.plugin_name.clone())));
+            // Dependencies are satisfied; a plugin that passed its pre-flight check stays in that state
+            if self.lifecycle.state(id) != Some(PluginState::PreflightPassed) {
+                self.lifecycle.transition(id, PluginState::Resolved, "Dependencies satisfied")?;
             }
 
-            if dep_exists {
-                 if let Some(dep_plugin) = self.get_plugin(&dep.plugin_name) {
-                     if let Some(ref required_range) = dep.version_range {
-                         match semver::Version::parse(dep_plugin.version()) {
-                             Ok(dep_version) => {
-                                 if !required_range.includes(&dep_version) {
-                                     return Err(PluginSystemError::DependencyResolution(crate::plugin_system::dependency::DependencyError::IncompatibleVersion {
-                                         plugin_name: dep.plugin_name.clone(),
-                                         required_range: required_range.clone(),
-                                         actual_version: dep_plugin.version().to_string(),
-                                     }));
-                                 }
-                             },
-                             Err(e) => {
-                                 return Err(PluginSystemError::VersionParsing(crate::plugin_system::version::VersionError::ParseError(format!(
-                                     "Failed to parse version string '{}' for dependency plugin '{}': {}",
-                                     dep_plugin.version(), dep.plugin_name, e
-                                 ))));
-                             }
-                         }
-                     }
-                 }
+            for requirement in plugin_arc.required_services() {
+                self.services.check(&requirement)?;
             }
 
-            if dep.required && dep_exists && dep_enabled && !self.initialized.contains(&dep.plugin_name) {
-                 self.initialize_plugin_recursive(
-                     &dep.plugin_name,
-                     app,
-                     stage_registry_arc,
-                     currently_initializing
-                 ).await?;
+            let config_manager = app.storage_manager().get_config_manager().clone();
+            if let Some(schema) = plugin_arc.config_schema() {
+                config_manager.register_plugin_schema(id, schema);
             }
-       }
-
-       println!("[PluginRegistry] Initializing plugin: {}", id);
-       plugin_arc.init(app).map_err(|e| PluginSystemError::InitializationError {
-           plugin_id: id.to_string(),
-           message: e.to_string(),
-           source: Some(Box::new(crate::plugin_system::error::PluginSystemErrorSource::Other(e.to_string()))), // Or map specific source if possible
-       })?;
-       println!("[PluginRegistry] Plugin initialized: {}", id);
-
-       println!("[PluginRegistry] Attempting to register stages for plugin: {}...", id);
-       let mut registry_guard = stage_registry_arc.lock().await;
-       println!("[PluginRegistry] StageRegistry locked for plugin: {}", id);
-       plugin_arc.register_stages(&mut *registry_guard)?; // This now returns Result<_, PluginSystemError>
-       drop(registry_guard);
-       println!("[PluginRegistry] StageRegistry unlocked for plugin: {}", id);
-
-        self.initialized.insert(id.to_string());
+            let violations = config_manager.validate_plugin_config(id).map_err(|e| PluginSystemError::OperationError {
+                plugin_id: Some(id.to_string()),
+                message: format!("Failed to load configuration: {}", e),
+            })?;
+            if !violations.is_empty() {
+                return Err(PluginSystemError::InvalidConfig { plugin_id: id.to_string(), violations });
+            }
+
+            // Handlers the plugin registers run with its priority
+            app.event_manager().set_plugin_priority(id, plugin_arc.priority()).await;
+
+            println!("[PluginRegistry] Initializing plugin: {}", id);
+            // The plugin acts under its own ID through the access handle it finds on the app
+            app.set_plugin_access(Some(PluginAccess::for_plugin(app, id, self.permissions.clone(), self.services.clone())));
+            let init_result = plugin_arc.init(app);
+            app.set_plugin_access(None);
+            init_result.map_err(|e| PluginSystemError::InitializationError {
+                plugin_id: id.to_string(),
+                message: e.to_string(),
+                source: Some(Box::new(crate::plugin_system::error::PluginSystemErrorSource::Other(e.to_string()))), // Or map specific source if possible
+            })?;
+            println!("[PluginRegistry] Plugin initialized: {}", id);
+            self.lifecycle.transition(id, PluginState::Initialized, "Initialized")?;
+            self.init_order.retain(|initialized| initialized != id);
+            self.init_order.push(id.to_string());
+
+            println!("[PluginRegistry] Attempting to register stages for plugin: {}...", id);
+            let mut registry_guard = stage_registry_arc.lock().await;
+            println!("[PluginRegistry] StageRegistry locked for plugin: {}", id);
+            registry_guard.register_plugin_stages(plugin_arc.priority(), |registry| plugin_arc.register_stages(registry))?; // This now returns Result<_, PluginSystemError>
+            drop(registry_guard);
+            println!("[PluginRegistry] StageRegistry unlocked for plugin: {}", id);
+            self.lifecycle.transition(id, PluginState::Running, "Stages registered")?;
+            Ok(())
+        }
+        .await;
+
         currently_initializing.remove(id);
-        Ok(())
+        if let Err(e) = &result {
+            // `init` succeeded but registering the stages did not: undo the init
+            if self.lifecycle.state(id) == Some(PluginState::Initialized) {
+                if let Err(shutdown_error) = plugin_arc.shutdown() {
+                    eprintln!("[PluginRegistry] Error shutting down plugin {} after its stages failed to register: {}", id, shutdown_error);
+                }
+                self.init_order.retain(|initialized| initialized != id);
+                if let Err(stage_error) = stage_registry_arc.lock().await.unregister_stages_for_plugin(id) {
+                    eprintln!("[PluginRegistry] Failed to unregister the stages of plugin {}: {}", id, stage_error);
+                }
+            }
+            self.services.withdraw(id);
+            self.lifecycle.fail(id, &e.to_string());
+        }
+        result
         })
     }
 
     /// Initialize all enabled plugins in dependency order, after checking for conflicts.
     /// Requires the Application instance (for Plugin::init) and the correct StageRegistry Arc.
+    ///
+    /// A plugin that fails to initialize is handled according to the
+    /// [`init_failure_policy`](Self::init_failure_policy); failures of core plugins are
+    /// always returned. Returns which plugins came up and which did not.
     pub async fn initialize_all(
         &mut self,
         app: &mut Application,
         stage_registry_arc: &Arc<Mutex<StageRegistry>>, // Expect &Arc<Mutex<StageRegistry>>
-    ) -> KernelResult<()> {
+    ) -> KernelResult<StartupReport> {
         self.detect_all_conflicts().map_err(Error::from)?; // detect_all_conflicts returns PluginSystemError
         println!("[Init] Detected conflicts: {:?}", self.conflict_manager.get_conflicts());
 
@@ -542,10 +705,15 @@ impl PluginRegistry {
             }));
         }
 
-        let enabled_plugin_ids: HashSet<String> = self.enabled.iter().cloned().collect();
+        let mut disabled: Vec<String> = self.plugins.keys().filter(|id| !self.is_enabled(id)).cloned().collect();
+        disabled.sort();
+        let mut report = StartupReport { disabled, ..StartupReport::default() };
+
+        let enabled_plugin_ids = self.enabled_plugin_ids();
         if enabled_plugin_ids.is_empty() {
              println!("[Init] No enabled plugins to initialize.");
-             return Ok(());
+             self.last_startup_report = Some(report.clone());
+             return Ok(report);
         }
 
         println!("[Init] Building dependency graph for enabled plugins: {:?}", enabled_plugin_ids);
@@ -585,7 +753,7 @@ impl PluginRegistry {
                 
                 if let Some(required_version_range) = &dependency.version_range {
                     // Only check against other *enabled* plugins
-                    if self.enabled.contains(dep_name) {
+                    if self.is_enabled(dep_name) {
                         let dependent_plugin_arc = match self.plugins.get(dep_name) {
                             Some(p) => p,
                             None => {
@@ -624,7 +792,7 @@ impl PluginRegistry {
                         // This should ideally be caught by earlier checks (e.g. `check_dependencies` or `initialize_plugin_recursive`).
                         // However, if it reaches here, it means a required dependency for an enabled plugin is itself not enabled.
                         // This check is primarily for version mismatches of *loaded* (i.e., enabled) dependencies.
-                        // If `dep_name` is not in `self.enabled`, it won't be initialized as part of this `initialize_all` sequence.
+                        // If `dep_name` is not enabled, it won't be initialized as part of this `initialize_all` sequence.
                         // `initialize_plugin_recursive` for `current_plugin_arc` would fail if `dep_name` is required but not enabled and not initialized.
                         // For this pre-flight check, we focus on version compatibility of those dependencies that *are* enabled.
                         // If a required dependency is missing/disabled, that's a setup error caught elsewhere.
@@ -642,111 +810,101 @@ impl PluginRegistry {
 
         println!("[Init] Initializing plugins in topological order...");
         for id in sorted_plugin_ids {
-            if !self.initialized.contains(&id) {
-                 println!("[Init] Calling initialize_plugin for: {}", id);
-                 self.initialize_plugin(&id, app, stage_registry_arc).await?; // This returns KernelResult
-            } else {
+            if self.is_initialized(&id) {
                  println!("[Init] Plugin {} already initialized (likely by a dependency), skipping.", id);
+                 report.initialized.push(id);
+                 continue;
+            }
+            if !self.is_enabled(&id) {
+                 // Disabled because a plugin it requires failed; already in the report
+                 continue;
+            }
+            println!("[Init] Calling initialize_plugin for: {}", id);
+            match self.initialize_plugin(&id, app, stage_registry_arc).await {
+                Ok(()) => report.initialized.push(id),
+                Err(e) => self.handle_init_failure(&id, e, &mut report, stage_registry_arc).await?,
             }
         }
 
-        println!("[Init] All enabled plugins initialized successfully.");
-        Ok(())
+        if report.is_complete() {
+            println!("[Init] All enabled plugins initialized successfully.");
+        }
+        print!("[Init] {}", report);
+        self.last_startup_report = Some(report.clone());
+        Ok(report)
     }
-    
-    /// Shutdown all plugins
-    pub fn shutdown_all(&mut self) -> std::result::Result<(), PluginSystemError> {
-        // Build dependency graph for initialized plugins
-        let mut adj: HashMap<String, Vec<String>> = HashMap::new();
-        let mut reverse_adj: HashMap<String, Vec<String>> = HashMap::new(); // For reverse topological sort
-        let initialized_plugin_ids: HashSet<String> = self.initialized.iter().cloned().collect();
 
-        for id in &initialized_plugin_ids {
-            adj.entry(id.clone()).or_default(); // Ensure all initialized plugins are in the graph
-            reverse_adj.entry(id.clone()).or_default();
-            if let Some(plugin) = self.plugins.get(id) {
-                for dep in plugin.dependencies() {
-                    // Only consider dependencies that are also initialized
-                    if initialized_plugin_ids.contains(&dep.plugin_name) {
-                        // Standard adjacency list: id depends on dep.plugin_name
-                        adj.entry(id.clone()).or_default().push(dep.plugin_name.clone());
-                        // Reverse adjacency list: dep.plugin_name is depended on by id
-                        reverse_adj.entry(dep.plugin_name.clone()).or_default().push(id.clone());
-                    }
-                }
-            }
+    /// Applies the init failure policy to a plugin that failed to initialize.
+    ///
+    /// A plugin whose `init` succeeded before its stages failed to register has already been
+    /// shut down, with its stages and services withdrawn. Startup stops (the error is
+    /// returned) under [`InitFailurePolicy::FailFast`], for core plugins and for plugins a
+    /// core plugin requires; otherwise the plugins that require it are disabled.
+    async fn handle_init_failure(
+        &mut self,
+        id: &str,
+        error: Error,
+        report: &mut StartupReport,
+        stage_registry_arc: &Arc<Mutex<StageRegistry>>,
+    ) -> KernelResult<()> {
+        let dependents = self.dependents_of(id);
+        let is_core = |plugin_id: &str| self.plugins.get(plugin_id).is_some_and(|plugin| plugin.is_core());
+        if self.init_failure_policy == InitFailurePolicy::FailFast
+            || is_core(id)
+            || dependents.iter().any(|dependent| is_core(dependent))
+        {
+            eprintln!("[Init] Plugin {} failed to initialize, stopping startup: {}", id, error);
+            return Err(error);
         }
 
-        // Perform topological sort (Kahn's algorithm)
-        // We want to shut down plugins with *no dependents* first.
-        let mut in_degree: HashMap<String, usize> = HashMap::new();
-        for id in &initialized_plugin_ids {
-             // Calculate in-degree based on the *reverse* graph (how many plugins depend on this one)
-             in_degree.insert(id.clone(), reverse_adj.get(id).map_or(0, |dependents| dependents.len()));
+        eprintln!("[Init] Plugin {} failed to initialize and is skipped: {}", id, error);
+        report.failed.push(PluginOutcome { plugin_id: id.to_string(), reason: error.to_string() });
+        let reason = format!("Required plugin '{}' failed to initialize", id);
+        for dependent in dependents {
+            if let Err(e) = self.disable_plugin_with_reason(&dependent, &reason, stage_registry_arc).await {
+                eprintln!("[Init] Error disabling dependent plugin {}: {}", dependent, e);
+            }
+            report.skipped.push(PluginOutcome { plugin_id: dependent, reason: reason.clone() });
         }
-
-        // Queue contains nodes with in-degree 0 (in the reverse graph, meaning no other initialized plugin depends on them)
-        let mut queue: std::collections::VecDeque<String> = initialized_plugin_ids
-            .iter()
-            .filter(|id| *in_degree.get(*id).unwrap_or(&1) == 0)
-            .cloned()
+        Ok(())
+    }
+    
+    /// Shutdown all plugins, in the reverse of the order they were initialized in.
+    /// Dependents therefore shut down before their dependencies, and among independent
+    /// plugins the lower-priority ones shut down first.
+    pub fn shutdown_all(&mut self) -> std::result::Result<(), PluginSystemError> {
+        let mut shutdown_order: Vec<String> = self.initialization_order();
+        shutdown_order.reverse();
+
+        // Plugins marked initialized without going through `initialize_plugin` go first, lowest priority first
+        let mut untracked: Vec<(PluginPriority, String)> = self.initialized_plugin_ids()
+            .into_iter()
+            .filter(|id| !shutdown_order.contains(id))
+            .filter_map(|id| self.plugins.get(&id).map(|plugin| (plugin.priority(), id)))
             .collect();
+        untracked.sort();
+        shutdown_order.splice(0..0, untracked.into_iter().rev().map(|(_, id)| id));
+        println!("[shutdown_all] Shutdown order: {:?}", shutdown_order);
 
-        let mut shutdown_order = Vec::new(); // This will store the order: least dependent first
-        while let Some(id) = queue.pop_front() {
-// DEBUG LOGGING FOR CYCLE
-            println!("[shutdown_all] Processing ID: {}", id);
-            println!("[shutdown_all] Current Queue: {:?}", queue);
-            println!("[shutdown_all] Current In-Degrees: {:?}", in_degree);
-            println!("[shutdown_all] Current Shutdown Order: {:?}", shutdown_order);
-            // END DEBUG LOGGING
-            shutdown_order.push(id.clone());
-
-            // For each plugin `dep_id` that the current plugin `id` depends on (using original adj list)
-             if let Some(dependencies) = adj.get(&id) {
-                 for dep_id in dependencies {
-                     if let Some(degree) = in_degree.get_mut(dep_id) {
-                         *degree -= 1;
-                         if *degree == 0 {
-                             queue.push_back(dep_id.clone());
-                         }
-                     }
-                 }
-             }
-        }
-
-
-        // Check if topological sort included all initialized plugins (cycle detection)
-        if shutdown_order.len() != initialized_plugin_ids.len() {
-            // This indicates a cycle among initialized plugins, which ideally shouldn't happen
-            // if initialization succeeded, but handle defensively.
-            return Err(PluginSystemError::DependencyResolution(
-               crate::plugin_system::dependency::DependencyError::CyclicDependency(
-                   initialized_plugin_ids.iter().filter(|id| !shutdown_order.contains(id)).cloned().collect()
-               )
-            ));
-       }
-
-       // Shutdown plugins in the calculated topological order.
-        // The `shutdown_order` Vec contains plugins where dependencies appear *before*
-        // the plugins that depend on them (e.g., A before B if B depends on A).
-        // For shutdown, we need to process this list *as is* to ensure dependents
-        // are shut down before their dependencies.
         let mut shutdown_errors = Vec::new();
-        for id in shutdown_order { // Iterate normally
+        for id in shutdown_order {
             // Explicitly convert id (&String) to &str using as_str()
             if let Some(plugin) = self.plugins.get(id.as_str()) {
                  // Check if it's still marked as initialized before shutting down
-                 if self.initialized.contains(id.as_str()) { // Use as_str()
+                 if self.is_initialized(id.as_str()) { // Use as_str()
                     println!("Shutting down plugin: {}", id);
-                    if let Err(e) = plugin.shutdown() { // shutdown returns Result<_, PluginSystemError>
+                    // Mark as uninitialized *after* attempting shutdown
+                    let shutdown_result = plugin.shutdown();
+                    self.services.withdraw(&id);
+                    if let Err(e) = shutdown_result { // shutdown returns Result<_, PluginSystemError>
                         let err_msg = format!("Error shutting down plugin {}: {}", id, e);
                         eprintln!("{}", err_msg);
+                        self.lifecycle.fail(&id, &e.to_string());
                         shutdown_errors.push(e); // Push PluginSystemError
                         // Continue shutting down others even if one fails
+                    } else {
+                        self.lifecycle.transition(&id, PluginState::Loaded, "Shut down")?;
                     }
-                    // Mark as uninitialized *after* attempting shutdown
-                    self.initialized.remove(id.as_str());
                  }
             }
         }
@@ -778,7 +936,17 @@ impl PluginRegistry {
     
     /// Get the number of initialized plugins
     pub fn initialized_count(&self) -> usize {
-        self.initialized.len()
+        self.initialized_plugin_ids().len()
+    }
+
+    /// IDs of the registered plugins that are enabled
+    pub fn enabled_plugin_ids(&self) -> HashSet<String> {
+        self.plugins.keys().filter(|id| self.is_enabled(id)).cloned().collect()
+    }
+
+    /// IDs of the registered plugins that are initialized
+    pub fn initialized_plugin_ids(&self) -> HashSet<String> {
+        self.plugins.keys().filter(|id| self.is_initialized(id)).cloned().collect()
     }
     
     /// Get the current API version
@@ -833,36 +1001,43 @@ impl PluginRegistry {
          if !self.has_plugin(id) {
              return Err(PluginSystemError::RegistrationError { plugin_id: id.to_string(), message: "Cannot enable non-existent plugin".to_string() });
          }
-         self.enabled.insert(id.to_string());
+         if self.lifecycle.state(id) == Some(PluginState::Disabled) {
+             // Manifest-only plugins go back to waiting for their first use
+             let state = if self.is_plugin_code_loaded(id) { PluginState::Loaded } else { PluginState::Discovered };
+             self.lifecycle.transition(id, state, "Enabled")?;
+         }
          println!("Plugin {} enabled.", id);
          Ok(())
      }
 
     /// Attempts to shut down a single plugin instance.
     /// This includes calling its `shutdown` method and unregistering its stages.
-    /// The plugin is removed from the `initialized` set regardless of shutdown errors,
-    /// but errors encountered during stage unregistration are returned.
+    /// The plugin is no longer initialized afterwards regardless of shutdown errors
+    /// (a failed `shutdown` leaves it `Failed`), but errors encountered during stage
+    /// unregistration are returned.
     async fn shutdown_plugin_instance(
         &mut self,
         plugin_id: &str,
         stage_registry_arc: &Arc<Mutex<StageRegistry>>,
     ) -> std::result::Result<(), PluginSystemError> {
-        if !self.initialized.contains(plugin_id) {
-            println!("[PluginRegistry] Plugin {} not in initialized set; shutdown not performed.", plugin_id);
+        if !self.is_initialized(plugin_id) {
+            println!("[PluginRegistry] Plugin {} not initialized; shutdown not performed.", plugin_id);
             return Ok(());
         }
 
         let plugin_arc = self.plugins.get(plugin_id).cloned().ok_or_else(|| {
             PluginSystemError::OperationError {
                 plugin_id: Some(plugin_id.to_string()),
-                message: format!("Plugin {} found in initialized set but not in plugins map during shutdown.", plugin_id),
+                message: format!("Plugin {} is initialized but not in plugins map during shutdown.", plugin_id),
             }
         })?;
 
         println!("[PluginRegistry] Attempting to shut down plugin instance: {}", plugin_id);
 
         // 1. Call plugin.shutdown()
-        if let Err(e) = plugin_arc.shutdown() {
+        let shutdown_error = plugin_arc.shutdown().err();
+        self.services.withdraw(plugin_id);
+        if let Some(e) = &shutdown_error {
             eprintln!("[PluginRegistry] Error during plugin.shutdown() for {}: {}. Continuing with stage unregistration.", plugin_id, e);
         } else {
             println!("[PluginRegistry] plugin.shutdown() called successfully for {}.", plugin_id);
@@ -878,7 +1053,7 @@ impl PluginRegistry {
             };
             eprintln!("[PluginRegistry] Error unregistering stages for plugin {}: {}", plugin_id, unreg_error);
             // Mark as uninitialized even if stage unregistration fails, then return error.
-            self.initialized.remove(plugin_id);
+            self.lifecycle.fail(plugin_id, &unreg_error.to_string());
             println!("[PluginRegistry] Plugin {} marked as uninitialized after stage unregistration failure.", plugin_id);
             return Err(unreg_error);
         }
@@ -886,7 +1061,10 @@ impl PluginRegistry {
         println!("[PluginRegistry] Stages unregistered successfully for plugin: {}", plugin_id);
 
         // 3. Mark as uninitialized
-        self.initialized.remove(plugin_id);
+        match shutdown_error {
+            Some(e) => self.lifecycle.fail(plugin_id, &e.to_string()),
+            None => self.lifecycle.transition(plugin_id, PluginState::Loaded, "Shut down")?,
+        }
         println!("[PluginRegistry] Plugin {} successfully shut down and marked as uninitialized.", plugin_id);
 
         Ok(())
@@ -900,6 +1078,17 @@ impl PluginRegistry {
         &mut self,
         id: &str,
         stage_registry_arc: &Arc<Mutex<StageRegistry>>,
+    ) -> std::result::Result<(), PluginSystemError> {
+        self.disable_plugin_with_reason(id, "Disabled", stage_registry_arc).await
+    }
+
+    /// Disable a plugin like [`disable_plugin`](Self::disable_plugin), recording `reason`
+    /// as the reason for its `Disabled` state.
+    pub async fn disable_plugin_with_reason(
+        &mut self,
+        id: &str,
+        reason: &str,
+        stage_registry_arc: &Arc<Mutex<StageRegistry>>,
     ) -> std::result::Result<(), PluginSystemError> {
         if !self.plugins.contains_key(id) {
             return Err(PluginSystemError::RegistrationError {
@@ -908,41 +1097,243 @@ impl PluginRegistry {
             });
         }
 
-        let plugin_was_previously_enabled = self.enabled.remove(id);
-
-        if plugin_was_previously_enabled {
-            println!("[PluginRegistry] Plugin {} marked as disabled for future loads.", id);
-        } else {
-            println!("[PluginRegistry] Plugin {} was already marked as disabled; ensuring it's shut down if initialized.", id);
+        if !self.is_enabled(id) {
+            println!("[PluginRegistry] Plugin {} was already marked as disabled.", id);
+            return Ok(());
         }
 
         // If the plugin was initialized, it is shut down here.
         // This includes calling its `shutdown()` method and unregistering its stages.
         // The plugin is marked as disabled for future loads regardless of the shutdown outcome.
-        if self.initialized.contains(id) {
+        let mut shutdown_result = Ok(());
+        if self.is_initialized(id) {
             println!("[PluginRegistry] Plugin {} was initialized. Proceeding to shut it down.", id);
-            
-            match self.shutdown_plugin_instance(id, stage_registry_arc).await {
+            shutdown_result = self.shutdown_plugin_instance(id, stage_registry_arc).await;
+            match &shutdown_result {
                 Ok(()) => {
                     println!("[PluginRegistry] Plugin {} successfully shut down as part of disable operation.", id);
                 }
                 Err(shutdown_err) => {
                     eprintln!("[PluginRegistry] Error during shutdown of plugin {} as part of disable operation: {}. The plugin remains marked disabled for future loads.", id, shutdown_err);
-                    return Err(shutdown_err);
                 }
             }
         } else {
             println!("[PluginRegistry] Plugin {} was not initialized, no active shutdown needed.", id);
         }
 
-        Ok(())
+        self.lifecycle.transition(id, PluginState::Disabled, reason)?;
+        println!("[PluginRegistry] Plugin {} marked as disabled for future loads.", id);
+        shutdown_result
     }
- 
+
+    /// Disable a plugin together with the enabled plugins that require it.
+    ///
+    /// The dependents are shut down and disabled first, in [`dependents_of`](Self::dependents_of)
+    /// order, so no initialized plugin is left with a missing dependency. Nothing is disabled
+    /// if one of the dependents is a core plugin. Returns the dependents that were disabled.
+    pub async fn disable_plugin_cascade(
+        &mut self,
+        id: &str,
+        reason: &str,
+        stage_registry_arc: &Arc<Mutex<StageRegistry>>,
+    ) -> std::result::Result<Vec<String>, PluginSystemError> {
+        if !self.plugins.contains_key(id) {
+            return Err(PluginSystemError::RegistrationError {
+                plugin_id: id.to_string(),
+                message: "Plugin not found, cannot disable.".to_string(),
+            });
+        }
+        let dependents = self.dependents_of(id);
+        if let Some(core_dependent) = dependents.iter().find(|dependent| self.plugins[*dependent].is_core()) {
+            return Err(PluginSystemError::OperationError {
+                plugin_id: Some(id.to_string()),
+                message: format!("Core plugin '{}' requires it, so it cannot be disabled", core_dependent),
+            });
+        }
+
+        // Shutdown errors are reported once everything is disabled
+        let mut first_error = None;
+        let dependent_reason = format!("Required plugin '{}' was disabled", id);
+        for dependent in &dependents {
+            if let Err(e) = self.disable_plugin_with_reason(dependent, &dependent_reason, stage_registry_arc).await {
+                eprintln!("[PluginRegistry] Error disabling dependent plugin {}: {}", dependent, e);
+                first_error.get_or_insert(e);
+            }
+        }
+        if let Err(e) = self.disable_plugin_with_reason(id, reason, stage_registry_arc).await {
+            first_error.get_or_insert(e);
+        }
+        match first_error {
+            Some(e) => Err(e),
+            None => Ok(dependents),
+        }
+    }
+
+    /// Enabled plugins that require `id`, directly or through other plugins, in the order
+    /// they have to be shut down: every plugin comes before the plugins it depends on.
+    pub fn dependents_of(&self, id: &str) -> Vec<String> {
+        let mut dependents = HashSet::new();
+        let mut pending = vec![id.to_string()];
+        while let Some(current) = pending.pop() {
+            for (plugin_id, plugin) in &self.plugins {
+                if plugin_id != id
+                    && !dependents.contains(plugin_id)
+                    && self.is_enabled(plugin_id)
+                    && plugin.dependencies().iter().any(|dep| dep.required && dep.plugin_name == current)
+                {
+                    dependents.insert(plugin_id.clone());
+                    pending.push(plugin_id.clone());
+                }
+            }
+        }
+        let mut order = self.dependency_order(&dependents);
+        order.reverse();
+        order
+    }
+
+    /// Registered plugins that `id` requires, directly or through other plugins, in the order
+    /// they have to be initialized: every plugin comes after the plugins it depends on.
+    pub fn required_dependencies_of(&self, id: &str) -> Vec<String> {
+        let mut dependencies = HashSet::new();
+        let mut pending = vec![id.to_string()];
+        while let Some(current) = pending.pop() {
+            let Some(plugin) = self.plugins.get(&current) else { continue };
+            for dep in plugin.dependencies() {
+                if dep.required && dep.plugin_name != id && self.has_plugin(&dep.plugin_name) && dependencies.insert(dep.plugin_name.clone()) {
+                    pending.push(dep.plugin_name.clone());
+                }
+            }
+        }
+        self.dependency_order(&dependencies)
+    }
+
+    /// Sorts `plugin_ids` so that dependencies come before their dependents.
+    /// Plugins in a dependency cycle are sorted by ID instead.
+    fn dependency_order(&self, plugin_ids: &HashSet<String>) -> Vec<String> {
+        let (adj, _) = self.build_dependency_graph(plugin_ids);
+        match self.topological_sort(plugin_ids, &adj) {
+            Ok(order) => order,
+            Err(_) => {
+                let mut order: Vec<String> = plugin_ids.iter().cloned().collect();
+                order.sort();
+                order
+            }
+        }
+    }
+
+    /// Fully unloads a plugin from the registry.
+    /// If the plugin is initialized it is shut down first (calling `shutdown` and
+    /// unregistering its stages), then it is removed from the registry.
+    /// The removed plugin Arc is returned so the caller controls when the
+    /// underlying instance (and, for dynamic plugins, its library) is dropped.
+    pub async fn unload_plugin(
+        &mut self,
+        id: &str,
+        stage_registry_arc: &Arc<Mutex<StageRegistry>>,
+    ) -> std::result::Result<Arc<dyn Plugin>, PluginSystemError> {
+        if !self.plugins.contains_key(id) {
+            return Err(PluginSystemError::RegistrationError {
+                plugin_id: id.to_string(),
+                message: "Plugin not found, cannot unload.".to_string(),
+            });
+        }
+
+        if self.is_initialized(id) {
+            self.shutdown_plugin_instance(id, stage_registry_arc).await?;
+        }
+
+        let plugin = self.unregister_plugin(id)?;
+        println!("[PluginRegistry] Plugin {} unloaded from registry.", id);
+        Ok(plugin)
+    }
+
      /// Check if a plugin is enabled by ID
      pub fn is_enabled(&self, id: &str) -> bool {
-         self.enabled.contains(id)
+         self.has_plugin(id) && self.lifecycle.state(id).is_some_and(|state| state.is_enabled())
      }
 
+    /// Check if a plugin is initialized by ID
+    pub fn is_initialized(&self, id: &str) -> bool {
+        self.has_plugin(id) && self.lifecycle.state(id).is_some_and(|state| state.is_initialized())
+    }
+
+    /// Detect conflicts between enabled plugins and resolve them with `policies`.
+    ///
+    /// Plugins that lose a critical conflict are disabled (and shut down if initialized).
+    /// `explicitly_enabled` lists the plugins the user enabled by hand, for
+    /// [`ConflictPolicy::PreferExplicitlyEnabled`]. Returns the decisions that were made.
+    pub async fn apply_conflict_policies(
+        &mut self,
+        policies: &[ConflictPolicy],
+        explicitly_enabled: &HashSet<String>,
+        stage_registry_arc: &Arc<Mutex<StageRegistry>>,
+    ) -> std::result::Result<Vec<ConflictDecision>, PluginSystemError> {
+        self.apply_conflict_policies_where(policies, explicitly_enabled, stage_registry_arc, |_| true).await
+    }
+
+    /// Resolve the declared conflicts (`conflicts_with` and `incompatible_with`) between enabled
+    /// plugins that are still unresolved in favour of the plugin with the higher priority.
+    ///
+    /// This is the default for declared conflicts that neither a policy nor the user decided;
+    /// ties and other kinds of conflicts stay unresolved.
+    pub async fn prefer_higher_priority_in_declared_conflicts(
+        &mut self,
+        stage_registry_arc: &Arc<Mutex<StageRegistry>>,
+    ) -> std::result::Result<Vec<ConflictDecision>, PluginSystemError> {
+        self.apply_conflict_policies_where(
+            &[ConflictPolicy::PreferHigherPriority],
+            &HashSet::new(),
+            stage_registry_arc,
+            |conflict| conflict.conflict_type.is_declared(),
+        )
+        .await
+    }
+
+    async fn apply_conflict_policies_where(
+        &mut self,
+        policies: &[ConflictPolicy],
+        explicitly_enabled: &HashSet<String>,
+        stage_registry_arc: &Arc<Mutex<StageRegistry>>,
+        filter: impl Fn(&PluginConflict) -> bool,
+    ) -> std::result::Result<Vec<ConflictDecision>, PluginSystemError> {
+        self.detect_all_conflicts()?;
+
+        let parties: HashMap<String, ConflictParty> = self
+            .plugins
+            .iter()
+            .map(|(id, plugin)| {
+                let party = ConflictParty {
+                    priority: plugin.priority(),
+                    is_core: plugin.is_core(),
+                    explicitly_enabled: explicitly_enabled.contains(id),
+                };
+                (id.clone(), party)
+            })
+            .collect();
+        let decisions = self.conflict_manager.apply_policies_where(policies, &parties, filter);
+
+        for plugin_id in self.conflict_manager.get_plugins_to_disable() {
+            let reason = decisions
+                .iter()
+                .find(|decision| decision.disabled_plugin() == Some(plugin_id.as_str()))
+                .map(|decision| decision.to_string())
+                .unwrap_or_else(|| "Disabled to resolve a plugin conflict".to_string());
+            self.disable_plugin_with_reason(&plugin_id, &reason, stage_registry_arc).await?;
+        }
+        Ok(decisions)
+    }
+
+    /// Allow `first` and `second` to run together: conflicts detected between them from now on
+    /// are resolved with [`ResolutionStrategy::AllowWithWarning`].
+    pub fn allow_conflict(&mut self, first: &str, second: &str) {
+        self.allowed_conflicts.insert(conflict_pair(first, second));
+    }
+
+    /// Whether [`allow_conflict`](Self::allow_conflict) was called for these two plugins (in either order)
+    pub fn is_conflict_allowed(&self, first: &str, second: &str) -> bool {
+        self.allowed_conflicts.contains(&conflict_pair(first, second))
+    }
+
     /// Get a reference to the conflict manager
     pub fn conflict_manager(&self) -> &ConflictManager {
         &self.conflict_manager
@@ -958,7 +1349,7 @@ impl PluginRegistry {
     pub fn detect_all_conflicts(&mut self) -> std::result::Result<(), PluginSystemError> {
         self.conflict_manager = ConflictManager::new();
 
-        let enabled_plugin_ids: Vec<String> = self.enabled.iter().cloned().collect();
+        let enabled_plugin_ids: Vec<String> = self.enabled_plugin_ids().into_iter().collect();
         let mut plugins_to_check: Vec<Arc<dyn Plugin>> = enabled_plugin_ids // Make mutable
             .iter()
             .filter_map(|id| self.plugins.get(id).cloned())
@@ -1088,55 +1479,28 @@ impl PluginRegistry {
 
                 for claim_a in &claims_a {
                     for claim_b in &claims_b {
-                        if claim_a.resource == claim_b.resource {
-                            // Resources are the same, now check access types based on the conflict matrix
-                            let conflict_exists = match (claim_a.access_type, claim_b.access_type) {
-                                // ExclusiveWrite conflicts with anything
-                                (ResourceAccessType::ExclusiveWrite, _) | (_, ResourceAccessType::ExclusiveWrite) => true,
-
-                                // ExclusiveRead conflicts
-                                (ResourceAccessType::ExclusiveRead, ResourceAccessType::ExclusiveRead) => true,
-                                (ResourceAccessType::ExclusiveRead, ResourceAccessType::SharedWrite) => true,
-                                (ResourceAccessType::ExclusiveRead, ResourceAccessType::SharedRead) => true,
-                                (ResourceAccessType::ExclusiveRead, ResourceAccessType::ProvidesUniqueId) => true,
-                                (ResourceAccessType::SharedWrite, ResourceAccessType::ExclusiveRead) => true, // Symmetric
-                                (ResourceAccessType::SharedRead, ResourceAccessType::ExclusiveRead) => true,   // Symmetric
-                                (ResourceAccessType::ProvidesUniqueId, ResourceAccessType::ExclusiveRead) => true, // Symmetric
-                                
-                                // SharedWrite conflicts (includes Yes* from design)
-                                (ResourceAccessType::SharedWrite, ResourceAccessType::SharedWrite) => true,
-                                (ResourceAccessType::SharedWrite, ResourceAccessType::SharedRead) => true,
-                                (ResourceAccessType::SharedWrite, ResourceAccessType::ProvidesUniqueId) => true,
-                                (ResourceAccessType::SharedRead, ResourceAccessType::SharedWrite) => true,   // Symmetric
-                                (ResourceAccessType::ProvidesUniqueId, ResourceAccessType::SharedWrite) => true, // Symmetric
-
-                                // ProvidesUniqueId conflicts
-                                (ResourceAccessType::ProvidesUniqueId, ResourceAccessType::ProvidesUniqueId) => true,
-
-                                // Non-conflicting cases
-                                (ResourceAccessType::SharedRead, ResourceAccessType::SharedRead) => false,
-                                (ResourceAccessType::SharedRead, ResourceAccessType::ProvidesUniqueId) => false,
-                                (ResourceAccessType::ProvidesUniqueId, ResourceAccessType::SharedRead) => false,
-                                // Note: ResourceAccessType does not have a variant that would make this non-exhaustive
-                                // if all its variants are covered in relation to others.
-                            };
-
-                            if conflict_exists {
-                                let description = format!(
+                        if claim_a.conflicts_with(claim_b) {
+                            let description = if claim_a.resource == claim_b.resource {
+                                format!(
                                     "Resource conflict on {}:'{}'. Plugin '{}' requests {:?} access, while plugin '{}' requests {:?} access.",
                                     claim_a.resource.kind, claim_a.resource.id, id_a, claim_a.access_type, id_b, claim_b.access_type
-                                );
-                                self.conflict_manager.add_conflict(PluginConflict::new(
-                                    id_a,
-                                    id_b,
-                                    ConflictType::ResourceConflict {
-                                        resource: claim_a.resource.clone(),
-                                        first_plugin_access: claim_a.access_type,
-                                        second_plugin_access: claim_b.access_type,
-                                    },
-                                    &description,
-                                ));
-                            }
+                                )
+                            } else {
+                                format!(
+                                    "Resource conflict between overlapping {}:'{}' and {}:'{}'. Plugin '{}' requests {:?} access, while plugin '{}' requests {:?} access.",
+                                    claim_a.resource.kind, claim_a.resource.id, claim_b.resource.kind, claim_b.resource.id, id_a, claim_a.access_type, id_b, claim_b.access_type
+                                )
+                            };
+                            self.conflict_manager.add_conflict(PluginConflict::new(
+                                id_a,
+                                id_b,
+                                ConflictType::ResourceConflict {
+                                    resource: claim_a.resource.clone(),
+                                    first_plugin_access: claim_a.access_type,
+                                    second_plugin_access: claim_b.access_type,
+                                },
+                                &description,
+                            ));
                         }
                     }
                 }
@@ -1184,8 +1548,32 @@ impl PluginRegistry {
             }
         }
 
+        // Conflicts the user already chose to live with
+        let allowed: Vec<usize> = self
+            .conflict_manager
+            .get_conflicts()
+            .iter()
+            .enumerate()
+            .filter(|(_, conflict)| self.is_conflict_allowed(&conflict.first_plugin, &conflict.second_plugin))
+            .map(|(index, _)| index)
+            .collect();
+        for index in allowed {
+            self.conflict_manager
+                .resolve_conflict(index, ResolutionStrategy::AllowWithWarning)
+                .map_err(|e| PluginSystemError::ConflictError { message: e.to_string() })?;
+        }
+
         Ok(())
     }
 } // Close impl PluginRegistry
 
+/// Two plugin IDs in a fixed order, so a pair can be looked up either way round
+fn conflict_pair(first: &str, second: &str) -> (String, String) {
+    if first <= second {
+        (first.to_string(), second.to_string())
+    } else {
+        (second.to_string(), first.to_string())
+    }
+}
+
 
@@ -0,0 +1,497 @@
+//! # Plugin Dependency Resolver
+//!
+//! Chooses a consistent set of plugins from every discovered [`PluginManifest`],
+//! including several versions of the same plugin ID.
+//!
+//! A selection is consistent when:
+//! - at most one version of each plugin is selected,
+//! - every required dependency of a selected plugin is selected, in a version inside its range,
+//! - every optional dependency that is selected is inside its range,
+//! - no selected plugin lists another selected plugin in `conflicts_with`, or a selected
+//!   version of it in `incompatible_with` (unless these are
+//!   [deferred](DependencyResolver::defer_declared_conflicts) to the caller),
+//! - every selected manifest that declares `api_versions` supports the core API version.
+//!
+//! The search is a backtracking (DPLL-style) search over candidate versions, tried in the
+//! order they were given. Optional dependencies are added afterwards, one at a time, whenever
+//! the selection stays consistent. When no selection exists, the constraints that ruled out
+//! the candidates are returned as [`Incompatibility`] values.
+use std::collections::HashSet;
+use std::fmt;
+
+use semver::Version;
+
+use crate::plugin_system::dependency::{DependencyError, PluginDependency};
+use crate::plugin_system::lazy::DEFAULT_MANIFEST_PRIORITY;
+use crate::plugin_system::manifest::PluginManifest;
+use crate::plugin_system::traits::Plugin;
+use crate::plugin_system::version::ApiVersion;
+
+/// A constraint that ruled out a plugin version during resolution.
+///
+/// Plugins are named as `'id' version`; `requested` stands for the caller's own requirements.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Incompatibility {
+    /// A required plugin was not found at all
+    Missing {
+        required_by: String,
+        plugin_id: String,
+    },
+    /// A plugin's version is outside a range required by another plugin
+    VersionMismatch {
+        required_by: String,
+        plugin: String,
+        range: String,
+    },
+    /// Two plugins declare a conflict (`conflicts_with`)
+    Conflict {
+        plugin: String,
+        conflicts_with: String,
+    },
+    /// A plugin declares another plugin version incompatible (`incompatible_with`)
+    Incompatible {
+        plugin: String,
+        incompatible_with: String,
+        range: String,
+    },
+    /// A plugin does not support the core API version
+    UnsupportedApiVersion {
+        plugin: String,
+        api_version: String,
+    },
+}
+
+impl fmt::Display for Incompatibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Incompatibility::Missing { required_by, plugin_id } => {
+                write!(f, "{} requires '{}', which was not found", required_by, plugin_id)
+            }
+            Incompatibility::VersionMismatch { required_by, plugin, range } => {
+                write!(f, "{} requires version {}, which rules out {}", required_by, range, plugin)
+            }
+            Incompatibility::Conflict { plugin, conflicts_with } => {
+                write!(f, "{} conflicts with {}", plugin, conflicts_with)
+            }
+            Incompatibility::Incompatible { plugin, incompatible_with, range } => {
+                write!(f, "{} is incompatible with {} (range {})", plugin, incompatible_with, range)
+            }
+            Incompatibility::UnsupportedApiVersion { plugin, api_version } => {
+                write!(f, "{} does not support API version {}", plugin, api_version)
+            }
+        }
+    }
+}
+
+/// A plugin that [`DependencyResolver::resolve_all`] left out, and why.
+#[derive(Debug, Clone)]
+pub struct ExcludedPlugin {
+    pub plugin_id: String,
+    pub reasons: Vec<Incompatibility>,
+}
+
+/// An optional dependency that could not be added to the selection, and why.
+#[derive(Debug, Clone)]
+pub struct SkippedDependency {
+    /// The plugin declaring the optional dependency
+    pub plugin_id: String,
+    pub dependency: PluginDependency,
+    pub reasons: Vec<Incompatibility>,
+}
+
+/// The outcome of a successful resolution.
+#[derive(Debug, Clone, Default)]
+pub struct Resolution {
+    /// Selected manifests, one per plugin ID, in the order the candidates were given
+    pub selected: Vec<PluginManifest>,
+    /// Plugins left out by [`DependencyResolver::resolve_all`]
+    pub excluded: Vec<ExcludedPlugin>,
+    /// Optional dependencies of selected plugins that are not part of the selection
+    pub skipped_optional: Vec<SkippedDependency>,
+}
+
+impl Resolution {
+    /// The selected manifest for `plugin_id`, if any
+    pub fn get(&self, plugin_id: &str) -> Option<&PluginManifest> {
+        self.selected.iter().find(|manifest| manifest.id == plugin_id)
+    }
+
+    /// Whether a version of `plugin_id` was selected
+    pub fn is_selected(&self, plugin_id: &str) -> bool {
+        self.get(plugin_id).is_some()
+    }
+}
+
+/// A version of a plugin the resolver can select (or a loaded plugin it must keep).
+#[derive(Debug, Clone)]
+struct Candidate {
+    id: String,
+    version_str: String,
+    version: Option<Version>,
+    dependencies: Vec<PluginDependency>,
+    conflicts_with: Vec<String>,
+    incompatible_with: Vec<PluginDependency>,
+    /// None for already loaded plugins
+    manifest: Option<PluginManifest>,
+}
+
+impl Candidate {
+    fn from_manifest(manifest: PluginManifest) -> Self {
+        Self {
+            id: manifest.id.clone(),
+            version_str: manifest.version.clone(),
+            version: Version::parse(&manifest.version).ok(),
+            dependencies: manifest.dependencies.clone(),
+            conflicts_with: manifest.conflicts_with.clone(),
+            incompatible_with: manifest.incompatible_with.clone(),
+            manifest: Some(manifest),
+        }
+    }
+
+    fn label(&self) -> String {
+        format!("'{}' {}", self.id, self.version_str)
+    }
+
+    /// Whether this candidate's version satisfies `dependency`'s range (any version if none)
+    fn satisfies(&self, dependency: &PluginDependency) -> bool {
+        match (&dependency.version_range, &self.version) {
+            (None, _) => true,
+            (Some(range), Some(version)) => range.includes(version),
+            (Some(_), None) => false,
+        }
+    }
+}
+
+/// A dependency that must be satisfied, and who asked for it.
+#[derive(Debug, Clone)]
+struct Requirement {
+    required_by: String,
+    dependency: PluginDependency,
+}
+
+/// Picks a consistent set of plugin versions from discovered manifests.
+#[derive(Debug, Clone, Default)]
+pub struct DependencyResolver {
+    /// Candidates in order of preference
+    candidates: Vec<Candidate>,
+    /// Plugins that are already loaded and always part of the selection
+    loaded: Vec<Candidate>,
+    api_version: Option<ApiVersion>,
+    /// `conflicts_with` and `incompatible_with` do not rule out candidates
+    declared_conflicts_deferred: bool,
+}
+
+impl DependencyResolver {
+    /// Create a resolver over `manifests`. Versions of the same plugin ID are tried in the order given.
+    pub fn new<I: IntoIterator<Item = PluginManifest>>(manifests: I) -> Self {
+        Self {
+            candidates: manifests.into_iter().map(Candidate::from_manifest).collect(),
+            loaded: Vec::new(),
+            api_version: None,
+            declared_conflicts_deferred: false,
+        }
+    }
+
+    /// Reject manifests whose `api_versions` do not include `api_version`
+    pub fn with_api_version(mut self, api_version: ApiVersion) -> Self {
+        self.api_version = Some(api_version);
+        self
+    }
+
+    /// Select plugins regardless of `conflicts_with` and `incompatible_with`, leaving those
+    /// conflicts to the caller (the plugin manager settles them with its conflict policies).
+    pub fn defer_declared_conflicts(mut self) -> Self {
+        self.declared_conflicts_deferred = true;
+        self
+    }
+
+    /// Treat `plugin` as already loaded: it is always selected, its version and its conflicts
+    /// constrain the candidates, and manifests with the same ID are ignored. Its own dependencies
+    /// are not resolved here.
+    pub fn with_loaded_plugin(mut self, plugin: &dyn Plugin) -> Self {
+        let id = plugin.name().to_string();
+        self.candidates.retain(|candidate| candidate.id != id);
+        self.loaded.push(Candidate {
+            version_str: plugin.version().to_string(),
+            version: Version::parse(plugin.version()).ok(),
+            dependencies: plugin.dependencies(),
+            conflicts_with: plugin.conflicts_with(),
+            incompatible_with: plugin.incompatible_with(),
+            manifest: None,
+            id,
+        });
+        self
+    }
+
+    /// Select a version of every plugin in `requested` together with their dependencies.
+    ///
+    /// Required entries must be satisfied; optional entries, like optional dependencies, are
+    /// included when that keeps the selection consistent. Fails with
+    /// [`DependencyError::Unsatisfiable`] listing the constraints that could not be met together.
+    pub fn resolve(&self, requested: &[PluginDependency]) -> Result<Resolution, DependencyError> {
+        let (optional, roots): (Vec<_>, Vec<_>) = requested
+            .iter()
+            .map(|dependency| Requirement { required_by: "requested".to_string(), dependency: dependency.clone() })
+            .partition(|requirement| !requirement.dependency.required);
+
+        let mut search = Search::new(self);
+        let selection = search.solve_roots(&roots).ok_or(DependencyError::Unsatisfiable(search.reasons))?;
+
+        let mut resolution = Resolution::default();
+        let selection = self.add_optional(roots, selection, optional, &mut resolution);
+        resolution.selected = self.selected_manifests(&selection);
+        Ok(resolution)
+    }
+
+    /// Select as many of the discovered plugins as possible.
+    ///
+    /// Plugins are added one at a time, highest priority first; a plugin that cannot join the
+    /// current selection is left out and reported in [`Resolution::excluded`]. Never fails.
+    pub fn resolve_all(&self) -> Resolution {
+        let mut plugin_ids: Vec<(&Candidate, usize)> = Vec::new();
+        for (index, candidate) in self.candidates.iter().enumerate() {
+            if !plugin_ids.iter().any(|(existing, _)| existing.id == candidate.id) {
+                plugin_ids.push((candidate, index));
+            }
+        }
+        let priority = |candidate: &Candidate| {
+            candidate.manifest.as_ref().and_then(|manifest| manifest.get_priority()).unwrap_or(DEFAULT_MANIFEST_PRIORITY)
+        };
+        plugin_ids.sort_by(|(a, a_index), (b, b_index)| priority(a).cmp(&priority(b)).then(a_index.cmp(b_index)));
+
+        let mut resolution = Resolution::default();
+        let mut roots: Vec<Requirement> = Vec::new();
+        let mut selection: Vec<usize> = Vec::new();
+        for (candidate, _) in plugin_ids {
+            if selection.iter().any(|&index| self.candidates[index].id == candidate.id) {
+                continue; // Already pulled in as a dependency
+            }
+            let requirement = Requirement {
+                required_by: "requested".to_string(),
+                dependency: PluginDependency::required_any(&candidate.id),
+            };
+            roots.push(requirement);
+            let mut search = Search::new(self);
+            match search.solve_roots(&roots) {
+                Some(new_selection) => selection = new_selection,
+                None => {
+                    roots.pop();
+                    resolution.excluded.push(ExcludedPlugin { plugin_id: candidate.id.clone(), reasons: search.reasons });
+                }
+            }
+        }
+
+        let selection = self.add_optional(roots, selection, Vec::new(), &mut resolution);
+        resolution.selected = self.selected_manifests(&selection);
+        resolution
+    }
+
+    /// Add optional requirements (the given ones, then optional dependencies of the selection)
+    /// as long as the selection stays consistent.
+    fn add_optional(
+        &self,
+        mut roots: Vec<Requirement>,
+        mut selection: Vec<usize>,
+        mut pending: Vec<Requirement>,
+        resolution: &mut Resolution,
+    ) -> Vec<usize> {
+        let mut tried: HashSet<(String, String)> = HashSet::new();
+        loop {
+            // Queue optional dependencies of the current selection that were not tried yet
+            for candidate in selection.iter().map(|&index| &self.candidates[index]) {
+                for dependency in candidate.dependencies.iter().filter(|d| !d.required) {
+                    if tried.insert((candidate.id.clone(), dependency.plugin_name.clone())) {
+                        pending.push(Requirement { required_by: candidate.label(), dependency: dependency.clone() });
+                    }
+                }
+            }
+            if pending.is_empty() {
+                break;
+            }
+
+            for requirement in std::mem::take(&mut pending) {
+                let dependency = &requirement.dependency;
+                let mut current = self.loaded.iter().chain(selection.iter().map(|&index| &self.candidates[index]));
+                if current.any(|candidate| candidate.id == dependency.plugin_name && candidate.satisfies(dependency)) {
+                    continue;
+                }
+
+                let mut strict = requirement.clone();
+                strict.dependency.required = true;
+                roots.push(strict);
+                let mut search = Search::new(self);
+                match search.solve_roots(&roots) {
+                    Some(new_selection) => selection = new_selection,
+                    None => {
+                        roots.pop();
+                        resolution.skipped_optional.push(SkippedDependency {
+                            plugin_id: requirement.required_by.clone(),
+                            dependency: requirement.dependency.clone(),
+                            reasons: search.reasons,
+                        });
+                    }
+                }
+            }
+        }
+        selection
+    }
+
+    fn selected_manifests(&self, selection: &[usize]) -> Vec<PluginManifest> {
+        let mut indices = selection.to_vec();
+        indices.sort_unstable();
+        indices.into_iter().filter_map(|index| self.candidates[index].manifest.clone()).collect()
+    }
+}
+
+/// State of one backtracking search.
+struct Search<'a> {
+    resolver: &'a DependencyResolver,
+    /// Every constraint that ruled out a candidate, in the order found
+    reasons: Vec<Incompatibility>,
+}
+
+impl<'a> Search<'a> {
+    fn new(resolver: &'a DependencyResolver) -> Self {
+        Self { resolver, reasons: Vec::new() }
+    }
+
+    fn note(&mut self, reason: Incompatibility) {
+        if !self.reasons.contains(&reason) {
+            self.reasons.push(reason);
+        }
+    }
+
+    fn solve_roots(&mut self, roots: &[Requirement]) -> Option<Vec<usize>> {
+        let mut selection = Vec::new();
+        if self.solve(roots, &mut selection) { Some(selection) } else { None }
+    }
+
+    fn selected<'s>(&'s self, selection: &'s [usize]) -> impl Iterator<Item = &'a Candidate> + 's {
+        let resolver = self.resolver;
+        resolver.loaded.iter().chain(selection.iter().map(move |&index| &resolver.candidates[index]))
+    }
+
+    /// The first required dependency (roots first, then in selection order) with no selected plugin.
+    /// Dependencies of loaded plugins are not followed.
+    fn next_open(&self, roots: &[Requirement], selection: &[usize]) -> Option<Requirement> {
+        let resolver = self.resolver;
+        let from_selection = selection.iter().map(|&index| &resolver.candidates[index]).flat_map(|candidate| {
+            candidate
+                .dependencies
+                .iter()
+                .filter(|dependency| dependency.required)
+                .map(move |dependency| Requirement { required_by: candidate.label(), dependency: dependency.clone() })
+        });
+        roots
+            .iter()
+            .cloned()
+            .chain(from_selection)
+            .find(|requirement| !self.selected(selection).any(|c| c.id == requirement.dependency.plugin_name))
+    }
+
+    fn solve(&mut self, roots: &[Requirement], selection: &mut Vec<usize>) -> bool {
+        let Some(requirement) = self.next_open(roots, selection) else {
+            return true; // Every requirement is met
+        };
+
+        let plugin_id = &requirement.dependency.plugin_name;
+        let options: Vec<usize> = (0..self.resolver.candidates.len())
+            .filter(|&index| &self.resolver.candidates[index].id == plugin_id)
+            .collect();
+        if options.is_empty() {
+            self.note(Incompatibility::Missing { required_by: requirement.required_by.clone(), plugin_id: plugin_id.clone() });
+            return false;
+        }
+
+        for index in options {
+            if !self.accepts(index, roots, selection) {
+                continue;
+            }
+            selection.push(index);
+            if self.solve(roots, selection) {
+                return true;
+            }
+            selection.pop();
+        }
+        false
+    }
+
+    /// Whether candidate `index` can join `selection`, noting the reasons if it cannot
+    fn accepts(&mut self, index: usize, roots: &[Requirement], selection: &[usize]) -> bool {
+        let candidate = &self.resolver.candidates[index];
+        let mut reasons = Vec::new();
+
+        if let (Some(api_version), Some(manifest)) = (&self.resolver.api_version, &candidate.manifest)
+            && !manifest.api_versions.is_empty()
+        {
+            let api_semver = Version::new(api_version.major as u64, api_version.minor as u64, api_version.patch as u64);
+            if !manifest.api_versions.iter().any(|range| range.includes(&api_semver)) {
+                reasons.push(Incompatibility::UnsupportedApiVersion { plugin: candidate.label(), api_version: api_version.to_string() });
+            }
+        }
+
+        // Version ranges the caller asked for
+        for root in roots.iter().filter(|root| root.dependency.plugin_name == candidate.id) {
+            if !candidate.satisfies(&root.dependency) {
+                reasons.push(version_mismatch(&root.required_by, candidate, &root.dependency));
+            }
+        }
+
+        for other in self.selected(selection) {
+            // Ranges in either direction, optional dependencies included
+            for dependency in other.dependencies.iter().filter(|d| d.plugin_name == candidate.id) {
+                if !candidate.satisfies(dependency) {
+                    reasons.push(version_mismatch(&other.label(), candidate, dependency));
+                }
+            }
+            for dependency in candidate.dependencies.iter().filter(|d| d.plugin_name == other.id) {
+                if !other.satisfies(dependency) {
+                    reasons.push(version_mismatch(&candidate.label(), other, dependency));
+                }
+            }
+
+            if self.resolver.declared_conflicts_deferred {
+                continue;
+            }
+            if candidate.conflicts_with.contains(&other.id) {
+                reasons.push(Incompatibility::Conflict { plugin: candidate.label(), conflicts_with: other.label() });
+            }
+            if other.conflicts_with.contains(&candidate.id) {
+                reasons.push(Incompatibility::Conflict { plugin: other.label(), conflicts_with: candidate.label() });
+            }
+
+            for rule in candidate.incompatible_with.iter().filter(|rule| rule.plugin_name == other.id) {
+                if other.satisfies(rule) {
+                    reasons.push(incompatible(candidate, other, rule));
+                }
+            }
+            for rule in other.incompatible_with.iter().filter(|rule| rule.plugin_name == candidate.id) {
+                if candidate.satisfies(rule) {
+                    reasons.push(incompatible(other, candidate, rule));
+                }
+            }
+        }
+
+        let accepted = reasons.is_empty();
+        for reason in reasons {
+            self.note(reason);
+        }
+        accepted
+    }
+}
+
+fn range_label(dependency: &PluginDependency) -> String {
+    dependency.version_range.as_ref().map(|range| range.to_string()).unwrap_or_else(|| "*".to_string())
+}
+
+fn version_mismatch(required_by: &str, plugin: &Candidate, dependency: &PluginDependency) -> Incompatibility {
+    Incompatibility::VersionMismatch {
+        required_by: required_by.to_string(),
+        plugin: plugin.label(),
+        range: range_label(dependency),
+    }
+}
+
+fn incompatible(plugin: &Candidate, other: &Candidate, rule: &PluginDependency) -> Incompatibility {
+    Incompatibility::Incompatible { plugin: plugin.label(), incompatible_with: other.label(), range: range_label(rule) }
+}
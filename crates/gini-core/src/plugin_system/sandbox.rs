@@ -0,0 +1,687 @@
+//! # Out-of-Process Plugin Host
+//!
+//! Runs a dynamic plugin in a child process so that a crash (including a segfault,
+//! which `catch_unwind` cannot contain) or a hang only takes down that child.
+//!
+//! The core side is [`SandboxedPlugin`], a proxy implementing [`Plugin`]. It starts the
+//! host process, listens on a Unix socket in a private temporary directory and talks
+//! to the host over the framed protocol in [`ipc`](crate::plugin_system::ipc). `init`,
+//! `preflight_check`, `register_stages` and `shutdown` are forwarded; stages registered
+//! inside the host are registered in the core as [`RemoteStage`]s that execute remotely.
+//!
+//! The host side is [`run_plugin_host`], reached through the hidden `gini plugin-host`
+//! command. It reads the socket and library paths from [`HOST_SOCKET_ENV`] and
+//! [`HOST_LIBRARY_ENV`], loads the library with
+//! [`ffi_host::load_plugin_library`](crate::plugin_system::ffi_host::load_plugin_library)
+//! and serves requests with [`serve_plugin`].
+//!
+//! Requests block on the socket. From async code they run on the blocking thread pool
+//! (`preflight_check` and stage execution) or, for the synchronous `Plugin` methods,
+//! inside `block_in_place` on a multi-threaded runtime.
+//!
+//! If the host process exits, stops responding within the request timeout or breaks
+//! the protocol, the in-flight request fails and the proxy restarts the process,
+//! replaying `init` and `register_stages` if they had succeeded before. The request
+//! that was in flight is not retried. After [`SandboxConfig::max_restarts`] restarts
+//! the plugin stays stopped and every request fails.
+//!
+//! `init` runs against the host process's own `Application`, so a sandboxed plugin
+//! cannot register components with the core application, and only the mode,
+//! configuration directory and CLI arguments of a `StageContext` reach the host.
+
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::dependency::PluginDependency;
+use crate::plugin_system::error::{PluginSystemError, PluginSystemErrorSource};
+use crate::plugin_system::ffi_host;
+use crate::plugin_system::ipc::{
+    self, HostRequest, HostResponse, RemoteDependency, RemotePluginMetadata, RemoteStageContext,
+    RemoteStageInfo, RemoteStageRequirement, OP_REQUEST, OP_RESPONSE, PROTOCOL_VERSION,
+};
+use crate::plugin_system::traits::{Plugin, PluginPriority};
+use crate::plugin_system::version::VersionRange;
+use crate::stage_manager::context::StageContext;
+use crate::stage_manager::registry::StageRegistry;
+use crate::stage_manager::requirement::StageRequirement;
+use crate::stage_manager::Stage;
+
+/// Environment variable holding the socket path the plugin host connects to
+pub const HOST_SOCKET_ENV: &str = "GINI_PLUGIN_HOST_SOCKET";
+
+/// Environment variable holding the plugin library the plugin host loads
+pub const HOST_LIBRARY_ENV: &str = "GINI_PLUGIN_HOST_LIBRARY";
+
+/// Subcommand of the `gini` binary that runs [`run_plugin_host`]
+pub const HOST_SUBCOMMAND: &str = "plugin-host";
+
+/// How plugin host processes are started and supervised
+#[derive(Debug, Clone)]
+pub struct SandboxConfig {
+    /// Executable started as the plugin host
+    pub program: PathBuf,
+    /// Arguments passed to `program`
+    pub args: Vec<String>,
+    /// Extra environment variables for the host process
+    pub env: Vec<(String, String)>,
+    /// How long the host may take to connect after being started
+    pub startup_timeout: Duration,
+    /// How long a single request may take before the host is considered hung
+    pub request_timeout: Duration,
+    /// How many times a crashed host is restarted before the plugin stays stopped
+    pub max_restarts: u32,
+}
+
+impl Default for SandboxConfig {
+    /// Runs `gini plugin-host` using the current executable
+    fn default() -> Self {
+        Self {
+            program: std::env::current_exe().unwrap_or_else(|_| PathBuf::from("gini")),
+            args: vec![HOST_SUBCOMMAND.to_string()],
+            env: Vec::new(),
+            startup_timeout: Duration::from_secs(10),
+            request_timeout: Duration::from_secs(60),
+            max_restarts: 3,
+        }
+    }
+}
+
+/// A running plugin host process and its connection
+struct HostProcess {
+    child: Child,
+    stream: UnixStream,
+    _socket_dir: tempfile::TempDir, // Removed (with the socket) when the process is dropped
+}
+
+impl HostProcess {
+    /// Starts the host process and waits for it to connect
+    fn spawn(library_path: &Path, config: &SandboxConfig) -> std::io::Result<Self> {
+        let socket_dir = tempfile::Builder::new().prefix("gini-plugin-host-").tempdir()?;
+        let socket_path = socket_dir.path().join("host.sock");
+        let listener = UnixListener::bind(&socket_path)?;
+        listener.set_nonblocking(true)?;
+
+        let mut child = Command::new(&config.program)
+            .args(&config.args)
+            .envs(config.env.iter().map(|(key, value)| (key.as_str(), value.as_str())))
+            .env(HOST_SOCKET_ENV, &socket_path)
+            .env(HOST_LIBRARY_ENV, library_path)
+            .stdin(Stdio::null())
+            .spawn()?;
+
+        let deadline = Instant::now() + config.startup_timeout;
+        let stream = loop {
+            match listener.accept() {
+                Ok((stream, _)) => break stream,
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    if let Some(status) = child.try_wait()? {
+                        return Err(std::io::Error::other(format!("Plugin host exited before connecting ({})", status)));
+                    }
+                    if Instant::now() >= deadline {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            format!("Plugin host did not connect within {:?}", config.startup_timeout),
+                        ));
+                    }
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                Err(e) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(e);
+                }
+            }
+        };
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(config.request_timeout))?;
+        stream.set_write_timeout(Some(config.request_timeout))?;
+
+        Ok(Self { child, stream, _socket_dir: socket_dir })
+    }
+
+    /// Sends one request and waits for its response
+    fn exchange(&mut self, request: &HostRequest) -> std::io::Result<HostResponse> {
+        ipc::write_message(&mut self.stream, OP_REQUEST, request)?;
+        ipc::read_message(&mut self.stream, OP_RESPONSE)
+    }
+
+    /// Waits briefly for the process to exit on its own, then kills it.
+    /// Returns a description of how it ended.
+    fn terminate(&mut self, grace: Duration) -> String {
+        let deadline = Instant::now() + grace;
+        loop {
+            match self.child.try_wait() {
+                Ok(Some(status)) => return status.to_string(),
+                Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(10)),
+                Ok(None) => break,
+                Err(e) => return format!("unknown status ({})", e),
+            }
+        }
+        let _ = self.child.kill();
+        match self.child.wait() {
+            Ok(status) => format!("killed, {}", status),
+            Err(e) => format!("killed, unknown status ({})", e),
+        }
+    }
+}
+
+impl Drop for HostProcess {
+    fn drop(&mut self) {
+        if let Ok(None) = self.child.try_wait() {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+        }
+    }
+}
+
+/// Proxy state shared between the [`SandboxedPlugin`] and its [`RemoteStage`]s
+struct SandboxState {
+    plugin_id: String,
+    library_path: PathBuf,
+    config: SandboxConfig,
+    process: Option<HostProcess>,
+    initialized: bool, // `init` succeeded and is replayed after a restart
+    stages_registered: bool, // `register_stages` succeeded and is replayed after a restart
+    restarts: u32,
+    stopped: bool, // Restart limit reached; no more processes are started
+}
+
+impl SandboxState {
+    fn host_error(&self, operation: &str, message: String) -> PluginSystemError {
+        PluginSystemError::HostProcessError {
+            plugin_id: self.plugin_id.clone(),
+            operation: operation.to_string(),
+            message,
+        }
+    }
+
+    /// Starts a host process and checks that it serves the expected plugin
+    fn start_process(&self) -> std::result::Result<(HostProcess, RemotePluginMetadata), PluginSystemError> {
+        let mut process = HostProcess::spawn(&self.library_path, &self.config)
+            .map_err(|e| self.host_error("start", e.to_string()))?;
+        let metadata = match process.exchange(&HostRequest::Describe) {
+            Ok(HostResponse::Metadata { metadata }) => metadata,
+            Ok(HostResponse::Error { message }) => return Err(self.host_error("describe", message)),
+            Ok(other) => return Err(self.host_error("describe", format!("Unexpected response: {:?}", other))),
+            Err(e) => {
+                let status = process.terminate(Duration::from_millis(100));
+                return Err(self.host_error("describe", format!("{} (process {})", e, status)));
+            }
+        };
+        if metadata.protocol_version != PROTOCOL_VERSION {
+            return Err(self.host_error("describe", format!(
+                "Plugin host speaks protocol version {}, expected {}",
+                metadata.protocol_version, PROTOCOL_VERSION
+            )));
+        }
+        Ok((process, metadata))
+    }
+
+    /// Returns the running host process, starting one if the previous one was shut down
+    fn ensure_running(&mut self, operation: &str) -> std::result::Result<&mut HostProcess, PluginSystemError> {
+        if self.stopped {
+            return Err(self.host_error(operation, format!(
+                "Plugin host was restarted {} times and has been stopped",
+                self.restarts
+            )));
+        }
+        if self.process.is_none() {
+            let (process, metadata) = self.start_process()?;
+            if metadata.name != self.plugin_id {
+                return Err(self.host_error(operation, format!("Plugin host now reports plugin '{}'", metadata.name)));
+            }
+            self.process = Some(process);
+        }
+        Ok(self.process.as_mut().expect("host process was just started"))
+    }
+
+    /// Sends a request, restarting the host process if it crashed, hung or broke the protocol
+    fn request(&mut self, request: &HostRequest) -> std::result::Result<HostResponse, PluginSystemError> {
+        let operation = request.operation();
+        let result = self.ensure_running(operation)?.exchange(request);
+        match result {
+            Ok(response) => {
+                match (request, &response) {
+                    (HostRequest::Init, HostResponse::Ok) => self.initialized = true,
+                    (HostRequest::RegisterStages, HostResponse::Stages { .. }) => self.stages_registered = true,
+                    _ => {}
+                }
+                Ok(response)
+            }
+            Err(e) => {
+                let status = self.process.take().map(|mut process| process.terminate(Duration::from_millis(100))).unwrap_or_default();
+                eprintln!(
+                    "[Sandbox] Plugin host for '{}' failed during '{}': {} (process {}).",
+                    self.plugin_id, operation, e, status
+                );
+                self.restart(request);
+                Err(self.host_error(operation, format!("Plugin host process failed: {} (process {})", e, status)))
+            }
+        }
+    }
+
+    /// Starts a replacement host process and replays the lifecycle calls that had succeeded
+    fn restart(&mut self, failed_request: &HostRequest) {
+        if *failed_request == HostRequest::Init {
+            self.initialized = false; // Replaying the call that crashed would crash again
+        }
+        if *failed_request == HostRequest::Shutdown {
+            self.initialized = false;
+            self.stages_registered = false;
+            return; // The plugin was going away anyway; a new process starts on the next request
+        }
+        if self.restarts >= self.config.max_restarts {
+            eprintln!("[Sandbox] Plugin host for '{}' reached the restart limit ({}); plugin stopped.", self.plugin_id, self.config.max_restarts);
+            self.stopped = true;
+            return;
+        }
+        self.restarts += 1;
+        println!("[Sandbox] Restarting plugin host for '{}' (restart {}/{}).", self.plugin_id, self.restarts, self.config.max_restarts);
+
+        let mut replay = Vec::new();
+        if self.initialized {
+            replay.push(HostRequest::Init);
+        }
+        if self.stages_registered {
+            replay.push(HostRequest::RegisterStages);
+        }
+        let restarted = match self.ensure_running("restart") {
+            Ok(process) => replay.iter().try_for_each(|request| match process.exchange(request) {
+                Ok(HostResponse::Ok) | Ok(HostResponse::Stages { .. }) => Ok(()),
+                Ok(other) => Err(format!("replaying '{}' returned {:?}", request.operation(), other)),
+                Err(e) => Err(format!("replaying '{}' failed: {}", request.operation(), e)),
+            }),
+            Err(e) => Err(e.to_string()),
+        };
+        if let Err(message) = restarted {
+            // A replacement that cannot get back to the previous state is not usable either
+            eprintln!("[Sandbox] Failed to restart plugin host for '{}': {}; plugin stopped.", self.plugin_id, message);
+            self.process = None;
+            self.stopped = true;
+        }
+    }
+
+    /// Sends a request that is answered with `Ok` or `Error`, mapping errors with `on_error`
+    fn request_ok(
+        &mut self,
+        request: &HostRequest,
+        on_error: impl FnOnce(String) -> PluginSystemError,
+    ) -> std::result::Result<(), PluginSystemError> {
+        match self.request(request)? {
+            HostResponse::Ok => Ok(()),
+            HostResponse::Error { message } => Err(on_error(message)),
+            other => Err(self.host_error(request.operation(), format!("Unexpected response: {:?}", other))),
+        }
+    }
+}
+
+/// Locks the shared proxy state
+fn lock_state(state: &Mutex<SandboxState>) -> std::result::Result<std::sync::MutexGuard<'_, SandboxState>, PluginSystemError> {
+    state.lock().map_err(|_| PluginSystemError::InternalError("Sandbox state lock poisoned".to_string()))
+}
+
+/// Runs a blocking request off the async runtime
+async fn request_blocking<T: Send + 'static>(
+    state: &Arc<Mutex<SandboxState>>,
+    operation: &str,
+    call: impl FnOnce(&mut SandboxState) -> std::result::Result<T, PluginSystemError> + Send + 'static,
+) -> std::result::Result<T, PluginSystemError> {
+    let state = state.clone();
+    tokio::task::spawn_blocking(move || call(&mut *lock_state(&state)?))
+        .await
+        .map_err(|e| PluginSystemError::InternalError(format!("Sandbox request '{}' did not complete: {}", operation, e)))?
+}
+
+/// Runs a blocking request from one of the synchronous `Plugin` methods.
+/// On a multi-threaded runtime the worker thread is handed off first so other tasks keep
+/// running while the request waits on the host; outside a runtime it simply blocks.
+fn request_in_place<T>(call: impl FnOnce() -> T) -> T {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread => {
+            tokio::task::block_in_place(call)
+        }
+        _ => call(),
+    }
+}
+
+/// A dynamic plugin running in a separate plugin host process.
+pub struct SandboxedPlugin {
+    metadata: RemotePluginMetadata,
+    priority: PluginPriority,
+    state: Arc<Mutex<SandboxState>>,
+}
+
+impl SandboxedPlugin {
+    /// Starts a plugin host for the library at `library_path` and reads the plugin's metadata.
+    pub fn spawn(library_path: &Path, config: SandboxConfig) -> std::result::Result<Self, PluginSystemError> {
+        let mut state = SandboxState {
+            plugin_id: library_path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default(),
+            library_path: library_path.to_path_buf(),
+            config,
+            process: None,
+            initialized: false,
+            stages_registered: false,
+            restarts: 0,
+            stopped: false,
+        };
+        let (process, metadata) = state.start_process().map_err(|e| PluginSystemError::LoadingError {
+            plugin_id: state.plugin_id.clone(),
+            path: Some(library_path.to_path_buf()),
+            source: Box::new(PluginSystemErrorSource::Other(e.to_string())),
+        })?;
+        state.plugin_id = metadata.name.clone();
+        state.process = Some(process);
+        println!("[Sandbox] Plugin '{}' is running in a separate host process.", metadata.name);
+
+        let priority = PluginPriority::from_str(&metadata.priority).unwrap_or(PluginPriority::ThirdPartyLow(u8::MAX));
+        Ok(Self {
+            metadata,
+            priority,
+            state: Arc::new(Mutex::new(state)),
+        })
+    }
+
+    /// Number of times the host process has been restarted after a failure
+    pub fn restart_count(&self) -> u32 {
+        self.state.lock().map(|state| state.restarts).unwrap_or(0)
+    }
+
+    /// Whether a host process is currently running
+    pub fn is_running(&self) -> bool {
+        self.state.lock().map(|state| state.process.is_some()).unwrap_or(false)
+    }
+
+    /// Converts dependencies reported by the host, skipping unparsable version constraints
+    fn convert_dependencies(&self, dependencies: &[RemoteDependency]) -> Vec<PluginDependency> {
+        dependencies.iter().filter_map(|dependency| {
+            let converted = dependency.to_dependency();
+            if converted.is_none() {
+                eprintln!("[Sandbox] Ignoring dependency '{}' of plugin '{}' with invalid version constraint {:?}", dependency.plugin_name, self.metadata.name, dependency.version_constraint);
+            }
+            converted
+        }).collect()
+    }
+}
+
+#[async_trait]
+impl Plugin for SandboxedPlugin {
+    fn name(&self) -> &str {
+        &self.metadata.name
+    }
+
+    fn version(&self) -> &str {
+        &self.metadata.version
+    }
+
+    fn is_core(&self) -> bool {
+        self.metadata.is_core
+    }
+
+    fn priority(&self) -> PluginPriority {
+        self.priority.clone()
+    }
+
+    fn compatible_api_versions(&self) -> Vec<VersionRange> {
+        self.metadata.compatible_api_versions.iter()
+            .filter_map(|constraint| VersionRange::from_constraint(constraint).ok())
+            .collect()
+    }
+
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        self.convert_dependencies(&self.metadata.dependencies)
+    }
+
+    fn required_stages(&self) -> Vec<StageRequirement> {
+        self.metadata.required_stages.iter().map(StageRequirement::from).collect()
+    }
+
+    fn conflicts_with(&self) -> Vec<String> {
+        self.metadata.conflicts_with.clone()
+    }
+
+    fn incompatible_with(&self) -> Vec<PluginDependency> {
+        self.convert_dependencies(&self.metadata.incompatible_with)
+    }
+
+    fn init(&self, _app: &mut Application) -> std::result::Result<(), PluginSystemError> {
+        let plugin_id = self.metadata.name.clone();
+        request_in_place(|| {
+            lock_state(&self.state)?.request_ok(&HostRequest::Init, |message| PluginSystemError::InitializationError {
+                plugin_id,
+                message,
+                source: None,
+            })
+        })
+    }
+
+    async fn preflight_check(&self, context: &StageContext) -> std::result::Result<(), PluginSystemError> {
+        let request = HostRequest::Preflight { context: RemoteStageContext::from_context(context) };
+        let plugin_id = self.metadata.name.clone();
+        request_blocking(&self.state, "preflight_check", move |state| {
+            state.request_ok(&request, |message| PluginSystemError::PreflightCheckFailed { plugin_id, message })
+        }).await
+    }
+
+    fn register_stages(&self, registry: &mut StageRegistry) -> std::result::Result<(), PluginSystemError> {
+        let stages = request_in_place(|| {
+            let mut state = lock_state(&self.state)?;
+            match state.request(&HostRequest::RegisterStages)? {
+                HostResponse::Stages { stages } => Ok(stages),
+                HostResponse::Error { message } => Err(PluginSystemError::OperationError {
+                    plugin_id: Some(self.metadata.name.clone()),
+                    message: format!("register_stages failed in plugin host: {}", message),
+                }),
+                other => Err(state.host_error("register_stages", format!("Unexpected response: {:?}", other))),
+            }
+        })?;
+
+        for info in stages {
+            let stage_id = info.id.clone();
+            registry.register_stage(Box::new(RemoteStage { info, state: self.state.clone() }))
+                .map_err(|e| PluginSystemError::OperationError {
+                    plugin_id: Some(self.metadata.name.clone()),
+                    message: format!("Failed to register remote stage '{}': {}", stage_id, e),
+                })?;
+        }
+        Ok(())
+    }
+
+    fn shutdown(&self) -> std::result::Result<(), PluginSystemError> {
+        request_in_place(|| {
+            let mut state = lock_state(&self.state)?;
+            if state.process.is_none() {
+                return Ok(()); // Never started again after a previous shutdown or stop
+            }
+            let plugin_id = self.metadata.name.clone();
+            let result = state.request_ok(&HostRequest::Shutdown, |message| PluginSystemError::ShutdownError { plugin_id, message });
+            // The host exits after answering a shutdown request
+            if let Some(mut process) = state.process.take() {
+                process.terminate(Duration::from_secs(1));
+            }
+            state.initialized = false;
+            state.stages_registered = false;
+            result
+        })
+    }
+}
+
+/// A stage registered by a sandboxed plugin, executed inside its plugin host.
+pub struct RemoteStage {
+    info: RemoteStageInfo,
+    state: Arc<Mutex<SandboxState>>,
+}
+
+#[async_trait]
+impl Stage for RemoteStage {
+    fn id(&self) -> &str {
+        &self.info.id
+    }
+
+    fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    fn description(&self) -> &str {
+        &self.info.description
+    }
+
+    fn supports_dry_run(&self) -> bool {
+        self.info.supports_dry_run
+    }
+
+    async fn execute(&self, context: &mut StageContext) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let request = HostRequest::ExecuteStage {
+            stage_id: self.info.id.clone(),
+            context: RemoteStageContext::from_context(context),
+        };
+        request_blocking(&self.state, "execute_stage", move |state| {
+            let plugin_id = Some(state.plugin_id.clone());
+            state.request_ok(&request, |message| PluginSystemError::OperationError { plugin_id, message })
+        }).await.map_err(|e| e.into())
+    }
+}
+
+// --- Plugin Host Side ---
+
+/// Entry point of a plugin host process (`gini plugin-host`).
+///
+/// Connects to the socket in [`HOST_SOCKET_ENV`], loads the library in
+/// [`HOST_LIBRARY_ENV`] and serves requests until shutdown or until the core
+/// closes the connection. Must not be called from within an async runtime.
+pub fn run_plugin_host() -> std::result::Result<(), PluginSystemError> {
+    let env_path = |key: &str| std::env::var_os(key).map(PathBuf::from).ok_or_else(|| PluginSystemError::OperationError {
+        plugin_id: None,
+        message: format!("{} is not set; the plugin host is started by the core for sandboxed plugins", key),
+    });
+    let socket_path = env_path(HOST_SOCKET_ENV)?;
+    let library_path = env_path(HOST_LIBRARY_ENV)?;
+
+    let mut stream = UnixStream::connect(&socket_path).map_err(|e| PluginSystemError::LoadingError {
+        plugin_id: library_path.display().to_string(),
+        path: Some(socket_path.clone()),
+        source: Box::new(PluginSystemErrorSource::Io(e)),
+    })?;
+
+    match ffi_host::load_plugin_library(&library_path) {
+        Ok(plugin) => serve_plugin(stream, plugin),
+        Err(e) => {
+            // Report the load failure as the answer to the core's first request
+            let _ = ipc::read_message::<_, HostRequest>(&mut stream, OP_REQUEST);
+            let _ = ipc::write_message(&mut stream, OP_RESPONSE, &HostResponse::Error { message: e.to_string() });
+            Err(e)
+        }
+    }
+}
+
+/// Serves requests for `plugin` on `stream` until a shutdown request has been
+/// answered or the core closes the connection.
+///
+/// Requests are handled one at a time. Async plugin methods and stages run on a
+/// runtime owned by this function, so it must not be called from within one.
+pub fn serve_plugin(mut stream: UnixStream, plugin: Box<dyn Plugin>) -> std::result::Result<(), PluginSystemError> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| PluginSystemError::InternalError(format!("Failed to create plugin host runtime: {}", e)))?;
+    let mut app: Option<Application> = None; // Created on the first init request
+    let mut stage_registry = StageRegistry::new();
+
+    loop {
+        let request: HostRequest = match ipc::read_message(&mut stream, OP_REQUEST) {
+            Ok(request) => request,
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(()), // Core went away
+            Err(e) => return Err(PluginSystemError::LoadingError {
+                plugin_id: plugin.name().to_string(),
+                path: None,
+                source: Box::new(PluginSystemErrorSource::Io(e)),
+            }),
+        };
+
+        let response = match &request {
+            HostRequest::Describe => HostResponse::Metadata { metadata: describe_plugin(plugin.as_ref()) },
+            HostRequest::Init => {
+                let _guard = runtime.enter(); // Lets `init` use `Handle::current()`
+                let app = match app.as_mut() {
+                    Some(app) => Ok(app),
+                    None => Application::new().map(|created| app.insert(created)),
+                };
+                match app {
+                    Ok(app) => error_response(plugin.init(app)),
+                    Err(e) => HostResponse::Error { message: format!("Failed to create plugin host application: {}", e) },
+                }
+            }
+            HostRequest::Preflight { context } => {
+                let context = context.to_context();
+                error_response(runtime.block_on(plugin.preflight_check(&context)))
+            }
+            HostRequest::RegisterStages => {
+                let before = stage_registry.get_all_ids();
+                match plugin.register_stages(&mut stage_registry) {
+                    Ok(()) => {
+                        let mut stages: Vec<RemoteStageInfo> = stage_registry.get_all_ids().into_iter()
+                            .filter(|id| !before.contains(id))
+                            .filter_map(|id| stage_registry.get_stage(&id).map(|stage| RemoteStageInfo {
+                                id: stage.id().to_string(),
+                                name: stage.name().to_string(),
+                                description: stage.description().to_string(),
+                                supports_dry_run: stage.supports_dry_run(),
+                            }))
+                            .collect();
+                        stages.sort_by(|a, b| a.id.cmp(&b.id));
+                        HostResponse::Stages { stages }
+                    }
+                    Err(e) => HostResponse::Error { message: e.to_string() },
+                }
+            }
+            HostRequest::ExecuteStage { stage_id, context } => {
+                let mut context = context.to_context();
+                match runtime.block_on(stage_registry.execute_stage_internal(stage_id, &mut context)) {
+                    Ok(_) => HostResponse::Ok,
+                    Err(e) => HostResponse::Error { message: e.to_string() },
+                }
+            }
+            HostRequest::Shutdown => error_response(plugin.shutdown()),
+        };
+
+        ipc::write_message(&mut stream, OP_RESPONSE, &response).map_err(|e| PluginSystemError::LoadingError {
+            plugin_id: plugin.name().to_string(),
+            path: None,
+            source: Box::new(PluginSystemErrorSource::Io(e)),
+        })?;
+        if request == HostRequest::Shutdown {
+            return Ok(());
+        }
+    }
+}
+
+/// Collects a plugin's metadata for [`HostRequest::Describe`]
+fn describe_plugin(plugin: &dyn Plugin) -> RemotePluginMetadata {
+    RemotePluginMetadata {
+        protocol_version: PROTOCOL_VERSION,
+        name: plugin.name().to_string(),
+        version: plugin.version().to_string(),
+        is_core: plugin.is_core(),
+        priority: plugin.priority().to_string(),
+        compatible_api_versions: plugin.compatible_api_versions().iter().map(|range| range.constraint_string().to_string()).collect(),
+        dependencies: plugin.dependencies().iter().map(RemoteDependency::from_dependency).collect(),
+        required_stages: plugin.required_stages().iter().map(RemoteStageRequirement::from).collect(),
+        conflicts_with: plugin.conflicts_with(),
+        incompatible_with: plugin.incompatible_with().iter().map(RemoteDependency::from_dependency).collect(),
+    }
+}
+
+/// Maps a lifecycle result onto `Ok` / `Error`
+fn error_response(result: std::result::Result<(), PluginSystemError>) -> HostResponse {
+    match result {
+        Ok(()) => HostResponse::Ok,
+        Err(e) => HostResponse::Error { message: e.to_string() },
+    }
+}
@@ -0,0 +1,130 @@
+//! # Plugin Search Paths
+//!
+//! The directories scanned for plugin manifests, each tagged with the [`PluginPathSource`]
+//! it came from. The plugin manager collects them from the command line, the
+//! `GINI_PLUGIN_PATH` environment variable, the core settings config, the data directory
+//! and the working directory, in that order of precedence.
+//!
+//! [`PluginSearchPaths`] keeps the directories sorted by precedence and ignores duplicates,
+//! so discovery can scan them front to back and keep the first copy of each plugin ID.
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::kernel::constants;
+
+/// Where a plugin search directory came from.
+/// Variants are declared from highest to lowest precedence: when the same plugin
+/// ID is found in several directories, the copy from the higher-precedence source wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PluginPathSource {
+    /// Passed on the command line (e.g. `--plugin-dir`)
+    CommandLine,
+    /// Taken from the `GINI_PLUGIN_PATH` environment variable
+    Environment,
+    /// Listed in the application configuration
+    Config,
+    /// Derived from the XDG data directory
+    DataDir,
+    /// Built-in directories relative to the working directory
+    Default,
+}
+
+impl fmt::Display for PluginPathSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            PluginPathSource::CommandLine => "command line",
+            PluginPathSource::Environment => "environment",
+            PluginPathSource::Config => "config",
+            PluginPathSource::DataDir => "data dir",
+            PluginPathSource::Default => "default",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// A single plugin search directory and its origin.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginSearchPath {
+    /// Directory to scan for plugin manifests
+    pub path: PathBuf,
+    /// Where this directory was configured
+    pub source: PluginPathSource,
+}
+
+/// Ordered list of directories to scan for plugins.
+///
+/// Entries are kept sorted by [`PluginPathSource`] precedence; entries from the same
+/// source keep their insertion order. Within the built-in layouts, the core plugins
+/// directory comes before the third-party one, so core copies win over third-party copies.
+#[derive(Debug, Clone, Default)]
+pub struct PluginSearchPaths {
+    entries: Vec<PluginSearchPath>,
+}
+
+impl PluginSearchPaths {
+    /// Create an empty search path list
+    pub fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    /// Add a directory from the given source.
+    /// A directory that is already listed is ignored, keeping its earlier (higher-precedence) position.
+    pub fn add<P: Into<PathBuf>>(&mut self, path: P, source: PluginPathSource) -> &mut Self {
+        let path = path.into();
+        if path.as_os_str().is_empty() || self.contains(&path) {
+            return self;
+        }
+        // Insert after the last entry with the same or higher precedence
+        let position = self.entries.iter().position(|entry| entry.source > source).unwrap_or(self.entries.len());
+        self.entries.insert(position, PluginSearchPath { path, source });
+        self
+    }
+
+    /// Add every directory of a `PATH`-style list (e.g. the value of `GINI_PLUGIN_PATH`).
+    pub fn add_path_list(&mut self, value: &OsStr, source: PluginPathSource) -> &mut Self {
+        for path in std::env::split_paths(value) {
+            self.add(path, source);
+        }
+        self
+    }
+
+    /// Add the standard plugin layout below `base`: core plugins, third-party plugins,
+    /// then the general plugins directory.
+    pub fn add_standard_layout(&mut self, base: &Path, source: PluginPathSource) -> &mut Self {
+        self.add(base.join(constants::CORE_PLUGINS_DIR), source);
+        self.add(base.join(constants::THIRD_PARTY_PLUGINS_DIR), source);
+        self.add(base.join(constants::DEFAULT_PLUGINS_DIR), source);
+        self
+    }
+
+    /// Check whether a directory is already part of the search path
+    pub fn contains(&self, path: &Path) -> bool {
+        self.entries.iter().any(|entry| entry.path == path)
+    }
+
+    /// Get the source a directory was configured from
+    pub fn source_of(&self, path: &Path) -> Option<PluginPathSource> {
+        self.entries.iter().find(|entry| entry.path == path).map(|entry| entry.source)
+    }
+
+    /// Get all entries in precedence order
+    pub fn entries(&self) -> &[PluginSearchPath] {
+        &self.entries
+    }
+
+    /// Get all directories in precedence order
+    pub fn dirs(&self) -> Vec<PathBuf> {
+        self.entries.iter().map(|entry| entry.path.clone()).collect()
+    }
+
+    /// Number of directories in the search path
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the search path is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
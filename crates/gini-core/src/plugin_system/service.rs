@@ -0,0 +1,209 @@
+//! # Plugin Services
+//!
+//! Typed APIs that plugins offer each other. A plugin publishes an `Arc<dyn Trait>` under a
+//! service name and version through its [`PluginAccess`], which records the plugin as the
+//! provider. Plugins that depend on it look the service up with a type-checked getter
+//! instead of going through events or `StageContext` keys.
+//!
+//! A plugin lists the services it needs in [`Plugin::required_services`]. Before its `init`
+//! runs, the registry checks that each one is published in a matching version and fails the
+//! plugin with [`DependencyError::MissingService`] otherwise. The provider should be one of
+//! the plugin's required dependencies so that it initializes first.
+//!
+//! Services are withdrawn when their provider shuts down, fails to initialize or is
+//! unregistered. Plugins shut down after the plugins that require them, so a service stays
+//! available to its dependents for as long as they run.
+//!
+//! [`Plugin::required_services`]: crate::plugin_system::traits::Plugin::required_services
+//! [`PluginAccess`]: crate::plugin_system::permission::PluginAccess
+use std::any::{type_name, Any};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+use semver::Version;
+
+use crate::plugin_system::dependency::DependencyError;
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::version::{VersionError, VersionRange};
+
+/// A service a plugin needs before it can initialize.
+#[derive(Debug, Clone)]
+pub struct ServiceRequirement {
+    pub name: String,
+    /// Acceptable versions; any version when `None`
+    pub version_range: Option<VersionRange>,
+}
+
+impl ServiceRequirement {
+    /// Requires the service in any version
+    pub fn any(name: &str) -> Self {
+        Self { name: name.to_string(), version_range: None }
+    }
+
+    /// Requires the service in a version within `version_range`
+    pub fn new(name: &str, version_range: VersionRange) -> Self {
+        Self { name: name.to_string(), version_range: Some(version_range) }
+    }
+}
+
+impl fmt::Display for ServiceRequirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.version_range {
+            Some(range) => write!(f, "{} {}", self.name, range),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// A published service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceInfo {
+    pub name: String,
+    pub version: Version,
+    /// ID of the plugin that published it
+    pub provider_id: String,
+    /// Rust type of the published `Arc`, e.g. `dyn my_plugin::Sink`
+    pub type_name: &'static str,
+}
+
+impl fmt::Display for ServiceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} (provided by {}, type {})", self.name, self.version, self.provider_id, self.type_name)
+    }
+}
+
+struct ServiceEntry {
+    info: ServiceInfo,
+    /// The published `Arc<T>`
+    service: Box<dyn Any + Send + Sync>,
+}
+
+/// The services published by initialized plugins, keyed by service name.
+#[derive(Default)]
+pub struct ServiceRegistry {
+    services: RwLock<HashMap<String, ServiceEntry>>,
+}
+
+impl fmt::Debug for ServiceRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServiceRegistry")
+            .field("services", &self.services().iter().map(|info| info.name.clone()).collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl ServiceRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Publishes `service` under `name` on behalf of `provider_id`.
+    /// `T` is usually a trait object: publish an `Arc<dyn Trait>` and look it up as `dyn Trait`.
+    /// A name can only be published once until its provider withdraws it. Plugins publish
+    /// through [`PluginAccess::publish_service`], which supplies their own ID.
+    ///
+    /// [`PluginAccess::publish_service`]: crate::plugin_system::permission::PluginAccess::publish_service
+    pub(crate) fn publish<T: ?Sized + Send + Sync + 'static>(
+        &self,
+        provider_id: &str,
+        name: &str,
+        version: &str,
+        service: Arc<T>,
+    ) -> Result<(), PluginSystemError> {
+        let version = Version::parse(version).map_err(|e| {
+            VersionError::ParseError(format!("Invalid version '{}' for service '{}': {}", version, name, e))
+        })?;
+        let mut services = self.services.write().map_err(|_| PluginSystemError::InternalError("Service registry lock poisoned".to_string()))?;
+        if let Some(existing) = services.get(name) {
+            return Err(PluginSystemError::ServiceError {
+                service: name.to_string(),
+                reason: format!("already published by '{}'", existing.info.provider_id),
+            });
+        }
+        log::info!("Plugin '{}' published service '{}' {}", provider_id, name, version);
+        let info = ServiceInfo {
+            name: name.to_string(),
+            version,
+            provider_id: provider_id.to_string(),
+            type_name: type_name::<T>(),
+        };
+        services.insert(name.to_string(), ServiceEntry { info, service: Box::new(service) });
+        Ok(())
+    }
+
+    /// Looks up a service in any version.
+    pub fn get<T: ?Sized + Send + Sync + 'static>(&self, name: &str) -> Result<Arc<T>, PluginSystemError> {
+        self.get_matching(&ServiceRequirement::any(name))
+    }
+
+    /// Looks up a service that satisfies `requirement`. A missing service or version mismatch
+    /// is a [`DependencyError::MissingService`]; asking for the wrong type is a `ServiceError`.
+    pub fn get_matching<T: ?Sized + Send + Sync + 'static>(&self, requirement: &ServiceRequirement) -> Result<Arc<T>, PluginSystemError> {
+        let services = self.services.read().map_err(|_| PluginSystemError::InternalError("Service registry lock poisoned".to_string()))?;
+        let entry = services.get(&requirement.name).ok_or_else(|| not_published(requirement))?;
+        check_version(requirement, &entry.info)?;
+        entry.service.downcast_ref::<Arc<T>>().cloned().ok_or_else(|| PluginSystemError::ServiceError {
+            service: requirement.name.clone(),
+            reason: format!("published as {}, requested as {}", entry.info.type_name, type_name::<T>()),
+        })
+    }
+
+    /// Checks that a service satisfying `requirement` is published.
+    pub fn check(&self, requirement: &ServiceRequirement) -> Result<ServiceInfo, DependencyError> {
+        let info = self.info(&requirement.name).ok_or_else(|| not_published(requirement))?;
+        check_version(requirement, &info)?;
+        Ok(info)
+    }
+
+    pub fn info(&self, name: &str) -> Option<ServiceInfo> {
+        self.services.read().ok().and_then(|services| services.get(name).map(|entry| entry.info.clone()))
+    }
+
+    pub fn is_published(&self, name: &str) -> bool {
+        self.services.read().map(|services| services.contains_key(name)).unwrap_or(false)
+    }
+
+    /// Every published service, sorted by name.
+    pub fn services(&self) -> Vec<ServiceInfo> {
+        let mut infos: Vec<ServiceInfo> = self
+            .services
+            .read()
+            .map(|services| services.values().map(|entry| entry.info.clone()).collect())
+            .unwrap_or_default();
+        infos.sort_by(|a, b| a.name.cmp(&b.name));
+        infos
+    }
+
+    /// Withdraws every service published by `provider_id` and returns their names, sorted.
+    pub fn withdraw(&self, provider_id: &str) -> Vec<String> {
+        let Ok(mut services) = self.services.write() else {
+            return Vec::new();
+        };
+        let mut withdrawn: Vec<String> = services
+            .values()
+            .filter(|entry| entry.info.provider_id == provider_id)
+            .map(|entry| entry.info.name.clone())
+            .collect();
+        withdrawn.sort();
+        for name in &withdrawn {
+            services.remove(name);
+            log::info!("Service '{}' of plugin '{}' withdrawn", name, provider_id);
+        }
+        withdrawn
+    }
+}
+
+fn not_published(requirement: &ServiceRequirement) -> DependencyError {
+    DependencyError::MissingService { service: requirement.to_string(), reason: "not published".to_string() }
+}
+
+fn check_version(requirement: &ServiceRequirement, info: &ServiceInfo) -> Result<(), DependencyError> {
+    match &requirement.version_range {
+        Some(range) if !range.includes(&info.version) => Err(DependencyError::MissingService {
+            service: requirement.to_string(),
+            reason: format!("version {} published by '{}' does not match", info.version, info.provider_id),
+        }),
+        _ => Ok(()),
+    }
+}
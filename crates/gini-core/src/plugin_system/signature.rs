@@ -0,0 +1,436 @@
+//! # Plugin Signatures
+//!
+//! Optional ed25519 signatures for dynamic plugins. A signature lives next to the
+//! manifest in `manifest.sig` and covers the exact bytes of the manifest file together
+//! with the SHA-256 digest of the entry point, so neither can be changed without
+//! invalidating it:
+//!
+//! ```json
+//! {
+//!   "public_key": "<hex ed25519 public key>",
+//!   "entry_point_sha256": "<hex SHA-256 of the entry point>",
+//!   "signature": "<hex ed25519 signature>"
+//! }
+//! ```
+//!
+//! The signed message is [`SIGNATURE_CONTEXT`] followed by the SHA-256 digests of
+//! the manifest file and of the entry point. [`PluginSignature::sign_plugin`] produces it.
+//!
+//! A signature only counts as verified when its key is in the [`TrustStore`], a JSON file
+//! in the config directory ([`constants::PLUGIN_TRUST_STORE_FILE`]) that maps key names
+//! to hex public keys. The [`SignaturePolicy`] decides what happens to plugins that are
+//! not verified; [`SignatureVerifier`] applies it before a plugin library is opened and
+//! remembers the outcome for each plugin.
+//!
+//! [`constants::PLUGIN_TRUST_STORE_FILE`]: crate::kernel::constants::PLUGIN_TRUST_STORE_FILE
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::RwLock;
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::loader::PluginLoader;
+use crate::plugin_system::manifest::{PluginManifest, MANIFEST_FILE_NAMES};
+
+/// Name of the signature file next to a plugin's manifest.
+pub const SIGNATURE_FILE_NAME: &str = "manifest.sig";
+
+/// Prefix of every signed message, so plugin signatures cannot be confused with other ed25519 signatures.
+pub const SIGNATURE_CONTEXT: &[u8] = b"gini-plugin-signature-v1\0";
+
+/// The manifest file of the plugin in `dir`, or where its `manifest.json` would be
+fn manifest_file(dir: &Path) -> PathBuf {
+    PluginManifest::find_file(dir).unwrap_or_else(|| dir.join(MANIFEST_FILE_NAMES[0]))
+}
+
+/// What to do with plugins that are not signed by a trusted key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SignaturePolicy {
+    /// Load unsigned and unverified plugins without comment
+    #[default]
+    AllowUnsigned,
+    /// Load them, but print a warning
+    Warn,
+    /// Refuse to load them
+    Enforce,
+}
+
+impl FromStr for SignaturePolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_ascii_lowercase().as_str() {
+            "allow-unsigned" => Ok(SignaturePolicy::AllowUnsigned),
+            "warn" => Ok(SignaturePolicy::Warn),
+            "enforce" => Ok(SignaturePolicy::Enforce),
+            other => Err(format!("Unknown signature policy '{}' (expected allow-unsigned, warn or enforce)", other)),
+        }
+    }
+}
+
+impl fmt::Display for SignaturePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            SignaturePolicy::AllowUnsigned => "allow-unsigned",
+            SignaturePolicy::Warn => "warn",
+            SignaturePolicy::Enforce => "enforce",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// Outcome of checking a plugin's signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationStatus {
+    /// Signed by a key in the trust store
+    Verified { key_name: String },
+    /// Validly signed, but by a key that is not in the trust store
+    UntrustedKey { public_key: String },
+    /// There is no `manifest.sig` next to the manifest
+    Unsigned,
+    /// The signature is malformed or does not match the manifest and entry point
+    Invalid { reason: String },
+}
+
+impl VerificationStatus {
+    /// Whether the plugin is signed by a trusted key
+    pub fn is_verified(&self) -> bool {
+        matches!(self, VerificationStatus::Verified { .. })
+    }
+}
+
+impl fmt::Display for VerificationStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerificationStatus::Verified { key_name } => write!(f, "verified (key '{}')", key_name),
+            VerificationStatus::UntrustedKey { public_key } => write!(f, "signed by untrusted key {}", public_key),
+            VerificationStatus::Unsigned => write!(f, "unsigned"),
+            VerificationStatus::Invalid { reason } => write!(f, "invalid signature: {}", reason),
+        }
+    }
+}
+
+/// Contents of a `manifest.sig` file. All fields are lowercase hex.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginSignature {
+    /// ed25519 public key of the signer
+    pub public_key: String,
+    /// SHA-256 of the entry point at signing time
+    pub entry_point_sha256: String,
+    /// ed25519 signature over the signed message
+    pub signature: String,
+}
+
+/// The message a plugin signature covers.
+fn signed_message(manifest_digest: &[u8], entry_point_digest: &[u8]) -> Vec<u8> {
+    let mut message = SIGNATURE_CONTEXT.to_vec();
+    message.extend_from_slice(manifest_digest);
+    message.extend_from_slice(entry_point_digest);
+    message
+}
+
+fn sha256_file(path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hasher.finalize().to_vec())
+}
+
+fn parse_public_key(hex_key: &str) -> Result<VerifyingKey, String> {
+    let bytes: [u8; 32] = hex::decode(hex_key.trim())
+        .map_err(|e| format!("public key is not valid hex: {}", e))?
+        .try_into()
+        .map_err(|_| "public key must be 32 bytes".to_string())?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("invalid public key: {}", e))
+}
+
+impl PluginSignature {
+    /// Sign the plugin in `plugin_dir` (its manifest file and entry point) with
+    /// `signing_key`, and write the signature to its `manifest.sig`.
+    pub fn sign_plugin(plugin_dir: &Path, signing_key: &SigningKey) -> Result<Self, PluginSystemError> {
+        let manifest_path = manifest_file(plugin_dir);
+        let manifest_content = fs::read_to_string(&manifest_path)
+            .map_err(|e| signature_error(&manifest_path.display().to_string(), format!("Failed to read manifest: {}", e)))?;
+        let manifest = PluginLoader::parse_manifest(&manifest_content, &manifest_path)?;
+        let entry_point = PluginLoader::entry_point_path(&manifest)?;
+        let entry_point_digest = sha256_file(&entry_point)
+            .map_err(|e| signature_error(&manifest.id, format!("Failed to read entry point {}: {}", entry_point.display(), e)))?;
+
+        let message = signed_message(&Sha256::digest(manifest_content.as_bytes()), &entry_point_digest);
+        let signature = Self {
+            public_key: hex::encode(signing_key.verifying_key().as_bytes()),
+            entry_point_sha256: hex::encode(&entry_point_digest),
+            signature: hex::encode(signing_key.sign(&message).to_bytes()),
+        };
+        let json = serde_json::to_string_pretty(&signature)
+            .map_err(|e| signature_error(&manifest.id, format!("Failed to serialize signature: {}", e)))?;
+        fs::write(plugin_dir.join(SIGNATURE_FILE_NAME), json)
+            .map_err(|e| signature_error(&manifest.id, format!("Failed to write {}: {}", SIGNATURE_FILE_NAME, e)))?;
+        Ok(signature)
+    }
+
+    /// Check this signature against the manifest file and entry point on disk, and look up its key.
+    pub fn verify(&self, manifest_path: &Path, entry_point: &Path, trust_store: &TrustStore) -> VerificationStatus {
+        let invalid = |reason: String| VerificationStatus::Invalid { reason };
+
+        let public_key = match parse_public_key(&self.public_key) {
+            Ok(key) => key,
+            Err(reason) => return invalid(reason),
+        };
+        let signature = match hex::decode(self.signature.trim()).ok().and_then(|bytes| Signature::from_slice(&bytes).ok()) {
+            Some(signature) => signature,
+            None => return invalid("signature is not a hex-encoded ed25519 signature".to_string()),
+        };
+        let manifest_digest = match sha256_file(manifest_path) {
+            Ok(digest) => digest,
+            Err(e) => return invalid(format!("failed to read {}: {}", manifest_path.display(), e)),
+        };
+        let entry_point_digest = match sha256_file(entry_point) {
+            Ok(digest) => digest,
+            Err(e) => return invalid(format!("failed to read entry point {}: {}", entry_point.display(), e)),
+        };
+
+        if hex::encode(&entry_point_digest) != self.entry_point_sha256.trim().to_ascii_lowercase() {
+            return invalid("entry point does not match its signed SHA-256".to_string());
+        }
+        if public_key.verify(&signed_message(&manifest_digest, &entry_point_digest), &signature).is_err() {
+            return invalid("signature does not match the manifest and entry point".to_string());
+        }
+        match trust_store.key_name(&public_key) {
+            Some(key_name) => VerificationStatus::Verified { key_name: key_name.to_string() },
+            None => VerificationStatus::UntrustedKey { public_key: hex::encode(public_key.as_bytes()) },
+        }
+    }
+}
+
+fn signature_error(plugin_id: &str, message: String) -> PluginSystemError {
+    PluginSystemError::SignatureError { plugin_id: plugin_id.to_string(), message }
+}
+
+/// On-disk form of the trust store.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct TrustStoreFile {
+    /// Hex public keys by name
+    #[serde(default)]
+    keys: BTreeMap<String, String>,
+}
+
+/// Named public keys whose plugin signatures are trusted.
+#[derive(Debug, Clone, Default)]
+pub struct TrustStore {
+    keys: BTreeMap<String, VerifyingKey>,
+}
+
+impl TrustStore {
+    /// Create an empty trust store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load a trust store file. A missing file is an empty trust store.
+    pub fn load(path: &Path) -> Result<Self, PluginSystemError> {
+        let store_error = |message: String| PluginSystemError::OperationError { plugin_id: None, message };
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(e) => return Err(store_error(format!("Failed to read trust store {}: {}", path.display(), e))),
+        };
+        let file: TrustStoreFile = serde_json::from_str(&content)
+            .map_err(|e| store_error(format!("Failed to parse trust store {}: {}", path.display(), e)))?;
+
+        let mut store = Self::new();
+        for (name, hex_key) in file.keys {
+            let key = parse_public_key(&hex_key)
+                .map_err(|e| store_error(format!("Trust store {} has an invalid key '{}': {}", path.display(), name, e)))?;
+            store.add(&name, key);
+        }
+        Ok(store)
+    }
+
+    /// Write the trust store to `path`, creating its directory if needed.
+    pub fn save(&self, path: &Path) -> Result<(), PluginSystemError> {
+        let store_error = |message: String| PluginSystemError::OperationError { plugin_id: None, message };
+        let file = TrustStoreFile {
+            keys: self.keys.iter().map(|(name, key)| (name.clone(), hex::encode(key.as_bytes()))).collect(),
+        };
+        let json = serde_json::to_string_pretty(&file)
+            .map_err(|e| store_error(format!("Failed to serialize trust store: {}", e)))?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| store_error(format!("Failed to create {}: {}", parent.display(), e)))?;
+        }
+        fs::write(path, json).map_err(|e| store_error(format!("Failed to write trust store {}: {}", path.display(), e)))
+    }
+
+    /// Trust `key` under `name`, replacing any key with the same name
+    pub fn add(&mut self, name: &str, key: VerifyingKey) {
+        self.keys.insert(name.to_string(), key);
+    }
+
+    /// Stop trusting the key named `name`. Returns whether it was present.
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.keys.remove(name).is_some()
+    }
+
+    /// Name under which `key` is trusted, if it is
+    pub fn key_name(&self, key: &VerifyingKey) -> Option<&str> {
+        self.keys.iter().find(|(_, trusted)| *trusted == key).map(|(name, _)| name.as_str())
+    }
+
+    /// Trusted keys by name
+    pub fn keys(&self) -> impl Iterator<Item = (&str, &VerifyingKey)> {
+        self.keys.iter().map(|(name, key)| (name.as_str(), key))
+    }
+
+    /// Number of trusted keys
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether no key is trusted
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// Checks plugin signatures against the trust store and applies the signature policy.
+///
+/// Shared between the plugin manager, the registry and manifest-only plugins, so the
+/// policy and trust store can be changed in one place. The status of every checked
+/// plugin is remembered for display (e.g. `gini plugin list`).
+#[derive(Debug, Default)]
+pub struct SignatureVerifier {
+    policy: RwLock<SignaturePolicy>,
+    trust_store: RwLock<TrustStore>,
+    trust_store_path: RwLock<Option<PathBuf>>,
+    statuses: RwLock<HashMap<String, VerificationStatus>>,
+}
+
+impl SignatureVerifier {
+    /// Create a verifier with the given policy and trust store
+    pub fn new(policy: SignaturePolicy, trust_store: TrustStore) -> Self {
+        Self {
+            policy: RwLock::new(policy),
+            trust_store: RwLock::new(trust_store),
+            ..Self::default()
+        }
+    }
+
+    /// The current signature policy
+    pub fn policy(&self) -> SignaturePolicy {
+        self.policy.read().map(|policy| *policy).unwrap_or_default()
+    }
+
+    /// Change the signature policy
+    pub fn set_policy(&self, policy: SignaturePolicy) {
+        if let Ok(mut current) = self.policy.write() {
+            *current = policy;
+        }
+    }
+
+    /// A copy of the current trust store
+    pub fn trust_store(&self) -> TrustStore {
+        self.trust_store.read().map(|store| store.clone()).unwrap_or_default()
+    }
+
+    /// Replace the trust store
+    pub fn set_trust_store(&self, trust_store: TrustStore) {
+        if let Ok(mut current) = self.trust_store.write() {
+            *current = trust_store;
+        }
+    }
+
+    /// File the trust store is loaded from by [`reload_trust_store`](Self::reload_trust_store)
+    pub fn trust_store_path(&self) -> Option<PathBuf> {
+        self.trust_store_path.read().ok().and_then(|path| path.clone())
+    }
+
+    /// Set the file the trust store is loaded from
+    pub fn set_trust_store_path(&self, path: PathBuf) {
+        if let Ok(mut current) = self.trust_store_path.write() {
+            *current = Some(path);
+        }
+    }
+
+    /// Load the trust store from its file, if one is set.
+    pub fn reload_trust_store(&self) -> Result<(), PluginSystemError> {
+        if let Some(path) = self.trust_store_path() {
+            self.set_trust_store(TrustStore::load(&path)?);
+        }
+        Ok(())
+    }
+
+    /// Check a manifest's signature without applying the policy or recording the result.
+    pub fn verify(&self, manifest: &PluginManifest) -> VerificationStatus {
+        let signature_path = manifest.plugin_base_dir.join(SIGNATURE_FILE_NAME);
+        let content = match fs::read_to_string(&signature_path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return VerificationStatus::Unsigned,
+            Err(e) => return VerificationStatus::Invalid { reason: format!("failed to read {}: {}", SIGNATURE_FILE_NAME, e) },
+        };
+        let signature: PluginSignature = match serde_json::from_str(&content) {
+            Ok(signature) => signature,
+            Err(e) => return VerificationStatus::Invalid { reason: format!("malformed {}: {}", SIGNATURE_FILE_NAME, e) },
+        };
+        let entry_point = match PluginLoader::entry_point_path(manifest) {
+            Ok(path) => path,
+            Err(e) => return VerificationStatus::Invalid { reason: e.to_string() },
+        };
+        let trust_store = self.trust_store.read().map(|store| store.clone()).unwrap_or_default();
+        signature.verify(&manifest_file(&manifest.plugin_base_dir), &entry_point, &trust_store)
+    }
+
+    /// Check a manifest's signature and apply the policy: plugins that are not verified
+    /// are refused under [`SignaturePolicy::Enforce`] and reported under [`SignaturePolicy::Warn`].
+    /// Invalid signatures are always reported. The status is recorded either way.
+    pub fn check(&self, manifest: &PluginManifest) -> Result<VerificationStatus, PluginSystemError> {
+        let status = self.verify(manifest);
+        self.record(&manifest.id, status.clone());
+        self.apply_policy(&manifest.id, status)
+    }
+
+    /// Apply the policy to a library loaded by path without a manifest, which cannot carry a signature.
+    pub fn check_unsigned(&self, plugin_label: &str) -> Result<VerificationStatus, PluginSystemError> {
+        self.apply_policy(plugin_label, VerificationStatus::Unsigned)
+    }
+
+    /// The recorded status of a plugin, if it was checked
+    pub fn status(&self, plugin_id: &str) -> Option<VerificationStatus> {
+        self.statuses.read().ok().and_then(|statuses| statuses.get(plugin_id).cloned())
+    }
+
+    /// Record the status of a plugin checked under another name (e.g. a library loaded by path)
+    pub(crate) fn record(&self, plugin_id: &str, status: VerificationStatus) {
+        if let Ok(mut statuses) = self.statuses.write() {
+            statuses.insert(plugin_id.to_string(), status);
+        }
+    }
+
+    fn apply_policy(&self, plugin_id: &str, status: VerificationStatus) -> Result<VerificationStatus, PluginSystemError> {
+        if status.is_verified() {
+            return Ok(status);
+        }
+        match self.policy() {
+            SignaturePolicy::Enforce => Err(signature_error(plugin_id, format!(
+                "{}; the signature policy only allows plugins signed by a trusted key",
+                status
+            ))),
+            SignaturePolicy::Warn => {
+                eprintln!("Warning: Plugin '{}' is {}; loading it anyway (signature policy 'warn').", plugin_id, status);
+                Ok(status)
+            }
+            SignaturePolicy::AllowUnsigned => {
+                if let VerificationStatus::Invalid { reason } = &status {
+                    eprintln!("Warning: Plugin '{}' has an invalid signature: {}", plugin_id, reason);
+                }
+                Ok(status)
+            }
+        }
+    }
+}
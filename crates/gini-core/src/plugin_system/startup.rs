@@ -0,0 +1,99 @@
+//! # Plugin Startup
+//!
+//! [`InitFailurePolicy`] decides what
+//! [`PluginRegistry::initialize_all`](crate::plugin_system::registry::PluginRegistry::initialize_all)
+//! does when a plugin fails to initialize. Under [`InitFailurePolicy::Isolate`] a failing
+//! third-party plugin is left `Failed`, its partially registered stages are unregistered and
+//! the plugins that require it are disabled, while startup continues with the rest. Failing
+//! core plugins always stop startup.
+//!
+//! [`StartupReport`] records which plugins came up and which did not.
+use std::fmt;
+use std::str::FromStr;
+
+/// What happens when a non-core plugin fails to initialize.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InitFailurePolicy {
+    /// Leave the plugin `Failed`, disable its dependents and keep going
+    #[default]
+    Isolate,
+    /// Stop initializing plugins and return the error
+    FailFast,
+}
+
+impl FromStr for InitFailurePolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        // Accepts any case and separator, so "FailFast" and "fail_fast" work too
+        let normalized: String = value.trim().chars().filter(|c| !matches!(c, '-' | '_')).collect();
+        match normalized.to_ascii_lowercase().as_str() {
+            "isolate" => Ok(InitFailurePolicy::Isolate),
+            "failfast" => Ok(InitFailurePolicy::FailFast),
+            _ => Err(format!("Unknown plugin init failure policy '{}' (expected isolate or fail-fast)", value.trim())),
+        }
+    }
+}
+
+impl fmt::Display for InitFailurePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitFailurePolicy::Isolate => write!(f, "isolate"),
+            InitFailurePolicy::FailFast => write!(f, "fail-fast"),
+        }
+    }
+}
+
+/// A plugin that did not come up, and why.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PluginOutcome {
+    pub plugin_id: String,
+    /// The initialization error, or why the plugin was skipped
+    pub reason: String,
+}
+
+/// Outcome of initializing the enabled plugins.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StartupReport {
+    /// Plugins that are initialized, in initialization order
+    pub initialized: Vec<String>,
+    /// Plugins whose initialization failed
+    pub failed: Vec<PluginOutcome>,
+    /// Plugins disabled because a plugin they require failed
+    pub skipped: Vec<PluginOutcome>,
+    /// Plugins that were disabled before startup
+    pub disabled: Vec<String>,
+}
+
+impl StartupReport {
+    /// Whether every enabled plugin came up.
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.skipped.is_empty()
+    }
+}
+
+impl fmt::Display for StartupReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Plugin startup: {} initialized, {} failed, {} skipped, {} disabled",
+            self.initialized.len(),
+            self.failed.len(),
+            self.skipped.len(),
+            self.disabled.len()
+        )?;
+        if !self.initialized.is_empty() {
+            writeln!(f, "  initialized: {}", self.initialized.join(", "))?;
+        }
+        for outcome in &self.failed {
+            writeln!(f, "  failed: {}: {}", outcome.plugin_id, outcome.reason)?;
+        }
+        for outcome in &self.skipped {
+            writeln!(f, "  skipped: {}: {}", outcome.plugin_id, outcome.reason)?;
+        }
+        if !self.disabled.is_empty() {
+            writeln!(f, "  disabled: {}", self.disabled.join(", "))?;
+        }
+        Ok(())
+    }
+}
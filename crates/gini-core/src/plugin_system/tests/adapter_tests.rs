@@ -1,5 +1,6 @@
 // crates/gini-core/src/plugin_system/tests/adapter_tests.rs
 #![cfg(test)]
+#![allow(deprecated)]
 
 // Import macro from crate root, and other items from the adapter module
 use crate::define_adapter; // Use the macro from the crate root
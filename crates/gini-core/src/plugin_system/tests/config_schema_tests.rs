@@ -0,0 +1,77 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use tempfile::tempdir;
+use tokio::sync::Mutex;
+
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::lifecycle::PluginState;
+use crate::plugin_system::manifest::PluginManifest;
+use crate::plugin_system::tests::{TestPlugin, registry_with};
+use crate::stage_manager::registry::StageRegistry;
+use crate::storage::schema::{ConfigSchema, SchemaProperty, SchemaType};
+
+/// A plugin declaring `schema` that records whether its init ran
+fn schema_plugin(id: &str, schema: ConfigSchema, initialized: Arc<AtomicBool>) -> TestPlugin {
+    TestPlugin::new(id).config_schema(schema).on_init(move |_id, _app| {
+        initialized.store(true, Ordering::SeqCst);
+        Ok(())
+    })
+}
+
+#[tokio::test]
+async fn test_invalid_config_fails_the_plugin_before_init() {
+    let strict = Arc::new(AtomicBool::new(false));
+    let lenient = Arc::new(AtomicBool::new(false));
+    let mut registry = registry_with([
+        schema_plugin(
+            "config-schema-test-strict",
+            ConfigSchema::new().property("api_key", SchemaProperty::string().required()),
+            strict.clone(),
+        ),
+        schema_plugin(
+            "config-schema-test-lenient",
+            ConfigSchema::new().property("retries", SchemaProperty::integer().with_default(3)),
+            lenient.clone(),
+        ),
+    ]);
+    let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+    let mut app = Application::new().unwrap();
+
+    let report = registry.initialize_all(&mut app, &stage_registry).await.unwrap();
+
+    assert_eq!(report.initialized, vec!["config-schema-test-lenient"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].plugin_id, "config-schema-test-strict");
+    assert!(report.failed[0].reason.ends_with("Invalid configuration for plugin 'config-schema-test-strict': api_key: required but not set"), "{}", report.failed[0].reason);
+    assert_eq!(registry.lifecycle().state("config-schema-test-strict"), Some(PluginState::Failed));
+    assert!(!strict.load(Ordering::SeqCst), "init must not run with an invalid configuration");
+    assert!(lenient.load(Ordering::SeqCst));
+
+    let config = app.storage_manager().get_config_manager().get_plugin_config("config-schema-test-lenient").unwrap();
+    assert_eq!(config.get::<i64>("retries"), Some(3));
+}
+
+#[test]
+fn test_manifest_config_schema_is_read_from_the_plugin_directory() {
+    let dir = tempdir().unwrap();
+    let mut manifest = PluginManifest::new("schema_plugin", "Schema", "1.0.0", "Desc", "Auth");
+    manifest.plugin_base_dir = dir.path().to_path_buf();
+    assert!(manifest.load_config_schema().unwrap().is_none());
+
+    std::fs::write(dir.path().join("config.schema.json"), r#"{
+        "properties": { "level": { "type": "string", "default": "info" } }
+    }"#).unwrap();
+    manifest.config_schema = Some("config.schema.json".to_string());
+    let schema = manifest.load_config_schema().unwrap().unwrap();
+    assert_eq!(schema.get("level").unwrap().value_type, SchemaType::String);
+
+    std::fs::write(dir.path().join("config.schema.json"), r#"{ "properties": { "level": { "type": "text" } } }"#).unwrap();
+    let error = manifest.load_config_schema().unwrap_err();
+    assert!(matches!(error, PluginSystemError::ManifestError { .. }), "{}", error);
+    assert!(error.to_string().contains("Invalid config schema"), "{}", error);
+
+    manifest.config_schema = Some("missing.json".to_string());
+    assert!(manifest.load_config_schema().unwrap_err().to_string().contains("Failed to read config schema"));
+}
@@ -1,7 +1,7 @@
 // crates/gini-core/src/plugin_system/tests/conflict_tests.rs
 #![cfg(test)]
 
-use crate::plugin_system::conflict::{ConflictManager, ConflictType, PluginConflict, ResolutionStrategy, ResourceIdentifier, ResourceAccessType as GiniResourceAccessType};
+use crate::plugin_system::conflict::{ConflictManager, ConflictParty, ConflictPolicy, ConflictType, PluginConflict, ResolutionStrategy, ResourceIdentifier, ResourceAccessType as GiniResourceAccessType};
 use crate::plugin_system::{Plugin, PluginDependency, ApiVersion, VersionRange, PluginPriority, PluginRegistry};
 use crate::plugin_system::error::PluginSystemError; // Import PluginSystemError
 use crate::stage_manager::StageRequirement; // Removed unused Stage
@@ -11,7 +11,9 @@ use crate::stage_manager::context::StageContext; // Added for preflight_check
 use crate::kernel::bootstrap::Application; // Needed for Plugin trait
 use std::str::FromStr; // Needed for VersionRange::from_str
 use std::sync::Arc; // Added for Arc::new
+use std::collections::{HashMap, HashSet};
 use crate::plugin_system::manifest::{ManifestBuilder, ResourceAccessType}; // Added for new tests, removed PluginManifest
+use crate::plugin_system::conflict::ResourceClaim;
 
 
 #[test]
@@ -487,6 +489,85 @@ fn test_multiple_resources_multiple_conflicts() {
 }
 
 
+#[test]
+fn test_file_paths_overlap_by_containment() {
+    let dir = ResourceIdentifier::new("FilePath", "/var/lib/gini/vms");
+    assert!(dir.overlaps(&ResourceIdentifier::new("FilePath", "/var/lib/gini/vms/a.qcow2")));
+    assert!(ResourceIdentifier::new("FilePath", "/var/lib/gini/vms/a.qcow2").overlaps(&dir));
+    assert!(dir.overlaps(&ResourceIdentifier::new("file_path", "/var/lib/gini/vms/")), "Kinds ignore case and underscores");
+    assert!(!dir.overlaps(&ResourceIdentifier::new("FilePath", "/var/lib/gini/vms2")));
+    assert!(dir.overlaps(&ResourceIdentifier::new("FilePath", "/var/lib/gini")), "A parent directory contains the path");
+    assert!(!dir.overlaps(&ResourceIdentifier::new("Abstract", "/var/lib/gini/vms")), "Different kinds never overlap");
+}
+
+#[test]
+fn test_paths_are_normalized_and_empty_ids_overlap_nothing() {
+    let dir = ResourceIdentifier::new("FilePath", "/var/lib/gini/vms");
+    assert!(dir.overlaps(&ResourceIdentifier::new("FilePath", "/var/lib/gini/./logs/../vms/a.qcow2")));
+    assert!(!dir.overlaps(&ResourceIdentifier::new("FilePath", "/var/lib/gini/vms/../logs")), "'..' leaves the directory");
+    assert!(dir.overlaps(&ResourceIdentifier::new("FilePath", "/../var/lib")), "'..' stops at the root");
+    assert!(!ResourceIdentifier::new("StoragePath", "../data").overlaps(&ResourceIdentifier::new("StoragePath", "data")));
+
+    for empty in ["", "  "] {
+        for kind in ["FilePath", "NetworkPort", "Abstract"] {
+            let claim = ResourceIdentifier::new(kind, empty);
+            assert!(!claim.overlaps(&dir), "{:?} {:?}", kind, empty);
+            assert!(!claim.overlaps(&claim.clone()), "{:?} {:?}", kind, empty);
+        }
+    }
+}
+
+#[test]
+fn test_network_ports_overlap_by_range() {
+    let range = ResourceIdentifier::new("NetworkPort", "tcp:8000-8100");
+    assert!(range.overlaps(&ResourceIdentifier::new("NetworkPort", "8080")));
+    assert!(range.overlaps(&ResourceIdentifier::new("network_port", "tcp:8100-8200")));
+    assert!(!range.overlaps(&ResourceIdentifier::new("NetworkPort", "udp:8080")));
+    assert!(!range.overlaps(&ResourceIdentifier::new("NetworkPort", "8101")));
+}
+
+#[test]
+fn test_abstract_ids_overlap_by_glob() {
+    let pattern = ResourceIdentifier::new("Abstract", "vm.*.disk");
+    assert!(pattern.overlaps(&ResourceIdentifier::new("Abstract", "vm.main.disk")));
+    assert!(pattern.overlaps(&ResourceIdentifier::new("Abstract", "vm.main.*")));
+    assert!(pattern.overlaps(&ResourceIdentifier::new("Abstract", "*")));
+    assert!(ResourceIdentifier::new("Abstract", "lock-?").overlaps(&ResourceIdentifier::new("Abstract", "lock-1")));
+    assert!(!pattern.overlaps(&ResourceIdentifier::new("Abstract", "vm.main.net")));
+    assert!(!pattern.overlaps(&ResourceIdentifier::new("Abstract", "net.*")));
+}
+
+#[test]
+fn test_manifest_claims_overlapping_paths_conflict() {
+    let mut manager = ConflictManager::new();
+    let manifests = vec![
+        ManifestBuilder::new("plugin_P", "Plugin P", "1.0.0")
+            .resource("file_path", "/var/lib/gini/vms", ResourceAccessType::ExclusiveWrite)
+            .build(),
+        ManifestBuilder::new("plugin_Q", "Plugin Q", "1.0.0")
+            .resource("file_path", "/var/lib/gini/vms/a.qcow2", ResourceAccessType::SharedRead)
+            .build(),
+    ];
+    manager.detect_conflicts(&manifests).unwrap();
+    let conflicts = manager.get_conflicts();
+    assert_eq!(conflicts.len(), 1);
+    assert!(conflicts[0].is_critical());
+    assert!(conflicts[0].description.contains("identifiers '/var/lib/gini/vms' and '/var/lib/gini/vms/a.qcow2' overlap"), "{}", conflicts[0].description);
+}
+
+#[test]
+fn test_manifest_resource_claims_deserialize() {
+    let claims: Vec<ResourceClaim> = serde_json::from_value(serde_json::json!([
+        { "type": "file_path", "identifier": "/var/log/app.log", "access": "shared_write" },
+        { "type": "network_port", "identifier": "tcp:8080", "access": "exclusive_read" },
+    ]))
+    .unwrap();
+    assert_eq!(claims[0], ResourceClaim::new("file_path", "/var/log/app.log", ResourceAccessType::SharedWrite));
+    assert_eq!(claims[1].resource, ResourceIdentifier::new("network_port", "tcp:8080"));
+    assert_eq!(claims[1].access_type, GiniResourceAccessType::ExclusiveRead);
+    assert_eq!(serde_json::to_value(&claims[0]).unwrap()["access"], "shared_write");
+}
+
 // --- Tests for PluginRegistry::detect_all_conflicts ---
 mod registry_conflict_tests {
     use super::*; // Import items from the parent module
@@ -504,6 +585,7 @@ mod registry_conflict_tests {
     // Fields for explicit conflict/incompatibility declarations
     conflicts_with_ids: Vec<String>,
     incompatible_with_deps: Vec<PluginDependency>, // Use PluginDependency for consistency
+    resources: Vec<ResourceClaim>,
 }
 
 impl MockPlugin {
@@ -520,9 +602,15 @@ impl MockPlugin {
             is_core: false,
             conflicts_with_ids: vec![], // Initialize new fields
             incompatible_with_deps: vec![], // Initialize new fields
+            resources: vec![],
         }
     }
 
+    fn with_resources(mut self, resources: Vec<ResourceClaim>) -> Self {
+        self.resources = resources;
+        self
+    }
+
     // Builder-style methods to set conflicts/incompatibilities for tests
     fn conflicts_with(mut self, ids: &[&str]) -> Self {
         self.conflicts_with_ids = ids.iter().map(|s| s.to_string()).collect();
@@ -570,6 +658,7 @@ impl Plugin for MockPlugin {
 // Implement new trait methods
     fn conflicts_with(&self) -> Vec<String> { self.conflicts_with_ids.clone() }
     fn incompatible_with(&self) -> Vec<PluginDependency> { self.incompatible_with_deps.clone() }
+    fn declared_resources(&self) -> Vec<ResourceClaim> { self.resources.clone() }
 }
 
 // Helper to create a registry with mock plugins
@@ -746,6 +835,25 @@ fn test_registry_detect_resource_conflict_placeholder() {
     assert_eq!(conflicts.len(), 0, "Expected no conflicts as placeholder logic is removed and MockPlugin declares no resources. Conflicts found: {:?}", conflicts);
 }
 
+#[test]
+fn test_registry_checks_manifest_and_declared_resources_together() {
+    let declared = MockPlugin::new("vm-manager", "1.0.0")
+        .with_resources(vec![ResourceClaim::new("FilePath", "/var/lib/gini/vms", GiniResourceAccessType::ExclusiveWrite)]);
+    let mut registry = create_registry_with_plugins(vec![declared]);
+    let manifest = ManifestBuilder::new("disk-backup", "Disk Backup", "1.0.0")
+        .api_version(<VersionRange as FromStr>::from_str(">=0.1.0").unwrap())
+        .resource("file_path", "/var/lib/gini/vms/a.qcow2", ResourceAccessType::SharedRead)
+        .build();
+    registry.register_manifest(manifest).unwrap();
+    registry.enable_plugin("disk-backup").unwrap();
+
+    registry.detect_all_conflicts().unwrap();
+    let conflicts = registry.conflict_manager().get_conflicts();
+    assert_eq!(conflicts.len(), 1, "Conflicts found: {:?}", conflicts);
+    assert!(conflicts[0].is_critical());
+    assert!(conflicts[0].description.contains("overlapping"), "{}", conflicts[0].description);
+}
+
 #[test]
 fn test_registry_detect_no_conflicts_declared() {
     // Renamed from test_registry_detect_no_conflicts to be clearer
@@ -1038,4 +1146,76 @@ fn test_registry_detect_no_conflicts_declared() {
             other_err => panic!("Expected PluginSystemError::UnresolvedPluginConflicts, got {:?}", other_err),
         }
     }
-} // Close mod registry_conflict_tests
\ No newline at end of file
+
+    fn priority_party(priority: PluginPriority, is_core: bool) -> ConflictParty {
+        ConflictParty { priority, is_core, explicitly_enabled: false }
+    }
+
+    #[test]
+    fn test_conflict_policy_parsing() {
+        assert_eq!(ConflictPolicy::from_str("prefer-core"), Ok(ConflictPolicy::PreferCore));
+        assert_eq!(ConflictPolicy::from_str("prefer:plugin-a"), Ok(ConflictPolicy::PreferPlugin("plugin-a".to_string())));
+        assert_eq!(ConflictPolicy::PreferExplicitlyEnabled.to_string(), "prefer-explicitly-enabled");
+        assert!(ConflictPolicy::from_str("prefer:").is_err());
+        assert!(ConflictPolicy::from_str("disable-all").is_err());
+    }
+
+    #[test]
+    fn test_apply_policies_uses_first_deciding_rule() {
+        let mut manager = ConflictManager::new();
+        manager.add_conflict(PluginConflict::new("core-a", "plugin-b", ConflictType::MutuallyExclusive, "exclusive"));
+        manager.add_conflict(PluginConflict::new("plugin-b", "plugin-c", ConflictType::ExplicitlyIncompatible, "incompatible"));
+        manager.add_conflict(PluginConflict::new("plugin-c", "plugin-d", ConflictType::PartialOverlap, "overlap"));
+        manager.add_conflict(PluginConflict::new("plugin-d", "plugin-e", ConflictType::MutuallyExclusive, "tie"));
+
+        let parties: HashMap<String, ConflictParty> = [
+            ("core-a", priority_party(PluginPriority::Core(60), true)),
+            ("plugin-b", priority_party(PluginPriority::ThirdParty(160), false)),
+            ("plugin-c", priority_party(PluginPriority::ThirdParty(160), false)),
+            ("plugin-d", priority_party(PluginPriority::ThirdParty(160), false)),
+            ("plugin-e", priority_party(PluginPriority::ThirdParty(160), false)),
+        ]
+        .into_iter()
+        .map(|(id, party)| (id.to_string(), party))
+        .collect();
+
+        // The core plugin is never the loser, so prefer:plugin-b does not decide the first conflict
+        let policies = [ConflictPolicy::PreferPlugin("plugin-b".to_string()), ConflictPolicy::PreferCore, ConflictPolicy::PreferHigherPriority];
+        let decisions = manager.apply_policies(&policies, &parties);
+
+        assert_eq!(decisions.len(), 2, "Decisions: {:?}", decisions);
+        assert_eq!(decisions[0].disabled_plugin(), Some("plugin-b"));
+        assert_eq!(decisions[0].policy, Some(ConflictPolicy::PreferCore));
+        assert_eq!(decisions[0].to_string(), "Disabled 'plugin-b' in favour of 'core-a' (prefer-core): exclusive");
+        assert_eq!(decisions[1].strategy, ResolutionStrategy::AllowWithWarning);
+
+        // plugin-b is already disabled, and the tie between equal plugins stays unresolved
+        assert_eq!(manager.get_conflicts()[1].resolution, Some(ResolutionStrategy::DisableFirst));
+        assert_eq!(manager.get_plugins_to_disable(), vec!["plugin-b".to_string()]);
+        let unresolved = manager.get_critical_unresolved_conflicts();
+        assert_eq!(unresolved.len(), 1);
+        assert_eq!(unresolved[0].description, "tie");
+    }
+
+    #[tokio::test]
+    async fn test_apply_conflict_policies_lets_initialization_proceed() {
+        let mut plugin_a = MockPlugin::new("plugin-a-policy", "1.0.0").conflicts_with(&["plugin-b-policy"]);
+        plugin_a.priority = PluginPriority::ThirdPartyHigh(110);
+        let plugin_b = MockPlugin::new("plugin-b-policy", "1.0.0");
+        let mut registry = create_registry_with_plugins(vec![plugin_a, plugin_b]);
+        let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+
+        let explicitly_enabled: HashSet<String> = HashSet::from(["plugin-b-policy".to_string()]);
+        let decisions = registry
+            .apply_conflict_policies(&[ConflictPolicy::PreferHigherPriority], &explicitly_enabled, &stage_registry)
+            .await
+            .unwrap();
+        assert_eq!(decisions.len(), 1);
+        assert_eq!(decisions[0].kept_plugin(), Some("plugin-a-policy"));
+        assert!(!registry.is_enabled("plugin-b-policy"));
+
+        let mut app = Application::new().unwrap();
+        registry.initialize_all(&mut app, &stage_registry).await.expect("No critical conflicts should remain");
+        assert!(registry.initialized_count() >= 1);
+    }
+} // Close mod registry_conflict_tests
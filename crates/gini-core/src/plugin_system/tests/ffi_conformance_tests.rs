@@ -0,0 +1,327 @@
+// Conformance tests for the dynamic plugin FFI boundary.
+//
+// Every library in `CONFORMANCE_PLUGINS` is checked against the contract documented in
+// `plugin_system::ffi_host`: each VTable function is called directly, every returned
+// string/slice is handed back to its free function, and the VTable is torn down the way
+// the host does it. Libraries that have not been built are skipped.
+
+use std::env;
+use std::ffi::{CStr, c_void};
+use std::os::raw::c_char;
+use std::path::PathBuf;
+use std::ptr;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use libloading::{Library, Symbol};
+use semver::Version;
+use tokio::sync::Mutex;
+
+use crate::kernel::bootstrap::Application;
+use crate::kernel::constants;
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::ffi_host::{self, PLUGIN_ABI_SYMBOL, PLUGIN_INIT_SYMBOL, PluginInitFn};
+use crate::plugin_system::traits::{
+    FfiResult, FfiSlice, PluginAbiDescriptor, PluginVTable, PLUGIN_ABI_VERSION, PLUGIN_CAP_PREFLIGHT_CHECK,
+    PLUGIN_CAP_REGISTER_STAGES, PLUGIN_CAP_SHUTDOWN, PLUGIN_VTABLE_REQUIRED_SIZE,
+};
+use crate::plugin_system::version::VersionRange;
+use crate::stage_manager::context::StageContext;
+use crate::stage_manager::registry::StageRegistry;
+
+/// A plugin library the conformance suite runs against.
+struct ConformancePlugin {
+    /// File name of the built library in `target/debug`
+    library_file: &'static str,
+    /// Name the plugin is expected to report
+    expected_name: &'static str,
+}
+
+const CONFORMANCE_PLUGINS: &[ConformancePlugin] = &[
+    // Hand-written FFI functions
+    ConformancePlugin {
+        library_file: "libcompat_check_example.so",
+        expected_name: "CompatCheckExample",
+    },
+    // Generated by #[gini_plugin]
+    ConformancePlugin {
+        library_file: "libmacro_plugin_example.so",
+        expected_name: "MacroExample",
+    },
+];
+
+// Helper function to find a compiled plugin library
+// Same search locations as loading_tests.rs
+fn find_plugin_library(library_file: &str) -> Option<PathBuf> {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+    let search_paths = vec![
+        current_dir.join("../../target/debug").join(library_file),
+        current_dir.join("target/debug").join(library_file),
+        PathBuf::from("./target/debug").join(library_file),
+    ];
+    search_paths.into_iter().find(|path| path.exists())
+}
+
+/// Copies a plugin-owned string, then frees it through the VTable.
+unsafe fn take_string(ptr: *const c_char, free_fn: extern "C" fn(*mut c_char), what: &str) -> String {
+    assert!(!ptr.is_null(), "{} returned a null pointer", what);
+    let value = unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .unwrap_or_else(|_| panic!("{} is not valid UTF-8", what))
+        .to_owned();
+    free_fn(ptr as *mut c_char);
+    value
+}
+
+/// Converts every element of a plugin-owned slice, then frees it through the VTable.
+/// Strings inside the slice are owned by it, so they must be copied before the free call.
+unsafe fn take_slice<T: Copy, R>(slice: FfiSlice<T>, free_fn: extern "C" fn(FfiSlice<T>), convert: impl Fn(T) -> R) -> Vec<R> {
+    let items = unsafe { slice.as_slice() }
+        .map(|items| items.iter().map(|&item| convert(item)).collect())
+        .unwrap_or_default();
+    free_fn(slice);
+    items
+}
+
+unsafe fn c_str(ptr: *const c_char, what: &str) -> String {
+    assert!(!ptr.is_null(), "{} contains a null string", what);
+    unsafe { CStr::from_ptr(ptr) }
+        .to_str()
+        .unwrap_or_else(|_| panic!("{} contains invalid UTF-8", what))
+        .to_owned()
+}
+
+/// Runs every VTable function of one plugin library against the ownership contract.
+fn check_vtable_conformance(case: &ConformancePlugin, library_path: &PathBuf) {
+    let library = unsafe { Library::new(library_path) }.expect("Failed to open plugin library");
+    let init_fn: PluginInitFn = {
+        let symbol: Symbol<PluginInitFn> = unsafe { library.get(PLUGIN_INIT_SYMBOL) }.expect("Missing _plugin_init symbol");
+        *symbol
+    };
+
+    // --- ABI handshake ---
+    let descriptor: PluginAbiDescriptor = {
+        let symbol: Symbol<*const PluginAbiDescriptor> = unsafe { library.get(PLUGIN_ABI_SYMBOL) }.expect("Missing GINI_PLUGIN_ABI symbol");
+        unsafe { ptr::read_unaligned(*symbol) }
+    };
+    ffi_host::check_abi_descriptor(&descriptor, case.library_file, Some(library_path)).expect("ABI descriptor rejected");
+    // Plugins built from this tree must describe this tree's VTable exactly
+    assert_eq!(descriptor, PluginAbiDescriptor::current(descriptor.capabilities));
+
+    let vtable_ptr = unsafe { init_fn() };
+    assert!(!vtable_ptr.is_null(), "{}: _plugin_init returned a null VTable", case.library_file);
+    let vtable: &PluginVTable = unsafe { &*vtable_ptr };
+    assert!(!vtable.instance.is_null(), "{}: VTable has a null instance", case.library_file);
+    let instance = vtable.instance as *const c_void;
+
+    // --- Metadata ---
+    let name = unsafe { take_string((vtable.name)(instance), vtable.free_name, "name") };
+    assert_eq!(name, case.expected_name);
+
+    let version = unsafe { take_string((vtable.version)(instance), vtable.free_version, "version") };
+    assert!(Version::parse(&version).is_ok(), "{}: version '{}' is not semver", name, version);
+
+    let _is_core = (vtable.is_core)(instance);
+    let priority = (vtable.priority)(instance);
+    assert!(priority.to_plugin_priority().is_some(), "{}: invalid priority {:?}", name, priority);
+
+    // Calling a getter twice must hand out independent allocations
+    let first = (vtable.name)(instance);
+    let second = (vtable.name)(instance);
+    (vtable.free_name)(first as *mut c_char);
+    assert_eq!(unsafe { c_str(second, "name") }, case.expected_name);
+    (vtable.free_name)(second as *mut c_char);
+
+    // --- Slices ---
+    let api_versions: Vec<VersionRange> = unsafe {
+        take_slice((vtable.compatible_api_versions)(instance), vtable.free_compatible_api_versions, |range| {
+            VersionRange::from_str(&c_str(range.constraint, "compatible_api_versions")).expect("Invalid API version constraint")
+        })
+    };
+    let api_version = Version::parse(constants::API_VERSION).unwrap();
+    assert!(
+        api_versions.iter().any(|range| range.includes(&api_version)),
+        "{}: no compatible_api_versions entry matches API {}",
+        name,
+        constants::API_VERSION
+    );
+
+    unsafe {
+        take_slice((vtable.dependencies)(instance), vtable.free_dependencies, |dep| c_str(dep.plugin_name, "dependencies"));
+        take_slice((vtable.required_stages)(instance), vtable.free_required_stages, |req| c_str(req.stage_id, "required_stages"));
+        take_slice((vtable.conflicts_with)(instance), vtable.free_conflicts_with, |conflict| c_str(conflict, "conflicts_with"));
+        take_slice((vtable.incompatible_with)(instance), vtable.free_incompatible_with, |dep| c_str(dep.plugin_name, "incompatible_with"));
+    }
+
+    // --- Free functions must accept null/empty values ---
+    (vtable.free_name)(ptr::null_mut());
+    (vtable.free_version)(ptr::null_mut());
+    (vtable.free_compatible_api_versions)(FfiSlice { ptr: ptr::null(), len: 0 });
+    (vtable.free_dependencies)(FfiSlice { ptr: ptr::null(), len: 0 });
+    (vtable.free_required_stages)(FfiSlice { ptr: ptr::null(), len: 0 });
+    (vtable.free_conflicts_with)(FfiSlice { ptr: ptr::null(), len: 0 });
+    (vtable.free_incompatible_with)(FfiSlice { ptr: ptr::null(), len: 0 });
+
+    // --- Lifecycle ---
+    let mut app = Application::new().expect("Failed to create Application");
+    let result = (vtable.init)(vtable.instance, &mut app as *mut _ as *mut c_void);
+    assert_eq!(result, FfiResult::Ok, "{}: init failed", name);
+
+    // Optional entries must be present exactly when their capability bit is set
+    for (capability, present) in [
+        (PLUGIN_CAP_PREFLIGHT_CHECK, vtable.preflight_check.is_some()),
+        (PLUGIN_CAP_REGISTER_STAGES, vtable.register_stages.is_some()),
+        (PLUGIN_CAP_SHUTDOWN, vtable.shutdown.is_some()),
+    ] {
+        assert_eq!(descriptor.has_capability(capability), present, "{}: capability {:#x} does not match its VTable entry", name, capability);
+    }
+
+    if let Some(preflight_fn) = vtable.preflight_check {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let context = StageContext::new_dry_run(tmp_dir.path().to_path_buf());
+        let result = preflight_fn(instance, &context as *const _ as *const c_void);
+        assert_eq!(result, FfiResult::Ok, "{}: preflight_check failed", name);
+    }
+
+    if let Some(register_fn) = vtable.register_stages {
+        let mut stage_registry = StageRegistry::new();
+        let result = register_fn(instance, &mut stage_registry as *mut _ as *mut c_void);
+        assert_eq!(result, FfiResult::Ok, "{}: register_stages failed", name);
+    }
+
+    if let Some(shutdown_fn) = vtable.shutdown {
+        let result = shutdown_fn(vtable.instance);
+        assert_eq!(result, FfiResult::Ok, "{}: shutdown failed", name);
+    }
+
+    // --- Teardown, in the order the host uses ---
+    (vtable.destroy)(vtable.instance);
+    (vtable.free_vtable)(vtable_ptr);
+    drop(library);
+}
+
+#[test]
+fn test_vtable_conformance() {
+    let mut checked = 0;
+    for case in CONFORMANCE_PLUGINS {
+        let Some(library_path) = find_plugin_library(case.library_file) else {
+            println!("Skipping FFI conformance for {}: library not built", case.library_file);
+            continue;
+        };
+        println!("Checking FFI conformance of {:?}", library_path);
+        check_vtable_conformance(case, &library_path);
+        checked += 1;
+    }
+    println!("FFI conformance checked for {} plugin(s)", checked);
+}
+
+#[tokio::test]
+async fn test_ffi_host_wrapper_conformance() {
+    for case in CONFORMANCE_PLUGINS {
+        let Some(library_path) = find_plugin_library(case.library_file) else {
+            println!("Skipping FFI host conformance for {}: library not built", case.library_file);
+            continue;
+        };
+
+        let plugin = ffi_host::load_plugin_library(&library_path).expect("ffi_host failed to load plugin");
+        assert_eq!(plugin.name(), case.expected_name);
+        assert!(Version::parse(plugin.version()).is_ok());
+        let api_version = Version::parse(constants::API_VERSION).unwrap();
+        assert!(plugin.compatible_api_versions().iter().any(|range| range.includes(&api_version)));
+
+        // Metadata getters can be called repeatedly; every call allocates and frees its own copy
+        for _ in 0..3 {
+            let _ = plugin.dependencies();
+            let _ = plugin.required_stages();
+            let _ = plugin.conflicts_with();
+            let _ = plugin.incompatible_with();
+            let _ = plugin.compatible_api_versions();
+        }
+
+        let mut app = Application::new().expect("Failed to create Application");
+        plugin.init(&mut app).expect("init failed");
+
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let context = StageContext::new_dry_run(tmp_dir.path().to_path_buf());
+        plugin.preflight_check(&context).await.expect("preflight_check failed");
+
+        let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+        plugin.register_stages(&mut *stage_registry.lock().await).expect("register_stages failed");
+        plugin.shutdown().expect("shutdown failed");
+
+        // Dropping the wrapper destroys the instance, frees the VTable and closes the library
+        drop(plugin);
+    }
+}
+
+#[test]
+fn test_ffi_host_reports_missing_library() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let missing = tmp_dir.path().join("libdoes_not_exist.so");
+    let err = ffi_host::load_plugin_library(&missing).err().expect("Loading a missing library should fail");
+    assert!(err.to_string().contains("libloading error"), "Unexpected error: {}", err);
+}
+
+#[test]
+fn test_abi_descriptor_checks() {
+    let path = PathBuf::from("/plugins/libexample.so");
+    let current = PluginAbiDescriptor::current(PLUGIN_CAP_SHUTDOWN);
+    assert!(ffi_host::check_abi_descriptor(&current, "example", Some(&path)).is_ok());
+
+    // A different ABI version is rejected
+    let wrong_version = PluginAbiDescriptor { abi_version: PLUGIN_ABI_VERSION + 1, ..current };
+    match ffi_host::check_abi_descriptor(&wrong_version, "example", Some(&path)) {
+        Err(PluginSystemError::AbiMismatch { plugin_id, path: err_path, message }) => {
+            assert_eq!(plugin_id, "example");
+            assert_eq!(err_path, Some(path.clone()));
+            assert!(message.contains(&format!("ABI version {}", PLUGIN_ABI_VERSION + 1)), "Unexpected message: {}", message);
+        }
+        other => panic!("Expected AbiMismatch, got {:?}", other),
+    }
+
+    // A VTable missing required entries is rejected
+    let too_small = PluginAbiDescriptor { vtable_size: PLUGIN_VTABLE_REQUIRED_SIZE - std::mem::size_of::<usize>(), ..current };
+    assert!(matches!(
+        ffi_host::check_abi_descriptor(&too_small, "example", None),
+        Err(PluginSystemError::AbiMismatch { .. })
+    ));
+
+    // An older plugin without the optional entries, and a newer one with extra entries and
+    // capabilities this host does not know, are both accepted
+    let older = PluginAbiDescriptor { vtable_size: PLUGIN_VTABLE_REQUIRED_SIZE, capabilities: 0, ..current };
+    assert!(ffi_host::check_abi_descriptor(&older, "example", None).is_ok());
+    let newer = PluginAbiDescriptor {
+        vtable_size: std::mem::size_of::<PluginVTable>() + 2 * std::mem::size_of::<usize>(),
+        capabilities: current.capabilities | (1 << 40),
+        ..current
+    };
+    assert!(ffi_host::check_abi_descriptor(&newer, "example", None).is_ok());
+}
+
+#[tokio::test]
+async fn test_macro_plugin_metadata_round_trip() {
+    let Some(library_path) = find_plugin_library("libmacro_plugin_example.so") else {
+        println!("Skipping macro plugin round trip: library not built");
+        return;
+    };
+    let plugin = ffi_host::load_plugin_library(&library_path).expect("ffi_host failed to load macro plugin");
+
+    assert_eq!(plugin.priority(), crate::plugin_system::traits::PluginPriority::ThirdParty(160));
+    let dependencies = plugin.dependencies();
+    assert_eq!(dependencies.len(), 1);
+    assert_eq!(dependencies[0].plugin_name, "core-logging");
+    assert!(!dependencies[0].required);
+    assert_eq!(dependencies[0].version_range.as_ref().map(|r| r.constraint_string()), Some(">=0.1.0"));
+    assert_eq!(plugin.conflicts_with(), vec!["legacy-macro-example".to_string()]);
+    let incompatible = plugin.incompatible_with();
+    assert_eq!(incompatible.len(), 1);
+    assert_eq!(incompatible[0].plugin_name, "old-macro-example");
+    let stages = plugin.required_stages();
+    assert_eq!(stages.len(), 1);
+    assert_eq!(stages[0].stage_id, "core::plugin_preflight_check");
+    assert!(!stages[0].required);
+
+    // The async preflight check runs inside the plugin, not as a host-side stub
+    let tmp_dir = tempfile::tempdir().unwrap();
+    plugin.preflight_check(&StageContext::new_dry_run(tmp_dir.path().to_path_buf())).await.expect("preflight_check failed");
+}
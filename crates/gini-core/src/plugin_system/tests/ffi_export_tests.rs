@@ -0,0 +1,155 @@
+// In-process tests for the plugin side of the FFI boundary (`ffi_export`).
+// The exported library path is covered by `ffi_conformance_tests` via the macro example plugin.
+
+use std::ffi::{CStr, c_void};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use async_trait::async_trait;
+
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::dependency::PluginDependency;
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::ffi_export;
+use crate::plugin_system::traits::{FfiResult, Plugin, PluginPriority};
+use crate::plugin_system::version::VersionRange;
+use crate::stage_manager::context::StageContext;
+use crate::stage_manager::registry::StageRegistry;
+use crate::stage_manager::requirement::StageRequirement;
+
+struct ExportTestPlugin {
+    fail_preflight: bool,
+    panic_in_version: bool,
+    dropped: Arc<AtomicBool>, // Set when the instance is destroyed
+}
+
+impl ExportTestPlugin {
+    fn new(dropped: Arc<AtomicBool>) -> Self {
+        Self { fail_preflight: false, panic_in_version: false, dropped }
+    }
+}
+
+impl Drop for ExportTestPlugin {
+    fn drop(&mut self) {
+        self.dropped.store(true, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl Plugin for ExportTestPlugin {
+    fn name(&self) -> &'static str { "ExportTestPlugin" }
+    fn version(&self) -> &str {
+        if self.panic_in_version {
+            panic!("version panicked");
+        }
+        "1.2.3"
+    }
+    fn is_core(&self) -> bool { true }
+    fn priority(&self) -> PluginPriority { PluginPriority::Core(70) }
+    fn compatible_api_versions(&self) -> Vec<VersionRange> { vec![">=0.1.0".parse().unwrap()] }
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        vec![
+            PluginDependency::required("base", VersionRange::from_constraint("^1.0").unwrap()),
+            PluginDependency::optional_any("extra"),
+        ]
+    }
+    fn required_stages(&self) -> Vec<StageRequirement> { vec![StageRequirement::require("some::stage")] }
+    fn conflicts_with(&self) -> Vec<String> { vec!["rival".to_string()] }
+    fn incompatible_with(&self) -> Vec<PluginDependency> { vec![] }
+    fn init(&self, _app: &mut Application) -> std::result::Result<(), PluginSystemError> { Ok(()) }
+    async fn preflight_check(&self, _context: &StageContext) -> std::result::Result<(), PluginSystemError> {
+        tokio::task::yield_now().await;
+        if self.fail_preflight {
+            return Err(PluginSystemError::PreflightCheckFailed {
+                plugin_id: self.name().to_string(),
+                message: "Simulated preflight failure".to_string(),
+            });
+        }
+        Ok(())
+    }
+    fn register_stages(&self, _registry: &mut StageRegistry) -> std::result::Result<(), PluginSystemError> { Ok(()) }
+    fn shutdown(&self) -> std::result::Result<(), PluginSystemError> { Ok(()) }
+}
+
+#[test]
+fn test_exported_vtable_metadata() {
+    let dropped = Arc::new(AtomicBool::new(false));
+    let vtable = ffi_export::build_vtable(ExportTestPlugin::new(dropped.clone()));
+    let instance = vtable.instance as *const c_void;
+
+    let name_ptr = (vtable.name)(instance);
+    assert_eq!(unsafe { CStr::from_ptr(name_ptr) }.to_str().unwrap(), "ExportTestPlugin");
+    (vtable.free_name)(name_ptr as *mut _);
+
+    assert!((vtable.is_core)(instance));
+    assert_eq!((vtable.priority)(instance).to_plugin_priority(), Some(PluginPriority::Core(70)));
+
+    let deps = (vtable.dependencies)(instance);
+    {
+        let items = unsafe { deps.as_slice() }.expect("dependencies should not be empty");
+        assert_eq!(items.len(), 2);
+        assert_eq!(unsafe { CStr::from_ptr(items[0].plugin_name) }.to_str().unwrap(), "base");
+        assert_eq!(unsafe { CStr::from_ptr(items[0].version_constraint) }.to_str().unwrap(), "^1.0");
+        assert!(items[0].required);
+        assert!(items[1].version_constraint.is_null(), "Any-version dependency should have no constraint");
+        assert!(!items[1].required);
+    }
+    (vtable.free_dependencies)(deps);
+
+    // Empty lists are handed out as null slices
+    let incompatible = (vtable.incompatible_with)(instance);
+    assert!(incompatible.ptr.is_null());
+    assert_eq!(incompatible.len, 0);
+    (vtable.free_incompatible_with)(incompatible);
+
+    (vtable.destroy)(vtable.instance);
+    assert!(dropped.load(Ordering::SeqCst), "destroy should drop the plugin instance");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_exported_lifecycle_results() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let context = StageContext::new_dry_run(tmp_dir.path().to_path_buf());
+    let context_ptr = &context as *const _ as *const c_void;
+
+    // The async preflight check runs to completion even when called from inside a runtime
+    let passing = ffi_export::build_vtable(ExportTestPlugin::new(Arc::new(AtomicBool::new(false))));
+    let preflight = passing.preflight_check.expect("exported VTables provide preflight_check");
+    assert_eq!(preflight(passing.instance, context_ptr), FfiResult::Ok);
+    assert_eq!(preflight(passing.instance, std::ptr::null()), FfiResult::NullPointer);
+    (passing.destroy)(passing.instance);
+
+    let mut failing_plugin = ExportTestPlugin::new(Arc::new(AtomicBool::new(false)));
+    failing_plugin.fail_preflight = true;
+    let failing = ffi_export::build_vtable(failing_plugin);
+    let preflight = failing.preflight_check.unwrap();
+    assert_eq!(preflight(failing.instance, context_ptr), FfiResult::Err);
+    (failing.destroy)(failing.instance);
+}
+
+#[test]
+fn test_exported_getter_panic_is_contained() {
+    let mut plugin = ExportTestPlugin::new(Arc::new(AtomicBool::new(false)));
+    plugin.panic_in_version = true;
+    let vtable = ffi_export::build_vtable(plugin);
+
+    // The panic stays on the plugin side; the host sees a null string
+    let version_ptr = (vtable.version)(vtable.instance);
+    assert!(version_ptr.is_null());
+    (vtable.free_version)(version_ptr as *mut _);
+    (vtable.destroy)(vtable.instance);
+}
+
+#[test]
+fn test_exported_vtable_frees_itself() {
+    let dropped = Arc::new(AtomicBool::new(false));
+    let vtable_ptr = ffi_export::plugin_init(|| ExportTestPlugin::new(dropped.clone()));
+    assert!(!vtable_ptr.is_null());
+
+    // Teardown in the host's order: the instance first, then the VTable allocation
+    let vtable = unsafe { &*vtable_ptr };
+    let (destroy, free_vtable, instance) = (vtable.destroy, vtable.free_vtable, vtable.instance);
+    destroy(instance);
+    assert!(dropped.load(Ordering::SeqCst));
+    free_vtable(vtable_ptr);
+}
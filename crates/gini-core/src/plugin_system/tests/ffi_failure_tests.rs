@@ -178,4 +178,28 @@ async fn test_load_invalid_vtable_plugin() {
         }
         other_error => panic!("Expected KernelError::PluginSystem(PluginSystemError::LoadingError) for Invalid VTable, got {:?}", other_error),
     }
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn test_load_abi_mismatch_plugin() {
+    let plugin_loader = create_plugin_loader();
+    let plugin_crate_name = "abi_mismatch_plugin";
+    let (so_path, lib_filename, _target_dir) = compile_test_plugin(plugin_crate_name, "tests/test_plugins/failing_ffi/abi_mismatch_plugin")
+        .expect("Failed to compile abi_mismatch_plugin");
+
+    let manifest = create_test_manifest(plugin_crate_name, &lib_filename, so_path.parent().unwrap().to_path_buf());
+    let result = plugin_loader.load_plugin(&manifest).await;
+
+    if result.is_ok() {
+        panic!("Expected loading to fail due to ABI mismatch for plugin '{}', but it succeeded.", manifest.id);
+    }
+    match result.err().unwrap() {
+        // Rejected during the handshake, before `_plugin_init` is called
+        KernelError::PluginSystem(PluginSystemError::AbiMismatch { plugin_id, path, message }) => {
+            assert!(plugin_id.contains(plugin_crate_name), "Expected plugin_id ('{}') to contain crate name ('{}')", plugin_id, plugin_crate_name);
+            assert_eq!(path.as_deref(), Some(so_path.as_path()));
+            assert!(message.contains("ABI version 0"), "Unexpected error message for ABI mismatch: {}", message);
+        }
+        other_error => panic!("Expected KernelError::PluginSystem(PluginSystemError::AbiMismatch), got {:?}", other_error),
+    }
+}
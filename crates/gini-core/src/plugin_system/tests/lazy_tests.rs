@@ -0,0 +1,141 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use tempfile::tempdir;
+use tokio::sync::Mutex;
+
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::conflict;
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::lazy::LazyPlugin;
+use crate::plugin_system::manifest::{ManifestBuilder, ResourceAccessType};
+use crate::plugin_system::registry::PluginRegistry;
+use crate::plugin_system::tests::manifest_in;
+use crate::plugin_system::traits::{Plugin, PluginPriority};
+use crate::plugin_system::version::{ApiVersion, VersionRange};
+use crate::stage_manager::registry::StageRegistry;
+
+// Helper function to find the path to the compiled example plugin
+// Copied from loading_tests.rs
+fn get_example_plugin_path() -> Option<PathBuf> {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+    let plugin_name = "libcompat_check_example.so";
+    let search_paths = vec![
+        current_dir.join("../../target/debug").join(plugin_name),
+        current_dir.join("target/debug").join(plugin_name),
+        PathBuf::from("./target/debug").join(plugin_name),
+    ];
+    search_paths.into_iter().find(|path| path.exists())
+}
+
+#[test]
+fn test_lazy_plugin_metadata_comes_from_manifest() {
+    let manifest = ManifestBuilder::new("lazy_meta", "Lazy Meta", "2.3.4")
+        .api_version(VersionRange::from_str("^0.1").unwrap())
+        .dependency("base_plugin", Some(VersionRange::from_str(">=1.0.0").unwrap()), true)
+        .conflict("rival_plugin")
+        .incompatibility("old_plugin", None)
+        .priority(PluginPriority::Core(60))
+        .core(true)
+        .resource("network_port", "8080", ResourceAccessType::ExclusiveWrite)
+        .build();
+
+    let plugin = LazyPlugin::new(manifest);
+    assert_eq!(plugin.name(), "lazy_meta");
+    assert_eq!(plugin.version(), "2.3.4");
+    assert!(plugin.is_core());
+    assert_eq!(plugin.priority(), PluginPriority::Core(60));
+    assert_eq!(plugin.compatible_api_versions().len(), 1);
+    assert_eq!(plugin.dependencies()[0].plugin_name, "base_plugin");
+    assert_eq!(plugin.conflicts_with(), vec!["rival_plugin".to_string()]);
+    assert_eq!(plugin.incompatible_with()[0].plugin_name, "old_plugin");
+
+    let resources = plugin.declared_resources();
+    assert_eq!(resources.len(), 1);
+    assert_eq!(resources[0].resource.kind, "network_port");
+    assert_eq!(resources[0].resource.id, "8080");
+    assert_eq!(resources[0].access_type, conflict::ResourceAccessType::ExclusiveWrite);
+
+    assert!(!plugin.is_loaded(), "Reading metadata must not load the library");
+    assert!(plugin.shutdown().is_ok(), "Shutting down an unloaded plugin is a no-op");
+}
+
+#[tokio::test]
+async fn test_broken_library_only_fails_on_init() {
+    let tmp_dir = tempdir().unwrap();
+    fs::write(tmp_dir.path().join("libbroken.so"), b"definitely not a shared library").unwrap();
+    let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+
+    let mut registry = PluginRegistry::new(ApiVersion::from_str("0.1.0").unwrap());
+    registry.register_manifest(manifest_in(tmp_dir.path(), "broken", "libbroken.so")).unwrap();
+    assert!(registry.has_plugin("broken"));
+    assert!(registry.get_manifest("broken").is_some());
+    assert!(!registry.is_plugin_code_loaded("broken"));
+
+    // Disabling and re-enabling never touch the library
+    registry.disable_plugin("broken", &stage_registry).await.unwrap();
+    registry.enable_plugin("broken").unwrap();
+
+    let mut app = Application::new().expect("Failed to create Application");
+    let result = registry.initialize_plugin("broken", &mut app, &stage_registry).await;
+    assert!(result.is_err(), "Initializing a plugin with a broken library should fail");
+    assert!(!registry.is_initialized("broken"));
+    assert!(!registry.is_plugin_code_loaded("broken"));
+}
+
+#[test]
+fn test_lazy_plugin_rejects_unsafe_entry_point() {
+    let plugin = LazyPlugin::new(manifest_in(Path::new("/tmp"), "escaper", "../libescaper.so"));
+    match plugin.load() {
+        Err(PluginSystemError::LoadingError { plugin_id, .. }) => assert_eq!(plugin_id, "escaper"),
+        Err(other) => panic!("Expected LoadingError, got {:?}", other),
+        Ok(_) => panic!("Entry points outside the plugin directory must be rejected"),
+    }
+}
+
+#[tokio::test]
+async fn test_lazy_plugin_loads_library_on_init() {
+    let example_plugin_src = match get_example_plugin_path() {
+        Some(path) => path,
+        None => {
+            println!("Skipping test: Could not find the compiled example plugin.");
+            return;
+        }
+    };
+    let tmp_dir = tempdir().unwrap();
+    fs::copy(&example_plugin_src, tmp_dir.path().join("libcompat_check_example.so")).unwrap();
+    let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+
+    let mut registry = PluginRegistry::new(ApiVersion::from_str("0.1.0").unwrap());
+    registry
+        .register_manifest(manifest_in(tmp_dir.path(), "CompatCheckExample", "libcompat_check_example.so"))
+        .unwrap();
+    assert!(!registry.is_plugin_code_loaded("CompatCheckExample"));
+
+    let mut app = Application::new().expect("Failed to create Application");
+    registry.initialize_plugin("CompatCheckExample", &mut app, &stage_registry).await.unwrap();
+    assert!(registry.is_plugin_code_loaded("CompatCheckExample"), "Initializing should load the library");
+    assert!(registry.is_initialized("CompatCheckExample"));
+
+    registry.disable_plugin("CompatCheckExample", &stage_registry).await.unwrap();
+}
+
+#[test]
+fn test_lazy_plugin_rejects_mismatched_library_id() {
+    let example_plugin_src = match get_example_plugin_path() {
+        Some(path) => path,
+        None => {
+            println!("Skipping test: Could not find the compiled example plugin.");
+            return;
+        }
+    };
+    let tmp_dir = tempdir().unwrap();
+    fs::copy(&example_plugin_src, tmp_dir.path().join("libcompat_check_example.so")).unwrap();
+
+    let plugin = LazyPlugin::new(manifest_in(tmp_dir.path(), "not_compat_check", "libcompat_check_example.so"));
+    assert!(plugin.load().is_err(), "A library reporting a different plugin ID should be rejected");
+    assert!(!plugin.is_loaded());
+}
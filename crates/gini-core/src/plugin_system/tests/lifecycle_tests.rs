@@ -0,0 +1,148 @@
+use std::sync::{Arc, Mutex as StdMutex};
+
+use tokio::sync::Mutex;
+
+use crate::event::{DefaultEventManager, EventManager, EventResult, SystemEvent};
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::lifecycle::{PluginLifecycle, PluginState};
+use crate::plugin_system::tests::{registry_with, TestPlugin};
+use crate::stage_manager::registry::StageRegistry;
+
+#[test]
+fn test_state_transitions() {
+    use PluginState::*;
+    assert!(Discovered.can_transition_to(Loaded));
+    assert!(Loaded.can_transition_to(Resolved));
+    assert!(Resolved.can_transition_to(PreflightPassed));
+    assert!(PreflightPassed.can_transition_to(Initialized));
+    assert!(Initialized.can_transition_to(Running));
+    assert!(Running.can_transition_to(Loaded), "Shutting down keeps the code loaded");
+    assert!(Running.can_transition_to(Failed));
+    assert!(Failed.can_transition_to(Disabled));
+    assert!(Disabled.can_transition_to(Loaded));
+
+    assert!(!Loaded.can_transition_to(Initialized), "Dependencies must be resolved first");
+    assert!(!Discovered.can_transition_to(Running));
+    assert!(!Disabled.can_transition_to(Initialized), "Disabled plugins must be enabled first");
+    assert!(!Disabled.can_transition_to(Failed));
+    assert!(!Unloaded.can_transition_to(Disabled));
+    assert!(!Unloaded.can_transition_to(Unloaded));
+
+    assert!(Running.is_initialized() && Initialized.is_initialized());
+    assert!(!PreflightPassed.is_initialized());
+    assert!(Failed.is_enabled());
+    assert!(!Disabled.is_enabled() && !Unloaded.is_enabled());
+}
+
+#[test]
+fn test_invalid_transition_is_rejected() {
+    let lifecycle = PluginLifecycle::new();
+    lifecycle.register("plugin", PluginState::Loaded, "Registered").unwrap();
+
+    match lifecycle.transition("plugin", PluginState::Running, "Skipping ahead") {
+        Err(PluginSystemError::InvalidStateTransition { plugin_id, from, to }) => {
+            assert_eq!(plugin_id, "plugin");
+            assert_eq!(from, "Loaded");
+            assert_eq!(to, "Running");
+        }
+        other => panic!("Expected InvalidStateTransition, got {:?}", other),
+    }
+    assert_eq!(lifecycle.state("plugin"), Some(PluginState::Loaded));
+    assert!(lifecycle.transition("unknown", PluginState::Resolved, "Never registered").is_err());
+}
+
+#[test]
+fn test_status_keeps_reason_error_and_history() {
+    let lifecycle = PluginLifecycle::new();
+    lifecycle.register("plugin", PluginState::Loaded, "Registered").unwrap();
+    lifecycle.transition("plugin", PluginState::Resolved, "Dependencies satisfied").unwrap();
+    lifecycle.fail("plugin", "init exploded");
+
+    let status = lifecycle.status("plugin").expect("Status of a registered plugin");
+    assert_eq!(status.state, PluginState::Failed);
+    assert_eq!(status.reason, "init exploded");
+    assert_eq!(status.last_error.as_deref(), Some("init exploded"));
+    let states: Vec<_> = status.history.iter().map(|t| (t.from, t.to)).collect();
+    assert_eq!(states, vec![
+        (None, PluginState::Loaded),
+        (Some(PluginState::Loaded), PluginState::Resolved),
+        (Some(PluginState::Resolved), PluginState::Failed),
+    ]);
+
+    // Disabling keeps the error that explains it
+    lifecycle.transition("plugin", PluginState::Disabled, "Disabled by the user").unwrap();
+    lifecycle.fail("plugin", "shutdown exploded");
+    let status = lifecycle.status("plugin").unwrap();
+    assert_eq!(status.state, PluginState::Disabled);
+    assert_eq!(status.last_error.as_deref(), Some("shutdown exploded"));
+
+    let text = status.to_string();
+    assert!(text.contains("Plugin 'plugin': Disabled (Disabled by the user)"), "{}", text);
+    assert!(text.contains("last error: shutdown exploded"), "{}", text);
+    assert!(text.contains("Resolved -> Failed: init exploded"), "{}", text);
+}
+
+#[tokio::test]
+async fn test_transitions_emit_state_change_events() {
+    let lifecycle = PluginLifecycle::new();
+    let event_manager = Arc::new(DefaultEventManager::new());
+    let seen = Arc::new(StdMutex::new(Vec::new()));
+    let seen_clone = seen.clone();
+    event_manager
+        .register_sync_handler("plugin.state_changed", move |event| {
+            if let Some(SystemEvent::PluginStateChanged { plugin_id, from, to, .. }) = event.as_any().downcast_ref::<SystemEvent>() {
+                seen_clone.lock().unwrap().push(format!("{}: {} -> {}", plugin_id, from, to));
+            }
+            EventResult::Continue
+        })
+        .await;
+    lifecycle.set_event_manager(event_manager.clone());
+
+    lifecycle.register("plugin", PluginState::Loaded, "Registered").unwrap();
+    lifecycle.transition("plugin", PluginState::Resolved, "Dependencies satisfied").unwrap();
+    lifecycle.fail("plugin", "init exploded");
+    assert!(lifecycle.transition("plugin", PluginState::Running, "Invalid").is_err());
+
+    tokio::task::yield_now().await; // Let the state change events be queued
+    event_manager.process_queue().await;
+    let mut seen = seen.lock().unwrap().clone();
+    seen.sort();
+    assert_eq!(seen, vec!["plugin: Loaded -> Resolved", "plugin: Resolved -> Failed"]);
+}
+
+#[tokio::test]
+async fn test_registry_records_plugin_states() {
+    let mut registry = registry_with([
+        TestPlugin::new("base"),
+        TestPlugin::new("broken").failing_init(),
+        TestPlugin::new("unused"),
+    ]);
+    let lifecycle = registry.lifecycle().clone();
+    assert_eq!(lifecycle.state("base"), Some(PluginState::Loaded));
+
+    let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+    registry.disable_plugin_with_reason("unused", "Not needed", &stage_registry).await.unwrap();
+    let mut app = Application::new().unwrap();
+    let report = registry.initialize_all(&mut app, &stage_registry).await.unwrap();
+    assert_eq!(report.failed.len(), 1, "The failing plugin is isolated");
+
+    assert_eq!(lifecycle.state("base"), Some(PluginState::Running));
+    assert!(registry.is_initialized("base"));
+    let broken = lifecycle.status("broken").unwrap();
+    assert_eq!(broken.state, PluginState::Failed);
+    assert!(broken.last_error.unwrap().contains("init failed on purpose"));
+    assert!(!registry.is_initialized("broken"));
+    let unused = lifecycle.status("unused").unwrap();
+    assert_eq!((unused.state, unused.reason.as_str()), (PluginState::Disabled, "Not needed"));
+    assert!(!registry.is_enabled("unused"));
+
+    registry.shutdown_all().unwrap();
+    assert_eq!(lifecycle.state("base"), Some(PluginState::Loaded));
+
+    registry.enable_plugin("unused").unwrap();
+    assert_eq!(lifecycle.state("unused"), Some(PluginState::Loaded));
+    registry.unregister_plugin("unused").unwrap();
+    assert_eq!(lifecycle.state("unused"), Some(PluginState::Unloaded));
+    assert!(!registry.is_enabled("unused"));
+}
@@ -0,0 +1,96 @@
+use std::fs;
+
+use tempfile::tempdir;
+
+use crate::plugin_system::lint::{LintSeverity, ManifestLint};
+
+fn issues(lint: &ManifestLint) -> Vec<(LintSeverity, &str)> {
+    lint.issues.iter().map(|issue| (issue.severity, issue.field.as_str())).collect()
+}
+
+#[test]
+#[cfg(feature = "toml-config")]
+fn test_lint_accepts_a_complete_plugin() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("libgood.so"), b"lib").unwrap();
+    fs::write(dir.path().join("config.schema.json"), r#"{ "properties": { "level": { "type": "string" } } }"#).unwrap();
+    fs::write(dir.path().join("plugin.toml"), r#"
+id = "good"
+name = "Good"
+version = "1.0.0"
+description = "Lints cleanly"
+author = "Gini"
+api_versions = ["^0.1"]
+priority = "third_party:151"
+entry_point = "libgood.so"
+config_schema = "config.schema.json"
+"#).unwrap();
+
+    let lint = ManifestLint::lint(dir.path()).unwrap();
+
+    assert!(lint.issues.is_empty(), "{}", lint);
+    assert!(!lint.has_errors());
+    assert!(lint.to_string().ends_with("plugin.toml: no problems found\n"), "{}", lint);
+}
+
+#[test]
+fn test_lint_reports_every_problem() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("manifest.json");
+    fs::write(&path, r#"{
+        "id": "bad",
+        "name": "Bad",
+        "version": "1.0.0",
+        "description": "",
+        "dependecies": [],
+        "api_versions": ["^0.1", "not a range"],
+        "dependencies": [{ "id": "logger", "version_range": ">>1", "optional": true }],
+        "incompatible_with": [{ "version_range": "<1.0" }],
+        "priority": "urgent:1",
+        "resources": [{ "type": "file_path", "identifier": "/tmp/x", "access": "exclusive" }],
+        "files": ["missing.dat"],
+        "config_schema": "missing.schema.json"
+    }"#).unwrap();
+
+    let lint = ManifestLint::lint(&path).unwrap();
+
+    assert!(lint.has_errors());
+    assert_eq!(issues(&lint), vec![
+        (LintSeverity::Warning, "dependecies"),
+        (LintSeverity::Error, "author"),
+        (LintSeverity::Error, "api_versions[1]"),
+        (LintSeverity::Warning, "dependencies[0].optional"),
+        (LintSeverity::Error, "dependencies[0].version_range"),
+        (LintSeverity::Error, "incompatible_with[0].id"),
+        (LintSeverity::Error, "priority"),
+        (LintSeverity::Error, "resources[0]"),
+        (LintSeverity::Warning, "entry_point"),
+        (LintSeverity::Error, "entry_point"),
+        (LintSeverity::Error, "files[0]"),
+        (LintSeverity::Error, "config_schema"),
+    ]);
+    let text = lint.to_string();
+    assert!(text.contains("  warning: entry_point: not set; the loader uses 'libbad.so'\n"), "{}", text);
+    assert!(text.ends_with("9 error(s), 3 warning(s)\n"), "{}", text);
+}
+
+#[test]
+#[cfg(feature = "yaml-config")]
+fn test_lint_reports_type_errors_and_unreadable_manifests() {
+    let dir = tempdir().unwrap();
+    fs::write(dir.path().join("libtyped.so"), b"lib").unwrap();
+    let path = dir.path().join("plugin.yaml");
+    fs::write(&path, "id: typed\nname: Typed\nversion: 1.0.0\ndescription: ''\nauthor: ''\nentry_point: libtyped.so\nis_core: maybe\n").unwrap();
+
+    let lint = ManifestLint::lint(&path).unwrap();
+    assert_eq!(issues(&lint), vec![(LintSeverity::Error, "manifest")]);
+    assert!(lint.issues[0].message.contains("Failed to parse manifest YAML"), "{}", lint);
+
+    fs::write(&path, "id: [").unwrap();
+    let lint = ManifestLint::lint(&path).unwrap();
+    assert!(lint.issues[0].message.starts_with("not valid YAML"), "{}", lint);
+
+    assert!(ManifestLint::lint(dir.path().join("nowhere")).is_err());
+    let empty = tempdir().unwrap();
+    assert!(ManifestLint::lint(empty.path()).unwrap_err().to_string().contains("No manifest file found"));
+}
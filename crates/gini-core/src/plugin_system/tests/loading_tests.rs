@@ -223,4 +223,144 @@ async fn test_plugin_loader_get_manifests_public_api() -> Result<()> {
 
 // --- Dependency Resolution Tests ---
 // Removed resolve_dependencies unit tests as the function is private.
-// This logic will be tested via integration tests using register_all_plugins.
\ No newline at end of file
+// This logic will be tested via integration tests using register_all_plugins.
+// --- Hot-Reload Tests ---
+
+use crate::kernel::bootstrap::Application;
+use crate::event::EventResult;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime};
+
+#[tokio::test]
+async fn test_hot_reload_changed_so_plugin() -> Result<()> {
+    let tmp_dir = tempdir().expect("Failed to create temp directory");
+    let example_plugin_src = match get_example_plugin_path() {
+        Some(path) => path,
+        None => {
+            println!("Skipping test: Could not find the compiled example plugin.");
+            return Ok(());
+        }
+    };
+    let plugin_path = tmp_dir.path().join("libcompat_check_example.so");
+    fs::copy(&example_plugin_src, &plugin_path).expect("Failed to copy plugin to temp dir");
+
+    // Manager with an event manager attached so reload events can be observed
+    let event_manager = Arc::new(DefaultEventManager::new());
+    let unload_count = Arc::new(AtomicUsize::new(0));
+    let loaded_count = Arc::new(AtomicUsize::new(0));
+    let unload_counter = unload_count.clone();
+    event_manager.register_sync_handler("plugin.unload", move |_| {
+        unload_counter.fetch_add(1, Ordering::SeqCst);
+        EventResult::Continue
+    }).await;
+    let loaded_counter = loaded_count.clone();
+    event_manager.register_sync_handler("plugin.loaded", move |_| {
+        loaded_counter.fetch_add(1, Ordering::SeqCst);
+        EventResult::Continue
+    }).await;
+    let (manager, _tmp_dir_manager) = create_test_manager_for_loading();
+    let manager = manager.with_event_manager(event_manager.clone() as Arc<dyn EventManager>);
+
+    manager.load_plugin(&plugin_path).await?;
+    assert!(manager.detect_changed_plugins().await.is_empty(), "Freshly loaded plugin should not be reported as changed");
+
+    // Simulate a rebuild by bumping the modification time of the entry point
+    let file = fs::File::options().write(true).open(&plugin_path).expect("Failed to open plugin file");
+    file.set_modified(SystemTime::now() + Duration::from_secs(5)).expect("Failed to set mtime");
+    drop(file);
+
+    let mut app = Application::new().expect("Failed to create Application");
+    let reloaded = manager.reload_changed_plugins(&mut app).await?;
+    assert_eq!(reloaded, vec!["CompatCheckExample".to_string()]);
+    assert!(manager.detect_changed_plugins().await.is_empty(), "A change should only be reported once");
+
+    {
+        let registry = manager.registry().lock().await;
+        assert!(registry.has_plugin("CompatCheckExample"), "Reloaded plugin should be registered again");
+        assert!(registry.is_enabled("CompatCheckExample"), "Reloaded plugin should keep its enabled state");
+    }
+
+    event_manager.process_queue().await;
+    assert_eq!(unload_count.load(Ordering::SeqCst), 1, "PluginUnload should be emitted once");
+    assert_eq!(loaded_count.load(Ordering::SeqCst), 1, "PluginLoaded should be emitted once");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_failed_reload_keeps_old_build_registered_and_watched() -> Result<()> {
+    let tmp_dir = tempdir().expect("Failed to create temp directory");
+    let example_plugin_src = match get_example_plugin_path() {
+        Some(path) => path,
+        None => {
+            println!("Skipping test: Could not find the compiled example plugin.");
+            return Ok(());
+        }
+    };
+    let plugin_path = tmp_dir.path().join("libcompat_check_example.so");
+    fs::copy(&example_plugin_src, &plugin_path).expect("Failed to copy plugin to temp dir");
+    let (manager, _tmp_dir_manager) = create_test_manager_for_loading();
+    manager.load_plugin(&plugin_path).await?;
+    let mut app = Application::new().expect("Failed to create Application");
+
+    // A broken build replaces the entry point (as a new file; the loaded one stays mapped)
+    let staged_path = tmp_dir.path().join("staged.so");
+    fs::write(&staged_path, b"not a shared library").expect("Failed to write broken build");
+    fs::rename(&staged_path, &plugin_path).expect("Failed to replace plugin");
+    let file = fs::File::options().write(true).open(&plugin_path).expect("Failed to open plugin file");
+    file.set_modified(SystemTime::now() + Duration::from_secs(5)).expect("Failed to set mtime");
+    drop(file);
+
+    let reloaded = manager.reload_changed_plugins(&mut app).await?;
+    assert!(reloaded.is_empty(), "A build that fails to load is not reloaded");
+    {
+        let registry = manager.registry().lock().await;
+        assert!(registry.has_plugin("CompatCheckExample"), "The old build stays registered");
+        assert!(registry.is_enabled("CompatCheckExample"));
+    }
+
+    // The plugin is still watched, so the fixed build is picked up
+    fs::copy(&example_plugin_src, &staged_path).expect("Failed to copy fixed build");
+    fs::rename(&staged_path, &plugin_path).expect("Failed to restore plugin");
+    let file = fs::File::options().write(true).open(&plugin_path).expect("Failed to open plugin file");
+    file.set_modified(SystemTime::now() + Duration::from_secs(10)).expect("Failed to set mtime");
+    drop(file);
+    assert_eq!(manager.reload_changed_plugins(&mut app).await?, vec!["CompatCheckExample".to_string()]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_reload_non_dynamic_plugin_fails() -> Result<()> {
+    let (manager, _tmp_dir_manager) = create_test_manager_for_loading();
+    let mut app = Application::new().expect("Failed to create Application");
+    let result = manager.reload_plugin("not_a_dynamic_plugin", &mut app).await;
+    assert!(result.is_err(), "Reloading a plugin without a tracked entry point should fail");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_scan_for_manifests_first_directory_wins() -> Result<()> {
+    let tmp_dir = tempdir().expect("Failed to create temp directory");
+    let preferred_dir = tmp_dir.path().join("preferred");
+    let fallback_dir = tmp_dir.path().join("fallback");
+    for (dir, version) in [(&preferred_dir, "2.0.0"), (&fallback_dir, "1.0.0")] {
+        tokio_fs::create_dir_all(dir.join("dup_plugin")).await?;
+        let manifest_json = format!(
+            r#"{{ "id": "dup_plugin", "name": "Dup", "version": "{}", "description": "d", "author": "a" }}"#,
+            version
+        );
+        tokio_fs::write(dir.join("dup_plugin/manifest.json"), manifest_json).await?;
+    }
+
+    let mut loader = PluginLoader::new();
+    loader.add_plugin_dir(&preferred_dir);
+    loader.add_plugin_dir(&fallback_dir);
+    loader.add_plugin_dir(tmp_dir.path()); // Overlaps both directories above
+
+    let manifests = loader.scan_for_manifests().await?;
+    assert_eq!(manifests.len(), 1, "Duplicate plugin IDs should be collapsed to one manifest");
+    assert_eq!(manifests[0].version, "2.0.0", "The copy from the first directory should win");
+    assert_eq!(loader.get_manifest("dup_plugin").unwrap().version, "2.0.0");
+    Ok(())
+}
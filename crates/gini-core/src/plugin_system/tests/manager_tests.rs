@@ -17,7 +17,7 @@ use crate::stage_manager::Stage; // Import Stage trait
 use crate::stage_manager::registry::StageRegistry; // Added for register_stages
 use std::error::Error as StdError; // For boxing
 use async_trait::async_trait;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex};
 use tempfile::{tempdir, TempDir}; // Added TempDir
 use std::path::{Path, PathBuf}; // Added Path
 use std::str::FromStr; // Import FromStr for parsing VersionRange
@@ -28,6 +28,12 @@ use crate::event::{DefaultEventManager, EventManager}; // Added for StageManager
 use crate::stage_manager::manager::DefaultStageManager; // Added for StageManager
 use std::time::Duration;
 use serde_json::Value; // For deserializing state
+use crate::kernel::constants;
+use crate::plugin_system::search_path::PluginPathSource;
+use crate::plugin_system::lifecycle::PluginState;
+use crate::plugin_system::registry::PluginRegistry;
+use crate::plugin_system::conflict::ResolutionStrategy;
+use crate::ui_bridge::{UnifiedUiInterface, UnifiedUiManager, UiMessage, UiUpdateType, UserInput, error::UiBridgeError};
 // use rand; // Removed as no longer used after fixing test directory name
 
 // Constants for config file and key used by DefaultPluginManager
@@ -80,6 +86,7 @@ struct MockManagerPlugin {
     compatible_versions: Vec<VersionRange>,
     required_stages_list: Vec<StageRequirement>,
     conflicts_with_ids: Vec<String>, // Added for conflict testing
+    call_log: Option<Arc<StdMutex<Vec<String>>>>, // Records init and shutdown calls
 }
 
 enum ShutdownBehavior {
@@ -108,6 +115,18 @@ impl MockManagerPlugin {
             compatible_versions: vec![VersionRange::from_str(">=0.1.0").unwrap()],
             required_stages_list: vec![],
             conflicts_with_ids: vec![], // Initialize new field
+            call_log: None,
+        }
+    }
+
+    fn with_call_log(mut self, call_log: Arc<StdMutex<Vec<String>>>) -> Self {
+        self.call_log = Some(call_log);
+        self
+    }
+
+    fn log_call(&self, call: &str) {
+        if let Some(call_log) = &self.call_log {
+            call_log.lock().unwrap().push(format!("{}:{}", call, self.id));
         }
     }
 
@@ -202,6 +221,7 @@ impl Plugin for MockManagerPlugin {
     fn required_stages(&self) -> Vec<StageRequirement> { self.required_stages_list.clone() }
 
     fn shutdown(&self) -> std::result::Result<(), PluginSystemError> {
+        self.log_call("shutdown");
         match &self.shutdown_behavior {
             ShutdownBehavior::Success => Ok(()),
             ShutdownBehavior::Failure(msg) => Err(PluginSystemError::ShutdownError {
@@ -217,6 +237,7 @@ impl Plugin for MockManagerPlugin {
     }
 
     fn init(&self, _app: &mut Application) -> std::result::Result<(), PluginSystemError> {
+        self.log_call("init");
         match &self.init_behavior {
             InitBehavior::Success => Ok(()),
             InitBehavior::Failure(msg) => Err(PluginSystemError::InitializationError {
@@ -702,27 +723,75 @@ async fn test_get_plugin_manifest_not_found() {
 
 #[tokio::test]
 async fn test_manager_initialize_with_plugin_dir() {
-    // Test initialization when the default plugin dir *does* exist
+    // Test initialization with a plugin directory listed in the core settings
     // This requires the example plugin to be compiled in target/debug
-    let (manager, _tmp_dir) = create_test_manager();
-    let plugin_dir = PathBuf::from("./target/debug");
+    let example_plugin_src = match get_example_plugin_path() {
+        Some(path) => path,
+        None => {
+            println!("Skipping test: Example plugin not found in target/debug");
+            return;
+        }
+    };
+    let (manager, tmp_dir) = create_test_manager();
 
-    if !plugin_dir.exists() {
-         println!("Skipping test: Default plugin directory ./target/debug not found.");
-         return;
-    }
-     if get_example_plugin_path().is_none() {
-         println!("Skipping test: Example plugin not found in ./target/debug");
-         return;
-     }
+    // Lay out <plugins>/compat_check/{manifest.json, libcompat_check_example.so}
+    let plugins_dir = tmp_dir.path().join("configured_plugins");
+    let plugin_subdir = plugins_dir.join("compat_check");
+    fs::create_dir_all(&plugin_subdir).unwrap();
+    fs::copy(&example_plugin_src, plugin_subdir.join("libcompat_check_example.so")).unwrap();
+    fs::write(
+        plugin_subdir.join("manifest.json"),
+        r#"{ "id": "CompatCheckExample", "name": "Compat Check", "version": "0.1.0", "description": "d", "author": "a", "entry_point": "libcompat_check_example.so" }"#,
+    ).unwrap();
+    write_search_paths_config(tmp_dir.path(), &[&plugins_dir]);
+
+    // Call initialize via the KernelComponent trait explicitly
+    let result = KernelComponent::initialize(&manager).await;
+    assert!(result.is_ok(), "Initialize should succeed when dir exists");
+
+    // Check if the example plugin was loaded from the configured directory
+    let loaded = manager.is_plugin_loaded("CompatCheckExample").await.unwrap();
+    assert!(loaded, "Example plugin should have been loaded during initialization");
+}
 
-     // Call initialize via the KernelComponent trait explicitly
-     let result = KernelComponent::initialize(&manager).await;
-     assert!(result.is_ok(), "Initialize should succeed when dir exists");
+/// Moves a registered plugin to `Initialized` without running `Plugin::init`.
+fn mark_initialized(registry: &PluginRegistry, plugin_id: &str) {
+    let lifecycle = registry.lifecycle();
+    lifecycle.transition(plugin_id, PluginState::Resolved, "Test setup").unwrap();
+    lifecycle.transition(plugin_id, PluginState::Initialized, "Test setup").unwrap();
+}
 
-     // Check if the example plugin was loaded (assuming it's the only one)
-     let loaded = manager.is_plugin_loaded("CompatCheckExample").await.unwrap();
-     assert!(loaded, "Example plugin should have been loaded during initialization");
+/// Writes the core settings file of a test manager's config dir.
+fn write_core_settings(tmp_dir: &Path, settings: Value) {
+    let config_path = tmp_dir.join("app_config").join(format!("{}.json", CORE_SETTINGS_CONFIG_NAME_VAL));
+    fs::write(config_path, settings.to_string()).unwrap();
+}
+
+/// Writes `core.plugins.search_paths` into the core settings of a test manager's config dir.
+fn write_search_paths_config(tmp_dir: &Path, dirs: &[&Path]) {
+    let dirs: Vec<String> = dirs.iter().map(|d| d.to_string_lossy().into_owned()).collect();
+    write_core_settings(tmp_dir, serde_json::json!({ "core.plugins.search_paths": dirs }));
+}
+
+#[tokio::test]
+async fn test_plugin_search_paths_precedence() {
+    let (manager, tmp_dir) = create_test_manager();
+    let config_dir = tmp_dir.path().join("from_config");
+    let cli_dir = tmp_dir.path().join("from_cli");
+    write_search_paths_config(tmp_dir.path(), &[&config_dir]);
+    manager.add_cli_plugin_dirs(std::slice::from_ref(&cli_dir)).await;
+
+    let search_paths = manager.plugin_search_paths().await;
+    let dirs = search_paths.dirs();
+    let cli_pos = dirs.iter().position(|d| d == &cli_dir).expect("CLI dir should be listed");
+    let config_pos = dirs.iter().position(|d| d == &config_dir).expect("Config dir should be listed");
+    assert!(cli_pos < config_pos, "Command line directories should take precedence over configured ones");
+
+    // Built-in layout comes last, core before third-party
+    let core_pos = dirs.iter().position(|d| d == &Path::new(".").join(constants::CORE_PLUGINS_DIR)).unwrap();
+    let third_party_pos = dirs.iter().position(|d| d == &Path::new(".").join(constants::THIRD_PARTY_PLUGINS_DIR)).unwrap();
+    assert!(config_pos < core_pos && core_pos < third_party_pos);
+    assert_eq!(search_paths.source_of(&cli_dir), Some(PluginPathSource::CommandLine));
 }
 
 
@@ -843,9 +912,9 @@ async fn test_initialize_plugins_with_dependencies() {
         registry.register_plugin(dependent_plugin_2).unwrap();
 
         // Mark them as initialized to mirror a real scenario
-        registry.initialized.insert("base_plugin".to_string());
-        registry.initialized.insert("dependent_plugin_1".to_string());
-        registry.initialized.insert("dependent_plugin_2".to_string());
+        mark_initialized(&registry, "base_plugin");
+        mark_initialized(&registry, "dependent_plugin_1");
+        mark_initialized(&registry, "dependent_plugin_2");
     }
 
     // Test stop method which should call shutdown on plugins in correct order
@@ -868,7 +937,7 @@ async fn test_plugin_initialization_failure() {
         registry.register_plugin(failing_plugin).unwrap();
 
         // Manually add to initialized set to test shutdown flow
-        registry.initialized.insert("failing_plugin".to_string());
+        mark_initialized(&registry, "failing_plugin");
     }
 
     // In real scenario, initialization would fail, but we're testing the shutdown here
@@ -891,7 +960,7 @@ async fn test_plugin_shutdown_error() {
         registry.register_plugin(failing_shutdown_plugin).unwrap();
 
         // Mark as initialized
-        registry.initialized.insert("failing_shutdown".to_string());
+        mark_initialized(&registry, "failing_shutdown");
     }
 
     // Test stop method which should propagate the shutdown error
@@ -939,10 +1008,10 @@ async fn test_complex_shutdown_ordering() {
         registry.register_plugin(Arc::new(MockManagerPlugin::new("p4", p4_deps))).unwrap();
 
         // Mark all as initialized
-        registry.initialized.insert("p1".to_string());
-        registry.initialized.insert("p2".to_string());
-        registry.initialized.insert("p3".to_string());
-        registry.initialized.insert("p4".to_string());
+        mark_initialized(&registry, "p1");
+        mark_initialized(&registry, "p2");
+        mark_initialized(&registry, "p3");
+        mark_initialized(&registry, "p4");
     }
 
     // Test the stop method
@@ -1069,7 +1138,7 @@ async fn test_plugin_with_stages() {
         registry.register_plugin(plugin).unwrap();
 
         // Mark as initialized
-        registry.initialized.insert("stage_plugin".to_string());
+        mark_initialized(&registry, "stage_plugin");
     }
 
     // Get plugin and verify its stages
@@ -1165,7 +1234,7 @@ async fn test_plugin_with_long_running_operations() {
         registry.register_plugin(shutdown_timeout_plugin).unwrap();
 
         // Mark shutdown plugin as initialized
-        registry.initialized.insert("shutdown_timeout".to_string());
+        mark_initialized(&registry, "shutdown_timeout");
     }
 
     // Test behaviors
@@ -1513,3 +1582,328 @@ async fn test_state_load_on_initialize() {
      }
      } // Close mod initialize_conflict_tests
 }
+
+
+#[tokio::test]
+async fn test_lazy_loading_lists_and_disables_broken_plugin() {
+    let (manager, tmp_dir) = create_test_manager();
+
+    // A manifest whose entry point is not a valid shared library
+    let plugins_dir = tmp_dir.path().join("lazy_plugins");
+    let plugin_subdir = plugins_dir.join("broken");
+    fs::create_dir_all(&plugin_subdir).unwrap();
+    fs::write(plugin_subdir.join("libbroken_plugin.so"), b"not an ELF file").unwrap();
+    fs::write(
+        plugin_subdir.join("manifest.json"),
+        r#"{ "id": "broken_plugin", "name": "Broken", "version": "1.2.0", "description": "d", "author": "a",
+             "api_versions": [">=0.1.0"], "entry_point": "libbroken_plugin.so", "dependencies": [{ "id": "optional_helper", "required": false }] }"#,
+    ).unwrap();
+    write_core_settings(tmp_dir.path(), serde_json::json!({
+        "core.plugins.search_paths": [plugins_dir.to_string_lossy()],
+        "core.plugins.lazy_loading": true,
+    }));
+
+    KernelComponent::initialize(&manager).await.expect("Initialize should not open manifest-only libraries");
+
+    assert!(manager.is_plugin_loaded("broken_plugin").await.unwrap(), "Plugin should be listed from its manifest");
+    {
+        let registry = manager.registry().lock().await;
+        assert!(!registry.is_plugin_code_loaded("broken_plugin"), "Library should not be loaded yet");
+        let plugin = registry.get_plugin("broken_plugin").unwrap();
+        assert_eq!(plugin.version(), "1.2.0");
+        assert_eq!(plugin.dependencies()[0].plugin_name, "optional_helper");
+    }
+    let manifest = manager.get_plugin_manifest("broken_plugin").await.unwrap().expect("Manifest should be available");
+    assert_eq!(manifest.entry_point, "libbroken_plugin.so");
+
+    manager.persist_disable_plugin("broken_plugin").await.expect("Disabling should not need the library");
+    assert!(!manager.is_plugin_enabled("broken_plugin").await.unwrap());
+}
+
+#[tokio::test]
+async fn test_conflict_policies_disable_and_persist_losers() {
+    let (manager, tmp_dir) = create_test_manager();
+    {
+        let mut registry = manager.registry().lock().await;
+        let favourite = MockManagerPlugin::new("favourite_plugin", vec![]).with_conflicts(vec!["rival_plugin".to_string()]);
+        let rival = MockManagerPlugin::new("rival_plugin", vec![]).with_priority(PluginPriority::ThirdPartyHigh(110));
+        registry.register_plugin(Arc::new(favourite)).unwrap();
+        registry.register_plugin(Arc::new(rival)).unwrap();
+        registry.enable_plugin("favourite_plugin").unwrap();
+        registry.enable_plugin("rival_plugin").unwrap();
+    }
+
+    // The explicit enable outranks the rival's higher priority
+    write_core_settings(tmp_dir.path(), serde_json::json!({
+        "core.plugins.conflict_policies": ["prefer-explicitly-enabled", "prefer-higher-priority"],
+    }));
+    manager.persist_enable_plugin("favourite_plugin").await.unwrap();
+    let decisions = manager.apply_conflict_policies().await.unwrap();
+    assert_eq!(decisions.len(), 1);
+    assert_eq!(decisions[0].disabled_plugin(), Some("rival_plugin"));
+    assert!(!manager.is_plugin_enabled("rival_plugin").await.unwrap());
+
+    let config_path = tmp_dir.path().join("app_config").join(format!("{}.json", CORE_SETTINGS_CONFIG_NAME_VAL));
+    let json_value: Value = serde_json::from_str(&fs::read_to_string(config_path).unwrap()).unwrap();
+    assert_eq!(json_value[DISABLED_PLUGINS_KEY_VAL], serde_json::json!(["rival_plugin"]));
+    let reason = json_value["core.plugins.conflict_decisions"]["rival_plugin"].as_str().unwrap();
+    assert!(reason.contains("in favour of 'favourite_plugin' (prefer-explicitly-enabled)"), "{}", reason);
+
+    // Enabling the loser by hand clears the recorded decision
+    manager.persist_enable_plugin("rival_plugin").await.unwrap();
+    assert!(manager.is_plugin_enabled("rival_plugin").await.unwrap());
+}
+
+/// Writes a manifest-only plugin for lazy loading; its library is never opened
+fn write_lazy_manifest(plugins_dir: &Path, id: &str, priority: &str, conflicts_with: &[&str]) {
+    let plugin_subdir = plugins_dir.join(id);
+    fs::create_dir_all(&plugin_subdir).unwrap();
+    fs::write(plugin_subdir.join(format!("lib{}.so", id)), b"never loaded").unwrap();
+    let manifest = serde_json::json!({
+        "id": id, "name": id, "version": "1.0.0", "description": "d", "author": "a",
+        "api_versions": [">=0.1.0"], "entry_point": format!("lib{}.so", id),
+        "priority": priority, "conflicts_with": conflicts_with,
+    });
+    fs::write(plugin_subdir.join("manifest.json"), manifest.to_string()).unwrap();
+}
+
+#[tokio::test]
+async fn test_declared_conflicts_at_startup_follow_the_conflict_policies() {
+    let (manager, tmp_dir) = create_test_manager();
+    let plugins_dir = tmp_dir.path().join("lazy_plugins");
+    write_lazy_manifest(&plugins_dir, "eager_plugin", "third_party_high:110", &["modest_plugin"]);
+    write_lazy_manifest(&plugins_dir, "modest_plugin", "third_party_low:200", &[]);
+    write_core_settings(tmp_dir.path(), serde_json::json!({
+        "core.plugins.search_paths": [plugins_dir.to_string_lossy()],
+        "core.plugins.lazy_loading": true,
+        "core.plugins.conflict_policies": ["prefer:modest_plugin"],
+    }));
+
+    KernelComponent::initialize(&manager).await.unwrap();
+
+    // Both plugins reach the policies, and the lower priority one wins
+    assert!(manager.is_plugin_loaded("eager_plugin").await.unwrap());
+    assert!(!manager.is_plugin_enabled("eager_plugin").await.unwrap());
+    assert!(manager.is_plugin_enabled("modest_plugin").await.unwrap());
+
+    // Without a policy the higher priority wins, and nothing is persisted
+    let (manager, tmp_dir) = create_test_manager();
+    let plugins_dir = tmp_dir.path().join("lazy_plugins");
+    write_lazy_manifest(&plugins_dir, "eager_plugin", "third_party_high:110", &["modest_plugin"]);
+    write_lazy_manifest(&plugins_dir, "modest_plugin", "third_party_low:200", &[]);
+    write_core_settings(tmp_dir.path(), serde_json::json!({
+        "core.plugins.search_paths": [plugins_dir.to_string_lossy()],
+        "core.plugins.lazy_loading": true,
+    }));
+
+    KernelComponent::initialize(&manager).await.unwrap();
+
+    assert!(manager.is_plugin_enabled("eager_plugin").await.unwrap());
+    assert!(!manager.is_plugin_enabled("modest_plugin").await.unwrap());
+    let config_path = tmp_dir.path().join("app_config").join(format!("{}.json", CORE_SETTINGS_CONFIG_NAME_VAL));
+    let json_value: Value = serde_json::from_str(&fs::read_to_string(config_path).unwrap()).unwrap();
+    assert!(json_value.get(DISABLED_PLUGINS_KEY_VAL).is_none(), "{}", json_value);
+}
+
+/// Each prompt shown to the user, with its options
+type Prompts = Arc<std::sync::Mutex<Vec<(String, Vec<String>)>>>;
+
+/// Interactive UI interface that records the choices it is shown.
+#[derive(Debug)]
+struct PromptRecorder {
+    prompts: Prompts,
+}
+
+impl UnifiedUiInterface for PromptRecorder {
+    fn name(&self) -> &str { "prompt_recorder" }
+    fn initialize(&mut self) -> Result<(), UiBridgeError> { Ok(()) }
+    fn handle_message(&mut self, message: &UiMessage) -> Result<(), UiBridgeError> {
+        if let UiUpdateType::Choice(prompt, options) = &message.update_type {
+            self.prompts.lock().unwrap().push((prompt.clone(), options.clone()));
+        }
+        Ok(())
+    }
+    fn send_input(&mut self, _input: UserInput) -> Result<(), UiBridgeError> { Ok(()) }
+    fn update(&mut self) -> Result<(), UiBridgeError> { Ok(()) }
+    fn finalize(&mut self) -> Result<(), UiBridgeError> { Ok(()) }
+    fn supports_interactive(&self) -> bool { true }
+}
+
+#[tokio::test]
+async fn test_conflicts_are_resolved_through_the_ui() {
+    let (manager, tmp_dir) = create_test_manager();
+    write_core_settings(tmp_dir.path(), serde_json::json!({}));
+    let ui_manager = UnifiedUiManager::new(Arc::new(DefaultEventManager::new()) as Arc<dyn EventManager>);
+    let prompts: Prompts = Arc::default();
+    ui_manager.register_interface(Box::new(PromptRecorder { prompts: prompts.clone() })).unwrap();
+    let manager = manager.with_ui_manager(ui_manager.clone());
+    {
+        let mut registry = manager.registry().lock().await;
+        let alpha = MockManagerPlugin::new("alpha_plugin", vec![]).with_conflicts(vec!["beta_plugin".to_string(), "gamma_plugin".to_string()]);
+        registry.register_plugin(Arc::new(alpha)).unwrap();
+        registry.register_plugin(Arc::new(MockManagerPlugin::new("beta_plugin", vec![]))).unwrap();
+        registry.register_plugin(Arc::new(MockManagerPlugin::new("gamma_plugin", vec![]))).unwrap();
+        for id in ["alpha_plugin", "beta_plugin", "gamma_plugin"] {
+            registry.enable_plugin(id).unwrap();
+        }
+    }
+
+    let asking = tokio::spawn({
+        let manager = manager.clone();
+        async move { manager.resolve_conflicts_interactively().await }
+    });
+    // Disable beta for the first conflict, allow alpha and gamma together for the second
+    for (shown, answer) in [(1, UserInput::Text("2".to_string())), (2, UserInput::Selection(2))] {
+        for _ in 0..500 {
+            if prompts.lock().unwrap().len() >= shown {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(prompts.lock().unwrap().len(), shown, "Expected conflict question {}", shown);
+        ui_manager.submit_user_input(answer, "prompt_recorder").await.unwrap();
+    }
+    let decisions = asking.await.unwrap().unwrap();
+
+    let (prompt, options) = prompts.lock().unwrap()[0].clone();
+    assert!(prompt.contains("'alpha_plugin' and 'beta_plugin'"), "{}", prompt);
+    assert_eq!(options, vec!["Disable 'alpha_plugin'", "Disable 'beta_plugin'", "Allow both to run (with a warning)"]);
+    assert_eq!(decisions.len(), 2);
+    assert_eq!(decisions[0].disabled_plugin(), Some("beta_plugin"));
+    assert_eq!(decisions[1].strategy, ResolutionStrategy::AllowWithWarning);
+    assert!(!manager.is_plugin_enabled("beta_plugin").await.unwrap());
+    assert!(manager.is_plugin_enabled("gamma_plugin").await.unwrap());
+
+    let config_path = tmp_dir.path().join("app_config").join(format!("{}.json", CORE_SETTINGS_CONFIG_NAME_VAL));
+    let json_value: Value = serde_json::from_str(&fs::read_to_string(config_path).unwrap()).unwrap();
+    assert_eq!(json_value[DISABLED_PLUGINS_KEY_VAL], serde_json::json!(["beta_plugin"]));
+    assert_eq!(json_value["core.plugins.allowed_conflicts"], serde_json::json!([["alpha_plugin", "gamma_plugin"]]));
+
+    // Nothing is left to ask about
+    assert!(manager.resolve_conflicts_interactively().await.unwrap().is_empty());
+    assert_eq!(prompts.lock().unwrap().len(), 2);
+    let mut registry = manager.registry().lock().await;
+    registry.detect_all_conflicts().unwrap();
+    assert!(registry.conflict_manager().all_critical_conflicts_resolved());
+}
+
+/// Registers `base`, `mid` (requires `base`), `top` (requires `mid`) and `optional_user`
+/// (optionally uses `base`), all logging their init and shutdown calls to `call_log`.
+async fn register_dependency_chain(manager: &DefaultPluginManager, call_log: &Arc<StdMutex<Vec<String>>>) {
+    let mut registry = manager.registry().lock().await;
+    let plugins = vec![
+        MockManagerPlugin::new("base", vec![]),
+        MockManagerPlugin::new("mid", vec![PluginDependency::required_any("base")]),
+        MockManagerPlugin::new("top", vec![PluginDependency::required_any("mid")]),
+        MockManagerPlugin::new("optional_user", vec![PluginDependency::optional_any("base")]),
+    ];
+    for plugin in plugins {
+        registry.register_plugin(Arc::new(plugin.with_call_log(call_log.clone()))).unwrap();
+    }
+}
+
+fn persisted_list(tmp_dir: &Path, key: &str) -> Vec<String> {
+    let config_path = tmp_dir.join("app_config").join(format!("{}.json", CORE_SETTINGS_CONFIG_NAME_VAL));
+    let json: Value = serde_json::from_str(&fs::read_to_string(config_path).unwrap()).unwrap();
+    serde_json::from_value(json[key].clone()).unwrap_or_default()
+}
+
+#[tokio::test]
+async fn test_disable_plugin_cascades_to_dependents() {
+    let (manager, tmp_dir) = create_test_manager();
+    let call_log = Arc::new(StdMutex::new(Vec::new()));
+    register_dependency_chain(&manager, &call_log).await;
+    let mut app = Application::new().unwrap();
+
+    let enabled_along = manager.enable_plugin("top", &mut app).await.unwrap();
+    assert!(enabled_along.is_empty(), "The required plugins were already enabled");
+    assert_eq!(*call_log.lock().unwrap(), vec!["init:base", "init:mid", "init:top"]);
+    call_log.lock().unwrap().clear();
+
+    let err = manager.disable_plugin("base", false).await.expect_err("Dependents require confirmation");
+    assert!(err.to_string().contains("top, mid"), "{}", err);
+    assert!(manager.registry().lock().await.is_initialized("base"), "Nothing is disabled without cascade");
+
+    let dependents = manager.disable_plugin("base", true).await.unwrap();
+    assert_eq!(dependents, vec!["top", "mid"]);
+    assert_eq!(*call_log.lock().unwrap(), vec!["shutdown:top", "shutdown:mid", "shutdown:base"],
+        "Dependents are shut down before their dependencies");
+
+    let registry = manager.registry().lock().await;
+    for plugin_id in ["base", "mid", "top"] {
+        assert!(!registry.is_enabled(plugin_id), "{} should be disabled", plugin_id);
+    }
+    assert!(registry.is_enabled("optional_user"), "Optional dependents keep running");
+    assert_eq!(registry.lifecycle().status("mid").unwrap().reason, "Required plugin 'base' was disabled");
+    let mut disabled = persisted_list(tmp_dir.path(), DISABLED_PLUGINS_KEY_VAL);
+    disabled.sort();
+    assert_eq!(disabled, vec!["base", "mid", "top"]);
+}
+
+#[tokio::test]
+async fn test_enable_plugin_initializes_required_plugins() {
+    let (manager, tmp_dir) = create_test_manager();
+    let call_log = Arc::new(StdMutex::new(Vec::new()));
+    register_dependency_chain(&manager, &call_log).await;
+    manager.disable_plugin("base", true).await.unwrap();
+    assert!(call_log.lock().unwrap().is_empty(), "Nothing was initialized, so nothing is shut down");
+
+    let mut app = Application::new().unwrap();
+    let enabled_along = manager.enable_plugin("top", &mut app).await.unwrap();
+    assert_eq!(enabled_along, vec!["base", "mid"]);
+    assert_eq!(*call_log.lock().unwrap(), vec!["init:base", "init:mid", "init:top"]);
+
+    let registry = manager.registry().lock().await;
+    for plugin_id in ["base", "mid", "top"] {
+        assert!(registry.is_initialized(plugin_id), "{} should be initialized", plugin_id);
+    }
+    assert!(persisted_list(tmp_dir.path(), DISABLED_PLUGINS_KEY_VAL).is_empty());
+    drop(registry);
+
+    assert!(manager.enable_plugin("missing", &mut app).await.is_err());
+}
+
+/// A manager running `favourite_plugin`, which conflicts with the disabled, higher priority
+/// `rival_plugin`, under the given conflict policies
+async fn running_favourite_with_disabled_rival(policies: &[&str], call_log: &Arc<StdMutex<Vec<String>>>) -> (DefaultPluginManager, TempDir, Application) {
+    let (manager, tmp_dir) = create_test_manager();
+    write_core_settings(tmp_dir.path(), serde_json::json!({ "core.plugins.conflict_policies": policies }));
+    {
+        let mut registry = manager.registry().lock().await;
+        let favourite = MockManagerPlugin::new("favourite_plugin", vec![]).with_conflicts(vec!["rival_plugin".to_string()]);
+        let rival = MockManagerPlugin::new("rival_plugin", vec![]).with_priority(PluginPriority::ThirdPartyHigh(110));
+        registry.register_plugin(Arc::new(favourite.with_call_log(call_log.clone()))).unwrap();
+        registry.register_plugin(Arc::new(rival.with_call_log(call_log.clone()))).unwrap();
+    }
+    manager.disable_plugin("rival_plugin", false).await.unwrap();
+    let mut app = Application::new().unwrap();
+    manager.enable_plugin("favourite_plugin", &mut app).await.unwrap();
+    (manager, tmp_dir, app)
+}
+
+#[tokio::test]
+async fn test_enable_plugin_settles_conflicts_with_running_plugins() {
+    // Without a policy or a UI the conflict stays open, so the rival is refused
+    let call_log = Arc::new(StdMutex::new(Vec::new()));
+    let (manager, tmp_dir, mut app) = running_favourite_with_disabled_rival(&[], &call_log).await;
+    let err = manager.enable_plugin("rival_plugin", &mut app).await.expect_err("Conflicting plugin should be refused");
+    assert!(matches!(err, Error::PluginSystem(PluginSystemError::UnresolvedPluginConflicts { .. })), "{}", err);
+    assert!(!manager.is_plugin_enabled("rival_plugin").await.unwrap());
+    assert_eq!(persisted_list(tmp_dir.path(), DISABLED_PLUGINS_KEY_VAL), vec!["rival_plugin"], "The persisted state is restored");
+    assert_eq!(*call_log.lock().unwrap(), vec!["init:favourite_plugin"]);
+
+    // A policy that keeps the running plugin leaves the rival disabled
+    let call_log = Arc::new(StdMutex::new(Vec::new()));
+    let (manager, _tmp_dir, mut app) = running_favourite_with_disabled_rival(&["prefer:favourite_plugin"], &call_log).await;
+    let err = manager.enable_plugin("rival_plugin", &mut app).await.expect_err("The policy prefers the running plugin");
+    assert!(err.to_string().contains("in favour of 'favourite_plugin' (prefer:favourite_plugin)"), "{}", err);
+    assert!(!manager.is_plugin_enabled("rival_plugin").await.unwrap());
+    assert_eq!(*call_log.lock().unwrap(), vec!["init:favourite_plugin"], "The rival was never initialized");
+
+    // A policy that prefers the newcomer shuts the running plugin down instead
+    let call_log = Arc::new(StdMutex::new(Vec::new()));
+    let (manager, _tmp_dir, mut app) = running_favourite_with_disabled_rival(&["prefer-higher-priority"], &call_log).await;
+    manager.enable_plugin("rival_plugin", &mut app).await.unwrap();
+    assert!(!manager.is_plugin_enabled("favourite_plugin").await.unwrap());
+    assert_eq!(*call_log.lock().unwrap(), vec!["init:favourite_plugin", "shutdown:favourite_plugin", "init:rival_plugin"]);
+}
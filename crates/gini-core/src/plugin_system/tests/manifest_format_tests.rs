@@ -0,0 +1,131 @@
+use std::path::Path;
+use std::str::FromStr;
+
+#[cfg(all(feature = "toml-config", feature = "yaml-config"))]
+use tempfile::tempdir;
+
+use crate::plugin_system::conflict::ResourceAccessType;
+use crate::plugin_system::dependency::PluginDependency;
+use crate::plugin_system::loader::PluginLoader;
+use crate::plugin_system::manifest::{PluginManifest, ResourceClaim};
+use crate::plugin_system::version::VersionRange;
+use crate::storage::config::ConfigFormat;
+
+/// A manifest that sets every field the loader reads
+fn full_manifest() -> PluginManifest {
+    let mut manifest = PluginManifest::new("full", "Full Plugin", "1.2.3", "Sets every field", "Gini");
+    manifest.website = Some("https://example.com".to_string());
+    manifest.license = Some("MIT".to_string());
+    manifest.api_versions = vec![VersionRange::from_str("^0.1").unwrap()];
+    manifest.add_dependency("logger", Some(VersionRange::from_str(">=1.0, <2.0").unwrap()), true);
+    manifest.add_dependency("metrics", None, false);
+    manifest.is_core = true;
+    manifest.priority = Some("core:80".to_string());
+    manifest.entry_point = "libfull.so".to_string();
+    manifest.files = vec!["data/defaults.json".to_string()];
+    manifest.config_schema = Some("config.schema.json".to_string());
+    manifest.tags = vec!["example".to_string()];
+    manifest.conflicts_with = vec!["legacy-full".to_string()];
+    manifest.incompatible_with = vec![PluginDependency::required("old-logger", VersionRange::from_str("<1.0").unwrap())];
+    manifest.resources = vec![ResourceClaim::new("network_port", "tcp:8080", ResourceAccessType::ExclusiveWrite)];
+    manifest
+}
+
+fn formats() -> Vec<(ConfigFormat, &'static str)> {
+    vec![
+        (ConfigFormat::Json, "manifest.json"),
+        #[cfg(feature = "toml-config")]
+        (ConfigFormat::Toml, "plugin.toml"),
+        #[cfg(feature = "yaml-config")]
+        (ConfigFormat::Yaml, "plugin.yaml"),
+    ]
+}
+
+#[test]
+fn test_manifest_round_trips_in_every_format() {
+    let manifest = full_manifest();
+    let expected = manifest.to_manifest_string(ConfigFormat::Json).unwrap();
+
+    for (format, file_name) in formats() {
+        let written = manifest.to_manifest_string(format).unwrap();
+        let path = Path::new("/plugins/full").join(file_name);
+        let parsed = PluginLoader::parse_manifest(&written, &path)
+            .unwrap_or_else(|e| panic!("{:?} manifest did not parse: {}\n{}", format, e, written));
+
+        assert_eq!(parsed.to_manifest_string(ConfigFormat::Json).unwrap(), expected, "{:?} lost a field", format);
+        assert_eq!(parsed.plugin_base_dir, Path::new("/plugins/full"));
+    }
+}
+
+#[test]
+#[cfg(all(feature = "toml-config", feature = "yaml-config"))]
+fn test_parse_manifest_reads_all_fields() {
+    let toml = r#"
+id = "toml-plugin"
+name = "TOML Plugin"
+version = "0.3.0"
+description = "Written in TOML"
+author = "Gini"
+api_versions = ["^0.1"]
+conflicts_with = ["other-plugin"]
+entry_point = "libtoml_plugin.so"
+
+[[dependencies]]
+id = "logger"
+version_range = "^1.0"
+required = true
+
+[[incompatible_with]]
+id = "old-logger"
+version_range = "<1.0"
+required = true
+
+[[resources]]
+type = "file_path"
+identifier = "/var/log/toml.log"
+access = "exclusive_write"
+"#;
+
+    let manifest = PluginLoader::parse_manifest(toml, Path::new("plugin.toml")).unwrap();
+
+    assert_eq!(manifest.conflicts_with, vec!["other-plugin"]);
+    assert_eq!(manifest.incompatible_with.len(), 1);
+    assert_eq!(manifest.incompatible_with[0].plugin_name, "old-logger");
+    assert_eq!(manifest.incompatible_with[0].version_range.as_ref().unwrap().to_string(), "<1.0");
+    assert_eq!(manifest.resources, vec![ResourceClaim::new("file_path", "/var/log/toml.log", ResourceAccessType::ExclusiveWrite)]);
+
+    let invalid = PluginLoader::parse_manifest("id: [", Path::new("plugin.yaml")).unwrap_err();
+    assert!(invalid.to_string().contains("Failed to parse manifest YAML"), "{}", invalid);
+    let bad_range = "id = \"a\"\nname = \"A\"\nversion = \"1.0.0\"\ndescription = \"\"\nauthor = \"\"\n[[incompatible_with]]\nid = \"b\"\nversion_range = \"~>1\"\n";
+    let error = PluginLoader::parse_manifest(bad_range, Path::new("plugin.toml")).unwrap_err();
+    assert!(error.to_string().contains("incompatibility version range '~>1'"), "{}", error);
+    let empty_claim = "id = \"a\"\nname = \"A\"\nversion = \"1.0.0\"\ndescription = \"\"\nauthor = \"\"\n[[resources]]\ntype = \"file_path\"\nidentifier = \"\"\naccess = \"shared_read\"\n";
+    let error = PluginLoader::parse_manifest(empty_claim, Path::new("plugin.toml")).unwrap_err();
+    assert!(error.to_string().contains("'file_path' has an empty identifier"), "{}", error);
+}
+
+#[tokio::test]
+#[cfg(all(feature = "toml-config", feature = "yaml-config"))]
+async fn test_scan_finds_toml_and_yaml_manifests() {
+    let dir = tempdir().unwrap();
+    for (format, file_name) in formats() {
+        let id = format!("plugin-{}", format.extension());
+        let manifest = PluginManifest::new(&id, &id, "1.0.0", "", "");
+        let plugin_dir = dir.path().join(&id);
+        std::fs::create_dir(&plugin_dir).unwrap();
+        std::fs::write(plugin_dir.join(file_name), manifest.to_manifest_string(format).unwrap()).unwrap();
+    }
+    // manifest.json wins over plugin.toml in the same directory
+    let both = dir.path().join("both");
+    std::fs::create_dir(&both).unwrap();
+    std::fs::write(both.join("manifest.json"), PluginManifest::new("from-json", "J", "1.0.0", "", "").to_manifest_string(ConfigFormat::Json).unwrap()).unwrap();
+    std::fs::write(both.join("plugin.toml"), PluginManifest::new("from-toml", "T", "1.0.0", "", "").to_manifest_string(ConfigFormat::Toml).unwrap()).unwrap();
+
+    let mut loader = PluginLoader::new();
+    loader.add_plugin_dir(dir.path());
+    let mut ids: Vec<String> = loader.scan_for_manifests().await.unwrap().into_iter().map(|manifest| manifest.id).collect();
+    ids.sort();
+
+    assert_eq!(ids, vec!["from-json", "plugin-json", "plugin-toml", "plugin-yaml"]);
+    assert_eq!(PluginManifest::find_file(&both), Some(both.join("manifest.json")));
+}
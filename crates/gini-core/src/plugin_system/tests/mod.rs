@@ -8,3 +8,171 @@ pub mod preflight_tests;
 pub mod loading_tests;
 pub mod manager_tests; // Added manager tests
 pub mod ffi_failure_tests;
+pub mod search_path_tests;
+pub mod lazy_tests;
+pub mod ffi_conformance_tests;
+pub mod ffi_export_tests;
+pub mod sandbox_tests;
+pub mod permission_tests;
+pub mod package_tests;
+pub mod signature_tests;
+pub mod resolver_tests;
+pub mod lifecycle_tests;
+pub mod startup_tests;
+pub mod service_tests;
+pub mod config_schema_tests;
+pub mod manifest_format_tests;
+pub mod lint_tests;
+pub mod priority_tests;
+#[cfg(feature = "wasm-plugins")]
+pub mod wasm_tests;
+
+// --- Shared helpers ---
+
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::dependency::PluginDependency;
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::manifest::{ManifestBuilder, PluginManifest};
+use crate::plugin_system::registry::PluginRegistry;
+use crate::plugin_system::service::ServiceRequirement;
+use crate::plugin_system::traits::{Plugin, PluginPriority};
+use crate::plugin_system::version::{ApiVersion, VersionRange};
+use crate::stage_manager::registry::StageRegistry;
+use crate::stage_manager::requirement::StageRequirement;
+use crate::storage::schema::ConfigSchema;
+
+type InitHook = Box<dyn Fn(&str, &mut Application) -> Result<(), PluginSystemError> + Send + Sync>;
+type StagesHook = Box<dyn Fn(&str, &mut StageRegistry) -> Result<(), PluginSystemError> + Send + Sync>;
+type ShutdownHook = Box<dyn Fn(&str) -> Result<(), PluginSystemError> + Send + Sync>;
+
+/// Configurable plugin for registry tests. By default a third-party plugin (priority 151)
+/// compatible with API 0.1.0 whose lifecycle methods succeed without doing anything;
+/// hooks receive the plugin's ID.
+pub(crate) struct TestPlugin {
+    id: String,
+    is_core: bool,
+    priority: PluginPriority,
+    dependencies: Vec<PluginDependency>,
+    required_services: Vec<ServiceRequirement>,
+    config_schema: Option<ConfigSchema>,
+    on_init: Option<InitHook>,
+    on_register_stages: Option<StagesHook>,
+    on_shutdown: Option<ShutdownHook>,
+}
+
+impl TestPlugin {
+    pub(crate) fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            is_core: false,
+            priority: PluginPriority::ThirdParty(151),
+            dependencies: vec![],
+            required_services: vec![],
+            config_schema: None,
+            on_init: None,
+            on_register_stages: None,
+            on_shutdown: None,
+        }
+    }
+
+    /// Marks the plugin as core, with core priority
+    pub(crate) fn core(mut self) -> Self {
+        self.is_core = true;
+        self.priority = PluginPriority::Core(50);
+        self
+    }
+
+    pub(crate) fn priority(mut self, priority: PluginPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Adds a required dependency on any version of `dependency`
+    pub(crate) fn requiring(mut self, dependency: &str) -> Self {
+        self.dependencies.push(PluginDependency::required_any(dependency));
+        self
+    }
+
+    pub(crate) fn requiring_service(mut self, requirement: ServiceRequirement) -> Self {
+        self.required_services.push(requirement);
+        self
+    }
+
+    pub(crate) fn config_schema(mut self, schema: ConfigSchema) -> Self {
+        self.config_schema = Some(schema);
+        self
+    }
+
+    pub(crate) fn on_init(mut self, hook: impl Fn(&str, &mut Application) -> Result<(), PluginSystemError> + Send + Sync + 'static) -> Self {
+        self.on_init = Some(Box::new(hook));
+        self
+    }
+
+    pub(crate) fn on_register_stages(mut self, hook: impl Fn(&str, &mut StageRegistry) -> Result<(), PluginSystemError> + Send + Sync + 'static) -> Self {
+        self.on_register_stages = Some(Box::new(hook));
+        self
+    }
+
+    pub(crate) fn on_shutdown(mut self, hook: impl Fn(&str) -> Result<(), PluginSystemError> + Send + Sync + 'static) -> Self {
+        self.on_shutdown = Some(Box::new(hook));
+        self
+    }
+
+    /// Makes `init` fail with an `InitializationError`
+    pub(crate) fn failing_init(self) -> Self {
+        self.on_init(|id, _app| Err(PluginSystemError::InitializationError {
+            plugin_id: id.to_string(),
+            message: "init failed on purpose".to_string(),
+            source: None,
+        }))
+    }
+}
+
+#[async_trait]
+impl Plugin for TestPlugin {
+    fn name(&self) -> &str { &self.id }
+    fn version(&self) -> &str { "1.0.0" }
+    fn is_core(&self) -> bool { self.is_core }
+    fn priority(&self) -> PluginPriority { self.priority.clone() }
+    fn compatible_api_versions(&self) -> Vec<VersionRange> { vec![VersionRange::from_str(">=0.1.0").unwrap()] }
+    fn dependencies(&self) -> Vec<PluginDependency> { self.dependencies.clone() }
+    fn required_stages(&self) -> Vec<StageRequirement> { vec![] }
+    fn required_services(&self) -> Vec<ServiceRequirement> { self.required_services.clone() }
+    fn config_schema(&self) -> Option<ConfigSchema> { self.config_schema.clone() }
+    fn init(&self, app: &mut Application) -> Result<(), PluginSystemError> {
+        self.on_init.as_ref().map_or(Ok(()), |hook| hook(&self.id, app))
+    }
+    fn register_stages(&self, registry: &mut StageRegistry) -> Result<(), PluginSystemError> {
+        self.on_register_stages.as_ref().map_or(Ok(()), |hook| hook(&self.id, registry))
+    }
+    fn shutdown(&self) -> Result<(), PluginSystemError> {
+        self.on_shutdown.as_ref().map_or(Ok(()), |hook| hook(&self.id))
+    }
+    fn conflicts_with(&self) -> Vec<String> { vec![] }
+    fn incompatible_with(&self) -> Vec<PluginDependency> { vec![] }
+}
+
+/// A registry for API version 0.1.0 with `plugins` registered.
+pub(crate) fn registry_with<P: Plugin + 'static>(plugins: impl IntoIterator<Item = P>) -> PluginRegistry {
+    let mut registry = PluginRegistry::new(ApiVersion::from_str("0.1.0").unwrap());
+    for plugin in plugins {
+        registry.register_plugin(Arc::new(plugin)).unwrap();
+    }
+    registry
+}
+
+/// Builds a manifest for `id` whose entry point lives in `base_dir`.
+pub(crate) fn manifest_in(base_dir: &Path, id: &str, entry_point: &str) -> PluginManifest {
+    let mut manifest = ManifestBuilder::new(id, id, "0.1.0")
+        .api_version(VersionRange::from_str(">=0.1.0").unwrap())
+        .entry_point(entry_point)
+        .build();
+    manifest.plugin_base_dir = base_dir.to_path_buf();
+    manifest
+}
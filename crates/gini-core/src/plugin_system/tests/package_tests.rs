@@ -0,0 +1,264 @@
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "toml-config")]
+use std::str::FromStr;
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde_json::json;
+use tempfile::{tempdir, TempDir};
+
+use crate::plugin_system::error::PluginSystemError;
+#[cfg(feature = "toml-config")]
+use crate::plugin_system::manifest::PluginManifest;
+use crate::plugin_system::package::{PluginArchive, PluginInstaller};
+use crate::plugin_system::version::ApiVersion;
+#[cfg(feature = "toml-config")]
+use crate::plugin_system::version::VersionRange;
+#[cfg(feature = "toml-config")]
+use crate::storage::config::ConfigFormat;
+
+/// Writes a plugin directory with a manifest, a stand-in library and a data file,
+/// packs it, and returns the archive path.
+fn make_archive(root: &Path, id: &str, version: &str, extra: serde_json::Value) -> PathBuf {
+    let plugin_dir = root.join(format!("src-{}-{}", id, version));
+    fs::create_dir_all(plugin_dir.join("data")).unwrap();
+    let mut manifest = json!({
+        "id": id,
+        "name": format!("{} plugin", id),
+        "version": version,
+        "description": "Packaged test plugin",
+        "author": "Test",
+        "api_versions": [">=0.1.0"],
+        "entry_point": format!("lib{}.so", id),
+        "files": ["data/defaults.json"],
+    });
+    if let (Some(manifest), Some(extra)) = (manifest.as_object_mut(), extra.as_object()) {
+        manifest.extend(extra.clone());
+    }
+    fs::write(plugin_dir.join("manifest.json"), manifest.to_string()).unwrap();
+    fs::write(plugin_dir.join(format!("lib{}.so", id)), format!("library {}", version)).unwrap();
+    fs::write(plugin_dir.join("data/defaults.json"), "{}").unwrap();
+
+    let archive_path = root.join(format!("{}-{}.tar.gz", id, version));
+    PluginArchive::create(&plugin_dir, &archive_path).expect("Failed to pack test plugin");
+    archive_path
+}
+
+fn installer_in(root: &TempDir) -> PluginInstaller {
+    PluginInstaller::for_data_dir(&root.path().join("data"))
+}
+
+#[test]
+fn test_create_and_open_archive() {
+    let root = tempdir().unwrap();
+    let archive_path = make_archive(root.path(), "packaged", "1.0.0", json!({}));
+
+    let archive = PluginArchive::open(&archive_path).unwrap();
+    assert_eq!(archive.manifest().id, "packaged");
+    assert_eq!(archive.file_names().collect::<Vec<_>>(), vec!["data/defaults.json", "libpackaged.so", "manifest.json"]);
+    assert!(archive.check_api_version(&ApiVersion::new(0, 1, 0)).is_ok());
+    assert!(matches!(
+        archive.check_api_version(&ApiVersion::new(0, 0, 9)),
+        Err(PluginSystemError::InstallError { .. })
+    ));
+}
+
+#[test]
+fn test_archive_rejects_undeclared_and_special_entries() {
+    let root = tempdir().unwrap();
+    let manifest = json!({
+        "id": "sneaky", "name": "Sneaky", "version": "1.0.0", "description": "", "author": "",
+        "api_versions": [">=0.1.0"], "entry_point": "libsneaky.so",
+    })
+    .to_string();
+
+    let write_archive = |name: &str, add_extra: &dyn Fn(&mut tar::Builder<GzEncoder<File>>)| -> PathBuf {
+        let path = root.path().join(name);
+        let mut builder = tar::Builder::new(GzEncoder::new(File::create(&path).unwrap(), Compression::default()));
+        for (entry_name, content) in [("manifest.json", manifest.as_bytes()), ("libsneaky.so", b"lib".as_slice())] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, entry_name, content).unwrap();
+        }
+        add_extra(&mut builder);
+        builder.into_inner().unwrap().finish().unwrap();
+        path
+    };
+
+    let valid = write_archive("valid.tar.gz", &|_| {});
+    assert!(PluginArchive::open(&valid).is_ok());
+
+    let undeclared = write_archive("undeclared.tar.gz", &|builder| {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(2);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder.append_data(&mut header, "hook.sh", b"ls".as_slice()).unwrap();
+    });
+    match PluginArchive::open(&undeclared) {
+        Err(PluginSystemError::ArchiveError { message, .. }) => assert!(message.contains("hook.sh"), "{}", message),
+        other => panic!("Expected ArchiveError, got {:?}", other),
+    }
+
+    let symlink = write_archive("symlink.tar.gz", &|builder| {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_size(0);
+        header.set_cksum();
+        builder.append_link(&mut header, "data/defaults.json", "/etc/passwd").unwrap();
+    });
+    assert!(matches!(PluginArchive::open(&symlink), Err(PluginSystemError::ArchiveError { .. })));
+}
+
+#[tokio::test]
+async fn test_install_and_uninstall() {
+    let root = tempdir().unwrap();
+    let installer = installer_in(&root);
+    let archive_path = make_archive(root.path(), "packaged", "1.0.0", json!({}));
+
+    let outcome = installer.install(&archive_path).await.unwrap();
+    let plugin_dir = root.path().join("data/plugins/third_party/packaged");
+    assert_eq!(outcome.manifest.plugin_base_dir, plugin_dir);
+    assert!(outcome.previous_version.is_none());
+    assert!(plugin_dir.join("libpackaged.so").is_file());
+    assert!(plugin_dir.join("data/defaults.json").is_file());
+    assert_eq!(installer.installed().await.unwrap().len(), 1);
+    assert_eq!(fs::read_dir(installer.install_dir()).unwrap().count(), 1, "Staging directory was not cleaned up");
+
+    match installer.install(&archive_path).await {
+        Err(PluginSystemError::InstallError { message, .. }) => assert!(message.contains("already installed"), "{}", message),
+        other => panic!("Expected a second install to fail, got {:?}", other),
+    }
+
+    let removed = installer.uninstall("packaged").await.unwrap();
+    assert_eq!(removed.version, "1.0.0");
+    assert!(!plugin_dir.exists());
+    assert!(installer.uninstall("packaged").await.is_err());
+}
+
+#[tokio::test]
+#[cfg(feature = "toml-config")]
+async fn test_pack_and_install_toml_manifest() {
+    let root = tempdir().unwrap();
+    let plugin_dir = root.path().join("src-toml");
+    fs::create_dir_all(&plugin_dir).unwrap();
+    let mut manifest = PluginManifest::new("toml-packaged", "TOML Plugin", "1.0.0", "Packaged with a TOML manifest", "Test");
+    manifest.api_versions = vec![VersionRange::from_str(">=0.1.0").unwrap()];
+    manifest.entry_point = "libtoml_packaged.so".to_string();
+    fs::write(plugin_dir.join("plugin.toml"), manifest.to_manifest_string(ConfigFormat::Toml).unwrap()).unwrap();
+    fs::write(plugin_dir.join("libtoml_packaged.so"), "library").unwrap();
+
+    let archive_path = root.path().join("toml-packaged-1.0.0.tar.gz");
+    let archive = PluginArchive::create(&plugin_dir, &archive_path).unwrap();
+    assert_eq!(archive.file_names().collect::<Vec<_>>(), vec!["libtoml_packaged.so", "plugin.toml"]);
+
+    let installer = installer_in(&root);
+    let outcome = installer.install(&archive_path).await.unwrap();
+    assert_eq!(outcome.manifest.id, "toml-packaged");
+    assert!(outcome.manifest.plugin_base_dir.join("plugin.toml").is_file());
+    assert_eq!(installer.installed().await.unwrap().len(), 1);
+    installer.uninstall("toml-packaged").await.unwrap();
+
+    // A second manifest is not part of the archive format
+    fs::write(plugin_dir.join("manifest.json"), manifest.to_manifest_string(ConfigFormat::Json).unwrap()).unwrap();
+    let mixed = root.path().join("mixed.tar.gz");
+    let file = File::create(&mixed).unwrap();
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    for name in ["manifest.json", "plugin.toml", "libtoml_packaged.so"] {
+        builder.append_path_with_name(plugin_dir.join(name), name).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap();
+    match PluginArchive::open(&mixed) {
+        Err(PluginSystemError::ArchiveError { message, .. }) => assert!(message.contains("plugin.toml"), "{}", message),
+        other => panic!("Expected ArchiveError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_install_rejects_incompatible_api_and_critical_conflicts() {
+    let root = tempdir().unwrap();
+    let installer = installer_in(&root);
+
+    let future_api = make_archive(root.path(), "future", "1.0.0", json!({ "api_versions": [">=9.0.0"] }));
+    assert!(matches!(installer.install(&future_api).await, Err(PluginSystemError::InstallError { .. })));
+
+    // The search directories count as installed plugins
+    let core_dir = root.path().join("core");
+    fs::create_dir_all(core_dir.join("port-owner")).unwrap();
+    fs::write(
+        core_dir.join("port-owner/manifest.json"),
+        json!({
+            "id": "port-owner", "name": "Port Owner", "version": "1.0.0", "description": "", "author": "",
+            "resources": [{ "type": "network_port", "identifier": "8080", "access": "exclusive_write" }],
+        })
+        .to_string(),
+    )
+    .unwrap();
+    let installer = installer.with_search_dirs([core_dir]);
+    let conflicting = make_archive(
+        root.path(),
+        "port-taker",
+        "1.0.0",
+        json!({ "resources": [{ "type": "network_port", "identifier": "8080", "access": "shared_read" }] }),
+    );
+    match installer.install(&conflicting).await {
+        Err(PluginSystemError::UnresolvedPluginConflicts { conflicts }) => {
+            assert_eq!(conflicts.len(), 1);
+            assert_eq!(conflicts[0].first_plugin, "port-owner");
+        }
+        other => panic!("Expected a critical conflict, got {:?}", other),
+    }
+    assert!(installer.installed().await.unwrap().is_empty(), "Nothing is written when validation fails");
+}
+
+#[tokio::test]
+async fn test_upgrade_replaces_installed_version() {
+    let root = tempdir().unwrap();
+    let installer = installer_in(&root);
+    let v1 = make_archive(root.path(), "packaged", "1.0.0", json!({}));
+    let v2 = make_archive(root.path(), "packaged", "1.1.0", json!({}));
+
+    assert!(installer.upgrade(&v2).await.is_err(), "Only installed plugins can be upgraded");
+    installer.install(&v1).await.unwrap();
+
+    let outcome = installer.upgrade(&v2).await.unwrap();
+    assert_eq!(outcome.previous_version.as_deref(), Some("1.0.0"));
+    assert_eq!(outcome.manifest.version, "1.1.0");
+    let library = installer.install_dir().join("packaged/libpackaged.so");
+    assert_eq!(fs::read_to_string(library).unwrap(), "library 1.1.0");
+    assert_eq!(fs::read_dir(installer.install_dir()).unwrap().count(), 1, "Previous version was not cleaned up");
+
+    match installer.upgrade(&v1).await {
+        Err(PluginSystemError::InstallError { message, .. }) => assert!(message.contains("not newer"), "{}", message),
+        other => panic!("Expected a downgrade to fail, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_dependents_block_uninstall_and_incompatible_upgrades() {
+    let root = tempdir().unwrap();
+    let installer = installer_in(&root);
+    installer.install(make_archive(root.path(), "base", "1.0.0", json!({}))).await.unwrap();
+    installer
+        .install(make_archive(
+            root.path(),
+            "addon",
+            "1.0.0",
+            json!({ "dependencies": [{ "id": "base", "version_range": "^1.0.0", "required": true }] }),
+        ))
+        .await
+        .unwrap();
+
+    match installer.uninstall("base").await {
+        Err(PluginSystemError::InstallError { message, .. }) => assert!(message.contains("addon"), "{}", message),
+        other => panic!("Expected uninstall to be blocked, got {:?}", other),
+    }
+    assert!(installer.upgrade(make_archive(root.path(), "base", "2.0.0", json!({}))).await.is_err());
+    assert!(installer.upgrade(make_archive(root.path(), "base", "1.2.0", json!({}))).await.is_ok());
+
+    installer.uninstall("addon").await.unwrap();
+    installer.uninstall("base").await.unwrap();
+}
@@ -0,0 +1,265 @@
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tempfile::tempdir;
+
+use crate::event::dispatcher::sync_event_handler;
+use crate::event::{DefaultEventManager, Event, EventManager, EventResult, SystemEvent};
+use crate::plugin_system::conflict::{ResourceAccessType, ResourceClaim, ResourceIdentifier};
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::manifest::{self, ManifestBuilder};
+use crate::plugin_system::service::ServiceRegistry;
+use crate::plugin_system::permission::{Access, Capability, PermissionEnforcement, PermissionManager, PermissionSet, PluginAccess};
+use crate::plugin_system::registry::PluginRegistry;
+use crate::plugin_system::traits::PluginPriority;
+use crate::plugin_system::version::{ApiVersion, VersionRange};
+use crate::stage_manager::context::StageContext;
+use crate::storage::config::{ConfigData, ConfigFormat, ConfigManager, ConfigScope};
+use crate::storage::error::StorageSystemError;
+use crate::storage::local::LocalStorageProvider;
+
+fn claim(kind: &str, id: &str, access_type: ResourceAccessType) -> ResourceClaim {
+    ResourceClaim {
+        resource: ResourceIdentifier { kind: kind.to_string(), id: id.to_string() },
+        access_type,
+    }
+}
+
+/// A `PluginAccess` for `plugin_id` backed by storage and configuration in `root`.
+fn access_in(root: &Path, plugin_id: &str, claims: &[ResourceClaim]) -> (PluginAccess, Arc<DefaultEventManager>) {
+    let permissions = Arc::new(PermissionManager::new());
+    permissions.set_data_dir(root.join("data"));
+    permissions.register(plugin_id, claims);
+    let provider = Arc::new(LocalStorageProvider::new(root.to_path_buf()));
+    let config_manager = Arc::new(ConfigManager::new(
+        provider.clone(),
+        root.join("config"),
+        root.join("config/plugins"),
+        ConfigFormat::Json,
+    ));
+    let event_manager = Arc::new(DefaultEventManager::new());
+    permissions.set_event_manager(event_manager.clone());
+    let access = PluginAccess::new(
+        plugin_id,
+        permissions,
+        provider,
+        config_manager,
+        Some(event_manager.clone()),
+        Arc::new(ServiceRegistry::new()),
+    );
+    (access, event_manager)
+}
+
+#[test]
+fn test_permission_set_from_claims() {
+    let set = PermissionSet::from_claims(
+        "perm_plugin",
+        &[
+            claim("context_key", "shared_key", ResourceAccessType::SharedRead),
+            claim("event", "perm.done", ResourceAccessType::ProvidesUniqueId),
+            claim("network_port", "8080", ResourceAccessType::ExclusiveWrite), // Not a capability
+        ],
+    );
+    assert_eq!(set.grants().len(), 3, "Two declared capabilities plus the plugin's own config");
+    assert!(set.grants().iter().any(|grant| grant.implicit && grant.identifier == "plugin:perm_plugin"));
+
+    assert!(set.allows(Capability::ContextKey, "shared_key", Access::Read, None));
+    assert!(!set.allows(Capability::ContextKey, "shared_key", Access::Write, None), "SharedRead does not grant writes");
+    assert!(set.allows(Capability::Event, "perm.done", Access::Write, None));
+    assert!(!set.allows(Capability::Event, "perm.other", Access::Write, None));
+    assert!(set.allows(Capability::ConfigScope, "plugin:perm_plugin", Access::Write, None));
+    assert!(!set.allows(Capability::ConfigScope, "app:core_settings", Access::Read, None));
+}
+
+#[test]
+fn test_storage_path_claims_cover_subpaths() {
+    let set = PermissionSet::from_claims(
+        "perm_plugin",
+        &[claim("storage_path", "cache/perm_plugin", ResourceAccessType::ExclusiveWrite)],
+    );
+    let data_dir = Some(Path::new("/var/lib/gini"));
+    assert!(set.allows(Capability::StoragePath, "cache/perm_plugin/index.json", Access::Write, data_dir));
+    assert!(set.allows(Capability::StoragePath, "/var/lib/gini/cache/perm_plugin", Access::Read, data_dir));
+    assert!(!set.allows(Capability::StoragePath, "cache/other_plugin/index.json", Access::Read, data_dir));
+    assert!(!set.allows(Capability::StoragePath, "cache/perm_plugin_evil", Access::Read, data_dir));
+}
+
+#[tokio::test]
+async fn test_denied_access_is_reported_and_audited() {
+    let permissions = PermissionManager::new();
+    permissions.register("perm_plugin", &[claim("context_key", "allowed", ResourceAccessType::SharedRead)]);
+
+    let event_manager = Arc::new(DefaultEventManager::new());
+    let audited = Arc::new(AtomicUsize::new(0));
+    let audited_clone = audited.clone();
+    event_manager
+        .register_sync_handler("plugin.permission_denied", move |_event| {
+            audited_clone.fetch_add(1, Ordering::SeqCst);
+            EventResult::Continue
+        })
+        .await;
+    permissions.set_event_manager(event_manager.clone());
+
+    permissions.check("perm_plugin", Capability::ContextKey, "allowed", Access::Read).unwrap();
+    permissions.check("perm_plugin", Capability::ContextKey, "allowed", Access::Read).unwrap();
+    match permissions.check("perm_plugin", Capability::ContextKey, "secret", Access::Read) {
+        Err(PluginSystemError::PermissionDenied { plugin_id, resource_type, identifier, access }) => {
+            assert_eq!(plugin_id, "perm_plugin");
+            assert_eq!(resource_type, "context_key");
+            assert_eq!(identifier, "secret");
+            assert_eq!(access, "read");
+        }
+        other => panic!("Expected PermissionDenied, got {:?}", other),
+    }
+    assert!(permissions.check("unknown_plugin", Capability::Event, "anything", Access::Write).is_err(),
+        "Plugins without a permission set are denied");
+
+    tokio::task::yield_now().await; // Let the audit events be queued
+    event_manager.process_queue().await;
+    assert_eq!(audited.load(Ordering::SeqCst), 2);
+
+    let report = permissions.report("perm_plugin").expect("Report for a registered plugin");
+    let allowed = &report.accesses[&(Capability::ContextKey, "allowed".to_string(), Access::Read)];
+    assert_eq!((allowed.allowed, allowed.denied), (2, 0));
+    let denied: Vec<_> = report.denied().collect();
+    assert_eq!(denied.len(), 1);
+    assert_eq!(denied[0].0 .1, "secret");
+    let text = report.to_string();
+    assert!(text.contains("granted read context_key 'allowed'"), "Unexpected report: {}", text);
+    assert!(text.contains("0 allowed, 1 denied"), "Unexpected report: {}", text);
+}
+
+#[test]
+fn test_registry_grants_declared_resources() {
+    let mut registry = PluginRegistry::new(ApiVersion::from_str("0.1.0").unwrap());
+    let manifest = ManifestBuilder::new("perm_manifest", "Perm Manifest", "0.1.0")
+        .api_version(VersionRange::from_str(">=0.1.0").unwrap())
+        .resource("event", "perm.ready", manifest::ResourceAccessType::ProvidesUniqueId)
+        .build();
+    registry.register_manifest(manifest).unwrap();
+
+    let permissions = registry.permissions().clone();
+    assert!(permissions.is_allowed("perm_manifest", Capability::Event, "perm.ready", Access::Write));
+    assert_eq!(permissions.reports().len(), 1);
+
+    registry.unregister_plugin("perm_manifest").unwrap();
+    assert!(!permissions.is_allowed("perm_manifest", Capability::Event, "perm.ready", Access::Write));
+    assert!(permissions.reports().is_empty());
+}
+
+#[test]
+fn test_reports_say_how_far_permissions_are_enforced() {
+    let permissions = PermissionManager::new();
+    permissions.register("native_plugin", &[]);
+    permissions.register("wasm_plugin", &[]);
+    permissions.set_enforcement("wasm_plugin", PermissionEnforcement::Enforced);
+
+    assert_eq!(permissions.enforcement("native_plugin"), PermissionEnforcement::NotSandboxed, "Native plugins are the default");
+    let native = permissions.report("native_plugin").unwrap().to_string();
+    assert!(native.contains("enforcement: not sandboxed"), "Unexpected report: {}", native);
+    let wasm = permissions.report("wasm_plugin").unwrap().to_string();
+    assert!(wasm.contains("enforcement: enforced"), "Unexpected report: {}", wasm);
+
+    permissions.unregister("wasm_plugin");
+    assert_eq!(permissions.enforcement("wasm_plugin"), PermissionEnforcement::NotSandboxed);
+}
+
+#[test]
+fn test_plugin_access_storage_is_restricted() {
+    let root = tempdir().unwrap();
+    let (access, _) = access_in(
+        root.path(),
+        "perm_plugin",
+        &[
+            claim("storage_path", "perm_plugin", ResourceAccessType::ExclusiveWrite),
+            claim("storage_path", "shared", ResourceAccessType::SharedRead),
+        ],
+    );
+    let storage = access.storage();
+
+    storage.create_dir_all(Path::new("perm_plugin")).unwrap();
+    storage.write_string(Path::new("perm_plugin/state.txt"), "ok").unwrap();
+    assert_eq!(storage.read_to_string(Path::new("perm_plugin/state.txt")).unwrap(), "ok");
+    assert!(root.path().join("data/perm_plugin/state.txt").exists(), "Relative paths resolve against the data dir");
+
+    assert!(matches!(
+        storage.write_string(Path::new("shared/state.txt"), "no"),
+        Err(StorageSystemError::AccessDenied(..))
+    ));
+    assert!(matches!(
+        storage.read_to_string(Path::new("perm_plugin/../other/state.txt")),
+        Err(StorageSystemError::AccessDenied(..))
+    ));
+    assert!(!storage.exists(&PathBuf::from("/etc/passwd")), "Undeclared paths are invisible");
+}
+
+#[tokio::test]
+async fn test_plugin_access_registers_handlers_under_its_id() {
+    let root = tempdir().unwrap();
+    let (access, event_manager) = access_in(root.path(), "perm_plugin", &[]);
+    event_manager.set_plugin_priority("perm_plugin", PluginPriority::Core(10)).await;
+    event_manager.set_plugin_priority("rival", PluginPriority::ThirdPartyLow(200)).await;
+
+    let handled = Arc::new(Mutex::new(Vec::new()));
+    let recorder = |name: &'static str| {
+        let handled = handled.clone();
+        sync_event_handler(move |_| {
+            handled.lock().unwrap().push(name);
+            EventResult::Continue
+        })
+    };
+    let event_name = SystemEvent::ApplicationStart.name();
+    event_manager.register_plugin_handler("rival", event_name, recorder("rival")).await;
+    access.register_event_handler(event_name, recorder("perm_plugin")).await.unwrap();
+
+    // The handler runs with perm_plugin's Core priority, ahead of the earlier third-party one
+    event_manager.dispatch(&SystemEvent::ApplicationStart).await;
+    assert_eq!(*handled.lock().unwrap(), vec!["perm_plugin", "rival"]);
+}
+
+#[tokio::test]
+async fn test_plugin_access_config_events_and_context() {
+    let root = tempdir().unwrap();
+    let (access, event_manager) = access_in(
+        root.path(),
+        "perm_plugin",
+        &[
+            claim("config_scope", "app:shared_settings", ResourceAccessType::SharedRead),
+            claim("event", "perm.done", ResourceAccessType::ProvidesUniqueId),
+            claim("context_key", "perm.result", ResourceAccessType::ExclusiveWrite),
+        ],
+    );
+
+    // Own configuration is always available
+    let mut own = ConfigData::new();
+    own.set("level", 3).unwrap();
+    access.save_config("perm_plugin", &own, ConfigScope::Plugin(crate::storage::config::PluginConfigScope::User)).unwrap();
+    assert_eq!(access.plugin_config().unwrap().get::<i32>("level"), Some(3));
+
+    assert!(access.load_config("shared_settings", ConfigScope::Application).is_ok());
+    assert!(matches!(
+        access.save_config("shared_settings", &own, ConfigScope::Application),
+        Err(PluginSystemError::PermissionDenied { .. })
+    ));
+    assert!(matches!(
+        access.load_config("core_settings", ConfigScope::Application),
+        Err(PluginSystemError::PermissionDenied { .. })
+    ));
+
+    access.emit_event("perm.done", "{}").await.unwrap();
+    assert!(access.emit_event("perm.undeclared", "{}").await.is_err());
+
+    let mut context = StageContext::new_live(PathBuf::from("/tmp"));
+    access.set_context_data(&mut context, "perm.result", 42u32).unwrap();
+    assert_eq!(access.context_data::<u32>(&context, "perm.result").unwrap(), Some(&42));
+    assert!(access.set_context_data(&mut context, "other.key", 1u32).is_err());
+    assert!(context.get_data::<u32>("other.key").is_none());
+
+    tokio::task::yield_now().await;
+    assert!(event_manager.process_queue().await >= 1);
+
+    let report = access.permissions().report("perm_plugin").unwrap();
+    assert_eq!(report.denied().count(), 4);
+}
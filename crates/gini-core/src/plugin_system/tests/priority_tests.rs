@@ -0,0 +1,103 @@
+use std::sync::{Arc, Mutex as StdMutex};
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::event::{Event, EventManager, EventResult, SystemEvent};
+use crate::event::dispatcher::sync_event_handler;
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::tests::{TestPlugin, registry_with};
+use crate::plugin_system::traits::PluginPriority;
+use crate::stage_manager::Stage;
+use crate::stage_manager::context::StageContext;
+use crate::stage_manager::pipeline::StagePipeline;
+use crate::stage_manager::registry::{SharedStageRegistry, StageRegistry};
+
+type Tracker = Arc<StdMutex<Vec<String>>>;
+
+/// Records its plugin's ID when it runs
+struct RecordingStage {
+    id: String,
+    tracker: Tracker,
+}
+
+#[async_trait]
+impl Stage for RecordingStage {
+    fn id(&self) -> &str { &self.id }
+    fn name(&self) -> &str { &self.id }
+    fn description(&self) -> &str { "Records that it ran" }
+    async fn execute(&self, _context: &mut StageContext) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        self.tracker.lock().unwrap().push(self.id.clone());
+        Ok(())
+    }
+}
+
+/// A plugin that registers one stage, `<id>::work`, and records its shutdown
+fn priority_plugin(id: &str, priority: PluginPriority, stages: &Tracker, shutdowns: &Tracker) -> TestPlugin {
+    let (stages, shutdowns) = (stages.clone(), shutdowns.clone());
+    TestPlugin::new(id)
+        .priority(priority)
+        .on_register_stages(move |id, registry| {
+            registry.register_stage(Box::new(RecordingStage { id: format!("{}::work", id), tracker: stages.clone() }))
+                .map_err(|e| PluginSystemError::InternalError(e.to_string()))
+        })
+        .on_shutdown(move |id| {
+            shutdowns.lock().unwrap().push(id.to_string());
+            Ok(())
+        })
+}
+
+#[tokio::test]
+async fn test_priority_orders_handlers_stages_and_shutdown() {
+    let stages: Tracker = Arc::default();
+    let shutdowns: Tracker = Arc::default();
+    // The core plugin depends on the third-party one, which therefore comes up before it
+    let mut registry = registry_with([
+        priority_plugin("priority-test-third", PluginPriority::ThirdParty(151), &stages, &shutdowns),
+        priority_plugin("priority-test-low", PluginPriority::ThirdPartyLow(220), &stages, &shutdowns),
+        priority_plugin("priority-test-core", PluginPriority::Core(60), &stages, &shutdowns).requiring("priority-test-third"),
+        priority_plugin("priority-test-kernel", PluginPriority::Kernel(5), &stages, &shutdowns),
+    ]);
+    let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+    let mut app = Application::new().unwrap();
+
+    registry.initialize_all(&mut app, &stage_registry).await.unwrap();
+    let init_order = vec!["priority-test-kernel", "priority-test-third", "priority-test-core", "priority-test-low"];
+    assert_eq!(registry.initialization_order(), init_order);
+
+    // Handlers inherit the priority recorded for their plugin
+    let handlers: Tracker = Arc::default();
+    let event_manager = app.event_manager();
+    let event_name = SystemEvent::ApplicationStart.name();
+    for plugin_id in ["priority-test-low", "priority-test-third", "priority-test-core", "priority-test-kernel"] {
+        let handlers = handlers.clone();
+        event_manager.register_plugin_handler(plugin_id, event_name, sync_event_handler(move |_| {
+            handlers.lock().unwrap().push(plugin_id.to_string());
+            EventResult::Continue
+        })).await;
+    }
+    event_manager.dispatch(&SystemEvent::ApplicationStart).await;
+    let handled: Vec<String> = handlers.lock().unwrap().iter().filter(|id| id.starts_with("priority-test-")).cloned().collect();
+    assert_eq!(handled, vec!["priority-test-kernel", "priority-test-core", "priority-test-third", "priority-test-low"]);
+
+    // Independent stages run in plugin priority order, whatever order they were added in
+    assert_eq!(stage_registry.lock().await.stage_priority("priority-test-core::work"), Some(&PluginPriority::Core(60)));
+    let mut pipeline = StagePipeline::new("priority-test", "Stages of plugins with different priorities");
+    pipeline.add_stages(&["priority-test-low::work", "priority-test-third::work", "priority-test-core::work", "priority-test-kernel::work"]).unwrap();
+    pipeline.add_dependency("priority-test-core::work", "priority-test-low::work").unwrap();
+    let mut context = StageContext::new_live(std::env::temp_dir());
+    pipeline.execute(&mut context, &SharedStageRegistry { registry: stage_registry.clone() }).await.unwrap();
+    assert_eq!(*stages.lock().unwrap(), vec![
+        "priority-test-kernel::work",
+        "priority-test-low::work",
+        "priority-test-core::work",
+        "priority-test-third::work",
+    ]);
+
+    registry.shutdown_all().unwrap();
+    let mut shutdown_order = init_order.clone();
+    shutdown_order.reverse();
+    assert_eq!(*shutdowns.lock().unwrap(), shutdown_order);
+    assert!(registry.initialization_order().is_empty());
+}
@@ -3,6 +3,7 @@ use crate::plugin_system::version::{ApiVersion, VersionRange};
 use crate::plugin_system::traits::{Plugin, PluginPriority}; // Removed PluginError
 use crate::plugin_system::dependency::PluginDependency;
 use crate::plugin_system::error::PluginSystemError; // Import PluginSystemError
+use crate::plugin_system::startup::InitFailurePolicy;
 use crate::kernel::bootstrap::Application;
 use crate::kernel::error::{Error}; // Removed unused Result as KernelResult
 use crate::stage_manager::context::StageContext;
@@ -159,7 +160,7 @@ fn create_mock_app() -> Application {
         let registry = create_test_registry();
         assert_eq!(registry.plugin_count(), 0);
         assert_eq!(registry.initialized_count(), 0);
-        assert!(registry.enabled.is_empty());
+        assert!(registry.enabled_plugin_ids().is_empty());
         assert_eq!(registry.api_version().to_string(), "0.1.0");
     }
 
@@ -174,7 +175,7 @@ fn create_mock_app() -> Application {
         assert_eq!(registry.plugin_count(), 1);
         assert!(registry.has_plugin(&plugin_id));
         assert!(registry.is_enabled(&plugin_id), "Plugin should be enabled by default");
-        assert!(!registry.initialized.contains(&plugin_id), "Plugin should not be initialized yet");
+        assert!(!registry.is_initialized(&plugin_id), "Plugin should not be initialized yet");
     }
 
     #[tokio::test] // Use tokio::test
@@ -314,13 +315,13 @@ fn create_mock_app() -> Application {
          registry.register_plugin(plugin).unwrap();
  
          assert!(!init_flag.load(Ordering::SeqCst));
-         assert!(!registry.initialized.contains(plugin_id));
+         assert!(!registry.is_initialized(plugin_id));
  
          // Pass the stage_registry_arc
          let result = registry.initialize_plugin(plugin_id, &mut app, &stage_registry_arc).await;
          assert!(result.is_ok());
          assert!(init_flag.load(Ordering::SeqCst), "Plugin init method should have been called");
-         assert!(registry.initialized.contains(plugin_id), "Plugin should be marked as initialized");
+         assert!(registry.is_initialized(plugin_id), "Plugin should be marked as initialized");
  
          // Initialize again (should be no-op)
          let init_flag_before = init_flag.load(Ordering::SeqCst);
@@ -346,7 +347,7 @@ fn create_mock_app() -> Application {
          let result = registry.initialize_plugin(plugin_id, &mut app, &stage_registry_arc).await;
          assert!(result.is_ok(), "Initializing a disabled plugin should succeed (but be a no-op)");
          assert!(!init_flag.load(Ordering::SeqCst), "Init should NOT be called for disabled plugin");
-         assert!(!registry.initialized.contains(plugin_id), "Disabled plugin should not be marked initialized");
+         assert!(!registry.is_initialized(plugin_id), "Disabled plugin should not be marked initialized");
      }
 
      #[tokio::test] // Make test async
@@ -374,9 +375,9 @@ fn create_mock_app() -> Application {
          assert!(!init2.load(Ordering::SeqCst), "Plugin2 should NOT be initialized");
          assert!(init3.load(Ordering::SeqCst), "Plugin3 should be initialized");
 
-         assert!(registry.initialized.contains("plugin1"));
-         assert!(!registry.initialized.contains("plugin2"));
-         assert!(registry.initialized.contains("plugin3"));
+         assert!(registry.is_initialized("plugin1"));
+         assert!(!registry.is_initialized("plugin2"));
+         assert!(registry.is_initialized("plugin3"));
          assert_eq!(registry.initialized_count(), 2);
      }
 
@@ -516,7 +517,7 @@ fn create_mock_app() -> Application {
  
          // Initialize it, passing the stage_registry_arc
          registry.initialize_plugin(plugin_id, &mut app, &stage_registry_arc).await.unwrap();
-         assert!(registry.initialized.contains(plugin_id));
+         assert!(registry.is_initialized(plugin_id));
          assert!(registry.is_enabled(plugin_id));
 
          // Try to disable
@@ -525,7 +526,7 @@ fn create_mock_app() -> Application {
          // If shutdown is successful, disable_plugin returns Ok(()) and the plugin is no longer initialized or enabled.
          assert!(result.is_ok(), "Disabling an initialized plugin should now attempt shutdown and succeed if shutdown works. Error: {:?}", result.err());
          assert!(!registry.is_enabled(plugin_id), "Plugin should be disabled after successful shutdown");
-         assert!(!registry.initialized.contains(plugin_id), "Plugin should not be initialized after successful shutdown");
+         assert!(!registry.is_initialized(plugin_id), "Plugin should not be initialized after successful shutdown");
      }
  
      #[tokio::test] // Use tokio::test
@@ -701,6 +702,7 @@ fn create_mock_app() -> Application {
          // The error should occur during the recursive initialize_plugin call.
          let stage_registry_arc = create_mock_stage_registry_arc(); // Create mock stage registry
          // Pass the stage_registry_arc
+         registry.set_init_failure_policy(InitFailurePolicy::FailFast); // The error is returned instead of isolating A
          let result = registry.initialize_all(&mut app, &stage_registry_arc).await;
          assert!(result.is_err(), "Initialization should fail due to missing dependency B");
          if let Err(Error::PluginSystem(PluginSystemError::DependencyResolution(dep_err))) = result {
@@ -729,6 +731,7 @@ fn create_mock_app() -> Application {
          registry.disable_plugin("B", &stage_registry_arc).await.unwrap(); // Disable B
  
          // Pass the stage_registry_arc
+         registry.set_init_failure_policy(InitFailurePolicy::FailFast); // The error is returned instead of isolating A
          let result = registry.initialize_all(&mut app, &stage_registry_arc).await;
          assert!(result.is_err(), "Initialization should fail due to disabled dependency B");
          if let Err(Error::PluginSystem(PluginSystemError::DependencyResolution(dep_err))) = result {
@@ -994,6 +997,7 @@ fn create_mock_app() -> Application {
         // The transitive check should *not* fail for PluginB because it's not enabled.
         // The failure should occur later during PluginA's individual initialization attempt
         // due to a missing *required* dependency.
+        registry.set_init_failure_policy(InitFailurePolicy::FailFast); // The error is returned instead of isolating PluginA
         let result = registry.initialize_all(&mut app, &stage_registry_arc).await;
         
         assert!(result.is_err(), "Initialization should fail because PluginA's required dependency PluginB is not enabled.");
@@ -1058,7 +1062,7 @@ fn create_mock_app() -> Application {
         let init_result = registry.initialize_plugin(plugin_id, &mut app, &stage_registry_arc).await;
         assert!(init_result.is_ok(), "Plugin initialization failed: {:?}", init_result.err());
         assert!(init_called_flag.load(Ordering::SeqCst), "Plugin init() was not called.");
-        assert!(registry.initialized.contains(plugin_id), "Plugin should be in initialized set.");
+        assert!(registry.is_initialized(plugin_id), "Plugin should be in initialized set.");
         assert!(registry.is_enabled(plugin_id), "Plugin should be enabled after initialization.");
 
         // Disable the initialized plugin
@@ -1067,7 +1071,7 @@ fn create_mock_app() -> Application {
 
         // Assertions
         assert!(shutdown_called_flag.load(Ordering::SeqCst), "Plugin shutdown() was not called.");
-        assert!(!registry.initialized.contains(plugin_id), "Plugin should NOT be in initialized set after disable.");
+        assert!(!registry.is_initialized(plugin_id), "Plugin should NOT be in initialized set after disable.");
         assert!(!registry.is_enabled(plugin_id), "Plugin should NOT be enabled after disable.");
 
         // Verify stages would be unregistered (conceptual check, actual stage unregistration is harder to assert here)
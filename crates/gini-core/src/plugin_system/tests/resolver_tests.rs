@@ -0,0 +1,150 @@
+use std::fs;
+
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::plugin_system::dependency::{DependencyError, PluginDependency};
+use crate::plugin_system::loader::PluginLoader;
+use crate::plugin_system::manifest::{ManifestBuilder, PluginManifest};
+use crate::plugin_system::resolver::{DependencyResolver, Incompatibility};
+use crate::plugin_system::traits::PluginPriority;
+use crate::plugin_system::version::{ApiVersion, VersionRange};
+
+fn range(constraint: &str) -> Option<VersionRange> {
+    Some(VersionRange::from_constraint(constraint).unwrap())
+}
+
+fn plugin(id: &str, version: &str) -> ManifestBuilder {
+    ManifestBuilder::new(id, id, version)
+}
+
+fn requested(ids: &[&str]) -> Vec<PluginDependency> {
+    ids.iter().map(|id| PluginDependency::required_any(id)).collect()
+}
+
+fn selected_versions(manifests: &[PluginManifest]) -> Vec<String> {
+    manifests.iter().map(|manifest| format!("{}@{}", manifest.id, manifest.version)).collect()
+}
+
+#[test]
+fn test_picks_first_version_in_range() {
+    let resolver = DependencyResolver::new([
+        plugin("app", "1.0.0").dependency("lib", range("^1.0.0"), true).build(),
+        plugin("lib", "2.0.0").build(),
+        plugin("lib", "1.5.0").build(),
+        plugin("lib", "1.0.0").build(),
+    ]);
+
+    let resolution = resolver.resolve(&requested(&["app"])).unwrap();
+    assert_eq!(selected_versions(&resolution.selected), vec!["app@1.0.0", "lib@1.5.0"]);
+}
+
+#[test]
+fn test_backtracks_to_an_older_version() {
+    // app 2.0.0 needs lib 2.x, which is incompatible with the requested host 1.x
+    let resolver = DependencyResolver::new([
+        plugin("app", "2.0.0").dependency("lib", range("^2.0.0"), true).build(),
+        plugin("app", "1.0.0").dependency("lib", range("^1.0.0"), true).build(),
+        plugin("lib", "2.0.0").incompatibility("host", range("<2.0.0")).build(),
+        plugin("lib", "1.0.0").build(),
+        plugin("host", "1.0.0").build(),
+    ]);
+
+    let resolution = resolver.resolve(&requested(&["app", "host"])).unwrap();
+    assert_eq!(selected_versions(&resolution.selected), vec!["app@1.0.0", "lib@1.0.0", "host@1.0.0"]);
+}
+
+#[test]
+fn test_optional_dependencies_are_included_when_satisfiable() {
+    let resolver = DependencyResolver::new([
+        plugin("app", "1.0.0")
+            .dependency("metrics", range("^1.0.0"), false)
+            .dependency("tracing", None, false)
+            .dependency("telemetry", range("^2.0.0"), false)
+            .dependency("absent", None, false)
+            .build(),
+        plugin("metrics", "1.2.0").build(),
+        plugin("tracing", "0.3.0").conflict("app").build(),
+        plugin("telemetry", "1.0.0").build(),
+    ]);
+
+    let resolution = resolver.resolve(&requested(&["app"])).unwrap();
+    assert_eq!(selected_versions(&resolution.selected), vec!["app@1.0.0", "metrics@1.2.0"]);
+
+    let skipped: Vec<&str> = resolution.skipped_optional.iter().map(|s| s.dependency.plugin_name.as_str()).collect();
+    assert_eq!(skipped, vec!["tracing", "telemetry", "absent"]);
+    assert!(resolution.skipped_optional[0].reasons.contains(&Incompatibility::Conflict {
+        plugin: "'tracing' 0.3.0".to_string(),
+        conflicts_with: "'app' 1.0.0".to_string(),
+    }));
+}
+
+#[test]
+fn test_unsatisfiable_constraints_are_explained() {
+    let resolver = DependencyResolver::new([
+        plugin("app", "1.0.0").dependency("lib", range("^1.0.0"), true).dependency("db", None, true).build(),
+        plugin("lib", "2.0.0").build(),
+        plugin("ui", "1.0.0").dependency("lib", range("^3.0.0"), true).build(),
+    ]);
+
+    match resolver.resolve(&requested(&["app"])) {
+        Err(DependencyError::Unsatisfiable(reasons)) => assert_eq!(
+            reasons,
+            vec![Incompatibility::VersionMismatch {
+                required_by: "'app' 1.0.0".to_string(),
+                plugin: "'lib' 2.0.0".to_string(),
+                range: "^1.0.0".to_string(),
+            }]
+        ),
+        other => panic!("Expected an unsatisfiable resolution, got {:?}", other),
+    }
+
+    let missing = DependencyResolver::new([plugin("app", "1.0.0").dependency("db", None, true).build()]);
+    let error = missing.resolve(&requested(&["app"])).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "No consistent set of plugins satisfies the constraints: 'app' 1.0.0 requires 'db', which was not found"
+    );
+}
+
+#[test]
+fn test_resolve_all_leaves_out_what_does_not_fit() {
+    let resolver = DependencyResolver::new([
+        plugin("extra", "1.0.0").conflict("base").build(),
+        plugin("base", "1.0.0").priority(PluginPriority::Core(60)).build(),
+        plugin("future", "1.0.0").api_version(range(">=2.0.0").unwrap()).build(),
+        plugin("addon", "1.0.0").dependency("base", range("^1.0.0"), true).build(),
+    ])
+    .with_api_version(ApiVersion::new(0, 1, 0));
+
+    let resolution = resolver.resolve_all();
+    assert_eq!(selected_versions(&resolution.selected), vec!["base@1.0.0", "addon@1.0.0"]);
+
+    let excluded: Vec<&str> = resolution.excluded.iter().map(|e| e.plugin_id.as_str()).collect();
+    assert_eq!(excluded, vec!["extra", "future"], "The higher-priority plugin wins a conflict");
+    assert!(matches!(resolution.excluded[1].reasons[0], Incompatibility::UnsupportedApiVersion { .. }));
+}
+
+#[tokio::test]
+async fn test_loader_returns_every_candidate() {
+    let root = tempdir().unwrap();
+    let first = root.path().join("first");
+    let second = root.path().join("second");
+    for (dir, version) in [(first.join("lib-1"), "1.0.0"), (first.join("lib-2"), "2.0.0"), (second.join("lib"), "3.0.0")] {
+        fs::create_dir_all(&dir).unwrap();
+        let manifest = json!({
+            "id": "lib", "name": "Lib", "version": version, "description": "", "author": "",
+        });
+        fs::write(dir.join("manifest.json"), manifest.to_string()).unwrap();
+    }
+
+    let mut loader = PluginLoader::new();
+    loader.add_plugin_dir(&first);
+    loader.add_plugin_dir(&second);
+    let candidates = loader.scan_for_manifest_candidates().await.unwrap();
+    let versions: Vec<&str> = candidates.iter().map(|manifest| manifest.version.as_str()).collect();
+    assert_eq!(versions, vec!["2.0.0", "1.0.0", "3.0.0"], "Directory precedence first, then newest first");
+
+    let shadowed = loader.scan_for_manifests().await.unwrap();
+    assert_eq!(shadowed.len(), 1);
+}
@@ -0,0 +1,269 @@
+// Tests for out-of-process plugin hosts (`ipc` and `sandbox`).
+// The plugin host processes are this test binary, re-run with only `sandbox_child_host` selected.
+
+use std::env;
+use std::io::Cursor;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::dependency::PluginDependency;
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::ipc::{self, HostRequest, HostResponse, RemoteStageContext, OP_REQUEST};
+use crate::plugin_system::sandbox::{self, SandboxConfig, SandboxedPlugin, HOST_SOCKET_ENV};
+use crate::plugin_system::traits::{Plugin, PluginPriority};
+use crate::plugin_system::version::VersionRange;
+use crate::stage_manager::context::StageContext;
+use crate::stage_manager::registry::StageRegistry;
+use crate::stage_manager::requirement::StageRequirement;
+use crate::stage_manager::Stage;
+
+const CHILD_PLUGIN_ENV: &str = "GINI_SANDBOX_TEST_PLUGIN"; // "builtin" serves SandboxTestPlugin
+const CRASH_MARKER_ENV: &str = "GINI_SANDBOX_TEST_CRASH_MARKER"; // init aborts once, creating this file
+
+/// Plugin served by the test plugin host
+struct SandboxTestPlugin {
+    crash_marker: Option<PathBuf>,
+}
+
+struct EchoStage;
+
+#[async_trait]
+impl Stage for EchoStage {
+    fn id(&self) -> &str { "sandbox-test::echo" }
+    fn name(&self) -> &str { "Echo" }
+    fn description(&self) -> &str { "Succeeds if the greeting CLI argument arrived" }
+    async fn execute(&self, context: &mut StageContext) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        match context.get_cli_arg("greeting") {
+            Some("hello") => Ok(()),
+            other => Err(format!("unexpected greeting {:?}", other).into()),
+        }
+    }
+}
+
+struct FailingStage;
+
+#[async_trait]
+impl Stage for FailingStage {
+    fn id(&self) -> &str { "sandbox-test::fail" }
+    fn name(&self) -> &str { "Fail" }
+    fn description(&self) -> &str { "Always fails" }
+    fn supports_dry_run(&self) -> bool { false }
+    async fn execute(&self, _context: &mut StageContext) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        Err("stage failed on purpose".into())
+    }
+}
+
+#[async_trait]
+impl Plugin for SandboxTestPlugin {
+    fn name(&self) -> &'static str { "sandbox-test" }
+    fn version(&self) -> &str { "0.3.0" }
+    fn is_core(&self) -> bool { false }
+    fn priority(&self) -> PluginPriority { PluginPriority::ThirdParty(170) }
+    fn compatible_api_versions(&self) -> Vec<VersionRange> { vec![">=0.1.0".parse().unwrap()] }
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        vec![PluginDependency::required("base", VersionRange::from_constraint("^1.2").unwrap())]
+    }
+    fn required_stages(&self) -> Vec<StageRequirement> { vec![StageRequirement::provide("sandbox-test::echo")] }
+    fn conflicts_with(&self) -> Vec<String> { vec!["rival".to_string()] }
+    fn incompatible_with(&self) -> Vec<PluginDependency> { vec![PluginDependency::optional_any("legacy")] }
+    fn init(&self, _app: &mut Application) -> std::result::Result<(), PluginSystemError> {
+        if let Some(marker) = &self.crash_marker
+            && !marker.exists()
+        {
+            std::fs::write(marker, b"crashed").unwrap();
+            std::process::abort(); // Not catchable, like a segfault
+        }
+        Ok(())
+    }
+    async fn preflight_check(&self, context: &StageContext) -> std::result::Result<(), PluginSystemError> {
+        tokio::task::yield_now().await;
+        if context.get_cli_arg("fail_preflight").is_some() {
+            return Err(PluginSystemError::PreflightCheckFailed {
+                plugin_id: self.name().to_string(),
+                message: "asked to fail".to_string(),
+            });
+        }
+        Ok(())
+    }
+    fn register_stages(&self, registry: &mut StageRegistry) -> std::result::Result<(), PluginSystemError> {
+        for stage in [Box::new(EchoStage) as Box<dyn Stage>, Box::new(FailingStage)] {
+            registry.register_stage(stage).map_err(|e| PluginSystemError::OperationError {
+                plugin_id: Some(self.name().to_string()),
+                message: e.to_string(),
+            })?;
+        }
+        Ok(())
+    }
+    fn shutdown(&self) -> std::result::Result<(), PluginSystemError> { Ok(()) }
+}
+
+/// Plugin host entry point, only does something when started by `test_config`.
+#[test]
+#[ignore = "plugin host process for the sandbox tests"]
+fn sandbox_child_host() {
+    let Some(socket_path) = env::var_os(HOST_SOCKET_ENV) else { return };
+    let result = if env::var(CHILD_PLUGIN_ENV).as_deref() == Ok("builtin") {
+        let plugin = SandboxTestPlugin { crash_marker: env::var_os(CRASH_MARKER_ENV).map(PathBuf::from) };
+        sandbox::serve_plugin(UnixStream::connect(socket_path).unwrap(), Box::new(plugin))
+    } else {
+        sandbox::run_plugin_host()
+    };
+    result.expect("plugin host failed");
+}
+
+/// Starts this test binary as the plugin host
+fn test_config(env: &[(&str, &str)]) -> SandboxConfig {
+    SandboxConfig {
+        program: env::current_exe().unwrap(),
+        args: ["--exact", "plugin_system::tests::sandbox_tests::sandbox_child_host", "--ignored", "--nocapture", "--test-threads=1"]
+            .iter().map(|arg| arg.to_string()).collect(),
+        env: env.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect(),
+        startup_timeout: Duration::from_secs(30),
+        request_timeout: Duration::from_secs(30),
+        max_restarts: 2,
+    }
+}
+
+fn spawn_builtin(extra_env: &[(&str, &str)]) -> SandboxedPlugin {
+    let mut env = vec![(CHILD_PLUGIN_ENV, "builtin")];
+    env.extend_from_slice(extra_env);
+    SandboxedPlugin::spawn(Path::new("builtin"), test_config(&env)).expect("failed to start plugin host")
+}
+
+// Helper function to find the path to the compiled example plugin
+fn get_example_plugin_path() -> Option<PathBuf> {
+    let current_dir = env::current_dir().expect("Failed to get current directory");
+    let plugin_name = "libcompat_check_example.so";
+    let search_paths = vec![
+        current_dir.join("../../target/debug").join(plugin_name),
+        current_dir.join("target/debug").join(plugin_name),
+        PathBuf::from("./target/debug").join(plugin_name),
+    ];
+    search_paths.into_iter().find(|path| path.exists())
+}
+
+#[test]
+fn test_framed_message_round_trip() {
+    let request = HostRequest::ExecuteStage {
+        stage_id: "plugin::stage".to_string(),
+        context: RemoteStageContext::from_context(&StageContext::new_dry_run(PathBuf::from("/tmp/config"))),
+    };
+    let mut buffer = Vec::new();
+    ipc::write_message(&mut buffer, OP_REQUEST, &request).unwrap();
+    assert_eq!(&buffer[0..4], &OP_REQUEST.to_le_bytes());
+    assert_eq!(u32::from_le_bytes(buffer[4..8].try_into().unwrap()) as usize, buffer.len() - 8);
+
+    let decoded: HostRequest = ipc::read_message(&mut Cursor::new(&buffer), OP_REQUEST).unwrap();
+    assert_eq!(decoded, request);
+
+    // Wrong opcode and oversized length headers are rejected
+    let wrong = ipc::read_message::<_, HostRequest>(&mut Cursor::new(&buffer), ipc::OP_RESPONSE).unwrap_err();
+    assert_eq!(wrong.kind(), std::io::ErrorKind::InvalidData);
+    let mut oversized = ipc::frame_message(OP_REQUEST, "").unwrap();
+    oversized[4..8].copy_from_slice(&(ipc::MAX_FRAME_PAYLOAD + 1).to_le_bytes());
+    let err = ipc::read_framed_message(&mut Cursor::new(&oversized)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_remote_stage_context_round_trip() {
+    let mut context = StageContext::new_dry_run(PathBuf::from("/tmp/config"));
+    context.set_cli_arg("key", "value");
+    let rebuilt = RemoteStageContext::from_context(&context).to_context();
+    assert!(rebuilt.is_dry_run());
+    assert_eq!(rebuilt.config_dir(), &PathBuf::from("/tmp/config"));
+    assert_eq!(rebuilt.get_cli_arg("key"), Some("value"));
+
+    let response = HostResponse::Error { message: "boom".to_string() };
+    let json = serde_json::to_string(&response).unwrap();
+    assert_eq!(json, r#"{"type":"error","message":"boom"}"#);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sandboxed_plugin_forwards_lifecycle() {
+    let plugin = spawn_builtin(&[]);
+    assert_eq!(plugin.name(), "sandbox-test");
+    assert_eq!(plugin.version(), "0.3.0");
+    assert_eq!(plugin.priority(), PluginPriority::ThirdParty(170));
+    assert_eq!(plugin.dependencies()[0].version_range.as_ref().unwrap().constraint_string(), "^1.2");
+    assert!(plugin.incompatible_with()[0].version_range.is_none());
+    assert_eq!(plugin.required_stages(), vec![StageRequirement::provide("sandbox-test::echo")]);
+    assert_eq!(plugin.conflicts_with(), vec!["rival".to_string()]);
+
+    let mut app = Application::new().unwrap();
+    plugin.init(&mut app).expect("init should be forwarded");
+
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let mut context = StageContext::new_live(tmp_dir.path().to_path_buf());
+    plugin.preflight_check(&context).await.expect("preflight should pass");
+    context.set_cli_arg("fail_preflight", "yes");
+    let err = plugin.preflight_check(&context).await.unwrap_err();
+    assert!(matches!(err, PluginSystemError::PreflightCheckFailed { .. }), "got {:?}", err);
+
+    // Stages registered in the host are executed remotely
+    let mut registry = StageRegistry::new();
+    plugin.register_stages(&mut registry).unwrap();
+    assert!(registry.has_stage("sandbox-test::echo"));
+    assert!(!registry.get_stage("sandbox-test::fail").unwrap().supports_dry_run());
+
+    let mut live = StageContext::new_live(tmp_dir.path().to_path_buf());
+    live.set_cli_arg("greeting", "hello");
+    registry.execute_stage_internal("sandbox-test::echo", &mut live).await.expect("echo stage should pass");
+    let err = registry.execute_stage_internal("sandbox-test::fail", &mut live).await.unwrap_err();
+    assert!(err.to_string().contains("sandbox-test::fail"), "got {}", err);
+
+    plugin.shutdown().unwrap();
+    assert!(!plugin.is_running(), "host process should exit after shutdown");
+    assert_eq!(plugin.restart_count(), 0);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_sandboxed_plugin_restarts_after_crash() {
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let marker = tmp_dir.path().join("crashed");
+    let plugin = spawn_builtin(&[(CRASH_MARKER_ENV, marker.to_str().unwrap())]);
+
+    // The first init aborts the host process; the core survives and restarts it
+    let mut app = Application::new().unwrap();
+    let err = plugin.init(&mut app).unwrap_err();
+    assert!(matches!(err, PluginSystemError::HostProcessError { .. }), "got {:?}", err);
+    assert!(marker.exists());
+    assert_eq!(plugin.restart_count(), 1);
+    assert!(plugin.is_running());
+
+    plugin.init(&mut app).expect("init should succeed in the restarted host");
+    let context = StageContext::new_live(tmp_dir.path().to_path_buf());
+    plugin.preflight_check(&context).await.unwrap();
+    plugin.shutdown().unwrap();
+}
+
+#[test]
+fn test_sandboxed_plugin_loads_library_in_host() {
+    let Some(library_path) = get_example_plugin_path() else {
+        println!("Skipping test_sandboxed_plugin_loads_library_in_host: example plugin not built");
+        return;
+    };
+    let plugin = SandboxedPlugin::spawn(&library_path, test_config(&[])).expect("failed to start plugin host");
+    assert_eq!(plugin.name(), "CompatCheckExample");
+    assert!(!plugin.compatible_api_versions().is_empty());
+    plugin.shutdown().unwrap();
+}
+
+#[test]
+fn test_sandboxed_plugin_reports_host_failures() {
+    // Library the host cannot load: the error comes back over the socket
+    let tmp_dir = tempfile::tempdir().unwrap();
+    let missing = tmp_dir.path().join("libmissing.so");
+    let err = SandboxedPlugin::spawn(&missing, test_config(&[])).err().expect("missing library should fail");
+    assert!(matches!(err, PluginSystemError::LoadingError { .. }), "got {:?}", err);
+    assert!(err.to_string().contains("libloading error"), "got {}", err);
+
+    // Host executable that does not exist
+    let mut config = test_config(&[]);
+    config.program = tmp_dir.path().join("no-such-host");
+    assert!(SandboxedPlugin::spawn(&missing, config).is_err());
+}
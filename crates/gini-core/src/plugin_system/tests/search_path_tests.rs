@@ -0,0 +1,64 @@
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use crate::kernel::constants;
+use crate::plugin_system::search_path::{PluginPathSource, PluginSearchPaths};
+
+#[test]
+fn test_entries_ordered_by_source_precedence() {
+    let mut paths = PluginSearchPaths::new();
+    paths.add("/defaults", PluginPathSource::Default);
+    paths.add("/config_a", PluginPathSource::Config);
+    paths.add("/cli", PluginPathSource::CommandLine);
+    paths.add("/config_b", PluginPathSource::Config);
+    paths.add("/env", PluginPathSource::Environment);
+
+    assert_eq!(
+        paths.dirs(),
+        vec![
+            PathBuf::from("/cli"),
+            PathBuf::from("/env"),
+            PathBuf::from("/config_a"),
+            PathBuf::from("/config_b"),
+            PathBuf::from("/defaults"),
+        ],
+        "Entries should be sorted by precedence, keeping insertion order within a source"
+    );
+}
+
+#[test]
+fn test_duplicate_dir_keeps_higher_precedence_source() {
+    let mut paths = PluginSearchPaths::new();
+    paths.add("/shared", PluginPathSource::CommandLine);
+    paths.add("/shared", PluginPathSource::Config);
+    paths.add("", PluginPathSource::Config); // Empty paths are ignored
+
+    assert_eq!(paths.len(), 1);
+    assert_eq!(paths.source_of(Path::new("/shared")), Some(PluginPathSource::CommandLine));
+}
+
+#[test]
+fn test_add_path_list_splits_env_value() {
+    let joined: OsString = std::env::join_paths(["/opt/gini/plugins", "/usr/lib/gini/plugins"]).unwrap();
+    let mut paths = PluginSearchPaths::new();
+    paths.add_path_list(&joined, PluginPathSource::Environment);
+
+    assert_eq!(paths.dirs(), vec![PathBuf::from("/opt/gini/plugins"), PathBuf::from("/usr/lib/gini/plugins")]);
+    assert!(paths.entries().iter().all(|entry| entry.source == PluginPathSource::Environment));
+}
+
+#[test]
+fn test_standard_layout_puts_core_first() {
+    let base = Path::new("/data/gini");
+    let mut paths = PluginSearchPaths::new();
+    paths.add_standard_layout(base, PluginPathSource::DataDir);
+
+    assert_eq!(
+        paths.dirs(),
+        vec![
+            base.join(constants::CORE_PLUGINS_DIR),
+            base.join(constants::THIRD_PARTY_PLUGINS_DIR),
+            base.join(constants::DEFAULT_PLUGINS_DIR),
+        ]
+    );
+}
@@ -0,0 +1,189 @@
+use std::str::FromStr;
+use std::sync::{Arc, Mutex as StdMutex};
+
+use tokio::sync::Mutex;
+
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::dependency::DependencyError;
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::lifecycle::PluginState;
+use crate::plugin_system::permission::PluginAccess;
+use crate::plugin_system::registry::PluginRegistry;
+use crate::plugin_system::service::{ServiceRegistry, ServiceRequirement};
+use crate::plugin_system::tests::TestPlugin;
+use crate::plugin_system::version::{ApiVersion, VersionRange};
+use crate::stage_manager::registry::StageRegistry;
+
+trait Greeter: Send + Sync {
+    fn greet(&self, name: &str) -> String;
+}
+
+struct EnglishGreeter;
+
+impl Greeter for EnglishGreeter {
+    fn greet(&self, name: &str) -> String {
+        format!("Hello, {}", name)
+    }
+}
+
+trait Counter: Send + Sync {}
+
+/// Publishes a `dyn Greeter` as "greeter" during init
+fn provider(id: &str, version: &'static str, registry: &PluginRegistry, log: &Arc<StdMutex<Vec<String>>>) -> TestPlugin {
+    TestPlugin::new(id)
+        .on_init(move |_, app| {
+            PluginAccess::current(app)?.publish_service("greeter", version, Arc::new(EnglishGreeter) as Arc<dyn Greeter>)?;
+            Ok(())
+        })
+        .on_shutdown(log_shutdown(registry, log))
+}
+
+/// Requires "greeter" from `provider` and logs a greeting during init
+fn consumer(id: &str, requirement: ServiceRequirement, provider: &str, registry: &PluginRegistry, log: &Arc<StdMutex<Vec<String>>>) -> TestPlugin {
+    let services = registry.services().clone();
+    let init_log = log.clone();
+    let greeter_requirement = requirement.clone();
+    TestPlugin::new(id)
+        .requiring(provider)
+        .requiring_service(requirement)
+        .on_init(move |id, _| {
+            let greeter = services.get_matching::<dyn Greeter>(&greeter_requirement)?;
+            init_log.lock().unwrap().push(greeter.greet(id));
+            Ok(())
+        })
+        .on_shutdown(log_shutdown(registry, log))
+}
+
+/// Logs whether "greeter" is still published when the plugin shuts down
+fn log_shutdown(registry: &PluginRegistry, log: &Arc<StdMutex<Vec<String>>>) -> impl Fn(&str) -> std::result::Result<(), PluginSystemError> + Send + Sync + 'static {
+    let services = registry.services().clone();
+    let log = log.clone();
+    move |id| {
+        let available = services.is_published("greeter");
+        log.lock().unwrap().push(format!("shutdown:{} greeter={}", id, available));
+        Ok(())
+    }
+}
+
+fn new_registry() -> PluginRegistry {
+    PluginRegistry::new(ApiVersion::from_str("0.1.0").unwrap())
+}
+
+#[test]
+fn test_service_lookup_is_type_checked() {
+    let services = ServiceRegistry::new();
+    services.publish("provider", "greeter", "1.2.0", Arc::new(EnglishGreeter) as Arc<dyn Greeter>).unwrap();
+
+    let greeter = services.get::<dyn Greeter>("greeter").unwrap();
+    assert_eq!(greeter.greet("gini"), "Hello, gini");
+
+    let wrong_type = services.get::<dyn Counter>("greeter").err().unwrap();
+    assert!(matches!(&wrong_type, PluginSystemError::ServiceError { service, .. } if service == "greeter"), "{}", wrong_type);
+
+    let duplicate = services.publish("other", "greeter", "2.0.0", Arc::new(EnglishGreeter) as Arc<dyn Greeter>);
+    assert!(duplicate.unwrap_err().to_string().contains("already published by 'provider'"));
+    assert!(services.publish("other", "bad", "one", Arc::new(EnglishGreeter)).is_err());
+
+    let info = services.info("greeter").unwrap();
+    assert_eq!((info.provider_id.as_str(), info.version.to_string()), ("provider", "1.2.0".to_string()));
+    assert!(info.type_name.contains("Greeter"));
+}
+
+#[test]
+fn test_service_version_requirements() {
+    let services = ServiceRegistry::new();
+    services.publish("provider", "greeter", "1.2.0", Arc::new(EnglishGreeter) as Arc<dyn Greeter>).unwrap();
+
+    let compatible = ServiceRequirement::new("greeter", VersionRange::from_str("^1.0").unwrap());
+    assert!(services.check(&compatible).is_ok());
+    assert!(services.get_matching::<dyn Greeter>(&compatible).is_ok());
+
+    let too_new = ServiceRequirement::new("greeter", VersionRange::from_str("^2.0").unwrap());
+    match services.check(&too_new) {
+        Err(DependencyError::MissingService { service, reason }) => {
+            assert_eq!(service, "greeter ^2.0");
+            assert!(reason.contains("version 1.2.0 published by 'provider'"), "{}", reason);
+        }
+        other => panic!("Expected MissingService, got {:?}", other),
+    }
+
+    assert!(matches!(
+        services.get::<dyn Greeter>("translator"),
+        Err(PluginSystemError::DependencyResolution(DependencyError::MissingService { .. }))
+    ));
+}
+
+#[test]
+fn test_withdraw_removes_only_the_providers_services() {
+    let services = ServiceRegistry::new();
+    services.publish("a", "greeter", "1.0.0", Arc::new(EnglishGreeter) as Arc<dyn Greeter>).unwrap();
+    services.publish("a", "farewell", "1.0.0", Arc::new(EnglishGreeter) as Arc<dyn Greeter>).unwrap();
+    services.publish("b", "welcome", "1.0.0", Arc::new(EnglishGreeter) as Arc<dyn Greeter>).unwrap();
+
+    assert_eq!(services.withdraw("a"), vec!["farewell", "greeter"]);
+    let names: Vec<String> = services.services().into_iter().map(|info| info.name).collect();
+    assert_eq!(names, vec!["welcome"]);
+    assert!(services.withdraw("a").is_empty());
+}
+
+#[tokio::test]
+async fn test_services_live_until_their_provider_shuts_down() {
+    let log = Arc::new(StdMutex::new(Vec::new()));
+    let mut registry = new_registry();
+    let provider = provider("provider", "1.0.0", &registry, &log);
+    let consumer = consumer("consumer", ServiceRequirement::new("greeter", VersionRange::from_str("^1.0").unwrap()), "provider", &registry, &log);
+    registry.register_plugin(Arc::new(consumer)).unwrap();
+    registry.register_plugin(Arc::new(provider)).unwrap();
+    let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+    let mut app = Application::new().unwrap();
+
+    let report = registry.initialize_all(&mut app, &stage_registry).await.unwrap();
+    assert!(report.is_complete(), "{}", report);
+    assert_eq!(registry.services().info("greeter").unwrap().provider_id, "provider", "Published under the registry ID");
+    assert!(PluginAccess::current(&app).is_err(), "Access is only handed out during init");
+
+    registry.shutdown_all().unwrap();
+
+    assert_eq!(*log.lock().unwrap(), vec![
+        "Hello, consumer".to_string(),
+        "shutdown:consumer greeter=true".to_string(),
+        "shutdown:provider greeter=true".to_string(),
+    ]);
+    assert!(!registry.services().is_published("greeter"), "Withdrawn once the provider shut down");
+}
+
+#[tokio::test]
+async fn test_missing_service_fails_the_plugin_as_a_dependency_error() {
+    let log = Arc::new(StdMutex::new(Vec::new()));
+    let mut registry = new_registry();
+    let provider = provider("provider", "1.0.0", &registry, &log);
+    let consumer = consumer("consumer", ServiceRequirement::new("greeter", VersionRange::from_str("^2.0").unwrap()), "provider", &registry, &log);
+    registry.register_plugin(Arc::new(consumer)).unwrap();
+    registry.register_plugin(Arc::new(provider)).unwrap();
+    let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+    let mut app = Application::new().unwrap();
+
+    let report = registry.initialize_all(&mut app, &stage_registry).await.unwrap();
+
+    assert_eq!(report.initialized, vec!["provider"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].plugin_id, "consumer");
+    assert!(report.failed[0].reason.contains("Required service not available: 'greeter ^2.0'"), "{}", report.failed[0].reason);
+    assert_eq!(registry.lifecycle().state("consumer"), Some(PluginState::Failed));
+    assert!(log.lock().unwrap().is_empty(), "The consumer's init never ran");
+}
+
+#[tokio::test]
+async fn test_disabling_the_provider_withdraws_its_services() {
+    let log = Arc::new(StdMutex::new(Vec::new()));
+    let mut registry = new_registry();
+    registry.register_plugin(Arc::new(provider("provider", "1.0.0", &registry, &log))).unwrap();
+    let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+    let mut app = Application::new().unwrap();
+    registry.initialize_all(&mut app, &stage_registry).await.unwrap();
+    assert!(registry.services().is_published("greeter"));
+
+    registry.disable_plugin("provider", &stage_registry).await.unwrap();
+
+    assert!(registry.services().services().is_empty());
+}
@@ -0,0 +1,172 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+use ed25519_dalek::SigningKey;
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::lazy::LazyPlugin;
+use crate::plugin_system::loader::PluginLoader;
+use crate::plugin_system::manifest::PluginManifest;
+use crate::plugin_system::package::{PluginArchive, PluginInstaller};
+use crate::plugin_system::signature::{
+    PluginSignature, SignaturePolicy, SignatureVerifier, TrustStore, VerificationStatus, SIGNATURE_FILE_NAME,
+};
+
+fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+/// Writes a plugin with a stand-in library (never opened by these tests unless verification passes).
+fn write_plugin(dir: &Path, id: &str) -> PluginManifest {
+    fs::create_dir_all(dir).unwrap();
+    let manifest_path = dir.join("manifest.json");
+    let content = json!({
+        "id": id, "name": id, "version": "1.0.0", "description": "", "author": "",
+        "api_versions": [">=0.1.0"], "entry_point": format!("lib{}.so", id),
+    })
+    .to_string();
+    fs::write(&manifest_path, &content).unwrap();
+    fs::write(dir.join(format!("lib{}.so", id)), b"not a real library").unwrap();
+    PluginLoader::parse_manifest(&content, &manifest_path).unwrap()
+}
+
+fn trusting(name: &str, key: &SigningKey) -> TrustStore {
+    let mut store = TrustStore::new();
+    store.add(name, key.verifying_key());
+    store
+}
+
+#[test]
+fn test_signature_statuses() {
+    let root = tempdir().unwrap();
+    let manifest = write_plugin(root.path(), "signed_plugin");
+    let key = signing_key(7);
+
+    let verifier = SignatureVerifier::new(SignaturePolicy::Enforce, trusting("release", &key));
+    assert_eq!(verifier.verify(&manifest), VerificationStatus::Unsigned);
+
+    let signature = PluginSignature::sign_plugin(root.path(), &key).unwrap();
+    assert!(root.path().join(SIGNATURE_FILE_NAME).is_file());
+    assert_eq!(signature.public_key, hex::encode(key.verifying_key().as_bytes()));
+    assert_eq!(verifier.verify(&manifest), VerificationStatus::Verified { key_name: "release".to_string() });
+
+    let stranger = SignatureVerifier::new(SignaturePolicy::Enforce, trusting("other", &signing_key(8)));
+    assert_eq!(
+        stranger.verify(&manifest),
+        VerificationStatus::UntrustedKey { public_key: signature.public_key.clone() }
+    );
+}
+
+#[test]
+fn test_tampering_invalidates_signature() {
+    let root = tempdir().unwrap();
+    let key = signing_key(7);
+    let verifier = SignatureVerifier::new(SignaturePolicy::Enforce, trusting("release", &key));
+
+    let manifest = write_plugin(&root.path().join("library"), "tampered_lib");
+    PluginSignature::sign_plugin(&manifest.plugin_base_dir, &key).unwrap();
+    fs::write(manifest.plugin_base_dir.join("libtampered_lib.so"), b"patched library").unwrap();
+    match verifier.verify(&manifest) {
+        VerificationStatus::Invalid { reason } => assert!(reason.contains("entry point"), "{}", reason),
+        other => panic!("Expected an invalid signature, got {:?}", other),
+    }
+
+    let manifest = write_plugin(&root.path().join("manifest"), "tampered_manifest");
+    PluginSignature::sign_plugin(&manifest.plugin_base_dir, &key).unwrap();
+    let manifest_path = manifest.plugin_base_dir.join("manifest.json");
+    let edited = fs::read_to_string(&manifest_path).unwrap().replace("1.0.0", "1.0.1");
+    fs::write(&manifest_path, edited).unwrap();
+    assert!(matches!(verifier.verify(&manifest), VerificationStatus::Invalid { .. }));
+
+    fs::write(manifest.plugin_base_dir.join(SIGNATURE_FILE_NAME), "{ not json").unwrap();
+    assert!(matches!(verifier.verify(&manifest), VerificationStatus::Invalid { .. }));
+}
+
+#[test]
+fn test_policy_decides_unverified_plugins() {
+    assert_eq!("enforce".parse::<SignaturePolicy>(), Ok(SignaturePolicy::Enforce));
+    assert_eq!("Allow-Unsigned".parse::<SignaturePolicy>(), Ok(SignaturePolicy::AllowUnsigned));
+    assert!("strict".parse::<SignaturePolicy>().is_err());
+
+    let root = tempdir().unwrap();
+    let manifest = write_plugin(root.path(), "unsigned_plugin");
+    let verifier = SignatureVerifier::default();
+    assert_eq!(verifier.policy(), SignaturePolicy::AllowUnsigned);
+    assert_eq!(verifier.check(&manifest).unwrap(), VerificationStatus::Unsigned);
+
+    verifier.set_policy(SignaturePolicy::Warn);
+    assert!(verifier.check(&manifest).is_ok());
+
+    verifier.set_policy(SignaturePolicy::Enforce);
+    match verifier.check(&manifest) {
+        Err(PluginSystemError::SignatureError { plugin_id, message }) => {
+            assert_eq!(plugin_id, "unsigned_plugin");
+            assert!(message.contains("unsigned"), "{}", message);
+        }
+        other => panic!("Expected SignatureError, got {:?}", other),
+    }
+    assert_eq!(verifier.status("unsigned_plugin"), Some(VerificationStatus::Unsigned));
+    assert!(verifier.check_unsigned("/plugins/libloose.so").is_err(), "Libraries loaded by path count as unsigned");
+}
+
+#[test]
+fn test_trust_store_round_trip() {
+    let root = tempdir().unwrap();
+    let path = root.path().join("config/trusted_plugin_keys.json");
+    assert!(TrustStore::load(&path).unwrap().is_empty(), "A missing trust store is empty");
+
+    let mut store = trusting("release", &signing_key(7));
+    store.add("nightly", signing_key(8).verifying_key());
+    store.save(&path).unwrap();
+
+    let mut loaded = TrustStore::load(&path).unwrap();
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(loaded.key_name(&signing_key(8).verifying_key()), Some("nightly"));
+    assert!(loaded.remove("nightly"));
+    assert_eq!(loaded.key_name(&signing_key(8).verifying_key()), None);
+
+    let verifier = SignatureVerifier::default();
+    verifier.set_trust_store_path(path.clone());
+    verifier.reload_trust_store().unwrap();
+    assert_eq!(verifier.trust_store().len(), 2);
+
+    fs::write(&path, r#"{"keys": {"broken": "abcd"}}"#).unwrap();
+    assert!(TrustStore::load(&path).is_err());
+}
+
+#[test]
+fn test_signature_is_checked_before_library_is_opened() {
+    let root = tempdir().unwrap();
+    let manifest = write_plugin(root.path(), "guarded_plugin");
+    let key = signing_key(7);
+    let verifier = Arc::new(SignatureVerifier::new(SignaturePolicy::Enforce, trusting("release", &key)));
+
+    let plugin = LazyPlugin::new(manifest.clone()).with_signature_verifier(verifier.clone());
+    assert!(matches!(plugin.load(), Err(PluginSystemError::SignatureError { .. })));
+
+    // Once signed, verification passes and the (bogus) library itself fails to load
+    PluginSignature::sign_plugin(root.path(), &key).unwrap();
+    let plugin = LazyPlugin::new(manifest).with_signature_verifier(verifier.clone());
+    assert!(matches!(plugin.load(), Err(PluginSystemError::LoadingError { .. })));
+    assert!(verifier.status("guarded_plugin").is_some_and(|status| status.is_verified()));
+}
+
+#[tokio::test]
+async fn test_signature_survives_archive_install() {
+    let root = tempdir().unwrap();
+    let key = signing_key(7);
+    let source = root.path().join("source");
+    write_plugin(&source, "archived_plugin");
+    PluginSignature::sign_plugin(&source, &key).unwrap();
+
+    let archive = PluginArchive::create(&source, root.path().join("archived_plugin-1.0.0.tar.gz")).unwrap();
+    assert!(archive.file_names().any(|name| name == SIGNATURE_FILE_NAME));
+
+    let installer = PluginInstaller::for_data_dir(&root.path().join("data"));
+    let outcome = installer.install(archive.path()).await.unwrap();
+    let verifier = SignatureVerifier::new(SignaturePolicy::Enforce, trusting("release", &key));
+    assert!(verifier.verify(&outcome.manifest).is_verified());
+}
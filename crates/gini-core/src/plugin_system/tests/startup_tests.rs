@@ -0,0 +1,164 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use async_trait::async_trait;
+use tokio::sync::Mutex;
+
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::lifecycle::PluginState;
+use crate::plugin_system::startup::{InitFailurePolicy, PluginOutcome, StartupReport};
+use crate::plugin_system::tests::{registry_with, TestPlugin};
+use crate::stage_manager::context::StageContext;
+use crate::stage_manager::registry::StageRegistry;
+use crate::stage_manager::Stage;
+
+struct NamedStage(String);
+
+#[async_trait]
+impl Stage for NamedStage {
+    fn id(&self) -> &str { &self.0 }
+    fn name(&self) -> &str { &self.0 }
+    fn description(&self) -> &str { "Stage registered by a startup test plugin" }
+    async fn execute(&self, _context: &mut StageContext) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> { Ok(()) }
+}
+
+/// Registers `<id>::first`, the stage every startup test plugin provides
+fn register_first_stage(id: &str, registry: &mut StageRegistry) -> std::result::Result<(), PluginSystemError> {
+    registry
+        .register_stage(Box::new(NamedStage(format!("{}::first", id))))
+        .map_err(|e| PluginSystemError::InternalError(e.to_string()))
+}
+
+fn startup_plugin(id: &str) -> TestPlugin {
+    TestPlugin::new(id).on_register_stages(register_first_stage)
+}
+
+#[test]
+fn test_init_failure_policy_parsing() {
+    assert_eq!("isolate".parse::<InitFailurePolicy>(), Ok(InitFailurePolicy::Isolate));
+    assert_eq!(" fail-fast ".parse::<InitFailurePolicy>(), Ok(InitFailurePolicy::FailFast));
+    assert_eq!("FailFast".parse::<InitFailurePolicy>(), Ok(InitFailurePolicy::FailFast));
+    assert_eq!("Isolate".parse::<InitFailurePolicy>(), Ok(InitFailurePolicy::Isolate));
+    assert!("ignore".parse::<InitFailurePolicy>().unwrap_err().contains("expected isolate or fail-fast"));
+    assert_eq!(InitFailurePolicy::default(), InitFailurePolicy::Isolate);
+    assert_eq!(InitFailurePolicy::FailFast.to_string(), "fail-fast");
+}
+
+#[tokio::test]
+async fn test_failing_plugin_is_isolated_with_its_dependents() {
+    let mut registry = registry_with(vec![
+        startup_plugin("base"),
+        startup_plugin("broken").failing_init(),
+        startup_plugin("dependent").requiring("broken"),
+        startup_plugin("grand_dependent").requiring("dependent"),
+        startup_plugin("standalone"),
+        startup_plugin("off"),
+    ]);
+    let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+    registry.disable_plugin("off", &stage_registry).await.unwrap();
+    let mut app = Application::new().unwrap();
+
+    let report = registry.initialize_all(&mut app, &stage_registry).await.expect("Startup continues");
+
+    assert_eq!(report.initialized, vec!["base", "standalone"]);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(report.failed[0].plugin_id, "broken");
+    assert!(report.failed[0].reason.contains("init failed on purpose"), "{}", report.failed[0].reason);
+    let reason = "Required plugin 'broken' failed to initialize".to_string();
+    assert_eq!(report.skipped, vec![
+        PluginOutcome { plugin_id: "grand_dependent".to_string(), reason: reason.clone() },
+        PluginOutcome { plugin_id: "dependent".to_string(), reason: reason.clone() },
+    ]);
+    assert_eq!(report.disabled, vec!["off"]);
+    assert!(!report.is_complete());
+    assert_eq!(registry.last_startup_report(), Some(&report));
+
+    let lifecycle = registry.lifecycle();
+    assert_eq!(lifecycle.state("broken"), Some(PluginState::Failed));
+    let dependent = lifecycle.status("dependent").unwrap();
+    assert_eq!((dependent.state, dependent.reason), (PluginState::Disabled, reason));
+    assert!(registry.is_initialized("standalone"));
+    assert!(stage_registry.lock().await.has_stage("standalone::first"));
+}
+
+#[tokio::test]
+async fn test_partially_registered_stages_are_unregistered() {
+    let shutdowns = Arc::new(AtomicUsize::new(0));
+    let counter = shutdowns.clone();
+    // Registers its first stage, then fails
+    let halfway = TestPlugin::new("halfway")
+        .on_register_stages(|id, registry| {
+            register_first_stage(id, registry)?;
+            Err(PluginSystemError::InternalError("second stage failed on purpose".to_string()))
+        })
+        .on_shutdown(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        });
+    let mut registry = registry_with(vec![halfway]);
+    let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+    let mut app = Application::new().unwrap();
+
+    let report = registry.initialize_all(&mut app, &stage_registry).await.unwrap();
+
+    assert_eq!(report.failed[0].plugin_id, "halfway");
+    assert!(!stage_registry.lock().await.has_stage("halfway::first"), "The stage would run without its plugin");
+    assert_eq!(registry.lifecycle().state("halfway"), Some(PluginState::Failed));
+    assert_eq!(shutdowns.load(Ordering::SeqCst), 1, "Its successful init is undone");
+    assert!(registry.initialization_order().is_empty());
+
+    // Shutting down the application does not shut it down again
+    registry.shutdown_all().unwrap();
+    assert_eq!(shutdowns.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn test_core_failures_stop_startup() {
+    let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+    let mut app = Application::new().unwrap();
+
+    let mut registry = registry_with(vec![startup_plugin("core_broken").core().failing_init()]);
+    assert!(registry.initialize_all(&mut app, &stage_registry).await.is_err(), "Core plugins stay fatal");
+
+    // A third-party plugin that a core plugin requires is just as fatal
+    let mut registry = registry_with(vec![
+        startup_plugin("helper").failing_init(),
+        startup_plugin("core_user").core().requiring("helper"),
+    ]);
+    assert!(registry.initialize_all(&mut app, &stage_registry).await.is_err());
+    assert!(registry.is_enabled("core_user"), "Core plugins are never disabled");
+}
+
+#[tokio::test]
+async fn test_fail_fast_policy_returns_the_first_error() {
+    let mut registry = registry_with(vec![
+        startup_plugin("broken").failing_init(),
+        startup_plugin("later"),
+    ]);
+    registry.set_init_failure_policy(InitFailurePolicy::FailFast);
+    let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+    let mut app = Application::new().unwrap();
+
+    let result = registry.initialize_all(&mut app, &stage_registry).await;
+
+    assert!(result.unwrap_err().to_string().contains("init failed on purpose"));
+    assert!(!registry.is_initialized("later"));
+}
+
+#[test]
+fn test_startup_report_display() {
+    let report = StartupReport {
+        initialized: vec!["a".to_string(), "b".to_string()],
+        failed: vec![PluginOutcome { plugin_id: "c".to_string(), reason: "boom".to_string() }],
+        skipped: vec![PluginOutcome { plugin_id: "d".to_string(), reason: "Required plugin 'c' failed to initialize".to_string() }],
+        disabled: vec![],
+    };
+    let text = report.to_string();
+    assert!(text.starts_with("Plugin startup: 2 initialized, 1 failed, 1 skipped, 0 disabled\n"), "{}", text);
+    assert!(text.contains("  initialized: a, b\n"));
+    assert!(text.contains("  failed: c: boom\n"));
+    assert!(text.contains("  skipped: d: Required plugin 'c' failed to initialize\n"));
+    assert!(!text.contains("disabled:"));
+    assert!(StartupReport::default().is_complete());
+}
@@ -0,0 +1,339 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use tempfile::tempdir;
+use tokio::sync::Mutex;
+
+use crate::event::EventManager;
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::error::PluginSystemError;
+use crate::plugin_system::lazy::LazyPlugin;
+use crate::plugin_system::loader::PluginLoader;
+use crate::plugin_system::manifest::{ManifestBuilder, PluginManifest, ResourceAccessType, ResourceClaim};
+use crate::plugin_system::registry::PluginRegistry;
+use crate::plugin_system::tests::manifest_in;
+use crate::plugin_system::traits::{Plugin, PluginPriority};
+use crate::plugin_system::version::{ApiVersion, VersionRange};
+use crate::plugin_system::wasm::WasmPlugin;
+use crate::stage_manager::context::StageContext;
+use crate::stage_manager::registry::StageRegistry;
+use crate::storage::config::{ConfigData, ConfigScope, PluginConfigScope};
+
+/// Guest exercising every host import. Stage IDs are told apart by length:
+/// "wasm_test::greet" (16 bytes) copies the greeting, "wasm_test::fail" fails.
+const GUEST_WAT: &str = r#"
+(module
+  (import "gini" "log" (func $log (param i32 i32 i32)))
+  (import "gini" "set_error" (func $set_error (param i32 i32)))
+  (import "gini" "register_stage" (func $register_stage (param i32 i32 i32 i32 i32 i32) (result i32)))
+  (import "gini" "context_is_dry_run" (func $context_is_dry_run (result i32)))
+  (import "gini" "context_get" (func $context_get (param i32 i32) (result i64)))
+  (import "gini" "context_set" (func $context_set (param i32 i32 i32 i32) (result i32)))
+  (import "gini" "config_get" (func $config_get (param i32 i32) (result i64)))
+  (import "gini" "emit_event" (func $emit_event (param i32 i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (global $heap (mut i32) (i32.const 4096))
+  (global $config_ptr (mut i32) (i32.const 0))
+  (global $config_len (mut i32) (i32.const 0))
+  (data (i32.const 0) "wasm_test::greet")
+  (data (i32.const 32) "Greet")
+  (data (i32.const 48) "Copies the greeting")
+  (data (i32.const 80) "greeting")
+  (data (i32.const 96) "reply")
+  (data (i32.const 112) "wasm_test::fail")
+  (data (i32.const 128) "stage failed on purpose")
+  (data (i32.const 160) "wasm.greeted")
+  (data (i32.const 176) "mode")
+  (data (i32.const 192) "config_mode")
+  (data (i32.const 208) "missing greeting")
+  (data (i32.const 224) "wasm.initialized")
+  (data (i32.const 256) "initialized")
+
+  (func (export "gini_abi_version") (result i32) (i32.const 1))
+
+  (func (export "gini_alloc") (param $len i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (global.set $heap (i32.add (global.get $heap) (local.get $len)))
+    (local.get $ptr))
+
+  (func (export "gini_init") (result i32)
+    (local $value i64)
+    (call $log (i32.const 2) (i32.const 256) (i32.const 11))
+    (local.set $value (call $config_get (i32.const 176) (i32.const 4)))
+    (if (i64.ne (local.get $value) (i64.const -1))
+      (then
+        (global.set $config_ptr (i32.wrap_i64 (i64.shr_u (local.get $value) (i64.const 32))))
+        (global.set $config_len (i32.wrap_i64 (local.get $value)))))
+    (drop (call $emit_event (i32.const 224) (i32.const 16) (i32.const 0) (i32.const 0)))
+    (i32.const 0))
+
+  (func (export "gini_preflight_check") (result i32)
+    (if (i32.eq (call $context_is_dry_run) (i32.const -1))
+      (then (return (i32.const 2))))
+    (if (i64.eq (call $context_get (i32.const 80) (i32.const 8)) (i64.const -1))
+      (then
+        (call $set_error (i32.const 208) (i32.const 16))
+        (return (i32.const 1))))
+    (i32.const 0))
+
+  (func (export "gini_register_stages") (result i32)
+    (drop (call $register_stage (i32.const 0) (i32.const 16) (i32.const 32) (i32.const 5) (i32.const 48) (i32.const 19)))
+    (call $register_stage (i32.const 112) (i32.const 15) (i32.const 32) (i32.const 5) (i32.const 48) (i32.const 19)))
+
+  (func (export "gini_execute_stage") (param $ptr i32) (param $len i32) (result i32)
+    (local $value i64)
+    (if (i32.ne (local.get $len) (i32.const 16))
+      (then
+        (call $set_error (i32.const 128) (i32.const 23))
+        (return (i32.const 1))))
+    (local.set $value (call $context_get (i32.const 80) (i32.const 8)))
+    (if (i64.eq (local.get $value) (i64.const -1))
+      (then (return (i32.const 2))))
+    (drop (call $context_set (i32.const 96) (i32.const 5)
+      (i32.wrap_i64 (i64.shr_u (local.get $value) (i64.const 32)))
+      (i32.wrap_i64 (local.get $value))))
+    (if (i32.gt_s (global.get $config_len) (i32.const 0))
+      (then
+        (drop (call $context_set (i32.const 192) (i32.const 11) (global.get $config_ptr) (global.get $config_len)))))
+    (drop (call $emit_event (i32.const 160) (i32.const 12)
+      (i32.wrap_i64 (i64.shr_u (local.get $value) (i64.const 32)))
+      (i32.wrap_i64 (local.get $value))))
+    (i32.const 0))
+
+  (func (export "gini_shutdown") (result i32) (i32.const 0))
+)
+"#;
+
+/// Like [`manifest_in`], but also declaring the context keys and events the guest module uses.
+fn guest_manifest_in(base_dir: &Path, id: &str, entry_point: &str) -> PluginManifest {
+    let mut manifest = manifest_in(base_dir, id, entry_point);
+    manifest.resources.extend([
+        ResourceClaim::new("context_key", "greeting", ResourceAccessType::SharedRead),
+        ResourceClaim::new("context_key", "reply", ResourceAccessType::ExclusiveWrite),
+        ResourceClaim::new("context_key", "config_mode", ResourceAccessType::ExclusiveWrite),
+        ResourceClaim::new("event", "wasm.initialized", ResourceAccessType::ProvidesUniqueId),
+        ResourceClaim::new("event", "wasm.greeted", ResourceAccessType::ProvidesUniqueId),
+    ]);
+    manifest
+}
+
+fn guest_plugin() -> WasmPlugin {
+    let manifest = guest_manifest_in(Path::new("/tmp"), "wasm_test", "wasm_test.wasm");
+    WasmPlugin::from_bytes(&manifest, &wat::parse_str(GUEST_WAT).unwrap()).expect("Guest module should load")
+}
+
+/// Writes the compiled guest module into `dir` and returns its path.
+fn write_guest_module(dir: &Path, file_name: &str) -> PathBuf {
+    let path = dir.join(file_name);
+    fs::write(&path, wat::parse_str(GUEST_WAT).unwrap()).unwrap();
+    path
+}
+
+#[test]
+fn test_wasm_plugin_metadata_comes_from_manifest() {
+    let mut manifest = ManifestBuilder::new("wasm_test", "Wasm Test", "1.2.3")
+        .api_version(VersionRange::from_str("^0.1").unwrap())
+        .dependency("base_plugin", None, true)
+        .conflict("rival_plugin")
+        .priority(PluginPriority::ThirdParty(160))
+        .resource("file", "/tmp/wasm.lock", ResourceAccessType::ExclusiveWrite)
+        .entry_point("wasm_test.wasm")
+        .build();
+    manifest.plugin_base_dir = PathBuf::from("/tmp");
+
+    let plugin = WasmPlugin::from_bytes(&manifest, &wat::parse_str(GUEST_WAT).unwrap()).unwrap();
+    assert_eq!(plugin.name(), "wasm_test");
+    assert_eq!(plugin.version(), "1.2.3");
+    assert_eq!(plugin.priority(), PluginPriority::ThirdParty(160));
+    assert_eq!(plugin.compatible_api_versions().len(), 1);
+    assert_eq!(plugin.dependencies()[0].plugin_name, "base_plugin");
+    assert_eq!(plugin.conflicts_with(), vec!["rival_plugin".to_string()]);
+    assert_eq!(plugin.declared_resources()[0].resource.id, "/tmp/wasm.lock");
+    assert_eq!(plugin.declared_resources().len(), 1);
+    assert!(plugin.shutdown().is_ok());
+}
+
+#[test]
+fn test_wasm_plugin_requires_abi_version_export() {
+    let manifest = guest_manifest_in(Path::new("/tmp"), "no_abi", "no_abi.wasm");
+    let bytes = wat::parse_str(r#"(module (memory (export "memory") 1))"#).unwrap();
+    match WasmPlugin::from_bytes(&manifest, &bytes) {
+        Err(PluginSystemError::AbiMismatch { plugin_id, message, .. }) => {
+            assert_eq!(plugin_id, "no_abi");
+            assert!(message.contains("gini_abi_version"), "Unexpected message: {}", message);
+        }
+        Err(other) => panic!("Expected AbiMismatch, got {:?}", other),
+        Ok(_) => panic!("A module without gini_abi_version must be rejected"),
+    }
+
+    let bytes = wat::parse_str(
+        r#"(module (memory (export "memory") 1) (func (export "gini_abi_version") (result i32) (i32.const 99)))"#,
+    )
+    .unwrap();
+    assert!(matches!(
+        WasmPlugin::from_bytes(&manifest, &bytes),
+        Err(PluginSystemError::AbiMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_wasm_plugin_rejects_invalid_module() {
+    let manifest = guest_manifest_in(Path::new("/tmp"), "garbage", "garbage.wasm");
+    assert!(matches!(
+        WasmPlugin::from_bytes(&manifest, b"not a wasm module"),
+        Err(PluginSystemError::LoadingError { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_wasm_preflight_reads_context() {
+    let plugin = guest_plugin();
+
+    let context = StageContext::new_live(PathBuf::from("/tmp"));
+    match plugin.preflight_check(&context).await {
+        Err(PluginSystemError::PreflightCheckFailed { message, .. }) => assert_eq!(message, "missing greeting"),
+        other => panic!("Expected PreflightCheckFailed, got {:?}", other),
+    }
+
+    let mut context = StageContext::new_live(PathBuf::from("/tmp"));
+    context.set_cli_arg("greeting", "hello");
+    plugin.preflight_check(&context).await.expect("Preflight should pass with a greeting");
+}
+
+#[tokio::test]
+async fn test_wasm_stages_register_and_execute() {
+    let plugin = guest_plugin();
+    let mut stage_registry = StageRegistry::new();
+    plugin.register_stages(&mut stage_registry).expect("Stages should register");
+    assert!(stage_registry.has_stage("wasm_test::greet"));
+    assert!(stage_registry.has_stage("wasm_test::fail"));
+    assert_eq!(stage_registry.get_stage("wasm_test::greet").unwrap().name(), "Greet");
+
+    let mut context = StageContext::new_live(PathBuf::from("/tmp"));
+    context.set_cli_arg("greeting", "hello");
+    stage_registry.execute_stage_internal("wasm_test::greet", &mut context).await.unwrap();
+    assert_eq!(context.get_data::<String>("reply").map(String::as_str), Some("hello"));
+    assert!(context.get_data::<String>("config_mode").is_none(), "No configuration is read before init");
+
+    let error = stage_registry
+        .execute_stage_internal("wasm_test::fail", &mut context)
+        .await
+        .expect_err("The fail stage should fail");
+    assert!(error.to_string().contains("wasm_test::fail"), "Unexpected error: {}", error);
+    let source = std::error::Error::source(&error).map(|e| e.to_string()).unwrap_or_default();
+    assert!(source.contains("stage failed on purpose"), "Unexpected source: {}", source);
+
+    stage_registry.unregister_stages_for_plugin("wasm_test").unwrap();
+    assert!(!stage_registry.has_stage("wasm_test::greet"));
+}
+
+#[test]
+fn test_wasm_stage_ids_must_be_namespaced() {
+    let manifest = guest_manifest_in(Path::new("/tmp"), "other_plugin", "other_plugin.wasm");
+    let plugin = WasmPlugin::from_bytes(&manifest, &wat::parse_str(GUEST_WAT).unwrap()).unwrap();
+    let mut stage_registry = StageRegistry::new();
+    match plugin.register_stages(&mut stage_registry) {
+        Err(PluginSystemError::RegistrationError { message, .. }) => {
+            assert!(message.contains("other_plugin::"), "Unexpected message: {}", message)
+        }
+        other => panic!("Expected RegistrationError, got {:?}", other),
+    }
+    assert!(stage_registry.get_all_ids().is_empty());
+}
+
+#[test]
+fn test_wasm_runaway_guest_runs_out_of_fuel() {
+    let manifest = guest_manifest_in(Path::new("/tmp"), "spinner", "spinner.wasm");
+    let bytes = wat::parse_str(
+        r#"(module
+          (memory (export "memory") 1)
+          (func (export "gini_abi_version") (result i32) (i32.const 1))
+          (func (export "gini_shutdown") (result i32) (loop $spin (br $spin)) (i32.const 0)))"#,
+    )
+    .unwrap();
+    let plugin = WasmPlugin::from_bytes(&manifest, &bytes).unwrap();
+    match plugin.shutdown() {
+        Err(PluginSystemError::WasmError { plugin_id, operation, .. }) => {
+            assert_eq!(plugin_id, "spinner");
+            assert_eq!(operation, "gini_shutdown");
+        }
+        other => panic!("Expected WasmError, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_wasm_init_emits_events() {
+    let mut app = Application::new().expect("Failed to create Application");
+    let plugin = guest_plugin();
+    plugin.init(&mut app).expect("Init should succeed");
+
+    tokio::task::yield_now().await; // Let the detached queueing of init events run
+    assert!(app.event_manager().process_queue().await >= 1, "gini_init emits an event");
+}
+
+#[tokio::test]
+async fn test_wasm_entry_point_loads_through_registry() {
+    let tmp_dir = tempdir().unwrap();
+    write_guest_module(tmp_dir.path(), "wasm_test.wasm");
+    let manifest = guest_manifest_in(tmp_dir.path(), "wasm_test", "wasm_test.wasm");
+    assert!(PluginLoader::is_wasm_entry_point(&manifest));
+
+    let plugin_id = "wasm_test";
+    let mut app = Application::new().expect("Failed to create Application");
+    let config_manager = app.storage_manager().get_config_manager().clone();
+    let mut config = ConfigData::new();
+    config.set("mode", "fast").unwrap();
+    config_manager.save_plugin_config(plugin_id, &config, PluginConfigScope::User).unwrap();
+
+    let stage_registry = Arc::new(Mutex::new(StageRegistry::new()));
+    let mut registry = PluginRegistry::new(ApiVersion::from_str("0.1.0").unwrap());
+    registry.register_manifest(manifest.clone()).unwrap();
+    assert!(!registry.is_plugin_code_loaded(plugin_id));
+
+    registry.initialize_plugin(plugin_id, &mut app, &stage_registry).await.unwrap();
+    assert!(registry.is_plugin_code_loaded(plugin_id), "Initializing should instantiate the module");
+    assert!(registry.is_initialized(plugin_id));
+
+    let mut context = StageContext::new_live(PathBuf::from("/tmp"));
+    context.set_cli_arg("greeting", "hi there");
+    {
+        let stages = stage_registry.lock().await;
+        assert!(stages.has_stage("wasm_test::greet"));
+        stages.execute_stage_internal("wasm_test::greet", &mut context).await.unwrap();
+    }
+    assert_eq!(context.get_data::<String>("reply").map(String::as_str), Some("hi there"));
+    assert_eq!(
+        context.get_data::<String>("config_mode").map(String::as_str),
+        Some("\"fast\""),
+        "config_get returns the value as JSON"
+    );
+    assert!(app.event_manager().process_queue().await >= 1, "The stage emits an event");
+
+    registry.disable_plugin(plugin_id, &stage_registry).await.unwrap();
+    assert!(!stage_registry.lock().await.has_stage("wasm_test::greet"));
+
+    // Loading the same manifest directly and lazily gives the same plugin
+    let lazy = LazyPlugin::new(manifest);
+    assert_eq!(lazy.load().unwrap().name(), plugin_id);
+
+    let config_path = config_manager.resolve_config_path(plugin_id, ConfigScope::Plugin(PluginConfigScope::User));
+    let _ = fs::remove_file(config_path);
+}
+
+#[tokio::test]
+async fn test_wasm_undeclared_context_key_is_denied() {
+    let mut manifest = manifest_in(Path::new("/tmp"), "wasm_test", "wasm_test.wasm");
+    manifest.resources.push(ResourceClaim::new("context_key", "greeting", ResourceAccessType::SharedRead));
+    let plugin = WasmPlugin::from_bytes(&manifest, &wat::parse_str(GUEST_WAT).unwrap()).unwrap();
+    let mut stage_registry = StageRegistry::new();
+    plugin.register_stages(&mut stage_registry).unwrap();
+
+    // Reading "greeting" is declared, writing "reply" is not
+    let mut context = StageContext::new_live(PathBuf::from("/tmp"));
+    context.set_cli_arg("greeting", "hello");
+    stage_registry.execute_stage_internal("wasm_test::greet", &mut context).await.unwrap();
+    assert!(context.get_data::<String>("reply").is_none(), "Undeclared context keys must not be written");
+}
@@ -6,6 +6,8 @@ use crate::stage_manager::context::StageContext; // Added for preflight context
 use async_trait::async_trait;
 use crate::stage_manager::registry::StageRegistry; // Added for register_stages
 use crate::plugin_system::conflict::ResourceClaim; // Import ResourceClaim
+use crate::plugin_system::service::ServiceRequirement;
+use crate::storage::schema::ConfigSchema;
 // Removed incorrect import: use crate::plugin_system::error::PluginError;
 use crate::stage_manager::requirement::StageRequirement;
 
@@ -253,6 +255,7 @@ pub struct FfiStageRequirement {
 
 /// The VTable struct passed across the FFI boundary.
 /// Contains function pointers to access plugin metadata and manage its lifecycle.
+/// See [`ffi_host`](crate::plugin_system::ffi_host) for the ownership and freeing contract.
 #[repr(C)]
 pub struct PluginVTable {
     /// Opaque pointer to the plugin's internal instance data.
@@ -263,22 +266,27 @@ pub struct PluginVTable {
     /// The implementation must free all resources associated with the instance.
     pub destroy: extern "C" fn(instance: *mut c_void),
 
+    /// Frees the VTable allocation returned by `_plugin_init`. Called by the host exactly
+    /// once, after `destroy`; no entry is used afterwards. The plugin allocated the VTable,
+    /// so it must also free it with the same allocator.
+    pub free_vtable: extern "C" fn(vtable: *mut PluginVTable),
+
     // --- Metadata Accessors ---
 
     /// Gets the plugin's name.
     /// Returns a pointer to a null-terminated UTF-8 string.
-    /// The string data is owned by the plugin and must remain valid for the lifetime of the plugin instance.
+    /// The string data is owned by the plugin until the host passes it to `free_name`.
     /// The host MUST call `free_name` on the returned pointer when done.
     pub name: extern "C" fn(instance: *const c_void) -> *const c_char,
-    /// Frees the memory allocated for the string returned by `name`.
+    /// Frees the memory allocated for the string returned by `name`. Must accept a null pointer.
     pub free_name: extern "C" fn(name_ptr: *mut c_char),
 
     /// Gets the plugin's version.
     /// Returns a pointer to a null-terminated UTF-8 string.
-    /// The string data is owned by the plugin and must remain valid for the lifetime of the plugin instance.
+    /// The string data is owned by the plugin until the host passes it to `free_version`.
     /// The host MUST call `free_version` on the returned pointer when done.
     pub version: extern "C" fn(instance: *const c_void) -> *const c_char,
-    /// Frees the memory allocated for the string returned by `version`.
+    /// Frees the memory allocated for the string returned by `version`. Must accept a null pointer.
     pub free_version: extern "C" fn(version_ptr: *mut c_char),
 
     /// Checks if the plugin is a core plugin.
@@ -331,26 +339,84 @@ pub struct PluginVTable {
     /// Returns `FfiResult::Ok` on success, or an error code on failure.
     pub init: extern "C" fn(instance: *mut c_void, app_ptr: *mut c_void) -> FfiResult,
 
-    /// Performs pre-flight checks for the plugin.
+    // --- Optional Lifecycle Methods ---
+    // Optional entries must stay after all required ones. The host only calls an
+    // optional entry if the matching capability bit is set in the plugin's
+    // `PluginAbiDescriptor` and the entry lies within the reported `vtable_size`.
+
+    /// Performs pre-flight checks for the plugin. Capability: [`PLUGIN_CAP_PREFLIGHT_CHECK`].
     /// `context_ptr` is a raw pointer to `gini_core::stage_manager::context::StageContext`.
     /// The plugin should cast this pointer to perform checks.
     /// Returns `FfiResult::Ok` if checks pass, or an error code if they fail.
     /// Note: This is a synchronous FFI call. If async operations are needed,
     /// the plugin must manage its own runtime or use a blocking approach.
-    pub preflight_check: extern "C" fn(instance: *const c_void, context_ptr: *const c_void) -> FfiResult,
+    pub preflight_check: Option<extern "C" fn(instance: *const c_void, context_ptr: *const c_void) -> FfiResult>,
 
-    /// Registers stages provided by this plugin.
+    /// Registers stages provided by this plugin. Capability: [`PLUGIN_CAP_REGISTER_STAGES`].
     /// `registry_ptr` is a raw pointer to `gini_core::stage_manager::registry::StageRegistry`.
     /// The plugin should cast this pointer and use it to register its stages.
     /// Returns `FfiResult::Ok` on success, or an error code on failure.
-    pub register_stages: extern "C" fn(instance: *const c_void, registry_ptr: *mut c_void) -> FfiResult,
-    
-    /// Shuts down the plugin.
+    pub register_stages: Option<extern "C" fn(instance: *const c_void, registry_ptr: *mut c_void) -> FfiResult>,
+
+    /// Shuts down the plugin. Capability: [`PLUGIN_CAP_SHUTDOWN`].
     /// Returns `FfiResult::Ok` on success, or an error code on failure.
-    pub shutdown: extern "C" fn(instance: *mut c_void) -> FfiResult,
+    pub shutdown: Option<extern "C" fn(instance: *mut c_void) -> FfiResult>,
+}
+
+/// Version of the plugin ABI: the layout of the required `PluginVTable` entries and the
+/// calling contract. Bumped when a required entry changes; appending optional entries
+/// (gated by a capability bit) does not require a bump.
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Size of the required part of `PluginVTable`. A plugin's VTable must be at least this large.
+pub const PLUGIN_VTABLE_REQUIRED_SIZE: usize = std::mem::offset_of!(PluginVTable, preflight_check);
+
+/// Capability bit: the plugin provides `PluginVTable::preflight_check`.
+pub const PLUGIN_CAP_PREFLIGHT_CHECK: u64 = 1 << 0;
+/// Capability bit: the plugin provides `PluginVTable::register_stages`.
+pub const PLUGIN_CAP_REGISTER_STAGES: u64 = 1 << 1;
+/// Capability bit: the plugin provides `PluginVTable::shutdown`.
+pub const PLUGIN_CAP_SHUTDOWN: u64 = 1 << 2;
+/// All capability bits known to this version of the host.
+pub const PLUGIN_KNOWN_CAPABILITIES: u64 = PLUGIN_CAP_PREFLIGHT_CHECK | PLUGIN_CAP_REGISTER_STAGES | PLUGIN_CAP_SHUTDOWN;
+
+/// ABI descriptor exported by every dynamic plugin as the `GINI_PLUGIN_ABI` static.
+/// The host reads it before calling `_plugin_init` and rejects plugins built for a
+/// different ABI, so a `PluginVTable` with an unexpected layout is never touched.
+///
+/// ```ignore
+/// #[no_mangle]
+/// pub static GINI_PLUGIN_ABI: PluginAbiDescriptor =
+///     PluginAbiDescriptor::current(PLUGIN_CAP_PREFLIGHT_CHECK | PLUGIN_CAP_SHUTDOWN);
+/// ```
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PluginAbiDescriptor {
+    /// ABI version the plugin was built against (see [`PLUGIN_ABI_VERSION`]).
+    pub abi_version: u32,
+    /// `size_of::<PluginVTable>()` as seen by the plugin.
+    pub vtable_size: usize,
+    /// Bitmask of `PLUGIN_CAP_*` values for the optional VTable entries the plugin provides.
+    pub capabilities: u64,
+}
+
+impl PluginAbiDescriptor {
+    /// Descriptor for a plugin built against this version of `gini-core`.
+    pub const fn current(capabilities: u64) -> Self {
+        Self {
+            abi_version: PLUGIN_ABI_VERSION,
+            vtable_size: std::mem::size_of::<PluginVTable>(),
+            capabilities,
+        }
+    }
+
+    /// Checks whether a capability bit is set.
+    pub fn has_capability(&self, capability: u64) -> bool {
+        self.capabilities & capability == capability
+    }
 }
 
-// Helper functions `ffi_string_from_ptr` and `ffi_opt_string_from_ptr` moved to manager.rs
+// Helper functions `ffi_string_from_ptr` and `ffi_opt_string_from_ptr` live in ffi_host.rs
 
 impl FfiPluginPriority {
     /// Converts FFI priority to the internal PluginPriority enum.
@@ -406,7 +472,7 @@ impl<T: Copy> FfiSlice<T> {
 #[async_trait]
 pub trait Plugin: Send + Sync {
     /// The name of the plugin
-    fn name(&self) -> &'static str;
+    fn name(&self) -> &str;
     
     /// The version of the plugin
     fn version(&self) -> &str;
@@ -444,7 +510,29 @@ pub trait Plugin: Send + Sync {
         Vec::new()
     }
     
+    /// Services that must be published before this plugin initializes.
+    /// A missing service fails the plugin with a dependency error; see
+    /// [`service`](crate::plugin_system::service).
+    ///
+    /// The default implementation requires no services.
+    fn required_services(&self) -> Vec<ServiceRequirement> {
+        Vec::new()
+    }
+
+    /// Schema of the plugin's configuration. When present (or when the manifest names a
+    /// `config_schema` file), missing keys are filled in from its defaults and the
+    /// configuration is validated before `init`; violations fail the plugin.
+    ///
+    /// The default implementation declares no schema.
+    fn config_schema(&self) -> Option<ConfigSchema> {
+        None
+    }
+
     /// Initialize the plugin
+    ///
+    /// `app` gives full access to the application, so the plugin's declared permissions are
+    /// only checked for accesses it makes through its [`PluginAccess`](crate::plugin_system::permission::PluginAccess),
+    /// which `PluginAccess::current(app)` returns while `init` runs.
     fn init(&self, app: &mut crate::kernel::bootstrap::Application) -> std::result::Result<(), PluginSystemError>;
 
     /// Perform pre-flight checks.
@@ -0,0 +1,735 @@
+//! # WebAssembly Plugin Runtime
+//!
+//! Loads plugins whose manifest `entry_point` is a `.wasm` module and runs them in an
+//! embedded WebAssembly interpreter. Unlike native `.so` plugins, a WASM plugin is not
+//! tied to the host's rustc version and has no access to the host beyond the functions
+//! imported from the `gini` module below.
+//!
+//! Metadata (name, version, priority, dependencies, conflicts and resources) comes from
+//! the manifest, so a [`WasmPlugin`] registers with the
+//! [`PluginRegistry`](crate::plugin_system::PluginRegistry) like any other plugin.
+//!
+//! ## Guest ABI
+//!
+//! The module must export its linear `memory` and `gini_abi_version() -> i32` returning
+//! [`WASM_ABI_VERSION`]. All other exports are optional; lifecycle exports return `0` on
+//! success and any other value on failure:
+//!
+//! - `gini_alloc(len: i32) -> i32`: allocates guest memory for strings passed in by the host
+//!   (needed for `gini_execute_stage`, `context_get` and `config_get`).
+//! - `gini_init() -> i32`, `gini_preflight_check() -> i32`, `gini_shutdown() -> i32`
+//! - `gini_register_stages() -> i32`: calls `register_stage` for each stage it provides.
+//! - `gini_execute_stage(id_ptr: i32, id_len: i32) -> i32`
+//!
+//! Strings cross the boundary as UTF-8 `(ptr, len)` pairs. Values returned to the guest
+//! are packed into an `i64` as `(ptr << 32) | len`, or `-1` if there is no value.
+//!
+//! Context keys, configuration and event names are checked against the plugin's declared
+//! resources (see [`permission`](crate::plugin_system::permission)). A denied access
+//! returns [`PERMISSION_DENIED`] and sets the error message for the current call.
+//!
+//! Host functions imported from the `gini` module:
+//!
+//! - `log(level, ptr, len)`: level 0 = error, 1 = warn, 2 = info, 3 = debug, 4 = trace.
+//! - `set_error(ptr, len)`: message reported when the current call returns non-zero.
+//! - `register_stage(id_ptr, id_len, name_ptr, name_len, desc_ptr, desc_len) -> i32`:
+//!   only during `gini_register_stages`; stage IDs must start with `<plugin id>::`.
+//! - `context_is_dry_run() -> i32`: `1`/`0`, or `-1` outside preflight and stage execution.
+//! - `context_get(key_ptr, key_len) -> i64`: string shared data, falling back to the CLI argument.
+//! - `context_set(key_ptr, key_len, val_ptr, val_len) -> i32`: stores string shared data;
+//!   only during stage execution.
+//! - `config_get(key_ptr, key_len) -> i64`: the value from the plugin's configuration, as JSON.
+//! - `emit_event(name_ptr, name_len, data_ptr, data_len) -> i32`: queues a [`PluginEvent`].
+//!
+//! Every guest call runs with a fuel budget ([`WASM_CALL_FUEL`]) and linear memory is
+//! capped at [`WASM_MEMORY_LIMIT`], so a runaway plugin traps instead of hanging the host.
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use wasmi::{
+    AsContext, AsContextMut, Caller, Config, Engine, Extern, Instance, Linker, Memory, Module, Store,
+    StoreLimits, StoreLimitsBuilder, TypedFunc, WasmParams,
+};
+
+use crate::event::{EventManager, EventPriority, PluginEvent};
+use crate::kernel::bootstrap::Application;
+use crate::plugin_system::conflict;
+use crate::plugin_system::dependency::PluginDependency;
+use crate::plugin_system::error::{PluginSystemError, PluginSystemErrorSource};
+use crate::plugin_system::lazy::DEFAULT_MANIFEST_PRIORITY;
+use crate::plugin_system::loader::PluginLoader;
+use crate::plugin_system::manifest::PluginManifest;
+use crate::plugin_system::traits::{Plugin, PluginPriority};
+use crate::plugin_system::version::VersionRange;
+use crate::stage_manager::context::StageContext;
+use crate::stage_manager::registry::StageRegistry;
+use crate::stage_manager::requirement::StageRequirement;
+use crate::stage_manager::Stage;
+use crate::plugin_system::permission::{config_scope_id, Access, Capability, PermissionManager};
+use crate::storage::config::{ConfigManager, ConfigScope, PluginConfigScope};
+
+/// Guest ABI version a module must report from `gini_abi_version`.
+pub const WASM_ABI_VERSION: i32 = 1;
+
+/// Module name host functions are imported from.
+pub const HOST_MODULE: &str = "gini";
+
+/// Fuel available to each call into the guest.
+pub const WASM_CALL_FUEL: u64 = 100_000_000;
+
+/// Maximum size of a plugin's linear memory, in bytes.
+pub const WASM_MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
+/// Maximum length of a single string read from guest memory.
+const MAX_GUEST_STRING: usize = 1024 * 1024;
+
+/// Value returned to the guest when there is nothing to return.
+const NO_VALUE: i64 = -1;
+
+/// Value returned to the guest when it accesses a resource it did not declare.
+pub const PERMISSION_DENIED: i32 = -2;
+
+/// Access the guest currently has to a `StageContext`.
+///
+/// The pointers are only set for the duration of a single guest call made while the
+/// caller holds the context borrow, and are cleared before that call returns.
+#[derive(Clone, Copy)]
+enum ContextAccess {
+    None,
+    Read(*const StageContext),
+    Write(*mut StageContext),
+}
+
+// SAFETY: the pointers are only dereferenced during the guest call that set them, on the
+// thread that holds the borrow they were created from.
+unsafe impl Send for ContextAccess {}
+unsafe impl Sync for ContextAccess {}
+
+/// A stage the guest registered through `register_stage`.
+#[derive(Debug, Clone)]
+struct WasmStageInfo {
+    id: String,
+    name: String,
+    description: String,
+}
+
+/// Host side state available to imported functions.
+struct HostState {
+    plugin_id: String,
+    limits: StoreLimits,
+    last_error: Option<String>,
+    registering: bool, // Whether `register_stage` may be called
+    registered_stages: Vec<WasmStageInfo>,
+    context: ContextAccess,
+    permissions: Arc<PermissionManager>,
+    config_manager: Option<Arc<ConfigManager>>,
+    event_manager: Option<Arc<dyn EventManager>>,
+    pending_events: Vec<PluginEvent>, // Emitted by the guest, queued once the call returns
+}
+
+impl HostState {
+    fn context(&self) -> Option<&StageContext> {
+        // SAFETY: see `ContextAccess`
+        match self.context {
+            ContextAccess::None => None,
+            ContextAccess::Read(ptr) => unsafe { ptr.as_ref() },
+            ContextAccess::Write(ptr) => unsafe { ptr.as_ref() },
+        }
+    }
+
+    fn context_mut(&mut self) -> Option<&mut StageContext> {
+        // SAFETY: see `ContextAccess`
+        match self.context {
+            ContextAccess::Write(ptr) => unsafe { ptr.as_mut() },
+            _ => None,
+        }
+    }
+
+    /// Checks an access against the plugin's permissions; a denial becomes the call's error message.
+    fn permitted(&mut self, capability: Capability, identifier: &str, access: Access) -> bool {
+        match self.permissions.check(&self.plugin_id, capability, identifier, access) {
+            Ok(()) => true,
+            Err(e) => {
+                self.last_error = Some(e.to_string());
+                false
+            }
+        }
+    }
+}
+
+/// Reads a UTF-8 string from guest memory.
+fn read_guest_string(ctx: impl AsContext, memory: Memory, ptr: i32, len: i32) -> Result<String, wasmi::Error> {
+    if ptr < 0 || len < 0 {
+        return Err(wasmi::Error::new(format!("invalid guest string ({}, {})", ptr, len)));
+    }
+    if len as usize > MAX_GUEST_STRING {
+        return Err(wasmi::Error::new(format!("guest string of {} bytes exceeds the {} byte limit", len, MAX_GUEST_STRING)));
+    }
+    let mut buffer = vec![0u8; len as usize];
+    memory
+        .read(ctx, ptr as usize, &mut buffer)
+        .map_err(|e| wasmi::Error::new(format!("guest string out of bounds: {}", e)))?;
+    String::from_utf8(buffer).map_err(|e| wasmi::Error::new(format!("guest string is not valid UTF-8: {}", e)))
+}
+
+/// Copies `value` into memory allocated by the guest's `gini_alloc` and returns the packed `(ptr << 32) | len`.
+fn write_guest_string(
+    mut ctx: impl AsContextMut,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    value: &str,
+) -> Result<i64, wasmi::Error> {
+    let len = i32::try_from(value.len()).map_err(|_| wasmi::Error::new("value too large for guest memory"))?;
+    let ptr = alloc.call(&mut ctx, len)?;
+    if ptr < 0 {
+        return Err(wasmi::Error::new(format!("gini_alloc returned invalid pointer {}", ptr)));
+    }
+    memory
+        .write(&mut ctx, ptr as usize, value.as_bytes())
+        .map_err(|e| wasmi::Error::new(format!("gini_alloc returned memory out of bounds: {}", e)))?;
+    Ok(((ptr as i64) << 32) | len as i64)
+}
+
+/// Looks up the exported memory from inside a host function.
+fn caller_memory(caller: &Caller<'_, HostState>) -> Result<Memory, wasmi::Error> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| wasmi::Error::new("module does not export 'memory'"))
+}
+
+/// Reads a string argument from inside a host function.
+fn caller_string(caller: &Caller<'_, HostState>, ptr: i32, len: i32) -> Result<String, wasmi::Error> {
+    let memory = caller_memory(caller)?;
+    read_guest_string(caller, memory, ptr, len)
+}
+
+/// Returns a string to the guest from inside a host function, or `-1` for `None`.
+fn return_string(caller: &mut Caller<'_, HostState>, value: Option<String>) -> Result<i64, wasmi::Error> {
+    let Some(value) = value else {
+        return Ok(NO_VALUE);
+    };
+    let memory = caller_memory(caller)?;
+    let alloc = caller
+        .get_export("gini_alloc")
+        .and_then(Extern::into_func)
+        .ok_or_else(|| wasmi::Error::new("module does not export 'gini_alloc'"))?
+        .typed::<i32, i32>(&*caller)?;
+    write_guest_string(caller, memory, alloc, &value)
+}
+
+/// Defines the `gini` host module.
+fn host_linker(engine: &Engine) -> Result<Linker<HostState>, wasmi::Error> {
+    let mut linker = Linker::<HostState>::new(engine);
+
+    linker.func_wrap(HOST_MODULE, "log", |caller: Caller<'_, HostState>, level: i32, ptr: i32, len: i32| -> Result<(), wasmi::Error> {
+        let message = caller_string(&caller, ptr, len)?;
+        let level = match level {
+            0 => log::Level::Error,
+            1 => log::Level::Warn,
+            2 => log::Level::Info,
+            3 => log::Level::Debug,
+            _ => log::Level::Trace,
+        };
+        log::log!(level, "[wasm:{}] {}", caller.data().plugin_id, message);
+        Ok(())
+    })?;
+
+    linker.func_wrap(HOST_MODULE, "set_error", |mut caller: Caller<'_, HostState>, ptr: i32, len: i32| -> Result<(), wasmi::Error> {
+        let message = caller_string(&caller, ptr, len)?;
+        caller.data_mut().last_error = Some(message);
+        Ok(())
+    })?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "register_stage",
+        |mut caller: Caller<'_, HostState>, id_ptr: i32, id_len: i32, name_ptr: i32, name_len: i32, desc_ptr: i32, desc_len: i32| -> Result<i32, wasmi::Error> {
+            let id = caller_string(&caller, id_ptr, id_len)?;
+            let name = caller_string(&caller, name_ptr, name_len)?;
+            let description = caller_string(&caller, desc_ptr, desc_len)?;
+            let state = caller.data_mut();
+            if !state.registering {
+                state.last_error = Some("register_stage is only available during gini_register_stages".to_string());
+                return Ok(-1);
+            }
+            let prefix = format!("{}::", state.plugin_id);
+            if !id.starts_with(&prefix) {
+                state.last_error = Some(format!("Stage ID '{}' must start with '{}'", id, prefix));
+                return Ok(-1);
+            }
+            state.registered_stages.push(WasmStageInfo { id, name, description });
+            Ok(0)
+        },
+    )?;
+
+    linker.func_wrap(HOST_MODULE, "context_is_dry_run", |caller: Caller<'_, HostState>| -> i32 {
+        match caller.data().context() {
+            Some(context) => context.is_dry_run() as i32,
+            None => -1,
+        }
+    })?;
+
+    linker.func_wrap(HOST_MODULE, "context_get", |mut caller: Caller<'_, HostState>, key_ptr: i32, key_len: i32| -> Result<i64, wasmi::Error> {
+        let key = caller_string(&caller, key_ptr, key_len)?;
+        if !caller.data_mut().permitted(Capability::ContextKey, &key, Access::Read) {
+            return Ok(PERMISSION_DENIED as i64);
+        }
+        let value = caller.data().context().and_then(|context| {
+            context
+                .get_data::<String>(&key)
+                .cloned()
+                .or_else(|| context.get_cli_arg(&key).map(str::to_string))
+        });
+        return_string(&mut caller, value)
+    })?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "context_set",
+        |mut caller: Caller<'_, HostState>, key_ptr: i32, key_len: i32, val_ptr: i32, val_len: i32| -> Result<i32, wasmi::Error> {
+            let key = caller_string(&caller, key_ptr, key_len)?;
+            let value = caller_string(&caller, val_ptr, val_len)?;
+            let state = caller.data_mut();
+            if !state.permitted(Capability::ContextKey, &key, Access::Write) {
+                return Ok(PERMISSION_DENIED);
+            }
+            match state.context_mut() {
+                Some(context) => {
+                    context.set_data(&key, value);
+                    Ok(0)
+                }
+                None => {
+                    state.last_error = Some("context_set is only available during stage execution".to_string());
+                    Ok(-1)
+                }
+            }
+        },
+    )?;
+
+    linker.func_wrap(HOST_MODULE, "config_get", |mut caller: Caller<'_, HostState>, key_ptr: i32, key_len: i32| -> Result<i64, wasmi::Error> {
+        let key = caller_string(&caller, key_ptr, key_len)?;
+        let scope = config_scope_id(&caller.data().plugin_id, ConfigScope::Plugin(PluginConfigScope::User));
+        if !caller.data_mut().permitted(Capability::ConfigScope, &scope, Access::Read) {
+            return Ok(PERMISSION_DENIED as i64);
+        }
+        let state = caller.data();
+        let value = match &state.config_manager {
+            Some(config_manager) => match config_manager.get_plugin_config(&state.plugin_id) {
+                Ok(config) => config.get::<serde_json::Value>(&key).map(|value| value.to_string()),
+                Err(e) => {
+                    log::warn!("[wasm:{}] Failed to load plugin configuration: {}", state.plugin_id, e);
+                    None
+                }
+            },
+            None => None, // Configuration is available once the plugin is initialized
+        };
+        return_string(&mut caller, value)
+    })?;
+
+    linker.func_wrap(
+        HOST_MODULE,
+        "emit_event",
+        |mut caller: Caller<'_, HostState>, name_ptr: i32, name_len: i32, data_ptr: i32, data_len: i32| -> Result<i32, wasmi::Error> {
+            let name = caller_string(&caller, name_ptr, name_len)?;
+            let data = caller_string(&caller, data_ptr, data_len)?;
+            let state = caller.data_mut();
+            if !state.permitted(Capability::Event, &name, Access::Write) {
+                return Ok(PERMISSION_DENIED);
+            }
+            state.pending_events.push(PluginEvent {
+                name,
+                source: state.plugin_id.clone(),
+                data,
+                priority: EventPriority::Normal,
+                cancelable: false,
+            });
+            Ok(0)
+        },
+    )?;
+
+    Ok(linker)
+}
+
+/// An instantiated module and its store.
+struct WasmInstance {
+    store: Store<HostState>,
+    instance: Instance,
+}
+
+impl WasmInstance {
+    fn plugin_id(&self) -> &str {
+        &self.store.data().plugin_id
+    }
+
+    fn wasm_error(&self, operation: &str, message: impl Into<String>) -> PluginSystemError {
+        PluginSystemError::WasmError {
+            plugin_id: self.plugin_id().to_string(),
+            operation: operation.to_string(),
+            message: message.into(),
+        }
+    }
+
+    /// Calls an optional `fn(..) -> i32` export with a fresh fuel budget.
+    /// Returns `Ok(None)` if the module does not export it.
+    fn call<P: WasmParams>(&mut self, export: &str, params: P) -> Result<Option<i32>, PluginSystemError> {
+        let func = match self.instance.get_typed_func::<P, i32>(&self.store, export) {
+            Ok(func) => func,
+            Err(_) if self.instance.get_export(&self.store, export).is_none() => return Ok(None),
+            Err(e) => return Err(self.wasm_error(export, format!("export has the wrong signature: {}", e))),
+        };
+        self.store.data_mut().last_error = None;
+        self.store
+            .set_fuel(WASM_CALL_FUEL)
+            .map_err(|e| self.wasm_error(export, e.to_string()))?;
+        func.call(&mut self.store, params)
+            .map(Some)
+            .map_err(|e| self.wasm_error(export, format!("trapped: {}", e)))
+    }
+
+    /// Turns a non-zero status into the message the guest set with `set_error`.
+    fn status_message(&mut self, export: &str, status: i32) -> Option<String> {
+        if status == 0 {
+            return None;
+        }
+        let message = self.store.data_mut().last_error.take();
+        Some(message.unwrap_or_else(|| format!("{} returned status {}", export, status)))
+    }
+
+    /// Copies a string into guest memory through `gini_alloc`.
+    fn write_string(&mut self, value: &str) -> Result<(i32, i32), PluginSystemError> {
+        let memory = self
+            .instance
+            .get_memory(&self.store, "memory")
+            .ok_or_else(|| self.wasm_error("gini_alloc", "module does not export 'memory'"))?;
+        let alloc = self
+            .instance
+            .get_typed_func::<i32, i32>(&self.store, "gini_alloc")
+            .map_err(|e| self.wasm_error("gini_alloc", e.to_string()))?;
+        self.store
+            .set_fuel(WASM_CALL_FUEL)
+            .map_err(|e| self.wasm_error("gini_alloc", e.to_string()))?;
+        let packed = write_guest_string(&mut self.store, memory, alloc, value)
+            .map_err(|e| self.wasm_error("gini_alloc", e.to_string()))?;
+        Ok(((packed >> 32) as i32, value.len() as i32))
+    }
+
+    /// Takes the events emitted during the last call, with the event manager to queue them on.
+    fn take_events(&mut self) -> Option<(Arc<dyn EventManager>, Vec<PluginEvent>)> {
+        let state = self.store.data_mut();
+        let events = std::mem::take(&mut state.pending_events);
+        match &state.event_manager {
+            Some(event_manager) if !events.is_empty() => Some((event_manager.clone(), events)),
+            _ => None,
+        }
+    }
+}
+
+/// Queues events emitted by the guest.
+async fn queue_events(pending: Option<(Arc<dyn EventManager>, Vec<PluginEvent>)>) {
+    if let Some((event_manager, events)) = pending {
+        for event in events {
+            event_manager.queue_event(Box::new(event)).await;
+        }
+    }
+}
+
+/// Queues events emitted by the guest from a synchronous lifecycle method.
+fn queue_events_detached(pending: Option<(Arc<dyn EventManager>, Vec<PluginEvent>)>) {
+    if pending.is_none() {
+        return;
+    }
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) => {
+            handle.spawn(queue_events(pending));
+        }
+        Err(_) => log::warn!("Dropping events emitted by a WASM plugin outside of a tokio runtime"),
+    }
+}
+
+/// Permissions used until the plugin is initialized with the application's `PermissionManager`.
+fn standalone_permissions(manifest: &PluginManifest) -> Arc<PermissionManager> {
+    let permissions = PermissionManager::new();
+    permissions.register(&manifest.id, &manifest.resources);
+    Arc::new(permissions)
+}
+
+/// A plugin implemented as a WebAssembly module.
+pub struct WasmPlugin {
+    manifest: PluginManifest,
+    priority: PluginPriority,
+    instance: Arc<Mutex<WasmInstance>>, // Shared with the stages it registered
+}
+
+impl WasmPlugin {
+    /// Loads the module at the manifest's entry point.
+    pub fn from_manifest(manifest: &PluginManifest) -> Result<Self, PluginSystemError> {
+        let path = PluginLoader::entry_point_path(manifest)?;
+        let bytes = std::fs::read(&path).map_err(|e| PluginSystemError::LoadingError {
+            plugin_id: manifest.id.clone(),
+            path: Some(path.clone()),
+            source: Box::new(PluginSystemErrorSource::Io(e)),
+        })?;
+        Self::instantiate(manifest, &bytes, Some(&path))
+    }
+
+    /// Instantiates a module from its binary (or, for tests, its text) form.
+    pub fn from_bytes(manifest: &PluginManifest, bytes: &[u8]) -> Result<Self, PluginSystemError> {
+        Self::instantiate(manifest, bytes, None)
+    }
+
+    fn instantiate(manifest: &PluginManifest, bytes: &[u8], path: Option<&Path>) -> Result<Self, PluginSystemError> {
+        let loading_error = |message: String| PluginSystemError::LoadingError {
+            plugin_id: manifest.id.clone(),
+            path: path.map(Path::to_path_buf),
+            source: Box::new(PluginSystemErrorSource::Other(message)),
+        };
+        let abi_mismatch = |message: String| PluginSystemError::AbiMismatch {
+            plugin_id: manifest.id.clone(),
+            path: path.map(Path::to_path_buf),
+            message,
+        };
+
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, bytes).map_err(|e| loading_error(format!("Invalid WebAssembly module: {}", e)))?;
+
+        let state = HostState {
+            plugin_id: manifest.id.clone(),
+            limits: StoreLimitsBuilder::new().memory_size(WASM_MEMORY_LIMIT).build(),
+            last_error: None,
+            registering: false,
+            registered_stages: Vec::new(),
+            context: ContextAccess::None,
+            permissions: standalone_permissions(manifest),
+            config_manager: None,
+            event_manager: None,
+            pending_events: Vec::new(),
+        };
+        let mut store = Store::new(&engine, state);
+        store.limiter(|state| &mut state.limits);
+        store
+            .set_fuel(WASM_CALL_FUEL)
+            .map_err(|e| loading_error(e.to_string()))?;
+
+        let linker = host_linker(&engine).map_err(|e| loading_error(format!("Failed to define host functions: {}", e)))?;
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| loading_error(format!("Failed to instantiate module: {}", e)))?;
+
+        if instance.get_memory(&store, "memory").is_none() {
+            return Err(abi_mismatch("Module does not export 'memory'".to_string()));
+        }
+        let abi_version = instance
+            .get_typed_func::<(), i32>(&store, "gini_abi_version")
+            .map_err(|_| abi_mismatch("Module does not export 'gini_abi_version() -> i32'".to_string()))?
+            .call(&mut store, ())
+            .map_err(|e| abi_mismatch(format!("gini_abi_version trapped: {}", e)))?;
+        if abi_version != WASM_ABI_VERSION {
+            return Err(abi_mismatch(format!(
+                "Module targets WASM ABI version {} but the host supports version {}",
+                abi_version, WASM_ABI_VERSION
+            )));
+        }
+
+        let priority = manifest.get_priority().unwrap_or(DEFAULT_MANIFEST_PRIORITY);
+        Ok(Self {
+            manifest: manifest.clone(),
+            priority,
+            instance: Arc::new(Mutex::new(WasmInstance { store, instance })),
+        })
+    }
+
+    /// The manifest this plugin was loaded from
+    pub fn manifest(&self) -> &PluginManifest {
+        &self.manifest
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, WasmInstance>, PluginSystemError> {
+        self.instance.lock().map_err(|_| PluginSystemError::OperationError {
+            plugin_id: Some(self.manifest.id.clone()),
+            message: "WASM instance lock poisoned".to_string(),
+        })
+    }
+}
+
+#[async_trait]
+impl Plugin for WasmPlugin {
+    fn name(&self) -> &str {
+        self.manifest.id.as_str() // The plugin ID, not the display name
+    }
+
+    fn version(&self) -> &str {
+        &self.manifest.version
+    }
+
+    fn is_core(&self) -> bool {
+        self.manifest.is_core
+    }
+
+    fn priority(&self) -> PluginPriority {
+        self.priority.clone()
+    }
+
+    fn compatible_api_versions(&self) -> Vec<VersionRange> {
+        self.manifest.api_versions.clone()
+    }
+
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        self.manifest.dependencies.clone()
+    }
+
+    fn required_stages(&self) -> Vec<StageRequirement> {
+        Vec::new() // Stage requirements are not part of the manifest
+    }
+
+    fn conflicts_with(&self) -> Vec<String> {
+        self.manifest.conflicts_with.clone()
+    }
+
+    fn incompatible_with(&self) -> Vec<PluginDependency> {
+        self.manifest.incompatible_with.clone()
+    }
+
+    fn declared_resources(&self) -> Vec<conflict::ResourceClaim> {
+        self.manifest.resources.clone()
+    }
+
+    fn init(&self, app: &mut Application) -> Result<(), PluginSystemError> {
+        let mut instance = self.lock()?;
+        {
+            let event_manager = app.event_manager() as Arc<dyn EventManager>;
+            let state = instance.store.data_mut();
+            state.config_manager = Some(app.storage_manager().get_config_manager().clone());
+            state.event_manager = Some(event_manager.clone());
+            // Switch to the shared permissions once the registry knows the plugin, so accesses
+            // show up in its report; otherwise keep the manifest's permissions and audit through the app
+            let shared = app.plugin_manager().permissions().clone();
+            if shared.permission_set(&state.plugin_id).is_some() {
+                state.permissions = shared;
+            } else {
+                state.permissions.set_event_manager(event_manager);
+            }
+        }
+        let status = instance.call("gini_init", ())?;
+        queue_events_detached(instance.take_events());
+        match status.and_then(|status| instance.status_message("gini_init", status)) {
+            Some(message) => Err(PluginSystemError::InitializationError {
+                plugin_id: self.manifest.id.clone(),
+                message,
+                source: None,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    async fn preflight_check(&self, context: &StageContext) -> Result<(), PluginSystemError> {
+        let (result, events) = {
+            let mut instance = self.lock()?;
+            instance.store.data_mut().context = ContextAccess::Read(context as *const StageContext);
+            let status = instance.call("gini_preflight_check", ());
+            instance.store.data_mut().context = ContextAccess::None;
+            let result = status.map(|status| status.and_then(|status| instance.status_message("gini_preflight_check", status)));
+            (result, instance.take_events())
+        };
+        queue_events(events).await;
+        match result? {
+            Some(message) => Err(PluginSystemError::PreflightCheckFailed {
+                plugin_id: self.manifest.id.clone(),
+                message,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn register_stages(&self, registry: &mut StageRegistry) -> Result<(), PluginSystemError> {
+        let stages = {
+            let mut instance = self.lock()?;
+            instance.store.data_mut().registering = true;
+            let status = instance.call("gini_register_stages", ());
+            instance.store.data_mut().registering = false;
+            let stages = std::mem::take(&mut instance.store.data_mut().registered_stages);
+            if let Some(message) = status?.and_then(|status| instance.status_message("gini_register_stages", status)) {
+                return Err(PluginSystemError::RegistrationError {
+                    plugin_id: self.manifest.id.clone(),
+                    message,
+                });
+            }
+            stages
+        };
+
+        for info in stages {
+            println!("[WasmPlugin] Registering stage '{}' for plugin '{}'", info.id, self.manifest.id);
+            let stage = WasmStage {
+                info,
+                instance: self.instance.clone(),
+            };
+            registry.register_stage(Box::new(stage)).map_err(|e| PluginSystemError::RegistrationError {
+                plugin_id: self.manifest.id.clone(),
+                message: e.to_string(),
+            })?;
+        }
+        Ok(())
+    }
+
+    fn shutdown(&self) -> Result<(), PluginSystemError> {
+        let mut instance = self.lock()?;
+        let status = instance.call("gini_shutdown", ())?;
+        queue_events_detached(instance.take_events());
+        match status.and_then(|status| instance.status_message("gini_shutdown", status)) {
+            Some(message) => Err(PluginSystemError::ShutdownError {
+                plugin_id: self.manifest.id.clone(),
+                message,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A stage registered by a WASM plugin; executing it calls `gini_execute_stage`.
+pub struct WasmStage {
+    info: WasmStageInfo,
+    instance: Arc<Mutex<WasmInstance>>,
+}
+
+#[async_trait]
+impl Stage for WasmStage {
+    fn id(&self) -> &str {
+        &self.info.id
+    }
+
+    fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    fn description(&self) -> &str {
+        &self.info.description
+    }
+
+    async fn execute(&self, context: &mut StageContext) -> Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
+        let (result, events) = {
+            let mut instance = self
+                .instance
+                .lock()
+                .map_err(|_| format!("WASM instance lock poisoned while executing stage '{}'", self.info.id))?;
+            let result = instance.write_string(&self.info.id).and_then(|(ptr, len)| {
+                instance.store.data_mut().context = ContextAccess::Write(context as *mut StageContext);
+                let status = instance.call("gini_execute_stage", (ptr, len));
+                instance.store.data_mut().context = ContextAccess::None;
+                match status? {
+                    Some(status) => Ok(instance.status_message("gini_execute_stage", status)),
+                    None => Err(instance.wasm_error("gini_execute_stage", "module does not export 'gini_execute_stage'")),
+                }
+            });
+            (result, instance.take_events())
+        };
+        queue_events(events).await;
+        match result? {
+            Some(message) => Err(format!("Stage '{}' failed: {}", self.info.id, message).into()),
+            None => Ok(()),
+        }
+    }
+}
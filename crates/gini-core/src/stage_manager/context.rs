@@ -1,5 +1,7 @@
-use std::collections::HashMap;
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
+use std::sync::Arc;
 use crate::kernel::error::Result;
 
 /// Execution mode for stages
@@ -24,6 +26,13 @@ impl ExecutionMode {
 }
 
 /// Context provided to stages during execution
+///
+/// When a pipeline runs stages in parallel, each stage gets a scoped copy of the
+/// pipeline's context. It sees the data set before it started, including by the stages
+/// it depends on. The data it sets is merged back into the pipeline's context when it
+/// finishes. Values a scoped stage did not set itself are shared with the stages running
+/// alongside it, so [`get_data_mut`](Self::get_data_mut) returns `None` for them; store
+/// values that several stages update behind a `Mutex`.
 pub struct StageContext {
     /// The execution mode
     pub mode: ExecutionMode,
@@ -32,10 +41,13 @@ pub struct StageContext {
     config_dir: PathBuf,
     
     /// Shared data between stages
-    shared_data: HashMap<String, Box<dyn std::any::Any + Send + Sync>>,
+    shared_data: HashMap<String, Arc<dyn Any + Send + Sync>>,
     
     /// Passed CLI arguments
     cli_args: HashMap<String, String>,
+
+    /// Keys set in a scoped context, merged back into the pipeline's context; `None` otherwise
+    scope_writes: Option<HashSet<String>>,
 }
 
 impl StageContext {
@@ -46,6 +58,7 @@ impl StageContext {
             config_dir,
             shared_data: HashMap::new(),
             cli_args: HashMap::new(),
+            scope_writes: None,
         }
     }
     
@@ -56,6 +69,7 @@ impl StageContext {
             config_dir,
             shared_data: HashMap::new(),
             cli_args: HashMap::new(),
+            scope_writes: None,
         }
     }
     
@@ -69,6 +83,11 @@ impl StageContext {
         self.cli_args.get(key).map(|s| s.as_str())
     }
     
+    /// Get all CLI arguments
+    pub fn cli_args(&self) -> &HashMap<String, String> {
+        &self.cli_args
+    }
+    
     /// Get the configuration directory
     pub fn config_dir(&self) -> &PathBuf {
         &self.config_dir
@@ -76,7 +95,10 @@ impl StageContext {
     
     /// Set a shared data value
     pub fn set_data<T: 'static + Send + Sync>(&mut self, key: &str, value: T) {
-        self.shared_data.insert(key.to_string(), Box::new(value));
+        if let Some(writes) = &mut self.scope_writes {
+            writes.insert(key.to_string());
+        }
+        self.shared_data.insert(key.to_string(), Arc::new(value));
     }
     
     /// Get a shared data value
@@ -84,9 +106,62 @@ impl StageContext {
         self.shared_data.get(key).and_then(|data| data.downcast_ref::<T>())
     }
     
-    /// Get a mutable reference to a shared data value
+    /// Whether a shared data value is set under `key`, whatever its type
+    pub fn has_data(&self, key: &str) -> bool {
+        self.shared_data.contains_key(key)
+    }
+    
+    /// Get a mutable reference to a shared data value.
+    /// Returns `None` in a scoped context for values the stage did not set itself.
     pub fn get_data_mut<T: 'static + Send + Sync>(&mut self, key: &str) -> Option<&mut T> {
-        self.shared_data.get_mut(key).and_then(|data| data.downcast_mut::<T>())
+        self.shared_data.get_mut(key).and_then(Arc::get_mut).and_then(|data| data.downcast_mut::<T>())
+    }
+
+    /// The value set under `key`, whatever its type, to put back later with [`restore_data`](Self::restore_data)
+    pub(crate) fn data_entry(&self, key: &str) -> Option<Arc<dyn Any + Send + Sync>> {
+        self.shared_data.get(key).cloned()
+    }
+
+    /// Put back a value taken with [`data_entry`](Self::data_entry); `None` leaves `key` unset
+    pub(crate) fn restore_data(&mut self, key: &str, entry: Option<Arc<dyn Any + Send + Sync>>) {
+        match entry {
+            Some(value) => self.shared_data.insert(key.to_string(), value),
+            None => self.shared_data.remove(key),
+        };
+    }
+
+    /// Keep the values set under `keys` in a scoped context from being merged back
+    pub(crate) fn discard_scope_writes<'a>(&mut self, keys: impl IntoIterator<Item = &'a String>) {
+        if let Some(writes) = &mut self.scope_writes {
+            for key in keys {
+                writes.remove(key);
+            }
+        }
+    }
+
+    /// A copy of this context for a stage that runs alongside others.
+    /// Data is shared, not copied; what the stage sets is kept apart until [`merge_scope`](Self::merge_scope).
+    pub(crate) fn scoped(&self) -> StageContext {
+        Self {
+            mode: self.mode,
+            config_dir: self.config_dir.clone(),
+            shared_data: self.shared_data.clone(),
+            cli_args: self.cli_args.clone(),
+            scope_writes: Some(HashSet::new()),
+        }
+    }
+
+    /// Take over the data set in a scoped context. Where two scopes set the same key,
+    /// the one merged last wins.
+    pub(crate) fn merge_scope(&mut self, mut scope: StageContext) {
+        for key in scope.scope_writes.take().unwrap_or_default() {
+            if let Some(value) = scope.shared_data.remove(&key) {
+                if let Some(writes) = &mut self.scope_writes {
+                    writes.insert(key.clone());
+                }
+                self.shared_data.insert(key, value);
+            }
+        }
     }
     
     /// Check if dry run mode is active
@@ -4,7 +4,7 @@ use async_trait::async_trait;
 use tokio::sync::Mutex; // Already present, ensure it's used
 
 use crate::kernel::bootstrap::Application; // Needed for Plugin::init
-use crate::kernel::error::Error as KernelError; // Renamed for clarity, KernelResult removed
+use crate::plugin_system::lifecycle::PluginState;
 use crate::plugin_system::registry::PluginRegistry;
 use crate::stage_manager::{Stage, StageContext};
 use crate::stage_manager::error::StageSystemError; // Import StageSystemError
@@ -14,6 +14,7 @@ use crate::stage_manager::registry::SharedStageRegistry; // Add SharedStageRegis
 pub const PLUGIN_REGISTRY_KEY: &str = "plugin_registry";
 pub const PREFLIGHT_FAILURES_KEY: &str = "preflight_failures";
 pub const APPLICATION_KEY: &str = "application"; // Key for Application reference
+pub const STARTUP_REPORT_KEY: &str = "plugin_startup_report"; // StartupReport of the initialization stage
 
 // --- Core Stage Definitions ---
 
@@ -42,6 +43,7 @@ impl Stage for PluginPreflightCheckStage {
             .clone(); // Clone the Arc to work with it
 
         let registry = registry_arc_mutex.lock().await;
+        let lifecycle = registry.lifecycle().clone();
         let mut failures = HashSet::new();
         // If a fundamental error occurs (e.g. context access), this will be set.
         // Individual plugin preflight failures are logged and added to `failures` set.
@@ -54,13 +56,29 @@ impl Stage for PluginPreflightCheckStage {
             if failures.contains(id) {
                 continue;
             }
+            // Disabled plugins are never initialized, so there is nothing to check
+            // (and manifest-only plugins would otherwise have their libraries loaded)
+            if !registry.is_enabled(id) {
+                println!("  - Skipping disabled plugin: {}", plugin.name());
+                continue;
+            }
+            // Plugins that are already running keep their state
+            let record_state = !registry.is_initialized(id);
             println!("  - Checking plugin: {}", plugin.name());
             match plugin.preflight_check(context).await {
                 Ok(_) => {
                     println!("    - Pre-flight check PASSED for {}", plugin.name());
+                    if record_state
+                        && let Err(e) = lifecycle.transition(id, PluginState::PreflightPassed, "Pre-flight check passed")
+                    {
+                        log::debug!("{}", e);
+                    }
                 }
                 Err(e) => {
                     eprintln!("    - Pre-flight check FAILED for {}: {}", plugin.name(), e);
+                    if record_state {
+                        lifecycle.fail(id, &e.to_string());
+                    }
                     failures.insert(id.clone());
                     // We log the error and add to failures, but the stage itself doesn't fail here.
                     // The PluginInitializationStage will use the `failures` set.
@@ -103,6 +121,7 @@ impl Stage for PluginInitializationStage {
     fn id(&self) -> &str { "core::plugin_initialization" }
     fn name(&self) -> &str { "Plugin Initialization" }
     fn description(&self) -> &str { "Initializes all plugins that passed previous checks." }
+    fn is_exclusive(&self) -> bool { true } // Borrows the Application in the context mutably
 
     async fn execute(&self, context: &mut StageContext) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync + 'static>> {
         println!("Executing Stage: {}", self.name());
@@ -143,7 +162,7 @@ impl Stage for PluginInitializationStage {
             for failed_plugin_id in preflight_failures {
                 // Pass the stage_registry_arc.registry (which is Arc<Mutex<StageRegistry>>)
                 // The disable_plugin method expects &Arc<Mutex<StageRegistry>>
-                match registry.disable_plugin(&failed_plugin_id, &stage_registry_arc.registry).await {
+                match registry.disable_plugin_with_reason(&failed_plugin_id, "Pre-flight check failed", &stage_registry_arc.registry).await {
                     Ok(_) => println!("  - Plugin '{}' disabled due to pre-flight failure.", failed_plugin_id),
                     Err(e) => eprintln!("  - Warning: Failed to disable plugin '{}' after pre-flight failure: {}", failed_plugin_id, e),
                 }
@@ -152,12 +171,13 @@ impl Stage for PluginInitializationStage {
 
         // Call the registry's initialize_all method, passing the app and the StageRegistry Arc.
         // This method internally checks if plugins are enabled before initializing them.
-        // It returns Result<_, PluginSystemError>, which needs to be boxed if we want to propagate it directly.
-        // Or, map it to KernelError then box, or handle it here.
-        // For now, let's map to KernelError then box.
-        registry.initialize_all(app, &stage_registry_arc.registry).await.map_err(|e| Box::new(KernelError::from(e)) as Box<dyn std::error::Error + Send + Sync + 'static>)?;
+        // It already returns a KernelError, which only needs to be boxed to propagate it.
+        let report = registry.initialize_all(app, &stage_registry_arc.registry).await.map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync + 'static>)?;
+        drop(registry);
 
         println!("Plugin initialization complete.");
+        // Plugins that failed without stopping startup are listed in the report
+        context.set_data(STARTUP_REPORT_KEY, report);
         Ok(())
     }
 
@@ -0,0 +1,307 @@
+//! # Pipeline Definitions
+//!
+//! A [`PipelineDefinition`] describes a pipeline by name: its stages, the stages each one
+//! `depends_on`, the parameters injected into the [`StageContext`] before a stage runs,
+//! the [`StageCondition`] under which it runs and its [`StagePolicy`].
+//!
+//! Plugins register definitions from code through
+//! [`StageRegistry::register_pipeline`](crate::stage_manager::registry::StageRegistry::register_pipeline).
+//! Users write them as files in the `pipelines` directory of the application configuration
+//! (`$XDG_CONFIG_HOME/gini/pipelines/*.{toml,yaml,json}`), which
+//! [`load_pipeline_definitions`] reads through the [`ConfigManager`]:
+//!
+//! ```toml
+//! description = "Checks the packages once the OS is known"
+//! max_parallelism = 2
+//!
+//! [[stages]]
+//! id = "env_check:gather_os_info"
+//!
+//! [[stages]]
+//! id = "env_check:check_system_packages"
+//! depends_on = ["env_check:gather_os_info"]
+//! params = { verbose = true }
+//! condition = { not = { env = "CI" } }
+//! policy = { timeout_ms = 30000, on_failure = "continue" }
+//! ```
+//!
+//! A file without a `name` is named after the file.
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::kernel::error::Result as KernelResult;
+use crate::stage_manager::context::StageContext;
+use crate::stage_manager::error::StageSystemError;
+use crate::stage_manager::pipeline::StagePipeline;
+use crate::stage_manager::policy::StagePolicy;
+use crate::storage::config::{ConfigData, ConfigManager, ConfigScope};
+
+/// Subdirectory of the application configuration that holds pipeline definition files
+pub const PIPELINES_CONFIG_DIR: &str = "pipelines";
+
+/// When a stage of a pipeline runs. Checked just before the stage would start, so it
+/// sees the data set by the stages it depends on; a stage whose condition is not met is skipped.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum StageCondition {
+    /// Context data is set under the key, whatever its type
+    Set(String),
+    /// Context data under `key` equals `value`. Strings, booleans, integers, floats and
+    /// JSON values in the context can be compared.
+    Equals { key: String, value: Value },
+    /// The environment variable is set
+    Env(String),
+    /// The environment variable is set to `value`
+    EnvEquals { name: String, value: String },
+    /// The condition is not met
+    Not(Box<StageCondition>),
+    /// Every condition is met
+    All(Vec<StageCondition>),
+    /// At least one condition is met
+    Any(Vec<StageCondition>),
+}
+
+impl StageCondition {
+    /// Whether the condition holds for `context` and the process environment
+    pub fn is_met(&self, context: &StageContext) -> bool {
+        match self {
+            StageCondition::Set(key) => context.has_data(key),
+            StageCondition::Equals { key, value } => context_value(context, key).as_ref() == Some(value),
+            StageCondition::Env(name) => std::env::var_os(name).is_some(),
+            StageCondition::EnvEquals { name, value } => std::env::var(name).is_ok_and(|actual| actual == *value),
+            StageCondition::Not(condition) => !condition.is_met(context),
+            StageCondition::All(conditions) => conditions.iter().all(|condition| condition.is_met(context)),
+            StageCondition::Any(conditions) => conditions.iter().any(|condition| condition.is_met(context)),
+        }
+    }
+}
+
+impl fmt::Display for StageCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |conditions: &[StageCondition]| conditions.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+        match self {
+            StageCondition::Set(key) => write!(f, "set({})", key),
+            StageCondition::Equals { key, value } => write!(f, "{} == {}", key, value),
+            StageCondition::Env(name) => write!(f, "env({})", name),
+            StageCondition::EnvEquals { name, value } => write!(f, "env({}) == {:?}", name, value),
+            StageCondition::Not(condition) => write!(f, "not({})", condition),
+            StageCondition::All(conditions) => write!(f, "all({})", list(conditions)),
+            StageCondition::Any(conditions) => write!(f, "any({})", list(conditions)),
+        }
+    }
+}
+
+/// Context data under `key` as JSON, if it has one of the types parameters and CLI variables are stored as
+fn context_value(context: &StageContext, key: &str) -> Option<Value> {
+    if let Some(value) = context.get_data::<Value>(key) {
+        return Some(value.clone());
+    }
+    if let Some(value) = context.get_data::<String>(key) {
+        return Some(Value::from(value.as_str()));
+    }
+    if let Some(value) = context.get_data::<&'static str>(key) {
+        return Some(Value::from(*value));
+    }
+    if let Some(value) = context.get_data::<bool>(key) {
+        return Some(Value::from(*value));
+    }
+    if let Some(value) = context.get_data::<i64>(key) {
+        return Some(Value::from(*value));
+    }
+    if let Some(value) = context.get_data::<f64>(key) {
+        return Some(Value::from(*value));
+    }
+    None
+}
+
+/// Set a stage parameter in the context: strings as `String`, booleans as `bool`,
+/// integers as `i64`, other numbers as `f64` and anything else as a JSON `Value`
+pub(crate) fn set_param(context: &mut StageContext, key: &str, value: &Value) {
+    match value {
+        Value::String(value) => context.set_data(key, value.clone()),
+        Value::Bool(value) => context.set_data(key, *value),
+        Value::Number(number) => match (number.as_i64(), number.as_f64()) {
+            (Some(value), _) => context.set_data(key, value),
+            (None, Some(value)) => context.set_data(key, value),
+            (None, None) => context.set_data(key, value.clone()),
+        },
+        _ => context.set_data(key, value.clone()),
+    }
+}
+
+/// One stage of a [`PipelineDefinition`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineStageDefinition {
+    /// ID of the registered stage
+    pub id: String,
+    /// Stages of the same pipeline that must finish first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+    /// Values set in the context under their keys while the stage runs
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, Value>,
+    /// The stage is skipped unless this holds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub condition: Option<StageCondition>,
+    #[serde(default)]
+    pub policy: StagePolicy,
+}
+
+impl PipelineStageDefinition {
+    /// A stage without dependencies, parameters or a condition, run with the default policy
+    pub fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            depends_on: Vec::new(),
+            params: BTreeMap::new(),
+            condition: None,
+            policy: StagePolicy::default(),
+        }
+    }
+}
+
+fn default_max_parallelism() -> usize {
+    1
+}
+
+/// Describes a pipeline that can be looked up by name and built with [`to_pipeline`](Self::to_pipeline)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineDefinition {
+    /// The unique identifier name for the pipeline.
+    pub name: String,
+    /// An optional description of the pipeline's purpose.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// How many independent stages may run at once
+    #[serde(default = "default_max_parallelism")]
+    pub max_parallelism: usize,
+    /// The stages of the pipeline, in the order they are added to it
+    pub stages: Vec<PipelineStageDefinition>,
+}
+
+impl PipelineDefinition {
+    /// A pipeline that runs `stage_ids` one after another
+    pub fn new(name: &str, stage_ids: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            description: None,
+            max_parallelism: default_max_parallelism(),
+            stages: stage_ids.iter().map(|id| PipelineStageDefinition::new(id)).collect(),
+        }
+    }
+
+    /// Set the description
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Read a definition from a loaded configuration file; `default_name` names it if the file does not
+    pub fn from_config(config: &ConfigData, default_name: &str) -> Result<Self, StageSystemError> {
+        let invalid = |reason: String| StageSystemError::InvalidPipelineDefinition { name: default_name.to_string(), reason };
+        let mut value = serde_json::to_value(config).map_err(|e| invalid(e.to_string()))?;
+        if let Some(fields) = value.as_object_mut() {
+            fields.entry("name").or_insert_with(|| Value::from(default_name));
+        }
+        let definition: Self = serde_json::from_value(value).map_err(|e| invalid(e.to_string()))?;
+        definition.validate()?;
+        Ok(definition)
+    }
+
+    /// IDs of the stages, in order
+    pub fn stage_ids(&self) -> impl Iterator<Item = &str> {
+        self.stages.iter().map(|stage| stage.id.as_str())
+    }
+
+    /// Check that no stage is listed twice and that stages only depend on stages of this pipeline.
+    /// Whether the stages are registered is checked when the definition is registered.
+    pub fn validate(&self) -> Result<(), StageSystemError> {
+        let invalid = |reason: String| StageSystemError::InvalidPipelineDefinition { name: self.name.clone(), reason };
+        if self.stages.is_empty() {
+            return Err(invalid("it has no stages".to_string()));
+        }
+        let mut ids = HashSet::new();
+        for stage in &self.stages {
+            if !ids.insert(stage.id.as_str()) {
+                return Err(invalid(format!("stage '{}' is listed more than once", stage.id)));
+            }
+        }
+        for stage in &self.stages {
+            if let Some(dependency) = stage.depends_on.iter().find(|dependency| !ids.contains(dependency.as_str())) {
+                return Err(invalid(format!("stage '{}' depends on '{}', which is not a stage of the pipeline", stage.id, dependency)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Build the pipeline this definition describes
+    pub fn to_pipeline(&self) -> Result<StagePipeline, StageSystemError> {
+        let mut pipeline = StagePipeline::new(&self.name, self.description.as_deref().unwrap_or(""));
+        pipeline.set_max_parallelism(self.max_parallelism);
+        for stage in &self.stages {
+            pipeline.add_stage(&stage.id).map_err(|e| StageSystemError::InternalError(e.to_string()))?;
+        }
+        for stage in &self.stages {
+            for dependency in &stage.depends_on {
+                pipeline.add_dependency(&stage.id, dependency)?;
+            }
+            pipeline.set_stage_policy(&stage.id, stage.policy)?;
+            if !stage.params.is_empty() {
+                pipeline.set_stage_params(&stage.id, stage.params.clone())?;
+            }
+            if let Some(condition) = &stage.condition {
+                pipeline.set_stage_condition(&stage.id, condition.clone())?;
+            }
+        }
+        Ok(pipeline)
+    }
+}
+
+impl fmt::Display for PipelineDefinition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Pipeline: {}", self.name)?;
+        if let Some(description) = &self.description {
+            writeln!(f, "Description: {}", description)?;
+        }
+        writeln!(f, "Max parallelism: {}", self.max_parallelism)?;
+        writeln!(f, "Stages:")?;
+        for stage in &self.stages {
+            writeln!(f, "  - {}", stage.id)?;
+            if !stage.depends_on.is_empty() {
+                writeln!(f, "      depends on: {}", stage.depends_on.join(", "))?;
+            }
+            for (key, value) in &stage.params {
+                writeln!(f, "      param: {} = {}", key, value)?;
+            }
+            if let Some(condition) = &stage.condition {
+                writeln!(f, "      condition: {}", condition)?;
+            }
+            if stage.policy != StagePolicy::default() {
+                writeln!(f, "      policy: {}", stage.policy)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Load the pipeline definition files in the [`PIPELINES_CONFIG_DIR`] directory of the
+/// application configuration, in file name order. Fails only if the directory cannot be
+/// read; each file that is not a valid definition gives an error of its own.
+pub fn load_pipeline_definitions(config_manager: &ConfigManager) -> KernelResult<Vec<Result<PipelineDefinition, StageSystemError>>> {
+    let files = config_manager.list_app_config_files(PIPELINES_CONFIG_DIR)?;
+    Ok(files
+        .into_iter()
+        .map(|file| {
+            let default_name = std::path::Path::new(&file).file_stem().and_then(|stem| stem.to_str()).unwrap_or_default().to_string();
+            let config = config_manager.load_config(&file, ConfigScope::Application).map_err(|e| {
+                StageSystemError::InvalidPipelineDefinition { name: default_name.clone(), reason: format!("cannot read {}: {}", file, e) }
+            })?;
+            PipelineDefinition::from_config(&config, &default_name)
+        })
+        .collect())
+}
@@ -40,6 +40,12 @@ pub enum StageSystemError {
         source: Box<dyn std::error::Error + Send + Sync + 'static>,
     },
 
+    #[error("Stage '{stage_id}' timed out after {timeout:?}")]
+    StageTimedOut { stage_id: String, timeout: std::time::Duration },
+
+    #[error("Invalid pipeline definition '{name}': {reason}")]
+    InvalidPipelineDefinition { name: String, reason: String },
+
     #[error("Invalid stage dependency for stage '{stage_id}': {reason}")]
     InvalidStageDependency { stage_id: String, reason: String },
 
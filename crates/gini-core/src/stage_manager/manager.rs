@@ -8,6 +8,8 @@ use crate::kernel::component::KernelComponent;
 use crate::kernel::error::{Result, Error as KernelError}; // Import KernelError for specific error creation
 use crate::stage_manager::{Stage, StageContext, StageResult};
 use crate::stage_manager::pipeline::{StagePipeline, PipelineBuilder};
+use crate::stage_manager::definition::{load_pipeline_definitions, PipelineDefinition};
+use crate::storage::config::ConfigManager;
 use crate::stage_manager::error::StageSystemError; // Import StageSystemError
 use crate::stage_manager::registry::SharedStageRegistry;
 use crate::event::EventManager; // Added for EventManager
@@ -36,6 +38,12 @@ pub trait StageManager: KernelComponent {
     /// Retrieve a predefined pipeline by its name, constructing it from its definition.
     async fn get_pipeline_by_name(&self, name: &str) -> Result<Option<StagePipeline>>;
 
+    /// Register a pipeline definition; its stages must be registered
+    async fn register_pipeline(&self, definition: PipelineDefinition) -> Result<()>;
+
+    /// Get the registered pipeline definitions, sorted by name
+    async fn get_pipeline_definitions(&self) -> Result<Vec<PipelineDefinition>>;
+
     /// Execute a pipeline with the given context
     async fn execute_pipeline(&self, pipeline: &mut StagePipeline, context: &mut StageContext) -> Result<HashMap<String, StageResult>>;
 
@@ -71,6 +79,20 @@ impl DefaultStageManager {
     pub fn registry(&self) -> Arc<tokio::sync::Mutex<crate::stage_manager::registry::StageRegistry>> {
         self.shared_registry.registry()
     }
+
+    /// Register the pipeline definition files of the application configuration
+    /// (see [`load_pipeline_definitions`]). Files that cannot be loaded or registered are
+    /// left out; their errors are returned.
+    pub async fn register_configured_pipelines(&self, config_manager: &ConfigManager) -> Result<Vec<StageSystemError>> {
+        let mut errors = Vec::new();
+        for definition in load_pipeline_definitions(config_manager)? {
+            let mut registry = self.shared_registry.registry.lock().await;
+            if let Err(e) = definition.and_then(|definition| registry.register_pipeline(definition)) {
+                errors.push(e);
+            }
+        }
+        Ok(errors)
+    }
 }
 
 #[async_trait]
@@ -128,30 +150,36 @@ impl StageManager for DefaultStageManager {
     async fn get_pipeline_by_name(&self, name: &str) -> Result<Option<StagePipeline>> {
         let registry_guard = self.shared_registry.registry.lock().await;
         if let Some(pipeline_def) = registry_guard.get_pipeline_definition(name) {
-            // Found the definition, now construct a StagePipeline
-            let mut builder = PipelineBuilder::new(pipeline_def.name, pipeline_def.description.unwrap_or(""));
-            for stage_id_ref in pipeline_def.stages {
-                let stage_id = stage_id_ref.to_string();
-                // Ensure stage still exists in the registry using the public method
-                if !registry_guard.has_stage(&stage_id) {
-                    return Err(KernelError::from(StageSystemError::StageNotFoundInPipelineDefinition {
-                        pipeline_name: name.to_string(), // Use the pipeline name from the function argument
-                        stage_id: stage_id.clone(),
-                    }));
-                }
-                builder = builder.add_stage(&stage_id);
+            // Ensure the stages still exist in the registry using the public method
+            if let Some(stage_id) = pipeline_def.stage_ids().find(|id| !registry_guard.has_stage(id)) {
+                return Err(KernelError::from(StageSystemError::StageNotFoundInPipelineDefinition {
+                    pipeline_name: name.to_string(), // Use the pipeline name from the function argument
+                    stage_id: stage_id.to_string(),
+                }));
             }
-            Ok(Some(builder.build()))
+            // Found the definition, now construct a StagePipeline
+            Ok(Some(pipeline_def.to_pipeline().map_err(KernelError::from)?))
         } else {
             Ok(None) // Pipeline definition not found
         }
     }
 
+    async fn register_pipeline(&self, definition: PipelineDefinition) -> Result<()> {
+        self.shared_registry.registry.lock().await.register_pipeline(definition).map_err(KernelError::from)
+    }
+
+    async fn get_pipeline_definitions(&self) -> Result<Vec<PipelineDefinition>> {
+        let registry = self.shared_registry.registry.lock().await;
+        Ok(registry.get_pipeline_definitions().into_iter().cloned().collect())
+    }
+
     async fn execute_pipeline(&self, pipeline: &mut StagePipeline, context: &mut StageContext) -> Result<HashMap<String, StageResult>> {
         let pipeline_name = pipeline.name().to_string();
         let execution_result = pipeline.execute(context, &self.shared_registry).await;
         
-        let success = execution_result.is_ok();
+        // Stages that failed without aborting the pipeline still make it unsuccessful
+        let success = execution_result.as_ref()
+            .is_ok_and(|results| !results.values().any(|result| matches!(result, StageResult::Failure(_))));
         
         let event = PipelineExecutionCompletedEvent {
             pipeline_name,
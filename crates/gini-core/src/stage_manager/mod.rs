@@ -17,7 +17,8 @@
 //!   the lifecycle of stages, including registration, dependency resolution,
 //!   pipeline construction, and execution.
 //! - **[`StagePipeline`](pipeline::StagePipeline)**: Represents an ordered sequence of stages
-//!   to be executed. Pipelines can be dynamically built and configured.
+//!   to be executed. Pipelines can be dynamically built and configured, and can run
+//!   independent stages in parallel.
 //! - **[`StageRegistry`](registry::StageRegistry)**: A collection of all available stages,
 //!   allowing them to be discovered and utilized by the `StageManager`.
 //! - **[`StageResult`]**: An enum indicating the outcome of a stage's execution
@@ -30,6 +31,8 @@
 //!     - `error`: Defines error types specific to the stage manager ([`StageError`](error::StageError)).
 //!     - `manager`: Contains the `StageManager`.
 //!     - `pipeline`: Defines the `StagePipeline`.
+//!     - `definition`: Owned [`PipelineDefinition`](definition::PipelineDefinition)s, written in code or loaded from configuration files.
+//!     - `policy`: Per-stage timeouts, retries and failure handling ([`StagePolicy`](policy::StagePolicy)).
 //!     - `registry`: Contains the `StageRegistry`.
 //!     - `requirement`: Logic for stage requirements and capabilities.
 //!
@@ -44,6 +47,8 @@ pub mod dry_run;
 pub mod dependency;
 pub mod manager;
 pub mod requirement;
+pub mod policy;
+pub mod definition;
 pub mod core_stages; // Make the new module public
 
 // Removed: use crate::kernel::error::Result;
@@ -66,6 +71,13 @@ pub trait Stage: Send + Sync {
     fn supports_dry_run(&self) -> bool {
         true // Most stages should support dry run by default
     }
+
+    /// Whether this stage must run on its own when a pipeline runs stages in parallel.
+    /// An exclusive stage waits for the running stages to finish and gets the
+    /// pipeline's context itself, e.g. to borrow data other stages set mutably.
+    fn is_exclusive(&self) -> bool {
+        false
+    }
     
     /// Execute the stage with the given context
     async fn execute(&self, context: &mut context::StageContext) -> std::result::Result<(), Box<dyn std::error::Error + Send + Sync + 'static>>;
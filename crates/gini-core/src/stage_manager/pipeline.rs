@@ -1,21 +1,20 @@
-use std::collections::{HashMap, HashSet};
+use std::any::Any;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+use serde_json::Value;
+use tokio::task::JoinSet;
 use crate::kernel::error::{Error as KernelError, Result as KernelResult}; // Renamed Error & Result
-use crate::stage_manager::{StageContext, StageResult};
+use crate::stage_manager::{Stage, StageContext, StageResult};
 use crate::stage_manager::error::StageSystemError; // Import StageSystemError
 // Import SharedStageRegistry for execute method
-use crate::stage_manager::registry::SharedStageRegistry;
-
-/// Represents a static definition of a pipeline.
-/// Used for defining constant pipelines that can be easily referenced.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct PipelineDefinition {
-    /// The unique identifier name for the pipeline.
-    pub name: &'static str,
-    /// An ordered slice of stage IDs included in this pipeline.
-    pub stages: &'static [&'static str],
-    /// An optional description of the pipeline's purpose.
-    pub description: Option<&'static str>,
-}
+use crate::stage_manager::registry::{SharedStageRegistry, StageRegistry};
+use crate::stage_manager::definition::{set_param, StageCondition};
+use crate::stage_manager::policy::{FailureAction, StagePolicy};
+use crate::plugin_system::traits::PluginPriority;
+
+/// Declared in [`definition`](crate::stage_manager::definition), together with the policies of its stages
+pub use crate::stage_manager::definition::PipelineDefinition;
+
 /// Stage execution pipeline
 pub struct StagePipeline {
     /// Name of the pipeline
@@ -26,6 +25,14 @@ pub struct StagePipeline {
     stages: Vec<String>,
     /// Optional dependencies between stages
     dependencies: HashMap<String, Vec<String>>,
+    /// How many independent stages may run at once; 1 runs them one after another
+    max_parallelism: usize,
+    /// Execution policies by stage ID; stages without one use `StagePolicy::default()`
+    policies: HashMap<String, StagePolicy>,
+    /// Values set in the context before a stage runs, by stage ID
+    params: HashMap<String, BTreeMap<String, Value>>,
+    /// Conditions under which stages run, by stage ID
+    conditions: HashMap<String, StageCondition>,
     // Removed registry: StageRegistry field
 }
 
@@ -37,6 +44,10 @@ impl StagePipeline {
             description: description.to_string(),
             stages: Vec::new(),
             dependencies: HashMap::new(),
+            max_parallelism: 1,
+            policies: HashMap::new(),
+            params: HashMap::new(),
+            conditions: HashMap::new(),
             // No registry initialization here
         }
     }
@@ -88,6 +99,56 @@ impl StagePipeline {
         Ok(())
     }
 
+    /// Run up to `max_parallelism` stages at once once the stages they depend on have
+    /// succeeded. Stages without a dependency between them may then run in any order.
+    /// 1, the default, runs stages one after another; 0 is treated as 1.
+    pub fn set_max_parallelism(&mut self, max_parallelism: usize) {
+        self.max_parallelism = max_parallelism.max(1);
+    }
+
+    /// How many stages may run at once
+    pub fn max_parallelism(&self) -> usize {
+        self.max_parallelism
+    }
+
+    /// Set how a stage of this pipeline runs: its timeout, its retries and what its failure
+    /// does to the rest of the pipeline
+    pub fn set_stage_policy(&mut self, stage_id: &str, policy: StagePolicy) -> std::result::Result<(), StageSystemError> {
+        self.check_has_stage(stage_id, "a policy")?;
+        self.policies.insert(stage_id.to_string(), policy);
+        Ok(())
+    }
+
+    /// The policy a stage of this pipeline runs with
+    pub fn stage_policy(&self, stage_id: &str) -> StagePolicy {
+        self.policies.get(stage_id).copied().unwrap_or_default()
+    }
+
+    /// Set values in the context under their keys just before a stage of this pipeline runs;
+    /// once it has run, the keys get back the values they had before. Strings are set as `String`, booleans as `bool`, integers as `i64`, other numbers as
+    /// `f64` and arrays and tables as `serde_json::Value`.
+    pub fn set_stage_params(&mut self, stage_id: &str, params: BTreeMap<String, Value>) -> std::result::Result<(), StageSystemError> {
+        self.check_has_stage(stage_id, "parameters")?;
+        self.params.insert(stage_id.to_string(), params);
+        Ok(())
+    }
+
+    /// Only run a stage of this pipeline when `condition` holds; otherwise it is skipped
+    pub fn set_stage_condition(&mut self, stage_id: &str, condition: StageCondition) -> std::result::Result<(), StageSystemError> {
+        self.check_has_stage(stage_id, "a condition")?;
+        self.conditions.insert(stage_id.to_string(), condition);
+        Ok(())
+    }
+
+    fn check_has_stage(&self, stage_id: &str, what: &str) -> std::result::Result<(), StageSystemError> {
+        if self.stages.iter().any(|id| id == stage_id) {
+            return Ok(());
+        }
+        Err(StageSystemError::PipelineValidationFailed {
+            reason: format!("Pipeline '{}' has no stage '{}' to set {} for", self.name, stage_id, what),
+        })
+    }
+
     /// Validate the pipeline structure (cycles) and stage existence against a registry
     // Changed to return Result<(), StageSystemError>
     pub async fn validate(&self, registry: &SharedStageRegistry) -> std::result::Result<(), StageSystemError> {
@@ -156,25 +217,37 @@ impl StagePipeline {
  
     /// Generate a topologically sorted execution order
     /// Validation (including cycle check) should happen before calling this.
+    ///
+    /// Of the stages that do not depend on each other, those registered by
+    /// higher-priority plugins (`priorities`) come first; stages registered by the
+    /// application come before plugin stages. Otherwise the order stages were added in is kept.
     // Changed to return Result<Vec<String>, StageSystemError>
-    fn get_execution_order(&self) -> std::result::Result<Vec<String>, StageSystemError> {
+    fn get_execution_order(&self, priorities: &HashMap<String, PluginPriority>) -> std::result::Result<Vec<String>, StageSystemError> {
         // Assume validate() was called externally and succeeded
         let mut result = Vec::new();
         let mut visited = HashSet::new();
         let mut temp_mark = HashSet::new(); // For cycle detection during sort
 
-        for stage_id in &self.stages {
+        for stage_id in Self::by_priority(&self.stages, priorities) {
             if !visited.contains(stage_id) {
-                self.visit_for_topsort(stage_id, &mut visited, &mut temp_mark, &mut result)?;
+                self.visit_for_topsort(stage_id, priorities, &mut visited, &mut temp_mark, &mut result)?;
             }
         }
         Ok(result)
     }
 
+    /// Stage IDs in a stable order of plugin priority, application stages first
+    fn by_priority<'a>(stage_ids: &'a [String], priorities: &HashMap<String, PluginPriority>) -> Vec<&'a String> {
+        let mut ordered: Vec<&String> = stage_ids.iter().collect();
+        ordered.sort_by_key(|id| priorities.get(*id));
+        ordered
+    }
+
     /// Visit nodes for topological sort (internal helper)
     fn visit_for_topsort(
         &self,
         stage_id: &str,
+        priorities: &HashMap<String, PluginPriority>,
         visited: &mut HashSet<String>,
         temp_mark: &mut HashSet<String>,
         result: &mut Vec<String>,
@@ -193,8 +266,8 @@ impl StagePipeline {
         temp_mark.insert(stage_id.to_string());
 
         if let Some(deps) = self.dependencies.get(stage_id) {
-            for dep in deps {
-                self.visit_for_topsort(dep, visited, temp_mark, result)?;
+            for dep in Self::by_priority(deps, priorities) {
+                self.visit_for_topsort(dep, priorities, visited, temp_mark, result)?;
             }
         }
 
@@ -220,7 +293,8 @@ impl StagePipeline {
              self.validate(registry).await.map_err(KernelError::from)?; // Validate against registry
              println!("Dry run validation successful.");
              // Return simulated success for all stages in order
-             let execution_order = self.get_execution_order().map_err(KernelError::from)?;
+             let priorities = registry.stage_priorities(&self.stages).await;
+             let execution_order = self.get_execution_order(&priorities).map_err(KernelError::from)?;
              let results = execution_order.into_iter().map(|id| (id, StageResult::Success)).collect();
              return Ok(results);
         }
@@ -236,37 +310,202 @@ impl StagePipeline {
 
 
         // Get the execution order
-        let execution_order = self.get_execution_order().map_err(KernelError::from)?;
+        let priorities = registry.stage_priorities(&self.stages).await;
+        let execution_order = self.get_execution_order(&priorities).map_err(KernelError::from)?;
+        if self.max_parallelism > 1 {
+            return self.execute_parallel(execution_order, context, registry).await;
+        }
         let mut results = HashMap::new();
  
         // Execute each stage in order using the provided registry
         for stage_id in execution_order {
-            // Use the registry passed as argument
-            // registry.execute_stage now returns KernelResult<StageResult>
-            // which wraps Result<StageResult, StageSystemError>
-            match registry.execute_stage(&stage_id, context).await {
-                Ok(stage_outcome) => {
-                    results.insert(stage_id.clone(), stage_outcome.clone());
-                    // The StageResult::Failure case for aborting is removed because
-                    // execute_stage_internal now returns Err(StageSystemError::StageExecutionFailed)
-                    // for actual errors from Stage::execute.
-                    // If StageResult::Failure were to be used for other "logical" non-error failures
-                    // that should still halt the pipeline, that logic would go here.
-                    // For now, only an Err from execute_stage halts the pipeline.
-                }
-                Err(kernel_err) => {
-                    // This means execute_stage_internal returned Err(StageSystemError),
-                    // which was mapped to KernelError by SharedStageRegistry::execute_stage.
-                    // This is a hard error from the stage execution itself (e.g. StageExecutionFailed).
-                    println!("Pipeline aborted due to stage error: {} - {}", stage_id, kernel_err);
-                    return Err(kernel_err); // Propagate the KernelError
-                }
+            if let Some(reason) = self.skip_reason(&stage_id, &results, context) {
+                println!("Skipping stage {}: {}", stage_id, reason);
+                results.insert(stage_id, StageResult::Skipped(reason));
+                continue;
+            }
+            let stage = registry.get_stage_arc(&stage_id).await
+                .ok_or_else(|| KernelError::from(StageSystemError::StageNotFound { stage_id: stage_id.clone() }))?;
+            let replaced = self.set_params(&stage_id, context);
+            let outcome = Self::run_with_policy(stage.as_ref(), self.stage_policy(&stage_id), context).await;
+            Self::restore_params(context, replaced);
+            // Only a failure under the `abort` policy halts the pipeline
+            if let Err(e) = self.record_outcome(stage_id.clone(), outcome, &mut results) {
+                println!("Pipeline aborted due to stage error: {} - {}", stage_id, e);
+                return Err(KernelError::from(e));
             }
         }
  
         Ok(results)
     }
 
+    /// Run a stage under `policy`: each attempt is bounded by the timeout, and a failed
+    /// attempt is retried after the backoff. Retries see what earlier attempts set in the context.
+    async fn run_with_policy(stage: &dyn Stage, policy: StagePolicy, context: &mut StageContext) -> std::result::Result<StageResult, StageSystemError> {
+        let mut retry = 0;
+        loop {
+            let attempt = StageRegistry::run_stage(stage, context);
+            let result = match policy.timeout {
+                Some(timeout) => tokio::time::timeout(timeout, attempt).await
+                    .unwrap_or_else(|_| Err(StageSystemError::StageTimedOut { stage_id: stage.id().to_string(), timeout })),
+                None => attempt.await,
+            };
+            match result {
+                Err(e) if retry < policy.retries => {
+                    retry += 1;
+                    let wait = policy.backoff_before(retry);
+                    println!("Stage {} failed ({}); retry {} of {} in {:?}", stage.id(), e, retry, policy.retries, wait);
+                    tokio::time::sleep(wait).await;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Record how a stage ended. A failure is recorded as `StageResult::Failure` unless the
+    /// stage's policy aborts the pipeline, in which case the error is returned.
+    fn record_outcome(
+        &self,
+        stage_id: String,
+        outcome: std::result::Result<StageResult, StageSystemError>,
+        results: &mut HashMap<String, StageResult>,
+    ) -> std::result::Result<(), StageSystemError> {
+        let result = match outcome {
+            Ok(result) => result,
+            Err(e) if self.stage_policy(&stage_id).on_failure == FailureAction::Abort => return Err(e),
+            Err(e) => StageResult::Failure(e.to_string()),
+        };
+        results.insert(stage_id, result);
+        Ok(())
+    }
+
+    /// Why a stage is skipped, if it is: a stage it depends on was skipped, or failed
+    /// under the `skip-dependents` policy, or the stage's condition does not hold
+    fn skip_reason(&self, stage_id: &str, results: &HashMap<String, StageResult>, context: &StageContext) -> Option<String> {
+        let dependency_reason = self.dependencies.get(stage_id).into_iter().flatten().find_map(|dep| match results.get(dep) {
+            Some(StageResult::Skipped(_)) => Some(format!("dependency '{}' was skipped", dep)),
+            Some(StageResult::Failure(_)) if self.stage_policy(dep).on_failure == FailureAction::SkipDependents => {
+                Some(format!("dependency '{}' failed", dep))
+            }
+            _ => None,
+        });
+        dependency_reason.or_else(|| {
+            let condition = self.conditions.get(stage_id)?;
+            (!condition.is_met(context)).then(|| format!("condition not met: {}", condition))
+        })
+    }
+
+    /// Set the parameters of a stage in the context it runs with and return the values they
+    /// replace, to put back with [`restore_params`](Self::restore_params) once the stage has run
+    fn set_params(&self, stage_id: &str, context: &mut StageContext) -> Vec<(String, Option<Arc<dyn Any + Send + Sync>>)> {
+        let mut replaced = Vec::new();
+        for (key, value) in self.params.get(stage_id).into_iter().flatten() {
+            replaced.push((key.clone(), context.data_entry(key)));
+            set_param(context, key, value);
+        }
+        replaced
+    }
+
+    /// Put back the values a stage's parameters replaced, so they do not reach later stages
+    fn restore_params(context: &mut StageContext, replaced: Vec<(String, Option<Arc<dyn Any + Send + Sync>>)>) {
+        for (key, entry) in replaced {
+            context.restore_data(&key, entry);
+        }
+    }
+
+    /// Run the stages of `execution_order` as soon as their dependencies have finished,
+    /// at most `max_parallelism` at a time, each with a scoped copy of `context`.
+    /// Ready stages start in execution order. After a stage fails under the `abort` policy
+    /// no further stages start; the running ones finish and the first error is returned.
+    async fn execute_parallel(
+        &self,
+        execution_order: Vec<String>,
+        context: &mut StageContext,
+        registry: &SharedStageRegistry,
+    ) -> KernelResult<HashMap<String, StageResult>> {
+        let mut pending = execution_order;
+        let mut results = HashMap::new();
+        let mut running = JoinSet::new();
+        let mut running_ids = HashMap::new(); // Task ID -> stage ID, to report panicking stages
+        let mut failure: Option<StageSystemError> = None;
+
+        loop {
+            while failure.is_none() && running.len() < self.max_parallelism {
+                let Some(index) = pending.iter().position(|id| self.dependencies_finished(id, &results)) else {
+                    break;
+                };
+                if let Some(reason) = self.skip_reason(&pending[index], &results, context) {
+                    let stage_id = pending.remove(index);
+                    println!("Skipping stage {}: {}", stage_id, reason);
+                    results.insert(stage_id, StageResult::Skipped(reason));
+                    continue;
+                }
+                let Some(stage) = registry.get_stage_arc(&pending[index]).await else {
+                    failure = Some(StageSystemError::StageNotFound { stage_id: pending.remove(index) });
+                    break;
+                };
+                if stage.is_exclusive() {
+                    if !running.is_empty() {
+                        break; // Runs once the stages already started have finished
+                    }
+                    let stage_id = pending.remove(index);
+                    let replaced = self.set_params(&stage_id, context);
+                    let outcome = Self::run_with_policy(stage.as_ref(), self.stage_policy(&stage_id), context).await;
+                    Self::restore_params(context, replaced);
+                    if let Err(e) = self.record_outcome(stage_id, outcome, &mut results) {
+                        failure = Some(e);
+                    }
+                    continue;
+                }
+
+                let stage_id = pending.remove(index);
+                let mut scope = context.scoped();
+                self.set_params(&stage_id, &mut scope);
+                let task_stage_id = stage_id.clone();
+                let policy = self.stage_policy(&stage_id);
+                let handle = running.spawn(async move {
+                    let result = Self::run_with_policy(stage.as_ref(), policy, &mut scope).await;
+                    (task_stage_id, scope, result)
+                });
+                running_ids.insert(handle.id(), stage_id);
+            }
+
+            let Some(joined) = running.join_next().await else {
+                break;
+            };
+            match joined {
+                Ok((stage_id, mut scope, result)) => {
+                    // The stage's parameters stay with the stage
+                    scope.discard_scope_writes(self.params.get(&stage_id).into_iter().flat_map(|params| params.keys()));
+                    context.merge_scope(scope);
+                    if let Err(e) = self.record_outcome(stage_id, result, &mut results) {
+                        failure.get_or_insert(e);
+                    }
+                }
+                Err(join_error) => {
+                    let stage_id = running_ids.get(&join_error.id()).cloned().unwrap_or_default();
+                    failure.get_or_insert(StageSystemError::StageExecutionFailed {
+                        stage_id,
+                        source: format!("Stage task did not complete: {}", join_error).into(),
+                    });
+                }
+            }
+        }
+
+        match failure {
+            Some(e) => {
+                println!("Pipeline aborted due to stage error: {}", e);
+                Err(KernelError::from(e))
+            }
+            None => Ok(results),
+        }
+    }
+
+    /// Whether every stage `stage_id` depends on has finished, whatever its result
+    fn dependencies_finished(&self, stage_id: &str, results: &HashMap<String, StageResult>) -> bool {
+        self.dependencies.get(stage_id).is_none_or(|deps| deps.iter().all(|dep| results.contains_key(dep)))
+    }
+
     /// Get the name of the pipeline
     pub fn name(&self) -> &str {
         &self.name
@@ -323,6 +562,30 @@ impl PipelineBuilder {
         self
     }
 
+    /// Run up to `max_parallelism` independent stages at once
+    pub fn max_parallelism(mut self, max_parallelism: usize) -> Self {
+        self.pipeline.set_max_parallelism(max_parallelism);
+        self
+    }
+
+    /// Set how a stage runs; ignored if the stage has not been added
+    pub fn stage_policy(mut self, stage_id: &str, policy: StagePolicy) -> Self {
+        let _ = self.pipeline.set_stage_policy(stage_id, policy); // Ignore result here
+        self
+    }
+
+    /// Set values in the context while a stage runs; ignored if the stage has not been added
+    pub fn stage_params(mut self, stage_id: &str, params: BTreeMap<String, Value>) -> Self {
+        let _ = self.pipeline.set_stage_params(stage_id, params); // Ignore result here
+        self
+    }
+
+    /// Only run a stage when `condition` holds; ignored if the stage has not been added
+    pub fn stage_condition(mut self, stage_id: &str, condition: StageCondition) -> Self {
+        let _ = self.pipeline.set_stage_condition(stage_id, condition); // Ignore result here
+        self
+    }
+
     /// Build the pipeline. Validation against a registry must be done separately.
     pub fn build(self) -> StagePipeline {
         // Basic structural validation (cycles) can be done here if desired,
@@ -0,0 +1,144 @@
+//! # Stage Execution Policies
+//!
+//! A [`StagePolicy`] says how a pipeline runs one of its stages: how long an attempt
+//! may take, how often a failed attempt is retried and how long to wait in between,
+//! and what the pipeline does once the stage has failed for good ([`FailureAction`]).
+//! Stages without a policy run once, without a timeout, and abort the pipeline on failure.
+//!
+//! In pipeline definition files, durations are given in milliseconds:
+//! `policy = { timeout_ms = 30000, retries = 2, backoff_ms = 500, on_failure = "skip-dependents" }`.
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// What a pipeline does when a stage has failed on its last attempt.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailureAction {
+    /// Stop the pipeline and return the stage's error
+    #[default]
+    Abort,
+    /// Record the failure and keep running the other stages, including those that depend on it
+    Continue,
+    /// Record the failure and skip every stage that depends on it, directly or not
+    SkipDependents,
+}
+
+impl FromStr for FailureAction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "abort" => Ok(FailureAction::Abort),
+            "continue" => Ok(FailureAction::Continue),
+            "skip-dependents" => Ok(FailureAction::SkipDependents),
+            other => Err(format!("Unknown stage failure action '{}' (expected abort, continue or skip-dependents)", other)),
+        }
+    }
+}
+
+impl fmt::Display for FailureAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FailureAction::Abort => write!(f, "abort"),
+            FailureAction::Continue => write!(f, "continue"),
+            FailureAction::SkipDependents => write!(f, "skip-dependents"),
+        }
+    }
+}
+
+/// How a pipeline runs a stage. The builder methods are `const`, so policies can be constants.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StagePolicy {
+    /// How long one attempt may take before it counts as failed
+    #[serde(rename = "timeout_ms", with = "optional_millis", skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<Duration>,
+    /// How many times a failed attempt is retried
+    pub retries: u32,
+    /// Wait before the first retry, doubled before each further one
+    #[serde(rename = "backoff_ms", with = "millis")]
+    pub backoff: Duration,
+    /// What happens once the last attempt has failed
+    pub on_failure: FailureAction,
+}
+
+impl StagePolicy {
+    /// Run once, without a timeout, and abort the pipeline on failure
+    pub const fn new() -> Self {
+        Self {
+            timeout: None,
+            retries: 0,
+            backoff: Duration::ZERO,
+            on_failure: FailureAction::Abort,
+        }
+    }
+
+    /// Fail an attempt that takes longer than `timeout`
+    pub const fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retry a failed attempt up to `retries` times, waiting `backoff` before the
+    /// first retry and twice as long before each further one
+    pub const fn retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.retries = retries;
+        self.backoff = backoff;
+        self
+    }
+
+    /// What to do once the last attempt has failed
+    pub const fn on_failure(mut self, action: FailureAction) -> Self {
+        self.on_failure = action;
+        self
+    }
+
+    /// The wait before retry number `retry`, counting from 1
+    pub fn backoff_before(&self, retry: u32) -> Duration {
+        self.backoff.saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+    }
+}
+
+impl fmt::Display for StagePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(timeout) = self.timeout {
+            write!(f, "timeout {:?}, ", timeout)?;
+        }
+        if self.retries > 0 {
+            write!(f, "{} retries (backoff {:?}), ", self.retries, self.backoff)?;
+        }
+        write!(f, "on failure {}", self.on_failure)
+    }
+}
+
+/// Serializes a `Duration` as whole milliseconds
+mod millis {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_millis)
+    }
+}
+
+/// Serializes an optional `Duration` as whole milliseconds
+mod optional_millis {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => super::millis::serialize(duration, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        Option::<u64>::deserialize(deserializer).map(|millis| millis.map(Duration::from_millis))
+    }
+}
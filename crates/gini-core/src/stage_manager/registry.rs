@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::Mutex; // Use tokio's Mutex
 use std::fmt; // Import fmt
@@ -6,16 +6,19 @@ use std::fmt; // Import fmt
 use crate::kernel::error::{Error as KernelError, Result as KernelResult}; // Renamed Error & Result
 use crate::stage_manager::{Stage, StageContext, StageResult};
 use crate::stage_manager::error::StageSystemError; // Import StageSystemError
-use crate::stage_manager::pipeline::PipelineDefinition; // Added for storing pipeline definitions
+use crate::stage_manager::definition::PipelineDefinition; // Added for storing pipeline definitions
+use crate::plugin_system::traits::PluginPriority; // Orders stages contributed by plugins
 
 /// Registry for managing stages and pipeline definitions
 // Removed Clone derive as Box<dyn Stage> is not Clone
 // Implement Debug manually
 pub struct StageRegistry {
-    /// Registered stages by ID
-    stages: HashMap<String, Box<dyn Stage>>,
+    /// Registered stages by ID; shared so pipelines can run them without holding the registry
+    stages: HashMap<String, Arc<dyn Stage>>,
     /// Registered pipeline definitions by name
     pipelines: HashMap<String, PipelineDefinition>, // Ensure no 'static here
+    /// Priority of the plugin that registered each stage; absent for stages registered by the application
+    stage_priorities: HashMap<String, PluginPriority>,
 }
 
 // Manual Debug implementation
@@ -38,6 +41,7 @@ impl StageRegistry {
         Self {
             stages: HashMap::new(),
             pipelines: HashMap::new(),
+            stage_priorities: HashMap::new(),
         }
     }
 
@@ -49,25 +53,45 @@ impl StageRegistry {
             return Err(StageSystemError::StageAlreadyExists { stage_id: id });
         }
  
-        self.stages.insert(id, stage);
+        self.stages.insert(id, Arc::from(stage));
         Ok(())
     }
 
+    /// Run `register` (typically a plugin's `register_stages`) and record `priority`
+    /// for every stage it registers. Pipelines run independent stages of
+    /// higher-priority plugins first.
+    pub fn register_plugin_stages<E>(
+        &mut self,
+        priority: PluginPriority,
+        register: impl FnOnce(&mut Self) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E> {
+        let existing: HashSet<String> = self.stages.keys().cloned().collect();
+        let result = register(self);
+        let added: Vec<String> = self.stages.keys().filter(|id| !existing.contains(*id)).cloned().collect();
+        for id in added {
+            self.stage_priorities.insert(id, priority.clone());
+        }
+        result
+    }
+
+    /// Priority of the plugin that registered a stage, if a plugin registered it
+    pub fn stage_priority(&self, id: &str) -> Option<&PluginPriority> {
+        self.stage_priorities.get(id)
+    }
+
     /// Register a pipeline definition
     pub fn register_pipeline(&mut self, pipeline_def: PipelineDefinition) -> std::result::Result<(), StageSystemError> { // Ensure no 'static here
-        let name = pipeline_def.name.to_string();
+        let name = pipeline_def.name.clone();
         if self.pipelines.contains_key(&name) {
             return Err(StageSystemError::PipelineAlreadyExists { pipeline_name: name });
         }
+        pipeline_def.validate()?;
         // Validate that all stages in the pipeline definition exist in the stage registry
-        for stage_id_ref in pipeline_def.stages {
-            let stage_id = stage_id_ref.to_string(); // Convert &&str to String if necessary, or adjust type
-            if !self.stages.contains_key(&stage_id) {
-                return Err(StageSystemError::StageNotFoundInPipelineDefinition {
-                    pipeline_name: name,
-                    stage_id,
-                });
-            }
+        if let Some(stage_id) = pipeline_def.stage_ids().find(|id| !self.stages.contains_key(*id)) {
+            return Err(StageSystemError::StageNotFoundInPipelineDefinition {
+                pipeline_name: name,
+                stage_id: stage_id.to_string(),
+            });
         }
         self.pipelines.insert(name, pipeline_def);
         Ok(())
@@ -78,13 +102,31 @@ impl StageRegistry {
         self.stages.contains_key(id)
     }
 
+    /// Get a registered stage by ID
+    pub fn get_stage(&self, id: &str) -> Option<&dyn Stage> {
+        self.stages.get(id).map(|stage| stage.as_ref())
+    }
+
+    /// Get a shared handle to a registered stage, to run it without holding the registry
+    pub fn get_stage_arc(&self, id: &str) -> Option<Arc<dyn Stage>> {
+        self.stages.get(id).cloned()
+    }
+
     /// Get a reference to a pipeline definition by its name
     pub fn get_pipeline_definition(&self, name: &str) -> Option<&PipelineDefinition> { // Ensure no 'static here
         self.pipelines.get(name)
     }
 
+    /// Get all registered pipeline definitions, sorted by name
+    pub fn get_pipeline_definitions(&self) -> Vec<&PipelineDefinition> {
+        let mut definitions: Vec<&PipelineDefinition> = self.pipelines.values().collect();
+        definitions.sort_by(|a, b| a.name.cmp(&b.name));
+        definitions
+    }
+
     /// Remove a stage by ID
-    pub fn remove_stage(&mut self, id: &str) -> Option<Box<dyn Stage>> {
+    pub fn remove_stage(&mut self, id: &str) -> Option<Arc<dyn Stage>> {
+        self.stage_priorities.remove(id);
         self.stages.remove(id)
     }
 
@@ -101,13 +143,19 @@ impl StageRegistry {
     /// Clear all stages
     pub fn clear(&mut self) {
         self.stages.clear();
+        self.stage_priorities.clear();
     }
 
     /// Execute a specific stage asynchronously (internal method)
     /// Takes &self because Stage::execute takes &self.
     pub async fn execute_stage_internal(&self, id: &str, context: &mut StageContext) -> std::result::Result<StageResult, StageSystemError> {
         let stage = self.stages.get(id).ok_or_else(|| StageSystemError::StageNotFound { stage_id: id.to_string() })?;
- 
+        Self::run_stage(stage.as_ref(), context).await
+    }
+
+    /// Execute a stage, or describe it in dry run mode
+    pub(crate) async fn run_stage(stage: &dyn Stage, context: &mut StageContext) -> std::result::Result<StageResult, StageSystemError> {
+        let id = stage.id();
         println!("Executing stage: {} ({})", stage.name(), id);
  
         if context.is_dry_run() {
@@ -150,14 +198,13 @@ impl StageRegistry {
 
         for stage_id_to_remove in stages_to_remove {
             self.stages.remove(&stage_id_to_remove);
+            self.stage_priorities.remove(&stage_id_to_remove);
             // Also remove from any pipelines if they reference this stage by ID.
             // This is more complex as pipelines store stage IDs as strings.
             // For now, we'll focus on removing from the primary stages map.
             // A more robust solution might involve checking/updating pipeline definitions.
             // For now, we only remove from the main stages map.
             // If a pipeline definition refers to a removed stage, it will fail validation/execution later.
-            // Consider if PipelineDefinition.stages should be Vec<String> if runtime modification is desired.
-            // Given &'static [&'static str], they are intended to be static.
         }
         Ok(())
     }
@@ -200,15 +247,27 @@ impl SharedStageRegistry {
         registry.has_stage(id)
     }
  
-    /// Execute a specific stage asynchronously
+    /// Execute a specific stage asynchronously.
+    /// The registry is not locked while the stage runs, so stages may use it.
     pub async fn execute_stage(&self, id: &str, context: &mut StageContext) -> KernelResult<StageResult> {
-        // Lock the registry immutably first to get the stage reference if needed,
-        // or lock mutably if the internal execute needs mutable access.
-        // Since execute_stage_internal now takes &self, we lock immutably.
-        let registry = self.registry.lock().await;
-        registry.execute_stage_internal(id, context).await.map_err(KernelError::from)
+        let stage = self.get_stage_arc(id).await
+            .ok_or_else(|| KernelError::from(StageSystemError::StageNotFound { stage_id: id.to_string() }))?;
+        StageRegistry::run_stage(stage.as_ref(), context).await.map_err(KernelError::from)
+    }
+
+    /// Get a shared handle to a registered stage
+    pub async fn get_stage_arc(&self, id: &str) -> Option<Arc<dyn Stage>> {
+        self.registry.lock().await.get_stage_arc(id)
     }
  
+    /// Priorities of the given stages that were registered by plugins
+    pub async fn stage_priorities(&self, ids: &[String]) -> HashMap<String, PluginPriority> {
+        let registry = self.registry.lock().await;
+        ids.iter()
+            .filter_map(|id| registry.stage_priority(id).map(|priority| (id.clone(), priority.clone())))
+            .collect()
+    }
+
     /// Get all registered stage IDs
     pub async fn get_all_ids(&self) -> Vec<String> { // Made infallible
         let registry = self.registry.lock().await;
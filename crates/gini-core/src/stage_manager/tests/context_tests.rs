@@ -135,4 +135,30 @@ fn test_context_get_data_mut() {
 #[derive(Debug, Clone, PartialEq)]
 struct MyTestData {
     value: i32,
-}
\ No newline at end of file
+}
+#[test]
+fn test_scoped_context_merges_what_the_stage_set() {
+    let mut context = StageContext::new_live(dummy_path());
+    context.set_data("shared", MyTestData { value: 1 });
+    context.set_cli_arg("verbose", "true");
+
+    let mut first = context.scoped();
+    let mut second = context.scoped();
+    assert_eq!(first.get_data::<MyTestData>("shared"), Some(&MyTestData { value: 1 }));
+    assert_eq!(first.get_cli_arg("verbose"), Some("true"));
+    assert!(first.get_data_mut::<MyTestData>("shared").is_none(), "Inherited values are shared with other scopes");
+    first.set_data("first", 10u32);
+    *first.get_data_mut::<u32>("first").unwrap() += 1;
+    second.set_data("shared", MyTestData { value: 2 });
+    second.set_data("second", "done".to_string());
+
+    context.merge_scope(second);
+    context.merge_scope(first);
+
+    assert_eq!(context.get_data::<u32>("first"), Some(&11));
+    assert_eq!(context.get_data::<String>("second").map(String::as_str), Some("done"));
+    // `first` never set "shared", so merging it does not bring back the old value
+    assert_eq!(context.get_data::<MyTestData>("shared"), Some(&MyTestData { value: 2 }));
+    context.get_data_mut::<MyTestData>("shared").unwrap().value = 3;
+    assert_eq!(context.get_data::<MyTestData>("shared").unwrap().value, 3);
+}
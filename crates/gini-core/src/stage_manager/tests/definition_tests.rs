@@ -0,0 +1,215 @@
+use crate::event::{DefaultEventManager, EventManager};
+use crate::stage_manager::{Stage, StageContext, StageManager, StageResult};
+use crate::stage_manager::definition::{set_param, PipelineDefinition, PipelineStageDefinition, StageCondition};
+use crate::stage_manager::error::StageSystemError;
+use crate::stage_manager::manager::DefaultStageManager;
+use crate::stage_manager::pipeline::PipelineBuilder;
+use crate::stage_manager::policy::{FailureAction, StagePolicy};
+use crate::storage::config::ConfigData;
+use crate::storage::tests::config_manager_in;
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::error::Error as StdError;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tempfile::tempdir;
+
+type Seen = Arc<Mutex<Vec<String>>>;
+
+/// Records the `mode` and `level` parameters it sees and sets `<id>.done`
+struct ParamStage {
+    id: String,
+    seen: Seen,
+}
+
+#[async_trait]
+impl Stage for ParamStage {
+    fn id(&self) -> &str { &self.id }
+    fn name(&self) -> &str { &self.id }
+    fn description(&self) -> &str { "Records its parameters" }
+
+    async fn execute(&self, context: &mut StageContext) -> std::result::Result<(), Box<dyn StdError + Send + Sync + 'static>> {
+        self.seen.lock().unwrap().push(format!(
+            "{}: mode={:?} level={:?}",
+            self.id, context.get_data::<String>("mode"), context.get_data::<i64>("level")
+        ));
+        context.set_data(&format!("{}.done", self.id), true);
+        Ok(())
+    }
+}
+
+async fn stage_manager_with(ids: &[&str], seen: &Seen) -> DefaultStageManager {
+    let stage_manager = DefaultStageManager::new(Arc::new(DefaultEventManager::new()) as Arc<dyn EventManager>);
+    for id in ids {
+        stage_manager.register_stage(Box::new(ParamStage { id: id.to_string(), seen: seen.clone() })).await.unwrap();
+    }
+    stage_manager
+}
+
+#[tokio::test]
+async fn test_configured_pipelines_load_register_and_run() {
+    let dir = tempdir().unwrap();
+    let pipelines = dir.path().join("config/pipelines");
+    fs::create_dir_all(&pipelines).unwrap();
+    fs::write(pipelines.join("checks.json"), r#"{
+        "description": "Gathers, then reports",
+        "stages": [
+            { "id": "gather" },
+            {
+                "id": "report",
+                "depends_on": ["gather"],
+                "params": { "mode": "full", "level": 2 },
+                "condition": { "set": "gather.done" },
+                "policy": { "retries": 1, "on_failure": "continue" }
+            }
+        ]
+    }"#).unwrap();
+    fs::write(pipelines.join("ghost.json"), r#"{ "stages": [{ "id": "ghost" }] }"#).unwrap();
+    fs::write(pipelines.join("notes.txt"), "not a pipeline").unwrap();
+    #[cfg(feature = "toml-config")]
+    fs::write(pipelines.join("nightly.toml"), r#"
+name = "nightly-checks"
+max_parallelism = 2
+
+[[stages]]
+id = "gather"
+
+[[stages]]
+id = "optional"
+condition = { env = "GINI_DEFINITION_TEST_UNSET" }
+
+[[stages]]
+id = "report"
+depends_on = ["optional"]
+policy = { timeout_ms = 1000 }
+"#).unwrap();
+    #[cfg(feature = "yaml-config")]
+    fs::write(pipelines.join("typo.yaml"), "stages:\n  - id: gather\n    depends: [report]\n").unwrap();
+    let seen = Seen::default();
+    let stage_manager = stage_manager_with(&["gather", "optional", "report"], &seen).await;
+
+    let errors = stage_manager.register_configured_pipelines(&config_manager_in(dir.path())).await.unwrap();
+
+    let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    assert!(errors.iter().any(|e| e.contains("'ghost' not found")), "{:?}", errors);
+    #[cfg(feature = "yaml-config")]
+    assert!(errors.iter().any(|e| e.contains("'typo'") && e.contains("unknown field `depends`")), "{:?}", errors);
+    let names: Vec<String> = stage_manager.get_pipeline_definitions().await.unwrap().into_iter().map(|definition| definition.name).collect();
+    #[cfg(feature = "toml-config")]
+    assert_eq!(names, vec!["checks", "nightly-checks"]);
+    #[cfg(not(feature = "toml-config"))]
+    assert_eq!(names, vec!["checks"]);
+
+    let mut pipeline = stage_manager.get_pipeline_by_name("checks").await.unwrap().unwrap();
+    assert_eq!(pipeline.stage_policy("report").on_failure, FailureAction::Continue);
+    let mut context = StageContext::new_live(dir.path().to_path_buf());
+    let results = stage_manager.execute_pipeline(&mut pipeline, &mut context).await.unwrap();
+    assert!(results.values().all(|result| matches!(result, StageResult::Success)), "{:?}", results);
+    assert_eq!(*seen.lock().unwrap(), vec![
+        "gather: mode=None level=None",
+        "report: mode=Some(\"full\") level=Some(2)",
+    ]);
+
+    #[cfg(feature = "toml-config")]
+    {
+        let mut pipeline = stage_manager.get_pipeline_by_name("nightly-checks").await.unwrap().unwrap();
+        assert_eq!(pipeline.max_parallelism(), 2);
+        let results = stage_manager.execute_pipeline(&mut pipeline, &mut context).await.unwrap();
+        assert!(matches!(results.get("gather"), Some(StageResult::Success)));
+        assert_eq!(results["optional"].to_string(), "Skipped: condition not met: env(GINI_DEFINITION_TEST_UNSET)");
+        assert_eq!(results["report"].to_string(), "Skipped: dependency 'optional' was skipped");
+    }
+}
+
+#[tokio::test]
+async fn test_stage_params_stay_with_their_stage() {
+    let dir = tempdir().unwrap();
+    let seen = Seen::default();
+    let stage_manager = stage_manager_with(&["first", "second", "third"], &seen).await;
+
+    for max_parallelism in [1, 2] {
+        seen.lock().unwrap().clear();
+        let mut pipeline = PipelineBuilder::new("scoped-params", "Two stages set the same parameter")
+            .add_stages(&["first", "second", "third"])
+            .add_dependency("second", "first")
+            .add_dependency("third", "second")
+            .stage_params("first", BTreeMap::from([("mode".to_string(), json!("fast")), ("level".to_string(), json!(1))]))
+            .stage_params("second", BTreeMap::from([("mode".to_string(), json!("slow"))]))
+            .max_parallelism(max_parallelism)
+            .build();
+        let mut context = StageContext::new_live(dir.path().to_path_buf());
+        context.set_data("mode", "default".to_string());
+
+        stage_manager.execute_pipeline(&mut pipeline, &mut context).await.unwrap();
+
+        assert_eq!(*seen.lock().unwrap(), vec![
+            "first: mode=Some(\"fast\") level=Some(1)",
+            "second: mode=Some(\"slow\") level=None",
+            "third: mode=Some(\"default\") level=None",
+        ], "max_parallelism {}", max_parallelism);
+        assert_eq!(context.get_data::<String>("mode").map(String::as_str), Some("default"));
+        assert!(!context.has_data("level"));
+        assert_eq!(context.get_data::<bool>("second.done"), Some(&true), "Data the stages set is kept");
+    }
+}
+
+#[test]
+fn test_definitions_are_validated_and_displayed() {
+    let invalid = |definition: PipelineDefinition| definition.validate().unwrap_err().to_string();
+    assert!(invalid(PipelineDefinition::new("empty", &[])).contains("has no stages"));
+    assert!(invalid(PipelineDefinition::new("twice", &["a", "a"])).contains("'a' is listed more than once"));
+    let mut dangling = PipelineDefinition::new("dangling", &["a"]);
+    dangling.stages[0].depends_on.push("b".to_string());
+    assert!(invalid(dangling).contains("'a' depends on 'b', which is not a stage of the pipeline"));
+
+    let mut config = ConfigData::new();
+    config.set("stages", json!([{ "id": "a", "policy": { "timeout": 5 } }])).unwrap();
+    let error = PipelineDefinition::from_config(&config, "file-name").unwrap_err();
+    assert!(matches!(&error, StageSystemError::InvalidPipelineDefinition { name, .. } if name == "file-name"), "{}", error);
+
+    let mut definition = PipelineDefinition::new("shown", &["a"]).with_description("Shown stage by stage");
+    let mut stage = PipelineStageDefinition::new("b");
+    stage.depends_on = vec!["a".to_string()];
+    stage.params.insert("level".to_string(), json!(2));
+    stage.condition = Some(StageCondition::Not(Box::new(StageCondition::Env("CI".to_string()))));
+    stage.policy = StagePolicy::new().timeout(Duration::from_millis(1500)).retries(2, Duration::from_millis(10));
+    definition.stages.push(stage);
+    assert_eq!(definition.to_string(), "\
+Pipeline: shown
+Description: Shown stage by stage
+Max parallelism: 1
+Stages:
+  - a
+  - b
+      depends on: a
+      param: level = 2
+      condition: not(env(CI))
+      policy: timeout 1.5s, 2 retries (backoff 10ms), on failure abort
+");
+}
+
+#[test]
+fn test_conditions_compare_parameters_and_context_data() {
+    let condition: StageCondition = serde_json::from_value(json!({
+        "all": [
+            { "equals": { "key": "mode", "value": "full" } },
+            { "equals": { "key": "level", "value": 2 } },
+            { "any": [{ "set": "missing" }, { "equals": { "key": "verbose", "value": true } }] },
+            { "not": { "env_equals": { "name": "GINI_DEFINITION_TEST_UNSET", "value": "1" } } }
+        ]
+    })).unwrap();
+    let mut context = StageContext::new_live(std::env::temp_dir());
+    assert!(!condition.is_met(&context));
+
+    set_param(&mut context, "mode", &json!("full"));
+    set_param(&mut context, "level", &json!(2));
+    set_param(&mut context, "verbose", &json!(true));
+    assert!(condition.is_met(&context));
+    assert_eq!(context.get_data::<i64>("level"), Some(&2));
+
+    context.set_data("mode", "quick"); // A &'static str, as stages often set
+    assert!(!condition.is_met(&context));
+    assert!(serde_json::from_value::<StageCondition>(json!({ "exists": "mode" })).is_err());
+}
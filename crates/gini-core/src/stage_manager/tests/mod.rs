@@ -9,5 +9,23 @@ mod context_tests;
 mod dependency_tests;
 #[cfg(test)]
 mod dry_run_tests;
+#[cfg(test)]
+mod policy_tests;
+#[cfg(test)]
+mod definition_tests;
+
+// All planned stage manager test modules included.
+
+// --- Shared helpers ---
+
+use crate::stage_manager::Stage;
+use crate::stage_manager::registry::SharedStageRegistry;
 
-// All planned stage manager test modules included.
\ No newline at end of file
+/// A shared registry holding `stages`.
+pub(crate) async fn registry_with<S: Stage + 'static>(stages: impl IntoIterator<Item = S>) -> SharedStageRegistry {
+    let registry = SharedStageRegistry::new();
+    for stage in stages {
+        registry.register_stage(Box::new(stage)).await.unwrap();
+    }
+    registry
+}
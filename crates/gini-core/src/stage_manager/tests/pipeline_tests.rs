@@ -252,4 +252,127 @@ async fn test_pipeline_get_stages() {
 }
 
 // Note: The `clear` method test was removed as StagePipeline doesn't have `clear`.
-// To achieve clearing, simply create a new StagePipeline instance.
\ No newline at end of file
+// To achieve clearing, simply create a new StagePipeline instance.
+
+/// Counts how many stages run at once and records what each stage saw
+#[derive(Default)]
+struct Concurrency {
+    running: std::sync::atomic::AtomicUsize,
+    max_running: std::sync::atomic::AtomicUsize,
+    finished: std::sync::Mutex<Vec<String>>,
+}
+
+/// Takes a while, sets `<id>.done` and checks that the stages it `needs` have set theirs
+struct SlowStage {
+    id: String,
+    needs: Vec<String>,
+    exclusive: bool,
+    fails: bool,
+    delay_ms: u64,
+    concurrency: Arc<Concurrency>,
+}
+
+impl SlowStage {
+    fn new(id: &str, needs: &[&str], concurrency: &Arc<Concurrency>) -> Self {
+        Self {
+            id: id.to_string(),
+            needs: needs.iter().map(|need| need.to_string()).collect(),
+            exclusive: false,
+            fails: false,
+            delay_ms: 50,
+            concurrency: concurrency.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl Stage for SlowStage {
+    fn id(&self) -> &str { &self.id }
+    fn name(&self) -> &str { &self.id }
+    fn description(&self) -> &str { "Sleeps briefly" }
+    fn is_exclusive(&self) -> bool { self.exclusive }
+
+    async fn execute(&self, context: &mut StageContext) -> std::result::Result<(), Box<dyn StdError + Send + Sync + 'static>> {
+        let running = self.concurrency.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.concurrency.max_running.fetch_max(running, Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_millis(self.delay_ms)).await;
+        self.concurrency.running.fetch_sub(1, Ordering::SeqCst);
+
+        for need in &self.needs {
+            if context.get_data::<bool>(&format!("{}.done", need)).is_none() {
+                return Err(format!("{} ran before {}", self.id, need).into());
+            }
+        }
+        if self.exclusive {
+            *context.get_data_mut::<u32>("counter").ok_or("counter not borrowed mutably")? += 1;
+        }
+        if self.fails {
+            return Err("failed on purpose".into());
+        }
+        context.set_data(&format!("{}.done", self.id), true);
+        self.concurrency.finished.lock().unwrap().push(self.id.clone());
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_parallel_pipeline_runs_independent_stages_at_once() {
+    let concurrency = Arc::new(Concurrency::default());
+    let shared_registry = SharedStageRegistry::new();
+    let mut exclusive = SlowStage::new("report", &["gather.1", "gather.2", "gather.3", "gather.4"], &concurrency);
+    exclusive.exclusive = true;
+    register_stages(&shared_registry, vec![
+        Box::new(SlowStage::new("gather.1", &[], &concurrency)),
+        Box::new(SlowStage::new("gather.2", &[], &concurrency)),
+        Box::new(SlowStage::new("gather.3", &[], &concurrency)),
+        Box::new(SlowStage::new("gather.4", &["gather.1"], &concurrency)),
+        Box::new(exclusive),
+    ]).await;
+    let mut pipeline = crate::stage_manager::pipeline::PipelineBuilder::new("Parallel", "Gathers at once")
+        .add_stages(&["gather.1", "gather.2", "gather.3", "gather.4", "report"])
+        .add_dependency("gather.4", "gather.1")
+        .add_dependency("report", "gather.4")
+        .max_parallelism(2)
+        .build();
+    assert_eq!(pipeline.max_parallelism(), 2);
+    let mut context = StageContext::new_live(std::env::temp_dir());
+    context.set_data("counter", 0u32);
+
+    let results = pipeline.execute(&mut context, &shared_registry).await.unwrap();
+
+    assert_eq!(results.len(), 5);
+    assert!(results.values().all(|result| matches!(result, StageResult::Success)));
+    assert_eq!(concurrency.max_running.load(Ordering::SeqCst), 2, "At most two stages run at once");
+    assert_eq!(concurrency.finished.lock().unwrap().last().map(String::as_str), Some("report"));
+    for id in ["gather.1", "gather.2", "gather.3", "gather.4", "report"] {
+        assert_eq!(context.get_data::<bool>(&format!("{}.done", id)), Some(&true), "{} was not merged back", id);
+    }
+    assert_eq!(context.get_data::<u32>("counter"), Some(&1));
+}
+
+#[tokio::test]
+async fn test_parallel_pipeline_stops_starting_stages_after_a_failure() {
+    let concurrency = Arc::new(Concurrency::default());
+    let shared_registry = SharedStageRegistry::new();
+    let mut failing = SlowStage::new("fail", &[], &concurrency);
+    failing.fails = true;
+    failing.delay_ms = 5;
+    register_stages(&shared_registry, vec![
+        Box::new(failing),
+        Box::new(SlowStage::new("alongside", &[], &concurrency)),
+        Box::new(SlowStage::new("after", &[], &concurrency)),
+        Box::new(SlowStage::new("dependent", &["fail"], &concurrency)),
+    ]).await;
+    let mut pipeline = StagePipeline::new("Failing", "Stops after a failure");
+    pipeline.add_stages(&["fail", "alongside", "after", "dependent"]).unwrap();
+    pipeline.add_dependency("dependent", "fail").unwrap();
+    pipeline.set_max_parallelism(2);
+    let mut context = StageContext::new_live(std::env::temp_dir());
+
+    let error = pipeline.execute(&mut context, &shared_registry).await.unwrap_err();
+
+    assert!(error.to_string().contains("fail"), "{}", error);
+    // The stage started alongside the failing one still finishes; nothing starts afterwards
+    assert_eq!(*concurrency.finished.lock().unwrap(), vec!["alongside"]);
+    assert_eq!(context.get_data::<bool>("alongside.done"), Some(&true));
+}
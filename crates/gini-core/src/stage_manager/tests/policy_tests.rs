@@ -0,0 +1,144 @@
+use crate::stage_manager::{Stage, StageContext, StageResult};
+use crate::stage_manager::error::StageSystemError;
+use crate::stage_manager::pipeline::{PipelineBuilder, StagePipeline};
+use crate::stage_manager::policy::{FailureAction, StagePolicy};
+use crate::stage_manager::tests::registry_with;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// Fails until it has been attempted `succeeds_on` times; 0 never succeeds
+struct FlakyStage {
+    id: String,
+    succeeds_on: u32,
+    delay_ms: u64,
+    attempts: Arc<AtomicU32>,
+}
+
+impl FlakyStage {
+    fn new(id: &str, succeeds_on: u32) -> Self {
+        Self { id: id.to_string(), succeeds_on, delay_ms: 0, attempts: Arc::default() }
+    }
+}
+
+#[async_trait]
+impl Stage for FlakyStage {
+    fn id(&self) -> &str { &self.id }
+    fn name(&self) -> &str { &self.id }
+    fn description(&self) -> &str { "Fails a few times" }
+
+    async fn execute(&self, _context: &mut StageContext) -> std::result::Result<(), Box<dyn StdError + Send + Sync + 'static>> {
+        let attempt = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        tokio::time::sleep(Duration::from_millis(self.delay_ms)).await;
+        if self.succeeds_on == 0 || attempt < self.succeeds_on {
+            return Err(format!("attempt {} failed", attempt).into());
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_failure_action_parses_and_backoff_doubles() {
+    assert_eq!(FailureAction::from_str("skip-dependents"), Ok(FailureAction::SkipDependents));
+    assert_eq!(FailureAction::from_str(" continue "), Ok(FailureAction::Continue));
+    assert!(FailureAction::from_str("ignore").is_err());
+    assert_eq!(FailureAction::Abort.to_string(), "abort");
+
+    let policy = StagePolicy::new().retries(3, Duration::from_millis(100));
+    assert_eq!(StagePolicy::default(), StagePolicy::new());
+    assert_eq!(policy.backoff_before(1), Duration::from_millis(100));
+    assert_eq!(policy.backoff_before(3), Duration::from_millis(400));
+
+    let parsed: StagePolicy = serde_json::from_value(serde_json::json!({
+        "timeout_ms": 1500, "retries": 2, "backoff_ms": 10, "on_failure": "skip-dependents"
+    })).unwrap();
+    assert_eq!(parsed, StagePolicy::new()
+        .timeout(Duration::from_millis(1500))
+        .retries(2, Duration::from_millis(10))
+        .on_failure(FailureAction::SkipDependents));
+    assert!(serde_json::from_value::<StagePolicy>(serde_json::json!({ "timeout": 5 })).is_err());
+}
+
+#[tokio::test]
+async fn test_policy_retries_and_times_out_stages() {
+    let flaky = FlakyStage::new("flaky", 3);
+    let flaky_attempts = flaky.attempts.clone();
+    let mut slow = FlakyStage::new("slow", 1);
+    slow.delay_ms = 500;
+    let slow_attempts = slow.attempts.clone();
+    let registry = registry_with(vec![flaky, slow, FlakyStage::new("after", 1)]).await;
+    let mut pipeline = PipelineBuilder::new("Policies", "Retries and timeouts")
+        .add_stages(&["flaky", "slow", "after"])
+        .stage_policy("flaky", StagePolicy::new().retries(2, Duration::from_millis(1)))
+        .stage_policy("slow", StagePolicy::new()
+            .timeout(Duration::from_millis(10))
+            .retries(1, Duration::ZERO)
+            .on_failure(FailureAction::Continue))
+        .build();
+    let mut context = StageContext::new_live(std::env::temp_dir());
+
+    let results = pipeline.execute(&mut context, &registry).await.unwrap();
+
+    assert!(matches!(results.get("flaky"), Some(StageResult::Success)));
+    assert_eq!(flaky_attempts.load(Ordering::SeqCst), 3);
+    match results.get("slow") {
+        Some(StageResult::Failure(message)) => assert!(message.contains("timed out after 10ms"), "{}", message),
+        other => panic!("Expected the slow stage to time out, got {:?}", other),
+    }
+    assert_eq!(slow_attempts.load(Ordering::SeqCst), 2);
+    assert!(matches!(results.get("after"), Some(StageResult::Success)));
+
+    // Out of retries under the default policy, the pipeline aborts
+    let registry = registry_with(vec![FlakyStage::new("flaky", 3)]).await;
+    let mut pipeline = PipelineBuilder::new("Abort", "Too few retries")
+        .add_stage("flaky")
+        .stage_policy("flaky", StagePolicy::new().retries(1, Duration::ZERO))
+        .build();
+    let error = pipeline.execute(&mut context, &registry).await.unwrap_err();
+    assert!(error.to_string().contains("attempt 2 failed"), "{}", error);
+}
+
+#[tokio::test]
+async fn test_skip_dependents_in_sequence_and_in_parallel() {
+    for max_parallelism in [1, 2] {
+        let registry = registry_with(vec![
+            FlakyStage::new("fails", 0),
+            FlakyStage::new("child", 1),
+            FlakyStage::new("grandchild", 1),
+            FlakyStage::new("tolerated", 0),
+            FlakyStage::new("after_tolerated", 1),
+        ]).await;
+        let mut pipeline = PipelineBuilder::new("Skipping", "Skips what depends on a failure")
+            .add_stages(&["fails", "child", "grandchild", "tolerated", "after_tolerated"])
+            .add_dependency("child", "fails")
+            .add_dependency("grandchild", "child")
+            .add_dependency("after_tolerated", "tolerated")
+            .stage_policy("fails", StagePolicy::new().on_failure(FailureAction::SkipDependents))
+            .stage_policy("tolerated", StagePolicy::new().on_failure(FailureAction::Continue))
+            .max_parallelism(max_parallelism)
+            .build();
+        let mut context = StageContext::new_live(std::env::temp_dir());
+
+        let results = pipeline.execute(&mut context, &registry).await.unwrap();
+        let report: HashMap<&str, String> = results.iter().map(|(id, result)| (id.as_str(), result.to_string())).collect();
+
+        assert_eq!(report["fails"], "Failure: Stage execution failed for stage 'fails': attempt 1 failed");
+        assert_eq!(report["child"], "Skipped: dependency 'fails' failed");
+        assert_eq!(report["grandchild"], "Skipped: dependency 'child' was skipped");
+        assert!(report["tolerated"].starts_with("Failure: "), "{:?}", report);
+        assert_eq!(report["after_tolerated"], "Success", "A failure under 'continue' does not skip dependents");
+    }
+}
+
+#[test]
+fn test_policies_must_name_pipeline_stages() {
+    let mut pipeline = StagePipeline::new("Policies", "Only for its own stages");
+    pipeline.add_stage("known").unwrap();
+    assert!(matches!(pipeline.set_stage_policy("unknown", StagePolicy::new()), Err(StageSystemError::PipelineValidationFailed { .. })));
+    pipeline.set_stage_policy("known", StagePolicy::new().on_failure(FailureAction::Continue)).unwrap();
+    assert_eq!(pipeline.stage_policy("known").on_failure, FailureAction::Continue);
+}
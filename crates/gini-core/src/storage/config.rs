@@ -13,6 +13,7 @@ use toml;
 use crate::kernel::error::Error as KernelError; // Renamed for clarity
 use std::result::Result as StdResult; // Import StdResult
 use crate::storage::error::StorageSystemError; // Import StorageSystemError
+use crate::storage::schema::{ConfigEntry, ConfigSchema, ConfigValueSource, ConfigViolation, PluginConfigReport}; // Plugin config validation
 // use crate::storage::manager::StorageManager; // Import StorageManager trait
 use crate::storage::StorageProvider; // Keep for direct provider access if needed elsewhere
 
@@ -250,6 +251,8 @@ pub struct ConfigManager {
     default_format: Arc<RwLock<ConfigFormat>>, // Use Arc<RwLock> for shared mutability
     /// In-memory cache of loaded configurations (Thread-safe and Cloneable via Arc)
     cache: Arc<RwLock<HashMap<String, ConfigData>>>,
+    /// Schemas of plugin configurations, by plugin name
+    schemas: Arc<RwLock<HashMap<String, ConfigSchema>>>,
 }
 
 impl ConfigManager {
@@ -269,6 +272,7 @@ impl ConfigManager {
             plugin_config_path, // Store path directly
             default_format: Arc::new(RwLock::new(default_format)),
             cache: Arc::new(RwLock::new(HashMap::new())),
+            schemas: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -418,7 +422,8 @@ impl ConfigManager {
     }
     
     /// Get plugin configuration with user override support
-    /// This will first load the user-specific configuration, then fall back to defaults
+    /// This will first load the user-specific configuration, then fall back to defaults.
+    /// Keys set in neither are filled in from the plugin's schema, if one is registered.
     pub fn get_plugin_config(&self, plugin_name: &str) -> Result<ConfigData> {
         // First try to load user-specific configuration
         let user_config = self.load_config(plugin_name, ConfigScope::Plugin(PluginConfigScope::User))?;
@@ -429,9 +434,79 @@ impl ConfigManager {
         // Merge them with user config taking priority
         let mut merged_config = default_config;
         merged_config.merge(&user_config);
+
+        if let Some(schema) = self.plugin_schema(plugin_name) {
+            schema.apply_defaults(&mut merged_config);
+        }
         
         Ok(merged_config)
     }
+
+    /// Register the schema of a plugin's configuration, replacing any previous one
+    pub fn register_plugin_schema(&self, plugin_name: &str, schema: ConfigSchema) {
+        self.schemas.write().unwrap().insert(plugin_name.to_string(), schema);
+    }
+
+    /// Get the registered schema of a plugin's configuration
+    pub fn plugin_schema(&self, plugin_name: &str) -> Option<ConfigSchema> {
+        self.schemas.read().unwrap().get(plugin_name).cloned()
+    }
+
+    /// Check the plugin's effective configuration against its schema.
+    /// Returns no violations when no schema is registered.
+    pub fn validate_plugin_config(&self, plugin_name: &str) -> Result<Vec<ConfigViolation>> {
+        let Some(schema) = self.plugin_schema(plugin_name) else {
+            return Ok(Vec::new());
+        };
+        let config = self.get_plugin_config(plugin_name)?;
+        Ok(schema.validate(&config))
+    }
+
+    /// Describe the effective value of each key of a plugin's configuration, where it comes
+    /// from and its schema default
+    pub fn plugin_config_report(&self, plugin_name: &str) -> Result<PluginConfigReport> {
+        let user_config = self.load_config(plugin_name, ConfigScope::Plugin(PluginConfigScope::User))?;
+        let default_config = self.load_config(plugin_name, ConfigScope::Plugin(PluginConfigScope::Default))?;
+        let effective = self.get_plugin_config(plugin_name)?;
+        let schema = self.plugin_schema(plugin_name);
+
+        let mut keys = effective.keys();
+        if let Some(schema) = &schema {
+            keys.extend(schema.properties().keys().cloned());
+        }
+        keys.sort();
+        keys.dedup();
+
+        let entries = keys
+            .into_iter()
+            .map(|key| {
+                let property = schema.as_ref().and_then(|schema| schema.get(&key));
+                let source = if user_config.contains_key(&key) {
+                    ConfigValueSource::User
+                } else if default_config.contains_key(&key) {
+                    ConfigValueSource::DefaultFile
+                } else if effective.contains_key(&key) {
+                    ConfigValueSource::Schema
+                } else {
+                    ConfigValueSource::Unset
+                };
+                ConfigEntry {
+                    effective: effective.get(&key),
+                    default: property.and_then(|property| property.default.clone()),
+                    source,
+                    description: property.and_then(|property| property.description.clone()),
+                    key,
+                }
+            })
+            .collect();
+
+        Ok(PluginConfigReport {
+            plugin_name: plugin_name.to_string(),
+            has_schema: schema.is_some(),
+            entries,
+            violations: schema.map(|schema| schema.validate(&effective)).unwrap_or_default(),
+        })
+    }
     
     /// Save plugin-specific configuration
     pub fn save_plugin_config(
@@ -520,6 +595,23 @@ impl ConfigManager {
         
         Ok(config_files)
     }
+
+    /// List the configuration files in a subdirectory of the application configuration,
+    /// sorted, as names that `load_config` accepts with `ConfigScope::Application`
+    /// (e.g. `pipelines/nightly.toml`). A missing subdirectory has no files.
+    pub fn list_app_config_files(&self, subdir: &str) -> Result<Vec<String>> {
+        let dir_path = self.app_config_path.join(subdir);
+        if !self.provider.is_dir(&dir_path) {
+            return Ok(vec![]);
+        }
+        let mut names: Vec<String> = self.provider.read_dir(&dir_path).map_err(KernelError::from)?
+            .into_iter()
+            .filter(|path| self.provider.is_file(path) && ConfigFormat::from_path(path).is_some())
+            .filter_map(|path| path.file_name().and_then(|name| name.to_str()).map(|name| format!("{}/{}", subdir, name)))
+            .collect();
+        names.sort();
+        Ok(names)
+    }
 }
 
 // Manual Debug implementation for ConfigManager
@@ -552,6 +644,7 @@ impl Clone for ConfigManager {
             plugin_config_path: self.plugin_config_path.clone(),
             default_format: Arc::clone(&self.default_format),
             cache: Arc::clone(&self.cache),
+            schemas: Arc::clone(&self.schemas),
         }
     }
 }
\ No newline at end of file
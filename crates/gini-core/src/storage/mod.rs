@@ -11,6 +11,8 @@
 //!   It includes [`ConfigManager`](config::ConfigManager) for application-wide
 //!   configuration and [`Configurable`](config::Configurable) for components
 //!   that require their own specific configurations.
+//! - **[`schema`]**: Schemas of plugin configurations ([`ConfigSchema`](schema::ConfigSchema)),
+//!   their defaults and validation.
 //! - **[`error`]**: Defines storage-specific error types, such as [`StorageError`](error::StorageError).
 //! - **[`local`]**: Provides implementations for local file system storage,
 //!   including XDG-compliant path resolution through [`LocalFsProvider`](local::LocalFsProvider).
@@ -27,6 +29,7 @@ pub mod provider;
 pub mod local;
 pub mod manager; // Add manager module
 pub mod config; // Add configuration module
+pub mod schema; // Plugin configuration schemas
 pub mod error; // Add error module
 
 
@@ -43,4 +46,4 @@ pub use error::StorageSystemError; // Export the new error type
     
     // Test module declaration
     #[cfg(test)]
-    mod tests;
\ No newline at end of file
+    pub(crate) mod tests;
\ No newline at end of file
@@ -0,0 +1,436 @@
+//! # Configuration Schemas
+//!
+//! A [`ConfigSchema`] describes the keys of a plugin configuration: the type of each value,
+//! its default, the values it may take and whether it is required. Schemas are built in Rust
+//! ([`Plugin::config_schema`]) or read from the JSON Schema file a manifest names in
+//! `config_schema`. The supported subset of JSON Schema is an object with `properties`, each
+//! with `type`, `description`, `default`, `enum`, `minimum` and `maximum`, plus `required` and
+//! `additionalProperties`:
+//!
+//! ```json
+//! {
+//!   "type": "object",
+//!   "properties": {
+//!     "default_level": { "type": "string", "enum": ["error", "warn", "info", "debug", "trace"], "default": "info" },
+//!     "port": { "type": "integer", "minimum": 1, "maximum": 65535 }
+//!   },
+//!   "required": ["port"],
+//!   "additionalProperties": false
+//! }
+//! ```
+//!
+//! Once a schema is registered with the [`ConfigManager`](crate::storage::config::ConfigManager),
+//! [`get_plugin_config`](crate::storage::config::ConfigManager::get_plugin_config) fills in its
+//! defaults, and the plugin registry rejects a configuration with [`ConfigViolation`]s before
+//! the plugin's `init` runs.
+//!
+//! [`Plugin::config_schema`]: crate::plugin_system::traits::Plugin::config_schema
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::storage::config::ConfigData;
+use crate::storage::error::StorageSystemError;
+
+/// The type a configuration value must have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaType {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Array,
+    Object,
+    /// Any value
+    Any,
+}
+
+impl SchemaType {
+    /// The JSON Schema name of the type.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SchemaType::String => "string",
+            SchemaType::Integer => "integer",
+            SchemaType::Number => "number",
+            SchemaType::Boolean => "boolean",
+            SchemaType::Array => "array",
+            SchemaType::Object => "object",
+            SchemaType::Any => "any",
+        }
+    }
+
+    fn from_json_name(name: &str) -> Option<Self> {
+        match name {
+            "string" => Some(SchemaType::String),
+            "integer" => Some(SchemaType::Integer),
+            "number" => Some(SchemaType::Number),
+            "boolean" => Some(SchemaType::Boolean),
+            "array" => Some(SchemaType::Array),
+            "object" => Some(SchemaType::Object),
+            _ => None,
+        }
+    }
+
+    fn matches(&self, value: &Value) -> bool {
+        match self {
+            SchemaType::String => value.is_string(),
+            SchemaType::Integer => value.is_i64() || value.is_u64(),
+            SchemaType::Number => value.is_number(),
+            SchemaType::Boolean => value.is_boolean(),
+            SchemaType::Array => value.is_array(),
+            SchemaType::Object => value.is_object(),
+            SchemaType::Any => true,
+        }
+    }
+}
+
+impl fmt::Display for SchemaType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One key of a configuration schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaProperty {
+    pub value_type: SchemaType,
+    pub description: Option<String>,
+    /// Used when the key is not set
+    pub default: Option<Value>,
+    /// The only values allowed; any value of the right type when empty
+    pub allowed: Vec<Value>,
+    pub minimum: Option<f64>,
+    pub maximum: Option<f64>,
+    /// The key must be set, either in a configuration file or by its default
+    pub required: bool,
+}
+
+impl SchemaProperty {
+    pub fn new(value_type: SchemaType) -> Self {
+        Self {
+            value_type,
+            description: None,
+            default: None,
+            allowed: Vec::new(),
+            minimum: None,
+            maximum: None,
+            required: false,
+        }
+    }
+
+    pub fn string() -> Self {
+        Self::new(SchemaType::String)
+    }
+
+    pub fn integer() -> Self {
+        Self::new(SchemaType::Integer)
+    }
+
+    pub fn number() -> Self {
+        Self::new(SchemaType::Number)
+    }
+
+    pub fn boolean() -> Self {
+        Self::new(SchemaType::Boolean)
+    }
+
+    pub fn array() -> Self {
+        Self::new(SchemaType::Array)
+    }
+
+    pub fn object() -> Self {
+        Self::new(SchemaType::Object)
+    }
+
+    pub fn describe(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    /// Sets the default, e.g. `with_default("info")` or `with_default(json!([1, 2]))`.
+    pub fn with_default(mut self, default: impl Into<Value>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    /// Restricts the value to one of `values`.
+    pub fn one_of<T: Clone + Into<Value>>(mut self, values: &[T]) -> Self {
+        self.allowed = values.iter().cloned().map(Into::into).collect();
+        self
+    }
+
+    /// Restricts a numeric value to `minimum..=maximum`.
+    pub fn range(mut self, minimum: f64, maximum: f64) -> Self {
+        self.minimum = Some(minimum);
+        self.maximum = Some(maximum);
+        self
+    }
+
+    pub fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    /// Describes what is wrong with `value`, if anything.
+    fn check(&self, value: &Value) -> Option<String> {
+        if !self.value_type.matches(value) {
+            return Some(format!("expected {}, found {}", self.value_type, value));
+        }
+        if !self.allowed.is_empty() && !self.allowed.contains(value) {
+            let allowed: Vec<String> = self.allowed.iter().map(|value| value.to_string()).collect();
+            return Some(format!("{} is not one of {}", value, allowed.join(", ")));
+        }
+        if let Some(number) = value.as_f64()
+            && (self.minimum.is_some_and(|minimum| number < minimum) || self.maximum.is_some_and(|maximum| number > maximum))
+        {
+            let bound = |bound: Option<f64>| bound.map(|b| b.to_string()).unwrap_or_else(|| "..".to_string());
+            return Some(format!("{} is outside {}..={}", value, bound(self.minimum), bound(self.maximum)));
+        }
+        None
+    }
+}
+
+/// A configuration value that does not satisfy the schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigViolation {
+    pub key: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.key, self.message)
+    }
+}
+
+/// The keys a configuration may contain, with their types and defaults.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigSchema {
+    properties: BTreeMap<String, SchemaProperty>,
+    /// Whether keys without a property are accepted
+    allow_unknown: bool,
+}
+
+impl Default for ConfigSchema {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigSchema {
+    /// An empty schema that accepts unknown keys.
+    pub fn new() -> Self {
+        Self { properties: BTreeMap::new(), allow_unknown: true }
+    }
+
+    pub fn property(mut self, key: &str, property: SchemaProperty) -> Self {
+        self.properties.insert(key.to_string(), property);
+        self
+    }
+
+    /// Reports keys the schema does not describe as violations.
+    pub fn deny_unknown(mut self) -> Self {
+        self.allow_unknown = false;
+        self
+    }
+
+    pub fn properties(&self) -> &BTreeMap<String, SchemaProperty> {
+        &self.properties
+    }
+
+    pub fn get(&self, key: &str) -> Option<&SchemaProperty> {
+        self.properties.get(key)
+    }
+
+    /// Parses the supported subset of JSON Schema.
+    pub fn from_json(schema: &Value) -> Result<Self, StorageSystemError> {
+        let invalid = |message: String| StorageSystemError::DeserializationError {
+            format: "JSON Schema".to_string(),
+            source: message.into(),
+        };
+        let root = schema.as_object().ok_or_else(|| invalid("schema must be an object".to_string()))?;
+        if let Some(root_type) = root.get("type")
+            && root_type != "object"
+        {
+            return Err(invalid(format!("schema type must be \"object\", found {}", root_type)));
+        }
+
+        let required: Vec<&str> = match root.get("required") {
+            None => Vec::new(),
+            Some(Value::Array(keys)) => keys
+                .iter()
+                .map(|key| key.as_str().ok_or_else(|| invalid(format!("required entry {} is not a string", key))))
+                .collect::<Result<_, _>>()?,
+            Some(other) => return Err(invalid(format!("required must be an array, found {}", other))),
+        };
+
+        let mut parsed = Self::new();
+        if let Some(properties) = root.get("properties") {
+            let properties = properties.as_object().ok_or_else(|| invalid("properties must be an object".to_string()))?;
+            for (key, definition) in properties {
+                let definition = definition.as_object().ok_or_else(|| invalid(format!("property '{}' must be an object", key)))?;
+                let value_type = match definition.get("type") {
+                    None => SchemaType::Any,
+                    Some(Value::String(name)) => SchemaType::from_json_name(name)
+                        .ok_or_else(|| invalid(format!("property '{}' has unsupported type '{}'", key, name)))?,
+                    Some(other) => return Err(invalid(format!("type of property '{}' must be a string, found {}", key, other))),
+                };
+                let bound = |name: &str| match definition.get(name) {
+                    None => Ok(None),
+                    Some(value) => value
+                        .as_f64()
+                        .map(Some)
+                        .ok_or_else(|| invalid(format!("{} of property '{}' must be a number, found {}", name, key, value))),
+                };
+                let property = SchemaProperty {
+                    value_type,
+                    description: definition.get("description").and_then(Value::as_str).map(str::to_string),
+                    default: definition.get("default").cloned(),
+                    allowed: match definition.get("enum") {
+                        None => Vec::new(),
+                        Some(Value::Array(values)) => values.clone(),
+                        Some(other) => return Err(invalid(format!("enum of property '{}' must be an array, found {}", key, other))),
+                    },
+                    minimum: bound("minimum")?,
+                    maximum: bound("maximum")?,
+                    required: required.contains(&key.as_str()),
+                };
+                if let Some(problem) = property.default.as_ref().and_then(|default| property.check(default)) {
+                    return Err(invalid(format!("default of property '{}' is invalid: {}", key, problem)));
+                }
+                parsed.properties.insert(key.clone(), property);
+            }
+        }
+        if let Some(key) = required.iter().find(|key| !parsed.properties.contains_key(**key)) {
+            return Err(invalid(format!("required key '{}' has no property", key)));
+        }
+        parsed.allow_unknown = root.get("additionalProperties").and_then(Value::as_bool).unwrap_or(true);
+        Ok(parsed)
+    }
+
+    /// Parses a JSON Schema document.
+    pub fn from_json_str(schema: &str) -> Result<Self, StorageSystemError> {
+        let value: Value = serde_json::from_str(schema).map_err(|e| StorageSystemError::DeserializationError {
+            format: "JSON Schema".to_string(),
+            source: Box::new(e),
+        })?;
+        Self::from_json(&value)
+    }
+
+    /// The default of every property that has one.
+    pub fn defaults(&self) -> ConfigData {
+        let mut defaults = ConfigData::new();
+        self.apply_defaults(&mut defaults);
+        defaults
+    }
+
+    /// Sets the keys that are missing from `config` to their defaults and returns them.
+    pub fn apply_defaults(&self, config: &mut ConfigData) -> Vec<String> {
+        let mut filled = Vec::new();
+        for (key, property) in &self.properties {
+            if let Some(default) = &property.default
+                && !config.contains_key(key)
+                && config.set(key, default).is_ok()
+            {
+                filled.push(key.clone());
+            }
+        }
+        filled
+    }
+
+    /// Checks `config` against the schema, in key order.
+    pub fn validate(&self, config: &ConfigData) -> Vec<ConfigViolation> {
+        let mut violations = Vec::new();
+        for (key, property) in &self.properties {
+            match config.get::<Value>(key) {
+                Some(value) => {
+                    if let Some(message) = property.check(&value) {
+                        violations.push(ConfigViolation { key: key.clone(), message });
+                    }
+                }
+                None if property.required => {
+                    violations.push(ConfigViolation { key: key.clone(), message: "required but not set".to_string() });
+                }
+                None => {}
+            }
+        }
+        if !self.allow_unknown {
+            let mut unknown: Vec<String> = config.keys().into_iter().filter(|key| !self.properties.contains_key(key)).collect();
+            unknown.sort();
+            violations.extend(unknown.into_iter().map(|key| ConfigViolation { key, message: "unknown key".to_string() }));
+        }
+        violations
+    }
+}
+
+/// Where the effective value of a configuration key comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigValueSource {
+    /// The user configuration file
+    User,
+    /// The default configuration file
+    DefaultFile,
+    /// The schema default
+    Schema,
+    /// Not set
+    Unset,
+}
+
+impl fmt::Display for ConfigValueSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConfigValueSource::User => "user",
+            ConfigValueSource::DefaultFile => "default file",
+            ConfigValueSource::Schema => "schema default",
+            ConfigValueSource::Unset => "unset",
+        })
+    }
+}
+
+/// The effective and default value of one configuration key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigEntry {
+    pub key: String,
+    pub effective: Option<Value>,
+    /// The schema default
+    pub default: Option<Value>,
+    pub source: ConfigValueSource,
+    pub description: Option<String>,
+}
+
+/// Effective configuration of a plugin, as shown by `gini plugin config`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PluginConfigReport {
+    pub plugin_name: String,
+    /// Whether a schema is registered for the plugin
+    pub has_schema: bool,
+    /// Every key that is set or described by the schema, sorted
+    pub entries: Vec<ConfigEntry>,
+    pub violations: Vec<ConfigViolation>,
+}
+
+impl fmt::Display for PluginConfigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let schema = if self.has_schema { "" } else { " (no schema)" };
+        writeln!(f, "Configuration of plugin '{}'{}:", self.plugin_name, schema)?;
+        if self.entries.is_empty() {
+            writeln!(f, "  No configuration values.")?;
+        }
+        let show = |value: &Option<Value>| value.as_ref().map(|value| value.to_string()).unwrap_or_else(|| "-".to_string());
+        for entry in &self.entries {
+            write!(f, "  {} = {} ({}), default: {}", entry.key, show(&entry.effective), entry.source, show(&entry.default))?;
+            match &entry.description {
+                Some(description) => writeln!(f, " - {}", description)?,
+                None => writeln!(f)?,
+            }
+        }
+        if !self.violations.is_empty() {
+            writeln!(f, "  Violations:")?;
+            for violation in &self.violations {
+                writeln!(f, "    - {}", violation)?;
+            }
+        }
+        Ok(())
+    }
+}
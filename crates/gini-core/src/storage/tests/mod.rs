@@ -3,6 +3,26 @@
 mod local_tests;
 #[cfg(test)] // Add cfg(test) here
 mod config_tests;
+#[cfg(test)]
+mod schema_tests;
 // Additional test files to be implemented:
 // mod provider_tests;
-// mod manager_tests;
\ No newline at end of file
+// mod manager_tests;
+
+// --- Shared helpers ---
+
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::storage::config::{ConfigFormat, ConfigManager};
+use crate::storage::local::LocalStorageProvider;
+
+/// A JSON config manager storing everything under `root`.
+pub(crate) fn config_manager_in(root: &Path) -> ConfigManager {
+    ConfigManager::new(
+        Arc::new(LocalStorageProvider::new(root.to_path_buf())),
+        root.join("config"),
+        root.join("config/plugins"),
+        ConfigFormat::Json,
+    )
+}
@@ -0,0 +1,126 @@
+use serde_json::json;
+use tempfile::tempdir;
+
+use crate::storage::config::{ConfigData, PluginConfigScope};
+use crate::storage::schema::{ConfigSchema, ConfigValueSource, ConfigViolation, SchemaProperty, SchemaType};
+use crate::storage::tests::config_manager_in;
+
+fn logging_schema() -> ConfigSchema {
+    ConfigSchema::new()
+        .property("level", SchemaProperty::string().with_default("info").describe("Log filter"))
+        .property("format", SchemaProperty::string().one_of(&["compact", "json"]).with_default("compact"))
+        .property("port", SchemaProperty::integer().range(1.0, 65535.0))
+}
+
+fn config(values: serde_json::Value) -> ConfigData {
+    serde_json::from_value(values).unwrap()
+}
+
+#[test]
+fn test_schema_validation_reports_each_violation() {
+    let schema = logging_schema().property("endpoint", SchemaProperty::string().required()).deny_unknown();
+
+    let violations = schema.validate(&config(json!({
+        "level": 3,
+        "format": "xml",
+        "port": 70000,
+        "colour": true,
+    })));
+
+    let keys: Vec<&str> = violations.iter().map(|violation| violation.key.as_str()).collect();
+    assert_eq!(keys, vec!["endpoint", "format", "level", "port", "colour"]);
+    assert_eq!(violations[0].message, "required but not set");
+    assert!(violations[1].message.contains("\"xml\" is not one of \"compact\", \"json\""), "{}", violations[1]);
+    assert_eq!(violations[2].to_string(), "level: expected string, found 3");
+    assert!(violations[3].message.contains("outside 1..=65535"), "{}", violations[3]);
+    assert_eq!(violations[4], ConfigViolation { key: "colour".to_string(), message: "unknown key".to_string() });
+
+    assert!(schema.validate(&config(json!({ "endpoint": "localhost", "port": 8080 }))).is_empty());
+}
+
+#[test]
+fn test_schema_defaults_fill_only_missing_keys() {
+    let schema = logging_schema();
+    let mut values = config(json!({ "format": "json" }));
+
+    assert_eq!(schema.apply_defaults(&mut values), vec!["level"]);
+    assert_eq!(values.get::<String>("level").as_deref(), Some("info"));
+    assert_eq!(values.get::<String>("format").as_deref(), Some("json"));
+    assert!(!values.contains_key("port"), "Keys without a default stay unset");
+    assert_eq!(schema.defaults().keys().len(), 2);
+
+    // Any JSON value can be a default or an allowed value
+    let schema = ConfigSchema::new()
+        .property("targets", SchemaProperty::array().with_default(json!(["stdout"])))
+        .property("retries", SchemaProperty::integer().one_of(&[1, 3, 5]).with_default(3));
+    let mut values = ConfigData::new();
+    schema.apply_defaults(&mut values);
+    assert_eq!(values.get::<Vec<String>>("targets"), Some(vec!["stdout".to_string()]));
+    assert_eq!(values.get::<i64>("retries"), Some(3));
+    assert_eq!(schema.validate(&config(json!({ "retries": 2 })))[0].message, "2 is not one of 1, 3, 5");
+}
+
+#[test]
+fn test_schema_from_json_schema() {
+    let schema = ConfigSchema::from_json_str(r#"{
+        "type": "object",
+        "properties": {
+            "level": { "type": "string", "enum": ["info", "debug"], "default": "info", "description": "Log filter" },
+            "port": { "type": "integer", "minimum": 1, "maximum": 65535 },
+            "extra": {}
+        },
+        "required": ["port"],
+        "additionalProperties": false
+    }"#).unwrap();
+
+    let level = schema.get("level").unwrap();
+    assert_eq!(level.value_type, SchemaType::String);
+    assert_eq!(level.default, Some(json!("info")));
+    assert_eq!(level.description.as_deref(), Some("Log filter"));
+    assert!(schema.get("port").unwrap().required);
+    assert_eq!(schema.get("extra").unwrap().value_type, SchemaType::Any);
+    assert_eq!(schema.validate(&config(json!({ "port": 80, "other": 1 })))[0].key, "other");
+
+    let invalid = [
+        r#"{ "type": "array" }"#,
+        r#"{ "properties": { "port": { "type": "float" } } }"#,
+        r#"{ "properties": { "level": { "type": "string", "default": 1 } } }"#,
+        r#"{ "required": ["missing"] }"#,
+        "not json",
+    ];
+    for schema in invalid {
+        assert!(ConfigSchema::from_json_str(schema).is_err(), "Accepted {}", schema);
+    }
+}
+
+#[test]
+fn test_plugin_config_fills_defaults_and_validates() {
+    let dir = tempdir().unwrap();
+    let manager = config_manager_in(dir.path());
+    let user = config(json!({ "format": "xml" }));
+    manager.save_plugin_config("logger", &user, PluginConfigScope::User).unwrap();
+    let defaults_file = config(json!({ "port": 8080 }));
+    manager.save_plugin_config("logger", &defaults_file, PluginConfigScope::Default).unwrap();
+
+    assert!(manager.validate_plugin_config("logger").unwrap().is_empty(), "No schema, nothing to check");
+    manager.register_plugin_schema("logger", logging_schema());
+
+    let effective = manager.get_plugin_config("logger").unwrap();
+    assert_eq!(effective.get::<String>("level").as_deref(), Some("info"));
+    let violations = manager.validate_plugin_config("logger").unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].key, "format");
+
+    let report = manager.plugin_config_report("logger").unwrap();
+    let sources: Vec<(&str, ConfigValueSource)> = report.entries.iter().map(|entry| (entry.key.as_str(), entry.source)).collect();
+    assert_eq!(sources, vec![
+        ("format", ConfigValueSource::User),
+        ("level", ConfigValueSource::Schema),
+        ("port", ConfigValueSource::DefaultFile),
+    ]);
+    assert_eq!(report.entries[0].default, Some(json!("compact")));
+    let text = report.to_string();
+    assert!(text.contains("  level = \"info\" (schema default), default: \"info\" - Log filter\n"), "{}", text);
+    assert!(text.contains("  port = 8080 (default file), default: -\n"), "{}", text);
+    assert!(text.contains("    - format: \"xml\" is not one of"), "{}", text);
+}
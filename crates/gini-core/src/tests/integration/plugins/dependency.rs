@@ -204,9 +204,9 @@ async fn test_plugin_shutdown_cycle() {
 
     {
         let registry = plugin_manager.registry().lock().await;
-        assert!(!registry.initialized.contains("CycleA"));
-        assert!(!registry.initialized.contains("CycleB"));
-        assert!(!registry.initialized.contains("CycleC"));
+        assert!(!registry.is_initialized("CycleA"));
+        assert!(!registry.is_initialized("CycleB"));
+        assert!(!registry.is_initialized("CycleC"));
     }
 }
 
@@ -250,7 +250,7 @@ async fn test_register_all_plugins_dep_resolution_fail_via_manager() -> KernelRe
     }
 
     let registry = plugin_manager.registry().lock().await;
-    assert!(!registry.initialized.contains(&plugin_a_name));
+    assert!(!registry.is_initialized(&plugin_a_name));
 
     Ok(())
 }
@@ -297,10 +297,10 @@ async fn test_initialize_all_diamond_dependency_order() {
 
     {
         let registry = plugin_manager.registry().lock().await;
-        assert!(registry.initialized.contains("DiamondA"));
-        assert!(registry.initialized.contains("DiamondB"));
-        assert!(registry.initialized.contains("DiamondC"));
-        assert!(registry.initialized.contains("DiamondD"));
+        assert!(registry.is_initialized("DiamondA"));
+        assert!(registry.is_initialized("DiamondB"));
+        assert!(registry.is_initialized("DiamondC"));
+        assert!(registry.is_initialized("DiamondD"));
         assert_eq!(registry.initialized_count(), 4);
     }
 
@@ -183,8 +183,8 @@ async fn test_plugin_shutdown_order() {
      // Verify both are marked as initialized
     {
          let registry = plugin_manager.registry().lock().await;
-         assert!(registry.initialized.contains("DepShutdownA"), "A should be initialized");
-         assert!(registry.initialized.contains("DepShutdownB"), "B should be initialized");
+         assert!(registry.is_initialized("DepShutdownA"), "A should be initialized");
+         assert!(registry.is_initialized("DepShutdownB"), "B should be initialized");
     }
 
 
@@ -207,8 +207,8 @@ async fn test_plugin_shutdown_order() {
     // Verify plugins are no longer marked as initialized
     {
         let registry = plugin_manager.registry().lock().await;
-        assert!(!registry.initialized.contains("DepShutdownA"), "A should no longer be initialized");
-        assert!(!registry.initialized.contains("DepShutdownB"), "B should no longer be initialized");
+        assert!(!registry.is_initialized("DepShutdownA"), "A should no longer be initialized");
+        assert!(!registry.is_initialized("DepShutdownB"), "B should no longer be initialized");
     }
 }
 
@@ -259,8 +259,8 @@ async fn test_plugin_shutdown_error_handling() {
      // Verify both are marked as initialized
     {
          let registry = plugin_manager.registry().lock().await;
-         assert!(registry.initialized.contains(&error_plugin_name), "Error plugin should be initialized");
-         assert!(registry.initialized.contains(&success_plugin_name), "Success plugin should be initialized");
+         assert!(registry.is_initialized(&error_plugin_name), "Error plugin should be initialized");
+         assert!(registry.is_initialized(&success_plugin_name), "Success plugin should be initialized");
     }
 
     // Shutdown all plugins - expect an error because one fails
@@ -294,8 +294,8 @@ async fn test_plugin_shutdown_error_handling() {
     // Verify both plugins are marked as uninitialized, even though one failed shutdown
     {
         let registry = plugin_manager.registry().lock().await;
-        assert!(!registry.initialized.contains(&error_plugin_name), "Error plugin should be uninitialized after shutdown attempt");
-        assert!(!registry.initialized.contains(&success_plugin_name), "Success plugin should be uninitialized after shutdown");
+        assert!(!registry.is_initialized(&error_plugin_name), "Error plugin should be uninitialized after shutdown attempt");
+        assert!(!registry.is_initialized(&success_plugin_name), "Success plugin should be uninitialized after shutdown");
     }
 }
 
@@ -356,6 +356,6 @@ async fn test_plugin_preflight_check_failure_handling() {
         assert!(registry.get_plugin(&plugin_name).is_some(), "Plugin should still be registered");
         assert!(registry.is_enabled(&plugin_name), "Plugin should still be enabled even if preflight failed");
         // If initialization *was* attempted and failed due to preflight, then check initialized:
-        // assert!(!registry.initialized.contains(&plugin_name), "Plugin should not be marked as initialized after failed preflight");
+        // assert!(!registry.is_initialized(&plugin_name), "Plugin should not be marked as initialized after failed preflight");
     }
 }
\ No newline at end of file
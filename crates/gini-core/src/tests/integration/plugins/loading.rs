@@ -119,7 +119,7 @@ async fn test_static_plugin_initialization_succeeds() -> KernelResult<()> {
         registry_lock.register_plugin(Arc::new(plugin))?; // Use Arc::new
         // Ensure it's enabled but not initialized
         assert!(registry_lock.is_enabled(&plugin_name));
-        assert!(!registry_lock.initialized.contains(&plugin_name));
+        assert!(!registry_lock.is_initialized(&plugin_name));
     }
 
     // Initialize the plugin using initialize_all
@@ -134,7 +134,7 @@ async fn test_static_plugin_initialization_succeeds() -> KernelResult<()> {
     
      // Verify plugin is marked as initialized
     let registry_check = plugin_manager.registry().lock().await;
-    assert!(registry_check.initialized.contains(&plugin_name), "Plugin '{}' should be marked as initialized after successful init. Initialized: {:?}", plugin_name, registry_check.initialized);
+    assert!(registry_check.is_initialized(&plugin_name), "Plugin '{}' should be marked as initialized after successful init. Initialized: {:?}", plugin_name, registry_check.initialized_plugin_ids());
 
     Ok(())
 }
@@ -93,7 +93,7 @@ async fn test_plugin_interaction_with_storage() {
 
      // Create the plugin instance, passing the storage manager Arc
      let plugin = StorageInteractingPlugin::new("StoragePlugin", storage_manager.clone()); // Pass storage_manager
-     let plugin_name_for_init = plugin.name(); // Get &'static str before plugin is moved
+     let plugin_name_for_init = plugin.name().to_string(); // Owned, the plugin is moved below
 
      // Register the plugin
     {
@@ -128,7 +128,7 @@ async fn test_plugin_interaction_with_storage() {
         let stage_registry_arc = stage_manager.registry(); // Assuming this provides Arc<Mutex<StageRegistry>>
         let mut reg_lock = plugin_registry_arc.lock().await;
         // Call initialize_plugin and await it immediately while reg_lock is still in scope.
-        reg_lock.initialize_plugin(plugin_name_for_init, &mut app, &stage_registry_arc).await
+        reg_lock.initialize_plugin(&plugin_name_for_init, &mut app, &stage_registry_arc).await
     };
 
     assert!(init_result.is_ok(), "Plugin initialization (with storage interaction) failed: {:?}", init_result.err());
@@ -50,6 +50,7 @@ use std::sync::{Arc, Mutex}; // Added Arc
 use std::collections::HashMap;
 use std::time::{SystemTime, UNIX_EPOCH};
 use std::fmt::Debug; // Import Debug
+use tokio::sync::oneshot; // Carries the answer to request_choice
 
 // --- Added Definitions ---
 
@@ -62,7 +63,9 @@ pub enum UserInput {
     Command(String, Vec<String>),
     /// Confirmation response (e.g., yes/no).
     Confirmation(bool),
-    // Add other input types as necessary (e.g., selection from a list)
+    /// Selection from the options of a [`UiUpdateType::Choice`], by index.
+    Selection(usize),
+    // Add other input types as necessary
 }
 
 // --- End Added Definitions ---
@@ -96,6 +99,8 @@ pub enum UiUpdateType {
     Log(String, MessageSeverity),
     /// Dialog message
     Dialog(String, MessageSeverity),
+    /// Question the user answers by picking one of the options (see [`UnifiedUiManager::request_choice`])
+    Choice(String, Vec<String>),
 }
 
 impl PartialEq for UiUpdateType {
@@ -106,6 +111,7 @@ impl PartialEq for UiUpdateType {
             (UiUpdateType::Status(a), UiUpdateType::Status(b)) => a == b,
             (UiUpdateType::Log(a, s1), UiUpdateType::Log(b, s2)) => a == b && s1 == s2,
             (UiUpdateType::Dialog(a, s1), UiUpdateType::Dialog(b, s2)) => a == b && s1 == s2,
+            (UiUpdateType::Choice(a, o1), UiUpdateType::Choice(b, o2)) => a == b && o1 == o2,
             _ => false,
         }
     }
@@ -169,6 +175,7 @@ impl UnifiedUiInterface for ConsoleUiProvider {
             UiUpdateType::Status(msg) => format!("Status: {}", msg),
             UiUpdateType::Log(msg, severity) => format!("{:?}: {}", severity, msg),
             UiUpdateType::Dialog(msg, severity) => format!("Dialog ({:?}): {}", severity, msg),
+            UiUpdateType::Choice(prompt, options) => format!("Choice: {} {:?}", prompt, options),
         };
         
         let time_str = Self::format_time(message.timestamp);
@@ -207,6 +214,7 @@ pub struct UnifiedUiManager {
     default_interface: Arc<Mutex<Option<String>>>,
     message_buffer: Arc<Mutex<Vec<UiMessage>>>,
     event_manager: Arc<dyn EventManager>, // Added EventManager
+    pending_choice: Arc<Mutex<Option<oneshot::Sender<UserInput>>>>, // Receives the next user input while a choice is open
     // Note: UserInput is currently submitted via a direct method call to UnifiedUiManager.
     // Alternative patterns like channels or callbacks could be considered for future enhancements
     // if more complex input routing or decoupling is required.
@@ -225,6 +233,7 @@ impl UnifiedUiManager {
             default_interface: Arc::new(Mutex::new(Some(console_name))),
             message_buffer: Arc::new(Mutex::new(Vec::new())),
             event_manager,
+            pending_choice: Arc::new(Mutex::new(None)),
         }
     }
     
@@ -299,6 +308,12 @@ impl UnifiedUiManager {
     pub async fn submit_user_input(&self, input: UserInput, source_interface_name: &str) -> Result<(), UiBridgeError> {
         log::debug!("Processing user input from '{}': {:?}", source_interface_name, input);
 
+        // An open choice (see request_choice) takes the next input, whatever it is
+        let pending = self.pending_choice.lock().map_err(|e| UiBridgeError::LockError { entity: "pending_choice".to_string(), operation: format!("submit_user_input - lock: {}", e) })?.take();
+        if let Some(sender) = pending {
+            return sender.send(input).map_err(|_| UiBridgeError::InputProcessingError("The choice was cancelled before the answer arrived".to_string()));
+        }
+
         match input {
             UserInput::Text(text_content) => {
                 if text_content.trim().to_lowercase() == "ping" {
@@ -339,6 +354,77 @@ impl UnifiedUiManager {
                 );
                 Ok(())
             }
+            UserInput::Selection(index) => {
+                log::info!(
+                    "Unhandled selection input from '{}': {} (no choice is open)",
+                    source_interface_name,
+                    index
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Whether any registered UI interface can take user input.
+    pub fn has_interactive_interface(&self) -> bool {
+        match self.interfaces.lock() {
+            Ok(interfaces_guard) => interfaces_guard
+                .values()
+                .any(|interface| interface.lock().map(|interface| interface.supports_interactive()).unwrap_or(false)),
+            Err(_) => false,
+        }
+    }
+
+    /// Ask the user to pick one of `options` and wait for the answer.
+    ///
+    /// The question is broadcast as a [`UiUpdateType::Choice`]; the next input passed to
+    /// [`submit_user_input`](Self::submit_user_input) answers it. Accepted answers are
+    /// [`UserInput::Selection`], or text holding the option's number (starting at 1) or the
+    /// option itself. Any other answer is reported and the question is asked again.
+    /// Returns the index of the chosen option.
+    pub async fn request_choice(&self, source: &str, prompt: &str, options: &[String]) -> Result<usize, UiBridgeError> {
+        if options.is_empty() {
+            return Err(UiBridgeError::InputError(format!("No options to choose from for '{}'", prompt)));
+        }
+        if !self.has_interactive_interface() {
+            return Err(UiBridgeError::InputError("No interactive UI interface is registered".to_string()));
+        }
+
+        loop {
+            let (sender, receiver) = oneshot::channel();
+            {
+                let mut pending = self.pending_choice.lock().map_err(|e| UiBridgeError::LockError { entity: "pending_choice".to_string(), operation: format!("request_choice - lock: {}", e) })?;
+                if pending.as_ref().is_some_and(|sender| !sender.is_closed()) {
+                    return Err(UiBridgeError::InputError("Another choice is already waiting for an answer".to_string()));
+                }
+                *pending = Some(sender);
+            }
+            self.broadcast_message(UiMessage {
+                update_type: UiUpdateType::Choice(prompt.to_string(), options.to_vec()),
+                source: source.to_string(),
+                timestamp: SystemTime::now(),
+            })?;
+
+            let input = receiver.await.map_err(|_| UiBridgeError::InputError("The choice was dropped without an answer".to_string()))?;
+            match Self::chosen_option(&input, options) {
+                Some(index) => return Ok(index),
+                None => self.log(source, &format!("'{:?}' is not one of the options, please choose again.", input), MessageSeverity::Warning)?,
+            }
+        }
+    }
+
+    /// The option `input` picks, if it picks one
+    fn chosen_option(input: &UserInput, options: &[String]) -> Option<usize> {
+        match input {
+            UserInput::Selection(index) if *index < options.len() => Some(*index),
+            UserInput::Text(text) => {
+                let text = text.trim();
+                match text.parse::<usize>() {
+                    Ok(number) if (1..=options.len()).contains(&number) => Some(number - 1),
+                    _ => options.iter().position(|option| option.eq_ignore_ascii_case(text)),
+                }
+            }
+            _ => None,
         }
     }
     
@@ -189,6 +189,50 @@ mod tests {
         assert!(manager.finalize_all().is_ok(), "UnifiedUiManager finalization failed");
     }
 
+    /// Wait until a choice is open on `manager`
+    async fn wait_for_choice(manager: &UnifiedUiManager) {
+        for _ in 0..500 {
+            if manager.pending_choice.lock().unwrap().is_some() {
+                return;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("No choice was requested");
+    }
+
+    /// Test that a choice is answered by the next submitted input
+    #[tokio::test]
+    async fn test_request_choice_takes_next_input() {
+        let event_manager = Arc::new(DefaultEventManager::new()) as Arc<dyn EventManager>;
+        let manager = UnifiedUiManager::new(event_manager);
+        let options = vec!["Keep".to_string(), "Remove".to_string()];
+        assert!(
+            manager.request_choice("test_source", "Keep the file?", &options).await.is_err(),
+            "The console interface cannot answer a choice"
+        );
+        manager.register_interface(Box::new(TestInterface::new())).unwrap();
+
+        let asker = manager.clone();
+        let asked_options = options.clone();
+        let choice = tokio::spawn(async move { asker.request_choice("test_source", "Keep the file?", &asked_options).await });
+
+        // An answer that is not an option asks again
+        wait_for_choice(&manager).await;
+        manager.submit_user_input(UserInput::Text("maybe".to_string()), "test_interface").await.unwrap();
+        wait_for_choice(&manager).await;
+        manager.submit_user_input(UserInput::Text("2".to_string()), "test_interface").await.unwrap();
+        assert_eq!(choice.await.unwrap().unwrap(), 1);
+
+        let asker = manager.clone();
+        let choice = tokio::spawn(async move { asker.request_choice("test_source", "Keep the file?", &options).await });
+        wait_for_choice(&manager).await;
+        manager.submit_user_input(UserInput::Text("keep".to_string()), "test_interface").await.unwrap();
+        assert_eq!(choice.await.unwrap().unwrap(), 0);
+
+        // Without an open choice, input goes its usual way
+        assert!(manager.submit_user_input(UserInput::Selection(0), "test_interface").await.is_ok());
+    }
+
     /// Test UiMessage equality implementation
     #[test]
     fn test_message_equality() {
@@ -0,0 +1,34 @@
+#![allow(dead_code)] // Allow unused structs for FFI definition
+
+use std::os::raw::c_char;
+
+#[repr(C)]
+pub struct PluginAbiDescriptor {
+    pub abi_version: u32,
+    pub vtable_size: usize,
+    pub capabilities: u64,
+}
+
+// Simulates a plugin built against an incompatible gini-core: the ABI version
+// does not match the host and the VTable layout below is not the one it expects.
+#[no_mangle]
+pub static GINI_PLUGIN_ABI: PluginAbiDescriptor = PluginAbiDescriptor {
+    abi_version: 0,
+    vtable_size: std::mem::size_of::<PluginVTable>(),
+    capabilities: 0,
+};
+
+#[repr(C)]
+pub struct PluginVTable {
+    pub name: extern "C" fn() -> *const c_char,
+}
+
+extern "C" fn fake_name() -> *const c_char {
+    std::ptr::null()
+}
+
+#[no_mangle]
+pub extern "C" fn _plugin_init() -> *mut PluginVTable {
+    // The host must reject the plugin before calling this; a leaked VTable is fine here.
+    Box::into_raw(Box::new(PluginVTable { name: fake_name }))
+}
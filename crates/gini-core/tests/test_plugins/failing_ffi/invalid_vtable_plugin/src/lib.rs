@@ -17,6 +17,22 @@ pub struct PluginVTable {
     pub on_unload: extern "C" fn(),
 }
 
+#[repr(C)]
+pub struct PluginAbiDescriptor {
+    pub abi_version: u32,
+    pub vtable_size: usize,
+    pub capabilities: u64,
+}
+
+// A valid descriptor for gini-core's ABI version 1 (23 pointer-sized VTable entries),
+// so the host gets past the ABI handshake and reaches the null VTable below.
+#[no_mangle]
+pub static GINI_PLUGIN_ABI: PluginAbiDescriptor = PluginAbiDescriptor {
+    abi_version: 1,
+    vtable_size: 23 * std::mem::size_of::<usize>(),
+    capabilities: 0,
+};
+
 #[no_mangle]
 pub extern "C" fn _plugin_init() -> *const PluginVTable {
     // Intentionally return a null pointer, simulating a corrupted or invalid VTable.
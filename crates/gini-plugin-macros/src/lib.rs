@@ -0,0 +1,123 @@
+//! # Gini Plugin Macros
+//!
+//! Procedural macros for writing dynamically loaded Gini plugins.
+//!
+//! [`macro@gini_plugin`] turns a type implementing `gini_core::plugin_system::Plugin`
+//! into a loadable plugin library: it exports the `GINI_PLUGIN_ABI` descriptor and the
+//! `_plugin_init` entry point, whose VTable is built by
+//! `gini_core::plugin_system::ffi_export` (string/slice allocation and freeing, panic
+//! guards, and the async `preflight_check`).
+//!
+//! ```ignore
+//! use gini_plugin_macros::gini_plugin;
+//!
+//! #[gini_plugin]
+//! #[derive(Default)]
+//! pub struct MyPlugin;
+//!
+//! #[async_trait::async_trait]
+//! impl Plugin for MyPlugin { /* ... */ }
+//! ```
+//!
+//! The plugin crate must be built as a `cdylib` and depend on `gini-core`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{DeriveInput, ExprPath, parse_macro_input};
+
+/// Arguments accepted by `#[gini_plugin(...)]`.
+#[derive(Default)]
+struct PluginArgs {
+    /// Function called to construct the plugin; defaults to `Default::default`
+    constructor: Option<ExprPath>,
+}
+
+/// Exports a `Plugin` implementation from a `cdylib` plugin crate.
+///
+/// Place it on the plugin type. The type is constructed with `Default::default()`,
+/// or with the function given as `#[gini_plugin(constructor = MyPlugin::new)]`.
+/// Only one type per crate can be exported, since the generated symbols are fixed.
+#[proc_macro_attribute]
+pub fn gini_plugin(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut args = PluginArgs::default();
+    let arg_parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("constructor") {
+            args.constructor = Some(meta.value()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta.error("unsupported gini_plugin argument, expected `constructor = path::to::fn`"))
+        }
+    });
+    parse_macro_input!(attr with arg_parser);
+    let input = parse_macro_input!(item as DeriveInput);
+
+    expand_gini_plugin(args, input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_gini_plugin(args: PluginArgs, input: DeriveInput) -> syn::Result<TokenStream2> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "#[gini_plugin] cannot be used on generic types",
+        ));
+    }
+
+    let plugin_type = &input.ident;
+    let constructor = match args.constructor {
+        Some(path) => quote! { #path },
+        None => quote! { <#plugin_type as ::core::default::Default>::default },
+    };
+
+    Ok(quote! {
+        #input
+
+        /// ABI descriptor read by the Gini host before `_plugin_init` is called.
+        #[doc(hidden)]
+        #[unsafe(no_mangle)]
+        pub static GINI_PLUGIN_ABI: ::gini_core::plugin_system::traits::PluginAbiDescriptor =
+            ::gini_core::plugin_system::ffi_export::EXPORTED_ABI;
+
+        /// Entry point called by the Gini host to create the plugin's VTable.
+        #[doc(hidden)]
+        #[unsafe(no_mangle)]
+        pub extern "C" fn _plugin_init() -> *mut ::gini_core::plugin_system::traits::PluginVTable {
+            ::gini_core::plugin_system::ffi_export::plugin_init::<#plugin_type, _>(#constructor)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(args: PluginArgs, item: TokenStream2) -> syn::Result<String> {
+        expand_gini_plugin(args, syn::parse2(item).unwrap()).map(|tokens| tokens.to_string())
+    }
+
+    #[test]
+    fn test_expansion_exports_abi_and_init_symbols() {
+        let output = expand(PluginArgs::default(), quote! { pub struct MyPlugin; }).unwrap();
+        assert!(output.contains("pub struct MyPlugin"));
+        assert!(output.contains("GINI_PLUGIN_ABI"));
+        assert!(output.contains("fn _plugin_init"));
+        assert!(output.contains("plugin_init :: < MyPlugin , _ >"));
+        assert!(output.contains("Default > :: default"));
+    }
+
+    #[test]
+    fn test_expansion_uses_custom_constructor() {
+        let args = PluginArgs { constructor: Some(syn::parse_quote!(MyPlugin::new)) };
+        let output = expand(args, quote! { pub struct MyPlugin { value: u8 } }).unwrap();
+        assert!(output.contains("(MyPlugin :: new)"));
+        assert!(!output.contains("Default"));
+    }
+
+    #[test]
+    fn test_generic_types_are_rejected() {
+        let err = expand(PluginArgs::default(), quote! { pub struct MyPlugin<T> { value: T } }).unwrap_err();
+        assert!(err.to_string().contains("generic"));
+    }
+}
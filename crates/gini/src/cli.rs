@@ -1,11 +1,55 @@
-use gini_core::ui_bridge::{UnifiedUiInterface, UiMessage, UserInput, error::UiBridgeError, MessageSeverity};
+use gini_core::ui_bridge::{UnifiedUiInterface, UnifiedUiManager, UiMessage, UiUpdateType, UserInput, error::UiBridgeError, MessageSeverity};
+use std::io::{self, BufRead, IsTerminal, Write};
 
 /// A basic UI interface for the command-line interface.
 ///
 /// This interface handles messages by printing them to the console
 /// and can send user input back to the core.
 #[derive(Debug)]
-pub struct CliInterface;
+pub struct CliInterface {
+    /// Receives the answers to choices read from stdin
+    ui_manager: UnifiedUiManager,
+}
+
+impl CliInterface {
+    /// Creates the CLI interface; answers to choices are submitted to `ui_manager`.
+    pub fn new(ui_manager: UnifiedUiManager) -> Self {
+        Self { ui_manager }
+    }
+
+    /// Prints the options of a choice and reads the answer from stdin in the background,
+    /// submitting it to the UI manager once a line has been read.
+    fn ask(&self, prompt: &str, options: &[String]) {
+        println!("{}", prompt);
+        for (number, option) in options.iter().enumerate() {
+            println!("  {}) {}", number + 1, option);
+        }
+        print!("Choose 1-{}: ", options.len());
+        let _ = io::stdout().flush();
+
+        let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+            eprintln!("[CLI] Cannot read an answer outside of the async runtime.");
+            return;
+        };
+        let ui_manager = self.ui_manager.clone();
+        runtime.spawn(async move {
+            let line = tokio::task::spawn_blocking(|| {
+                let mut line = String::new();
+                io::stdin().lock().read_line(&mut line).map(|_| line)
+            })
+            .await;
+            match line {
+                Ok(Ok(line)) => {
+                    if let Err(e) = ui_manager.submit_user_input(UserInput::Text(line.trim().to_string()), "cli").await {
+                        eprintln!("[CLI] Failed to submit answer: {}", e);
+                    }
+                }
+                Ok(Err(e)) => eprintln!("[CLI] Failed to read answer: {}", e),
+                Err(e) => eprintln!("[CLI] Answer reader stopped: {}", e),
+            }
+        });
+    }
+}
 
 impl UnifiedUiInterface for CliInterface {
     /// Returns the name of this interface.
@@ -25,6 +69,10 @@ impl UnifiedUiInterface for CliInterface {
     ///
     /// It prints the message to standard output, with basic formatting.
     fn handle_message(&mut self, message: &UiMessage) -> Result<(), UiBridgeError> {
+        if let UiUpdateType::Choice(prompt, options) = &message.update_type {
+            self.ask(prompt, options);
+            return Ok(());
+        }
         let severity_prefix = match message.update_type {
             gini_core::ui_bridge::UiUpdateType::Log(_, severity) => match severity {
                 MessageSeverity::Error | MessageSeverity::Critical => "[ERROR] ",
@@ -93,8 +141,10 @@ impl UnifiedUiInterface for CliInterface {
     }
 
     /// Checks if the CLI interface supports interactive mode.
+    ///
+    /// Only when stdin is a terminal, so piped or scripted runs are never left waiting for an answer.
     fn supports_interactive(&self) -> bool {
-        true // CLI is typically interactive
+        io::stdin().is_terminal()
     }
 }
 
@@ -4,8 +4,13 @@ use gini_core::kernel::bootstrap::Application;
 // use gini_core::kernel::error::Error; // Import Error
 // use gini_core::storage::DefaultStorageManager; // Import DefaultStorageManager
 use gini_core::stage_manager::{StageManager, StageContext, StageResult}; // Remove unused StagePipeline
+use gini_core::stage_manager::definition::PIPELINES_CONFIG_DIR;
+use gini_core::plugin_system::error::PluginSystemError;
+use gini_core::plugin_system::lint::ManifestLint;
+use gini_core::plugin_system::package::{InstallOutcome, PluginInstaller};
 use clap::{Parser, Subcommand}; // Use clap for argument parsing
 use std::sync::Arc; // Use Arc for shared ownership of the connector
+use std::path::PathBuf; // For --plugin-dir
 use log::{info, error}; // Added logging imports
 
 // --- Import Core Plugins for Static Registration ---
@@ -29,6 +34,10 @@ struct CliArgs {
     #[arg(long)]
     ping: bool,
 
+    /// Additional directory to search for plugins (can be repeated; takes precedence over other sources)
+    #[arg(long = "plugin-dir", value_name = "DIR", global = true)]
+    plugin_dirs: Vec<PathBuf>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -40,6 +49,11 @@ enum Commands {
         #[command(subcommand)]
         command: PluginCommand,
     },
+    /// List, show and run named pipelines
+    Pipeline {
+        #[command(subcommand)]
+        command: PipelineCommand,
+    },
     /// Run a specific stage by its ID
     RunStage {
         /// The ID of the stage to run
@@ -48,22 +62,118 @@ enum Commands {
         #[arg(long, value_parser = parse_key_val)]
         context_vars: Vec<(String, String)>,
     },
+    /// Serve a sandboxed plugin in this process (started by the core, not meant to be run by hand)
+    #[command(name = "plugin-host", hide = true)]
+    PluginHost,
 }
 
 #[derive(Subcommand, Debug)]
 enum PluginCommand {
     /// List registered plugins
     List {},
-    /// Enable a plugin (persist setting)
+    /// Enable a plugin and the plugins it requires, and initialize them (persist setting)
     Enable {
         /// The name of the plugin to enable
         name: String
     },
-    /// Disable a plugin (persist setting)
+    /// Disable a plugin and shut it down (persist setting)
     Disable {
         /// The name of the plugin to disable
-        name: String
+        name: String,
+        /// Also disable the enabled plugins that require it
+        #[arg(long)]
+        cascade: bool,
+    },
+    /// Show the permissions granted to plugins from their declared resources
+    Permissions {
+        /// Only show this plugin
+        name: Option<String>,
+    },
+    /// Show the lifecycle state of plugins, why they are in it and their last error
+    Status {
+        /// Only show this plugin
+        name: Option<String>,
+    },
+    /// Show the effective configuration of a plugin, with defaults and schema violations
+    Config {
+        /// The ID of the plugin
+        name: String,
     },
+    /// Check a plugin manifest for unknown keys, invalid versions and priorities, and missing files
+    Lint {
+        /// Path to the manifest file or the plugin directory
+        path: PathBuf,
+    },
+    /// Install a plugin archive into the third-party plugin directory
+    Install {
+        /// Path to the plugin archive (.tar.gz)
+        archive: PathBuf,
+    },
+    /// Remove an installed third-party plugin
+    Uninstall {
+        /// The ID of the plugin to remove
+        name: String,
+    },
+    /// Replace an installed third-party plugin with a newer version from an archive
+    Upgrade {
+        /// Path to the plugin archive (.tar.gz)
+        archive: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum PipelineCommand {
+    /// List the registered pipelines, from plugins and from the pipelines configuration directory
+    List {},
+    /// Show the stages of a pipeline with their dependencies, parameters, conditions and policies
+    Show {
+        /// The name of the pipeline
+        name: String,
+    },
+    /// Run a pipeline and report the result of each stage
+    Run {
+        /// The name of the pipeline
+        name: String,
+        /// Context variables to set for the stages (e.g., key=value)
+        #[arg(long, value_parser = parse_key_val)]
+        context_vars: Vec<(String, String)>,
+        /// Describe what the stages would do instead of running them
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+/// Installer for the third-party plugin directory below the data directory.
+/// Plugins in the rest of the plugin search path count as installed for conflict checks.
+async fn plugin_installer(app: &Application) -> PluginInstaller {
+    let search_dirs = app.plugin_manager().plugin_search_paths().await.dirs();
+    PluginInstaller::for_data_dir(app.storage_manager().data_dir()).with_search_dirs(search_dirs)
+}
+
+/// Prints the result of an install or upgrade, or exits with an error.
+fn report_install_outcome(action: &str, result: Result<InstallOutcome, PluginSystemError>) {
+    match result {
+        Ok(outcome) => {
+            for conflict in &outcome.warnings {
+                println!("Warning: {}", conflict.description);
+            }
+            let manifest = &outcome.manifest;
+            match &outcome.previous_version {
+                Some(previous) => println!(
+                    "{} plugin '{}' from {} to {} in {}.",
+                    action, manifest.id, previous, manifest.version, manifest.plugin_base_dir.display()
+                ),
+                None => println!(
+                    "{} plugin '{}' {} into {}.",
+                    action, manifest.id, manifest.version, manifest.plugin_base_dir.display()
+                ),
+            }
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
 
@@ -81,6 +191,17 @@ async fn main() {
         return; // Exit after pong
     }
 
+    // Sandboxed plugin host: serve the plugin named in the environment and exit.
+    // Runs on a blocking thread, since the host drives its own runtime.
+    if let Some(Commands::PluginHost) = args.command {
+        match tokio::task::spawn_blocking(gini_core::plugin_system::sandbox::run_plugin_host).await {
+            Ok(Ok(())) => return,
+            Ok(Err(e)) => eprintln!("Plugin host failed: {}", e),
+            Err(e) => eprintln!("Plugin host failed: {}", e),
+        }
+        std::process::exit(1);
+    }
+
     println!("Initializing application...");
     
     // Create the application instance
@@ -99,6 +220,7 @@ async fn main() {
     // This needs to happen after app init but before commands that might rely on these plugins.
     println!("Registering static core plugins...");
     let plugin_manager = app.plugin_manager(); // Get PluginManager Arc
+    plugin_manager.add_cli_plugin_dirs(&args.plugin_dirs).await; // Highest-precedence plugin search dirs
     let registry_arc = plugin_manager.registry(); // Get Registry Arc<Mutex>
     { // Scope for the MutexGuard
         let mut registry = registry_arc.lock().await; // Lock the registry
@@ -159,6 +281,19 @@ async fn main() {
     println!("All plugins initialized.");
     // --- End Plugin Initialization ---
 
+    // --- Register Configured Pipelines ---
+    // Pipeline definition files in the configuration directory; their stages are registered by now
+    let config_manager = app.storage_manager().get_config_manager().clone();
+    match app.stage_manager().register_configured_pipelines(&config_manager).await {
+        Ok(errors) => {
+            for e in errors {
+                error!("Skipping pipeline definition in '{}': {}", PIPELINES_CONFIG_DIR, e);
+            }
+        }
+        Err(e) => error!("Failed to read the pipeline definitions in '{}': {}", PIPELINES_CONFIG_DIR, e),
+    }
+    // --- End Configured Pipelines ---
+
     // --- Run Startup Pipeline ---
     info!("Running startup environment check pipeline...");
     let stage_manager = app.stage_manager();
@@ -182,7 +317,14 @@ async fn main() {
             };
             let mut context = StageContext::new_live(storage_manager.config_dir().to_path_buf());
             match stage_manager.execute_pipeline(&mut startup_pipeline, &mut context).await {
-                Ok(_) => info!("Startup environment check pipeline completed successfully."),
+                Ok(results) => {
+                    info!("Startup environment check pipeline completed.");
+                    for stage_id in startup_pipeline.stages() {
+                        if let Some(result) = results.get(stage_id) {
+                            info!("  - {}: {}", stage_id, result);
+                        }
+                    }
+                }
                 Err(e) => error!("Startup environment check pipeline failed during execution: {:?}", e),
             }
         }
@@ -206,13 +348,22 @@ async fn main() {
                     let plugin_manager = app.plugin_manager(); // Get PluginManager Arc
                     let registry_arc = plugin_manager.registry(); // Get Registry Arc<Mutex>
                     let registry = registry_arc.lock().await; // Lock the registry
+                    let verifier = plugin_manager.signature_verifier();
 
                     if registry.iter_plugins().next().is_none() {
                         println!("  No plugins registered.");
                     } else {
                         for (id, plugin_arc) in registry.iter_plugins() {
                             let status = if registry.is_enabled(id) { "Enabled" } else { "Disabled" };
-                            println!("  - Name: {}, Version: {}, Status: {}", plugin_arc.name(), plugin_arc.version(), status);
+                            let loaded = if registry.is_plugin_code_loaded(id) { "" } else { " (not loaded)" }; // Manifest-only plugins
+                            // Checked when the library was loaded; manifest-only plugins are checked now, statically linked ones have no signature
+                            let signature = match (verifier.status(id), registry.get_manifest(id)) {
+                                (Some(verification), _) => verification.to_string(),
+                                (None, Some(manifest)) => verifier.verify(manifest).to_string(),
+                                (None, None) => "built-in".to_string(),
+                            };
+                            let state = registry.lifecycle().state(id).map(|state| state.to_string()).unwrap_or_else(|| "Unknown".to_string());
+                            println!("  - Name: {}, Version: {}, Status: {}{}, State: {}, Signature: {}", plugin_arc.name(), plugin_arc.version(), status, loaded, state, signature);
                         }
                     }
                     // Command handled, exit successfully
@@ -221,10 +372,12 @@ async fn main() {
                 PluginCommand::Enable { name } => {
                     println!("Attempting to enable plugin '{}'...", name);
                     let plugin_manager = app.plugin_manager(); // Get PluginManager Arc
-                    match plugin_manager.persist_enable_plugin(&name).await {
-                        Ok(_) => {
-                            println!("Successfully marked plugin '{}' as enabled.", name);
-                            // Note: Actual loading happens on next app start based on persisted state.
+                    match plugin_manager.enable_plugin(&name, &mut app).await {
+                        Ok(dependencies) => {
+                            if !dependencies.is_empty() {
+                                println!("Enabled required plugins: {}", dependencies.join(", "));
+                            }
+                            println!("Successfully enabled plugin '{}'.", name);
                         }
                         Err(e) => {
                             eprintln!("Error enabling plugin '{}': {}", name, e);
@@ -233,16 +386,129 @@ async fn main() {
                     // Command handled, exit successfully
                     return;
                 }
-                PluginCommand::Disable { name } => {
+                PluginCommand::Permissions { name } => {
+                    let permissions = app.plugin_manager().permissions().clone();
+                    let reports = match name {
+                        Some(name) => match permissions.report(&name) {
+                            Some(report) => vec![report],
+                            None => {
+                                eprintln!("Plugin '{}' is not registered.", name);
+                                std::process::exit(1);
+                            }
+                        },
+                        None => permissions.reports(),
+                    };
+                    if reports.is_empty() {
+                        println!("  No plugins registered.");
+                    }
+                    for report in reports {
+                        print!("{}", report);
+                    }
+                    // Command handled, exit successfully
+                    return;
+                }
+                PluginCommand::Status { name } => {
+                    let lifecycle = app.plugin_manager().lifecycle().clone();
+                    let statuses = match name {
+                        Some(name) => match lifecycle.status(&name) {
+                            Some(status) => vec![status],
+                            None => {
+                                eprintln!("Plugin '{}' is not registered.", name);
+                                std::process::exit(1);
+                            }
+                        },
+                        None => {
+                            if let Some(report) = app.plugin_manager().registry().lock().await.last_startup_report() {
+                                print!("{}", report);
+                            }
+                            lifecycle.statuses().into_values().collect()
+                        }
+                    };
+                    if statuses.is_empty() {
+                        println!("  No plugins registered.");
+                    }
+                    let permissions = app.plugin_manager().permissions().clone();
+                    for status in statuses {
+                        print!("{}", status);
+                        println!("  permissions: {}", permissions.enforcement(&status.plugin_id));
+                    }
+                    // Command handled, exit successfully
+                    return;
+                }
+                PluginCommand::Config { name } => {
+                    match app.plugin_manager().plugin_config_report(&name).await {
+                        Ok(report) => {
+                            print!("{}", report);
+                            if !report.violations.is_empty() {
+                                std::process::exit(1);
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                    // Command handled, exit successfully
+                    return;
+                }
+                PluginCommand::Lint { path } => {
+                    match ManifestLint::lint(&path) {
+                        Ok(lint) => {
+                            print!("{}", lint);
+                            if lint.has_errors() {
+                                std::process::exit(1);
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                    // Command handled, exit successfully
+                    return;
+                }
+                PluginCommand::Install { archive } => {
+                    println!("Installing plugin archive {}...", archive.display());
+                    let installer = plugin_installer(&app).await;
+                    report_install_outcome("Installed", installer.install(&archive).await);
+                    // Command handled, exit successfully
+                    return;
+                }
+                PluginCommand::Upgrade { archive } => {
+                    println!("Upgrading from plugin archive {}...", archive.display());
+                    let installer = plugin_installer(&app).await;
+                    report_install_outcome("Upgraded", installer.upgrade(&archive).await);
+                    // Command handled, exit successfully
+                    return;
+                }
+                PluginCommand::Uninstall { name } => {
+                    println!("Uninstalling plugin '{}'...", name);
+                    let installer = plugin_installer(&app).await;
+                    match installer.uninstall(&name).await {
+                        Ok(manifest) => println!("Uninstalled plugin '{}' {}.", manifest.id, manifest.version),
+                        Err(e) => {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                    // Command handled, exit successfully
+                    return;
+                }
+                PluginCommand::Disable { name, cascade } => {
                     println!("Attempting to disable plugin '{}'...", name);
                     let plugin_manager = app.plugin_manager(); // Get PluginManager Arc
-                    match plugin_manager.persist_disable_plugin(&name).await {
-                        Ok(_) => {
-                            println!("Successfully marked plugin '{}' as disabled.", name);
-                            // Note: Actual unloading/prevention happens on next app start based on persisted state.
+                    match plugin_manager.disable_plugin(&name, cascade).await {
+                        Ok(dependents) => {
+                            if !dependents.is_empty() {
+                                println!("Disabled dependent plugins: {}", dependents.join(", "));
+                            }
+                            println!("Successfully disabled plugin '{}'.", name);
                         }
                         Err(e) => {
                             eprintln!("Error disabling plugin '{}': {}", name, e);
+                            if !cascade && !plugin_manager.registry().lock().await.dependents_of(&name).is_empty() {
+                                eprintln!("Run again with --cascade to disable the plugins that require it as well.");
+                            }
                         }
                     }
                     // Command handled, exit successfully
@@ -250,6 +516,67 @@ async fn main() {
                 }
             }
         }
+        Some(Commands::Pipeline { command }) => {
+            let stage_manager = app.stage_manager();
+            match command {
+                PipelineCommand::List {} => {
+                    let definitions = stage_manager.get_pipeline_definitions().await.unwrap_or_default();
+                    if definitions.is_empty() {
+                        println!("  No pipelines registered.");
+                    }
+                    for definition in definitions {
+                        println!("  - {} ({} stages): {}", definition.name, definition.stages.len(), definition.description.as_deref().unwrap_or(""));
+                    }
+                }
+                PipelineCommand::Show { name } => {
+                    let definitions = stage_manager.get_pipeline_definitions().await.unwrap_or_default();
+                    match definitions.into_iter().find(|definition| definition.name == name) {
+                        Some(definition) => print!("{}", definition),
+                        None => {
+                            eprintln!("Pipeline '{}' is not registered.", name);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+                PipelineCommand::Run { name, context_vars, dry_run } => {
+                    let mut pipeline = match stage_manager.get_pipeline_by_name(&name).await {
+                        Ok(Some(pipeline)) => pipeline,
+                        Ok(None) => {
+                            eprintln!("Pipeline '{}' is not registered.", name);
+                            std::process::exit(1);
+                        }
+                        Err(e) => {
+                            eprintln!("Error: {}", e);
+                            std::process::exit(1);
+                        }
+                    };
+                    let config_dir = app.storage_manager().config_dir().to_path_buf();
+                    let mut context = if dry_run { StageContext::new_dry_run(config_dir) } else { StageContext::new_live(config_dir) };
+                    for (key, value) in context_vars {
+                        context.set_data(&key, value);
+                    }
+                    match stage_manager.execute_pipeline(&mut pipeline, &mut context).await {
+                        Ok(results) => {
+                            println!("Pipeline '{}' finished. Results:", name);
+                            for stage_id in pipeline.stages() {
+                                if let Some(result) = results.get(stage_id) {
+                                    println!("  - {}: {}", stage_id, result);
+                                }
+                            }
+                            if results.values().any(|result| matches!(result, StageResult::Failure(_))) {
+                                std::process::exit(1);
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("Pipeline '{}' aborted: {}", name, e);
+                            std::process::exit(1);
+                        }
+                    }
+                }
+            }
+            // Command handled, exit successfully
+            return;
+        }
         Some(Commands::RunStage { stage_id, context_vars }) => {
             println!("Attempting to run stage '{}'...", stage_id);
             let stage_manager = app.stage_manager(); // Get StageManager Arc
@@ -313,11 +640,12 @@ async fn main() {
             // Command handled, exit successfully
             return;
         }
+        Some(Commands::PluginHost) => unreachable!("plugin-host is handled before application startup"),
         None => {
             // No command specified, proceed with default app run
             println!("No command specified, running default application loop...");
             // Create and register the CLI interface
-            let cli_interface = Box::new(cli::CliInterface); // Instantiate the interface
+            let cli_interface = Box::new(cli::CliInterface::new(app.ui_manager_mut().clone())); // Submits answers back to the manager
             if let Err(e) = app.ui_manager_mut().register_interface(cli_interface) {
                 eprintln!("Failed to register CLI interface: {}", e);
                 // Decide if this is fatal. For now, log and continue, but the UI might not work.
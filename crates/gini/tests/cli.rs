@@ -30,4 +30,101 @@ fn test_no_args_runs_normally() -> Result<(), Box<dyn std::error::Error>> {
         .stdout(predicate::str::contains("pong").not()); // Ensure "pong" is NOT printed
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_plugin_host_requires_core_environment() -> Result<(), Box<dyn std::error::Error>> {
+    // The hidden plugin-host command is started by the core with the socket and library in its environment
+    let mut cmd = Command::cargo_bin("gini")?;
+    cmd.arg("plugin-host")
+        .env_remove("GINI_PLUGIN_HOST_SOCKET")
+        .env_remove("GINI_PLUGIN_HOST_LIBRARY");
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("GINI_PLUGIN_HOST_SOCKET is not set"))
+        .stdout(predicate::str::contains("Initializing application...").not()); // Exits before application startup
+
+    Ok(())
+}
+
+#[test]
+fn test_plugin_permissions_command() -> Result<(), Box<dyn std::error::Error>> {
+    // Every registered plugin is granted at least its own configuration
+    let mut cmd = Command::cargo_bin("gini")?;
+    cmd.args(["plugin", "permissions", "core-logging"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Permissions for plugin 'core-logging'"))
+        .stdout(predicate::str::contains("granted write config_scope 'plugin:core-logging' (implicit)"));
+
+    let mut cmd = Command::cargo_bin("gini")?;
+    cmd.args(["plugin", "permissions", "no-such-plugin"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Plugin 'no-such-plugin' is not registered."));
+
+    Ok(())
+}
+
+#[test]
+fn test_plugin_list_shows_signature_status() -> Result<(), Box<dyn std::error::Error>> {
+    // Statically registered plugins are compiled in, so there is nothing to verify
+    let mut cmd = Command::cargo_bin("gini")?;
+    cmd.args(["plugin", "list"]);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("core-logging"))
+        .stdout(predicate::str::contains("Signature: built-in"));
+
+    Ok(())
+}
+
+#[test]
+fn test_plugin_install_and_uninstall_commands() -> Result<(), Box<dyn std::error::Error>> {
+    use gini_core::plugin_system::package::PluginArchive;
+
+    // Install into a throwaway XDG data dir
+    let root = tempfile::tempdir()?;
+    let plugin_dir = root.path().join("cli-packaged");
+    std::fs::create_dir_all(&plugin_dir)?;
+    std::fs::write(
+        plugin_dir.join("manifest.json"),
+        r#"{"id": "cli-packaged", "name": "CLI Packaged", "version": "1.0.0", "description": "", "author": "",
+            "api_versions": [">=0.1.0"], "entry_point": "libcli_packaged.so"}"#,
+    )?;
+    std::fs::write(plugin_dir.join("libcli_packaged.so"), "not a real library")?;
+    let archive = root.path().join("cli-packaged-1.0.0.tar.gz");
+    PluginArchive::create(&plugin_dir, &archive)?;
+
+    let gini = |args: &[&str]| -> Result<Command, Box<dyn std::error::Error>> {
+        let mut cmd = Command::cargo_bin("gini")?;
+        cmd.args(args)
+            .current_dir(root.path())
+            .env("XDG_DATA_HOME", root.path().join("data"))
+            .env("XDG_CONFIG_HOME", root.path().join("config"))
+            .env_remove("GINI_PLUGIN_PATH");
+        Ok(cmd)
+    };
+
+    gini(&["plugin", "install", archive.to_str().unwrap()])?
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed plugin 'cli-packaged' 1.0.0"));
+    assert!(root.path().join("data/gini/plugins/third_party/cli-packaged/manifest.json").is_file());
+
+    gini(&["plugin", "install", archive.to_str().unwrap()])?
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("already installed"));
+
+    gini(&["plugin", "uninstall", "cli-packaged"])?
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Uninstalled plugin 'cli-packaged' 1.0.0."));
+    assert!(!root.path().join("data/gini/plugins/third_party/cli-packaged").exists());
+
+    Ok(())
+}
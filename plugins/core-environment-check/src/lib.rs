@@ -16,7 +16,7 @@ use gini_core::stage_manager::{
     context::StageContext,       // Import StageContext
     requirement::StageRequirement, // Import StageRequirement
     registry::StageRegistry,     // Import StageRegistry
-    pipeline::PipelineDefinition, // Import PipelineDefinition
+    definition::PipelineDefinition, // Import PipelineDefinition
     Stage,                       // Import Stage trait (defined in stage_manager/mod.rs)
 };
 use log::info;
@@ -316,11 +316,8 @@ impl Plugin for EnvironmentCheckPlugin {
             "env_check:check_system_packages",
         ];
 
-        let startup_pipeline_def = PipelineDefinition {
-            name: "startup_environment_check",
-            stages: STARTUP_PIPELINE_STAGES,
-            description: Some("Core environment checks provided by the core-environment-check plugin."),
-        };
+        let startup_pipeline_def = PipelineDefinition::new("startup_environment_check", STARTUP_PIPELINE_STAGES)
+            .with_description("Core environment checks provided by the core-environment-check plugin.");
 
         registry.register_pipeline(startup_pipeline_def)
             .map_err(|e| PluginSystemError::InternalError(format!("Failed to register startup pipeline: {}", e)))?;
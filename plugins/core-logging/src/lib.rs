@@ -5,11 +5,8 @@ use gini_core::plugin_system::traits::{Plugin, PluginPriority};
 use gini_core::plugin_system::version::VersionRange;
 use gini_core::stage_manager::registry::StageRegistry;
 use gini_core::stage_manager::requirement::StageRequirement;
-use gini_core::storage::error::StorageSystemError as GiniStorageError; // Corrected Alias
+use gini_core::storage::schema::{ConfigSchema, SchemaProperty};
 // use gini_core::storage::DefaultStorageManager; // Import concrete type - Removed as unused
-use serde::{Deserialize, Serialize};
-use serde_json; // For ConfigData to LoggingConfig conversion
-use std::error::Error as StdError; // Import the standard Error trait
 
 // Tracing specific imports
 use tracing; // For macros like tracing::info!
@@ -21,13 +18,25 @@ use tracing_subscriber::{
 // tracing_log import removed as LogTracer::init() is not called directly.
 // The "fmt" feature of tracing-subscriber handles log bridging.
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+const DEFAULT_LEVEL: &str = "info";
+const DEFAULT_FORMAT: &str = "compact";
+
+#[derive(Debug)]
 struct LoggingConfig {
-    default_level: Option<String>,
-    format: Option<String>,
+    default_level: String,
+    format: String,
     // per_module_levels: Option<HashMap<String, String>>, // Example for future extension
 }
 
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            default_level: DEFAULT_LEVEL.to_string(),
+            format: DEFAULT_FORMAT.to_string(),
+        }
+    }
+}
+
 // Define the main plugin struct
 #[derive(Default)]
 #[allow(dead_code)] // Suppress warning as it might be loaded implicitly
@@ -87,126 +96,62 @@ impl Plugin for LoggingPlugin {
         vec![]
     }
 
-    fn init(&self, app: &mut Application) -> Result<(), PluginSystemError> {
-        let storage_manager = app.storage_manager(); // Arc<DefaultStorageManager>
-
-        let logging_config: LoggingConfig = match storage_manager.get_plugin_config(self.name()) {
-            Ok(config_data) => {
-                // ConfigData holds values as HashMap<String, serde_json::Value>.
-                // We need to convert this to our LoggingConfig struct.
-                // A common way is to serialize ConfigData to a serde_json::Value,
-                // then deserialize that Value into LoggingConfig.
-                match serde_json::to_value(config_data) {
-                    Ok(json_val) => {
-                        match serde_json::from_value::<LoggingConfig>(json_val.clone()) {
-                            Ok(cfg) => {
-                                tracing::debug!(
-                                    plugin_name = self.name(),
-                                    config_name = self.name(),
-                                    "Logging configuration loaded and parsed successfully."
-                                );
-                                cfg
-                            }
-                            Err(e) => {
-                                // This case means the file was found and read by ConfigManager,
-                                // but its structure doesn't match LoggingConfig.
-                                tracing::warn!(
-                                    plugin_name = self.name(),
-                                    config_name = self.name(),
-                                    error = %e,
-                                    loaded_config_json = %json_val, // Log the problematic JSON
-                                    "Failed to parse loaded logging configuration into LoggingConfig struct. Using default settings."
-                                );
-                                LoggingConfig::default()
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        // Error serializing ConfigData to serde_json::Value
-                        tracing::warn!(
-                            plugin_name = self.name(),
-                            config_name = self.name(),
-                            error = %e,
-                            "Failed to convert ConfigData to JSON for parsing. Using default settings."
-                        );
-                        LoggingConfig::default()
-                    }
-                }
-            }
-            Err(kernel_error) => {
-                // get_plugin_config returns KernelError. We need to check if it's due to NotFound.
-                // KernelError can wrap a StorageSystemError.
-                let mut is_not_found = false;
-                // Use a loop to traverse the source chain, as the direct source might not be GiniStorageError
-                let mut current_err: Option<&dyn StdError> = Some(&kernel_error);
-                while let Some(err) = current_err {
-                    if let Some(storage_err) = err.downcast_ref::<GiniStorageError>() {
-                        if matches!(storage_err, GiniStorageError::ConfigNotFound { .. } | GiniStorageError::FileNotFound(_)) {
-                            is_not_found = true;
-                            break; // Found the relevant error
-                        }
-                    }
-                    current_err = err.source();
-                }
+    fn config_schema(&self) -> Option<ConfigSchema> {
+        Some(
+            ConfigSchema::new()
+                .property(
+                    "default_level",
+                    SchemaProperty::string()
+                        .with_default(DEFAULT_LEVEL)
+                        .describe("Log filter used when RUST_LOG is not set, e.g. \"info\" or \"info,gini_core=debug\""),
+                )
+                .property(
+                    "format",
+                    SchemaProperty::string()
+                        .one_of(&["compact", "pretty", "json"])
+                        .with_default(DEFAULT_FORMAT)
+                        .describe("Output format of log lines"),
+                ),
+        )
+    }
 
-                if is_not_found {
-                    tracing::info!(
-                        plugin_name = self.name(),
-                        config_name = self.name(),
-                        "Logging configuration file ('{}.toml' or similar) not found. Using default settings.", self.name()
-                    );
-                } else {
-                    tracing::warn!(
-                        plugin_name = self.name(),
-                        config_name = self.name(),
-                        error = %kernel_error,
-                        "Failed to load logging configuration. Using default settings."
-                    );
-                }
+    fn init(&self, app: &mut Application) -> Result<(), PluginSystemError> {
+        // The registry has already checked the configuration against config_schema and
+        // filled in its defaults
+        let logging_config: LoggingConfig = match app.storage_manager().get_plugin_config(self.name()) {
+            Ok(config_data) => LoggingConfig {
+                default_level: config_data.get_or("default_level", DEFAULT_LEVEL.to_string()),
+                format: config_data.get_or("format", DEFAULT_FORMAT.to_string()),
+            },
+            Err(e) => {
+                tracing::warn!(
+                    plugin_name = self.name(),
+                    error = %e,
+                    "Failed to load logging configuration. Using default settings."
+                );
                 LoggingConfig::default()
             }
         };
 
         // Configure EnvFilter:
         // 1. Try to read from RUST_LOG environment variable.
-        // 2. If not set, fall back to `logging_config.default_level` or "info".
-        let default_log_level_from_config = logging_config
-            .default_level
-            .as_deref()
-            .unwrap_or("info");
-
+        // 2. If not set, fall back to `logging_config.default_level`.
         let env_filter = EnvFilter::try_from_default_env()
-            .or_else(|_| EnvFilter::try_new(default_log_level_from_config))
+            .or_else(|_| EnvFilter::try_new(&logging_config.default_level))
             .map_err(|e| {
                 PluginSystemError::InternalError(format!(
                     "Failed to create EnvFilter with effective level '{}': {}",
-                    default_log_level_from_config, e
+                    logging_config.default_level, e
                 ))
             })?;
 
-        // Configure Format Layer:
-        // Use `logging_config.format` or default to "compact".
-        let format_str = logging_config.format.as_deref().unwrap_or("compact");
-
         // Build the subscriber and initialize
         let subscriber_builder = Registry::default().with(env_filter);
 
-        match format_str {
+        match logging_config.format.as_str() {
             "pretty" => subscriber_builder.with(fmt::layer().pretty()).try_init(),
             "json" => subscriber_builder.with(fmt::layer().json()).try_init(),
-            "compact" | _ => {
-                if format_str != "compact" && logging_config.format.is_some() {
-                    // This warning uses the 'compact' logger as it's about to be set.
-                    // It will be emitted after try_init if we log it there.
-                    // Logging it here means it's queued for the new logger.
-                    tracing::warn!(
-                        plugin_name = self.name(),
-                        invalid_format = %logging_config.format.as_ref().unwrap(),
-                        "Unrecognized log format specified. Defaulting to 'compact'."
-                    );
-                }
-                subscriber_builder.with(fmt::layer().compact()).try_init()
-            }
+            _ => subscriber_builder.with(fmt::layer().compact()).try_init(),
         }
         .map_err(|e| {
             PluginSystemError::InternalError(format!(
@@ -216,21 +161,17 @@ impl Plugin for LoggingPlugin {
         })?;
 
         // Determine source of effective log level for clarity in logs
-        let (level_source_msg, final_effective_level_str) =
-            if std::env::var("RUST_LOG").is_ok() {
-                ("RUST_LOG environment variable", std::env::var("RUST_LOG").unwrap_or_default())
-            } else if logging_config.default_level.is_some() {
-                ("logging_config.default_level", default_log_level_from_config.to_string())
-            } else {
-                ("hardcoded_default", default_log_level_from_config.to_string())
-            };
+        let (level_source_msg, final_effective_level_str) = match std::env::var("RUST_LOG") {
+            Ok(rust_log) => ("RUST_LOG environment variable", rust_log),
+            Err(_) => ("logging_config.default_level", logging_config.default_level.clone()),
+        };
 
         tracing::info!(
             plugin_name = self.name(),
             plugin_version = self.version(),
             effective_log_level_source = %level_source_msg,
             effective_log_level = %final_effective_level_str, // RUST_LOG can be complex, this shows the fallback or actual RUST_LOG string
-            log_format = %format_str,
+            log_format = %logging_config.format,
             "Core Logging Plugin initialized with tracing."
         );
         Ok(())
@@ -6,6 +6,7 @@ use gini_core::kernel::bootstrap::Application;
 use gini_core::plugin_system::{
     error::PluginSystemError,
     error::PluginSystemErrorSource,
+    permission::PluginAccess,
     traits::{Plugin, PluginPriority},
     version::VersionRange,
     dependency::PluginDependency,
@@ -24,8 +25,9 @@ use tokio::sync::Mutex as TokioMutex; // Single import
 use thiserror::Error;
 use tokio::runtime::Handle;
 use chrono::{Utc, DateTime}; // Added DateTime
-use gini_core::event::{Event, AsyncEventHandler, EventResult, EventManager, SystemEvent, DefaultEventManager}; // Added DefaultEventManager
-use gini_core::event::dispatcher::BoxFuture;
+use gini_core::event::{Event, AsyncEventHandler, EventResult, SystemEvent};
+use gini_core::event::dispatcher::PluginHandlerFn;
+use gini_core::storage::schema::ConfigSchema;
 use serde::{Serialize, Deserialize}; // For event serialization if needed
 
 // use discord_presence::client::Client as DiscordClient; // No longer needed here
@@ -199,7 +201,7 @@ mod settings;
 mod ipc_linux;
 
 use rpc_wrapper::{DiscordRpcWrapper, WrapperError};
-use settings::{load_settings, RpcSettings, SettingsError};
+use settings::{config_schema, load_settings, RpcSettings, SettingsError};
 
 const PLUGIN_ID_STR: &str = "core-rpc";
 
@@ -298,6 +300,10 @@ impl Plugin for CoreRpcPlugin {
         vec![]
     }
 
+    fn config_schema(&self) -> Option<ConfigSchema> {
+        Some(config_schema())
+    }
+
     async fn preflight_check(&self, _context: &StageContext) -> std::result::Result<(), PluginSystemError> {
         Ok(())
     }
@@ -306,43 +312,24 @@ impl Plugin for CoreRpcPlugin {
         info!("Core RPC Plugin: Initializing...");
         let tokio_handle = Handle::current();
 
-        let storage_manager_arc = app.storage_manager();
-        let settings_for_task = Arc::clone(&self.settings);
+        // The registry has already checked the configuration against config_schema and
+        // filled in its defaults
+        let final_settings = load_settings(&app.storage_manager(), self.name()).map_err(CoreRpcError::from)?;
+        *self.settings.lock().unwrap() = Some(final_settings.clone());
         let rpc_wrapper_handle_clone = Arc::clone(&self.rpc_wrapper_handle);
 
-        // Assuming Application has `fn dependencies_arc(&self) -> Arc<TokioMutex<gini_core::kernel::component::DependencyRegistry>>`
-        // This method needs to be added to `Application` if it doesn't exist.
-        let app_dependencies_clone = app.dependencies_arc();
+        // Event handlers registered through the access handle run under this plugin's ID and priority
+        let plugin_access = PluginAccess::current(app)?;
 
         info!("Core RPC Plugin: Queuing async setup for RPC client and initial presence.");
 
         tokio_handle.spawn(async move {
             let rpc_wrapper_handle = rpc_wrapper_handle_clone;
-            let app_deps = app_dependencies_clone; // Move the cloned Arc in
             
             info!("Core RPC Plugin: Async task started.");
-            
-            // Fetch EventManager inside the async block using the cloned app_deps
-            let event_manager_option: Option<Arc<DefaultEventManager>> = {
-                let deps_registry_guard = app_deps.lock().await; // This is tokio::sync::Mutex
-                // Assuming DependencyRegistry has `get_concrete<T: KernelComponent + 'static>(&self) -> Option<Arc<T>>`
-                deps_registry_guard.get_concrete::<DefaultEventManager>()
-            };
-
-            // 1. Load Settings
-            let final_settings = match load_settings(storage_manager_arc).await {
-                Ok(s) => {
-                    debug!("RPC Settings loaded: {:?}", s);
-                    *settings_for_task.lock().unwrap() = Some(s.clone());
-                    s
-                }
-                Err(e) => {
-                    error!("Failed to load RPC settings: {}", e);
-                    return;
-                }
-            }; // Correctly closes match load_settings
 
-            // 2. Start RPC Client if enabled
+
+            // 1. Start RPC Client if enabled
             if final_settings.enabled {
                 if let Some(client_id) = &final_settings.client_id {
                     if !client_id.is_empty() {
@@ -362,87 +349,82 @@ impl Plugin for CoreRpcPlugin {
                         // drop(rpc_wrapper_guard); // Keep guard until after event handler registration
                         info!("DiscordRpcWrapper started and stored.");
 
-                        // 3. Register Event Handler
+                        // 2. Register Event Handler
                         if final_settings.enable_dynamic_updates {
-                            if let Some(event_manager) = event_manager_option.as_ref() {
-                                info!("Core RPC Plugin: Registering event handler for dynamic updates.");
-                                // Logic from PipelineCompleteEventHandler is now directly in the closure.
-                                // No need for the PipelineCompleteEventHandler struct or Arc<dyn AsyncEventHandler>.
-                                let rpc_wrapper_for_closure = Arc::clone(&rpc_wrapper_handle); // Capture rpc_wrapper_handle from the outer async block
-
-                                let adapted_handler: Box<dyn for<'a> Fn(&'a dyn Event) -> BoxFuture<'a> + Send + Sync>
-                                    = Box::new(move |event_ref: &dyn Event| {
-                                        let rpc_wrapper_captured = Arc::clone(&rpc_wrapper_for_closure); // Clone for this specific invocation's future
-
-                                        Box::pin(async move {
-                                            if let Some(concrete_event) = event_ref.as_any().downcast_ref::<SystemEvent>() {
-                                                if let SystemEvent::PipelineComplete { pipeline_id, success } = concrete_event {
-                                                    let pipeline_id_owned = pipeline_id.clone();
-                                                    let success_owned = *success;
-
-                                                    info!("[CoreRpcPlugin/PipelineHandler] Received PipelineCompleteEvent: ID='{}', Success='{}'",
-                                                        pipeline_id_owned, success_owned);
-
-                                                    let details = Some("Idle".to_string());
-                                                    let state = Some(format!("Pipeline '{}' {}", pipeline_id_owned, if success_owned { "finished." } else { "failed." }));
-                                                    let timestamp = Some(Utc::now());
-
-                                                    debug!("[CoreRpcPlugin/PipelineHandler] Formatted presence: Details='{:?}', State='{:?}', Timestamp='{:?}'",
-                                                           details, state, timestamp);
+                            info!("Core RPC Plugin: Registering event handler for dynamic updates.");
+                            // Logic from PipelineCompleteEventHandler is now directly in the closure.
+                            // No need for the PipelineCompleteEventHandler struct or Arc<dyn AsyncEventHandler>.
+                            let rpc_wrapper_for_closure = Arc::clone(&rpc_wrapper_handle); // Capture rpc_wrapper_handle from the outer async block
+
+                            let adapted_handler: PluginHandlerFn
+                                = Box::new(move |event_ref: &dyn Event| {
+                                    let rpc_wrapper_captured = Arc::clone(&rpc_wrapper_for_closure); // Clone for this specific invocation's future
+
+                                    Box::pin(async move {
+                                        if let Some(concrete_event) = event_ref.as_any().downcast_ref::<SystemEvent>() {
+                                            if let SystemEvent::PipelineComplete { pipeline_id, success } = concrete_event {
+                                                let pipeline_id_owned = pipeline_id.clone();
+                                                let success_owned = *success;
+
+                                                info!("[CoreRpcPlugin/PipelineHandler] Received PipelineCompleteEvent: ID='{}', Success='{}'",
+                                                    pipeline_id_owned, success_owned);
+
+                                                let details = Some("Idle".to_string());
+                                                let state = Some(format!("Pipeline '{}' {}", pipeline_id_owned, if success_owned { "finished." } else { "failed." }));
+                                                let timestamp = Some(Utc::now());
+
+                                                debug!("[CoreRpcPlugin/PipelineHandler] Formatted presence: Details='{:?}', State='{:?}', Timestamp='{:?}'",
+                                                       details, state, timestamp);
+                                                
+                                                let rpc_wrapper_guard = rpc_wrapper_captured.lock().await;
+                                                if let Some(wrapper_instance) = rpc_wrapper_guard.as_ref() {
+                                                    let raw_client_state_arc = Arc::clone(&wrapper_instance.raw_client_state);
+                                                    let current_presence_data_arc = Arc::clone(&wrapper_instance.current_presence_data);
+                                                    let client_ready_signal = Arc::clone(&wrapper_instance.client_ready_signal);
                                                     
-                                                    let rpc_wrapper_guard = rpc_wrapper_captured.lock().await;
-                                                    if let Some(wrapper_instance) = rpc_wrapper_guard.as_ref() {
-                                                        let raw_client_state_arc = Arc::clone(&wrapper_instance.raw_client_state);
-                                                        let current_presence_data_arc = Arc::clone(&wrapper_instance.current_presence_data);
-                                                        let client_ready_signal = Arc::clone(&wrapper_instance.client_ready_signal);
-                                                        
-                                                        drop(rpc_wrapper_guard); // Release lock before await
-
-                                                        client_ready_signal.notified().await;
-
-                                                        if let Err(e) = DiscordRpcWrapper::perform_update_activity_static(
-                                                            raw_client_state_arc,
-                                                            current_presence_data_arc,
-                                                            details,
-                                                            state,
-                                                            timestamp,
-                                                            None, None, None, None, None, None, None, None,
-                                                        ).await {
-                                                            warn!("[CoreRpcPlugin/PipelineHandler] Failed to update Discord presence for pipeline event: {}", e);
-                                                        } else {
-                                                            info!("[CoreRpcPlugin/PipelineHandler] Discord presence updated for pipeline event: ID='{}'", pipeline_id_owned);
-                                                        }
+                                                    drop(rpc_wrapper_guard); // Release lock before await
+
+                                                    client_ready_signal.notified().await;
+
+                                                    if let Err(e) = DiscordRpcWrapper::perform_update_activity_static(
+                                                        raw_client_state_arc,
+                                                        current_presence_data_arc,
+                                                        details,
+                                                        state,
+                                                        timestamp,
+                                                        None, None, None, None, None, None, None, None,
+                                                    ).await {
+                                                        warn!("[CoreRpcPlugin/PipelineHandler] Failed to update Discord presence for pipeline event: {}", e);
                                                     } else {
-                                                        warn!("[CoreRpcPlugin/PipelineHandler] DiscordRpcWrapper not available, cannot update presence for pipeline event ID='{}'.", pipeline_id_owned);
+                                                        info!("[CoreRpcPlugin/PipelineHandler] Discord presence updated for pipeline event: ID='{}'", pipeline_id_owned);
                                                     }
-                                                    EventResult::Continue
                                                 } else {
-                                                    debug!("[CoreRpcPlugin/PipelineHandler] Received SystemEvent, but not PipelineComplete: {:?}", concrete_event.name());
-                                                    EventResult::Continue
+                                                    warn!("[CoreRpcPlugin/PipelineHandler] DiscordRpcWrapper not available, cannot update presence for pipeline event ID='{}'.", pipeline_id_owned);
                                                 }
+                                                EventResult::Continue
                                             } else {
-                                                 error!("[CoreRpcPlugin/PipelineHandler] Failed to downcast event to SystemEvent. Event name: '{}'", event_ref.name());
-                                                 EventResult::Continue
+                                                debug!("[CoreRpcPlugin/PipelineHandler] Received SystemEvent, but not PipelineComplete: {:?}", concrete_event.name());
+                                                EventResult::Continue
                                             }
-                                        })
-                                    });
-                               
-                                let event_name = SystemEvent::PipelineComplete { pipeline_id: String::new(), success: false }.name();
-                                
-                                // register_handler is async, so await it.
-                                let _event_id = event_manager.register_handler(event_name, adapted_handler).await;
-                                info!("Core RPC Plugin: Successfully registered handler for PipelineCompleteEvent ({}).", event_name);
-                                // Note: register_handler typically doesn't return Result for subscription errors in the same way
-                                // a hypothetical subscribe_async might. It returns an EventId. Errors might panic or be logged by dispatcher.
-                            } else {
-                                warn!("Core RPC Plugin: EventManager (obtained in async task) not available, cannot register event handler for dynamic updates.");
+                                        } else {
+                                             error!("[CoreRpcPlugin/PipelineHandler] Failed to downcast event to SystemEvent. Event name: '{}'", event_ref.name());
+                                             EventResult::Continue
+                                        }
+                                    })
+                                });
+                           
+                            let event_name = SystemEvent::PipelineComplete { pipeline_id: String::new(), success: false }.name();
+                            
+                            match plugin_access.register_event_handler(event_name, adapted_handler).await {
+                                Ok(_event_id) => info!("Core RPC Plugin: Successfully registered handler for PipelineCompleteEvent ({}).", event_name),
+                                Err(e) => warn!("Core RPC Plugin: Failed to register handler for PipelineCompleteEvent ({}): {}", event_name, e),
                             }
                         } else {
                             info!("Core RPC Plugin: Dynamic updates are disabled in settings. Skipping event handler registration.");
                         }
                         drop(rpc_wrapper_guard); // Release lock after event handler registration attempt
 
-                        // 4. Initial Presence Update
+                        // 3. Initial Presence Update
                         if final_settings.default_details.is_some() || final_settings.default_state.is_some() {
                             info!("Waiting for client ready signal before initial presence update...");
                             client_ready_signal_for_initial_update.notified().await;
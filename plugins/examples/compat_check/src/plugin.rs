@@ -111,6 +111,7 @@ impl Plugin for CompatCheckPlugin {
 use gini_core::plugin_system::traits::{
     FfiResult, FfiSlice, FfiVersionRange, FfiPluginDependency,
     FfiStageRequirement, PluginVTable, FfiPluginPriority,
+    PluginAbiDescriptor, PLUGIN_CAP_PREFLIGHT_CHECK, PLUGIN_CAP_REGISTER_STAGES, PLUGIN_CAP_SHUTDOWN,
 };
 use std::os::raw::{c_char, c_void};
 use std::ffi::CString;
@@ -129,6 +130,13 @@ extern "C" fn ffi_destroy(instance: *mut c_void) {
     }
 }
 
+// Frees the VTable boxed in `_plugin_init`; the host hands it back after `destroy`.
+extern "C" fn ffi_free_vtable(vtable: *mut PluginVTable) {
+    if !vtable.is_null() {
+        let _ = unsafe { Box::from_raw(vtable) };
+    }
+}
+
 // Allocate and return a C string pointer. Host must free it.
 extern "C" fn ffi_get_name(instance: *const c_void) -> *const c_char {
     let plugin = unsafe { &*(instance as *const CompatCheckPlugin) };
@@ -212,10 +220,10 @@ extern "C" fn ffi_get_compatible_api_versions(instance: *const c_void) -> FfiSli
         }
     }
 
-    // Convert Vec to FfiSlice, transferring ownership of the Vec's buffer
-    let ptr = ffi_ranges.as_mut_ptr();
-    let len = ffi_ranges.len();
-    std::mem::forget(ffi_ranges); // Prevent Vec from dropping the buffer
+    // Convert to a boxed slice (capacity == len) and transfer ownership of the buffer
+    let boxed_ranges = ffi_ranges.into_boxed_slice();
+    let len = boxed_ranges.len();
+    let ptr = Box::into_raw(boxed_ranges) as *const FfiVersionRange;
 
     FfiSlice { ptr, len }
 }
@@ -223,8 +231,8 @@ extern "C" fn ffi_get_compatible_api_versions(instance: *const c_void) -> FfiSli
 extern "C" fn ffi_free_compatible_api_versions(slice: FfiSlice<FfiVersionRange>) {
     if !slice.ptr.is_null() {
         unsafe {
-            // Reconstruct the Vec to manage memory, casting ptr to mutable
-            let ffi_ranges = Vec::from_raw_parts(slice.ptr as *mut FfiVersionRange, slice.len, slice.len);
+            // Reconstruct the boxed slice to manage memory, casting ptr to mutable
+            let ffi_ranges = Box::from_raw(ptr::slice_from_raw_parts_mut(slice.ptr as *mut FfiVersionRange, slice.len));
             // Free the CString for each constraint
             for ffi_range in ffi_ranges {
                 if !ffi_range.constraint.is_null() {
@@ -232,7 +240,7 @@ extern "C" fn ffi_free_compatible_api_versions(slice: FfiSlice<FfiVersionRange>)
                     let _ = CString::from_raw(ffi_range.constraint as *mut c_char);
                 }
             }
-            // The Vec itself will be dropped here, freeing the main buffer
+            // The boxed slice itself will be dropped here, freeing the main buffer
         }
     }
 }
@@ -298,6 +306,13 @@ extern "C" fn ffi_shutdown(instance: *mut c_void) -> FfiResult {
 }
 
 
+// ABI descriptor read by the host before `_plugin_init` is called.
+// Declares the optional VTable entries this plugin provides.
+#[no_mangle]
+pub static GINI_PLUGIN_ABI: PluginAbiDescriptor = PluginAbiDescriptor::current(
+    PLUGIN_CAP_PREFLIGHT_CHECK | PLUGIN_CAP_REGISTER_STAGES | PLUGIN_CAP_SHUTDOWN,
+);
+
 /// The entry point function for the plugin loader.
 #[no_mangle]
 pub extern "C" fn _plugin_init() -> *mut PluginVTable {
@@ -310,6 +325,7 @@ pub extern "C" fn _plugin_init() -> *mut PluginVTable {
         let vtable = PluginVTable {
             instance: Box::into_raw(plugin_instance) as *mut c_void,
             destroy: ffi_destroy,
+            free_vtable: ffi_free_vtable,
             name: ffi_get_name,
             free_name: ffi_free_name, // Add free function
             version: ffi_get_version,
@@ -329,9 +345,9 @@ pub extern "C" fn _plugin_init() -> *mut PluginVTable {
             free_incompatible_with: ffi_free_empty_incompatible_with,
             // New lifecycle functions
             init: ffi_init,
-            preflight_check: ffi_preflight_check,
-            register_stages: ffi_register_stages,
-            shutdown: ffi_shutdown,
+            preflight_check: Some(ffi_preflight_check),
+            register_stages: Some(ffi_register_stages),
+            shutdown: Some(ffi_shutdown),
         };
 
         // Box the VTable and return the raw pointer
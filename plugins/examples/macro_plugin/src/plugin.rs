@@ -0,0 +1,86 @@
+use gini_core::plugin_system::{
+    Plugin, PluginDependency, PluginPriority, version::VersionRange,
+};
+use gini_core::plugin_system::error::PluginSystemError;
+use gini_core::stage_manager::{StageContext, requirement::StageRequirement};
+use gini_core::stage_manager::registry::StageRegistry;
+use gini_core::kernel::bootstrap::Application;
+use gini_plugin_macros::gini_plugin;
+use async_trait::async_trait;
+
+/// Example dynamic plugin exported with `#[gini_plugin]`.
+/// Compare with `compat_check`, which writes every FFI function by hand.
+#[gini_plugin]
+#[derive(Default)]
+pub struct MacroPlugin {
+    init_count: std::sync::atomic::AtomicUsize, // Number of times `init` ran
+}
+
+#[async_trait]
+impl Plugin for MacroPlugin {
+    fn name(&self) -> &'static str {
+        "MacroExample"
+    }
+
+    fn version(&self) -> &str {
+        "0.1.0"
+    }
+
+    fn is_core(&self) -> bool {
+        false
+    }
+
+    fn priority(&self) -> PluginPriority {
+        PluginPriority::ThirdParty(160)
+    }
+
+    fn compatible_api_versions(&self) -> Vec<VersionRange> {
+        vec![VersionRange::from_constraint("~0.1.0").expect("Invalid version range constraint")]
+    }
+
+    fn dependencies(&self) -> Vec<PluginDependency> {
+        // Optional, so the example loads without it
+        vec![PluginDependency::optional(
+            "core-logging",
+            VersionRange::from_constraint(">=0.1.0").expect("Invalid version range constraint"),
+        )]
+    }
+
+    fn required_stages(&self) -> Vec<StageRequirement> {
+        vec![StageRequirement::optional("core::plugin_preflight_check")]
+    }
+
+    fn conflicts_with(&self) -> Vec<String> {
+        vec!["legacy-macro-example".to_string()]
+    }
+
+    fn incompatible_with(&self) -> Vec<PluginDependency> {
+        vec![PluginDependency::required(
+            "old-macro-example",
+            VersionRange::from_constraint("<0.1.0").expect("Invalid version range constraint"),
+        )]
+    }
+
+    fn init(&self, _app: &mut Application) -> Result<(), PluginSystemError> {
+        let count = self.init_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+        println!("MacroPlugin initialized (init #{}).", count);
+        Ok(())
+    }
+
+    async fn preflight_check(&self, context: &StageContext) -> Result<(), PluginSystemError> {
+        // Runs on the plugin's own runtime; awaiting works as usual
+        tokio::task::yield_now().await;
+        println!("MacroPlugin preflight check passed (dry run: {}).", context.is_dry_run());
+        Ok(())
+    }
+
+    fn register_stages(&self, _registry: &mut StageRegistry) -> Result<(), PluginSystemError> {
+        println!("MacroPlugin provides no stages to register.");
+        Ok(())
+    }
+
+    fn shutdown(&self) -> Result<(), PluginSystemError> {
+        println!("MacroPlugin shut down.");
+        Ok(())
+    }
+}